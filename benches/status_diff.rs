@@ -0,0 +1,231 @@
+//! Benchmarks for the status and tree diff computations.
+//! Builds a fixture repository with many files spread across nested directories,
+//! commits it twice and then measures `StatusCommand::compute` and `Database::tree_diff`.
+//! A second fixture with a deep untracked tree compares status with and without the
+//! untracked cache.
+//! Storing many blobs one by one is compared with storing them as one batch.
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use tempfile::TempDir;
+
+use asheraflow::commands::add::AddCommand;
+use asheraflow::commands::commit::CommitCommand;
+use asheraflow::commands::init::InitCommand;
+use asheraflow::commands::status::StatusCommand;
+use asheraflow::core::database::blob::Blob;
+use asheraflow::core::database::database::Database;
+use asheraflow::core::index::index::Index;
+use asheraflow::core::path_filter::PathFilter;
+use asheraflow::core::refs::Refs;
+use asheraflow::core::workspace::Workspace;
+
+/// Number of top-level directories in the fixture
+const DIRS: usize = 20;
+/// Number of subdirectories per top-level directory
+const SUBDIRS: usize = 10;
+/// Number of files per subdirectory
+const FILES: usize = 10;
+/// Depth of each untracked directory chain
+const UNTRACKED_DEPTH: usize = 30;
+/// Number of untracked directory chains
+const UNTRACKED_CHAINS: usize = 20;
+/// Blobs written per run of the object store benchmarks
+const STORE_BLOBS: usize = 2000;
+
+struct Fixture {
+    _dir: TempDir,
+    root: PathBuf,
+    first_commit: String,
+    second_commit: String,
+}
+
+fn write_files(root: &Path, generation: usize) {
+    for d in 0..DIRS {
+        for s in 0..SUBDIRS {
+            let dir = root.join(format!("dir{}", d)).join(format!("sub{}", s));
+            fs::create_dir_all(&dir).unwrap();
+            for f in 0..FILES {
+                // Only touch a tenth of the files after the first generation
+                if generation > 0 && f % 10 != 0 {
+                    continue;
+                }
+                let content = format!("file {} in {}/{} generation {}\n", f, d, s, generation).repeat(8);
+                fs::write(dir.join(format!("file{}.txt", f)), content).unwrap();
+            }
+        }
+    }
+}
+
+fn commit_all(message: &str) -> String {
+    AddCommand::execute(&[".".to_string()]).unwrap();
+    CommitCommand::execute(message, false, None, false, None, None, &[]).unwrap();
+    Refs::new(Path::new(".ash")).read_head().unwrap().unwrap()
+}
+
+fn setup() -> Fixture {
+    let dir = TempDir::new().unwrap();
+    let root = dir.path().to_path_buf();
+    InitCommand::execute(root.to_str().unwrap()).unwrap();
+    env::set_current_dir(&root).unwrap();
+
+    write_files(&root, 0);
+    let first_commit = commit_all("Initial fixture");
+
+    write_files(&root, 1);
+    let second_commit = commit_all("Modify fixture");
+
+    // Leave a few unstaged modifications so status has something to report
+    write_files(&root, 2);
+
+    Fixture { _dir: dir, root, first_commit, second_commit }
+}
+
+fn bench_status(c: &mut Criterion, fixture: &Fixture) {
+    let git_path = fixture.root.join(".ash");
+
+    c.bench_function("status_compute", |b| {
+        b.iter(|| {
+            let workspace = Workspace::new(&fixture.root);
+            let mut database = Database::new(git_path.join("objects"));
+            let mut index = Index::new(git_path.join("index"));
+            let refs = Refs::new(&git_path);
+            index.load().unwrap();
+
+            StatusCommand::compute(&workspace, &mut database, &mut index, &refs, false).unwrap()
+        })
+    });
+}
+
+/// Builds a repository with one tracked file and untracked directory chains whose only
+/// file sits at the bottom, so finding out they are trackable means walking each chain
+fn setup_untracked() -> (TempDir, PathBuf) {
+    let dir = TempDir::new().unwrap();
+    let root = dir.path().to_path_buf();
+    InitCommand::execute(root.to_str().unwrap()).unwrap();
+    env::set_current_dir(&root).unwrap();
+
+    fs::write(root.join("tracked.txt"), "tracked\n").unwrap();
+    commit_all("Tracked file");
+
+    for c in 0..UNTRACKED_CHAINS {
+        let mut chain = root.join(format!("untracked{}", c));
+        for d in 0..UNTRACKED_DEPTH {
+            // Empty side directories make every level of the chain worth visiting
+            fs::create_dir_all(chain.join(format!("empty{}", d))).unwrap();
+            chain = chain.join(format!("level{}", d));
+        }
+        fs::create_dir_all(&chain).unwrap();
+        fs::write(chain.join("leaf.txt"), "leaf\n").unwrap();
+    }
+
+    (dir, root)
+}
+
+fn bench_untracked_cache(c: &mut Criterion) {
+    let (_dir, root) = setup_untracked();
+    let git_path = root.join(".ash");
+
+    let compute = |untracked_cache: bool| {
+        let workspace = Workspace::new(&root);
+        let mut database = Database::new(git_path.join("objects"));
+        let mut index = Index::new(git_path.join("index"));
+        let refs = Refs::new(&git_path);
+        index.load().unwrap();
+
+        StatusCommand::compute(&workspace, &mut database, &mut index, &refs, untracked_cache).unwrap()
+    };
+
+    // Both runs must agree, or the cache is answering wrongly
+    let uncached = compute(false);
+    assert_eq!(uncached.untracked.len(), UNTRACKED_CHAINS);
+
+    // Directories modified within the racy window aren't cached, so wait before warming it
+    std::thread::sleep(std::time::Duration::from_secs(3));
+    compute(true);
+    assert_eq!(compute(true).untracked, uncached.untracked);
+
+    c.bench_function("status_untracked_no_cache", |b| b.iter(|| compute(false)));
+    c.bench_function("status_untracked_cache", |b| b.iter(|| compute(true)));
+
+    // Removing the only file of a chain must invalidate its cached answer
+    let leaf = (0..UNTRACKED_DEPTH)
+        .fold(root.join("untracked0"), |dir, d| dir.join(format!("level{}", d)))
+        .join("leaf.txt");
+    fs::remove_file(leaf).unwrap();
+    assert_eq!(compute(true).untracked.len(), UNTRACKED_CHAINS - 1);
+}
+
+fn bench_tree_diff(c: &mut Criterion, fixture: &Fixture) {
+    let git_path = fixture.root.join(".ash");
+    let filter = PathFilter::new();
+
+    c.bench_function("tree_diff", |b| {
+        b.iter(|| {
+            let mut database = Database::new(git_path.join("objects"));
+            database
+                .tree_diff(Some(&fixture.first_commit), Some(&fixture.second_commit), &filter)
+                .unwrap()
+        })
+    });
+}
+
+/// Stores `STORE_BLOBS` distinct blobs in `objects`, returning their IDs in order
+fn store_blobs(objects: &Path, batched: bool) -> Vec<String> {
+    let mut database = Database::new(objects.to_path_buf());
+    if batched {
+        database.begin_batch();
+    }
+
+    let oids = (0..STORE_BLOBS)
+        .map(|i| {
+            let mut blob = Blob::new(format!("blob {}\n", i).repeat(8).into_bytes());
+            database.store(&mut blob).unwrap()
+        })
+        .collect();
+
+    if batched {
+        database.flush_batch().unwrap();
+    }
+    oids
+}
+
+fn bench_object_store(c: &mut Criterion) {
+    // Both paths must write the same objects, and the batched ones must be readable
+    let unbatched = TempDir::new().unwrap();
+    let batched = TempDir::new().unwrap();
+    let oids = store_blobs(batched.path(), true);
+    assert_eq!(store_blobs(unbatched.path(), false), oids);
+    let mut database = Database::new(batched.path().to_path_buf());
+    assert!(oids.iter().all(|oid| database.load(oid).is_ok()));
+
+    // Each run writes into a fresh store, dropped outside the measurement
+    let mut group = c.benchmark_group("object_store");
+    group.sample_size(20);
+    for (name, batched) in [("unbatched", false), ("batched", true)] {
+        group.bench_function(name, |b| {
+            b.iter_batched(
+                || TempDir::new().unwrap(),
+                |dir| {
+                    store_blobs(dir.path(), batched);
+                    dir
+                },
+                BatchSize::PerIteration,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn benches(c: &mut Criterion) {
+    let fixture = setup();
+    bench_status(c, &fixture);
+    bench_tree_diff(c, &fixture);
+    bench_untracked_cache(c);
+    bench_object_store(c);
+}
+
+criterion_group!(status_diff, benches);
+criterion_main!(status_diff);
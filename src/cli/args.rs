@@ -1,3 +1,7 @@
+use crate::core::commit_metadata::Priority;
+use crate::core::merge::diff3::ConflictStyle;
+use crate::core::merge::resolve::MergeStrategy;
+
 /// Defines all supported commands for the AsheraFlow CLI.
 #[derive(Debug)]
 pub enum Command {
@@ -9,23 +13,61 @@ pub enum Command {
         amend: bool,
         reuse_message: Option<String>,
         edit: bool,
+        /// Author identity as `Name <email>`
+        author: Option<String>,
+        /// Author date, overriding the current time
+        date: Option<String>,
+        /// Commit only the staged changes under these paths
+        paths: Vec<String>,
     },
     /// Stages files for commit.
-    Add { paths: Vec<String> },
+    Add {
+        paths: Vec<String>,
+        /// Interactively choose hunks to stage instead of adding whole files
+        patch: bool,
+    },
     /// Displays the current state of the working directory and index.
-    Status { porcelain: bool, color: String }, 
+    Status { porcelain: bool, porcelain_v2: bool, short: bool, branch: bool, color: String, no_untracked_cache: bool }, 
     /// Shows changes between commits, commit and working tree, etc.
-    Diff { paths: Vec<String>, cached: bool },
+    Diff { paths: Vec<String>, cached: bool, stat: bool, word_diff: bool, context: usize, git: bool, find_renames: bool, color: String },
+    /// Applies a patch to the working tree and/or the index.
+    Apply {
+        /// Patch file; standard input when missing or `-`
+        patch: Option<String>,
+        /// Apply to the index only
+        cached: bool,
+        /// Apply to both the working tree and the index
+        index: bool,
+        /// Only check that the patch applies
+        check: bool,
+    },
     /// Manages branches (create, delete, list, etc.).
     Branch { 
         name: String, 
         start_point: Option<String>,
         verbose: bool,
         delete: bool,
-        force: bool
+        force: bool,
+        /// `-m`/`-M`: rename `name` to `start_point`, or the current branch to `name`
+        rename: bool,
+        /// `-u`/`--set-upstream-to`: upstream for `name`, or the current branch
+        set_upstream: Option<String>
     },
     /// Switches branches or restores working tree files.
-    Checkout { target: String },
+    Checkout {
+        target: String,
+        orphan: bool,
+        /// Interactively discard working-tree hunks; `target` is then an optional revision
+        patch: bool,
+        /// Paths for `--patch`, or the files to restore with `checkout [<rev>] -- <paths>`
+        paths: Vec<String>,
+        /// Branch to create (`-b`) or reset (`-B`) at `target` before switching to it
+        new_branch: Option<String>,
+        /// `-B`: reset the new branch if it already exists
+        force: bool,
+        /// `--progress`/`--no-progress`; None shows progress only on a terminal
+        progress: Option<bool>,
+    },
     /// Displays commit logs with various formatting options.
     Log {
         revisions: Vec<String>,
@@ -33,14 +75,85 @@ pub enum Command {
         format: String,
         patch: bool,
         decorate: String,
+        /// Draw the commit graph to the left of each commit
+        graph: bool,
+        /// Never show a commit before its descendants, regardless of timestamps
+        topo_order: bool,
+        /// Only show commits whose author line matches this regex (or substring)
+        author: Option<String>,
+        /// Only show commits authored at or after this date
+        since: Option<String>,
+        /// Only show commits authored at or before this date
+        until: Option<String>,
+        /// Stop after this many commits
+        max_count: Option<usize>,
+    },
+    /// Shows a commit with its patch, a tree's entries, a blob's content or a tag.
+    Show { revision: Option<String> },
+    /// Checks that every reachable object is present and intact.
+    Fsck {
+        verbose: bool,
+        /// Only check that objects exist, without re-hashing them or the working tree
+        connectivity_only: bool,
+    },
+    /// Recomputes the ID of every loose and packed object to find corrupt ones.
+    VerifyObjects,
+    /// Prints the type, size or content of a single object.
+    CatFile {
+        object: String,
+        /// `-t`: print the object's type
+        show_type: bool,
+        /// `-s`: print the object's size in bytes
+        show_size: bool,
+        /// `-p`: pretty-print the object's content
+        pretty: bool,
     },
     /// Merges changes from another branch into the current branch.
     Merge {
-        branch: String,
+        branches: Vec<String>,
         message: Option<String>,
         abort: bool,
         continue_merge: bool,
         tool: Option<String>, 
+        strategy: MergeStrategy,
+        conflict_style: ConflictStyle,
+        no_commit: bool,
+        no_ff: bool,
+        /// `--progress`/`--no-progress`; None shows progress only on a terminal
+        progress: Option<bool>,
+    },
+    /// Finds common ancestors of two commits or answers ancestry questions between them.
+    MergeBase {
+        commits: Vec<String>,
+        is_ancestor: bool,
+        all: bool,
+    },
+    /// Prints the object IDs revisions resolve to.
+    RevParse { revisions: Vec<String> },
+    /// Saves, restores or lists stashed local changes.
+    Stash {
+        action: String,
+        message: Option<String>,
+        /// Also stash untracked files
+        include_untracked: bool,
+        /// Leave staged changes in the index and working tree
+        keep_index: bool,
+    },
+    /// Lists the paths recorded in the index.
+    LsFiles { stage: bool, unmerged: bool },
+    /// Packs loose objects.
+    Gc,
+    /// Deletes unreachable loose objects.
+    Prune { dry_run: bool, expire: Option<String> },
+    /// Shows the history of a ref.
+    Reflog { ref_name: Option<String> },
+    /// Creates, deletes or lists tags.
+    Tag {
+        name: Option<String>,
+        target: Option<String>,
+        message: Option<String>,
+        delete: bool,
+        list: bool,
     },
     /// Removes files from the working tree and/or index.
     Rm {
@@ -48,15 +161,38 @@ pub enum Command {
         cached: bool,
         force: bool,
         recursive: bool,
+        /// List what would be removed without removing anything
+        dry_run: bool,
+    },
+    /// Restores index entries and/or working-tree files from HEAD, the index or a commit.
+    Restore {
+        paths: Vec<String>,
+        /// Restore the index (unstage)
+        staged: bool,
+        /// Restore the working tree; the default when `staged` is not given
+        worktree: bool,
+        source: Option<String>,
+    },
+    /// Reads or writes a `section.key` setting in `.ash/config`.
+    Config {
+        name: String,
+        /// New value; without it the current value is printed
+        value: Option<String>,
     },
     /// Resets current HEAD to the specified state.
     Reset {
+        /// Target given before `--`; without it the first of `files` may be the target
+        revision: Option<String>,
         files: Vec<String>,
         soft: bool,
         mixed: bool,
         hard: bool,
         force: bool,
         reuse_message: Option<String>,
+        /// Interactively choose hunks to unstage instead of resetting whole paths
+        patch: bool,
+        /// `--progress`/`--no-progress`; None shows progress only on a terminal
+        progress: Option<bool>,
     },
     /// Applies changes from specific commits.
     CherryPick {
@@ -88,6 +224,7 @@ pub enum Command {
     /// Displays the burndown chart for a sprint.
     SprintBurndown {
         sprint_name: Option<String>,
+        format: Option<String>,
     },
     /// Shows sprint velocity statistics.
     SprintVelocity {},
@@ -99,11 +236,24 @@ pub enum Command {
     },
     /// Displays a summary of the current sprint.
     SprintView {},
+    /// Closes the active sprint, optionally tagging HEAD as its end.
+    SprintClose {
+        sprint_name: Option<String>,
+        tag: bool,
+        carry_over_to: Option<String>,
+    },
+    /// Summarizes a sprint's points, tasks, commits and contributors.
+    SprintReport {
+        sprint_name: Option<String>,
+        json: bool,
+    },
     /// Task management commands
     TaskCreate {
         id: String,
         description: String,
         story_points: Option<u32>,
+        depends_on: Vec<String>,
+        priority: Priority,
     },
     /// Marks a task as completed and optionally merges changes.
     TaskComplete {
@@ -1,4 +1,7 @@
 use crate::cli::args::{CliArgs, Command};
+use crate::core::commit_metadata::Priority;
+use crate::core::merge::diff3::ConflictStyle;
+use crate::core::merge::resolve::MergeStrategy;
 use crate::errors::error::Error;
 
 /// Provides parsing logic for command-line arguments and maps them to CLI commands.
@@ -12,7 +15,7 @@ impl CliParser {
     pub fn parse(args: Vec<String>) -> Result<CliArgs, Error> {
         if args.len() < 2 {
             return Err(Error::Generic(format!("{}\n\n{}",
-                 "Usage: ash <command> [options]",
+                 "Usage: ash [--no-pager] [--color[=<when>]] <command> [options]",
                  Self::format_help()
              )));
         }
@@ -30,6 +33,9 @@ impl CliParser {
                 let mut amend = false;
                 let mut reuse_message = None;
                 let mut edit = false;
+                let mut author = None;
+                let mut date = None;
+                let mut paths = Vec::new();
                 let mut i = 2;
                 while i < args.len() {
                     match args[i].as_str() {
@@ -66,6 +72,26 @@ impl CliParser {
                                 return Err(Error::Generic("--reedit-message requires a value".to_string()));
                             }
                         },
+                        "--author" | "--date" => {
+                            if i + 1 < args.len() {
+                                if args[i] == "--author" {
+                                    author = Some(args[i + 1].to_owned());
+                                } else {
+                                    date = Some(args[i + 1].to_owned());
+                                }
+                                i += 2;
+                            } else {
+                                return Err(Error::Generic(format!("{} requires a value", args[i])));
+                            }
+                        },
+                        arg if arg.starts_with("--author=") => {
+                            author = Some(arg["--author=".len()..].to_string());
+                            i += 1;
+                        },
+                        arg if arg.starts_with("--date=") => {
+                            date = Some(arg["--date=".len()..].to_string());
+                            i += 1;
+                        },
                         "--file" | "-F" => {
                             if i + 1 < args.len() {
                                 let file_path = &args[i + 1];
@@ -82,8 +108,16 @@ impl CliParser {
                                 return Err(Error::Generic("--file requires a value".to_string()));
                             }
                         },
+                        "--" => {
+                            paths.extend(args[i + 1..].iter().cloned());
+                            i = args.len();
+                        },
+                        arg if arg.starts_with('-') => {
+                            return Err(Error::Generic(format!("Unknown option for commit: {}", arg)));
+                        },
                         _ => {
-                            return Err(Error::Generic(format!("Unknown option for commit: {}", args[i])));
+                            paths.push(args[i].clone());
+                            i += 1;
                         }
                     }
                 }
@@ -96,6 +130,9 @@ impl CliParser {
                         amend,
                         reuse_message,
                         edit,
+                        author,
+                        date,
+                        paths,
                     },
                 }
             },
@@ -103,32 +140,52 @@ impl CliParser {
                 if args.len() < 3 {
                     return Err(Error::Generic("File path(s) are required for add command".to_string()));
                 }
+                // add -p [<paths>...]; without paths every tracked file is offered
+                let patch = args[2] == "-p" || args[2] == "--patch";
                 CliArgs {
                     command: Command::Add {
-                        paths: args[2..].to_vec(),
+                        paths: args[if patch { 3 } else { 2 }..].to_vec(),
+                        patch,
                     },
                 }
             },
             "status" => {
-                // Check for --porcelain flag
-                let porcelain = args.iter().skip(2).any(|arg| arg == "--porcelain");
-
-                // Check for --color option
-                let color = args.iter().skip(2).enumerate().find_map(|(i, arg)| {
-                    // Correct index check for color value
-                    if arg == "--color" && i + 2 < args.len() { // Look at index i+2 relative to args start
-                        Some(args[i + 2 + 1].clone()) // Argument is at i+2, value at i+3 (relative to args start)
-                    } else if arg.starts_with("--color=") {
-                        Some(arg.splitn(2, '=').nth(1).unwrap_or("auto").to_string())
-                    } else {
-                        None
+                // Check for --porcelain[=v1|v2] flag
+                let porcelain = args.iter().skip(2).any(|arg| arg == "--porcelain" || arg == "--porcelain=v1");
+                let porcelain_v2 = args.iter().skip(2).any(|arg| arg == "--porcelain=v2");
+                let short = args.iter().skip(2).any(|arg| arg == "-s" || arg == "--short");
+                let branch = args.iter().skip(2).any(|arg| arg == "-b" || arg == "--branch");
+                let no_untracked_cache = args.iter().skip(2).any(|arg| arg == "--no-untracked-cache");
+
+                // Check for --color[=<when>] or --color <when>; a bare --color means always
+                let mut color = None;
+                let mut i = 2;
+                while i < args.len() {
+                    if let Some(when) = args[i].strip_prefix("--color=") {
+                        color = Some(when.to_string());
+                    } else if args[i] == "--color" {
+                        match args.get(i + 1) {
+                            Some(when) if !when.starts_with('-') => {
+                                color = Some(when.clone());
+                                i += 1;
+                            },
+                            _ => color = Some("always".to_string()),
+                        }
+                    } else if args[i] == "--no-color" {
+                        color = Some("never".to_string());
                     }
-                }).unwrap_or_else(|| "auto".to_string()); // Default to auto
+                    i += 1;
+                }
+                let color = color.unwrap_or_else(|| "auto".to_string());
 
                 CliArgs {
                     command: Command::Status {
                         porcelain,
+                        porcelain_v2,
+                        short,
+                        branch,
                         color,
+                        no_untracked_cache,
                     },
                 }
             },
@@ -136,11 +193,34 @@ impl CliParser {
                 // Parse diff command arguments
                 let mut paths = Vec::new();
                 let mut cached = false;
+                let mut stat = false;
+                let mut word_diff = false;
+                let mut context = 3;
+                let mut git = false;
+                let mut find_renames = false;
+                let mut color = "auto".to_string();
 
                 // Check for --cached or --staged flag
                 for arg in args.iter().skip(2) {
                     if arg == "--cached" || arg == "--staged" {
                         cached = true;
+                    } else if arg == "--stat" {
+                        stat = true;
+                    } else if arg == "--word-diff" {
+                        word_diff = true;
+                    } else if arg == "--git" {
+                        git = true;
+                    } else if arg == "-M" || arg == "--find-renames" {
+                        find_renames = true;
+                    } else if arg == "--color" {
+                        color = "always".to_string();
+                    } else if arg == "--no-color" {
+                        color = "never".to_string();
+                    } else if let Some(when) = arg.strip_prefix("--color=") {
+                        color = when.to_string();
+                    } else if let Some(lines) = arg.strip_prefix("--unified=").or_else(|| arg.strip_prefix("-U")) {
+                        context = lines.parse::<usize>()
+                            .map_err(|_| Error::Generic(format!("Invalid number of context lines: {}", lines)))?;
                     } else if !arg.starts_with('-') { // Assume non-flag arguments are paths
                         paths.push(arg.clone());
                     } else {
@@ -153,9 +233,41 @@ impl CliParser {
                     command: Command::Diff {
                         paths,
                         cached,
+                        stat,
+                        word_diff,
+                        context,
+                        git,
+                        find_renames,
+                        color,
                     },
                 }
             },
+            "apply" => {
+                let mut patch = None;
+                let mut cached = false;
+                let mut index = false;
+                let mut check = false;
+                
+                for arg in args.iter().skip(2) {
+                    match arg.as_str() {
+                        "--cached" => cached = true,
+                        "--index" => index = true,
+                        "--check" => check = true,
+                        "-" => patch = Some(arg.clone()),
+                        a if a.starts_with('-') => {
+                            return Err(Error::Generic(format!("Unknown option for apply: {}", a)));
+                        },
+                        _ if patch.is_some() => {
+                            return Err(Error::Generic("apply takes a single patch file".to_string()));
+                        },
+                        _ => patch = Some(arg.clone()),
+                    }
+                }
+                
+                CliArgs {
+                    command: Command::Apply { patch, cached, index, check },
+                }
+            },
             "branch" => {
                 // Parse branch options
                 let mut name = String::new();
@@ -163,6 +275,8 @@ impl CliParser {
                 let mut verbose = false;
                 let mut delete = false;
                 let mut force = false;
+                let mut rename = false;
+                let mut set_upstream = None;
 
                 // Process all arguments for options
                 let mut i = 2;
@@ -182,6 +296,23 @@ impl CliParser {
                             delete = true;
                             force = true;
                         },
+                        "-m" | "--move" => {
+                            rename = true;
+                        },
+                        "-M" => {
+                            rename = true;
+                            force = true;
+                        },
+                        "-u" | "--set-upstream-to" => {
+                            if i + 1 >= args.len() {
+                                return Err(Error::Generic(format!("Option '{}' requires a value", arg)));
+                            }
+                            set_upstream = Some(args[i + 1].clone());
+                            i += 1;
+                        },
+                        a if a.starts_with("--set-upstream-to=") => {
+                            set_upstream = Some(a["--set-upstream-to=".len()..].to_string());
+                        },
                         // Check for other potential flags if needed
                         a if a.starts_with('-') => {
                             // Allow flags to appear anywhere relative to positional args
@@ -207,6 +338,9 @@ impl CliParser {
                 if delete && name.is_empty() {
                      return Err(Error::Generic("Branch name required for delete operation".to_string()));
                 }
+                if rename && name.is_empty() {
+                     return Err(Error::Generic("Branch name required for rename operation".to_string()));
+                }
 
 
                 CliArgs {
@@ -215,22 +349,164 @@ impl CliParser {
                         start_point,
                         verbose,
                         delete,
-                        force
+                        force,
+                        rename,
+                        set_upstream
                     },
                 }
             },
-            "checkout" => {
+            "checkout" | "switch" => {
+                let (args, progress) = Self::take_progress_flag(&args);
                 if args.len() < 3 {
                     return Err(Error::Generic("No checkout target specified (branch, commit, or path)".to_string()));
                 }
-                 // Allow multiple targets for file checkout? Git's behavior is complex here.
-                 // For now, assume one target (branch or commit).
-                 // Handle `checkout -- <paths...>` separately if needed.
-                let target = args[2].clone();
+                if args[2] == "-p" || args[2] == "--patch" {
+                    // checkout -p [<rev>] [--] [<paths>...]; without `--` the command decides
+                    // whether the first argument is a revision
+                    let rest = &args[3..];
+                    let (target, paths) = match rest.iter().position(|arg| arg == "--") {
+                        Some(0) => (String::new(), rest[1..].to_vec()),
+                        Some(1) => (rest[0].clone(), rest[2..].to_vec()),
+                        Some(_) => return Err(Error::Generic("checkout -p takes at most one revision before '--'".to_string())),
+                        None => (String::new(), rest.to_vec()),
+                    };
+
+                    return Ok(CliArgs {
+                        command: Command::Checkout {
+                            target,
+                            orphan: false,
+                            patch: true,
+                            paths,
+                            new_branch: None,
+                            force: false,
+                            progress,
+                        },
+                    });
+                }
+
+                if let Some(pos) = args[2..].iter().position(|arg| arg == "--") {
+                    // checkout [<rev>] -- <paths>...
+                    let (target, paths) = match pos {
+                        0 => (String::new(), args[3..].to_vec()),
+                        1 => (args[2].clone(), args[4..].to_vec()),
+                        _ => return Err(Error::Generic("checkout takes at most one revision before '--'".to_string())),
+                    };
+                    if paths.is_empty() {
+                        return Err(Error::Generic("checkout -- requires at least one path".to_string()));
+                    }
+
+                    return Ok(CliArgs {
+                        command: Command::Checkout {
+                            target,
+                            orphan: false,
+                            patch: false,
+                            paths,
+                            new_branch: None,
+                            force: false,
+                            progress,
+                        },
+                    });
+                }
+
+                if args[2] == "-b" || args[2] == "-B" {
+                    // checkout -b|-B <branch> [<start-point>]
+                    let new_branch = match args.get(3) {
+                        Some(name) => name.clone(),
+                        None => return Err(Error::Generic(format!("{} requires a branch name", args[2]))),
+                    };
+                    if args.len() > 5 {
+                        return Err(Error::Generic(format!("{} takes a branch name and at most one start point", args[2])));
+                    }
+
+                    return Ok(CliArgs {
+                        command: Command::Checkout {
+                            target: args.get(4).cloned().unwrap_or_default(),
+                            orphan: false,
+                            patch: false,
+                            paths: Vec::new(),
+                            new_branch: Some(new_branch),
+                            force: args[2] == "-B",
+                            progress,
+                        },
+                    });
+                }
+
+                let (target, orphan) = if args[2] == "--orphan" {
+                    if args.len() < 4 {
+                        return Err(Error::Generic("--orphan requires a branch name".to_string()));
+                    }
+                    (args[3].clone(), true)
+                } else {
+                    (args[2].clone(), false)
+                };
 
                 CliArgs {
                     command: Command::Checkout {
                         target,
+                        orphan,
+                        patch: false,
+                        paths: Vec::new(),
+                        new_branch: None,
+                        force: false,
+                        progress,
+                    },
+                }
+            },
+            "show" => {
+                if args.len() > 3 {
+                    return Err(Error::Generic("Usage: ash show [<object>]".to_string()));
+                }
+                
+                CliArgs {
+                    command: Command::Show {
+                        revision: args.get(2).cloned(),
+                    },
+                }
+            },
+            "fsck" => {
+                let mut verbose = false;
+                let mut connectivity_only = false;
+                
+                for arg in &args[2..] {
+                    match arg.as_str() {
+                        "-v" | "--verbose" => verbose = true,
+                        "--connectivity-only" => connectivity_only = true,
+                        a => return Err(Error::Generic(format!("Unknown option for fsck: {}", a))),
+                    }
+                }
+                
+                CliArgs {
+                    command: Command::Fsck { verbose, connectivity_only },
+                }
+            },
+            "verify-objects" => {
+                if args.len() > 2 {
+                    return Err(Error::Generic(format!("Unknown option for verify-objects: {}", args[2])));
+                }
+                
+                CliArgs {
+                    command: Command::VerifyObjects,
+                }
+            },
+            "cat-file" => {
+                let usage = || Error::Generic("Usage: ash cat-file (-t | -s | -p) <object>".to_string());
+                if args.len() != 4 {
+                    return Err(usage());
+                }
+                
+                let (show_type, show_size, pretty) = match args[2].as_str() {
+                    "-t" => (true, false, false),
+                    "-s" => (false, true, false),
+                    "-p" => (false, false, true),
+                    _ => return Err(usage()),
+                };
+                
+                CliArgs {
+                    command: Command::CatFile {
+                        object: args[3].clone(),
+                        show_type,
+                        show_size,
+                        pretty,
                     },
                 }
             },
@@ -241,6 +517,12 @@ impl CliParser {
                 let mut format = "medium".to_string();
                 let mut patch = false;
                 let mut decorate = "auto".to_string();
+                let mut graph = false;
+                let mut topo_order = false;
+                let mut author = None;
+                let mut since = None;
+                let mut until = None;
+                let mut max_count = None;
 
                 // Process arguments
                 let mut i = 2;
@@ -294,6 +576,45 @@ impl CliParser {
                         "--no-decorate" => {
                             decorate = "no".to_string();
                         },
+                        "--graph" => {
+                            graph = true;
+                        },
+                        "--topo-order" => {
+                            topo_order = true;
+                        },
+                        "--date-order" => {
+                            topo_order = false;
+                        },
+                        "--author" | "--since" | "--after" | "--until" | "--before" | "--max-count" | "-n" => {
+                            if i + 1 >= args.len() {
+                                return Err(Error::Generic(format!("Option '{}' requires a value", arg)));
+                            }
+                            let value = args[i + 1].clone();
+                            match arg.as_str() {
+                                "--author" => author = Some(value),
+                                "--since" | "--after" => since = Some(value),
+                                "--until" | "--before" => until = Some(value),
+                                _ => max_count = Some(Self::parse_max_count(&value)?),
+                            }
+                            i += 1;
+                        },
+                        a if a.starts_with("--author=") => {
+                            author = Some(a["--author=".len()..].to_string());
+                        },
+                        a if a.starts_with("--since=") || a.starts_with("--after=") => {
+                            since = a.split_once('=').map(|(_, v)| v.to_string());
+                        },
+                        a if a.starts_with("--until=") || a.starts_with("--before=") => {
+                            until = a.split_once('=').map(|(_, v)| v.to_string());
+                        },
+                        a if a.starts_with("--max-count=") => {
+                            max_count = Some(Self::parse_max_count(&a["--max-count=".len()..])?);
+                        },
+                        "--" => {
+                            // Everything after `--` is a path; keep the marker for LogCommand
+                            revisions.extend(args[i..].iter().cloned());
+                            break;
+                        },
                         a if a.starts_with('-') => {
                             // Unknown flag
                              return Err(Error::Generic(format!("Unknown option for log: {}", a)));
@@ -313,6 +634,69 @@ impl CliParser {
                         format,
                         patch,
                         decorate,
+                        graph,
+                        topo_order,
+                        author,
+                        since,
+                        until,
+                        max_count,
+                    },
+                }
+            },
+            "restore" => {
+                let mut paths = Vec::new();
+                let mut staged = false;
+                let mut worktree = false;
+                let mut source = None;
+                
+                let mut i = 2;
+                while i < args.len() {
+                    let arg = &args[i];
+                    match arg.as_str() {
+                        "-S" | "--staged" => staged = true,
+                        "-W" | "--worktree" => worktree = true,
+                        "-s" | "--source" => {
+                            if i + 1 >= args.len() {
+                                return Err(Error::Generic(format!("Option '{}' requires a value", arg)));
+                            }
+                            i += 1;
+                            source = Some(args[i].clone());
+                        },
+                        a if a.starts_with("--source=") => source = Some(a["--source=".len()..].to_string()),
+                        "--" => {
+                            paths.extend(args[i + 1..].iter().cloned());
+                            break;
+                        },
+                        a if a.starts_with('-') => {
+                            return Err(Error::Generic(format!("Unknown option for restore: {}", a)));
+                        },
+                        _ => paths.push(arg.clone()),
+                    }
+                    i += 1;
+                }
+                
+                if paths.is_empty() {
+                    return Err(Error::Generic("you must specify path(s) to restore".to_string()));
+                }
+                
+                CliArgs {
+                    command: Command::Restore {
+                        paths,
+                        staged,
+                        worktree,
+                        source,
+                    },
+                }
+            },
+            "config" => {
+                if args.len() < 3 || args.len() > 4 {
+                    return Err(Error::Generic("Usage: ash config <section.key> [<value>]".to_string()));
+                }
+                
+                CliArgs {
+                    command: Command::Config {
+                        name: args[2].clone(),
+                        value: args.get(3).cloned(),
                     },
                 }
             },
@@ -322,6 +706,7 @@ impl CliParser {
                 let mut cached = false;
                 let mut force = false;
                 let mut recursive = false;
+                let mut dry_run = false;
                 
                 // Process arguments
                 let mut i = 2;
@@ -337,6 +722,9 @@ impl CliParser {
                         "-r" | "--recursive" => {
                             recursive = true;
                         },
+                        "-n" | "--dry-run" => {
+                            dry_run = true;
+                        },
                         a if a.starts_with('-') => {
                             // Unknown flag
                             return Err(Error::Generic(format!("Unknown option for rm: {}", a)));
@@ -359,15 +747,21 @@ impl CliParser {
                         cached,
                         force,
                         recursive,
+                        dry_run,
                     },
                 }
             },
             "merge" => {
-                let mut branch = String::new();
+                let mut branches = Vec::new();
                 let mut message = None;
                 let mut abort = false;
                 let mut continue_merge = false;
                 let mut tool = None; 
+                let mut strategy = MergeStrategy::Recursive;
+                let mut conflict_style = ConflictStyle::Merge;
+                let mut no_commit = false;
+                let mut no_ff = false;
+                let mut progress = None;
 
                 let mut i = 2;
                 while i < args.len() {
@@ -387,6 +781,18 @@ impl CliParser {
                         "--continue" => {
                             continue_merge = true;
                         },
+                        "--no-commit" => {
+                            no_commit = true;
+                        },
+                        "--no-ff" => {
+                            no_ff = true;
+                        },
+                        "--progress" => {
+                            progress = Some(true);
+                        },
+                        "--no-progress" => {
+                            progress = Some(false);
+                        },
                         "--tool" | "-t" => {  // Added tool handling
                             if i + 1 < args.len() {
                                 tool = Some(args[i + 1].clone());
@@ -398,17 +804,38 @@ impl CliParser {
                         "--tool-only" => { 
                             tool = Some("default".to_string());
                         },
+                        "-X" | "--strategy-option" => {
+                            if i + 1 < args.len() {
+                                strategy = Self::parse_strategy_option(&args[i + 1])?;
+                                i += 1;
+                            } else {
+                                return Err(Error::Generic(format!("Option '{}' requires a value", arg)));
+                            }
+                        },
+                        a if a.starts_with("--strategy-option=") => {
+                            strategy = Self::parse_strategy_option(&a["--strategy-option=".len()..])?;
+                        },
+                        a if a.starts_with("-X") => {
+                            strategy = Self::parse_strategy_option(&a[2..])?;
+                        },
+                        "--conflict-style" => {
+                            if i + 1 < args.len() {
+                                conflict_style = Self::parse_conflict_style(&args[i + 1])?;
+                                i += 1;
+                            } else {
+                                return Err(Error::Generic(format!("Option '{}' requires a value", arg)));
+                            }
+                        },
+                        a if a.starts_with("--conflict-style=") => {
+                            conflict_style = Self::parse_conflict_style(&a["--conflict-style=".len()..])?;
+                        },
                         // Allow unknown flags for now or add error handling
                         _ if arg.starts_with('-') => {
                             return Err(Error::Generic(format!("Unknown option for merge: {}", arg)));
                         },
-                        // Assume the first non-flag argument is the branch name
-                        _ if branch.is_empty() => {
-                            branch = arg.clone();
-                        },
-                        // Handle unexpected additional arguments
+                        // Every non-flag argument is a branch to merge; several make an octopus merge
                         _ => {
-                            return Err(Error::Generic(format!("Unexpected argument for merge: {}", arg)));
+                            branches.push(arg.clone());
                         }
                     }
                     i += 1; // Increment index
@@ -416,25 +843,225 @@ impl CliParser {
 
                 // Special case: if --tool-only or --tool is provided without branch, it means
                 // we want to just run the tool on existing conflicts
-                let resolve_only = tool.is_some() && branch.is_empty() && !abort && !continue_merge;
+                let resolve_only = tool.is_some() && branches.is_empty() && !abort && !continue_merge;
                 
                 // Branch name is required unless --abort, --continue, or just running the tool
-                if branch.is_empty() && !abort && !continue_merge && !resolve_only {
+                if branches.is_empty() && !abort && !continue_merge && !resolve_only {
                     return Err(Error::Generic("No branch specified for merge and not using --abort, --continue, or --tool".to_string()));
                 }
                 
                 // Cannot specify branch name with --abort or --continue
-                if !branch.is_empty() && (abort || continue_merge) {
+                if !branches.is_empty() && (abort || continue_merge) {
                     return Err(Error::Generic("Cannot specify branch name with --abort or --continue".to_string()));
                 }
 
+                // An octopus merge either commits cleanly or changes nothing
+                if branches.len() > 1 && (no_commit || conflict_style != ConflictStyle::Merge) {
+                    return Err(Error::Generic("--no-commit and --conflict-style cannot be used when merging several branches".to_string()));
+                }
+
                 CliArgs {
                     command: Command::Merge {
-                        branch,
+                        branches,
                         message,
                         abort,
                         continue_merge,
                         tool,
+                        strategy,
+                        conflict_style,
+                        no_commit,
+                        no_ff,
+                        progress,
+                    },
+                }
+            },
+            "merge-base" => {
+                let mut commits = Vec::new();
+                let mut is_ancestor = false;
+                let mut all = false;
+
+                for arg in args.iter().skip(2) {
+                    match arg.as_str() {
+                        "--is-ancestor" => {
+                            is_ancestor = true;
+                        },
+                        "--all" | "-a" => {
+                            all = true;
+                        },
+                        a if a.starts_with('-') => {
+                            return Err(Error::Generic(format!("Unknown option for merge-base: {}", a)));
+                        },
+                        _ => {
+                            commits.push(arg.clone());
+                        }
+                    }
+                }
+
+                if commits.len() != 2 {
+                    return Err(Error::Generic("merge-base requires two commits".to_string()));
+                }
+
+                if is_ancestor && all {
+                    return Err(Error::Generic("--all cannot be used with --is-ancestor".to_string()));
+                }
+
+                CliArgs {
+                    command: Command::MergeBase {
+                        commits,
+                        is_ancestor,
+                        all,
+                    },
+                }
+            },
+            "rev-parse" => {
+                let revisions: Vec<String> = args.iter().skip(2).cloned().collect();
+
+                if let Some(option) = revisions.iter().find(|arg| arg.starts_with('-')) {
+                    return Err(Error::Generic(format!("Unknown option for rev-parse: {}", option)));
+                }
+                if revisions.is_empty() {
+                    return Err(Error::Generic("rev-parse requires at least one revision".to_string()));
+                }
+
+                CliArgs {
+                    command: Command::RevParse { revisions },
+                }
+            },
+            "stash" => {
+                let action = match args.get(2).map(|s| s.as_str()) {
+                    None => "push".to_string(),
+                    Some(a) if a.starts_with('-') => "push".to_string(),
+                    Some(a @ ("push" | "pop" | "list")) => a.to_string(),
+                    Some(a) => return Err(Error::Generic(format!("Unknown stash subcommand: {}", a))),
+                };
+
+                let mut message = None;
+                if let Some(pos) = args.iter().position(|a| a == "-m" || a == "--message") {
+                    match args.get(pos + 1) {
+                        Some(m) => message = Some(m.clone()),
+                        None => return Err(Error::Generic("Option -m requires a message".to_string())),
+                    }
+                }
+
+                let include_untracked = args.iter().skip(2).any(|a| a == "-u" || a == "--include-untracked");
+                let keep_index = args.iter().skip(2).any(|a| a == "-k" || a == "--keep-index");
+
+                CliArgs {
+                    command: Command::Stash {
+                        action,
+                        message,
+                        include_untracked,
+                        keep_index,
+                    },
+                }
+            },
+            "ls-files" => {
+                let mut stage = false;
+                let mut unmerged = false;
+
+                for arg in args.iter().skip(2) {
+                    match arg.as_str() {
+                        "-s" | "--stage" => stage = true,
+                        "-u" | "--unmerged" => unmerged = true,
+                        a => return Err(Error::Generic(format!("Unknown option for ls-files: {}", a))),
+                    }
+                }
+
+                CliArgs {
+                    command: Command::LsFiles { stage, unmerged },
+                }
+            },
+            "gc" => {
+                if let Some(arg) = args.get(2) {
+                    return Err(Error::Generic(format!("Unknown option for gc: {}", arg)));
+                }
+
+                CliArgs {
+                    command: Command::Gc,
+                }
+            },
+            "prune" => {
+                let mut dry_run = false;
+                let mut expire = None;
+
+                let mut i = 2;
+                while i < args.len() {
+                    match args[i].as_str() {
+                        "-n" | "--dry-run" => dry_run = true,
+                        "--expire" => {
+                            if i + 1 >= args.len() {
+                                return Err(Error::Generic("Option '--expire' requires a value".to_string()));
+                            }
+                            expire = Some(args[i + 1].clone());
+                            i += 1;
+                        },
+                        a if a.starts_with("--expire=") => {
+                            expire = Some(a["--expire=".len()..].to_string());
+                        },
+                        a => return Err(Error::Generic(format!("Unknown option for prune: {}", a))),
+                    }
+                    i += 1;
+                }
+
+                CliArgs {
+                    command: Command::Prune { dry_run, expire },
+                }
+            },
+            "reflog" => {
+                // Accept an optional "show" subcommand like git
+                let rest: Vec<&String> = args.iter().skip(2).filter(|a| a.as_str() != "show").collect();
+
+                CliArgs {
+                    command: Command::Reflog {
+                        ref_name: rest.first().map(|s| s.to_string()),
+                    },
+                }
+            },
+            "tag" => {
+                let mut positional = Vec::new();
+                let mut message = None;
+                let mut annotate = false;
+                let mut delete = false;
+                let mut list = false;
+
+                let mut i = 2;
+                while i < args.len() {
+                    match args[i].as_str() {
+                        "-a" | "--annotate" => annotate = true,
+                        "-d" | "--delete" => delete = true,
+                        "-l" | "--list" => list = true,
+                        "-m" | "--message" => {
+                            if i + 1 >= args.len() {
+                                return Err(Error::Generic("Option -m requires a message".to_string()));
+                            }
+                            message = Some(args[i + 1].clone());
+                            i += 1;
+                        },
+                        a if a.starts_with('-') => {
+                            return Err(Error::Generic(format!("Unknown option for tag: {}", a)));
+                        },
+                        _ => positional.push(args[i].clone()),
+                    }
+                    i += 1;
+                }
+
+                if annotate && message.is_none() {
+                    return Err(Error::Generic("Annotated tags require a message (-m <msg>)".to_string()));
+                }
+
+                if delete && positional.is_empty() {
+                    return Err(Error::Generic("tag -d requires a tag name".to_string()));
+                }
+
+                let mut positional = positional.into_iter();
+
+                CliArgs {
+                    command: Command::Tag {
+                        name: positional.next(),
+                        target: positional.next(),
+                        message,
+                        delete,
+                        list,
                     },
                 }
             },
@@ -446,6 +1073,10 @@ impl CliParser {
                 let mut hard = false;
                 let mut force = false;
                 let mut reuse_message = None;
+                let mut patch = false;
+                let mut revision = None;
+                let mut separator = false;
+                let mut progress = None;
                 
                 // Process all arguments for options
                 let mut i = 2;
@@ -468,6 +1099,14 @@ impl CliParser {
                             force = true;
                             i += 1;
                         },
+                        "--patch" | "-p" => {
+                            patch = true;
+                            i += 1;
+                        },
+                        "--progress" | "--no-progress" => {
+                            progress = Some(arg == "--progress");
+                            i += 1;
+                        },
                         "--reuse-message" | "-C" => {
                             if i + 1 < args.len() {
                                 reuse_message = Some(args[i + 1].clone());
@@ -476,6 +1115,19 @@ impl CliParser {
                                 return Err(Error::Generic("--reuse-message requires a value".to_string()));
                             }
                         },
+                        "--" => {
+                            // reset [<revision>] -- <paths>: everything after `--` is a path
+                            if files.len() > 1 {
+                                return Err(Error::Generic("reset takes at most one revision before '--'".to_string()));
+                            }
+                            revision = files.pop();
+                            separator = true;
+                            i += 1;
+                            while i < args.len() {
+                                files.push(args[i].clone());
+                                i += 1;
+                            }
+                        },
                         arg if arg.starts_with('-') => {
                             return Err(Error::Generic(format!("Unknown option for reset: {}", arg)));
                         },
@@ -486,14 +1138,21 @@ impl CliParser {
                     }
                 }
                 
+                if separator && files.is_empty() && !patch {
+                    return Err(Error::Generic("reset with '--' requires at least one path".to_string()));
+                }
+                
                 CliArgs {
                     command: Command::Reset {
+                        revision,
                         files,
                         soft,
                         mixed,
                         hard,
                         force,
                         reuse_message,
+                        patch,
+                        progress,
                     },
                 }
             },
@@ -681,16 +1340,48 @@ impl CliParser {
                         }
                     },
                     "burndown" => {
-                        // Optional sprint name parameter
-                        let sprint_name = if args.len() > 3 {
-                            Some(args[3].clone())
-                        } else {
-                            None
-                        };
+                        // Optional sprint name parameter and export format
+                        let mut sprint_name = None;
+                        let mut format = None;
+                        
+                        let mut i = 3;
+                        while i < args.len() {
+                            let arg = &args[i];
+                            match arg.as_str() {
+                                "--format" => {
+                                    if i + 1 < args.len() {
+                                        format = Some(args[i + 1].clone());
+                                        i += 1;
+                                    } else {
+                                        return Err(Error::Generic(format!("Option '{}' requires a value", arg)));
+                                    }
+                                },
+                                a if a.starts_with("--format=") => {
+                                    format = Some(a["--format=".len()..].to_string());
+                                },
+                                _ if arg.starts_with('-') => {
+                                    return Err(Error::Generic(format!("Unknown option for sprint burndown: {}", arg)));
+                                },
+                                _ if sprint_name.is_none() => {
+                                    sprint_name = Some(arg.clone());
+                                },
+                                _ => {
+                                    return Err(Error::Generic(format!("Unexpected argument for sprint burndown: {}", arg)));
+                                }
+                            }
+                            i += 1;
+                        }
+                        
+                        if let Some(format) = &format {
+                            if format != "csv" && format != "json" {
+                                return Err(Error::Generic(format!("Unknown burndown format: {} (expected csv or json)", format)));
+                            }
+                        }
                         
                         CliArgs {
                             command: Command::SprintBurndown {
                                 sprint_name,
+                                format,
                             },
                         }
                     },
@@ -716,6 +1407,63 @@ impl CliParser {
                             },
                         }
                     },
+                    "close" => {
+                        let mut sprint_name = None;
+                        let mut tag = false;
+                        let mut carry_over_to = None;
+                        let mut i = 3;
+                        while i < args.len() {
+                            let arg = &args[i];
+                            match arg.as_str() {
+                                "--tag" => tag = true,
+                                "--carry-over-to" => {
+                                    if i + 1 >= args.len() {
+                                        return Err(Error::Generic(format!("Option '{}' requires a value", arg)));
+                                    }
+                                    i += 1;
+                                    carry_over_to = Some(args[i].clone());
+                                },
+                                a if a.starts_with("--carry-over-to=") => {
+                                    carry_over_to = Some(a["--carry-over-to=".len()..].to_string());
+                                },
+                                _ if arg.starts_with('-') => {
+                                    return Err(Error::Generic(format!("Unknown option for sprint close: {}", arg)));
+                                },
+                                _ if sprint_name.is_none() => sprint_name = Some(arg.clone()),
+                                _ => return Err(Error::Generic(format!("Unexpected argument for sprint close: {}", arg))),
+                            }
+                            i += 1;
+                        }
+                        
+                        CliArgs {
+                            command: Command::SprintClose {
+                                sprint_name,
+                                tag,
+                                carry_over_to,
+                            },
+                        }
+                    },
+                    "report" => {
+                        let mut sprint_name = None;
+                        let mut json = false;
+                        for arg in &args[3..] {
+                            match arg.as_str() {
+                                "--json" => json = true,
+                                _ if arg.starts_with('-') => {
+                                    return Err(Error::Generic(format!("Unknown option for sprint report: {}", arg)));
+                                },
+                                _ if sprint_name.is_none() => sprint_name = Some(arg.clone()),
+                                _ => return Err(Error::Generic(format!("Unexpected argument for sprint report: {}", arg))),
+                            }
+                        }
+                        
+                        CliArgs {
+                            command: Command::SprintReport {
+                                sprint_name,
+                                json,
+                            },
+                        }
+                    },
                     _ => {
                         return Err(Error::Generic(format!("Unknown sprint subcommand: {}", subcommand)));
                     }
@@ -732,31 +1480,82 @@ impl CliParser {
                 }
                 
                 let subcommand = args[2].to_lowercase();
-                let mut args_iter = args.iter().skip(3);
+                let args_iter = args.iter().skip(3);
                 match subcommand.as_str() {
+                    "list" => {
+                        CliArgs {
+                            command: Command::TaskList {
+                                args: args_iter.cloned().collect(),
+                            },
+                        }
+                    },
                     "create" => {
-                        if args.len() < 4 {
-                            return Err(Error::Generic("task create requires an id and description".to_string()));
+                        // Positional id, description and story points, plus --depends-on <id,...> and --priority <p>
+                        let mut positional = Vec::new();
+                        let mut depends_on = Vec::new();
+                        let mut priority = Priority::default();
+                        
+                        let mut i = 3;
+                        while i < args.len() {
+                            let arg = &args[i];
+                            let deps = match arg.as_str() {
+                                "--depends-on" => {
+                                    if i + 1 >= args.len() {
+                                        return Err(Error::Generic(format!("Option '{}' requires a value", arg)));
+                                    }
+                                    i += 1;
+                                    Some(args[i].as_str())
+                                },
+                                a if a.starts_with("--depends-on=") => Some(&a["--depends-on=".len()..]),
+                                "--priority" => {
+                                    if i + 1 >= args.len() {
+                                        return Err(Error::Generic(format!("Option '{}' requires a value", arg)));
+                                    }
+                                    i += 1;
+                                    priority = Self::parse_priority(&args[i])?;
+                                    None
+                                },
+                                a if a.starts_with("--priority=") => {
+                                    priority = Self::parse_priority(&a["--priority=".len()..])?;
+                                    None
+                                },
+                                _ if arg.starts_with('-') => {
+                                    return Err(Error::Generic(format!("Unknown option for task create: {}", arg)));
+                                },
+                                _ => {
+                                    positional.push(arg.clone());
+                                    None
+                                }
+                            };
+                            if let Some(deps) = deps {
+                                depends_on.extend(deps.split(',').map(str::trim).filter(|d| !d.is_empty()).map(String::from));
+                            }
+                            i += 1;
                         }
                         
-                        let id = args[3].clone();
-                        let description = args[4].clone();
+                        if positional.len() < 2 {
+                            return Err(Error::Generic("task create requires an id and description".to_string()));
+                        }
+                        if positional.len() > 3 {
+                            return Err(Error::Generic(format!("Unexpected argument for task create: {}", positional[3])));
+                        }
                         
                         // Parse optional story points
-                        let story_points = if args.len() > 4 {
-                            match args[4].parse::<u32>() {
+                        let story_points = match positional.get(2) {
+                            Some(sp) => match sp.parse::<u32>() {
                                 Ok(sp) => Some(sp),
                                 Err(_) => return Err(Error::Generic("Story points must be a positive integer".to_string())),
-                            }
-                        } else {
-                            None
+                            },
+                            None => None,
                         };
                         
                         CliArgs {
                             command: Command::TaskCreate {
-                                id,
-                                description,
+                                id: positional[0].clone(),
+                                description: positional[1].clone(),
                                 story_points,
+                                depends_on,
+                                priority,
                             },
                         }
                     },
@@ -804,30 +1603,123 @@ impl CliParser {
         Ok(cli_args)
     }
 
+    /// Removes `--progress`/`--no-progress` from `args`, returning the rest and the last one
+    /// given: Some(true) to force progress output, Some(false) to suppress it
+    fn take_progress_flag(args: &[String]) -> (Vec<String>, Option<bool>) {
+        let mut progress = None;
+        let rest = args.iter()
+            .filter(|arg| match arg.as_str() {
+                "--progress" => { progress = Some(true); false },
+                "--no-progress" => { progress = Some(false); false },
+                _ => true,
+            })
+            .cloned()
+            .collect();
+        (rest, progress)
+    }
+
+    /// Parses the value of a merge `-X` option
+    fn parse_strategy_option(option: &str) -> Result<MergeStrategy, Error> {
+        MergeStrategy::from_option(option)
+            .ok_or_else(|| Error::Generic(format!("Unknown strategy option: -X{}", option)))
+    }
+
+    /// Parses the value of a task `--priority` option
+    fn parse_priority(name: &str) -> Result<Priority, Error> {
+        Priority::parse(name)
+            .ok_or_else(|| Error::Generic(format!("Unknown priority: {} (expected low, medium, high or critical)", name)))
+    }
+
+    /// Parses the value of a merge `--conflict-style` option
+    fn parse_conflict_style(style: &str) -> Result<ConflictStyle, Error> {
+        ConflictStyle::from_option(style)
+            .ok_or_else(|| Error::Generic(format!("Unknown conflict style: {} (expected merge or diff3)", style)))
+    }
+
+    /// Parses the value of a log `-n`/`--max-count` option
+    fn parse_max_count(value: &str) -> Result<usize, Error> {
+        value.parse()
+            .map_err(|_| Error::Generic(format!("Invalid max count: {}", value)))
+    }
+
     pub fn format_help() -> String {
         let mut help = String::new();
         
-        help.push_str("Usage: ash <command> [options]\n");
+        help.push_str("Usage: ash [--no-pager] [--color[=<when>]] <command> [options]\n");
+        help.push_str("Global options:\n");
+        help.push_str("  -P, --no-pager                    Write output directly instead of through a pager\n");
+        help.push_str("  --color[=<when>], --no-color      Color output always, never or auto (when stdout is a terminal)\n");
         help.push_str("Commands:\n");
         help.push_str("  init [path]                       Initialize a new repository\n");
         help.push_str("  add <paths...>                    Add file contents to the index\n");
-        help.push_str("  commit -m <message>               Commit changes to the repository\n");
-        help.push_str("  status [--porcelain] [--color=...] Show the working tree status\n");
-        help.push_str("  diff [--cached] [paths...]        Show changes (HEAD vs index or index vs workspace)\n");
+        help.push_str("        -p, --patch [paths]         Interactively stage hunks of tracked files\n");
+        help.push_str("  commit -m <message> [paths...]    Commit changes (only those staged under paths, if given)\n");
+        help.push_str("        --amend [-m <msg>|-C <rev>] [-e] Replace HEAD, keeping its message unless overridden\n");
+        help.push_str("        --author <ident> --date <date> Override the author (Name <email>) and date\n");
+        help.push_str("  status [--porcelain[=v2]] [--color=...] Show the working tree status\n");
+        help.push_str("        -s, --short                 Show a compact, colored status with a branch header\n");
+        help.push_str("        --no-untracked-cache        Rescan every untracked directory instead of using the cache\n");
+        help.push_str("        -b, --branch                Show a ## <branch> header line with --porcelain\n");
+        help.push_str("  diff [--cached] [--stat] [--word-diff] [paths...] Show changes (HEAD vs index or index vs workspace)\n");
+        help.push_str("        <commit> [<commit>]         Compare a commit with the working tree (index with --cached), or two commits\n");
+        help.push_str("        -U<n>, --unified=<n>        Show <n> lines of context around changes (default 3)\n");
+        help.push_str("        --git                       Write a plain patch for git apply (default when piped)\n");
+        help.push_str("        -M, --find-renames          Show deleted and added files with similar content as renames\n");
+        help.push_str("        --color[=<when>]            Color the diff always, never or auto, even when piped\n");
+        help.push_str("  apply [--cached|--index] [--check] [<patch>] Apply a patch (from stdin without <patch>), following renames\n");
         help.push_str("  branch [-v] [-d|-D] [<n> [<sp>]]  Manage branches (list, create, delete)\n");
+        help.push_str("        -m|-M [<old>] <new>         Rename a branch (the current one by default)\n");
+        help.push_str("        -u, --set-upstream-to <up> [<n>] Track <up> from a branch (the current one by default)\n");
         help.push_str("  checkout <target>                 Switch branches or restore working tree files\n");
+        help.push_str("        --orphan <name>             Start a new branch with no history (also: switch --orphan)\n");
+        help.push_str("        -p [<rev>] [--] [paths]     Interactively discard working-tree hunks\n");
+        help.push_str("        -b|-B <branch> [<start>]    Create (or with -B reset) a branch and switch to it\n");
+        help.push_str("        [<rev>] -- <paths>...       Restore files from the index or <rev>\n");
+        help.push_str("        --[no-]progress             Force or suppress the progress bar (checkout, merge, reset)\n");
         help.push_str("  log [--oneline] [--decorate=...]  Show commit logs\n");
+        help.push_str("        --graph                     Draw the commit graph beside the log\n");
+        help.push_str("        --topo-order                Show no parent before all of its children\n");
+        help.push_str("        --author=<pattern>          Only commits whose author matches <pattern>\n");
+        help.push_str("        --since=<date>, --until=<date> Only commits authored in the date range\n");
+        help.push_str("        -n, --max-count=<n>         Show at most <n> commits\n");
+        help.push_str("        -- <paths>...               Only commits touching <paths>\n");
+        help.push_str("  show [<object>]                   Show a commit and its patch, a tree, a blob or a tag\n");
+        help.push_str("  cat-file (-t|-s|-p) <object>      Print an object's type, size or content\n");
+        help.push_str("  fsck [-v] [--connectivity-only]   Check that reachable objects are present and intact\n");
+        help.push_str("  verify-objects                    Re-hash every stored object and list corrupt ones\n");
         help.push_str("  rm <files...> [--cached] [-f]     Remove files from the working tree and index\n");
+        help.push_str("        -r, -n/--dry-run            Remove directories recursively; only list what would be removed\n");
+        help.push_str("  config <section.key> [<value>]    Get or set a setting in .ash/config (e.g. user.name, core.editor)\n");
+        help.push_str("  restore [--staged] [--worktree] [--source=<rev>] <paths...>\n");
+        help.push_str("                                    Unstage paths or restore working files (from HEAD or the index)\n");
         help.push_str("  reset [--soft|--mixed|--hard]     Reset current HEAD to the specified state\n");
+        help.push_str("        [<commit>] [--] [paths]     Mixed (default) resets the index only; paths are unstaged\n");
+        help.push_str("        -p, --patch [<commit>] [paths] Interactively unstage hunks, keeping the working tree\n");
         help.push_str("  cherry-pick <commits...>          Apply changes from existing commits\n");
         help.push_str("        --continue|--abort|--quit   Control operation in progress\n");
         help.push_str("  revert <commits...>               Revert existing commits\n");
         help.push_str("        --continue|--abort|--quit   Control operation in progress\n");
         help.push_str("  merge <branch> [-m <msg>]         Merge the specified branch into the current branch\n");
+        help.push_str("  merge <branch> <branch>...        Octopus merge: merge several branches in one commit, if all merge cleanly\n");
+        help.push_str("  merge -X <ours|theirs> <branch>   Merge, resolving conflicts in favor of one side\n");
+        help.push_str("        --conflict-style=<style>    Write conflicts as 'merge' or 'diff3' (with the base section)\n");
+        help.push_str("        --no-commit                 Stage the merge result but stop before committing\n");
+        help.push_str("        --no-ff                     Create a merge commit even when a fast-forward is possible\n");
         help.push_str("        --abort                     Abort the current merge resolution process\n");
         help.push_str("        --continue                  Continue the merge after resolving conflicts\n");
         help.push_str("        --tool=<tool>               Use specified tool to resolve merge conflicts\n");
         help.push_str("        --tool-only                 Run merge tool to resolve conflicts without merging\n");
+        help.push_str("  merge-base [--all] <a> <b>        Show the best common ancestor (or all of them) of two commits\n");
+        help.push_str("  merge-base --is-ancestor <a> <b>  Exit 0 if <a> is an ancestor of <b>, 1 otherwise\n");
+        help.push_str("  rev-parse <revisions...>          Print the commit IDs revisions resolve to (e.g. @{u})\n");
+        help.push_str("  stash [push [-m <msg>]|pop|list]  Stash away local changes or restore them\n");
+        help.push_str("        -u, --include-untracked     Also stash untracked files (restored by pop)\n");
+        help.push_str("        -k, --keep-index            Keep staged changes in the index and working tree\n");
+        help.push_str("  ls-files [-s] [-u]                Show paths in the index, with stages or only unmerged ones\n");
+        help.push_str("  gc                                Pack loose objects and refs\n");
+        help.push_str("  prune [-n] [--expire=<time>]      Delete unreachable loose objects older than <time> (default 2.weeks.ago)\n");
+        help.push_str("  reflog [<ref>]                    Show where HEAD (or a branch) has pointed\n");
+        help.push_str("  tag [-l] [-d] [-a -m <msg>] [<name> [<commit>]] Create, delete or list tags\n");
         help.push_str("\n");
         help.push_str("Sprint & Task Management:\n");
         help.push_str("  sprint                         Interactive view of the current sprint\n");
@@ -835,10 +1727,19 @@ impl CliParser {
         help.push_str("  sprint info                    Show information about the current sprint\n");
         help.push_str("  sprint commitmap [<sprint>]    Display a table of commits for the current or specified sprint\n");
         help.push_str("  sprint burndown [<sprint>]     Display a burndown chart for the current or specified sprint\n");
+        help.push_str("        --format <csv|json>      Print the daily ideal and actual remaining points instead\n");
         help.push_str("  sprint velocity                Display a velocity chart showing progress across sprints\n");
         help.push_str("  sprint advance <n> <s> <e>     Advance to a new sprint with name, start and end dates\n");
+        help.push_str("  sprint close [<name>] [--tag]  Close the active or named sprint, tagging HEAD as sprint-<n>-end with --tag\n");
+        help.push_str("        --carry-over-to <sprint>    Move unfinished tasks to another open sprint\n");
+        help.push_str("  sprint report [<sprint>]       Summarize points, tasks, commits and contributors (--json for JSON)\n");
         help.push_str("  task                           Display and manage all tasks (interactive UI)\n");
+        help.push_str("  task list [--order=deps]          List tasks, optionally with dependencies first\n");
+        help.push_str("        --format=plain|json         Print tasks instead of opening the interactive list\n");
+        help.push_str("        --no-tui                    Same as --format=plain\n");
         help.push_str("  task create <id> <desc> [<sp>]    Create a new task in the current sprint\n");
+        help.push_str("        --depends-on <id,...>       Tasks that must be done before this one can be completed\n");
+        help.push_str("        --priority <level>          low, medium (default), high or critical\n");
         help.push_str("  task complete <id> [--auto-merge] Mark a task as completed\n");
         help.push_str("  task status <id>                  Show status information for a task\n");
         help.push_str("\n");
@@ -848,4 +1749,36 @@ impl CliParser {
         
         help
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status_color(args: &[&str]) -> String {
+        let mut argv = vec!["ash".to_string(), "status".to_string()];
+        argv.extend(args.iter().map(|arg| arg.to_string()));
+        match CliParser::parse(argv).unwrap().command {
+            Command::Status { color, .. } => color,
+            other => panic!("expected status, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn status_color_takes_a_separate_value() {
+        assert_eq!(status_color(&["--color", "always"]), "always");
+        assert_eq!(status_color(&["--color", "always", "--short"]), "always");
+    }
+
+    #[test]
+    fn status_color_takes_an_attached_value() {
+        assert_eq!(status_color(&["--color=never"]), "never");
+    }
+
+    #[test]
+    fn status_trailing_color_means_always() {
+        assert_eq!(status_color(&["--short", "--color"]), "always");
+        assert_eq!(status_color(&["--color", "--short"]), "always");
+        assert_eq!(status_color(&[]), "auto");
+    }
+}
@@ -1,15 +1,20 @@
 /// Implements the 'add' command for AsheraFlow.
 /// Handles staging files and directories for commit, including improved directory handling.
 use std::path::{Path, PathBuf};
-use std::collections::{HashSet, HashMap};
+use std::collections::{BTreeMap, HashSet, HashMap};
 use std::time::Instant;
 use crate::core::database::blob::Blob;
 use crate::core::database::database::Database;
 use crate::core::database::tree::{Tree, TreeEntry, TREE_MODE};
 use crate::core::database::commit::Commit;
-use crate::core::index::index::Index;
-use crate::core::workspace::Workspace;
-use crate::core::refs::Refs;
+use crate::core::diff::diff::split_lines;
+use crate::core::diff::hunks::{apply_hunks, build_hunks};
+use crate::core::diff::myers::is_binary_content;
+use crate::core::file_mode::FileMode;
+use crate::core::pathspec;
+use crate::core::repository::repository::Repository;
+use crate::core::workspace::path_key;
+use crate::commands::patch_mode;
 use crate::errors::error::Error;
 use std::fs;
 
@@ -34,10 +39,7 @@ impl AddCommand {
             return Err(Error::Generic("Not an ash repository (or any of the parent directories): .ash directory not found".into()));
         }
         
-        let workspace = Workspace::new(root_path);
-        let mut database = Database::new(git_path.join("objects"));
-        let mut index = Index::new(git_path.join("index"));
-        let refs = Refs::new(&git_path);
+        let mut repo = Repository::new(".")?;
         
         // Prepare a set to deduplicate files (in case of overlapping path arguments)
         let mut files_to_add: HashSet<PathBuf> = HashSet::new();
@@ -45,12 +47,13 @@ impl AddCommand {
         let mut had_missing_valid_files = false;
         
         // Try to acquire the lock on the index
-        if !index.load_for_update()? {
+        if !repo.load_index_for_update()? {
             return Err(Error::Lock(format!(
                 "Unable to acquire lock on index. Another process may be using it. \
                 If not, the .ash/index.lock file may need to be manually removed."
             )));
         }
+        let Repository { workspace, mut database, mut index, refs, .. } = repo;
         
         // Get current files in index to avoid unnecessary operations
         let mut existing_oids = HashMap::new();
@@ -58,6 +61,15 @@ impl AddCommand {
             existing_oids.insert(entry.get_path().to_string(), entry.oid.clone());
         }
         
+        // Expand glob pathspecs against the workspace and the index
+        let paths = match pathspec::expand(paths, &workspace, existing_oids.keys()) {
+            Ok(paths) => paths,
+            Err(e) => {
+                index.rollback()?;
+                return Err(e);
+            }
+        };
+        
         // Flag to track if we have deleted directories
         let mut has_deleted_dirs = false;
         
@@ -72,7 +84,7 @@ impl AddCommand {
         let mut modified_files = 0;
         
         // Check each path
-        for path_str in paths {
+        for path_str in &paths {
             let path = PathBuf::from(path_str);
             
             // Check if the path exists in the workspace
@@ -80,11 +92,11 @@ impl AddCommand {
                 // Path doesn't exist in workspace, check if it's in the index
                 let rel_path_str = if path.is_absolute() {
                     match path.strip_prefix(root_path) {
-                        Ok(rel) => rel.to_string_lossy().to_string(),
-                        Err(_) => path.to_string_lossy().to_string()
+                        Ok(rel) => path_key(rel),
+                        Err(_) => path_key(&path)
                     }
                 } else {
-                    path.to_string_lossy().to_string()
+                    path_key(&path)
                 };
                 
                 // Handle case where the path is exactly a file in the index
@@ -161,9 +173,8 @@ impl AddCommand {
         
         // First, handle deleted files
         for path_str in &files_to_delete {
-            if index.entries.remove(path_str).is_some() {
-                index.keys.remove(path_str);
-                index.changed = true;
+            if index.tracked(path_str) {
+                index.remove(Path::new(path_str))?;
                 deleted_count += 1;
             }
         }
@@ -171,22 +182,48 @@ impl AddCommand {
         // Create a buffer for batch processing
         let mut blobs_to_save: Vec<(PathBuf, Vec<u8>, fs::Metadata)> = Vec::with_capacity(files_to_add.len());
         
+        // Hash every candidate up front so files whose content is already staged are skipped
+        // without reading them again. A file that can't be read fails the add.
+        // With `core.eol` set the raw bytes are not what gets stored, so each file is hashed
+        // after normalization instead.
+        let full_paths: Vec<PathBuf> = files_to_add.iter()
+            .map(|file_path| workspace.root_path.join(file_path))
+            .collect();
+        let hashes = if workspace.eol.is_none() {
+            match database.hash_files_parallel(&full_paths) {
+                Ok(hashes) => hashes,
+                Err(e) => {
+                    index.rollback()?;
+                    return Err(Error::Generic(format!("error: {}\nfatal: adding files failed", e)));
+                }
+            }
+        } else {
+            HashMap::new()
+        };
+        
         // First pass: read all files and check for errors before we start modifying anything
-        for file_path in &files_to_add {
+        for (file_path, full_path) in files_to_add.iter().zip(&full_paths) {
+            let file_key = path_key(file_path);
+            if let (Some(new_oid), Some(old_oid)) = (hashes.get(full_path), existing_oids.get(&file_key)) {
+                if old_oid == new_oid {
+                    unchanged_count += 1;
+                    continue;
+                }
+            }
+            
+
             // Try to read file content and metadata
             match (
                 workspace.read_file(file_path),
                 workspace.stat_file(file_path)
             ) {
                 (Ok(data), Ok(stat)) => {
-                    // Check if file is already in index with same content
-                    let file_key = file_path.to_string_lossy().to_string();
+                    let data = workspace.normalize_eol(&file_key, data);
                     
-                    // Pre-compute hash to check if the file has changed
-                    let new_oid = database.hash_file_data(&data);
-                    
-                    if let Some(old_oid) = existing_oids.get(&file_key) {
-                        if old_oid == &new_oid {
+                    // Check if file is already in index with same content
+                    if !hashes.contains_key(full_path) {
+                        let new_oid = database.hash_file_data(&data);
+                        if existing_oids.get(&file_key) == Some(&new_oid) {
                             // File exists in index with same content, skip it
                             unchanged_count += 1;
                             continue;
@@ -223,12 +260,14 @@ impl AddCommand {
             }
         }
         
-        // Second pass: process all files that need to be updated
+        // Second pass: process all files that need to be updated, batching the blob writes
+        database.begin_batch();
         for (file_path, data, stat) in blobs_to_save {
             // Create and store the blob
             let mut blob = Blob::new(data);
             if let Err(e) = database.store(&mut blob) {
                 // Release the lock if we fail to store the blob
+                database.discard_batch();
                 index.rollback()?;
                 return Err(Error::Generic(format!(
                     "Failed to store blob for '{}': {}", file_path.display(), e
@@ -240,6 +279,7 @@ impl AddCommand {
                 Some(id) => id,
                 None => {
                     // Release the lock if the blob has no OID
+                    database.discard_batch();
                     index.rollback()?;
                     return Err(Error::Generic(
                         "Blob OID not set after storage".into()
@@ -249,6 +289,7 @@ impl AddCommand {
             
             // Add to index
             if let Err(e) = index.add(&file_path, oid, &stat) {
+                database.discard_batch();
                 index.rollback()?;
                 return Err(e);
             }
@@ -257,7 +298,7 @@ impl AddCommand {
             processed_paths.push(file_path.clone());
             
             // Determine if it's a new or modified file
-            let file_path_str = file_path.to_string_lossy().to_string();
+            let file_path_str = path_key(&file_path);
             if existing_oids.contains_key(&file_path_str) {
                 modified_files += 1;
             } else {
@@ -267,6 +308,11 @@ impl AddCommand {
             added_count += 1;
         }
         
+        if let Err(e) = database.flush_batch() {
+            index.rollback()?;
+            return Err(Error::Generic(format!("Failed to write objects: {}", e)));
+        }
+        
         // Write index updates
         if added_count > 0 || deleted_count > 0 {
             if index.write_updates()? {
@@ -364,6 +410,101 @@ impl AddCommand {
         }
     }
 
+    /// Interactively stages hunks of the difference between the index and the working tree for
+    /// tracked files under `pathspecs` (all tracked files when empty). Each accepted file gets a
+    /// new blob made of its staged version plus the accepted hunks; rejected hunks stay only in
+    /// the working tree.
+    pub fn execute_patch(pathspecs: &[String]) -> Result<(), Error> {
+        let mut repo = Repository::new(".")?;
+        
+        if !repo.load_index_for_update()? {
+            return Err(Error::Lock(
+                "Unable to acquire lock on index. Another process may be using it. \
+                If not, the .ash/index.lock file may need to be manually removed.".to_string()
+            ));
+        }
+        
+        match Self::stage_hunks(&mut repo, pathspecs) {
+            Ok(0) => {
+                repo.index.rollback()?;
+                println!("No changes staged");
+                Ok(())
+            },
+            Ok(staged) => {
+                repo.index.write_updates()?;
+                println!("Staged {} hunk{}", staged, if staged == 1 { "" } else { "s" });
+                Ok(())
+            },
+            Err(e) => {
+                repo.index.rollback()?;
+                Err(e)
+            }
+        }
+    }
+    
+    // Offers the hunks of each selected path and updates its index entry; returns how many hunks were staged
+    fn stage_hunks(repo: &mut Repository, pathspecs: &[String]) -> Result<usize, Error> {
+        let staged_files: BTreeMap<String, (String, FileMode)> = repo.index.each_entry()
+            .filter(|entry| entry.stage == 0)
+            .filter(|entry| patch_mode::matches_pathspec(entry.get_path(), pathspecs))
+            .map(|entry| (entry.get_path().to_string(), (entry.get_oid().to_string(), *entry.get_mode())))
+            .collect();
+        
+        let mut staged = 0;
+        for (path, (oid, mode)) in &staged_files {
+            let file_path = Path::new(path);
+            let staged_content = repo.database.load(oid)?.to_bytes();
+            let exists = repo.workspace.path_exists(file_path)?;
+            let work_content = if exists {
+                repo.workspace.normalize_eol(path, repo.workspace.read_file(file_path)?)
+            } else {
+                Vec::new()
+            };
+            
+            if staged_content == work_content && exists {
+                continue;
+            }
+            if is_binary_content(&staged_content) || is_binary_content(&work_content) {
+                println!("Skipping binary file {}", path);
+                continue;
+            }
+            
+            let a = split_lines(&String::from_utf8_lossy(&staged_content));
+            let b = split_lines(&String::from_utf8_lossy(&work_content));
+            let hunks = build_hunks(&a, &b, 3);
+            if hunks.is_empty() {
+                continue;
+            }
+            
+            let selection = patch_mode::select_hunks(path, &hunks, "Stage this hunk")?;
+            let selected = selection.selected.iter().filter(|&&s| s).count();
+            
+            if selected == hunks.len() {
+                // Everything staged: the entry matches the working tree, or goes away with the file
+                if exists {
+                    let mut blob = Blob::new(work_content);
+                    let new_oid = repo.database.store(&mut blob)?;
+                    let stat = repo.workspace.stat_file(file_path)?;
+                    repo.index.add(file_path, &new_oid, &stat)?;
+                } else {
+                    repo.index.remove(file_path)?;
+                }
+            } else if selected > 0 {
+                let accepted = apply_hunks(&a, &selection.hunks(&hunks, true));
+                let mut blob = Blob::new(patch_mode::join_lines(&accepted));
+                let new_oid = repo.database.store(&mut blob)?;
+                repo.index.add_without_stat(file_path, &new_oid, *mode);
+            }
+            
+            staged += selected;
+            if selection.quit {
+                break;
+            }
+        }
+        
+        Ok(staged)
+    }
+
     // Recursively collect all files from a tree and its subtrees
     fn collect_files_from_tree(
         database: &mut Database,
@@ -384,7 +525,7 @@ impl AddCommand {
                     prefix.join(name)
                 };
                 
-                let entry_path_str = entry_path.to_string_lossy().to_string();
+                let entry_path_str = path_key(&entry_path);
                 
                 match entry {
                     TreeEntry::Blob(oid, mode) => {
@@ -423,7 +564,7 @@ impl AddCommand {
                             prefix.join(name)
                         };
                         
-                        let entry_path_str = entry_path.to_string_lossy().to_string();
+                        let entry_path_str = path_key(&entry_path);
                         
                         match entry {
                             TreeEntry::Blob(oid, mode) => {
@@ -448,7 +589,7 @@ impl AddCommand {
                 Err(_) => {
                     // If we're at a non-root path, this might be a file
                     if !prefix.as_os_str().is_empty() {
-                        let path_str = prefix.to_string_lossy().to_string();
+                        let path_str = path_key(&prefix);
                         files.insert(path_str, tree_oid.to_string());
                         return Ok(());
                     }
@@ -0,0 +1,257 @@
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+use crate::core::database::blob::Blob;
+use crate::core::diff::patch::{parse_patch, FilePatch};
+use crate::core::file_mode::FileMode;
+use crate::core::repository::repository::Repository;
+use crate::errors::error::Error;
+
+/// Implements the 'apply' command for AsheraFlow.
+/// Applies a patch such as `ash diff` or `git diff` writes, including created, deleted
+/// and renamed files and mode changes, to the working tree and/or the index.
+pub struct ApplyCommand;
+
+/// A patched file, worked out before anything is written
+struct PatchedFile<'a> {
+    patch: &'a FilePatch,
+    /// The new content, or None when the file is deleted
+    content: Option<Vec<u8>>,
+    mode: FileMode,
+}
+
+impl ApplyCommand {
+    /// Applies the patch in `patch_file`, or standard input when it is None or "-". By default
+    /// only the working tree changes; `cached` changes only the index and `index` both.
+    /// With `check` nothing is written. Every file is patched in memory first, so a patch that
+    /// fails anywhere changes nothing.
+    pub fn execute(patch_file: Option<&str>, cached: bool, index: bool, check: bool) -> Result<(), Error> {
+        let text = match patch_file {
+            Some(path) if path != "-" => fs::read_to_string(path)
+                .map_err(|e| Error::Generic(format!("can't open patch '{}': {}", path, e)))?,
+            _ => {
+                let mut text = String::new();
+                io::stdin().read_to_string(&mut text)?;
+                text
+            }
+        };
+        let patches = parse_patch(&text)?;
+
+        let mut repo = Repository::new(".")?;
+        let update_index = cached || index;
+        if !update_index || check {
+            repo.load_index()?;
+            let patched = Self::patch_files(&mut repo, &patches, cached, update_index)?;
+            if !check {
+                Self::write(&mut repo, &patched, cached, update_index)?;
+            }
+            return Ok(());
+        }
+
+        if !repo.load_index_for_update()? {
+            return Err(Error::Lock(
+                "Unable to acquire lock on index. Another process may be using it.".to_string()
+            ));
+        }
+
+        let result = Self::patch_files(&mut repo, &patches, cached, update_index)
+            .and_then(|patched| Self::write(&mut repo, &patched, cached, update_index));
+        match result {
+            Ok(()) => {
+                repo.index.write_updates()?;
+                Ok(())
+            },
+            Err(e) => {
+                repo.index.rollback()?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Applies every file's hunks to its current content. The content is read from the index
+    /// when `cached`, otherwise from the working tree, which must then match the index when
+    /// the index is updated too.
+    fn patch_files<'a>(repo: &mut Repository, patches: &'a [FilePatch], cached: bool, update_index: bool) -> Result<Vec<PatchedFile<'a>>, Error> {
+        let mut patched = Vec::with_capacity(patches.len());
+
+        for patch in patches {
+            if patch.binary {
+                return Err(Error::Generic(format!("cannot apply binary patch to '{}'", patch.path())));
+            }
+
+            let (old_content, old_mode) = match &patch.old_path {
+                Some(path) => Self::read_preimage(repo, path, cached, update_index)?,
+                None => (Vec::new(), FileMode::REGULAR),
+            };
+
+            // A created or renamed file must not overwrite one that is already there
+            if let Some(new_path) = &patch.new_path {
+                if patch.old_path.as_ref() != Some(new_path) && Self::exists(repo, new_path, cached, update_index)? {
+                    return Err(Error::Generic(format!("{}: already exists in {}", new_path, if cached { "index" } else { "working directory" })));
+                }
+            }
+
+            let content = patch.apply(&old_content)?;
+            let content = match patch.new_path {
+                Some(_) => Some(content),
+                None if content.is_empty() => None,
+                None => return Err(Error::Generic(format!("{}: removal patch leaves file contents", patch.path()))),
+            };
+
+            patched.push(PatchedFile {
+                patch,
+                content,
+                mode: patch.new_mode.or(patch.old_mode).unwrap_or(old_mode),
+            });
+        }
+
+        Ok(patched)
+    }
+
+    /// The current content and mode of `path`, with line endings as they are stored
+    fn read_preimage(repo: &mut Repository, path: &str, cached: bool, update_index: bool) -> Result<(Vec<u8>, FileMode), Error> {
+        let staged = repo.index.entry_for_path(path, 0)
+            .map(|entry| (entry.get_oid().to_string(), *entry.get_mode()));
+
+        if cached {
+            let (oid, mode) = staged.ok_or_else(|| Error::Generic(format!("{}: does not exist in index", path)))?;
+            return Ok((repo.database.load(&oid)?.to_bytes(), mode));
+        }
+
+        let file_path = Path::new(path);
+        if !repo.workspace.path_exists(file_path)? {
+            return Err(Error::Generic(format!("{}: No such file or directory", path)));
+        }
+        let content = repo.workspace.normalize_eol(path, repo.workspace.read_file(file_path)?);
+        let mode = FileMode::from_metadata(&repo.workspace.stat_file(file_path)?);
+
+        if update_index {
+            match staged {
+                Some((oid, _)) if oid == repo.database.hash_file_data(&content) => {},
+                Some(_) => return Err(Error::Generic(format!("{}: does not match index", path))),
+                None => return Err(Error::Generic(format!("{}: does not exist in index", path))),
+            }
+        }
+
+        Ok((content, mode))
+    }
+
+    /// Whether `path` is in the index when `cached`, otherwise in the working tree (or the
+    /// index, when it is updated too)
+    fn exists(repo: &Repository, path: &str, cached: bool, update_index: bool) -> Result<bool, Error> {
+        let staged = repo.index.tracked(path);
+        if cached {
+            return Ok(staged);
+        }
+        Ok(repo.workspace.path_exists(Path::new(path))? || (update_index && staged))
+    }
+
+    /// Removes the old paths of deleted and renamed files, then writes the new contents
+    fn write(repo: &mut Repository, patched: &[PatchedFile], cached: bool, update_index: bool) -> Result<(), Error> {
+        for file in patched {
+            let old_path = match &file.patch.old_path {
+                Some(old_path) if file.patch.new_path.as_ref() != Some(old_path) => old_path,
+                _ => continue,
+            };
+            let path = Path::new(old_path);
+            if !cached {
+                let full_path = repo.workspace.root_path.join(path);
+                if full_path.is_file() {
+                    fs::remove_file(&full_path)?;
+                }
+            }
+            if update_index {
+                repo.index.remove(path)?;
+            }
+        }
+
+        for file in patched {
+            let (new_path, content) = match (&file.patch.new_path, &file.content) {
+                (Some(new_path), Some(content)) => (new_path, content),
+                _ => continue,
+            };
+            let path = Path::new(new_path);
+
+            if !cached {
+                let data = repo.workspace.denormalize_eol(new_path, content.clone());
+                repo.workspace.write_file(path, &data)?;
+                repo.workspace.set_mode(path, file.mode)?;
+            }
+
+            if update_index {
+                let mut blob = Blob::new(content.clone());
+                repo.database.store(&mut blob)?;
+                let oid = blob.get_oid().map(|oid| oid.to_string()).unwrap_or_default();
+                if cached {
+                    repo.index.add_without_stat(path, &oid, file.mode);
+                } else {
+                    let stat = repo.workspace.stat_file(path)?;
+                    repo.index.add(path, &oid, &stat)?;
+                }
+            }
+
+            if file.patch.is_rename() {
+                println!("Renamed {} => {}", file.patch.old_path.as_deref().unwrap_or_default(), new_path);
+            }
+        }
+
+        println!("Applied patch to {} file{}", patched.len(), if patched.len() == 1 { "" } else { "s" });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestRepo;
+
+    #[test]
+    fn applies_a_rename_with_changes() {
+        let repo = TestRepo::new();
+        repo.commit_files(&[("old.txt", "one\ntwo\nthree\nfour\nfive\n")], "base");
+
+        let patch = "diff --git a/old.txt b/new.txt\n\
+                     similarity index 80%\n\
+                     rename from old.txt\n\
+                     rename to new.txt\n\
+                     index 1111111..2222222 100644\n\
+                     --- a/old.txt\n\
+                     +++ b/new.txt\n\
+                     @@ -3,3 +3,3 @@ two\n \
+                     three\n\
+                     -four\n\
+                     +FOUR\n \
+                     five\n";
+        repo.write("rename.patch", patch);
+
+        ApplyCommand::execute(Some("rename.patch"), false, true, false).unwrap();
+
+        assert!(!repo.exists("old.txt"));
+        assert_eq!(repo.read("new.txt"), "one\ntwo\nthree\nFOUR\nfive\n");
+        assert_eq!(repo.staged_oid("old.txt"), None);
+        assert_eq!(repo.staged_oid("new.txt"), Some(repo.blob_oid("one\ntwo\nthree\nFOUR\nfive\n")));
+    }
+
+    #[test]
+    fn leaves_everything_alone_when_one_file_fails() {
+        let repo = TestRepo::new();
+        repo.commit_files(&[("a.txt", "a\n"), ("b.txt", "b\n")], "base");
+
+        let patch = "diff --git a/a.txt b/a.txt\n\
+                     --- a/a.txt\n\
+                     +++ b/a.txt\n\
+                     @@ -1 +1 @@\n\
+                     -a\n\
+                     +A\n\
+                     diff --git a/b.txt b/b.txt\n\
+                     --- a/b.txt\n\
+                     +++ b/b.txt\n\
+                     @@ -1 +1 @@\n\
+                     -not b\n\
+                     +B\n";
+        repo.write("bad.patch", patch);
+
+        assert!(ApplyCommand::execute(Some("bad.patch"), false, false, false).is_err());
+        assert_eq!(repo.read("a.txt"), "a\n");
+    }
+}
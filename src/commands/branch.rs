@@ -5,6 +5,9 @@ use crate::core::revision::Revision;
 use crate::core::color::Color;
 use crate::core::refs::Reference;
 use crate::core::database::commit::Commit;
+use crate::core::merge::common_ancestors::is_ancestor;
+use crate::core::branch_metadata::BranchMetadataManager;
+use crate::core::config::Config;
 
 /// Implements the 'branch' command for AsheraFlow.
 /// Handles branch creation, deletion, and listing.
@@ -19,6 +22,22 @@ impl BranchCommand {
         let verbose = std::env::var("ASH_BRANCH_VERBOSE").unwrap_or_default() == "1";
         let delete = std::env::var("ASH_BRANCH_DELETE").unwrap_or_default() == "1";
         let force = std::env::var("ASH_BRANCH_FORCE").unwrap_or_default() == "1";
+        let rename = std::env::var("ASH_BRANCH_MOVE").unwrap_or_default() == "1";
+        let set_upstream = std::env::var("ASH_BRANCH_SET_UPSTREAM").ok();
+        
+        // Handle `--set-upstream-to <upstream> [<branch>]`
+        if let Some(upstream) = set_upstream {
+            let branch = if branch_name.is_empty() { None } else { Some(branch_name) };
+            return Self::set_upstream(branch, &upstream);
+        }
+        
+        // Handle rename: `-m <new>` renames the current branch, `-m <old> <new>` any branch
+        if rename {
+            return match start_point {
+                Some(new_name) => Self::rename_branch(Some(branch_name), new_name, force),
+                None => Self::rename_branch(None, branch_name, force),
+            };
+        }
         
         // Handle no arguments - list branches
         if branch_name.is_empty() {
@@ -131,7 +150,12 @@ impl BranchCommand {
             // Add padding to align commit info
             let padding = " ".repeat(max_width.saturating_sub(name.len()));
             
-            Ok(format!("{} {} {}", padding, Color::yellow(short_oid), title))
+            let tracking = match BranchMetadataManager::new(&repo.path).tracking_summary(name, &oid, &mut repo.database)? {
+                Some(summary) => format!("[{}] ", Color::blue(&summary)),
+                None => String::new(),
+            };
+            
+            Ok(format!("{} {} {}{}", padding, Color::yellow(short_oid), tracking, title))
         } else {
             Ok(String::new())
         }
@@ -141,6 +165,10 @@ impl BranchCommand {
     fn create_branch(branch_name: &str, start_point: Option<&str>, force: bool) -> Result<(), Error> {
         let mut repo = Repository::new(".")?;
         
+        if !repo.refs.is_valid_branch_name(branch_name) {
+            return Err(Error::Generic(format!("'{}' is not a valid branch name.", branch_name)));
+        }
+        
         // Determine the start point (commit OID)
         let start_oid = if let Some(revision_expr) = start_point {
             // Resolve the revision to a commit OID
@@ -182,15 +210,24 @@ impl BranchCommand {
     
     // Delete a branch
     fn delete_branch(branch_name: &str, force: bool) -> Result<(), Error> {
-        // Force is required for now since we don't have merge functionality
+        let mut repo = Repository::new(".")?;
+        
+        // Without force, only delete branches whose tip is already contained in HEAD
         if !force {
-            eprintln!("error: The branch '{}' is not fully merged.", branch_name);
-            eprintln!("If you are sure you want to delete it, run 'ash branch -D {}'", branch_name);
-            return Err(Error::Generic("Branch not deleted".to_string()));
+            let branch_oid = repo.refs.read_ref(&format!("refs/heads/{}", branch_name))?
+                .ok_or_else(|| Error::Generic(format!("branch '{}' not found.", branch_name)))?;
+            let merged = match repo.refs.read_head()? {
+                Some(head_oid) => is_ancestor(&mut repo.database, &branch_oid, &head_oid)?,
+                None => false,
+            };
+            
+            if !merged {
+                eprintln!("error: The branch '{}' is not fully merged.", branch_name);
+                eprintln!("If you are sure you want to delete it, run 'ash branch -D {}'", branch_name);
+                return Err(Error::Generic("Branch not deleted".to_string()));
+            }
         }
         
-        let mut repo = Repository::new(".")?;
-        
         // Check if we're trying to delete the current branch
         let current_ref = repo.refs.current_ref()?;
         let is_current = match &current_ref {
@@ -220,4 +257,52 @@ impl BranchCommand {
             Err(e) => Err(e),
         }
     }
-}
\ No newline at end of file
+    
+    // Make a branch, by default the current one, track an upstream
+    fn set_upstream(branch_name: Option<&str>, upstream: &str) -> Result<(), Error> {
+        let repo = Repository::new(".")?;
+        let metadata = BranchMetadataManager::new(&repo.path);
+        
+        let branch_name = match branch_name {
+            Some(name) => name.to_string(),
+            None => metadata.get_current_branch()?,
+        };
+        
+        metadata.set_upstream(&branch_name, upstream)?;
+        println!("Branch '{}' set up to track '{}'.", branch_name, upstream);
+        
+        Ok(())
+    }
+    
+    // Rename a branch, defaulting to the current one
+    fn rename_branch(old_name: Option<&str>, new_name: &str, force: bool) -> Result<(), Error> {
+        let repo = Repository::new(".")?;
+        
+        let old_name = match old_name {
+            Some(name) => name.to_string(),
+            None => match repo.refs.current_ref()? {
+                Reference::Symbolic(path) if path.starts_with("refs/heads/") => repo.refs.short_name(&path),
+                _ => return Err(Error::Generic("HEAD is detached; name the branch to rename".to_string())),
+            },
+        };
+        
+        repo.refs.rename_branch(&old_name, new_name, force)?;
+        
+        // The upstream setting follows the branch
+        let mut config = Config::load(&repo.git_path);
+        if config.rename_section(&format!("branch.{}", old_name), &format!("branch.{}", new_name)) {
+            config.save()?;
+        }
+        
+        // Sprint metadata is stored under the branch name and records it, so it is not moved
+        for meta_ref in [format!("refs/meta/{}", old_name), format!("refs/meta/sprint-{}", old_name)] {
+            if repo.refs.read_ref(&meta_ref)?.is_some() {
+                eprintln!("warning: metadata ref '{}' still refers to the old branch name", meta_ref);
+            }
+        }
+        
+        println!("Renamed branch '{}' to '{}'", old_name, new_name);
+        
+        Ok(())
+    }
+}
@@ -0,0 +1,92 @@
+use std::io::{self, Write};
+use crate::errors::error::Error;
+use crate::core::database::tree::{Tree, TreeEntry, TREE_MODE};
+use crate::core::repository::repository::Repository;
+use crate::core::revision::Revision;
+
+/// Implements the 'cat-file' plumbing command for AsheraFlow.
+/// Prints the type, size or content of a single object.
+pub struct CatFileCommand;
+
+impl CatFileCommand {
+    /// Prints the type (`show_type`), the size in bytes (`show_size`) or the pretty-printed
+    /// content (`pretty`) of `object`, which may be an object ID, a prefix or a revision.
+    pub fn execute(object: &str, show_type: bool, show_size: bool, pretty: bool) -> Result<(), Error> {
+        let mut repo = Repository::new(".")?;
+        let output = Self::output(&mut repo, object, show_type, show_size, pretty)?;
+        io::stdout().write_all(&output)?;
+
+        Ok(())
+    }
+
+    /// What `execute` prints, as bytes since blobs need not be UTF-8
+    fn output(repo: &mut Repository, object: &str, show_type: bool, show_size: bool, pretty: bool) -> Result<Vec<u8>, Error> {
+        let oid = Revision::new(repo, object).resolve_object()?;
+        let loaded = repo.database.load(&oid)?;
+
+        let output = if show_type {
+            format!("{}\n", loaded.get_type()).into_bytes()
+        } else if show_size {
+            format!("{}\n", loaded.to_bytes().len()).into_bytes()
+        } else if pretty {
+            match loaded.as_any().downcast_ref::<Tree>() {
+                Some(tree) => format_tree_entries(tree).iter()
+                    .flat_map(|line| format!("{}\n", line).into_bytes())
+                    .collect(),
+                // Commits, tags and blobs are printed as stored; blobs byte for byte
+                None => loaded.to_bytes(),
+            }
+        } else {
+            Vec::new()
+        };
+
+        Ok(output)
+    }
+}
+
+/// A tree's entries as `<mode> <type> <oid>\t<name>` lines, sorted by name
+pub(crate) fn format_tree_entries(tree: &Tree) -> Vec<String> {
+    let mut entries: Vec<(&String, &TreeEntry)> = tree.get_entries().iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+
+    entries.into_iter()
+        .map(|(name, entry)| {
+            let (mode, kind, oid) = match entry {
+                TreeEntry::Blob(oid, mode) => (*mode, "blob", oid.clone()),
+                TreeEntry::Tree(subtree) => (TREE_MODE, "tree", subtree.get_oid().cloned().unwrap_or_default()),
+            };
+            format!("{:0>6} {} {}\t{}", mode.to_octal_string(), kind, oid, name)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::database::commit::Commit;
+    use crate::test_support::TestRepo;
+
+    #[test]
+    fn prints_commits_trees_and_blobs() {
+        let repo = TestRepo::new();
+        let head = repo.commit_files(&[("f.txt", "hello\n")], "first");
+        let mut r = repo.repo();
+        let object = r.database.load(&head).unwrap();
+        let tree = object.as_any().downcast_ref::<Commit>().unwrap().get_tree().to_string();
+        let blob = repo.blob_oid("hello\n");
+
+        assert_eq!(CatFileCommand::output(&mut r, "HEAD", true, false, false).unwrap(), b"commit\n");
+        let commit = String::from_utf8(CatFileCommand::output(&mut r, "HEAD", false, false, true).unwrap()).unwrap();
+        assert!(commit.starts_with(&format!("tree {}\n", tree)), "{}", commit);
+
+        assert_eq!(CatFileCommand::output(&mut r, &tree, true, false, false).unwrap(), b"tree\n");
+        assert_eq!(
+            CatFileCommand::output(&mut r, &tree, false, false, true).unwrap(),
+            format!("100644 blob {}\tf.txt\n", blob).into_bytes()
+        );
+
+        assert_eq!(CatFileCommand::output(&mut r, &blob, true, false, false).unwrap(), b"blob\n");
+        assert_eq!(CatFileCommand::output(&mut r, &blob, false, true, false).unwrap(), b"6\n");
+        assert_eq!(CatFileCommand::output(&mut r, &blob[..7], false, false, true).unwrap(), b"hello\n");
+    }
+}
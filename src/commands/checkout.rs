@@ -1,11 +1,26 @@
+use std::collections::BTreeMap;
+use std::path::Path;
 use std::time::Instant;
 use std::io::{self, Write};
 use crate::errors::error::Error;
 use crate::core::revision::Revision;
 use crate::core::repository::repository::Repository;
 use crate::core::color::Color;
-use crate::core::refs::Reference;
+use crate::core::progress::TerminalProgress;
+use crate::core::refs::{Reference, HEAD};
 use crate::core::database::commit::Commit;
+use crate::core::diff::diff::split_lines;
+use crate::core::diff::hunks::{apply_hunks, build_hunks};
+use crate::core::diff::myers::is_binary_content;
+use crate::commands::patch_mode;
+
+/// Where `checkout -- <paths>` takes file contents from
+pub enum RestoreSource {
+    /// The staged (stage 0) entries of the index
+    Index,
+    /// The tree of the given commit; restored files are staged as well
+    Revision(String),
+}
 
 /// Implements the 'checkout' command for AsheraFlow.
 /// Handles switching branches or restoring working tree files.
@@ -15,6 +30,18 @@ impl CheckoutCommand {
     /// Executes the checkout command, switching to the specified target branch or commit.
     /// Returns an error if the target cannot be resolved or migration fails.
     pub fn execute(target: &str) -> Result<(), Error> {
+        Self::checkout(target, None)
+    }
+    
+    /// Executes `checkout -b <branch> [<start-point>]`: creates the branch at the start point
+    /// (HEAD by default) and switches to it. With `force` (`-B`) an existing branch is reset to
+    /// the start point; otherwise an existing branch is an error. The branch is only written once
+    /// the working tree has been migrated, so a failed checkout leaves no branch behind.
+    pub fn execute_new_branch(branch_name: &str, start_point: Option<&str>, force: bool) -> Result<(), Error> {
+        Self::checkout(start_point.unwrap_or(HEAD), Some((branch_name, force)))
+    }
+    
+    fn checkout(target: &str, new_branch: Option<(&str, bool)>) -> Result<(), Error> {
         let start_time = Instant::now();
         
         // Initialize repository
@@ -43,6 +70,16 @@ impl CheckoutCommand {
             }
         };
         
+        // Check the new branch before touching the working tree
+        if let Some((branch_name, force)) = new_branch {
+            if !repo.refs.is_valid_branch_name(branch_name) {
+                return Err(Error::Generic(format!("'{}' is not a valid branch name.", branch_name)));
+            }
+            if repo.refs.branch_exists(branch_name)? && !force {
+                return Err(Error::Generic(format!("A branch named '{}' already exists.", branch_name)));
+            }
+        }
+        
         // Create a tree diff between current and target commits
         let tree_diff = repo.tree_diff(current_oid.as_deref(), Some(&target_oid))?;
         
@@ -51,14 +88,46 @@ impl CheckoutCommand {
         
         // Create and apply migration
         let mut migration = repo.migration(tree_diff);
+        let progress = TerminalProgress::new("Updating files");
         
-        match migration.apply_changes() {
+        match migration.apply_changes(&progress) {
             Ok(_) => {
                 // Migration succeeded, write index updates
                 repo.index.write_updates()?;
                 
-                // Update HEAD to point to the new target or branch
-                repo.refs.set_head(target, &target_oid)?;
+                // Create or reset the new branch, which becomes the target
+                let target = match new_branch {
+                    Some((branch_name, _)) => {
+                        let branch_ref = format!("refs/heads/{}", branch_name);
+                        match repo.refs.read_ref(&branch_ref)? {
+                            Some(old_oid) => {
+                                repo.refs.update_ref(&branch_ref, &target_oid)?;
+                                repo.refs.append_reflog(&branch_ref, Some(&old_oid), &target_oid, &format!("branch: Reset to {}", target))?;
+                            },
+                            None => repo.refs.create_branch(branch_name, &target_oid)?,
+                        }
+                        branch_name
+                    },
+                    None => target,
+                };
+                
+                // Attach HEAD to the branch, or detach it at the commit
+                if repo.refs.branch_exists(target)? {
+                    repo.refs.set_head_symbolic(&format!("refs/heads/{}", target))?;
+                } else {
+                    repo.refs.update_head_detached(&target_oid)?;
+                }
+                
+                let from = match &current_ref {
+                    Reference::Symbolic(path) => repo.refs.short_name(path),
+                    Reference::Direct(oid) => oid.clone(),
+                };
+                repo.refs.append_reflog(
+                    HEAD,
+                    current_oid.as_deref(),
+                    &target_oid,
+                    &format!("checkout: moving from {} to {}", from, target)
+                )?;
                 
                 // Get the new reference for output
                 let new_ref = repo.refs.current_ref()?;
@@ -71,10 +140,17 @@ impl CheckoutCommand {
                 
                 Ok(())
             },
-            Err(_e) => {
+            Err(e) => {
                 // Migration failed
                 // Clone the errors first before releasing the lock to avoid borrow conflicts
-                let errors = migration.errors.clone();
+                let mut errors = migration.errors.clone();
+                if errors.is_empty() {
+                    // Not a conflict, e.g. a file that could not be written
+                    errors.push(match e {
+                        Error::Generic(message) => message,
+                        other => other.to_string(),
+                    });
+                }
                 
                 // Release index lock
                 repo.index.rollback()?;
@@ -91,6 +167,227 @@ impl CheckoutCommand {
         }
     }
     
+    /// Interactively discards working-tree hunks. Hunks are taken between the index, or
+    /// `revision` when given, and the working tree; only the working tree is modified.
+    /// Without `--`, the first argument is taken as a revision if it names a commit and not a file.
+    pub fn execute_patch(revision: Option<&str>, args: &[String]) -> Result<(), Error> {
+        let mut repo = Repository::new(".")?;
+        repo.index.load()?;
+        
+        let mut revision = revision.map(|rev| rev.to_string());
+        let mut pathspecs = args.to_vec();
+        if revision.is_none()
+            && !pathspecs.is_empty()
+            && !repo.workspace.root_path.join(&pathspecs[0]).exists()
+            && Revision::new(&mut repo, &pathspecs[0]).resolve("commit").is_ok()
+        {
+            revision = Some(pathspecs.remove(0));
+        }
+        
+        // Source version of each path: the given revision's tree, or the staged entry
+        let source: BTreeMap<String, String> = match &revision {
+            Some(rev) => {
+                let commit_oid = Revision::new(&mut repo, rev).resolve("commit")?;
+                let files = patch_mode::commit_files(&mut repo, &commit_oid)?;
+                files.into_iter().map(|(path, (oid, _))| (path, oid)).collect()
+            },
+            None => repo.index.each_entry()
+                .filter(|entry| entry.stage == 0)
+                .map(|entry| (entry.get_path().to_string(), entry.get_oid().to_string()))
+                .collect(),
+        };
+        
+        let mut discarded = 0;
+        for (path, oid) in source.iter().filter(|(path, _)| patch_mode::matches_pathspec(path, &pathspecs)) {
+            let file_path = Path::new(path);
+            let source_content = repo.database.load(oid)?.to_bytes();
+            let work_content = if repo.workspace.path_exists(file_path)? {
+                repo.workspace.read_file(file_path)?
+            } else {
+                Vec::new()
+            };
+            
+            if source_content == work_content {
+                continue;
+            }
+            if is_binary_content(&source_content) || is_binary_content(&work_content) {
+                println!("Skipping binary file {}", path);
+                continue;
+            }
+            
+            let a = split_lines(&String::from_utf8_lossy(&source_content));
+            let b = split_lines(&String::from_utf8_lossy(&work_content));
+            let hunks = build_hunks(&a, &b, 3);
+            if hunks.is_empty() {
+                continue;
+            }
+            
+            let selection = patch_mode::select_hunks(path, &hunks, "Discard this hunk from worktree")?;
+            let selected = selection.selected.iter().filter(|&&s| s).count();
+            
+            if selected == hunks.len() {
+                repo.workspace.write_file(file_path, &source_content)?;
+            } else if selected > 0 {
+                let kept = apply_hunks(&a, &selection.hunks(&hunks, false));
+                repo.workspace.write_file(file_path, &patch_mode::join_lines(&kept))?;
+            }
+            
+            discarded += selected;
+            if selection.quit {
+                break;
+            }
+        }
+        
+        if discarded == 0 {
+            println!("No changes discarded");
+        } else {
+            println!("Discarded {} hunk{} from the working tree", discarded, if discarded == 1 { "" } else { "s" });
+        }
+        
+        Ok(())
+    }
+    
+    /// Executes `checkout [<rev>] -- <paths>`, overwriting working-tree files with their
+    /// content in the index or in the given commit. HEAD and the current branch are left alone.
+    /// A path missing from the source is reported and skipped; the rest are still restored.
+    pub fn restore_paths(source: RestoreSource, paths: &[String]) -> Result<(), Error> {
+        let mut repo = Repository::new(".")?;
+        
+        let files: BTreeMap<String, String> = match &source {
+            RestoreSource::Index => {
+                repo.index.load()?;
+                repo.index.each_entry()
+                    .filter(|entry| entry.stage == 0)
+                    .map(|entry| (entry.get_path().to_string(), entry.get_oid().to_string()))
+                    .collect()
+            },
+            RestoreSource::Revision(rev) => {
+                let commit_oid = Revision::new(&mut repo, rev).resolve("commit")?;
+                let files = patch_mode::commit_files(&mut repo, &commit_oid)?;
+                repo.index.load_for_update()?;
+                files.into_iter().map(|(path, (oid, _))| (path, oid)).collect()
+            },
+        };
+        
+        let (restored, unmatched) = match Self::write_paths(&mut repo, &source, &files, paths) {
+            Ok(counts) => counts,
+            Err(e) => {
+                if let RestoreSource::Revision(_) = source {
+                    repo.index.rollback()?;
+                }
+                return Err(e);
+            }
+        };
+        
+        if let RestoreSource::Revision(_) = source {
+            repo.index.write_updates()?;
+        }
+        
+        println!("Updated {} path{}", restored, if restored == 1 { "" } else { "s" });
+        
+        if unmatched > 0 {
+            return Err(Error::Generic(format!(
+                "{} pathspec{} did not match", unmatched, if unmatched == 1 { "" } else { "s" }
+            )));
+        }
+        
+        Ok(())
+    }
+    
+    /// Writes the files of `files` matching each pathspec, staging them when restoring from a
+    /// revision. Returns how many files were restored and how many pathspecs matched nothing.
+    fn write_paths(
+        repo: &mut Repository,
+        source: &RestoreSource,
+        files: &BTreeMap<String, String>,
+        paths: &[String],
+    ) -> Result<(usize, usize), Error> {
+        let mut restored = 0;
+        let mut unmatched = 0;
+        for spec in paths {
+            let spec = std::slice::from_ref(spec);
+            let mut matched = false;
+            
+            for (path, oid) in files.iter().filter(|(path, _)| patch_mode::matches_pathspec(path, spec)) {
+                let file_path = Path::new(path);
+                let content = repo.workspace.denormalize_eol(path, repo.database.load(oid)?.to_bytes());
+                repo.workspace.write_file(file_path, &content)?;
+                
+                if let RestoreSource::Revision(_) = source {
+                    let stat = repo.workspace.stat_file(file_path)?;
+                    repo.index.add(file_path, oid, &stat)?;
+                }
+                
+                matched = true;
+                restored += 1;
+            }
+            
+            if !matched {
+                eprintln!("error: pathspec '{}' did not match any file(s) known to ash", spec[0]);
+                unmatched += 1;
+            }
+        }
+        
+        Ok((restored, unmatched))
+    }
+    
+    /// Executes `checkout --orphan <name>`, starting a new branch with no history.
+    /// Tracked files are removed from the working tree and the index is emptied, so the
+    /// next commit on the branch is a root commit.
+    pub fn execute_orphan(branch_name: &str) -> Result<(), Error> {
+        let start_time = Instant::now();
+        
+        let mut repo = Repository::new(".")?;
+        
+        if repo.refs.read_ref(branch_name)?.is_some() {
+            return Err(Error::Generic(format!("A branch named '{}' already exists.", branch_name)));
+        }
+        
+        let current_oid = repo.refs.read_head()?;
+        
+        // Migrate from the current commit to an empty tree
+        let tree_diff = repo.tree_diff(current_oid.as_deref(), None)?;
+        
+        repo.index.load_for_update()?;
+        
+        let mut migration = repo.migration(tree_diff);
+        let progress = TerminalProgress::new("Updating files");
+        
+        if let Err(e) = migration.apply_changes(&progress) {
+            let mut errors = migration.errors.clone();
+            if errors.is_empty() {
+                errors.push(match e {
+                    Error::Generic(message) => message,
+                    other => other.to_string(),
+                });
+            }
+            
+            repo.index.rollback()?;
+            
+            for message in errors {
+                eprintln!("error: {}", message);
+            }
+            
+            eprintln!("Aborting");
+            
+            return Err(Error::Generic("Checkout failed due to conflicts".to_string()));
+        }
+        
+        // Drop anything still staged so the new branch starts from an empty index
+        repo.index.clear();
+        repo.index.set_changed(true);
+        repo.index.write_updates()?;
+        
+        repo.refs.set_head_to_unborn_branch(branch_name)?;
+        
+        eprintln!("Switched to a new branch '{}'", branch_name);
+        
+        let elapsed = start_time.elapsed();
+        println!("Checkout completed in {:.2}s", elapsed.as_secs_f32());
+        
+        Ok(())
+    }
+    
     // Print checkout status based on previous and current state
     fn print_checkout_status(
         repo: &Repository,
@@ -178,4 +475,41 @@ impl CheckoutCommand {
         
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::refs::Reference;
+    use crate::test_support::TestRepo;
+    
+    #[test]
+    fn commit_on_a_detached_head_moves_no_branch() {
+        let repo = TestRepo::new();
+        let first = repo.commit_files(&[("f.txt", "one\n")], "first");
+        let master = repo.commit_files(&[("f.txt", "two\n")], "second");
+        
+        CheckoutCommand::execute(&first).unwrap();
+        let detached = repo.commit_files(&[("g.txt", "new\n")], "detached");
+        
+        let r = repo.repo();
+        assert!(matches!(r.refs.current_ref().unwrap(), Reference::Direct(oid) if oid == detached));
+        assert_eq!(r.refs.read_ref("master").unwrap().as_deref(), Some(master.as_str()));
+        for branch in r.refs.list_branches().unwrap() {
+            if let Reference::Symbolic(path) = branch {
+                assert_ne!(r.refs.read_ref(&path).unwrap().as_deref(), Some(detached.as_str()), "{} moved", path);
+            }
+        }
+    }
+    
+    #[test]
+    fn failed_restore_from_a_revision_releases_the_index_lock() {
+        let repo = TestRepo::new();
+        repo.commit_files(&[("d/f.txt", "one\n")], "first");
+        std::fs::remove_dir_all(repo.path().join("d")).unwrap();
+        repo.write("d", "a file in the way\n");
+        
+        assert!(CheckoutCommand::restore_paths(RestoreSource::Revision("HEAD".to_string()), &["d/f.txt".to_string()]).is_err());
+        assert!(!repo.exists(".ash/index.lock"));
+    }
+}
@@ -1,14 +1,14 @@
-use std::env;
 use std::path::{Path, PathBuf};
 use std::time::Instant;
 use regex::Regex;
 
-use crate::core::database::database::Database;
-use crate::core::index::index::Index;
-use crate::core::refs::Refs;
+use crate::core::database::author::Author;
+use crate::core::config::Config;
+use crate::core::editor::Editor;
 use crate::core::repository::pending_commit::{PendingCommit, PendingCommitType};
 use crate::commands::commit_writer::CommitWriter;
 use crate::errors::error::Error;
+use crate::core::repository::repository::Repository;
 use crate::core::commit_metadata::{CommitMetadataManager, TaskMetadata, TaskStatus};
 
 /// Implements the 'commit' command for AsheraFlow.
@@ -17,10 +17,16 @@ pub struct CommitCommand;
 
 impl CommitCommand {
     /// Executes the commit command, creating or amending a commit with the provided message and options.
+    /// `author` (`Name <email>`) and `date` override the author identity and timestamp;
+    /// the committer is always the current user at the current time.
+    /// Non-empty `paths` commit only the staged changes under those paths.
     /// Returns an error if repository is not initialized or if no changes are staged.
-    pub fn execute(message: &str, amend: bool, reuse_message: Option<&str>, edit: bool) -> Result<(), Error> {
+    pub fn execute(message: &str, amend: bool, reuse_message: Option<&str>, edit: bool, author: Option<&str>, date: Option<&str>, paths: &[String]) -> Result<(), Error> {
         let start_time = Instant::now();
         
+        let author = author.map(Author::parse_ident).transpose()?;
+        let date = date.map(Author::parse_date).transpose()?;
+        
         // Initialize repository components
         let root_path = Path::new(".");
         let git_path = root_path.join(".ash");
@@ -30,9 +36,6 @@ impl CommitCommand {
             return Err(Error::Generic("Not an ash repository: .ash directory not found".into()));
         }
         
-        let db_path = git_path.join("objects");
-        let mut database = Database::new(db_path);
-        
         // Check for the index file
         let index_path = git_path.join("index");
         if !index_path.exists() {
@@ -45,21 +48,20 @@ impl CommitCommand {
             return Err(Error::Generic("Another git process seems to be running in this repository.".into()));
         }
         
-        let mut index = Index::new(index_path);
+        let mut repo = Repository::new(".")?;
         
         // Load the index
-        match index.load() {
+        match repo.load_index() {
             Ok(_) => {},
             Err(e) => return Err(Error::Generic(format!("Error loading index: {}", e))),
         }
+        let Repository { mut database, mut index, refs, .. } = repo;
         
         // Check if the index is empty
         if index.entries.is_empty() {
             return Err(Error::Generic("No changes staged for commit. Use 'ash add' to add files.".into()));
         }
         
-        let refs = Refs::new(&git_path);
-        
         // Check if we're in a task branch
         let current_branch = match refs.current_ref() {
             Ok(reference) => match reference {
@@ -99,6 +101,17 @@ impl CommitCommand {
             &refs
         );
         
+        // Only stage-0 entries can go into a tree, so unmerged paths must be resolved first
+        commit_writer.handle_conflicted_index()?;
+        
+        if !paths.is_empty() {
+            let pending = [PendingCommitType::Merge, PendingCommitType::CherryPick, PendingCommitType::Revert];
+            if pending.into_iter().any(|r#type| commit_writer.pending_commit.in_progress(r#type)) {
+                return Err(Error::Generic("cannot do a partial commit during a merge, cherry-pick or revert".to_string()));
+            }
+            commit_writer.limit_to_paths(paths)?;
+        }
+        
         // Check if there is a pending merge or other operation
         if commit_writer.pending_commit.in_progress(PendingCommitType::Merge) {
             return commit_writer.resume_merge(PendingCommitType::Merge, get_editor_command());
@@ -110,7 +123,8 @@ impl CommitCommand {
         
         // If amending, use the amend function
         if amend {
-            return commit_writer.handle_amend(get_editor_command());
+            let message = if message.is_empty() { None } else { Some(message) };
+            return commit_writer.handle_amend(message, reuse_message, edit, author, date, get_editor_command());
         }
         
         // Get the message
@@ -169,8 +183,19 @@ impl CommitCommand {
                 }
             };
             
+            // Author overrides apply on top of the current identity
+            let author = if author.is_some() || date.is_some() {
+                let mut author = author.unwrap_or_else(|| commit_writer.current_author());
+                if let Some(date) = date {
+                    author.timestamp = date;
+                }
+                Some(author)
+            } else {
+                None
+            };
+            
             // Create and write the commit
-            let commit = commit_writer.write_commit(parent, &message_text, None)?;
+            let commit = commit_writer.write_commit(parent, &message_text, author)?;
             
             // Print commit information
             commit_writer.print_commit(&commit)?;
@@ -222,9 +247,88 @@ impl CommitCommand {
     }
 }
 
+/// The editor for commit messages; see `Editor::resolve_command` for the lookup order
 pub fn get_editor_command() -> Option<String> {
-    env::var("GIT_EDITOR")
-        .or_else(|_| env::var("VISUAL"))
-        .or_else(|_| env::var("EDITOR"))
-        .ok()
+    Some(Editor::resolve_command(&Config::current()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::database::entry::DatabaseEntry;
+    use crate::test_support::TestRepo;
+
+    // Sets core.editor to a shell script that gets the message file as `$1`
+    #[cfg(unix)]
+    fn use_editor(repo: &TestRepo, script: &str) {
+        let path = repo.path().join(".ash").join("editor.sh");
+        std::fs::write(&path, script).unwrap();
+        let mut config = Config::load(&repo.path().join(".ash"));
+        config.set("core", "editor", &format!("sh {}", path.display()));
+        config.save().unwrap();
+    }
+
+    #[test]
+    fn commit_refuses_unmerged_paths() {
+        let repo = TestRepo::new();
+        let head = repo.commit_files(&[("clean.txt", "clean\n"), ("conflict.txt", "base\n")], "base");
+
+        let mut r = repo.repo();
+        r.index.load_for_update().unwrap();
+        let oid = r.index.entry_for_path("conflict.txt", 0).unwrap().get_oid().to_string();
+        let side = Some(DatabaseEntry::new("conflict.txt".to_string(), oid, "100644"));
+        r.index.add_conflict(Path::new("conflict.txt"), vec![side.clone(), side.clone(), side]);
+        r.index.write_updates().unwrap();
+
+        let err = CommitCommand::execute("resolve", false, None, false, None, None, &[]).unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("unmerged files"), "{}", message);
+        assert!(message.contains("\tconflict.txt\n"), "{}", message);
+        assert!(!message.contains("clean.txt"), "{}", message);
+        assert_eq!(repo.head(), head);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn failing_editor_aborts_the_commit() {
+        let repo = TestRepo::new();
+        let head = repo.commit_files(&[("f.txt", "one\n")], "first");
+        use_editor(&repo, "printf 'Message\\n' >> \"$1\"\nexit 1\n");
+
+        repo.write("f.txt", "two\n");
+        repo.add(&["f.txt"]);
+        let err = CommitCommand::execute("", false, None, false, None, None, &[]).unwrap_err();
+        assert!(err.to_string().contains("Aborting commit"), "{}", err);
+        assert_eq!(repo.head(), head);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn comment_lines_are_stripped_from_the_stored_message() {
+        let repo = TestRepo::new();
+        repo.commit_files(&[("f.txt", "one\n")], "first");
+        repo.write("template.txt", "Template subject\n# template comment\n");
+        let mut config = Config::load(&repo.path().join(".ash"));
+        config.set("commit", "template", "template.txt");
+        config.save().unwrap();
+        // Keep a copy of the buffer as the editor saw it, then add a body and a comment
+        use_editor(&repo, "cp \"$1\" \"$1.seen\"\nprintf 'Body line\\n# my comment\\n' >> \"$1\"\n");
+
+        repo.write("f.txt", "two\n");
+        repo.add(&["f.txt"]);
+        CommitCommand::execute("", false, None, false, None, None, &[]).unwrap();
+
+        let seen = repo.read(".ash/COMMIT_EDITMSG.seen");
+        assert!(seen.starts_with("Template subject\n"), "{}", seen);
+        assert!(seen.contains("# On branch master"), "{}", seen);
+        assert!(seen.contains("f.txt"), "{}", seen);
+
+        let head = repo.head();
+        let mut r = repo.repo();
+        let commit_obj = r.database.load(&head).unwrap();
+        let message = commit_obj.as_any().downcast_ref::<crate::core::database::commit::Commit>().unwrap().get_message().to_string();
+        assert!(message.starts_with("Template subject\n"), "{}", message);
+        assert!(message.contains("Body line"), "{}", message);
+        assert!(message.lines().all(|line| !line.starts_with('#')), "{}", message);
+    }
 }
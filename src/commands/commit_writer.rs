@@ -1,18 +1,21 @@
+use std::collections::BTreeMap;
 use std::fs::read_to_string;
 use std::path::{Path, PathBuf};
 
-use chrono::Local;
+use chrono::{DateTime, Utc};
 
 use crate::core::database::author::Author;
 use crate::core::database::commit::Commit;
 use crate::core::database::database::{Database, GitObject};
 use crate::core::database::entry::DatabaseEntry;
-use crate::core::database::tree::Tree;
+use crate::core::database::tree::{build_tree_from_index, index_tree_entries, store_tree, Tree, TreeEntry};
 use crate::core::index::index::Index;
-use crate::core::refs::Refs;
+use crate::core::refs::{Reference, Refs};
 use crate::core::editor::Editor;
+use crate::core::config::Config;
 use crate::core::repository::pending_commit::{PendingCommit, PendingCommitType};
 use crate::errors::error::Error;
+use crate::commands::patch_mode::matches_pathspec;
 
  /// Message displayed when prompting for a commit message.
  pub const COMMIT_NOTES: &str = "Please enter the commit message for your changes. Lines starting with
@@ -32,8 +35,7 @@ use crate::errors::error::Error;
 
  /// Message displayed when unresolved conflicts are detected during commit operations.
  pub const CONFLICT_MESSAGE: &str = "hint: Fix them up in the work tree, and then use 'ash add <file>'
- hint: as appropriate to mark resolution and make a commit.
- fatal: Exiting because of an unresolved conflict.";
+ hint: as appropriate to mark resolution and make a commit.";
 
  /// Handles writing commits, including merge and cherry-pick operations.
  pub struct CommitWriter<'a> {
@@ -43,6 +45,8 @@ use crate::errors::error::Error;
     pub index: &'a mut Index,
     pub refs: &'a Refs,
     pub pending_commit: PendingCommit,
+    /// When non-empty, commits only the staged changes under these paths
+    pathspecs: Vec<String>,
 }
 
 impl<'a> CommitWriter<'a> {
@@ -63,6 +67,7 @@ impl<'a> CommitWriter<'a> {
             index,
             refs,
             pending_commit,
+            pathspecs: Vec::new(),
         }
     }
 
@@ -92,12 +97,10 @@ impl<'a> CommitWriter<'a> {
         // Use current author as committer 
         let committer = self.current_author();
         
-        // Get the first parent or None
-        let parent = parents.first().cloned();
-        
+        let initial = parents.is_empty();
         let mut commit = Commit::new_with_committer(
-            parent,
-            tree.get_oid().map(|s| s.to_string()).unwrap_or_default(),
+            parents,
+            tree,
             author,
             committer,
             message.to_string()
@@ -107,60 +110,109 @@ impl<'a> CommitWriter<'a> {
         
         // Get the commit OID, making sure we handle the option correctly
         let oid = commit.get_oid().map(|s| s.to_string()).unwrap_or_default();
-        self.refs.update_head(&oid)?;
+        let reflog_message = if initial {
+            format!("commit (initial): {}", commit.title_line())
+        } else {
+            format!("commit: {}", commit.title_line())
+        };
+        self.refs.update_head(&oid, &reflog_message)?;
 
         Ok(commit)
     }
 
-    pub fn write_tree(&mut self) -> Result<Tree, Error> {
-        // Create a collection of DatabaseEntry from index entries
-        let entries: Vec<DatabaseEntry> = self.index.entries.values()
-            .map(|entry| DatabaseEntry::new(
-                entry.get_path().to_string(),
-                entry.get_oid().to_string(),
-                &entry.mode_octal()
-            ))
-            .collect();
+    /// Limits the trees written from now on to HEAD's tree with only the staged changes under
+    /// `pathspecs` applied, staged deletions included. Other staged changes stay in the index.
+    pub fn limit_to_paths(&mut self, pathspecs: &[String]) -> Result<(), Error> {
+        let head = self.head_entries()?;
+        
+        for spec in pathspecs {
+            let spec = std::slice::from_ref(spec);
+            let staged = self.index.each_entry()
+                .any(|entry| entry.stage == 0 && matches_pathspec(entry.get_path(), spec));
+            let deleted = head.keys()
+                .any(|path| matches_pathspec(path, spec) && !self.index.tracked(path));
+            
+            if !staged && !deleted {
+                return Err(Error::Generic(format!("pathspec '{}' did not match any staged files", spec[0])));
+            }
+        }
+        
+        self.pathspecs = pathspecs.to_vec();
+        Ok(())
+    }
+    
+    /// Writes the tree of the index's stage-0 entries, returning its ID; fails while any path
+    /// is unmerged, since such a tree would silently drop the conflicted files
+    pub fn write_tree(&mut self) -> Result<String, Error> {
+        if self.index.has_conflict() {
+            return Err(Error::Generic("cannot write a tree with unmerged paths; resolve the conflicts first".to_string()));
+        }
         
-        let mut root = Tree::build(entries.iter())?;
+        if self.pathspecs.is_empty() {
+            return build_tree_from_index(self.database, self.index);
+        }
         
-        // Store all tree objects in the database
-        root.traverse(|tree| {
-            self.database.store(tree)?;
-            Ok(())
-        })?;
+        // For a path-limited commit, start from HEAD's files outside the selected paths
+        let mut head_entries = self.head_entries()?;
+        let pathspecs = &self.pathspecs;
+        head_entries.retain(|path, _| !matches_pathspec(path, pathspecs));
+        
+        let index_entries = index_tree_entries(self.index).into_iter()
+            .filter(|entry| matches_pathspec(entry.get_name(), pathspecs));
         
-        Ok(root)
+        let entries: Vec<DatabaseEntry> = head_entries.into_values().chain(index_entries).collect();
+        store_tree(self.database, &entries)
     }
 
-    pub fn current_author(&self) -> Author {
-        // Try to get author name from environment variables
-        let name = std::env::var("GIT_AUTHOR_NAME")
-            .or_else(|_| std::env::var("USER"))
-            .unwrap_or_else(|_| "Unknown".to_string());
-            
-        // Try to get author email from environment variables
-        let email = std::env::var("GIT_AUTHOR_EMAIL")
-            .unwrap_or_else(|_| format!("{}@localhost", name));
-            
-        // Use current time
-        Author {
-            name,
-            email,
-            timestamp: Local::now().into(),
+    /// Files of HEAD's tree by path; empty before the first commit
+    fn head_entries(&mut self) -> Result<BTreeMap<String, DatabaseEntry>, Error> {
+        let mut entries = BTreeMap::new();
+        
+        if let Some(head_oid) = self.refs.read_head()? {
+            let head = self.database.load(&head_oid)?;
+            if let Some(commit) = head.as_any().downcast_ref::<Commit>() {
+                let tree_oid = commit.get_tree().to_string();
+                self.collect_tree_entries(&tree_oid, "", &mut entries)?;
+            }
+        }
+        
+        Ok(entries)
+    }
+    
+    fn collect_tree_entries(&mut self, tree_oid: &str, prefix: &str, entries: &mut BTreeMap<String, DatabaseEntry>) -> Result<(), Error> {
+        let object = self.database.load(tree_oid)?;
+        let tree = match object.as_any().downcast_ref::<Tree>() {
+            Some(tree) => tree,
+            None => return Ok(()),
+        };
+        
+        for (name, entry) in tree.get_entries() {
+            let path = if prefix.is_empty() { name.to_string() } else { format!("{}/{}", prefix, name) };
+            match entry {
+                TreeEntry::Blob(oid, mode) => {
+                    entries.insert(path.clone(), DatabaseEntry::new(path, oid.clone(), &mode.to_octal_string()));
+                },
+                TreeEntry::Tree(subtree) => {
+                    if let Some(subtree_oid) = subtree.get_oid() {
+                        self.collect_tree_entries(subtree_oid, &path, entries)?;
+                    }
+                }
+            }
         }
+        
+        Ok(())
+    }
+    
+    pub fn current_author(&self) -> Author {
+        // Prefer user.name/user.email from the config, then the environment
+        Config::load(&self.git_path).identity(&["GIT_AUTHOR"])
     }
 
     pub fn print_commit(&self, commit: &Commit) -> Result<(), Error> {
-        // Get current branch name or HEAD
-        let reference = self.refs.read_ref("HEAD")?.unwrap_or_default();
-        let info = if reference.is_empty() {
-            String::from("detached HEAD")
-        } else {
-            // Try to extract branch name from ref
-            let branch_name = reference.strip_prefix("refs/heads/")
-                .unwrap_or(&reference);
-            branch_name.to_string()
+        // Get current branch name, or note that HEAD is detached
+        let info = match self.refs.current_ref()? {
+            Reference::Symbolic(path) => self.refs.short_name(&path),
+            Reference::Direct(_) => String::from("detached HEAD"),
         };
         
         // Get short OID
@@ -189,15 +241,87 @@ impl<'a> CommitWriter<'a> {
         Ok(())
     }
 
+    /// Opens the commit message in the editor. Without an initial message the buffer is
+    /// seeded from `commit.template`; comment lines describe the branch and staged files
+    /// and are stripped afterwards. A failing editor gives no message, so the commit is
+    /// aborted like one left empty
     pub fn compose_message(&mut self, editor_cmd: Option<String>, initial_message: Option<&str>) -> Result<Option<String>, Error> {
-        self.edit_file(self.commit_message_path(), editor_cmd, |editor| {
+        let template = match initial_message {
+            Some(_) => None,
+            None => self.template_message()?,
+        };
+        let initial_message = initial_message.or(template.as_deref());
+        let notes = format!("{}\n\n{}", COMMIT_NOTES, self.commit_status_notes()?);
+        
+        let edited = self.edit_file(self.commit_message_path(), editor_cmd, |editor| {
             if let Some(msg) = initial_message {
-                editor.write(msg)?;
+                editor.write(msg.trim_end())?;
             }
             editor.write("")?;
-            editor.note(COMMIT_NOTES)?;
+            editor.note(&notes)?;
             Ok(())
-        })
+        });
+        
+        match edited {
+            Ok(message) => Ok(message),
+            Err(e) => {
+                eprintln!("error: {}", e);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Contents of the file named by `commit.template`, relative to the repository root
+    fn template_message(&self) -> Result<Option<String>, Error> {
+        let config = Config::load(&self.git_path);
+        let path = match config.get("commit", "template") {
+            Some(path) => self.root_path.join(path),
+            None => return Ok(None),
+        };
+        
+        read_to_string(&path)
+            .map(Some)
+            .map_err(|e| Error::Generic(format!("could not read commit template '{}': {}", path.display(), e)))
+    }
+
+    /// Describes the branch and the changes the commit will record, for the editor buffer
+    fn commit_status_notes(&mut self) -> Result<String, Error> {
+        let mut notes = match self.refs.current_ref()? {
+            Reference::Symbolic(path) => format!("On branch {}\n", self.refs.short_name(&path)),
+            Reference::Direct(_) => String::from("HEAD detached\n"),
+        };
+        
+        let pathspecs = self.pathspecs.clone();
+        let head = self.head_entries()?;
+        let staged: BTreeMap<String, (String, String)> = self.index.each_entry()
+            .filter(|entry| entry.stage == 0)
+            .map(|entry| (entry.get_path().to_string(), (entry.get_oid().to_string(), entry.mode_octal())))
+            .collect();
+        
+        let mut changes = Vec::new();
+        for (path, (oid, mode)) in &staged {
+            match head.get(path) {
+                None => changes.push(("new file", path)),
+                Some(entry) if entry.get_oid() != oid.as_str() || entry.get_mode() != mode.as_str() => changes.push(("modified", path)),
+                Some(_) => {}
+            }
+        }
+        for path in head.keys().filter(|path| !staged.contains_key(*path)) {
+            changes.push(("deleted", path));
+        }
+        changes.retain(|(_, path)| matches_pathspec(path, &pathspecs));
+        changes.sort_by(|a, b| a.1.cmp(b.1));
+        
+        if changes.is_empty() {
+            notes.push_str("\nNo changes");
+        } else {
+            notes.push_str("\nChanges to be committed:");
+            for (label, path) in changes {
+                notes.push_str(&format!("\n\t{:<12}{}", format!("{}:", label), path));
+            }
+        }
+        
+        Ok(notes)
     }
 
     pub fn compose_merge_message(&mut self, editor_cmd: Option<String>, initial_message: &str, notes: Option<&str>) -> Result<Option<String>, Error> {
@@ -225,45 +349,94 @@ impl<'a> CommitWriter<'a> {
         self.git_path.join("COMMIT_EDITMSG")
     }
 
+    /// Refuses to commit while the index has conflict stages, listing the unmerged paths
     pub fn handle_conflicted_index(&self) -> Result<(), Error> {
         if !self.index.has_conflict() {
             return Ok(());
         }
 
-        println!("error: Committing is not possible because you have unmerged files.");
-        println!("{}", CONFLICT_MESSAGE);
+        let mut paths = self.index.conflict_paths();
+        paths.sort();
+
+        let mut message = String::from("committing is not possible because you have unmerged files:\n");
+        for path in paths {
+            message.push_str(&format!("\t{}\n", path));
+        }
+        message.push_str(CONFLICT_MESSAGE);
 
-        Err(Error::Generic("Unresolved conflicts exist in the index".to_string()))
+        Err(Error::Generic(message))
     }
     
     // New methods for amending commits and handling merger operations
     
-    pub fn handle_amend(&mut self, editor_cmd: Option<String>) -> Result<(), Error> {
+    /// Replaces HEAD with a commit of the current index on HEAD's parent (none for a root commit).
+    /// The message is `message` if given, else the message of `reuse_message`, else HEAD's own;
+    /// the editor is only opened when `edit` is set. `author` and `date` override HEAD's author.
+    pub fn handle_amend(
+        &mut self,
+        message: Option<&str>,
+        reuse_message: Option<&str>,
+        edit: bool,
+        author: Option<Author>,
+        date: Option<DateTime<Utc>>,
+        editor_cmd: Option<String>,
+    ) -> Result<(), Error> {
         let head_oid = self.refs.read_head()?
             .ok_or_else(|| Error::Generic("No commit to amend".to_string()))?;
+        
+        // Amending only moves HEAD, so other branches at the same commit keep the old one
+        let shared_with = self.other_branches_at(&head_oid)?;
+        if !shared_with.is_empty() {
+            eprintln!(
+                "warning: the commit being amended is also the tip of {} '{}'; {} will keep pointing at the old commit",
+                if shared_with.len() == 1 { "branch" } else { "branches" },
+                shared_with.join("', '"),
+                if shared_with.len() == 1 { "it" } else { "they" }
+            );
+        }
             
         let old_commit_obj = self.database.load(&head_oid)?;
         let old_commit = old_commit_obj.as_any().downcast_ref::<Commit>()
             .ok_or_else(|| Error::Generic("Invalid commit object".to_string()))?;
             
         let tree = self.write_tree()?;
-        let message = self.compose_message(editor_cmd, Some(old_commit.get_message()))?
+        
+        let initial_message = match (message, reuse_message) {
+            (Some(message), _) => message.to_string(),
+            (None, Some(rev)) => self.reused_message(rev)?
+                .ok_or_else(|| Error::Generic(format!("Could not get message for revision: {}", rev)))?,
+            (None, None) => old_commit.get_message().to_string(),
+        };
+        
+        let message = if edit {
+            self.compose_message(editor_cmd, Some(&initial_message))?
+        } else {
+            Some(initial_message)
+        };
+        let message = message
+            .filter(|message| !message.trim().is_empty())
             .ok_or_else(|| Error::Generic("Aborting commit due to empty commit message".to_string()))?;
             
-        // Get the author from the old commit
-        let author = old_commit.get_author()
-            .ok_or_else(|| Error::Generic("No author in commit".to_string()))?
-            .clone();
+        // Get the author from the old commit, unless overridden
+        let mut author = match author {
+            Some(author) => author,
+            None => old_commit.get_author()
+                .ok_or_else(|| Error::Generic("No author in commit".to_string()))?
+                .clone(),
+        };
+        if let Some(date) = date {
+            author.timestamp = date;
+        }
             
         // Use current author as committer
         let committer = self.current_author();
         
         // Create new commit with the same parent(s) as the old commit
-        let parent = old_commit.get_parent().cloned();
+        let parents = old_commit.get_parents().to_vec();
         
         let mut new_commit = Commit::new_with_committer(
-            parent,
-            tree.get_oid().map(|s| s.to_string()).unwrap_or_default(),
+            parents,
+            tree,
             author,
             committer,
             message
@@ -275,13 +448,32 @@ impl<'a> CommitWriter<'a> {
         let new_oid = new_commit.get_oid()
             .ok_or_else(|| Error::Generic("New commit has no OID".to_string()))?;
             
-        self.refs.update_head(new_oid)?;
+        self.refs.update_head(new_oid, &format!("commit (amend): {}", new_commit.title_line()))?;
         
         self.print_commit(&new_commit)?;
         
         Ok(())
     }
     
+    /// Branches other than the current one whose tip is `oid`
+    fn other_branches_at(&self, oid: &str) -> Result<Vec<String>, Error> {
+        let current = match self.refs.current_ref()? {
+            Reference::Symbolic(path) => Some(path),
+            Reference::Direct(_) => None,
+        };
+        
+        let mut branches = Vec::new();
+        for branch in self.refs.list_branches()? {
+            if let Reference::Symbolic(path) = branch {
+                if Some(&path) != current.as_ref() && self.refs.read_ref(&path)?.as_deref() == Some(oid) {
+                    branches.push(self.refs.short_name(&path));
+                }
+            }
+        }
+        
+        Ok(branches)
+    }
+    
     pub fn reused_message(&mut self, revision: &str) -> Result<Option<String>, Error> {
         // TODO: Implement revision parsing to get the commit
         // For now, just try to use the OID directly
@@ -372,11 +564,6 @@ impl<'a> CommitWriter<'a> {
     }
 
     pub fn get_editor_command(&self) -> String {
-        let editor = std::env::var("GIT_EDITOR")
-            .or_else(|_| std::env::var("VISUAL"))
-            .or_else(|_| std::env::var("EDITOR"))
-            .unwrap_or_else(|_| "vi".to_string());
-        
-        editor
+        Editor::resolve_command(&Config::load(&self.git_path))
     }
 } 
\ No newline at end of file
@@ -0,0 +1,35 @@
+use std::path::Path;
+use crate::errors::error::Error;
+use crate::core::config::Config;
+
+/// Implements the 'config' command for AsheraFlow.
+/// Reads or writes a `section.key` setting in `.ash/config`.
+pub struct ConfigCommand;
+
+impl ConfigCommand {
+    /// Prints the value of `name`, or sets it to `value` when one is given.
+    /// Reading a setting that is not present is an error.
+    pub fn execute(name: &str, value: Option<&str>) -> Result<(), Error> {
+        let git_path = Path::new(".ash");
+        if !git_path.exists() {
+            return Err(Error::Generic("Not an ash repository (or any of the parent directories): .ash directory not found".into()));
+        }
+
+        let (section, key) = Config::split_name(name)?;
+        let mut config = Config::load(git_path);
+
+        match value {
+            Some(value) => {
+                config.set(&section, &key, value);
+                config.save()
+            },
+            None => match config.get(&section, &key) {
+                Some(value) => {
+                    println!("{}", value);
+                    Ok(())
+                },
+                None => Err(Error::Generic(format!("key '{}' is not set", name))),
+            },
+        }
+    }
+}
@@ -1,27 +1,66 @@
 /// Implements the 'diff' command for AsheraFlow.
 /// Handles showing changes between commits, working tree, and index, with pager support.
 use std::path::{Path, PathBuf};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::time::Instant;
 use crate::core::color::Color;
+use crate::core::config::Config;
 use crate::core::database::database::Database;
 use crate::core::database::tree::{Tree, TreeEntry, TREE_MODE};
 use crate::core::index::index::Index;
 use crate::core::database::commit::Commit;
+use crate::core::file_mode::FileMode;
+use crate::core::path_filter::PathFilter;
 use crate::core::refs::Refs;
-use crate::core::workspace::Workspace;
+use crate::core::repository::repository::Repository;
+use crate::core::revision::{Revision, COMMIT};
+use crate::core::workspace::{path_key, Workspace};
 use crate::core::diff::diff;
-use crate::core::diff::myers::{diff_lines, format_diff, is_binary_content};
+use crate::core::diff::patch::content_lines;
+use crate::core::diff::rename::{self, Rename, RenameCandidate};
+use crate::core::diff::myers::{diff_lines, format_diff, split_words, Edit};
 use crate::errors::error::Error;
 use crate::core::pager::Pager;
 
 /// Main struct for the diff command logic.
 pub struct DiffCommand;
 
+/// A changed path with its old and new contents
+type ChangedContents = (String, Vec<u8>, Vec<u8>);
+
+/// One changed file in `--git` output; a missing side is a created or deleted file
+struct PatchFile {
+    path: String,
+    old: Option<(String, FileMode)>,
+    new: Option<(String, FileMode)>,
+    old_content: Vec<u8>,
+    new_content: Vec<u8>,
+    /// The path the file was renamed from, with the percentage of content it kept
+    renamed_from: Option<(String, usize)>,
+}
+
+/// How a revision diff is shown
+struct DiffOptions {
+    /// Compare a single commit against the index rather than the working tree
+    cached: bool,
+    stat: bool,
+    word_diff: bool,
+    /// Lines of context around each change
+    context: usize,
+    git_format: bool,
+    /// Pair deleted files with the added files they were renamed to
+    find_renames: bool,
+}
+
 impl DiffCommand {
     /// Executes the diff command between index/HEAD and working tree.
-    /// Supports path filtering and cached mode.
-    pub fn execute(paths: &[String], cached: bool) -> Result<(), Error> {
+    /// Supports path filtering, cached mode, a `--stat` summary, `--word-diff` output and
+    /// `context` lines around each change (`-U<n>`). With `git`, or by default when the output
+    /// is piped, a plain patch that `git apply` accepts is written instead; `find_renames`
+    /// (`-M`) shows renamed files as renames rather than a deletion and an addition.
+    /// Leading arguments that name commits are compared instead: one commit against the
+    /// working tree (the index when cached), two commits against each other.
+    pub fn execute(paths: &[String], cached: bool, stat: bool, word_diff: bool, context: usize, git: bool, find_renames: bool) -> Result<(), Error> {
         let start_time = Instant::now();
         
         let root_path = Path::new(".");
@@ -32,14 +71,18 @@ impl DiffCommand {
             return Err(Error::Generic("fatal: not an ash repository (or any of the parent directories): .ash directory not found".into()));
         }
         
-        let workspace = Workspace::new(root_path);
-        let mut database = Database::new(git_path.join("objects"));
-        let mut index = Index::new(git_path.join("index"));
+        let (revisions, paths) = Self::split_revisions(paths)?;
+        let paths = paths.as_slice();
+        if revisions.len() == 2 && cached {
+            return Err(Error::Generic("--cached takes at most one commit".into()));
+        }
+        
+        let mut repo = Repository::new(".")?;
         
         // Load the index first
-        index.load()?;
+        repo.load_index()?;
         
-        let refs = Refs::new(&git_path);
+        let Repository { workspace, mut database, index, refs, .. } = repo;
         
         // Initialize the pager
         let mut pager = Pager::new();
@@ -47,10 +90,20 @@ impl DiffCommand {
         // Start the pager - this creates the pager process
         pager.start()?;
         
+        let git_format = git || (!word_diff && !atty::is(atty::Stream::Stdout));
+        
         // Execute diff commands
-        let result = if paths.is_empty() {
+        let result = if !revisions.is_empty() {
+            let options = DiffOptions { cached, stat, word_diff, context, git_format, find_renames };
+            Self::diff_revisions(&workspace, &mut database, &index, &revisions, paths, &options, &mut pager)
+        } else if stat {
+            Self::collect_changed_contents(&workspace, &mut database, &index, &refs, cached)
+                .and_then(|contents| Self::diff_stat(&workspace, contents, paths, &mut pager))
+        } else if git_format {
+            Self::diff_git(&workspace, &mut database, &index, &refs, paths, cached, context, find_renames, &mut pager)
+        } else if paths.is_empty() {
             // Treat the entire repository
-            Self::diff_all(&workspace, &mut database, &index, &refs, cached, &mut pager)
+            Self::diff_all(&workspace, &mut database, &index, &refs, cached, word_diff, context, find_renames, &mut pager)
         } else {
             // Process specific paths
             let mut overall_result = Ok(());
@@ -62,7 +115,7 @@ impl DiffCommand {
                 }
                 
                 let path = PathBuf::from(path_str);
-                if let Err(e) = Self::diff_path(&workspace, &mut database, &index, &refs, &path, cached, &mut pager) {
+                if let Err(e) = Self::diff_path(&workspace, &mut database, &index, &refs, &path, cached, word_diff, context, &mut pager) {
                     overall_result = Err(e);
                     break;
                 }
@@ -71,8 +124,9 @@ impl DiffCommand {
             overall_result
         };
         
-        // Only show completion message if pager is still active (user hasn't exited)
-        if pager.is_enabled() {
+        // Only show completion message if pager is still active (user hasn't exited);
+        // a patch must contain nothing but the patch
+        if pager.is_enabled() && !git_format {
             let elapsed = start_time.elapsed();
             let _ = pager.write(&format!("\n{}\n", Color::cyan(&format!("Diff completed in {:.2}s", elapsed.as_secs_f32()))));
         }
@@ -88,18 +142,217 @@ impl DiffCommand {
         }
     }
 
+    /// Splits off up to two leading arguments that resolve to commits, returning their
+    /// commit IDs and the remaining paths. As in `ash reset`, an argument that is also a file
+    /// in the working tree is taken as a path.
+    fn split_revisions(args: &[String]) -> Result<(Vec<String>, Vec<String>), Error> {
+        if args.is_empty() {
+            return Ok((Vec::new(), Vec::new()));
+        }
+        
+        let mut repo = Repository::new(".")?;
+        let mut revisions = Vec::new();
+        let mut rest = args;
+        
+        while revisions.len() < 2 {
+            let arg = match rest.first() {
+                Some(arg) => arg,
+                None => break,
+            };
+            if repo.workspace.root_path.join(arg).exists() {
+                break;
+            }
+            match Revision::new(&mut repo, arg).resolve(COMMIT) {
+                Ok(oid) => revisions.push(oid),
+                Err(_) => break,
+            }
+            rest = &rest[1..];
+        }
+        
+        Ok((revisions, rest.to_vec()))
+    }
+    
+    /// Diffs the tree of the first of `revisions` against the second, or without a second
+    /// against the index when cached and the working tree otherwise, in the same formats as
+    /// the other diffs
+    fn diff_revisions(
+        workspace: &Workspace,
+        database: &mut Database,
+        index: &Index,
+        revisions: &[String],
+        paths: &[String],
+        options: &DiffOptions,
+        pager: &mut Pager
+    ) -> Result<(), Error> {
+        let old = &revisions[0];
+        let new = revisions.get(1).map(String::as_str);
+        let mut files = Self::collect_revision_files(workspace, database, index, old, new, options.cached)?;
+        if options.find_renames {
+            files = Self::pair_renames(workspace, files)?;
+        }
+        
+        if options.stat {
+            let contents = files.into_iter()
+                .map(|file| (file.path, file.old_content, file.new_content))
+                .collect();
+            return Self::diff_stat(workspace, contents, paths, pager);
+        }
+        if options.git_format {
+            return Self::write_patches(workspace, files, paths, options.context, pager);
+        }
+        
+        files.retain(|file| Self::is_requested(&file.path, paths));
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        
+        if files.is_empty() {
+            pager.write(&format!("{}\n", Color::green("No changes")))?;
+            return Ok(());
+        }
+        
+        for file in files {
+            if !pager.is_enabled() {
+                break;
+            }
+            
+            let old_path = file.renamed_from.as_ref().map_or(file.path.as_str(), |(old_path, _)| old_path.as_str());
+            pager.write(&format!("diff --ash a/{} b/{}\n", Color::cyan(old_path), Color::cyan(&file.path)))?;
+            if let Some((old_path, score)) = &file.renamed_from {
+                pager.write(&Self::rename_header(old_path, &file.path, *score))?;
+            }
+            
+            if workspace.is_binary(&file.path, &file.old_content) || workspace.is_binary(&file.path, &file.new_content) {
+                pager.write(&format!("Binary files a/{} and b/{} differ\n", file.path, file.path))?;
+                continue;
+            }
+            
+            let old_lines = diff::split_lines(&String::from_utf8_lossy(&file.old_content));
+            let new_lines = diff::split_lines(&String::from_utf8_lossy(&file.new_content));
+            let edits = diff_lines(&old_lines, &new_lines);
+            let raw_diff = format_diff(&old_lines, &new_lines, &edits, options.context);
+            
+            pager.write(&Self::render_diff(&raw_diff, options.word_diff))?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Changed files between the tree of commit `old` and commit `new`, or without `new`
+    /// the index when cached and otherwise the working tree. As in `git diff <commit>`, the
+    /// working tree side holds the files tracked in the index.
+    fn collect_revision_files(
+        workspace: &Workspace,
+        database: &mut Database,
+        index: &Index,
+        old: &str,
+        new: Option<&str>,
+        cached: bool
+    ) -> Result<Vec<PatchFile>, Error> {
+        let old_files = Self::commit_files(database, old)?;
+        
+        // Files read from the working tree rather than the database
+        let mut worktree_paths = HashSet::new();
+        let new_files = match new {
+            Some(new) => Self::commit_files(database, new)?,
+            None => {
+                let mut files = HashMap::new();
+                for entry in index.each_entry() {
+                    if entry.stage != 0 {
+                        continue;
+                    }
+                    
+                    let staged = (entry.get_oid().to_string(), *entry.get_mode());
+                    if cached || entry.is_skip_worktree() {
+                        files.insert(entry.get_path().to_string(), staged);
+                        continue;
+                    }
+                    
+                    let path = Path::new(entry.get_path());
+                    if !workspace.path_exists(path)? {
+                        continue;
+                    }
+                    let mode = FileMode::from_metadata(&workspace.stat_file(path)?);
+                    files.insert(entry.get_path().to_string(), (workspace.hash_file(path)?, mode));
+                    worktree_paths.insert(entry.get_path().to_string());
+                }
+                files
+            }
+        };
+        
+        let mut all_paths: Vec<&String> = old_files.keys().chain(new_files.keys()).collect();
+        all_paths.sort();
+        all_paths.dedup();
+        
+        let mut files = Vec::new();
+        for path in all_paths {
+            let old = old_files.get(path).cloned();
+            let new = new_files.get(path).cloned();
+            
+            if let (Some((old_oid, old_mode)), Some((new_oid, new_mode))) = (&old, &new) {
+                if old_oid == new_oid && FileMode::are_equivalent(old_mode.0, new_mode.0) {
+                    continue;
+                }
+            }
+            
+            let old_content = match &old {
+                Some((oid, _)) => database.load(oid)?.to_bytes(),
+                None => Vec::new(),
+            };
+            let new_content = match &new {
+                Some(_) if worktree_paths.contains(path) => workspace.normalize_eol(path, workspace.read_file(Path::new(path))?),
+                Some((oid, _)) => database.load(oid)?.to_bytes(),
+                None => Vec::new(),
+            };
+            
+            files.push(PatchFile {
+                path: path.clone(),
+                old,
+                new,
+                old_content,
+                new_content,
+                renamed_from: None,
+            });
+        }
+        
+        Ok(files)
+    }
+    
+    /// Every file in the tree of a commit, with its object ID and mode, as the additions
+    /// from an empty tree
+    fn commit_files(database: &mut Database, commit_oid: &str) -> Result<HashMap<String, (String, FileMode)>, Error> {
+        let mut files = HashMap::new();
+        for (path, (_, entry)) in database.tree_diff(None, Some(commit_oid), &PathFilter::new())? {
+            if let Some(entry) = entry {
+                files.insert(path_key(&path), (entry.get_oid().to_string(), entry.get_file_mode()));
+            }
+        }
+        Ok(files)
+    }
+    
+    /// Whether a changed file is under one of the requested paths, matching directories by
+    /// prefix; no paths means every file
+    fn is_requested(path: &str, paths: &[String]) -> bool {
+        paths.is_empty() || paths.iter().any(|p| {
+            let p = p.trim_end_matches('/');
+            path == p || path.starts_with(&format!("{}/", p))
+        })
+    }
+
     /// Diff all changed files in the repository
+    #[allow(clippy::too_many_arguments)]
     fn diff_all(
         workspace: &Workspace,
         database: &mut Database,
         index: &Index,
         refs: &Refs,
         cached: bool,
+        word_diff: bool,
+        context: usize,
+        find_renames: bool,
         pager: &mut Pager
     ) -> Result<(), Error> {
         // If the cached flag is set, compare index with HEAD
         if cached {
-            return Self::diff_index_vs_head(workspace, database, index, refs, pager);
+            return Self::diff_index_vs_head(workspace, database, index, refs, word_diff, context, find_renames, pager);
         }
         
         // Otherwise, compare working tree with index
@@ -107,6 +360,11 @@ impl DiffCommand {
         
         // Get all files from index
         for entry in index.each_entry() {
+            // Skip-worktree paths are absent from the workspace by design
+            if entry.is_skip_worktree() {
+                continue;
+            }
+            
             let path = Path::new(entry.get_path());
             
             // Skip if file doesn't exist in workspace
@@ -123,7 +381,7 @@ impl DiffCommand {
                 let content = blob_obj.to_bytes();
                 
                 // Check if content is binary
-                if is_binary_content(&content) {
+                if workspace.is_binary(&path_str, &content) {
                     pager.write(&format!("Binary file a/{} has been deleted\n", path_str))?;
                     continue;
                 }
@@ -132,17 +390,14 @@ impl DiffCommand {
                 
                 // Show deletion diff
                 for line in &lines {
-                    pager.write(&format!("{}\n", Color::red(&format!("-{}", line))))?;
+                    pager.write(&Self::colorize_diff_output(&diff::format_line("-", line)))?;
                 }
                 
                 continue;
             }
             
-            // Read file content
-            let file_content = workspace.read_file(path)?;
-            
-            // Calculate hash for file content
-            let file_hash = database.hash_file_data(&file_content);
+            // Hash the file as it would be stored
+            let file_hash = workspace.hash_file(path)?;
             
             // If hash matches, there are no changes
             if file_hash == entry.get_oid() {
@@ -153,19 +408,20 @@ impl DiffCommand {
             
             // Print diff header
             let path_str = path.display().to_string();
+            let file_content = workspace.normalize_eol(&path_str, workspace.read_file(path)?);
             pager.write(&format!("diff --ash a/{} b/{}\n", Color::cyan(&path_str), Color::cyan(&path_str)))?;
             
             // Check if file is binary
-            if is_binary_content(&file_content) {
+            if workspace.is_binary(&path_str, &file_content) {
                 pager.write(&format!("Binary files a/{} and b/{} differ\n", path_str, path_str))?;
                 continue;
             }
             
             // Get diff between index and working copy
-            let raw_diff_output = diff::diff_with_database(workspace, database, path, entry.get_oid(), 3)?;
+            let raw_diff_output = diff::diff_with_database(workspace, database, path, entry.get_oid(), context)?;
             
             // Add colors to diff output
-            let colored_diff = Self::colorize_diff_output(&raw_diff_output);
+            let colored_diff = Self::render_diff(&raw_diff_output, word_diff);
             pager.write(&colored_diff)?;
         }
         
@@ -176,6 +432,640 @@ impl DiffCommand {
         Ok(())
     }
 
+    /// Writes the changes as a patch with `git diff` headers, colored only when colors are
+    /// forced, since this format is the default for piped output
+    #[allow(clippy::too_many_arguments)]
+    fn diff_git(
+        workspace: &Workspace,
+        database: &mut Database,
+        index: &Index,
+        refs: &Refs,
+        paths: &[String],
+        cached: bool,
+        context: usize,
+        find_renames: bool,
+        pager: &mut Pager
+    ) -> Result<(), Error> {
+        let mut files = Self::collect_patch_files(workspace, database, index, refs, cached)?;
+        if find_renames {
+            files = Self::pair_renames(workspace, files)?;
+        }
+        Self::write_patches(workspace, files, paths, context, pager)
+    }
+    
+    /// Writes one `git_patch` per file under the requested paths, sorted by path
+    fn write_patches(
+        workspace: &Workspace,
+        mut files: Vec<PatchFile>,
+        paths: &[String],
+        context: usize,
+        pager: &mut Pager
+    ) -> Result<(), Error> {
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        
+        for file in files {
+            if !Self::is_requested(&file.path, paths) {
+                continue;
+            }
+            
+            let patch = Self::git_patch(workspace, &file, context);
+            if Color::is_enabled() {
+                pager.write(&Self::colorize_patch(&patch))?;
+            } else {
+                pager.write(&patch)?;
+            }
+            
+            if !pager.is_enabled() {
+                break;
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Joins each deleted file with the added file it was renamed to into one renamed file.
+    /// Comparing files by similarity is bounded by `diff.renameLimit`; past it only identical
+    /// files are paired, with a warning.
+    fn pair_renames(workspace: &Workspace, files: Vec<PatchFile>) -> Result<Vec<PatchFile>, Error> {
+        let renames = {
+            let deleted: Vec<RenameCandidate> = files.iter()
+                .filter_map(|file| match (&file.old, &file.new) {
+                    (Some((oid, _)), None) => Some(RenameCandidate::new(&file.path, oid)),
+                    _ => None,
+                })
+                .collect();
+            let added: Vec<RenameCandidate> = files.iter()
+                .filter_map(|file| match (&file.old, &file.new) {
+                    (None, Some((oid, _))) => Some(RenameCandidate::new(&file.path, oid)),
+                    _ => None,
+                })
+                .collect();
+            
+            // Both sides' contents are already loaded
+            let mut contents: HashMap<&str, &[u8]> = HashMap::new();
+            for file in &files {
+                if let Some((oid, _)) = &file.old {
+                    contents.insert(oid, &file.old_content);
+                }
+                if let Some((oid, _)) = &file.new {
+                    contents.insert(oid, &file.new_content);
+                }
+            }
+            
+            let limit = rename::rename_limit(&Config::load(&workspace.root_path.join(".ash")), "diff");
+            rename::detect_renames(&deleted, &added, limit, |oid| {
+                Ok(contents.get(oid).map(|content| content.to_vec()).unwrap_or_default())
+            })?
+        };
+        
+        if let Some(needed) = renames.skipped {
+            eprintln!("{}", rename::limit_warning("diff.renameLimit", needed));
+        }
+        
+        let mut by_path: HashMap<String, PatchFile> = files.into_iter()
+            .map(|file| (file.path.clone(), file))
+            .collect();
+        for pair in renames.pairs {
+            if let (Some(old), Some(new)) = (by_path.remove(&pair.old_path), by_path.remove(&pair.new_path)) {
+                by_path.insert(pair.new_path, PatchFile {
+                    path: new.path,
+                    old: old.old,
+                    new: new.new,
+                    old_content: old.old_content,
+                    new_content: new.new_content,
+                    renamed_from: Some((old.path, pair.score)),
+                });
+            }
+        }
+        
+        Ok(by_path.into_values().collect())
+    }
+    
+    /// The extended header lines for a file renamed from `old_path` to `new_path`
+    fn rename_header(old_path: &str, new_path: &str, score: usize) -> String {
+        format!("similarity index {}%\nrename from {}\nrename to {}\n", score, old_path, new_path)
+    }
+    
+    /// Changed files with their object IDs and modes on both sides: HEAD against the index
+    /// in cached mode, otherwise the index against the workspace
+    fn collect_patch_files(
+        workspace: &Workspace,
+        database: &mut Database,
+        index: &Index,
+        refs: &Refs,
+        cached: bool
+    ) -> Result<Vec<PatchFile>, Error> {
+        let mut files = Vec::new();
+        
+        if !cached {
+            for entry in index.each_entry() {
+                if entry.is_skip_worktree() || entry.stage != 0 {
+                    continue;
+                }
+                
+                let path = Path::new(entry.get_path());
+                let old = Some((entry.get_oid().to_string(), *entry.get_mode()));
+                
+                if !workspace.path_exists(path)? {
+                    files.push(PatchFile {
+                        path: entry.get_path().to_string(),
+                        old,
+                        new: None,
+                        old_content: database.load(entry.get_oid())?.to_bytes(),
+                        new_content: Vec::new(),
+                        renamed_from: None,
+                    });
+                    continue;
+                }
+                
+                let file_content = workspace.normalize_eol(entry.get_path(), workspace.read_file(path)?);
+                let file_oid = database.hash_file_data(&file_content);
+                let file_mode = FileMode::from_metadata(&workspace.stat_file(path)?);
+                
+                if file_oid == entry.get_oid() && FileMode::are_equivalent(file_mode.0, entry.get_mode().0) {
+                    continue;
+                }
+                
+                files.push(PatchFile {
+                    path: entry.get_path().to_string(),
+                    old,
+                    new: Some((file_oid, file_mode)),
+                    old_content: database.load(entry.get_oid())?.to_bytes(),
+                    new_content: file_content,
+                    renamed_from: None,
+                });
+            }
+            
+            return Ok(files);
+        }
+        
+        let head_files = match refs.read_head()? {
+            Some(head_oid) => Self::commit_files(database, &head_oid)?,
+            None => HashMap::new(),
+        };
+        
+        for entry in index.each_entry() {
+            if entry.stage != 0 {
+                continue;
+            }
+            
+            let new = (entry.get_oid().to_string(), *entry.get_mode());
+            let old = head_files.get(entry.get_path()).cloned();
+            
+            if let Some((head_oid, head_mode)) = &old {
+                if head_oid == entry.get_oid() && FileMode::are_equivalent(head_mode.0, new.1.0) {
+                    continue;
+                }
+            }
+            
+            let old_content = match &old {
+                Some((head_oid, _)) => database.load(head_oid)?.to_bytes(),
+                None => Vec::new(),
+            };
+            
+            files.push(PatchFile {
+                path: entry.get_path().to_string(),
+                old,
+                new: Some(new),
+                old_content,
+                new_content: database.load(entry.get_oid())?.to_bytes(),
+                renamed_from: None,
+            });
+        }
+        
+        for (path, (head_oid, head_mode)) in head_files {
+            if !index.tracked(&path) {
+                let old_content = database.load(&head_oid)?.to_bytes();
+                files.push(PatchFile {
+                    path,
+                    old: Some((head_oid, head_mode)),
+                    new: None,
+                    old_content,
+                    new_content: Vec::new(),
+                    renamed_from: None,
+                });
+            }
+        }
+        
+        Ok(files)
+    }
+    
+    /// Renders one file of a `--git` patch, byte-compatible with `git diff`
+    fn git_patch(workspace: &Workspace, file: &PatchFile, context: usize) -> String {
+        let path = &file.path;
+        let old_path = file.renamed_from.as_ref().map_or(path, |(old_path, _)| old_path);
+        let abbrev = |side: &Option<(String, FileMode)>| match side {
+            Some((oid, _)) => oid[..oid.len().min(7)].to_string(),
+            None => "0000000".to_string(),
+        };
+        
+        let mut patch = format!("diff --git a/{} b/{}\n", old_path, path);
+        
+        match (&file.old, &file.new) {
+            (None, Some((_, mode))) => patch.push_str(&format!("new file mode {}\n", mode.to_octal_string())),
+            (Some((_, mode)), None) => patch.push_str(&format!("deleted file mode {}\n", mode.to_octal_string())),
+            (Some((_, old_mode)), Some((_, new_mode))) if !FileMode::are_equivalent(old_mode.0, new_mode.0) => {
+                patch.push_str(&format!("old mode {}\n", old_mode.to_octal_string()));
+                patch.push_str(&format!("new mode {}\n", new_mode.to_octal_string()));
+            },
+            _ => {},
+        }
+        
+        if let Some((_, score)) = &file.renamed_from {
+            patch.push_str(&Self::rename_header(old_path, path, *score));
+        }
+        
+        // A pure mode change or rename has no content section
+        let same_content = match (&file.old, &file.new) {
+            (Some((old_oid, _)), Some((new_oid, _))) => old_oid == new_oid,
+            _ => false,
+        };
+        if same_content {
+            return patch;
+        }
+        
+        // The mode goes on the index line only when it did not change
+        match (&file.old, &file.new) {
+            (Some((_, old_mode)), Some((_, new_mode))) if FileMode::are_equivalent(old_mode.0, new_mode.0) => {
+                patch.push_str(&format!("index {}..{} {}\n", abbrev(&file.old), abbrev(&file.new), old_mode.to_octal_string()));
+            },
+            _ => patch.push_str(&format!("index {}..{}\n", abbrev(&file.old), abbrev(&file.new))),
+        }
+        
+        let old_name = if file.old.is_some() { format!("a/{}", old_path) } else { "/dev/null".to_string() };
+        let new_name = if file.new.is_some() { format!("b/{}", path) } else { "/dev/null".to_string() };
+        
+        if workspace.is_binary(path, &file.old_content) || workspace.is_binary(path, &file.new_content) {
+            patch.push_str(&format!("Binary files {} and {} differ\n", old_name, new_name));
+            return patch;
+        }
+        
+        // An empty file created or deleted has no hunks, and so no file names either
+        if file.old_content.is_empty() && file.new_content.is_empty() {
+            return patch;
+        }
+        
+        patch.push_str(&format!("--- {}\n", old_name));
+        patch.push_str(&format!("+++ {}\n", new_name));
+        
+        let old_lines = content_lines(&file.old_content);
+        let new_lines = content_lines(&file.new_content);
+        let edits = diff_lines(&old_lines, &new_lines);
+        
+        patch.push_str(&format_diff(&old_lines, &new_lines, &edits, context));
+        patch
+    }
+    
+    /// Prints one summary line per changed file, given as path with old and new contents,
+    /// followed by the totals
+    fn diff_stat(
+        workspace: &Workspace,
+        contents: Vec<ChangedContents>,
+        paths: &[String],
+        pager: &mut Pager
+    ) -> Result<(), Error> {
+        let mut changes: Vec<(String, usize, usize, bool)> = Vec::new();
+        
+        for (path, old_content, new_content) in contents {
+            if !Self::is_requested(&path, paths) {
+                continue;
+            }
+            
+            if workspace.is_binary(&path, &old_content) || workspace.is_binary(&path, &new_content) {
+                changes.push((path, 0, 0, true));
+                continue;
+            }
+            
+            // Count from the same edit script used for the unified output
+            let old_lines = diff::split_lines(&String::from_utf8_lossy(&old_content));
+            let new_lines = diff::split_lines(&String::from_utf8_lossy(&new_content));
+            let edits = diff_lines(&old_lines, &new_lines);
+            
+            let insertions = edits.iter().filter(|e| matches!(e, Edit::Insert(_))).count();
+            let deletions = edits.iter().filter(|e| matches!(e, Edit::Delete(_))).count();
+            changes.push((path, insertions, deletions, false));
+        }
+        
+        changes.sort_by(|a, b| a.0.cmp(&b.0));
+        
+        if changes.is_empty() {
+            return Ok(());
+        }
+        
+        pager.write(&Self::render_stat(&changes))?;
+        Ok(())
+    }
+
+    /// Reads the old and new contents of every changed file.
+    /// In cached mode this compares HEAD with the index, otherwise the index with the workspace.
+    fn collect_changed_contents(
+        workspace: &Workspace,
+        database: &mut Database,
+        index: &Index,
+        refs: &Refs,
+        cached: bool
+    ) -> Result<Vec<ChangedContents>, Error> {
+        let mut contents = Vec::new();
+        
+        if !cached {
+            for entry in index.each_entry() {
+                if entry.is_skip_worktree() {
+                    continue;
+                }
+                
+                let path = Path::new(entry.get_path());
+                let index_content = |database: &mut Database| -> Result<Vec<u8>, Error> {
+                    Ok(database.load(entry.get_oid())?.to_bytes())
+                };
+                
+                if !workspace.path_exists(path)? {
+                    contents.push((entry.get_path().to_string(), index_content(database)?, Vec::new()));
+                    continue;
+                }
+                
+                if workspace.hash_file(path)? == entry.get_oid() {
+                    continue;
+                }
+                let file_content = workspace.normalize_eol(entry.get_path(), workspace.read_file(path)?);
+                
+                contents.push((entry.get_path().to_string(), index_content(database)?, file_content));
+            }
+            
+            return Ok(contents);
+        }
+        
+        let mut head_files: HashMap<String, String> = HashMap::new();
+        if let Some(head_oid) = refs.read_head()? {
+            let commit_obj = database.load(&head_oid)?;
+            let commit = match commit_obj.as_any().downcast_ref::<Commit>() {
+                Some(c) => c,
+                None => return Err(Error::Generic("HEAD is not a commit".into())),
+            };
+            DiffCommand::collect_files_from_commit(database, commit, &mut head_files)?;
+        }
+        
+        for entry in index.each_entry() {
+            let head_content = match head_files.get(entry.get_path()) {
+                Some(head_oid) if head_oid == entry.get_oid() => continue,
+                Some(head_oid) => database.load(head_oid)?.to_bytes(),
+                None => Vec::new(),
+            };
+            let index_content = database.load(entry.get_oid())?.to_bytes();
+            contents.push((entry.get_path().to_string(), head_content, index_content));
+        }
+        
+        for (path, head_oid) in &head_files {
+            if !index.tracked(path) {
+                let head_content = database.load(head_oid)?.to_bytes();
+                contents.push((path.clone(), head_content, Vec::new()));
+            }
+        }
+        
+        Ok(contents)
+    }
+
+    /// Renders the `--stat` table from (path, insertions, deletions, binary) rows, scaling the
+    /// bar graph to the terminal width. Binary files are shown with `Bin` instead of a bar.
+    fn render_stat(changes: &[(String, usize, usize, bool)]) -> String {
+        let term_width = crossterm::terminal::size()
+            .map(|(w, _)| w as usize)
+            .unwrap_or(80);
+        
+        Self::render_stat_width(changes, term_width)
+    }
+    
+    /// Lays out the `--stat` table within `width` columns the way git does: when the lines
+    /// don't fit, the bar gets at most 3/8 of the width and names are cut to the space left
+    fn render_stat_width(changes: &[(String, usize, usize, bool)], width: usize) -> String {
+        let max_change = changes.iter()
+            .filter(|(_, _, _, binary)| !binary)
+            .map(|(_, ins, del, _)| ins + del)
+            .max()
+            .unwrap_or(0);
+        let count_width = max_change.to_string().len().max(3);
+        
+        // " <name> | <count> <bar>", leaving the last column free
+        let width = width.max(16 + 6 + count_width);
+        let mut name_width = changes.iter().map(|(path, _, _, _)| path.chars().count()).max().unwrap_or(0);
+        let mut bar_width = max_change;
+        if name_width + count_width + 6 + bar_width > width {
+            bar_width = bar_width.min((width * 3 / 8).saturating_sub(count_width + 6).max(6));
+            if name_width > width - count_width - 6 - bar_width {
+                name_width = width - count_width - 6 - bar_width;
+            } else {
+                bar_width = width - count_width - 6 - name_width;
+            }
+        }
+        
+        let mut output = String::new();
+        let mut total_insertions = 0;
+        let mut total_deletions = 0;
+        
+        for (path, insertions, deletions, binary) in changes {
+            let name = Self::truncate_stat_name(path, name_width);
+            if *binary {
+                output.push_str(&format!(" {:<nw$} | {:>cw$}\n", name, "Bin", nw = name_width, cw = count_width));
+                continue;
+            }
+            
+            total_insertions += insertions;
+            total_deletions += deletions;
+            
+            let total = insertions + deletions;
+            let (plus, minus) = if max_change <= bar_width || total == 0 {
+                (*insertions, *deletions)
+            } else {
+                // Scale down, keeping at least one column for every non-zero side
+                let scaled = std::cmp::max(1, total * bar_width / max_change);
+                let mut plus = insertions * scaled / total;
+                if *insertions > 0 && plus == 0 {
+                    plus = 1;
+                }
+                let mut minus = scaled.saturating_sub(plus);
+                if *deletions > 0 && minus == 0 {
+                    minus = 1;
+                    if plus > 1 {
+                        plus -= 1;
+                    }
+                }
+                (plus, minus)
+            };
+            
+            output.push_str(&format!(
+                " {:<nw$} | {:>cw$} {}{}\n",
+                name,
+                total,
+                Color::green(&"+".repeat(plus)),
+                Color::red(&"-".repeat(minus)),
+                nw = name_width,
+                cw = count_width
+            ));
+        }
+        
+        // Counts that are zero are left out, so an all-binary diff only reports the files
+        let files = changes.len();
+        let mut summary = format!(" {} file{} changed", files, if files == 1 { "" } else { "s" });
+        if total_insertions > 0 {
+            summary.push_str(&format!(
+                ", {} insertion{}(+)",
+                total_insertions,
+                if total_insertions == 1 { "" } else { "s" }
+            ));
+        }
+        if total_deletions > 0 {
+            summary.push_str(&format!(
+                ", {} deletion{}(-)",
+                total_deletions,
+                if total_deletions == 1 { "" } else { "s" }
+            ));
+        }
+        output.push_str(&summary);
+        output.push('\n');
+        
+        output
+    }
+    
+    /// Shortens a path to `width` columns as git's `--stat` does: `...` followed by the end of
+    /// the path, starting at a directory separator when the kept part has one
+    fn truncate_stat_name(path: &str, width: usize) -> String {
+        let len = path.chars().count();
+        if len <= width {
+            return path.to_string();
+        }
+        
+        let tail: String = path.chars().skip(len - width.saturating_sub(3)).collect();
+        match tail.find('/') {
+            Some(slash) => format!("...{}", &tail[slash..]),
+            None => format!("...{}", tail),
+        }
+    }
+
+    /// Colors a unified diff, as whole lines or word by word
+    fn render_diff(diff: &str, word_diff: bool) -> String {
+        if word_diff {
+            Self::colorize_word_diff_output(diff)
+        } else {
+            Self::colorize_diff_output(diff)
+        }
+    }
+
+    /// Word-diff variant of `colorize_diff_output`: each run of removed lines is paired with
+    /// the added lines that follow it, and paired lines are shown once with the changed words
+    /// inline as `[-old-]{+new+}`. Lines without a partner are shown whole.
+    fn colorize_word_diff_output(diff: &str) -> String {
+        let mut result = String::new();
+        let mut in_hunk = false;
+        let mut removed: Vec<&str> = Vec::new();
+        let mut added: Vec<&str> = Vec::new();
+        
+        for line in diff.lines() {
+            if line.starts_with("@@") {
+                Self::flush_word_diff(&mut result, &mut removed, &mut added);
+                in_hunk = true;
+                result.push_str(&Color::cyan(line));
+                result.push('\n');
+            } else if !in_hunk {
+                // File headers and binary notices come before the first hunk
+                if line.starts_with("Binary files") {
+                    result.push_str(&Color::yellow(line));
+                } else {
+                    result.push_str(line);
+                }
+                result.push('\n');
+            } else if let Some(old) = line.strip_prefix('-') {
+                // A removal after additions starts a new change block
+                if !added.is_empty() {
+                    Self::flush_word_diff(&mut result, &mut removed, &mut added);
+                }
+                removed.push(old);
+            } else if let Some(new) = line.strip_prefix('+') {
+                added.push(new);
+            } else {
+                Self::flush_word_diff(&mut result, &mut removed, &mut added);
+                result.push_str(line.strip_prefix(' ').unwrap_or(line));
+                result.push('\n');
+            }
+        }
+        
+        Self::flush_word_diff(&mut result, &mut removed, &mut added);
+        result
+    }
+    
+    /// Renders one block of removed and added lines for `--word-diff`
+    fn flush_word_diff(result: &mut String, removed: &mut Vec<&str>, added: &mut Vec<&str>) {
+        let paired = removed.len().min(added.len());
+        
+        for (old, new) in removed.iter().zip(added.iter()) {
+            result.push_str(&Self::word_diff_line(old, new));
+            result.push('\n');
+        }
+        for old in &removed[paired..] {
+            result.push_str(&Color::red(&format!("[-{}-]", old)));
+            result.push('\n');
+        }
+        for new in &added[paired..] {
+            result.push_str(&Color::green(&format!("{{+{}+}}", new)));
+            result.push('\n');
+        }
+        
+        removed.clear();
+        added.clear();
+    }
+    
+    /// Diffs two lines word by word, marking removed words `[-...-]` in red and added
+    /// words `{+...+}` in green
+    fn word_diff_line(old: &str, new: &str) -> String {
+        let old_words: Vec<String> = split_words(old).into_iter().map(String::from).collect();
+        let new_words: Vec<String> = split_words(new).into_iter().map(String::from).collect();
+        
+        let mut line = String::new();
+        let mut deleted = String::new();
+        let mut inserted = String::new();
+        
+        let flush = |line: &mut String, deleted: &mut String, inserted: &mut String| {
+            if !deleted.is_empty() {
+                line.push_str(&Color::red(&format!("[-{}-]", deleted)));
+                deleted.clear();
+            }
+            if !inserted.is_empty() {
+                line.push_str(&Color::green(&format!("{{+{}+}}", inserted)));
+                inserted.clear();
+            }
+        };
+        
+        for edit in diff_lines(&old_words, &new_words) {
+            match edit {
+                Edit::Equal(i, _) => {
+                    flush(&mut line, &mut deleted, &mut inserted);
+                    line.push_str(&old_words[i]);
+                },
+                Edit::Delete(i) => deleted.push_str(&old_words[i]),
+                Edit::Insert(j) => inserted.push_str(&new_words[j]),
+            }
+        }
+        flush(&mut line, &mut deleted, &mut inserted);
+        
+        line
+    }
+
+    /// Colors a patch from `git_patch` as `git diff --color` does: the file header in bold,
+    /// then its hunks like `colorize_diff_output`
+    fn colorize_patch(patch: &str) -> String {
+        let (header, hunks) = match patch.find("\n@@") {
+            Some(pos) => patch.split_at(pos + 1),
+            None => (patch, ""),
+        };
+        
+        let mut result = String::new();
+        for line in header.lines() {
+            result.push_str(&Color::bold(line));
+            result.push('\n');
+        }
+        result.push_str(&Self::colorize_diff_output(hunks));
+        
+        result
+    }
+
     /// Helper method for coloring diff output
     fn colorize_diff_output(diff: &str) -> String {
         let mut result = String::new();
@@ -233,7 +1123,7 @@ impl DiffCommand {
         let obj = match database.load(tree_oid) {
             Ok(obj) => obj,
             Err(e) => {
-                println!("Warning: Could not load object {}: {}", tree_oid, e);
+                eprintln!("Warning: Could not load object {}: {}", tree_oid, e);
                 return Ok(());
             }
         };
@@ -248,7 +1138,7 @@ impl DiffCommand {
                     prefix.join(name)
                 };
                 
-                let entry_path_str = entry_path.to_string_lossy().to_string();
+                let entry_path_str = path_key(&entry_path);
                 
                 match entry {
                     TreeEntry::Blob(oid, mode) => {
@@ -256,7 +1146,7 @@ impl DiffCommand {
                         if *mode == TREE_MODE || mode.is_directory() {
                             // Process this directory recursively
                             if let Err(e) = Self::collect_files_from_tree(database, oid, entry_path, files) {
-                                println!("Warning: Error traversing directory '{}': {}", entry_path_str, e);
+                                eprintln!("Warning: Error traversing directory '{}': {}", entry_path_str, e);
                             }
                         } else {
                             // Regular file
@@ -267,7 +1157,7 @@ impl DiffCommand {
                         if let Some(subtree_oid) = subtree.get_oid() {
                             // Process this directory recursively
                             if let Err(e) = Self::collect_files_from_tree(database, subtree_oid, entry_path, files) {
-                                println!("Warning: Error traversing subtree '{}': {}", entry_path_str, e);
+                                eprintln!("Warning: Error traversing subtree '{}': {}", entry_path_str, e);
                             }
                         }
                     }
@@ -290,14 +1180,14 @@ impl DiffCommand {
                         prefix.join(name)
                     };
                     
-                    let entry_path_str = entry_path.to_string_lossy().to_string();
+                    let entry_path_str = path_key(&entry_path);
                     
                     match entry {
                         TreeEntry::Blob(oid, mode) => {
                             if *mode == TREE_MODE || mode.is_directory() {
                                 // Process this directory recursively
                                 if let Err(e) = Self::collect_files_from_tree(database, oid, entry_path, files) {
-                                    println!("Warning: Error traversing directory '{}': {}", entry_path_str, e);
+                                    eprintln!("Warning: Error traversing directory '{}': {}", entry_path_str, e);
                                 }
                             } else {
                                 // Regular file
@@ -308,7 +1198,7 @@ impl DiffCommand {
                             if let Some(subtree_oid) = subtree.get_oid() {
                                 // Process this directory recursively
                                 if let Err(e) = Self::collect_files_from_tree(database, subtree_oid, entry_path, files) {
-                                    println!("Warning: Error traversing subtree '{}': {}", entry_path_str, e);
+                                    eprintln!("Warning: Error traversing subtree '{}': {}", entry_path_str, e);
                                 }
                             }
                         }
@@ -319,7 +1209,7 @@ impl DiffCommand {
             } else {
                 // Dacă suntem la o cale non-root, acesta ar putea fi un fișier
                 if !prefix.as_os_str().is_empty() {
-                    let path_str = prefix.to_string_lossy().to_string();
+                    let path_str = path_key(&prefix);
                     files.insert(path_str, tree_oid.to_string());
                     return Ok(());
                 }
@@ -335,7 +1225,7 @@ impl DiffCommand {
                     // Încearcă să încarci și să traversezi ca director
                     let dir_path = PathBuf::from(&path);
                     if let Err(e) = Self::collect_files_from_tree(database, &oid, dir_path, files) {
-                        println!("Warning: Error traversing entry '{}': {}", path, e);
+                        eprintln!("Warning: Error traversing entry '{}': {}", path, e);
                         // Continuă cu alte intrări chiar dacă aceasta eșuează
                     }
                 }
@@ -346,6 +1236,7 @@ impl DiffCommand {
     }
     
     /// Diff a specific path
+    #[allow(clippy::too_many_arguments)]
     fn diff_path(
         workspace: &Workspace,
         database: &mut Database,
@@ -353,9 +1244,11 @@ impl DiffCommand {
         refs: &Refs,
         path: &Path,
         cached: bool,
+        word_diff: bool,
+        context: usize,
         pager: &mut Pager
     ) -> Result<(), Error> {
-        let path_str = path.to_string_lossy().to_string();
+        let path_str = path_key(path);
         
         // Dacă calea este în index
         if let Some(entry) = index.get_entry(&path_str) {
@@ -369,7 +1262,7 @@ impl DiffCommand {
                         let content = index_obj.to_bytes();
                         
                         // Verifică dacă fișierul este binar
-                        if is_binary_content(&content) {
+                        if workspace.is_binary(&path_str, &content) {
                             pager.write(&format!("Binary file b/{} created\n", path_str))?;
                             return Ok(());
                         }
@@ -386,7 +1279,7 @@ impl DiffCommand {
                         let lines = diff::split_lines(&String::from_utf8_lossy(&content));
                         
                         for line in &lines {
-                            pager.write(&format!("{}\n", Color::green(&format!("+{}", line))))?;
+                            pager.write(&Self::colorize_diff_output(&diff::format_line("+", line)))?;
                         }
                         
                         return Ok(());
@@ -419,7 +1312,7 @@ impl DiffCommand {
                     let index_content = index_obj.to_bytes();
                     
                     // Verifică dacă vreunul dintre fișiere este binar
-                    if is_binary_content(&head_content) || is_binary_content(&index_content) {
+                    if workspace.is_binary(&path_str, &head_content) || workspace.is_binary(&path_str, &index_content) {
                         pager.write(&format!("Binary files a/{} and b/{} differ\n", path_str, path_str))?;
                         return Ok(());
                     }
@@ -437,17 +1330,17 @@ impl DiffCommand {
                     
                     // Calculează diff-ul
                     let edits = diff_lines(&head_lines, &index_lines);
-                    let diff_text = format_diff(&head_lines, &index_lines, &edits, 3);
+                    let diff_text = format_diff(&head_lines, &index_lines, &edits, context);
                     
                     // Afișează diff-ul colorat
-                    pager.write(&DiffCommand::colorize_diff_output(&diff_text))?;
+                    pager.write(&DiffCommand::render_diff(&diff_text, word_diff))?;
                 } else {
                     // Fișierul este în index, dar nu în HEAD (fișier nou)
                     let index_obj = database.load(entry.get_oid())?;
                     let content = index_obj.to_bytes();
                     
                     // Verifică dacă fișierul este binar
-                    if is_binary_content(&content) {
+                    if workspace.is_binary(&path_str, &content) {
                         pager.write(&format!("Binary file b/{} created\n", path_str))?;
                         return Ok(());
                     }
@@ -464,7 +1357,7 @@ impl DiffCommand {
                     let lines = diff::split_lines(&String::from_utf8_lossy(&content));
                     
                     for line in &lines {
-                        pager.write(&format!("{}\n", Color::green(&format!("+{}", line))))?;
+                        pager.write(&Self::colorize_diff_output(&diff::format_line("+", line)))?;
                     }
                 }
             } else {
@@ -474,7 +1367,7 @@ impl DiffCommand {
                     let content = index_obj.to_bytes();
                     
                     // Verifică dacă fișierul este binar
-                    if is_binary_content(&content) {
+                    if workspace.is_binary(&path_str, &content) {
                         pager.write(&format!("Binary file a/{} has been deleted\n", path_str))?;
                         return Ok(());
                     }
@@ -491,14 +1384,14 @@ impl DiffCommand {
                     let lines = diff::split_lines(&String::from_utf8_lossy(&content));
                     
                     for line in &lines {
-                        pager.write(&format!("{}\n", Color::red(&format!("-{}", line))))?;
+                        pager.write(&Self::colorize_diff_output(&diff::format_line("-", line)))?;
                     }
                     
                     return Ok(());
                 }
                 
                 // Citește copia de lucru
-                let file_content = workspace.read_file(path)?;
+                let file_content = workspace.normalize_eol(&path_str, workspace.read_file(path)?);
                 
                 // Calculează hash-ul pentru conținutul fișierului
                 let file_hash = database.hash_file_data(&file_content);
@@ -510,7 +1403,7 @@ impl DiffCommand {
                 }
                 
                 // Verifică dacă fișierul este binar
-                if is_binary_content(&file_content) {
+                if workspace.is_binary(&path_str, &file_content) {
                     pager.write(&format!("index {}..{} {}\n", 
                             &entry.get_oid()[0..std::cmp::min(7, entry.get_oid().len())], 
                             &file_hash[0..std::cmp::min(7, file_hash.len())], 
@@ -529,7 +1422,7 @@ impl DiffCommand {
                 pager.write(&format!("+++ b/{}\n", path_str))?;
                 
                 // Folosește diff_with_database din modulul diff pentru a obține conținutul diff-ului
-                let raw_diff_output = diff::diff_with_database(workspace, database, path, entry.get_oid(), 3)?;
+                let raw_diff_output = diff::diff_with_database(workspace, database, path, entry.get_oid(), context)?;
                 
                 // Extrage doar partea cu diferențele (fără antetele adăugate de diff_with_database)
                 let lines: Vec<&str> = raw_diff_output.lines().collect();
@@ -541,7 +1434,7 @@ impl DiffCommand {
                 };
                 
                 // Colorează și afișează diff-ul
-                pager.write(&DiffCommand::colorize_diff_output(&diff_content))?;
+                pager.write(&DiffCommand::render_diff(&diff_content, word_diff))?;
             }
         } else {
             // Calea nu este în index
@@ -555,11 +1448,15 @@ impl DiffCommand {
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn diff_index_vs_head(
         workspace: &Workspace,
         database: &mut Database,
         index: &Index,
         refs: &Refs,
+        word_diff: bool,
+        context: usize,
+        find_renames: bool,
         pager: &mut Pager
     ) -> Result<(), Error> {
         // Obține commit-ul HEAD
@@ -582,13 +1479,23 @@ impl DiffCommand {
         let mut head_files: HashMap<String, String> = HashMap::new();
         DiffCommand::collect_files_from_commit(database, commit, &mut head_files)?;
         
+        let renames = if find_renames {
+            Self::staged_renames(workspace, database, index, &head_files)?
+        } else {
+            Vec::new()
+        };
+        
         let mut has_changes = false;
         
         // Compară fișierele din index cu HEAD
         for entry in index.each_entry() {
             let path = entry.get_path();
             
-            if let Some(head_oid) = head_files.get(path) {
+            if let Some(rename) = renames.iter().find(|rename| rename.new_path == path) {
+                has_changes = true;
+                let old_oid = &head_files[&rename.old_path];
+                Self::print_rename(workspace, database, rename, old_oid, entry.get_oid(), &entry.mode_octal(), context, pager)?;
+            } else if let Some(head_oid) = head_files.get(path) {
                 // Fișierul există atât în index, cât și în HEAD
                 if head_oid == entry.get_oid() {
                     // Nicio modificare
@@ -614,7 +1521,7 @@ impl DiffCommand {
                 let index_content = index_obj.to_bytes();
                 
                 // Verifică dacă fișierul este binar
-                if is_binary_content(&head_content) || is_binary_content(&index_content) {
+                if workspace.is_binary(path, &head_content) || workspace.is_binary(path, &index_content) {
                     pager.write(&format!("Binary files a/{} and b/{} differ\n", path, path))?;
                     continue;
                 }
@@ -624,10 +1531,10 @@ impl DiffCommand {
                 
                 // Calculează diff-ul
                 let edits = diff_lines(&head_lines, &index_lines);
-                let raw_diff = format_diff(&head_lines, &index_lines, &edits, 3);
+                let raw_diff = format_diff(&head_lines, &index_lines, &edits, context);
                 
                 // Colorează și afișează diff-ul
-                let colored_diff = DiffCommand::colorize_diff_output(&raw_diff);
+                let colored_diff = DiffCommand::render_diff(&raw_diff, word_diff);
                 pager.write(&colored_diff)?;
             } else {
                 // Fișierul există în index, dar nu în HEAD (fișier nou)
@@ -645,7 +1552,7 @@ impl DiffCommand {
                 let content = index_obj.to_bytes();
                 
                 // Verifică dacă fișierul este binar
-                if is_binary_content(&content) {
+                if workspace.is_binary(path, &content) {
                     pager.write(&format!("Binary file b/{} created\n", path))?;
                     continue;
                 }
@@ -657,14 +1564,14 @@ impl DiffCommand {
                 
                 // Arată diff-ul de adăugare
                 for line in &lines {
-                    pager.write(&format!("{}\n", Color::green(&format!("+{}", line))))?;
+                    pager.write(&Self::colorize_diff_output(&diff::format_line("+", line)))?;
                 }
             }
         }
         
         // Verifică fișierele din HEAD care au fost eliminate din index
         for (path, head_oid) in &head_files {
-            if !index.tracked(path) {
+            if !index.tracked(path) && !renames.iter().any(|rename| &rename.old_path == path) {
                 // Fișierul a fost în HEAD, dar a fost eliminat din index
                 has_changes = true;
                 
@@ -680,7 +1587,7 @@ impl DiffCommand {
                 let content = head_obj.to_bytes();
                 
                 // Verifică dacă fișierul este binar
-                if is_binary_content(&content) {
+                if workspace.is_binary(path, &content) {
                     pager.write(&format!("Binary file a/{} deleted\n", path))?;
                     continue;
                 }
@@ -692,7 +1599,7 @@ impl DiffCommand {
                 
                 // Arată diff-ul de ștergere
                 for line in &lines {
-                    pager.write(&format!("{}\n", Color::red(&format!("-{}", line))))?;
+                    pager.write(&Self::colorize_diff_output(&diff::format_line("-", line)))?;
                 }
             }
         }
@@ -703,4 +1610,191 @@ impl DiffCommand {
         
         Ok(())
     }  
-}
\ No newline at end of file
+
+    /// Pairs the files deleted from HEAD with the files added to the index, within
+    /// `diff.renameLimit`
+    fn staged_renames(
+        workspace: &Workspace,
+        database: &mut Database,
+        index: &Index,
+        head_files: &HashMap<String, String>,
+    ) -> Result<Vec<Rename>, Error> {
+        let deleted: Vec<RenameCandidate> = head_files.iter()
+            .filter(|(path, _)| !index.tracked(path))
+            .map(|(path, oid)| RenameCandidate::new(path, oid))
+            .collect();
+        let added: Vec<RenameCandidate> = index.each_entry()
+            .filter(|entry| !head_files.contains_key(entry.get_path()))
+            .map(|entry| RenameCandidate::new(entry.get_path(), entry.get_oid()))
+            .collect();
+        
+        let limit = rename::rename_limit(&Config::load(&workspace.root_path.join(".ash")), "diff");
+        let renames = rename::detect_renames(&deleted, &added, limit, |oid| Ok(database.load(oid)?.to_bytes()))?;
+        if let Some(needed) = renames.skipped {
+            eprintln!("{}", rename::limit_warning("diff.renameLimit", needed));
+        }
+        
+        Ok(renames.pairs)
+    }
+
+    /// Shows a renamed file: the rename headers, then the changes made along with the move
+    #[allow(clippy::too_many_arguments)]
+    fn print_rename(
+        workspace: &Workspace,
+        database: &mut Database,
+        rename: &Rename,
+        old_oid: &str,
+        new_oid: &str,
+        mode: &str,
+        context: usize,
+        pager: &mut Pager
+    ) -> Result<(), Error> {
+        pager.write(&format!("diff --ash a/{} b/{}\n", Color::cyan(&rename.old_path), Color::cyan(&rename.new_path)))?;
+        pager.write(&Self::rename_header(&rename.old_path, &rename.new_path, rename.score))?;
+        
+        if old_oid == new_oid {
+            return Ok(());
+        }
+        
+        pager.write(&format!("index {}..{} {}\n", &old_oid[..7], &new_oid[..7], mode))?;
+        pager.write(&format!("--- a/{}\n", rename.old_path))?;
+        pager.write(&format!("+++ b/{}\n", rename.new_path))?;
+        
+        let old_content = database.load(old_oid)?.to_bytes();
+        let new_content = database.load(new_oid)?.to_bytes();
+        if workspace.is_binary(&rename.old_path, &old_content) || workspace.is_binary(&rename.new_path, &new_content) {
+            pager.write(&format!("Binary files a/{} and b/{} differ\n", rename.old_path, rename.new_path))?;
+            return Ok(());
+        }
+        
+        let old_lines = diff::split_lines(&String::from_utf8_lossy(&old_content));
+        let new_lines = diff::split_lines(&String::from_utf8_lossy(&new_content));
+        let edits = diff_lines(&old_lines, &new_lines);
+        pager.write(&DiffCommand::colorize_diff_output(&format_diff(&old_lines, &new_lines, &edits, context)))?;
+        
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::rm::RmCommand;
+    use crate::core::diff::patch::parse_patch;
+    use crate::test_support::TestRepo;
+    
+    #[test]
+    fn render_stat_shows_bin_for_binary_rows() {
+        let changes = vec![
+            ("a.txt".to_string(), 2, 1, false),
+            ("img.png".to_string(), 0, 0, true),
+        ];
+        
+        let expected = format!(
+            " a.txt   |   3 {}{}\n img.png | Bin\n 2 files changed, 2 insertions(+), 1 deletion(-)\n",
+            Color::green("++"),
+            Color::red("-")
+        );
+        assert_eq!(DiffCommand::render_stat_width(&changes, 80), expected);
+    }
+    
+    #[test]
+    fn render_stat_leaves_out_zero_counts() {
+        let binary = vec![("img.png".to_string(), 0, 0, true)];
+        assert!(DiffCommand::render_stat_width(&binary, 80).ends_with("\n 1 file changed\n"));
+        
+        let added = vec![("a.txt".to_string(), 4, 0, false)];
+        assert!(DiffCommand::render_stat_width(&added, 80).ends_with("\n 1 file changed, 4 insertions(+)\n"));
+    }
+    
+    #[test]
+    fn render_stat_fits_narrow_widths() {
+        let path = "src/commands/very/deeply/nested/module/with_a_long_name.rs";
+        let changes = vec![(path.to_string(), 300, 200, false)];
+        
+        for width in [30, 40, 60, 80] {
+            let output = DiffCommand::render_stat_width(&changes, width);
+            // Count the columns without the color escapes around the bar
+            let mut columns = 0;
+            let mut in_escape = false;
+            for c in output.lines().next().unwrap().chars() {
+                match c {
+                    '\x1b' => in_escape = true,
+                    'm' if in_escape => in_escape = false,
+                    _ if !in_escape => columns += 1,
+                    _ => {}
+                }
+            }
+            assert!(columns < width, "{} columns at width {}:\n{}", columns, width, output);
+            assert!(output.starts_with(" ..."), "{}", output);
+        }
+        
+        assert_eq!(DiffCommand::truncate_stat_name("a/b/c/defgh.rs", 13), ".../defgh.rs");
+        assert_eq!(DiffCommand::truncate_stat_name("abcdefghij", 8), "...fghij");
+        assert_eq!(DiffCommand::truncate_stat_name("abcdefghij", 10), "abcdefghij");
+    }
+    
+    #[test]
+    fn diff_between_two_revisions() {
+        let repo = TestRepo::new();
+        let first = repo.commit_files(&[("a.txt", "one\n"), ("b.txt", "same\n")], "first");
+        let second = repo.commit_files(&[("a.txt", "two\n")], "second");
+        
+        let args = vec!["HEAD~1".to_string(), "HEAD".to_string()];
+        let (revisions, paths) = DiffCommand::split_revisions(&args).unwrap();
+        assert_eq!(revisions, vec![first, second]);
+        assert!(paths.is_empty());
+        
+        let mut r = repo.repo();
+        let files = DiffCommand::collect_revision_files(&r.workspace, &mut r.database, &r.index, &revisions[0], Some(&revisions[1]), false).unwrap();
+        let changed: Vec<(&str, &[u8], &[u8])> = files.iter()
+            .map(|file| (file.path.as_str(), file.old_content.as_slice(), file.new_content.as_slice()))
+            .collect();
+        assert_eq!(changed, vec![("a.txt", &b"one\n"[..], &b"two\n"[..])]);
+        
+        let options = DiffOptions { cached: false, stat: false, word_diff: false, context: 3, git_format: true, find_renames: false };
+        let mut pager = Pager::new();
+        DiffCommand::diff_revisions(&r.workspace, &mut r.database, &r.index, &revisions, &[], &options, &mut pager).unwrap();
+    }
+    
+    #[test]
+    fn staged_moves_are_paired_as_renames() {
+        let repo = TestRepo::new();
+        repo.commit_files(&[("old.txt", "one\ntwo\nthree\nfour\nfive\n"), ("kept.txt", "kept\n")], "first");
+        RmCommand::execute(&["old.txt".to_string()], false, false, false, false).unwrap();
+        repo.write("new.txt", "one\ntwo\nthree\nfour\nFIVE\n");
+        repo.add(&["new.txt"]);
+        
+        let mut r = repo.repo();
+        r.index.load().unwrap();
+        let head_files: HashMap<String, String> = [
+            ("old.txt".to_string(), repo.blob_oid("one\ntwo\nthree\nfour\nfive\n")),
+            ("kept.txt".to_string(), repo.blob_oid("kept\n")),
+        ].into_iter().collect();
+        
+        let renames = DiffCommand::staged_renames(&r.workspace, &mut r.database, &r.index, &head_files).unwrap();
+        assert_eq!(renames, vec![Rename { old_path: "old.txt".into(), new_path: "new.txt".into(), score: 79 }]);
+    }
+    
+    #[test]
+    fn renamed_files_get_rename_headers_that_apply_back() {
+        let repo = TestRepo::new();
+        repo.commit_files(&[("old.txt", "one\ntwo\nthree\nfour\nfive\n")], "first");
+        RmCommand::execute(&["old.txt".to_string()], false, false, false, false).unwrap();
+        repo.write("new.txt", "one\ntwo\nthree\nfour\nFIVE\n");
+        repo.add(&["new.txt"]);
+        
+        let mut r = repo.repo();
+        r.index.load().unwrap();
+        let files = DiffCommand::collect_patch_files(&r.workspace, &mut r.database, &r.index, &r.refs, true).unwrap();
+        let files = DiffCommand::pair_renames(&r.workspace, files).unwrap();
+        assert_eq!(files.len(), 1);
+        
+        let patch = DiffCommand::git_patch(&r.workspace, &files[0], 3);
+        assert!(patch.starts_with("diff --git a/old.txt b/new.txt\nsimilarity index 79%\nrename from old.txt\nrename to new.txt\n"));
+        
+        let parsed = parse_patch(&patch).unwrap();
+        assert_eq!(parsed[0].old_path.as_deref(), Some("old.txt"));
+        assert_eq!(parsed[0].apply(b"one\ntwo\nthree\nfour\nfive\n").unwrap(), b"one\ntwo\nthree\nfour\nFIVE\n");
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::HashSet;
+use std::path::Path;
+use crate::errors::error::Error;
+use crate::core::color::Color;
+use crate::core::database::commit::Commit;
+use crate::core::database::database::Database;
+use crate::core::database::tag::Tag;
+use crate::core::database::tree::{Tree, TreeEntry};
+use crate::core::refs::Reference;
+use crate::core::repository::repository::Repository;
+
+/// Problems found by a repository check.
+#[derive(Debug, Default)]
+pub struct RepositoryReport {
+    /// Objects read and checked
+    pub checked: usize,
+    /// Objects referenced by a ref, the index or another object that are not in the database
+    pub missing: Vec<String>,
+    /// Objects that can't be read or parsed, or whose content doesn't match their ID
+    pub corrupt: Vec<String>,
+    /// Tracked files whose working copy differs from the index; reported, but not errors
+    pub modified: Vec<String>,
+}
+
+impl RepositoryReport {
+    /// Whether every reachable object is present and intact
+    pub fn is_valid(&self) -> bool {
+        self.missing.is_empty() && self.corrupt.is_empty()
+    }
+}
+
+/// Implements the 'fsck' command for AsheraFlow.
+/// Checks that every object reachable from refs, HEAD and the index is present and intact.
+pub struct FsckCommand;
+
+impl FsckCommand {
+    /// Runs the check and prints the report; returns whether the repository is valid.
+    /// With `connectivity_only`, objects are only checked for presence: their content is
+    /// not re-hashed and the working tree is not compared with the index.
+    pub fn execute(verbose: bool, connectivity_only: bool) -> Result<bool, Error> {
+        let mut repo = Repository::new(".")?;
+        let report = Self::check_repository(&mut repo, verbose, connectivity_only)?;
+
+        for oid in &report.missing {
+            println!("{} {}", Color::red("missing"), oid);
+        }
+        for problem in &report.corrupt {
+            println!("{} {}", Color::red("corrupt"), problem);
+        }
+        if verbose {
+            for path in &report.modified {
+                println!("{} {}", Color::yellow("modified in working tree:"), path);
+            }
+        }
+
+        println!(
+            "Checked {} object{}: {} missing, {} corrupt",
+            report.checked,
+            if report.checked == 1 { "" } else { "s" },
+            report.missing.len(),
+            report.corrupt.len()
+        );
+
+        Ok(report.is_valid())
+    }
+
+    /// Walks every object reachable from HEAD, the refs and the index
+    pub fn check_repository(repo: &mut Repository, verbose: bool, connectivity_only: bool) -> Result<RepositoryReport, Error> {
+        let mut report = RepositoryReport::default();
+        let mut pending = Vec::new();
+
+        pending.extend(repo.refs.read_head()?);
+        for reference in repo.refs.list_refs_with_prefix("refs/")? {
+            if let Reference::Symbolic(path) = reference {
+                // Some refs (e.g. refs/meta/tasksprint/*) hold names rather than object IDs
+                pending.extend(repo.refs.read_ref(&path)?.filter(|value| is_oid(value)));
+            }
+        }
+
+        repo.index.load()?;
+        let index_entries: Vec<(String, String)> = repo.index.each_entry()
+            .map(|entry| (entry.get_path().to_string(), entry.get_oid().to_string()))
+            .collect();
+        pending.extend(index_entries.iter().map(|(_, oid)| oid.clone()));
+
+        let mut seen = HashSet::new();
+        while let Some(oid) = pending.pop() {
+            if !seen.insert(oid.clone()) {
+                continue;
+            }
+            if !repo.database.exists(&oid) {
+                report.missing.push(oid);
+                continue;
+            }
+
+            report.checked += 1;
+            match Self::check_object(&repo.database, &oid, verbose, connectivity_only) {
+                Ok(children) => pending.extend(children),
+                Err(e) => report.corrupt.push(format!("{}: {}", oid, e)),
+            }
+        }
+
+        if !connectivity_only {
+            for (path, oid) in &index_entries {
+                let file_path = Path::new(path);
+                if !repo.workspace.path_exists(file_path)? {
+                    continue;
+                }
+                if repo.workspace.hash_file(file_path)? != *oid {
+                    report.modified.push(path.clone());
+                }
+            }
+        }
+
+        report.missing.sort();
+        report.corrupt.sort();
+        Ok(report)
+    }
+
+    /// Reads one object, verifies it and returns the IDs of the objects it refers to
+    fn check_object(database: &Database, oid: &str, verbose: bool, connectivity_only: bool) -> Result<Vec<String>, Error> {
+        let (obj_type, content) = database.read_raw(oid)?;
+        if verbose {
+            println!("Checking {} {}", obj_type, oid);
+        }
+
+        if !connectivity_only {
+            let mut stored = format!("{} {}\0", obj_type, content.len()).into_bytes();
+            stored.extend_from_slice(&content);
+            let actual = database.hash_content(&stored);
+            if actual != oid {
+                return Err(Error::Generic(format!("hash mismatch (content hashes to {})", actual)));
+            }
+        }
+
+        let children = match obj_type.as_str() {
+            "commit" => {
+                let commit = Commit::parse(&content)?;
+                let mut children = vec![commit.get_tree().to_string()];
+                children.extend(commit.get_parents().iter().cloned());
+                children
+            },
+            "tree" => Tree::parse(&content)?.get_entries().values()
+                .filter_map(|entry| match entry {
+                    TreeEntry::Blob(oid, _) => Some(oid.clone()),
+                    TreeEntry::Tree(subtree) => subtree.get_oid().cloned(),
+                })
+                .collect(),
+            "tag" => vec![Tag::parse(&content)?.get_object().to_string()],
+            "blob" => Vec::new(),
+            other => return Err(Error::Generic(format!("unknown object type '{}'", other))),
+        };
+
+        Ok(children)
+    }
+}
+
+fn is_oid(value: &str) -> bool {
+    value.len() == 40 && value.chars().all(|c| c.is_ascii_hexdigit())
+}
@@ -0,0 +1,32 @@
+use crate::errors::error::Error;
+use crate::core::repository::repository::Repository;
+
+/// Implements the 'gc' command for AsheraFlow.
+/// Packs loose objects into a single pack file and loose refs into packed-refs.
+pub struct GcCommand;
+
+impl GcCommand {
+    /// Moves every loose object into `.ash/objects/pack/pack-<sha>.pack` with its index,
+    /// deleting the loose files once the pack has been verified. Loose refs are then moved
+    /// into `.ash/packed-refs`.
+    pub fn execute() -> Result<(), Error> {
+        let mut repo = Repository::new(".")?;
+
+        match repo.database.pack_loose_objects()? {
+            Some((pack_path, count)) => {
+                let name = pack_path.file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                println!("Packed {} objects into {}", count, name);
+            },
+            None => println!("No loose objects to pack"),
+        }
+
+        let ref_count = repo.refs.pack_refs()?;
+        if ref_count > 0 {
+            println!("Packed {} refs into packed-refs", ref_count);
+        }
+
+        Ok(())
+    }
+}
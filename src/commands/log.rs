@@ -1,23 +1,33 @@
 /// Implements the 'log' command for AsheraFlow.
 /// Handles displaying commit logs with formatting and filtering options.
 use std::time::Instant;
-use std::collections::HashMap;
+use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::path::PathBuf;
+use chrono::NaiveDate;
+use regex::Regex;
 
 use crate::errors::error::Error;
 use crate::core::color::Color;
 use crate::core::pager::Pager;
 use crate::core::database::database::Database;
-use crate::core::database::commit::Commit;
+use crate::core::database::author::Author;
+use crate::core::database::commit::{normalize_message, Commit};
+use crate::core::database::tag::Tag;
 use crate::core::path_filter::PathFilter;
 use crate::core::refs::{Refs, Reference};
-use crate::core::revision::Revision;
+use crate::core::repository::repository::Repository;
+use crate::core::revision::resolve_walk_tips;
+use crate::core::graph::Graph;
+
+/// Width of message lines in the medium format, excluding the four-space indent
+const MESSAGE_WIDTH: usize = 76;
 
 /// Main struct for the log command logic.
 pub struct LogCommand;
 
 impl LogCommand {
     /// Executes the log command, displaying commit logs with the specified options.
+    /// Revisions may be ranges such as `A..B` or `A...B` (see `RevisionSpec`).
     /// Returns an error if repository is not initialized or HEAD is missing.
     pub fn execute(revisions: &[String], options: &HashMap<String, String>) -> Result<(), Error> {
         let start_time = Instant::now();
@@ -41,39 +51,61 @@ impl LogCommand {
         let patch = options.get("patch").map_or(false, |v| v == "true");
         let decorate_default = "auto".to_string();
         let decorate = options.get("decorate").unwrap_or(&decorate_default);
+        let graph = options.get("graph").is_some_and(|v| v == "true");
+        // The graph always needs every child before its parents
+        let topo_walk = graph || options.get("topo_order").is_some_and(|v| v == "true");
+        let filter = CommitFilter::from_options(options)?;
+        let max_count = match options.get("max_count") {
+            Some(n) => Some(n.parse::<usize>().map_err(|_| Error::Generic(format!("Invalid max count: {}", n)))?),
+            None => None,
+        };
+        let mut shown = 0;
         
         // Initialize pager for output
         let mut pager = Pager::new();
         pager.start()?;
         
-        // Determine the starting commit - Use HEAD if no revision is specified
-        let head_oid = if revisions.is_empty() {
-            refs.read_head()?.ok_or_else(|| Error::Generic("No HEAD commit found. Repository may be empty.".to_string()))?
-        } else {
-            // Resolve the requested revision to a commit ID
-            let mut repo = crate::core::repository::repository::Repository::new(".")?;
-            let mut revision = Revision::new(&mut repo, &revisions[0]);
-            revision.resolve("commit")?
-        };
-        
-        // Check for path filtering
-        let mut path_filter = PathFilter::new();
+        // Arguments after `--` are paths, even if they no longer exist. Before it, arguments
+        // naming existing files are paths and everything else is a revision.
         let mut path_args = Vec::new();
+        let mut rev_args = Vec::new();
+        
+        let (revisions, explicit_paths) = match revisions.iter().position(|arg| arg == "--") {
+            Some(pos) => (&revisions[..pos], &revisions[pos + 1..]),
+            None => (revisions, &revisions[revisions.len()..]),
+        };
+        path_args.extend(explicit_paths.iter().map(PathBuf::from));
         
         for arg in revisions {
             let path = PathBuf::from(arg);
             if path.exists() {
                 path_args.push(path);
+            } else {
+                rev_args.push(arg.clone());
             }
         }
         
+        // Determine the starting commits - Use HEAD if no revision is specified
+        let (include, exclude) = if rev_args.is_empty() {
+            let head_oid = refs.read_head()?.ok_or_else(|| Error::Generic("No HEAD commit found. Repository may be empty.".to_string()))?;
+            (vec![head_oid], Vec::new())
+        } else {
+            let mut repo = Repository::new(".")?;
+            resolve_walk_tips(&mut repo, &rev_args)?
+        };
+        
+        // Commits reachable from an excluded tip are never shown
+        let hidden = collect_reachable(&mut database, &exclude)?;
+        
+        // Check for path filtering
+        let mut path_filter = PathFilter::new();
         if !path_args.is_empty() {
             path_filter = PathFilter::build(&path_args);
         }
         
         // Build reverse ref map for decoration if needed
         let reverse_refs = if decorate != "no" {
-            build_reverse_refs(&refs)?
+            build_reverse_refs(&refs, &mut database)?
         } else {
             HashMap::new()
         };
@@ -85,11 +117,40 @@ impl LogCommand {
             Reference::Direct(String::new())
         };
         
-        // Iterate through history beginning with the start commit
-        let mut oid = head_oid;
+        // Walk history newest-first, starting from every included tip. `--topo-order` and the
+        // graph list every child before its parents, whatever the timestamps say, so they
+        // walk a precomputed topological order instead.
+        let mut queue = BinaryHeap::new();
+        let mut queued = HashSet::new();
+        let mut topo = Vec::new().into_iter();
+        let mut lanes = Graph::new();
+        if topo_walk {
+            topo = topo_order(&mut database, &include, &hidden)?.into_iter();
+        } else {
+            for oid in include {
+                if !hidden.contains(&oid) && queued.insert(oid.clone()) {
+                    queue.push((commit_timestamp(&mut database, &oid)?, oid));
+                }
+            }
+        }
+        
         let mut first = true;
         
-        while !oid.is_empty() {
+        loop {
+            let (oid, parents) = if topo_walk {
+                match topo.next() {
+                    Some(next) => next,
+                    None => break,
+                }
+            } else {
+                match queue.pop() {
+                    Some((_, oid)) => (oid, Vec::new()),
+                    None => break,
+                }
+            };
+            let rows = if graph { Some(lanes.next(&oid, &parents)) } else { None };
+            
+
             let commit_obj = database.load(&oid)?;
             let commit = match commit_obj.as_any().downcast_ref::<Commit>() {
                 Some(c) => c,
@@ -115,19 +176,28 @@ impl LogCommand {
                 true
             };
             
-            // Only show commit if it affects the filtered paths
-            if commit_affects_paths {
+            // Only show commit if it affects the filtered paths and passes the author/date filters;
+            // its parents are walked either way
+            if commit_affects_paths && filter.matches(commit) && max_count.is_none_or(|max| shown < max) {
+                shown += 1;
                 // Add a blank line between commits except before the first one
-                if !first && format != "oneline" {
+                if !first && format != "oneline" && !graph {
                     pager.write("\n")?;
                 }
                 first = false;
                 
+                if let Some(rows) = &rows {
+                    pager.set_line_prefix(&rows.commit, &rows.padding);
+                }
+                
                 // Display the commit based on format
                 match format.as_str() {
                     "oneline" => {
                         show_commit_oneline(&mut pager, commit, abbrev, decorate, &reverse_refs, &current_ref)?;
                     },
+                    "raw" => {
+                        show_commit_raw(&mut pager, &database, &oid)?;
+                    },
                     _ => { // medium (default) format
                         show_commit_medium(&mut pager, commit, abbrev, decorate, &reverse_refs, &current_ref)?;
                     }
@@ -149,23 +219,37 @@ impl LogCommand {
                         &path_filter
                     )?;
                 }
+                
+                // With the graph, the blank separator carries the lanes down
+                if graph && format != "oneline" {
+                    pager.write("\n")?;
+                }
+                pager.clear_line_prefix();
             }
             
-            // Move to parent commit
-            if let Some(parent) = commit.get_parent() {
-                oid = parent.clone();
-            } else {
-                break;
+            if let Some(rows) = rows {
+                for row in rows.transitions {
+                    pager.write(&format!("{}\n", row))?;
+                }
             }
             
-            // Check if the pager was closed by the user
-            if !pager.is_enabled() {
+            // Queue the parents unless they are hidden or already queued
+            if !topo_walk {
+                for parent in commit_parents(commit) {
+                    if !hidden.contains(&parent) && queued.insert(parent.clone()) {
+                        queue.push((commit_timestamp(&mut database, &parent)?, parent));
+                    }
+                }
+            }
+            
+            // Check if the pager was closed by the user or enough commits were shown
+            if !pager.is_enabled() || max_count.is_some_and(|max| shown >= max) {
                 break;
             }
         }
         
-        // Display timing info
-        if pager.is_enabled() {
+        // Display timing info (an empty range prints nothing at all)
+        if pager.is_enabled() && !first {
             let elapsed = start_time.elapsed();
             pager.write(&format!("\n{}\n", Color::cyan(&format!("Log completed in {:.2}s", elapsed.as_secs_f32()))))?;
         }
@@ -177,8 +261,141 @@ impl LogCommand {
     }
 }
 
+/// `--author`, `--since` and `--until` restrictions on which commits are shown
+struct CommitFilter {
+    author: Option<Regex>,
+    since: Option<i64>,
+    until: Option<i64>,
+}
+
+impl CommitFilter {
+    fn from_options(options: &HashMap<String, String>) -> Result<Self, Error> {
+        // Patterns that aren't valid regexes are matched literally
+        let author = options.get("author").map(|pattern| {
+            Regex::new(pattern).unwrap_or_else(|_| Regex::new(&regex::escape(pattern)).unwrap())
+        });
+        let since = match options.get("since") {
+            Some(date) => Some(Author::parse_date(date)?.timestamp()),
+            None => None,
+        };
+        // A bare `YYYY-MM-DD` upper bound includes that whole day
+        let until = match options.get("until") {
+            Some(date) if NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").is_ok() => {
+                Some(Author::parse_date(date)?.timestamp() + 24 * 60 * 60 - 1)
+            },
+            Some(date) => Some(Author::parse_date(date)?.timestamp()),
+            None => None,
+        };
+        
+        Ok(CommitFilter { author, since, until })
+    }
+    
+    fn matches(&self, commit: &Commit) -> bool {
+        let author = &commit.author;
+        let timestamp = author.timestamp.timestamp();
+        
+        self.author.as_ref().is_none_or(|re| re.is_match(&format!("{} <{}>", author.name, author.email)))
+            && self.since.is_none_or(|since| timestamp >= since)
+            && self.until.is_none_or(|until| timestamp <= until)
+    }
+}
+
+// Parents of a commit: its recorded parents, then any `Merge-Parent:` lines left by older merges
+fn commit_parents(commit: &Commit) -> Vec<String> {
+    let mut parents = commit.get_parents().to_vec();
+    for line in commit.get_message().lines() {
+        if let Some(parent) = line.strip_prefix("Merge-Parent: ") {
+            let parent = parent.trim().to_string();
+            if !parents.contains(&parent) {
+                parents.push(parent);
+            }
+        }
+    }
+    parents
+}
+
+// Commits reachable from `include` but not `hidden`, each listed before its parents and
+// otherwise newest first, with the parents that are part of the walk
+fn topo_order(database: &mut Database, include: &[String], hidden: &HashSet<String>) -> Result<Vec<(String, Vec<String>)>, Error> {
+    let mut parents_of: HashMap<String, Vec<String>> = HashMap::new();
+    let mut timestamps = HashMap::new();
+    let mut pending: Vec<String> = include.iter().filter(|oid| !hidden.contains(*oid)).cloned().collect();
+    
+    while let Some(oid) = pending.pop() {
+        if parents_of.contains_key(&oid) {
+            continue;
+        }
+        let commit_obj = database.load(&oid)?;
+        let commit = match commit_obj.as_any().downcast_ref::<Commit>() {
+            Some(c) => c,
+            None => return Err(Error::Generic(format!("Object {} is not a commit", oid))),
+        };
+        
+        let parents: Vec<String> = commit_parents(commit).into_iter().filter(|p| !hidden.contains(p)).collect();
+        pending.extend(parents.iter().cloned());
+        timestamps.insert(oid.clone(), commit.committer.timestamp.timestamp());
+        parents_of.insert(oid, parents);
+    }
+    
+    // A commit is ready once all of its children have been listed
+    let mut children: HashMap<&String, usize> = HashMap::new();
+    for parents in parents_of.values() {
+        for parent in parents {
+            *children.entry(parent).or_insert(0) += 1;
+        }
+    }
+    
+    let mut ready: BinaryHeap<(i64, &String)> = parents_of.keys()
+        .filter(|oid| !children.contains_key(oid))
+        .map(|oid| (timestamps[oid], oid))
+        .collect();
+    
+    let mut order = Vec::new();
+    while let Some((_, oid)) = ready.pop() {
+        for parent in &parents_of[oid] {
+            if let Some(count) = children.get_mut(parent) {
+                *count -= 1;
+                if *count == 0 {
+                    ready.push((timestamps[parent], parent));
+                }
+            }
+        }
+        order.push((oid.clone(), parents_of[oid].clone()));
+    }
+    
+    Ok(order)
+}
+
+// Collect every commit reachable from the given tips
+fn collect_reachable(database: &mut Database, tips: &[String]) -> Result<HashSet<String>, Error> {
+    let mut reachable = HashSet::new();
+    let mut pending: Vec<String> = tips.to_vec();
+    
+    while let Some(oid) = pending.pop() {
+        if !reachable.insert(oid.clone()) {
+            continue;
+        }
+        
+        let commit_obj = database.load(&oid)?;
+        if let Some(commit) = commit_obj.as_any().downcast_ref::<Commit>() {
+            pending.extend(commit_parents(commit));
+        }
+    }
+    
+    Ok(reachable)
+}
+
+// Committer timestamp used to order the walk across several tips
+fn commit_timestamp(database: &mut Database, oid: &str) -> Result<i64, Error> {
+    let commit_obj = database.load(oid)?;
+    match commit_obj.as_any().downcast_ref::<Commit>() {
+        Some(commit) => Ok(commit.committer.timestamp.timestamp()),
+        None => Err(Error::Generic(format!("Object {} is not a commit", oid))),
+    }
+}
+
 // Helper function to build a map from commit OIDs to the refs that point to them
-fn build_reverse_refs(refs: &Refs) -> Result<HashMap<String, Vec<Reference>>, Error> {
+pub(crate) fn build_reverse_refs(refs: &Refs, database: &mut Database) -> Result<HashMap<String, Vec<Reference>>, Error> {
     let mut reverse_refs = HashMap::new();
     
     // Get current HEAD reference
@@ -197,11 +414,67 @@ fn build_reverse_refs(refs: &Refs) -> Result<HashMap<String, Vec<Reference>>, Er
         }
     }
     
+    // Get all remote-tracking branch references
+    for remote_ref in refs.list_refs_with_prefix("refs/remotes/")? {
+        if let Reference::Symbolic(path) = &remote_ref {
+            if let Ok(Some(oid)) = refs.read_ref(path) {
+                reverse_refs.entry(oid).or_insert_with(Vec::new).push(remote_ref.clone());
+            }
+        }
+    }
+    
+    // Get all tag references, keyed by the commit they point at
+    let tags = refs.list_tags()?;
+    for tag_ref in tags {
+        if let Reference::Symbolic(path) = &tag_ref {
+            if let Ok(Some(oid)) = refs.read_ref(path) {
+                let oid = peel_tag(database, &oid);
+                reverse_refs.entry(oid).or_insert_with(Vec::new).push(tag_ref.clone());
+            }
+        }
+    }
+    
     Ok(reverse_refs)
 }
 
+// Follow annotated tag objects to the commit they point at
+fn peel_tag(database: &mut Database, oid: &str) -> String {
+    let mut current = oid.to_string();
+    while let Ok(object) = database.load(&current) {
+        match object.as_any().downcast_ref::<Tag>() {
+            Some(tag) => current = tag.get_object().to_string(),
+            None => break,
+        }
+    }
+    current
+}
+
 // Display a commit in the medium format (default)
-fn show_commit_medium(
+/// Display a commit in raw format: its stored headers verbatim, then the indented message
+fn show_commit_raw(pager: &mut Pager, database: &Database, oid: &str) -> Result<(), Error> {
+    let (_, data) = database.read_raw(oid)?;
+    let text = String::from_utf8_lossy(&data);
+    
+    // Headers end at the first blank line; everything after it is the message
+    let (headers, message) = match text.find("\n\n") {
+        Some(pos) => (&text[..pos], &text[pos + 2..]),
+        None => (text.trim_end_matches('\n'), ""),
+    };
+    
+    pager.write(&format!("{} {}\n", Color::yellow("commit"), oid))?;
+    for line in headers.lines() {
+        pager.write(&format!("{}\n", line))?;
+    }
+    
+    pager.write("\n")?;
+    for line in message.lines() {
+        pager.write(&format!("    {}\n", line))?;
+    }
+    
+    Ok(())
+}
+
+pub(crate) fn show_commit_medium(
     pager: &mut Pager,
     commit: &Commit,
     abbrev: bool,
@@ -235,15 +508,39 @@ fn show_commit_medium(
         pager.write(&format!("Date:   {}\n", author.short_date()))?;
     }
     
-    // Display commit message
+    // Display commit message, normalizing line endings of older commits and wrapping long lines
     pager.write("\n")?;
-    for line in commit.get_message().lines() {
-        pager.write(&format!("    {}\n", line))?;
+    for line in normalize_message(commit.get_message()).lines() {
+        for wrapped in wrap_line(line, MESSAGE_WIDTH) {
+            pager.write(&format!("    {}\n", wrapped))?;
+        }
     }
     
     Ok(())
 }
 
+// Split a message line at spaces so each piece fits in `width` columns; a single word
+// longer than the width is kept whole
+fn wrap_line(line: &str, width: usize) -> Vec<String> {
+    if line.chars().count() <= width {
+        return vec![line.to_string()];
+    }
+    
+    let mut wrapped = Vec::new();
+    let mut current = String::new();
+    for word in line.split(' ') {
+        if !current.is_empty() && current.chars().count() + 1 + word.chars().count() > width {
+            wrapped.push(std::mem::take(&mut current));
+        } else if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+    wrapped.push(current);
+    
+    wrapped
+}
+
 // Display a commit in the oneline format
 fn show_commit_oneline(
     pager: &mut Pager,
@@ -279,84 +576,79 @@ fn show_commit_oneline(
     Ok(())
 }
 
-// Format the decoration (refs) for a commit
+// Format the decoration (refs) for a commit, grouped like git: HEAD (with the branch it
+// points at), then local branches, remote-tracking branches and tags, each in its own color
 fn format_decoration(
     commit: &Commit,
     reverse_refs: &HashMap<String, Vec<Reference>>,
     current_ref: &Reference,
     decorate: &str
 ) -> String {
-    // If no refs point to this commit, return empty string
-    if let Some(oid) = commit.get_oid() {
-        if let Some(refs) = reverse_refs.get(oid) {
-            if refs.is_empty() {
-                return String::new();
-            }
-            
-            // Format each ref name
-            let mut ref_names = Vec::new();
-            let mut has_head = false;
-            
-            for reference in refs {
-                match reference {
-                    Reference::Symbolic(path) => {
-                        // Handle HEAD specially
-                        if path == "HEAD" {
-                            has_head = true;
-                            continue;
-                        }
-                        
-                        // Format branch name
-                        let name = if decorate == "full" {
-                            path.clone()
-                        } else {
-                            // Extract short name for branches
-                            if path.starts_with("refs/heads/") {
-                                path.strip_prefix("refs/heads/").unwrap_or(path).to_string()
-                            } else {
-                                path.clone()
-                            }
-                        };
-                        
-                        // Check if this is the current branch
-                        if current_ref == reference {
-                            if has_head {
-                                ref_names.push(format!("{} -> {}", 
-                                    Color::cyan("HEAD"), 
-                                    Color::green(&name)));
-                            } else {
-                                ref_names.push(Color::green(&name));
-                            }
-                        } else {
-                            ref_names.push(Color::green(&name));
-                        }
-                    },
-                    Reference::Direct(direct_oid) => {
-                        // Only include direct refs in full decoration mode
-                        if decorate == "full" {
-                            ref_names.push(direct_oid.clone());
-                        }
-                    }
-                }
-            }
-            
-            // If HEAD points to this commit but we have no branch to annotate
-            if has_head && ref_names.is_empty() {
-                ref_names.push(Color::cyan("HEAD"));
-            }
-            
-            // Format the final decoration
-            if !ref_names.is_empty() {
-                return format!(" ({})", ref_names.join(", "));
-            }
+    let refs = match commit.get_oid().and_then(|oid| reverse_refs.get(oid)) {
+        Some(refs) if !refs.is_empty() => refs,
+        _ => return String::new(),
+    };
+    
+    let display_name = |path: &str, prefix: &str| -> String {
+        if decorate == "full" {
+            path.to_string()
+        } else {
+            path.strip_prefix(prefix).unwrap_or(path).to_string()
+        }
+    };
+    
+    let mut has_head = false;
+    let mut head_branch = None;
+    let mut branches = Vec::new();
+    let mut remotes = Vec::new();
+    let mut tags = Vec::new();
+    
+    for reference in refs {
+        let path = match reference {
+            Reference::Symbolic(path) => path,
+            Reference::Direct(_) => continue,
+        };
+        
+        if path == "HEAD" {
+            has_head = true;
+        } else if path.starts_with("refs/tags/") {
+            tags.push(display_name(path, "refs/tags/"));
+        } else if path.starts_with("refs/remotes/") {
+            remotes.push(display_name(path, "refs/remotes/"));
+        } else if current_ref == reference {
+            head_branch = Some(display_name(path, "refs/heads/"));
+        } else {
+            branches.push(display_name(path, "refs/heads/"));
         }
     }
     
-    String::new()
+    branches.sort();
+    remotes.sort();
+    tags.sort();
+    
+    let mut names = Vec::new();
+    
+    // The checked-out branch only follows HEAD when HEAD itself points here
+    match (has_head, head_branch) {
+        (true, Some(branch)) => names.push(format!("{} {}", Color::bold(&Color::cyan("HEAD ->")), Color::bold(&Color::green(&branch)))),
+        (true, None) => names.push(Color::bold(&Color::cyan("HEAD"))),
+        (false, Some(branch)) => names.push(Color::bold(&Color::green(&branch))),
+        (false, None) => {},
+    }
+    
+    names.extend(branches.iter().map(|name| Color::bold(&Color::green(name))));
+    names.extend(remotes.iter().map(|name| Color::bold(&Color::red(name))));
+    names.extend(tags.iter().map(|name| Color::bold(&Color::yellow(&format!("tag: {}", name)))));
+    
+    if names.is_empty() {
+        return String::new();
+    }
+    
+    format!(" {}{}{}", Color::yellow("("), names.join(&Color::yellow(", ")), Color::yellow(")"))
 }
 
 // Display the diff for a commit
-fn show_patch(
+pub(crate) fn show_patch(
     pager: &mut Pager,
     database: &mut Database,
     parent_oid: Option<&str>,
@@ -532,4 +824,46 @@ fn is_binary_content(content: &[u8]) -> bool {
         .count();
     
     (non_text_count as f64 / sample_size as f64) > 0.3
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::commit::CommitCommand;
+    use crate::test_support::TestRepo;
+    
+    // Commits a change to f.txt with a fixed author date, returning the commit
+    fn commit_at(repo: &TestRepo, content: &str, author: &str, date: &str) -> Commit {
+        repo.write("f.txt", content);
+        repo.add(&["f.txt"]);
+        CommitCommand::execute(content, false, None, false, Some(author), Some(date), &[]).unwrap();
+        
+        let commit_obj = repo.repo().database.load(&repo.head()).unwrap();
+        commit_obj.as_any().downcast_ref::<Commit>().unwrap().clone()
+    }
+    
+    fn filter(options: &[(&str, &str)]) -> CommitFilter {
+        let options = options.iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        CommitFilter::from_options(&options).unwrap()
+    }
+    
+    #[test]
+    fn filters_commits_by_seeded_dates_and_author() {
+        let repo = TestRepo::new();
+        let early = commit_at(&repo, "early", "Ada <ada@example.com>", "2024-01-01 12:00:00");
+        let middle = commit_at(&repo, "middle", "Grace <grace@example.com>", "2024-01-15 18:30:00");
+        let late = commit_at(&repo, "late", "Ada <ada@example.com>", "2024-02-01 12:00:00");
+        let commits = [&early, &middle, &late];
+        let shown = |filter: &CommitFilter| -> Vec<String> {
+            commits.iter().filter(|commit| filter.matches(commit)).map(|commit| commit.title_line()).collect()
+        };
+        
+        // A bare --until date includes the whole day
+        assert_eq!(shown(&filter(&[("since", "2024-01-10"), ("until", "2024-01-15")])), vec!["middle"]);
+        assert_eq!(shown(&filter(&[("since", "2024-01-15 18:30:00")])), vec!["middle", "late"]);
+        assert_eq!(shown(&filter(&[("until", "2024-01-15 18:29:59")])), vec!["early"]);
+        assert_eq!(shown(&filter(&[("author", "Ada"), ("since", "2024-01-02")])), vec!["late"]);
+    }
+}
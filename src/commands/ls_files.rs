@@ -0,0 +1,33 @@
+use crate::errors::error::Error;
+use crate::core::repository::repository::Repository;
+
+/// Implements the 'ls-files' command for AsheraFlow.
+/// Lists the paths recorded in the index.
+pub struct LsFilesCommand;
+
+impl LsFilesCommand {
+    /// Prints each indexed path once, or one `<mode> <oid> <stage>\t<path>` line per entry
+    /// with `stage`. `unmerged` restricts the listing to conflict stages and implies `stage`.
+    pub fn execute(stage: bool, unmerged: bool) -> Result<(), Error> {
+        let mut repo = Repository::new(".")?;
+        repo.index.load()?;
+
+        let mut last_path: Option<&str> = None;
+
+        for entry in repo.index.each_entry() {
+            if unmerged && entry.stage == 0 {
+                continue;
+            }
+
+            if stage || unmerged {
+                println!("{} {} {}\t{}", entry.mode_octal(), entry.get_oid(), entry.stage, entry.get_path());
+            } else if last_path != Some(entry.get_path()) {
+                println!("{}", entry.get_path());
+            }
+
+            last_path = Some(entry.get_path());
+        }
+
+        Ok(())
+    }
+}
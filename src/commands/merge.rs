@@ -1,21 +1,26 @@
 /// Implements the 'merge' command for AsheraFlow.
 /// Handles merging changes from another branch and resolving conflicts.
 use std::time::Instant;
-use std::env;
 use std::path::{Path, PathBuf};
 use std::collections::{HashMap, HashSet};
 use crate::errors::error::Error;
-use crate::core::merge::inputs::Inputs;
-use crate::core::merge::resolve::Resolve;
+use crate::core::merge::inputs::{CherryPick, Inputs};
+use crate::core::merge::diff3::ConflictStyle;
+use crate::core::merge::resolve::{MergeStrategy, Resolve};
+use crate::core::progress::TerminalProgress;
 use crate::core::refs::Refs;
+use crate::core::config::Config;
+use crate::core::repository::pending_commit::{PendingCommit, PendingCommitType};
+use crate::core::repository::repository::Repository;
+use crate::commands::patch_mode;
+use crate::commands::reset::ORIG_HEAD;
 use crate::core::database::database::Database;
 use crate::core::database::commit::Commit;
 use crate::core::database::author::Author;
 use crate::core::path_filter::PathFilter;
 use crate::core::workspace::Workspace;
-use crate::core::database::tree::{Tree, TreeEntry};
+use crate::core::database::tree::{build_tree_from_index, Tree, TreeEntry};
 use crate::core::file_mode::FileMode;
-use crate::core::database::entry::DatabaseEntry;
 
 
 /// Main struct for the merge command logic.
@@ -23,29 +28,28 @@ pub struct MergeCommand;
 
 impl MergeCommand {
     /// Executes the merge command, merging changes from the specified revision.
+    /// With `no_ff` a merge commit is made even when HEAD could be fast-forwarded; with
+    /// `no_commit` the merge result is staged and MERGE_HEAD/MERGE_MSG are left for `ash commit`.
+    /// `conflict_style` picks the marker layout written into conflicted files.
     /// Returns an error if repository is not initialized or conflicts exist.
-    pub fn execute(revision: &str, message: Option<&str>) -> Result<(), Error> {
+    pub fn execute(revision: &str, message: Option<&str>, strategy: MergeStrategy, conflict_style: ConflictStyle, no_commit: bool, no_ff: bool) -> Result<(), Error> {
         let start_time = Instant::now();
 
         println!("Merge started...");
 
         // Initialize repository components
-        let root_path = Path::new(".");
-        let git_path = root_path.join(".ash");
+        let mut repo = Repository::new(".")?;
 
-        if !git_path.exists() {
+        if !repo.git_path.exists() {
             return Err(Error::Generic("Not an ash repository (or any of the parent directories): .ash directory not found".into()));
         }
 
-        let workspace = Workspace::new(root_path);
-        let mut database = Database::new(git_path.join("objects"));
-        let mut index = crate::core::index::index::Index::new(git_path.join("index"));
-        let refs = Refs::new(&git_path);
-
         // --- Lock index EARLY and ensure rollback on ANY error ---
-        if !index.load_for_update()? {
+        if !repo.load_index_for_update()? {
              return Err(Error::Lock("Failed to acquire lock on index".to_string()));
         }
+
+        let Repository { workspace, mut database, mut index, refs, git_path, .. } = repo;
         // Use a guard or closure to ensure rollback, or call manually in all error paths
         let result = (|| { // Start closure
 
@@ -65,7 +69,7 @@ impl MergeCommand {
                 return Err(Error::Generic("Already up to date.".into())); // Use error channel for special messages
             }
 
-            if inputs.is_fast_forward() {
+            if inputs.is_fast_forward() && !no_ff {
                 println!("Fast-forward possible.");
                 // Pass mutable refs to database and index into fast forward
                 return Self::handle_fast_forward(
@@ -81,10 +85,11 @@ impl MergeCommand {
 
             // --- Recursive Merge ---
              println!("Performing recursive merge.");
-            let mut merge_resolver = Resolve::new(&mut database, &workspace, &mut index, &inputs);
-            merge_resolver.on_progress = |info| println!("{}", info);
+            let progress = TerminalProgress::new("Updating files");
+            let mut merge_resolver = Resolve::new(&mut database, &workspace, &mut index, &inputs, strategy);
+            merge_resolver.conflict_style = conflict_style;
 
-             let merge_result = merge_resolver.execute();
+             let merge_result = merge_resolver.execute(&progress);
 
              if let Err(e) = merge_result {
                   if e.to_string().contains("Automatic merge failed") || e.to_string().contains("fix conflicts") {
@@ -108,29 +113,31 @@ impl MergeCommand {
             let commit_message = message.map(|s| s.to_string()).unwrap_or_else(|| {
                 format!("Merge branch '{}' into {}", revision, inputs.left_name)
             });
-             // Ensure Author details are configured
-             let author_name = env::var("GIT_AUTHOR_NAME").unwrap_or_else(|_| {
-                 eprintln!("Warning: GIT_AUTHOR_NAME not set. Using default.");
-                 "Default Author".to_string()
-             });
-             let author_email = env::var("GIT_AUTHOR_EMAIL").unwrap_or_else(|_| {
-                  eprintln!("Warning: GIT_AUTHOR_EMAIL not set. Using default.");
-                  "author@example.com".to_string()
-             });
-            let author = Author::new(author_name, author_email);
+
+            if no_commit {
+                // Leave the merge pending; `ash commit` picks it up from MERGE_HEAD and MERGE_MSG
+                let pending_commit = PendingCommit::new(&git_path);
+                pending_commit.start(&inputs.right_oid, PendingCommitType::Merge)?;
+                std::fs::write(&pending_commit.message_path, format!("{}\n", commit_message))
+                    .map_err(|e| Error::Generic(format!("Could not write MERGE_MSG: {}", e)))?;
+                std::fs::write(git_path.join(ORIG_HEAD), format!("{}\n", head_oid))
+                    .map_err(|e| Error::Generic(format!("Could not write ORIG_HEAD: {}", e)))?;
+
+                println!("Automatic merge went well; stopped before committing as requested");
+                return Ok(());
+            }
+            let author = Self::merge_author();
 
 
-            let tree_oid = Self::write_tree_from_index(&mut database, &index)?; // Pass immutable index now
+            let tree_oid = build_tree_from_index(&mut database, &index)?; // Pass immutable index now
 
             let parent1 = head_oid.clone();
             let parent2 = inputs.right_oid.clone();
-            let final_message = format!("{}\n\nMerge-Parent: {}", commit_message, parent2); // Simplified parent info
-
-             let mut commit = Commit::new( Some(parent1), tree_oid.clone(), author.clone(), final_message );
+             let mut commit = Commit::new( vec![parent1, parent2], tree_oid.clone(), author.clone(), commit_message );
 
              database.store(&mut commit)?;
              let commit_oid = commit.get_oid().cloned().ok_or(Error::Generic("Commit OID not set after storage".into()))?;
-             refs.update_head(&commit_oid)?;
+             refs.update_head(&commit_oid, &format!("merge {}: Merge made by the 'recursive' strategy.", revision))?;
 
              let elapsed = start_time.elapsed();
              println!("Merge completed successfully in {:.2}s", elapsed.as_secs_f32());
@@ -159,6 +166,154 @@ impl MergeCommand {
         result // Return the final result (Ok or Err)
     }
 
+    /// Merges several revisions into HEAD at once and records one commit with every tip as a parent.
+    /// Each tip is merged on top of the result of the previous ones. Octopus merges cannot be
+    /// resolved by hand, so the first conflict aborts the whole merge and restores the starting state.
+    pub fn execute_octopus(revisions: &[String], message: Option<&str>, strategy: MergeStrategy) -> Result<(), Error> {
+        let mut repo = Repository::new(".")?;
+
+        if !repo.git_path.exists() {
+            return Err(Error::Generic("Not an ash repository (or any of the parent directories): .ash directory not found".into()));
+        }
+
+        if !repo.load_index_for_update()? {
+            return Err(Error::Lock("Failed to acquire lock on index".to_string()));
+        }
+
+        let result = Self::octopus(&mut repo, revisions, message, strategy);
+        if result.is_err() {
+            repo.index.rollback()?;
+        }
+        result
+    }
+
+    fn octopus(repo: &mut Repository, revisions: &[String], message: Option<&str>, strategy: MergeStrategy) -> Result<(), Error> {
+        if repo.index.has_conflict() {
+            return Err(Error::Generic("Cannot merge with conflicts. Fix conflicts and commit first.".into()));
+        }
+
+        let head_oid = repo.refs.read_head()?
+            .ok_or_else(|| Error::Generic("No HEAD commit found. Create an initial commit first.".into()))?;
+        let head_files = patch_mode::commit_files(repo, &head_oid)?;
+
+        // Only the files that differ between HEAD and some tip can be rewritten by the merge
+        let mut tips: Vec<(String, Inputs)> = Vec::new();
+        let mut touched = HashSet::new();
+        for revision in revisions {
+            let inputs = Inputs::new(&mut repo.database, &repo.refs, "HEAD".to_string(), revision.clone())?;
+            if inputs.already_merged() {
+                println!("Already up to date with {}", revision);
+                continue;
+            }
+            if tips.iter().any(|(_, tip)| tip.right_oid == inputs.right_oid) {
+                continue;
+            }
+
+            let tip_files = patch_mode::commit_files(repo, &inputs.right_oid)?;
+            touched.extend(head_files.iter().filter(|(path, entry)| tip_files.get(*path) != Some(*entry)).map(|(path, _)| path.clone()));
+            touched.extend(tip_files.keys().filter(|path| !head_files.contains_key(*path)).cloned());
+            tips.push((revision.clone(), inputs));
+        }
+
+        if tips.is_empty() {
+            println!("Already up to date.");
+            repo.index.rollback()?;
+            return Ok(());
+        }
+
+        // Restoring on failure resets those files to HEAD, so they must not hold local work
+        let head_tree = Self::commit_tree(&mut repo.database, &head_oid)?;
+        if build_tree_from_index(&mut repo.database, &repo.index)? != head_tree {
+            return Err(Error::Generic("Your index has staged changes; commit or stash them before an octopus merge".into()));
+        }
+        for path in &touched {
+            let full_path = repo.workspace.root_path.join(path);
+            let unchanged = match head_files.get(path) {
+                Some((oid, _)) => full_path.is_file()
+                    && repo.workspace.hash_file(Path::new(path))? == *oid,
+                None => !full_path.exists(),
+            };
+            if !unchanged {
+                return Err(Error::Generic(format!("Your local changes to '{}' would be overwritten by merge; commit or stash them first", path)));
+            }
+        }
+
+        let progress = TerminalProgress::new("Updating files");
+        let mut tree_oid = head_tree;
+        for (revision, inputs) in &tips {
+            println!("Trying simple merge with {}", revision);
+            let step = CherryPick::new(
+                "HEAD".to_string(),
+                revision.clone(),
+                tree_oid.clone(),
+                inputs.right_oid.clone(),
+                inputs.base_oids.clone(),
+            );
+            let mut merge_resolver = Resolve::new(&mut repo.database, &repo.workspace, &mut repo.index, &step, strategy);
+            let merged = merge_resolver.execute(&progress);
+
+            if merged.is_err() || repo.index.has_conflict() {
+                Self::restore_files(repo, &head_files, &touched)?;
+                return Err(Error::Generic(format!("Merge with strategy octopus failed: {} does not merge cleanly; no changes were made", revision)));
+            }
+            tree_oid = build_tree_from_index(&mut repo.database, &repo.index)?;
+        }
+
+        repo.index.write_updates()?;
+
+        let names: Vec<String> = tips.iter().map(|(revision, _)| format!("'{}'", revision)).collect();
+        let commit_message = message.map(|s| s.to_string()).unwrap_or_else(|| {
+            let (last, rest) = names.split_last().unwrap();
+            if rest.is_empty() {
+                format!("Merge branch {}", last)
+            } else {
+                format!("Merge branches {} and {}", rest.join(", "), last)
+            }
+        });
+
+        let mut parents = vec![head_oid];
+        parents.extend(tips.iter().map(|(_, inputs)| inputs.right_oid.clone()));
+        let mut commit = Commit::new(parents, tree_oid, Self::merge_author(), commit_message);
+        repo.database.store(&mut commit)?;
+        let commit_oid = commit.get_oid().cloned().ok_or(Error::Generic("Commit OID not set after storage".into()))?;
+        let merged: Vec<&str> = tips.iter().map(|(revision, _)| revision.as_str()).collect();
+        repo.refs.update_head(&commit_oid, &format!("merge {}: Merge made by the 'octopus' strategy.", merged.join(", ")))?;
+
+        println!("Merge made by the 'octopus' strategy.");
+        Ok(())
+    }
+
+    /// Puts the given files back to their HEAD version, removing those HEAD does not have.
+    fn restore_files(repo: &mut Repository, head_files: &HashMap<String, (String, FileMode)>, paths: &HashSet<String>) -> Result<(), Error> {
+        for path in paths {
+            let rel_path = Path::new(path);
+            match head_files.get(path) {
+                Some((oid, mode)) => {
+                    let blob = repo.database.load(oid)?;
+                    let content = repo.workspace.denormalize_eol(path, blob.to_bytes());
+                    repo.workspace.write_file(rel_path, &content)?;
+                    repo.workspace.set_mode(rel_path, *mode)?;
+                },
+                None => repo.workspace.remove_file(rel_path)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn commit_tree(database: &mut Database, commit_oid: &str) -> Result<String, Error> {
+        let commit_obj = database.load(commit_oid)?;
+        match commit_obj.as_any().downcast_ref::<Commit>() {
+            Some(commit) => Ok(commit.get_tree().to_string()),
+            None => Err(Error::Generic(format!("Object {} is not a commit", commit_oid))),
+        }
+    }
+
+    /// Author for merge commits, taken from user.name and user.email in the config,
+    /// or else GIT_AUTHOR_NAME and GIT_AUTHOR_EMAIL.
+    fn merge_author() -> Author {
+        Config::current().identity(&["GIT_AUTHOR"])
+    }
+
 
     // --- *** REVISED handle_fast_forward using DIFF approach *** ---
     fn handle_fast_forward(
@@ -286,7 +441,7 @@ impl MergeCommand {
 
         // 6. Update HEAD reference
         println!("Attempting to update HEAD to {}", target_oid);
-        match refs.update_head(target_oid) {
+        match refs.update_head(target_oid, &format!("merge {}: Fast-forward", b_short)) {
             Ok(_) => println!("Successfully updated HEAD"),
             Err(e) => {
                 println!("ERROR updating HEAD: {}", e);
@@ -402,34 +557,97 @@ impl MergeCommand {
         let blob_obj = database.load(oid)?;
         let content = blob_obj.to_bytes();
         workspace.write_file(&path, &content)?;
+        workspace.set_mode(path, *mode)?;
         let stat = workspace.stat_file(&path)?;
         index.add(&path, oid, &stat)?;
         Ok(())
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::branch::BranchCommand;
+    use crate::commands::checkout::CheckoutCommand;
+    use crate::commands::rm::RmCommand;
+    use crate::test_support::TestRepo;
+
+    // Edits the middle line of f.txt differently on master and topic, with an unrelated
+    // file added on topic, then merges topic into master with `strategy`
+    fn merge_conflicting_edits(repo: &TestRepo, strategy: MergeStrategy) {
+        repo.commit_files(&[("f.txt", "a\nb\nc\n")], "base");
+        BranchCommand::execute("topic", None).unwrap();
+        let ours = repo.commit_files(&[("f.txt", "a\nours\nc\n")], "ours");
+        CheckoutCommand::execute("topic").unwrap();
+        repo.commit_files(&[("f.txt", "a\ntheirs\nc\n"), ("g.txt", "topic only\n")], "theirs");
+        CheckoutCommand::execute("master").unwrap();
+
+        MergeCommand::execute("topic", Some("merge topic"), strategy, ConflictStyle::Merge, false, false).unwrap();
+
+        let head = repo.head();
+        let commit_obj = repo.repo().database.load(&head).unwrap();
+        let commit = commit_obj.as_any().downcast_ref::<Commit>().unwrap();
+        assert_eq!(commit.get_parent(), Some(&ours));
+        assert_eq!(repo.read("g.txt"), "topic only\n");
+    }
 
-    // --- write_tree_from_index - Takes immutable index ---
-    fn write_tree_from_index(database: &mut Database, index: &crate::core::index::index::Index) -> Result<String, Error> {
-        let database_entries: Vec<_> = index.each_entry()
-            .filter(|entry| entry.stage == 0) // Only include stage 0 entries
-            .map(|index_entry| {
-                DatabaseEntry::new(
-                    index_entry.get_path().to_string(),
-                    index_entry.get_oid().to_string(),
-                    &index_entry.mode_octal()
-                )
-            })
-            .collect();
-
-         if database_entries.is_empty() {
-              let mut empty_tree = Tree::new();
-              database.store(&mut empty_tree)?;
-              return empty_tree.get_oid().cloned().ok_or_else(|| Error::Generic("Failed to get OID for empty tree".into()));
-         }
+    #[test]
+    fn ours_strategy_keeps_our_side_of_conflicts() {
+        let repo = TestRepo::new();
+        merge_conflicting_edits(&repo, MergeStrategy::Ours);
+
+        assert_eq!(repo.read("f.txt"), "a\nours\nc\n");
+        assert_eq!(repo.staged_oid("f.txt"), Some(repo.blob_oid("a\nours\nc\n")));
+    }
+
+    #[test]
+    fn theirs_strategy_takes_their_side_of_conflicts() {
+        let repo = TestRepo::new();
+        merge_conflicting_edits(&repo, MergeStrategy::Theirs);
+
+        assert_eq!(repo.read("f.txt"), "a\ntheirs\nc\n");
+        assert_eq!(repo.staged_oid("f.txt"), Some(repo.blob_oid("a\ntheirs\nc\n")));
+    }
+
+    #[test]
+    fn edits_follow_a_rename_on_the_other_branch() {
+        let repo = TestRepo::new();
+        repo.commit_files(&[("old.txt", "one\ntwo\nthree\nfour\nfive\n")], "base");
+        BranchCommand::execute("topic", None).unwrap();
+        repo.commit_files(&[("old.txt", "one\ntwo\nthree\nfour\nFIVE\n")], "edit");
+        CheckoutCommand::execute("topic").unwrap();
+        RmCommand::execute(&["old.txt".to_string()], false, false, false, false).unwrap();
+        repo.commit_files(&[("new.txt", "ONE\ntwo\nthree\nfour\nfive\n")], "rename");
+        CheckoutCommand::execute("master").unwrap();
+
+        MergeCommand::execute("topic", Some("merge topic"), MergeStrategy::Recursive, ConflictStyle::Merge, false, false).unwrap();
+
+        assert!(!repo.exists("old.txt"));
+        assert_eq!(repo.read("new.txt"), "ONE\ntwo\nthree\nfour\nFIVE\n");
+        assert_eq!(repo.staged_oid("old.txt"), None);
+    }
 
-        let mut root = crate::core::database::tree::Tree::build(database_entries.iter())?;
-        root.traverse(|tree| database.store(tree).map(|_| ()))?;
-        let tree_oid = root.get_oid()
-            .ok_or(Error::Generic("Tree OID not set after storage".into()))?;
-        Ok(tree_oid.clone())
+    #[test]
+    fn octopus_merges_branches_editing_different_files() {
+        let repo = TestRepo::new();
+        repo.commit_files(&[("a.txt", "a\n"), ("b.txt", "b\n")], "base");
+        BranchCommand::execute("ba", None).unwrap();
+        BranchCommand::execute("bb", None).unwrap();
+        CheckoutCommand::execute("ba").unwrap();
+        let ba = repo.commit_files(&[("a.txt", "a from ba\n")], "edit a");
+        CheckoutCommand::execute("bb").unwrap();
+        let bb = repo.commit_files(&[("b.txt", "b from bb\n")], "edit b");
+        CheckoutCommand::execute("master").unwrap();
+        let master = repo.head();
+
+        MergeCommand::execute_octopus(&["ba".to_string(), "bb".to_string()], None, MergeStrategy::Recursive).unwrap();
+
+        assert_eq!(repo.read("a.txt"), "a from ba\n");
+        assert_eq!(repo.read("b.txt"), "b from bb\n");
+        let head = repo.head();
+        let commit_obj = repo.repo().database.load(&head).unwrap();
+        let commit = commit_obj.as_any().downcast_ref::<Commit>().unwrap();
+        assert_eq!(commit.get_parents(), [master, ba, bb]);
+        assert_eq!(commit.get_message().trim(), "Merge branches 'ba' and 'bb'");
     }
-}
\ No newline at end of file
+}
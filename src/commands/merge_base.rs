@@ -0,0 +1,54 @@
+use crate::core::merge::bases::merge_base;
+use crate::core::merge::common_ancestors;
+use crate::core::repository::repository::Repository;
+use crate::core::revision::Revision;
+use crate::errors::error::Error;
+
+/// Implements the 'merge-base' command for AsheraFlow.
+/// Answers ancestry questions about commits for scripts and other commands.
+pub struct MergeBaseCommand;
+
+impl MergeBaseCommand {
+    /// Executes the merge-base command.
+    /// Prints the best common ancestor of the two commits, or every one of them with `all`.
+    /// With `--is-ancestor`, returns whether the first commit is an ancestor of the second without printing anything.
+    /// Returns false when the commits share no history.
+    pub fn execute(revisions: &[String], is_ancestor: bool, all: bool) -> Result<bool, Error> {
+        if revisions.len() != 2 {
+            return Err(Error::Generic("merge-base requires exactly two commits".to_string()));
+        }
+
+        let mut repo = Repository::new(".")?;
+        let one = Self::resolve_commit(&mut repo, &revisions[0])?;
+        let two = Self::resolve_commit(&mut repo, &revisions[1])?;
+
+        if is_ancestor {
+            return common_ancestors::is_ancestor(&mut repo.database, &one, &two);
+        }
+
+        let bases = merge_base(&mut repo.database, &one, &two)?;
+        let shown = if all { bases.len() } else { bases.len().min(1) };
+        for base in &bases[..shown] {
+            println!("{}", base);
+        }
+
+        Ok(!bases.is_empty())
+    }
+
+    /// Resolves a revision expression to a commit OID, printing any resolution hints.
+    fn resolve_commit(repo: &mut Repository, expr: &str) -> Result<String, Error> {
+        let mut revision = Revision::new(repo, expr);
+        match revision.resolve("commit") {
+            Ok(oid) => Ok(oid),
+            Err(e) => {
+                for err in revision.errors {
+                    eprintln!("error: {}", err.message);
+                    for hint in &err.hint {
+                        eprintln!("hint: {}", hint);
+                    }
+                }
+                Err(e)
+            }
+        }
+    }
+}
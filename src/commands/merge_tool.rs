@@ -9,11 +9,13 @@ use std::io::{self, Write};
 use std::collections::{HashMap, HashSet};
 
 use crate::errors::error::Error;
+use crate::core::repository::repository::Repository;
 use crate::core::index::index::Index;
 use crate::core::workspace::Workspace;
 use crate::core::database::database::Database;
 use crate::core::database::blob::Blob;
 use crate::core::color::Color;
+use crate::core::config::Config;
 
 /// Main struct for the merge-tool command logic.
 pub struct MergeToolCommand;
@@ -43,17 +45,16 @@ impl MergeToolCommand {
             return Err(Error::Generic("Not an ash repository (or any of the parent directories): .ash directory not found".into()));
         }
         
-        let workspace = Workspace::new(root_path);
-        let mut database = Database::new(git_path.join("objects"));
-        let mut index = Index::new(git_path.join("index"));
+        let mut repo = Repository::new(".")?;
         
         // Try to acquire the lock on the index
-        if !index.load_for_update()? {
+        if !repo.load_index_for_update()? {
             return Err(Error::Lock(format!(
                 "Unable to acquire lock on index. Another process may be using it. \
                 If not, the .ash/index.lock file may need to be manually removed."
             )));
         }
+        let Repository { workspace, mut database, mut index, .. } = repo;
         
         // Check if there are conflicts to resolve
         if !index.has_conflict() {
@@ -683,7 +684,11 @@ impl MergeToolCommand {
             return Self::check_tool_available(tool_name);
         }
         
-        // Next, check environment variables
+        // Next, check the configured editor and environment variables
+        if let Some(editor) = Config::current().get("core", "editor") {
+            return Self::check_tool_available(editor);
+        }
+        
         if let Ok(editor) = env::var("ASH_EDITOR") {
             return Self::check_tool_available(&editor);
         }
@@ -5,15 +5,32 @@ pub mod commit;
 pub mod add;
 pub mod status;
 pub mod diff;
+pub mod apply;
 pub mod branch;
 pub mod checkout;
 pub mod log;
+pub mod show;
+pub mod cat_file;
+pub mod fsck;
+pub mod verify_objects;
 pub mod merge;
 pub mod merge_tool;
+pub mod merge_base;
+pub mod rev_parse;
+pub mod tag;
+pub mod reflog;
+pub mod stash;
 pub mod rm;
+pub mod ls_files;
+pub mod gc;
+pub mod prune;
 pub mod reset;
+pub mod restore;
+pub mod config;
 pub mod commit_writer;
 pub mod cherry_pick;
 pub mod revert;
+pub mod sequencing;
 pub mod sprint;
-pub mod task;
\ No newline at end of file
+pub mod task;
+pub mod patch_mode;
\ No newline at end of file
@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use crate::errors::error::Error;
+use crate::core::color::Color;
+use crate::core::database::commit::Commit;
+use crate::core::database::tree::{Tree, TreeEntry};
+use crate::core::diff::diff::NO_NEWLINE;
+use crate::core::diff::hunks::Hunk;
+use crate::core::file_mode::FileMode;
+use crate::core::repository::repository::Repository;
+use crate::core::workspace::path_key;
+
+/// Hunks chosen for one file in an interactive `--patch` session
+pub struct PatchSelection {
+    /// One flag per hunk, set for the hunks the user accepted
+    pub selected: Vec<bool>,
+    /// The user asked to stop; no further files should be offered
+    pub quit: bool,
+}
+
+impl PatchSelection {
+    /// Returns the hunks with the given selection state, in order
+    pub fn hunks<'a>(&self, hunks: &'a [Hunk], selected: bool) -> Vec<&'a Hunk> {
+        hunks.iter()
+            .zip(&self.selected)
+            .filter(|(_, &s)| s == selected)
+            .map(|(hunk, _)| hunk)
+            .collect()
+    }
+}
+
+/// Shows each hunk of `path` and asks `<question> [y,n,q,a,d,?]?` until every hunk is decided.
+/// Undecided hunks are left unselected when the user quits or skips the rest of the file.
+pub fn select_hunks(path: &str, hunks: &[Hunk], question: &str) -> Result<PatchSelection, Error> {
+    select_hunks_from(&mut io::stdin().lock(), path, hunks, question)
+}
+
+// The prompt loop of `select_hunks`, reading answers from `input`
+fn select_hunks_from(input: &mut dyn BufRead, path: &str, hunks: &[Hunk], question: &str) -> Result<PatchSelection, Error> {
+    let mut selection = PatchSelection {
+        selected: vec![false; hunks.len()],
+        quit: false,
+    };
+
+    println!("{}", Color::bold(&format!("diff --ash a/{} b/{}", path, path)));
+
+    let mut i = 0;
+    while i < hunks.len() {
+        print!("{}", hunks[i].to_colored_string());
+        print!("{} ({}/{}) [y,n,q,a,d,?]? ", Color::blue(question), i + 1, hunks.len());
+        io::stdout().flush()?;
+
+        let mut answer = String::new();
+        if input.read_line(&mut answer)? == 0 {
+            // End of input: treat like quitting
+            selection.quit = true;
+            break;
+        }
+
+        match answer.trim() {
+            "y" => selection.selected[i] = true,
+            "n" => {},
+            "q" => {
+                selection.quit = true;
+                break;
+            },
+            "a" => {
+                for selected in &mut selection.selected[i..] {
+                    *selected = true;
+                }
+                break;
+            },
+            "d" => break,
+            _ => {
+                println!("y - {} this hunk", question.to_lowercase());
+                println!("n - do not {} this hunk", question.to_lowercase());
+                println!("q - quit; do not {} this hunk or any of the remaining ones", question.to_lowercase());
+                println!("a - {} this hunk and all later hunks in the file", question.to_lowercase());
+                println!("d - do not {} this hunk or any of the later hunks in the file", question.to_lowercase());
+                println!("? - print help");
+                continue;
+            },
+        }
+
+        i += 1;
+    }
+
+    Ok(selection)
+}
+
+/// Every file in the tree of `commit_oid`, as path -> (oid, mode)
+pub fn commit_files(repo: &mut Repository, commit_oid: &str) -> Result<HashMap<String, (String, FileMode)>, Error> {
+    let commit_obj = repo.database.load(commit_oid)?;
+    let tree_oid = match commit_obj.as_any().downcast_ref::<Commit>() {
+        Some(commit) => commit.get_tree().to_string(),
+        None => return Err(Error::Generic(format!("Object {} is not a commit", commit_oid))),
+    };
+
+    let mut files = HashMap::new();
+    collect_tree_files(repo, &tree_oid, Path::new(""), &mut files)?;
+    Ok(files)
+}
+
+fn collect_tree_files(repo: &mut Repository, tree_oid: &str, prefix: &Path, files: &mut HashMap<String, (String, FileMode)>) -> Result<(), Error> {
+    let tree_obj = repo.database.load(tree_oid)?;
+    let tree = match tree_obj.as_any().downcast_ref::<Tree>() {
+        Some(tree) => tree,
+        None => return Ok(()),
+    };
+
+    for (name, entry) in tree.get_entries() {
+        let entry_path = prefix.join(name);
+        match entry {
+            TreeEntry::Blob(oid, mode) => {
+                files.insert(path_key(&entry_path), (oid.clone(), *mode));
+            },
+            TreeEntry::Tree(subtree) => {
+                if let Some(subtree_oid) = subtree.get_oid() {
+                    collect_tree_files(repo, subtree_oid, &entry_path, files)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether `path` is selected by `pathspecs`: the path itself, a directory containing it, or `.`.
+/// No pathspecs selects everything.
+pub fn matches_pathspec(path: &str, pathspecs: &[String]) -> bool {
+    pathspecs.is_empty() || pathspecs.iter().any(|spec| {
+        let spec = spec.trim_end_matches('/');
+        spec == "." || path == spec || path.starts_with(&format!("{}/", spec))
+    })
+}
+
+/// Joins lines from `diff::split_lines` back into file content. Every line ends with a newline
+/// except a last line carrying the missing-newline marker; a marked line that is no longer
+/// last (lines were added after it) gets its newline back.
+pub fn join_lines(lines: &[String]) -> Vec<u8> {
+    let mut content = String::new();
+    for (i, line) in lines.iter().enumerate() {
+        let unterminated = line.strip_suffix(NO_NEWLINE);
+        content.push_str(unterminated.unwrap_or(line));
+        if unterminated.is_none() || i + 1 < lines.len() {
+            content.push('\n');
+        }
+    }
+    content.into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::diff::hunks::{apply_hunks, build_hunks};
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(|line| format!("{}\n", line)).collect()
+    }
+
+    // Two changes far enough apart to land in separate hunks
+    fn two_hunks() -> (Vec<String>, Vec<Hunk>) {
+        let a = lines("1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12");
+        let b = lines("one\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\ntwelve");
+        let hunks = build_hunks(&a, &b, 3);
+        (a, hunks)
+    }
+
+    #[test]
+    fn answers_select_hunks_in_order() {
+        let (a, hunks) = two_hunks();
+        let selection = select_hunks_from(&mut "n\ny\n".as_bytes(), "f.txt", &hunks, "Stage").unwrap();
+        assert_eq!(selection.selected, vec![false, true]);
+        assert!(!selection.quit);
+
+        let applied = apply_hunks(&a, &selection.hunks(&hunks, true));
+        assert_eq!(applied, lines("1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\ntwelve"));
+        let skipped = apply_hunks(&a, &selection.hunks(&hunks, false));
+        assert_eq!(skipped, lines("one\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12"));
+    }
+
+    #[test]
+    fn help_repeats_the_hunk_and_all_selects_the_rest() {
+        let (_, hunks) = two_hunks();
+        let selection = select_hunks_from(&mut "?\na\n".as_bytes(), "f.txt", &hunks, "Stage").unwrap();
+        assert_eq!(selection.selected, vec![true, true]);
+        assert!(!selection.quit);
+    }
+
+    #[test]
+    fn quit_and_end_of_input_leave_later_hunks_unselected() {
+        let (_, hunks) = two_hunks();
+        let selection = select_hunks_from(&mut "y\nq\n".as_bytes(), "f.txt", &hunks, "Stage").unwrap();
+        assert_eq!(selection.selected, vec![true, false]);
+        assert!(selection.quit);
+
+        let selection = select_hunks_from(&mut "".as_bytes(), "f.txt", &hunks, "Stage").unwrap();
+        assert_eq!(selection.selected, vec![false, false]);
+        assert!(selection.quit);
+    }
+}
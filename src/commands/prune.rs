@@ -0,0 +1,273 @@
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use crate::errors::error::Error;
+use crate::core::database::author::Author;
+use crate::core::repository::repository::Repository;
+use crate::core::refs::Reference;
+
+/// Expiry used without `--expire`: unreachable objects younger than this are kept
+const DEFAULT_EXPIRE: &str = "2.weeks.ago";
+
+/// Pseudo-refs written by merges, resets and the sequencer, holding one commit ID per line
+/// (MERGE_HEAD lists every merged head of an octopus merge)
+const PSEUDO_REFS: [&str; 5] = ["ORIG_HEAD", "MERGE_HEAD", "CHERRY_PICK_HEAD", "REVERT_HEAD", "sequencer/abort-safety"];
+
+/// Implements the 'prune' command for AsheraFlow.
+/// Deletes loose objects that can't be reached from any ref.
+pub struct PruneCommand;
+
+impl PruneCommand {
+    /// Removes loose objects unreachable from refs, HEAD, pseudo-refs, the index, the sequencer
+    /// and reflogs, as long as they are older than `expire` (two weeks by default; "now" prunes
+    /// them all). An explicit `expire` also stops reflog entries made before it from keeping
+    /// objects alive, except in the stash reflog. With `dry_run` nothing is deleted.
+    pub fn execute(dry_run: bool, expire: Option<&str>) -> Result<(), Error> {
+        let cutoff = Self::parse_expire(expire.unwrap_or(DEFAULT_EXPIRE))?;
+        let mut repo = Repository::new(".")?;
+        let git_path = repo.git_path.clone();
+
+        // Deleting objects is only safe in a store whose roots are all known here
+        if git_path.file_name().is_none_or(|name| name != ".ash") || !git_path.is_dir() {
+            return Err(Error::Generic(format!("refusing to prune '{}': not an ash repository", git_path.display())));
+        }
+
+        let mut roots = Vec::new();
+
+        if let Some(oid) = repo.refs.read_head()? {
+            roots.push(oid);
+        }
+
+        for reference in repo.refs.list_refs_with_prefix("refs/")? {
+            if let Reference::Symbolic(path) = reference {
+                // Some refs (e.g. refs/meta/tasksprint/*) hold names rather than object IDs
+                if let Some(value) = repo.refs.read_ref(&path)? {
+                    roots.push(value);
+                }
+            }
+        }
+
+        for name in PSEUDO_REFS {
+            if let Ok(content) = fs::read_to_string(git_path.join(name)) {
+                roots.extend(content.lines().filter_map(|line| line.split_whitespace().next()).map(String::from));
+            }
+        }
+
+        // Commits still to be picked or reverted
+        if let Ok(todo) = fs::read_to_string(git_path.join("sequencer").join("todo")) {
+            for line in todo.lines() {
+                if let Some(oid) = line.split_whitespace().nth(1) {
+                    roots.extend(repo.database.resolve_oid(oid).ok());
+                }
+            }
+        }
+
+        // Staged blobs that haven't been committed yet
+        repo.index.load()?;
+        roots.extend(repo.index.each_entry().map(|entry| entry.get_oid().to_string()));
+
+        let logs_path = git_path.join("logs");
+        let stash_log = logs_path.join("refs").join("stash");
+        // The stash reflog is the stash list itself, not recovery history, so it never expires
+        let reflog_cutoff = expire.map(|_| cutoff);
+        Self::collect_reflog_oids(&logs_path, &stash_log, reflog_cutoff, &mut roots)?;
+
+        roots.retain(|oid| oid.len() == 40 && oid.chars().all(|c| c.is_ascii_hexdigit()));
+
+        let reachable = repo.database.reachable_from(roots)?;
+        let pruned = repo.database.prune(&reachable, cutoff, dry_run)?;
+        let total: u64 = pruned.iter().map(|(_, size)| size).sum();
+
+        if dry_run {
+            for (oid, size) in &pruned {
+                println!("{} ({} bytes)", oid, size);
+            }
+            println!("Would prune {} objects, {} bytes", pruned.len(), total);
+        } else {
+            println!("Pruned {} objects, {} bytes", pruned.len(), total);
+        }
+
+        Ok(())
+    }
+
+    /// Collects the old and new object IDs of every entry in the reflog at `path`,
+    /// or in every reflog below it when `path` is a directory. Entries made before `cutoff`
+    /// are skipped, except in `stash_log`.
+    fn collect_reflog_oids(path: &Path, stash_log: &Path, cutoff: Option<SystemTime>, roots: &mut Vec<String>) -> Result<(), Error> {
+        if path.is_dir() {
+            for entry in fs::read_dir(path)? {
+                Self::collect_reflog_oids(&entry?.path(), stash_log, cutoff, roots)?;
+            }
+        } else if path.is_file() {
+            let cutoff = cutoff.filter(|_| path != stash_log);
+            for line in fs::read_to_string(path)?.lines() {
+                // "<old> <new> <name> <email> <timestamp> <timezone>\t<message>"
+                let head = line.split('\t').next().unwrap_or("");
+                let fields: Vec<&str> = head.split_whitespace().collect();
+                if let Some(cutoff) = cutoff {
+                    let made = fields.len().checked_sub(2)
+                        .and_then(|i| fields[i].parse::<u64>().ok())
+                        .map(|seconds| UNIX_EPOCH + Duration::from_secs(seconds));
+                    if made.is_some_and(|made| made < cutoff) {
+                        continue;
+                    }
+                }
+                roots.extend(fields.iter().take(2).map(|oid| oid.to_string()));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Parses an `--expire` value: "now", "<n>.<unit>.ago" with units from seconds to weeks
+    /// (e.g. "2.weeks.ago"), or any date `commit --date` accepts
+    fn parse_expire(expire: &str) -> Result<SystemTime, Error> {
+        let now = SystemTime::now();
+        if expire == "now" {
+            return Ok(now);
+        }
+
+        let parts: Vec<&str> = expire.split('.').collect();
+        if let [count, unit, "ago"] = parts[..] {
+            let seconds = match unit.trim_end_matches('s') {
+                "second" => 1,
+                "minute" => 60,
+                "hour" => 60 * 60,
+                "day" => 24 * 60 * 60,
+                "week" => 7 * 24 * 60 * 60,
+                _ => return Err(Error::Generic(format!("invalid expiry unit '{}' in '{}'", unit, expire))),
+            };
+            let count: u64 = count.parse()
+                .map_err(|_| Error::Generic(format!("invalid expiry '{}'", expire)))?;
+            return Ok(now.checked_sub(Duration::from_secs(count * seconds)).unwrap_or(UNIX_EPOCH));
+        }
+
+        let date = Author::parse_date(expire)
+            .map_err(|_| Error::Generic(format!("invalid expiry '{}'", expire)))?;
+        Ok(UNIX_EPOCH + Duration::from_secs(date.timestamp().max(0) as u64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use crate::core::database::blob::Blob;
+    use crate::core::database::commit::Commit;
+    use crate::core::database::tree::Tree;
+    use crate::test_support::TestRepo;
+
+    const THREE_WEEKS: Duration = Duration::from_secs(3 * 7 * 24 * 60 * 60);
+
+    fn store_blob(repo: &TestRepo, content: &str) -> String {
+        repo.repo().database.store(&mut Blob::new(content.as_bytes().to_vec())).unwrap()
+    }
+
+    /// Makes every loose object look `age` old
+    fn age_objects(repo: &TestRepo, age: Duration) {
+        let database = repo.repo().database;
+        for oid in database.loose_objects().unwrap() {
+            let path = database.pathname.join(&oid[..2]).join(&oid[2..]);
+            File::options().write(true).open(path).unwrap().set_modified(SystemTime::now() - age).unwrap();
+        }
+    }
+
+    /// Moves every reflog entry back to 2001
+    fn backdate_reflogs(path: &Path) {
+        if path.is_dir() {
+            for entry in fs::read_dir(path).unwrap() {
+                backdate_reflogs(&entry.unwrap().path());
+            }
+            return;
+        }
+
+        let mut content = String::new();
+        for line in fs::read_to_string(path).unwrap().lines() {
+            let (head, message) = line.split_once('\t').unwrap();
+            let mut fields: Vec<&str> = head.split(' ').collect();
+            let timestamp = fields.len() - 2;
+            fields[timestamp] = "1000000000";
+            content.push_str(&format!("{}\t{}\n", fields.join(" "), message));
+        }
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn unreachable_objects_are_kept_until_they_expire() {
+        let repo = TestRepo::new();
+        let head = repo.commit_files(&[("f.txt", "one\n")], "first");
+        let old = store_blob(&repo, "old\n");
+        age_objects(&repo, THREE_WEEKS);
+        let fresh = store_blob(&repo, "fresh\n");
+
+        PruneCommand::execute(false, None).unwrap();
+        let database = repo.repo().database;
+        assert!(!database.exists(&old));
+        assert!(database.exists(&fresh));
+
+        PruneCommand::execute(false, Some("1.hour.ago")).unwrap();
+        assert!(repo.repo().database.exists(&fresh));
+
+        PruneCommand::execute(false, Some("now")).unwrap();
+        let database = repo.repo().database;
+        assert!(!database.exists(&fresh));
+        assert!(database.exists(&head));
+        assert_eq!(repo.read("f.txt"), "one\n");
+    }
+
+    #[test]
+    fn every_merge_head_line_is_a_root() {
+        let repo = TestRepo::new();
+        repo.commit_files(&[("f.txt", "one\n")], "first");
+
+        // The merged heads of an octopus merge that nothing else refers to
+        let mut database = repo.repo().database;
+        let tree = database.store(&mut Tree::new()).unwrap();
+        let author = Author::new("Test Author".to_string(), "author@test.invalid".to_string());
+        let heads: Vec<String> = ["second", "third"].iter()
+            .map(|message| database.store(&mut Commit::new(Vec::new(), tree.clone(), author.clone(), message.to_string())).unwrap())
+            .collect();
+        fs::write(repo.path().join(".ash/MERGE_HEAD"), format!("{}\n{}\n", heads[0], heads[1])).unwrap();
+
+        PruneCommand::execute(false, Some("now")).unwrap();
+        let database = repo.repo().database;
+        assert!(heads.iter().all(|head| database.exists(head)));
+        assert!(database.exists(&tree));
+
+        fs::remove_file(repo.path().join(".ash/MERGE_HEAD")).unwrap();
+        PruneCommand::execute(false, Some("now")).unwrap();
+        assert!(heads.iter().all(|head| !repo.repo().database.exists(head)));
+    }
+
+    #[test]
+    fn reflog_entries_before_an_explicit_expiry_are_not_roots() {
+        let repo = TestRepo::new();
+        let first = repo.commit_files(&[("f.txt", "one\n")], "first");
+        let second = repo.commit_files(&[("f.txt", "two\n")], "second");
+        repo.repo().refs.update_head(&first, "reset: moving to first").unwrap();
+        age_objects(&repo, THREE_WEEKS);
+
+        // Only the reflog still refers to the second commit
+        PruneCommand::execute(false, Some("1.week.ago")).unwrap();
+        assert!(repo.repo().database.exists(&second));
+
+        backdate_reflogs(&repo.path().join(".ash/logs"));
+        PruneCommand::execute(false, None).unwrap();
+        assert!(repo.repo().database.exists(&second));
+
+        PruneCommand::execute(false, Some("1.week.ago")).unwrap();
+        let database = repo.repo().database;
+        assert!(!database.exists(&second));
+        assert!(database.exists(&first));
+    }
+
+    #[test]
+    fn expiry_values_parse() {
+        let two_weeks = PruneCommand::parse_expire("2.weeks.ago").unwrap();
+        let age = SystemTime::now().duration_since(two_weeks).unwrap().as_secs();
+        assert!((14 * 24 * 60 * 60..14 * 24 * 60 * 60 + 5).contains(&age), "{}", age);
+        assert_eq!(PruneCommand::parse_expire("2024-05-01").unwrap(), UNIX_EPOCH + Duration::from_secs(1714521600));
+        assert!(PruneCommand::parse_expire("2.fortnights.ago").is_err());
+        assert!(PruneCommand::parse_expire("soon").is_err());
+    }
+}
@@ -0,0 +1,31 @@
+use crate::errors::error::Error;
+use crate::core::repository::repository::Repository;
+use crate::core::color::Color;
+use crate::core::refs::HEAD;
+
+/// Implements the 'reflog' command for AsheraFlow.
+/// Shows where a ref (HEAD by default) has pointed over time.
+pub struct ReflogCommand;
+
+impl ReflogCommand {
+    /// Prints the reflog of `ref_name` newest-first, one `<oid> <ref>@{n}: <message>` line per entry.
+    pub fn execute(ref_name: Option<&str>) -> Result<(), Error> {
+        let repo = Repository::new(".")?;
+        let ref_name = ref_name.unwrap_or(HEAD);
+        
+        let entries = repo.refs.read_reflog(ref_name)?;
+        
+        for (n, entry) in entries.iter().rev().enumerate() {
+            let short_oid = &entry.new_oid[0..std::cmp::min(7, entry.new_oid.len())];
+            println!(
+                "{} {}@{{{}}}: {}",
+                Color::yellow(short_oid),
+                ref_name,
+                n,
+                entry.message
+            );
+        }
+        
+        Ok(())
+    }
+}
@@ -2,7 +2,7 @@
 /// Handles resetting HEAD and index to specified state or commit.
 use std::path::{Path, PathBuf};
 use std::time::Instant;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::fs;
 use crate::errors::error::Error;
 use crate::core::revision::Revision;
@@ -10,6 +10,14 @@ use crate::core::repository::repository::Repository;
 use crate::core::database::tree::TreeEntry;
 use crate::core::database::commit::Commit;
 use crate::core::database::entry::DatabaseEntry;
+use crate::core::database::blob::Blob;
+use crate::core::diff::diff::split_lines;
+use crate::core::diff::hunks::{apply_hunks, build_hunks};
+use crate::core::diff::myers::is_binary_content;
+use crate::core::file_mode::FileMode;
+use crate::core::workspace::path_key;
+use crate::core::progress::TerminalProgress;
+use crate::commands::patch_mode;
 
 /// Constant for the original HEAD reference used in reset operations.
 pub const ORIG_HEAD: &str = "ORIG_HEAD";
@@ -28,8 +36,16 @@ pub struct ResetCommand;
 
 impl ResetCommand {
     /// Executes the reset command, resetting HEAD and index as specified.
+    /// `revision` is the target given before `--`. Without it, the first of `paths` is the target
+    /// if it resolves to a commit (and is not also a file); otherwise every argument is a path
+    /// reset against HEAD. Mixed mode (the default) only touches the index, never the working tree.
     /// Returns an error if repository is not initialized or arguments are invalid.
-    pub fn execute(paths: &[String], soft: bool, mixed: bool, hard: bool, force: bool, reuse_message: Option<&str>) -> Result<(), Error> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn execute(revision: Option<&str>, paths: &[String], soft: bool, mixed: bool, hard: bool, force: bool, reuse_message: Option<&str>, patch: bool) -> Result<(), Error> {
+        if patch && (soft || hard) {
+            return Err(Error::Generic("--patch is incompatible with --soft and --hard".to_string()));
+        }
+        
         let start_time = Instant::now();
         println!("Reset started...");
         
@@ -37,10 +53,7 @@ impl ResetCommand {
         let mut repo = Repository::new(".")?;
         
         // Citește starea curentă head
-        let head_oid = match repo.refs.read_head()? {
-            Some(oid) => oid,
-            None => return Err(Error::Generic("Fatal: Not a valid object name: HEAD".to_string()))
-        };
+        let head_oid = repo.refs.read_head()?;
         
         // Determinăm modul și ținta de reset
         let mode = if hard {
@@ -52,26 +65,57 @@ impl ResetCommand {
         };
         
         // Stabilim commit-ul de resetare
-        let mut commit_oid = head_oid.clone();
+        let mut target = head_oid.clone();
+        let mut target_name = "HEAD".to_string();
         let mut remaining_paths = paths.to_vec();
         
-        // Verificăm primul argument pentru a vedea dacă este o revizie
-        if let Some(first_arg) = paths.get(0) {
-            let mut revision = Revision::new(&mut repo, first_arg);
-            match revision.resolve("commit") {
-                Ok(oid) => {
-                    commit_oid = oid;
-                    remaining_paths.remove(0); // Îndepărtăm primul argument, rămân doar căile
-                },
-                Err(_) => {
-                    // Nu este o revizie validă, o tratăm ca pe o cale de fișier
+        if let Some(rev) = revision {
+            // Revizie dată explicit înainte de `--`
+            target = Some(Revision::new(&mut repo, rev).resolve("commit")?);
+            target_name = rev.to_string();
+        } else if let Some(first_arg) = paths.get(0) {
+            // Verificăm primul argument pentru a vedea dacă este o revizie
+            if let Ok(oid) = Revision::new(&mut repo, first_arg).resolve("commit") {
+                if repo.workspace.root_path.join(first_arg).exists() {
+                    return Err(Error::Generic(format!(
+                        "ambiguous argument '{}': both revision and filename\nUse '--' to separate paths from revisions, like this:\n'ash reset [<revision>...] -- [<file>...]'",
+                        first_arg
+                    )));
                 }
+                target = Some(oid);
+                target_name = first_arg.clone();
+                remaining_paths.remove(0); // Îndepărtăm primul argument, rămân doar căile
             }
+            // Altfel nu este o revizie validă; toate argumentele sunt căi resetate față de HEAD
         }
         
         // Încărcăm indexul pentru actualizare
         repo.index.load_for_update()?;
         
+        let commit_oid = match target {
+            Some(oid) => oid,
+            // Fără niciun commit, resetarea căilor doar le scoate din index
+            None if !remaining_paths.is_empty() && matches!(mode, Mode::Mixed) && !patch => {
+                for path_str in &remaining_paths {
+                    repo.index.remove(Path::new(path_str))?;
+                }
+                repo.index.write_updates()?;
+                println!("Paths have been reset in the index");
+                return Ok(());
+            },
+            None => {
+                repo.index.rollback()?;
+                return Err(Error::Generic("Fatal: Not a valid object name: HEAD".to_string()));
+            }
+        };
+        
+        // Patch mode only rewrites index entries; HEAD and the working tree stay as they are
+        if patch {
+            Self::reset_patch(&mut repo, &commit_oid, &remaining_paths)?;
+            repo.index.write_updates()?;
+            return Ok(());
+        }
+        
         // Procesăm resetarea în funcție de mod
         match mode {
             Mode::Soft => {
@@ -93,7 +137,7 @@ impl ResetCommand {
                     }
                     
                     // Actualizăm HEAD
-                    repo.refs.update_head(&commit_oid)?;
+                    repo.refs.update_head(&commit_oid, &format!("reset: moving to {}", target_name))?;
                     println!("HEAD is now at {}", Self::short_oid(&commit_oid));
                     println!("Commit message saved for reuse");
                 } else {
@@ -115,14 +159,14 @@ impl ResetCommand {
                     Self::reset_tree(&mut repo, &commit_oid, None)?;
                     
                     // Actualizează HEAD
-                    repo.refs.update_head(&commit_oid)?;
+                    repo.refs.update_head(&commit_oid, &format!("reset: moving to {}", target_name))?;
                     println!("HEAD is now at {}", Self::short_oid(&commit_oid));
                     println!("Index reset to {}", Self::short_oid(&commit_oid));
                 } else {
                     // Resetează doar căile specificate
-                    for path_str in &remaining_paths {
-                        let path = PathBuf::from(path_str);
-                        Self::reset_path(&mut repo, &commit_oid, &path)?;
+                    if let Err(e) = Self::reset_paths(&mut repo, &commit_oid, &remaining_paths) {
+                        repo.index.rollback()?;
+                        return Err(e);
                     }
                     println!("Paths have been reset in the index");
                 }
@@ -141,7 +185,7 @@ impl ResetCommand {
                     Self::hard_reset(&mut repo, &commit_oid, force)?;
                     
                     // Actualizează HEAD
-                    repo.refs.update_head(&commit_oid)?;
+                    repo.refs.update_head(&commit_oid, &format!("reset: moving to {}", target_name))?;
                     println!("HEAD is now at {}", Self::short_oid(&commit_oid));
                     println!("Index and workspace reset to {}", Self::short_oid(&commit_oid));
                 } else {
@@ -201,8 +245,61 @@ impl ResetCommand {
         Ok(())
     }
     
+    // Resetează căile date la starea din commit. Căile absente din commit sunt scoase din index;
+    // fișierele din workspace rămân neatinse.
+    fn reset_paths(repo: &mut Repository, commit_oid: &str, pathspecs: &[String]) -> Result<(), Error> {
+        let commit_files = patch_mode::commit_files(repo, commit_oid)?;
+        
+        for spec in pathspecs {
+            let spec = std::slice::from_ref(spec);
+            let staged: Vec<String> = repo.index.each_entry()
+                .filter(|entry| patch_mode::matches_pathspec(entry.get_path(), spec))
+                .map(|entry| entry.get_path().to_string())
+                .collect();
+            let committed: Vec<(&String, &(String, FileMode))> = commit_files.iter()
+                .filter(|(path, _)| patch_mode::matches_pathspec(path, spec))
+                .collect();
+            
+            if staged.is_empty() && committed.is_empty() {
+                return Err(Error::Generic(format!("pathspec '{}' did not match any file(s) known to ash", spec[0])));
+            }
+            
+            for path in &staged {
+                repo.index.remove(Path::new(path))?;
+            }
+            for (path, (oid, mode)) in committed {
+                Self::add_committed_entry(repo, Path::new(path), oid, *mode)?;
+            }
+        }
+        
+        Ok(())
+    }
+    
+    // Adaugă în index versiunea din commit a unui fișier. Stat-ul din workspace e folosit doar
+    // când conținutul fișierului coincide, altfel status ar ascunde modificările nestaged.
+    fn add_committed_entry(repo: &mut Repository, path: &Path, oid: &str, mode: FileMode) -> Result<(), Error> {
+        let full_path = repo.workspace.root_path.join(path);
+        if full_path.is_file() {
+            let matches = std::fs::read(&full_path)
+                .map(|data| repo.database.hash_file_data(&data) == oid)
+                .unwrap_or(false);
+            if matches {
+                let stat = std::fs::metadata(&full_path)?;
+                return repo.index.add(path, oid, &stat);
+            }
+        }
+        
+        repo.index.add_without_stat(path, oid, mode);
+        Ok(())
+    }
+    
     // Resetează un arbore întreg sau o cale specifică la starea din commit
-    fn reset_tree(repo: &mut Repository, commit_oid: &str, pathname: Option<&Path>) -> Result<(), Error> {
+    pub fn reset_tree(repo: &mut Repository, commit_oid: &str, pathname: Option<&Path>) -> Result<(), Error> {
+        // O cale specifică se resetează doar pe fișierele de sub ea
+        if let Some(path) = pathname {
+            return Self::reset_paths(repo, commit_oid, &[path_key(path)]);
+        }
+        
         // Încarcă arborele din commit
         let commit_obj = repo.database.load(commit_oid)?;
         
@@ -215,25 +312,12 @@ impl ResetCommand {
         
         let tree_oid = commit.get_tree();
         
-        // Șterge intrările existente din index pentru calea specificată
-        if let Some(path) = pathname {
-            repo.index.remove(path)?;
-        } else {
-            // Dacă nu este specificată nicio cale, resetăm întregul index
-            repo.index.clear();
-        }
+        // Resetăm întregul index
+        repo.index.clear();
         
-        // Încarcă arborele
+        // Încarcă arborele și adaugă recursiv toate intrările în index
         let tree_obj = repo.database.load(tree_oid)?;
-        
-        // Adaugă intrările din arbore în index
-        if let Some(path) = pathname {
-            // Pentru o cale specifică, adăugăm doar fișierele de sub acea cale
-            Self::add_tree_to_index(repo, &tree_obj, path)?;
-        } else {
-            // Pentru întregul index, adăugăm recursiv toate intrările
-            Self::add_tree_to_index(repo, &tree_obj, Path::new(""))?;
-        }
+        Self::add_tree_to_index(repo, &tree_obj, Path::new(""))?;
         
         Ok(())
     }
@@ -259,18 +343,7 @@ impl ResetCommand {
             match entry {
                 TreeEntry::Blob(oid, mode) => {
                     // Este un fișier, îl adăugăm direct în index
-                    // Pentru a adăuga în index, avem nevoie de stat
-                    if let Ok(stat) = std::fs::metadata(&repo.workspace.root_path.join(&entry_path)) {
-                        repo.index.add(&entry_path, &oid, &stat)?;
-                    } else {
-                        // Dacă fișierul nu există în workspace, îl adăugăm fără stat
-                        // Folosim o valoare dummy pentru stat (nu este ideal)
-                        let empty_stat = std::fs::metadata(&repo.workspace.root_path).unwrap_or_else(|_| {
-                            // Fallback în caz că metadata pentru root_path eșuează
-                            std::fs::metadata("/").unwrap()
-                        });
-                        repo.index.add(&entry_path, &oid, &empty_stat)?;
-                    }
+                    Self::add_committed_entry(repo, &entry_path, oid, *mode)?;
                 },
                 TreeEntry::Tree(subtree) => {
                     // Este un director, încarcă-l recursiv
@@ -285,9 +358,80 @@ impl ResetCommand {
         Ok(())
     }
     
-    // Resetează o cale specifică la starea din commit
-    fn reset_path(repo: &mut Repository, commit_oid: &str, pathname: &Path) -> Result<(), Error> {
-        Self::reset_tree(repo, commit_oid, Some(pathname))
+    // Interactively unstage hunks of the difference between the commit and the index.
+    // Each path's index entry is rebuilt from the commit version plus the hunks the user kept.
+    fn reset_patch(repo: &mut Repository, commit_oid: &str, pathspecs: &[String]) -> Result<(), Error> {
+        let commit_files = patch_mode::commit_files(repo, commit_oid)?;
+        
+        let index_files: HashMap<String, (String, FileMode)> = repo.index.each_entry()
+            .filter(|entry| entry.stage == 0)
+            .map(|entry| (entry.get_path().to_string(), (entry.get_oid().to_string(), *entry.get_mode())))
+            .collect();
+        
+        let paths: BTreeSet<&String> = commit_files.keys()
+            .chain(index_files.keys())
+            .filter(|path| patch_mode::matches_pathspec(path, pathspecs))
+            .collect();
+        
+        let mut unstaged = 0;
+        for path in paths {
+            let head = commit_files.get(path);
+            let staged = index_files.get(path);
+            if head.map(|(oid, _)| oid) == staged.map(|(oid, _)| oid) {
+                continue;
+            }
+            
+            let head_content = match head {
+                Some((oid, _)) => repo.database.load(oid)?.to_bytes(),
+                None => Vec::new(),
+            };
+            let staged_content = match staged {
+                Some((oid, _)) => repo.database.load(oid)?.to_bytes(),
+                None => Vec::new(),
+            };
+            
+            if is_binary_content(&head_content) || is_binary_content(&staged_content) {
+                println!("Skipping binary file {}", path);
+                continue;
+            }
+            
+            let a = split_lines(&String::from_utf8_lossy(&head_content));
+            let b = split_lines(&String::from_utf8_lossy(&staged_content));
+            let hunks = build_hunks(&a, &b, 3);
+            if hunks.is_empty() {
+                continue;
+            }
+            
+            let selection = patch_mode::select_hunks(path, &hunks, "Unstage this hunk")?;
+            let selected = selection.selected.iter().filter(|&&s| s).count();
+            
+            if selected == hunks.len() {
+                // Everything unstaged: the path goes back to exactly what the commit has
+                match head {
+                    Some((oid, mode)) => repo.index.add_without_stat(Path::new(path), oid, *mode),
+                    None => repo.index.remove(Path::new(path))?,
+                }
+            } else if selected > 0 {
+                let kept = apply_hunks(&a, &selection.hunks(&hunks, false));
+                let mut blob = Blob::new(patch_mode::join_lines(&kept));
+                let oid = repo.database.store(&mut blob)?;
+                let mode = staged.or(head).map(|(_, mode)| *mode).unwrap_or(FileMode::REGULAR);
+                repo.index.add_without_stat(Path::new(path), &oid, mode);
+            }
+            
+            unstaged += selected;
+            if selection.quit {
+                break;
+            }
+        }
+        
+        if unstaged == 0 {
+            println!("No changes unstaged");
+        } else {
+            println!("Unstaged {} hunk{}", unstaged, if unstaged == 1 { "" } else { "s" });
+        }
+        
+        Ok(())
     }
     
     // Hard reset - resetează HEAD, index și workspace la starea commit-ului specificat
@@ -307,7 +451,7 @@ impl ResetCommand {
         }
         
         // Aplicăm schimbările
-        migration.apply_changes()?;
+        migration.apply_changes(&TerminalProgress::new("Updating files"))?;
         
         Ok(())
     }
@@ -334,4 +478,49 @@ impl ResetCommand {
             oid.to_string()
         }
     }
-} 
\ No newline at end of file
+} 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestRepo;
+    
+    // Commits "one" then "two" to f.txt and resets to the first commit in `mode`
+    fn reset_to_first_commit(repo: &TestRepo, soft: bool, mixed: bool, hard: bool) -> String {
+        let first = repo.commit_files(&[("f.txt", "one\n")], "first");
+        repo.commit_files(&[("f.txt", "two\n")], "second");
+        
+        ResetCommand::execute(Some("HEAD~1"), &[], soft, mixed, hard, false, None, false).unwrap();
+        first
+    }
+    
+    #[test]
+    fn soft_reset_moves_only_head() {
+        let repo = TestRepo::new();
+        let first = reset_to_first_commit(&repo, true, false, false);
+        
+        assert_eq!(repo.head(), first);
+        assert_eq!(repo.staged_oid("f.txt"), Some(repo.blob_oid("two\n")));
+        assert_eq!(repo.read("f.txt"), "two\n");
+    }
+    
+    #[test]
+    fn mixed_reset_resets_the_index_but_not_the_working_tree() {
+        let repo = TestRepo::new();
+        let first = reset_to_first_commit(&repo, false, true, false);
+        
+        assert_eq!(repo.head(), first);
+        assert_eq!(repo.staged_oid("f.txt"), Some(repo.blob_oid("one\n")));
+        assert_eq!(repo.read("f.txt"), "two\n");
+    }
+    
+    #[test]
+    fn hard_reset_resets_the_index_and_working_tree() {
+        let repo = TestRepo::new();
+        let first = reset_to_first_commit(&repo, false, false, true);
+        
+        assert_eq!(repo.head(), first);
+        assert_eq!(repo.staged_oid("f.txt"), Some(repo.blob_oid("one\n")));
+        assert_eq!(repo.read("f.txt"), "one\n");
+    }
+}
@@ -0,0 +1,149 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+use crate::errors::error::Error;
+use crate::core::file_mode::FileMode;
+use crate::core::repository::repository::Repository;
+use crate::core::revision::Revision;
+use crate::commands::patch_mode;
+
+/// Implements the 'restore' command for AsheraFlow.
+/// Restores index entries (`--staged`) and/or working-tree files (`--worktree`) from a source.
+pub struct RestoreCommand;
+
+impl RestoreCommand {
+    /// Restores `paths` from `source`. `staged` rewrites their index entries, unstaging changes;
+    /// `worktree` overwrites the working files. With neither, only the working tree is restored.
+    /// The source defaults to HEAD when the index is restored and to the index otherwise.
+    /// Paths missing from the source are removed from whatever is being restored.
+    pub fn execute(paths: &[String], staged: bool, worktree: bool, source: Option<&str>) -> Result<(), Error> {
+        if paths.is_empty() {
+            return Err(Error::Generic("you must specify path(s) to restore".to_string()));
+        }
+        let worktree = worktree || !staged;
+
+        let mut repo = Repository::new(".")?;
+        if !repo.index.load_for_update()? {
+            return Err(Error::Lock(
+                "Unable to acquire lock on index. Another process may be using it.".to_string()
+            ));
+        }
+
+        match Self::restore(&mut repo, paths, staged, worktree, source) {
+            Ok(restored) => {
+                if staged {
+                    repo.index.write_updates()?;
+                } else {
+                    repo.index.rollback()?;
+                }
+                println!("Updated {} path{}", restored, if restored == 1 { "" } else { "s" });
+                Ok(())
+            },
+            Err(e) => {
+                repo.index.rollback()?;
+                Err(e)
+            }
+        }
+    }
+
+    fn restore(repo: &mut Repository, paths: &[String], staged: bool, worktree: bool, source: Option<&str>) -> Result<usize, Error> {
+        let index_files: BTreeMap<String, (String, FileMode)> = repo.index.each_entry()
+            .filter(|entry| entry.stage == 0)
+            .map(|entry| (entry.get_path().to_string(), (entry.get_oid().to_string(), *entry.get_mode())))
+            .collect();
+
+        // Without an explicit source, the index is restored from HEAD and the working tree from the index
+        let source_files: BTreeMap<String, (String, FileMode)> = match source {
+            Some(rev) => {
+                let commit_oid = Revision::new(repo, rev).resolve("commit")?;
+                patch_mode::commit_files(repo, &commit_oid)?.into_iter().collect()
+            },
+            None if staged => match repo.refs.read_head()? {
+                Some(head_oid) => patch_mode::commit_files(repo, &head_oid)?.into_iter().collect(),
+                // Nothing committed yet: unstaging removes the paths from the index
+                None => BTreeMap::new(),
+            },
+            None => index_files.clone(),
+        };
+
+        // Every spec must name something in the source or the index
+        for spec in paths {
+            let spec = std::slice::from_ref(spec);
+            let known = source_files.keys().chain(index_files.keys())
+                .any(|path| patch_mode::matches_pathspec(path, spec));
+            if !known {
+                return Err(Error::Generic(format!("pathspec '{}' did not match any file(s) known to ash", spec[0])));
+            }
+        }
+
+        let selected: BTreeSet<&String> = source_files.keys()
+            .chain(index_files.keys())
+            .filter(|path| patch_mode::matches_pathspec(path, paths))
+            .collect();
+
+        let mut restored = 0;
+        for path in selected {
+            let file_path = Path::new(path);
+            let wanted = source_files.get(path);
+            if !Self::differs(repo, file_path, wanted, index_files.get(path), staged, worktree)? {
+                continue;
+            }
+
+            if worktree {
+                match wanted {
+                    Some((oid, mode)) => {
+                        let content = repo.workspace.denormalize_eol(path, repo.database.load(oid)?.to_bytes());
+                        repo.workspace.write_file(file_path, &content)?;
+                        repo.workspace.set_mode(file_path, *mode)?;
+                    },
+                    None => {
+                        let full_path = repo.workspace.root_path.join(file_path);
+                        if full_path.is_file() {
+                            fs::remove_file(&full_path)?;
+                        }
+                    }
+                }
+            }
+
+            if staged {
+                match wanted {
+                    // The working file now matches the entry, so its stat can be recorded
+                    Some((oid, _)) if worktree => {
+                        let stat = repo.workspace.stat_file(file_path)?;
+                        repo.index.add(file_path, oid, &stat)?;
+                    },
+                    Some((oid, mode)) => repo.index.add_without_stat(file_path, oid, *mode),
+                    None => repo.index.remove(file_path)?,
+                }
+            }
+
+            restored += 1;
+        }
+
+        Ok(restored)
+    }
+
+    // Whether restoring `path` would change the index entry or the working file
+    fn differs(
+        repo: &mut Repository,
+        path: &Path,
+        wanted: Option<&(String, FileMode)>,
+        staged_entry: Option<&(String, FileMode)>,
+        staged: bool,
+        worktree: bool,
+    ) -> Result<bool, Error> {
+        if staged && wanted != staged_entry {
+            return Ok(true);
+        }
+        if worktree {
+            let exists = repo.workspace.path_exists(path)?;
+            return Ok(match wanted {
+                Some((oid, _)) => {
+                    !exists || repo.workspace.hash_file(path)? != *oid
+                },
+                None => exists,
+            });
+        }
+        Ok(false)
+    }
+}
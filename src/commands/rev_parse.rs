@@ -0,0 +1,33 @@
+use crate::core::repository::repository::Repository;
+use crate::core::revision::{Revision, COMMIT};
+use crate::errors::error::Error;
+
+/// Implements the 'rev-parse' command for AsheraFlow.
+/// Prints the commit ID each revision expression resolves to.
+pub struct RevParseCommand;
+
+impl RevParseCommand {
+    /// Resolves each revision and prints its full object ID, one per line.
+    /// Stops at the first revision that cannot be resolved.
+    pub fn execute(revisions: &[String]) -> Result<(), Error> {
+        let mut repo = Repository::new(".")?;
+
+        for expr in revisions {
+            let mut revision = Revision::new(&mut repo, expr);
+            match revision.resolve(COMMIT) {
+                Ok(oid) => println!("{}", oid),
+                Err(e) => {
+                    for err in revision.errors {
+                        eprintln!("error: {}", err.message);
+                        for hint in &err.hint {
+                            eprintln!("hint: {}", hint);
+                        }
+                    }
+                    return Err(e);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,10 +1,14 @@
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 use crate::errors::error::Error;
-use crate::core::workspace::Workspace;
-use crate::core::index::index::Index;
-use crate::core::database::database::Database;
 use crate::core::color::Color;
+use crate::core::file_mode::FileMode;
+use crate::core::index::index::Index;
+use crate::core::pathspec;
+use crate::core::repository::repository::Repository;
+use crate::core::workspace::path_key;
+use crate::commands::patch_mode;
 
 /// Enum representing the status of file removal checks.
 #[derive(Debug)]
@@ -21,28 +25,41 @@ pub struct RmCommand;
 
 impl RmCommand {
     /// Executes the rm command, removing the specified files or directories.
+    /// Without `force`, files whose staged or working content differs from HEAD are refused.
+    /// With `dry_run`, the files that would be removed are listed and nothing is changed.
     /// Returns an error if repository is not initialized or arguments are invalid.
-    pub fn execute(paths: &[String], cached: bool, force: bool, recursive: bool) -> Result<(), Error> {
-        let workspace = Workspace::new(Path::new("."));
-        let git_path = workspace.root_path.join(".ash");
-        let mut database = Database::new(git_path.join("objects"));
-        let mut index = Index::new(git_path.join("index"));
+    pub fn execute(paths: &[String], cached: bool, force: bool, recursive: bool, dry_run: bool) -> Result<(), Error> {
+        let mut repo = Repository::new(".")?;
         
         // Try to acquire the lock on the index
-        if !index.load_for_update()? {
+        if !repo.index.load_for_update()? {
             return Err(Error::Lock(format!(
                 "Unable to acquire lock on index. Another process may be using it."
             )));
         }
         
-        // Get HEAD OID
-        let head_oid = match workspace.read_head() {
-            Ok(head) => head,
-            Err(_) => {
-                // Release the lock if we can't get HEAD - for now, ignore the error
-                //index.release_lock()?;
-                return Err(Error::Generic("Failed to read HEAD".to_string()));
+        match Self::remove_paths(&mut repo, paths, cached, force, recursive, dry_run) {
+            Ok(true) => {
+                repo.index.write_updates()?;
+                Ok(())
+            },
+            Ok(false) => {
+                repo.index.rollback()?;
+                Ok(())
+            },
+            Err(e) => {
+                repo.index.rollback()?;
+                Err(e)
             }
+        }
+    }
+    
+    /// Checks and removes the expanded paths; returns whether the index was modified.
+    fn remove_paths(repo: &mut Repository, paths: &[String], cached: bool, force: bool, recursive: bool, dry_run: bool) -> Result<bool, Error> {
+        // Files in HEAD, as path -> (oid, mode); nothing is committed on an unborn branch
+        let head_files = match repo.refs.read_head()? {
+            Some(head_oid) => patch_mode::commit_files(repo, &head_oid)?,
+            None => HashMap::new(),
         };
         
         // Initialize error tracking
@@ -50,37 +67,30 @@ impl RmCommand {
         let mut unstaged: Vec<PathBuf> = Vec::new();
         let mut both_changed: Vec<PathBuf> = Vec::new();
         
+        // Expand glob pathspecs against the workspace and the index
+        let index_paths: Vec<String> = repo.index.each_entry()
+            .map(|entry| entry.get_path().to_string())
+            .collect();
+        let paths = pathspec::expand(paths, &repo.workspace, &index_paths)?;
+        
         // Expand and check each path
         let mut expanded_paths: Vec<PathBuf> = Vec::new();
-        
-        for path_str in paths {
-            match Self::expand_path(&index, path_str, recursive) {
-                Ok(mut paths) => {
-                    expanded_paths.append(&mut paths);
-                }
-                Err(e) => {
-                    // Release the lock on error - for now, ignore the error
-                    //index.release_lock()?;
-                    return Err(e);
+        for path_str in &paths {
+            for path in Self::expand_path(&repo.index, path_str, recursive)? {
+                if !expanded_paths.contains(&path) {
+                    expanded_paths.push(path);
                 }
             }
         }
         
         // Plan removal for each path
-        for path in &expanded_paths {
-            match Self::plan_removal(&workspace, &mut database, &index, path, &head_oid, force, cached) {
-                Ok(result) => {
-                    match result {
-                        RemovalStatus::BothChanged => both_changed.push(path.clone()),
-                        RemovalStatus::Uncommitted => if !cached { uncommitted.push(path.clone()) },
-                        RemovalStatus::Unstaged => if !cached { unstaged.push(path.clone()) },
-                        RemovalStatus::Safe => {}
-                    }
-                }
-                Err(e) => {
-                    // Release the lock on error - for now, ignore the error
-                    //index.release_lock()?;
-                    return Err(e);
+        if !force {
+            for path in &expanded_paths {
+                match Self::plan_removal(repo, &head_files, path, cached)? {
+                    RemovalStatus::BothChanged => both_changed.push(path.clone()),
+                    RemovalStatus::Uncommitted => uncommitted.push(path.clone()),
+                    RemovalStatus::Unstaged => unstaged.push(path.clone()),
+                    RemovalStatus::Safe => {}
                 }
             }
         }
@@ -91,31 +101,35 @@ impl RmCommand {
             Self::print_errors(&uncommitted, "changes staged in the index");
             Self::print_errors(&unstaged, "local modifications");
             
-            // Release the lock - for now, ignore the error
-            //index.release_lock()?;
-            return Err(Error::Generic("Cannot remove due to uncommitted changes".to_string()));
+            return Err(Error::Generic("Cannot remove due to uncommitted changes (use -f to force removal)".to_string()));
+        }
+        
+        if dry_run {
+            let target = if cached { "the index" } else { "the index and working tree" };
+            for path in &expanded_paths {
+                println!("Would remove '{}' from {}", path.display(), target);
+            }
+            return Ok(false);
         }
         
         // Remove all files
         for path in expanded_paths {
-            Self::remove_file(&workspace, &mut index, &path, cached)?;
+            Self::remove_file(repo, &path, cached)?;
             println!("rm '{}'", path.display());
         }
         
-        // Write index updates
-        index.write_updates()?;
-        
-        Ok(())
+        Ok(true)
     }
     
     /// Expands a path, handling directories if recursive is specified.
+    /// A directory expands to every index entry beneath it, at any depth.
     fn expand_path(index: &Index, path_str: &str, recursive: bool) -> Result<Vec<PathBuf>, Error> {
-        let path = PathBuf::from(path_str);
+        let path = PathBuf::from(path_str.trim_end_matches('/'));
         
         if index.tracked_directory(&path) {
             if recursive {
                 // Get all child paths
-                return Ok(index.child_paths(&path).iter().map(PathBuf::from).collect());
+                return Ok(index.child_paths(&path));
             } else {
                 return Err(Error::Generic(format!(
                     "not removing '{}' recursively without -r", path_str
@@ -132,59 +146,43 @@ impl RmCommand {
         }
     }
     
-    /// Plans the removal of a file, checking for conflicts and staged/unstaged changes.
+    /// Plans the removal of a file, checking for staged changes (HEAD vs index) and
+    /// unstaged changes (index vs working tree).
     fn plan_removal(
-        workspace: &Workspace, 
-        database: &mut Database, 
-        index: &Index, 
-        path: &Path, 
-        head_oid: &str, 
-        force: bool, 
+        repo: &mut Repository,
+        head_files: &HashMap<String, (String, FileMode)>,
+        path: &Path,
         cached: bool
     ) -> Result<RemovalStatus, Error> {
-        // Skip checks if force is enabled
-        if force {
-            return Ok(RemovalStatus::Safe);
-        }
+        let key = path_key(path);
         
         // Check if path is a directory and bail
-        match workspace.stat_file(path) {
-            Ok(stat) => {
-                if stat.is_dir() {
-                    return Err(Error::Generic(format!(
-                        "rm: '{}': Operation not permitted", path.display()
-                    )));
-                }
-            },
-            Err(_) => {} // Ignore errors if the file doesn't exist
+        let full_path = repo.workspace.root_path.join(path);
+        if full_path.is_dir() {
+            return Err(Error::Generic(format!(
+                "rm: '{}': Operation not permitted", path.display()
+            )));
         }
         
-        // Get the item from HEAD
-        let item = Self::load_tree_entry(database, head_oid, path)?;
-        
-        // Get the item from index
-        // Simplify with get_entry directly from Index
-        let entry = index.get_entry(&path.to_string_lossy());
-        
-        // Get the workspace stat
-        let stat_result = workspace.stat_file(path);
+        let entry_oid = repo.index.get_entry(&key).map(|entry| entry.get_oid().to_string());
         
         // Check for staged changes (HEAD vs index)
-        let staged_change = Self::compare_tree_to_index(item.as_ref(), entry);
+        let staged_change = head_files.get(&key).map(|(oid, _)| oid) != entry_oid.as_ref();
         
-        // Check for unstaged changes (index vs workspace)
-        let unstaged_change = if stat_result.is_ok() {
-            Self::compare_index_to_workspace(entry, stat_result.ok().as_ref())?
+        // Check for unstaged changes (index vs workspace); a file already deleted has nothing to lose
+        let unstaged_change = if full_path.is_file() {
+            let data = repo.workspace.read_file(path)?;
+            Some(repo.database.hash_file_data(&data)) != entry_oid
         } else {
-            None
+            false
         };
         
         // Determine status
-        if staged_change.is_some() && unstaged_change.is_some() {
+        if staged_change && unstaged_change {
             return Ok(RemovalStatus::BothChanged);
-        } else if staged_change.is_some() && !cached {
+        } else if staged_change && !cached {
             return Ok(RemovalStatus::Uncommitted);
-        } else if unstaged_change.is_some() && !cached {
+        } else if unstaged_change && !cached {
             return Ok(RemovalStatus::Unstaged);
         }
         
@@ -192,18 +190,13 @@ impl RmCommand {
     }
     
     /// Removes a file from index and workspace, unless --cached is used.
-    fn remove_file(
-        workspace: &Workspace, 
-        index: &mut Index, 
-        path: &Path, 
-        cached: bool
-    ) -> Result<(), Error> {
+    fn remove_file(repo: &mut Repository, path: &Path, cached: bool) -> Result<(), Error> {
         // Remove from index
-        index.remove(path)?;
+        repo.index.remove(path)?;
         
         // Remove from workspace unless --cached is used
-        if !cached {
-            workspace.remove(path)?;
+        if !cached && repo.workspace.root_path.join(path).exists() {
+            repo.workspace.remove(path)?;
         }
         
         Ok(())
@@ -217,9 +210,9 @@ impl RmCommand {
         
         let files_have = if paths.len() == 1 { "file has" } else { "files have" };
         
-        println!("{} the following {} {}:", 
-            Color::red("error:"), 
-            files_have, 
+        println!("{} the following {} {}:",
+            Color::red("error:"),
+            files_have,
             message
         );
         
@@ -227,25 +220,46 @@ impl RmCommand {
             println!("    {}", path.display());
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestRepo;
     
-    /// Helper to load a tree entry from a commit. Simplified implementation.
-    fn load_tree_entry(database: &mut Database, oid: &str, _path: &Path) -> Result<Option<Box<dyn crate::core::database::database::GitObject>>, Error> {
-        // For now, just verify that the commit exists, without looking for the specific object
-        match database.load(oid) {
-            Ok(_) => Ok(None), // Return None for any path
-            Err(e) => Err(e),
-        }
+    fn rm(paths: &[&str], force: bool, recursive: bool) -> Result<(), Error> {
+        let paths: Vec<String> = paths.iter().map(|path| path.to_string()).collect();
+        RmCommand::execute(&paths, false, force, recursive, false)
     }
     
-    /// Helper to compare tree entry to index entry. Simplified implementation.
-    fn compare_tree_to_index(_tree_entry: Option<&Box<dyn crate::core::database::database::GitObject>>, _index_entry: Option<&crate::core::index::entry::Entry>) -> Option<String> {
-        // For now, assume there are no differences
-        None
+    #[test]
+    fn recursive_rm_removes_nested_files() {
+        let repo = TestRepo::new();
+        repo.commit_files(&[("dir/a.txt", "a\n"), ("dir/sub/b.txt", "b\n"), ("keep.txt", "k\n")], "files");
+        
+        assert!(rm(&["dir"], false, false).is_err());
+        assert!(repo.exists("dir/sub/b.txt"));
+        
+        rm(&["dir"], false, true).unwrap();
+        assert!(!repo.exists("dir/a.txt"));
+        assert!(!repo.exists("dir/sub/b.txt"));
+        assert_eq!(repo.staged_oid("dir/a.txt"), None);
+        assert_eq!(repo.staged_oid("dir/sub/b.txt"), None);
+        assert!(repo.staged_oid("keep.txt").is_some());
     }
     
-    /// Helper to compare index entry to workspace. Simplified implementation.
-    fn compare_index_to_workspace(_index_entry: Option<&crate::core::index::entry::Entry>, _stat: Option<&std::fs::Metadata>) -> Result<Option<String>, Error> {
-        // For now, assume there are no differences
-        Ok(None)
+    #[test]
+    fn rm_refuses_a_modified_file_without_force() {
+        let repo = TestRepo::new();
+        repo.commit_files(&[("f.txt", "committed\n")], "file");
+        repo.write("f.txt", "edited\n");
+        
+        assert!(rm(&["f.txt"], false, false).is_err());
+        assert_eq!(repo.read("f.txt"), "edited\n");
+        assert!(repo.staged_oid("f.txt").is_some());
+        
+        rm(&["f.txt"], true, false).unwrap();
+        assert!(!repo.exists("f.txt"));
+        assert_eq!(repo.staged_oid("f.txt"), None);
     }
-} 
\ No newline at end of file
+}
@@ -0,0 +1,453 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use crate::core::database::commit::Commit;
+use crate::core::database::entry::DatabaseEntry;
+use crate::core::editor::Editor;
+use crate::core::merge::inputs::CherryPick;
+use crate::core::merge::resolve::{MergeStrategy, Resolve};
+use crate::core::progress::NoProgress;
+use crate::core::path_filter::PathFilter;
+use crate::core::refs::HEAD;
+use crate::core::repository::pending_commit::PendingCommitType;
+use crate::core::repository::repository::Repository;
+use crate::core::repository::sequencer::{Action, Sequencer};
+use crate::core::revision::Revision;
+use crate::commands::commit_writer::{CommitWriter, CHERRY_PICK_NOTES, COMMIT_NOTES};
+use crate::commands::reset::ResetCommand;
+use crate::errors::error::Error;
+
+const CONFLICT_NOTES: &str = "\
+after resolving the conflicts, mark the corrected paths
+with 'ash add <paths>' or 'ash rm <paths>'
+and commit the result with 'ash commit'";
+
+/// Resolves each revision argument to a commit, in the order given
+pub fn resolve_commits(repo: &mut Repository, args: &[String]) -> Result<Vec<Commit>, Error> {
+    let mut commits = Vec::with_capacity(args.len());
+
+    for arg in args {
+        let mut revision = Revision::new(repo, arg);
+        let oid = match revision.resolve("commit") {
+            Ok(oid) => oid,
+            Err(e) => {
+                for err in revision.errors {
+                    eprintln!("error: {}", err.message);
+                    for hint in &err.hint {
+                        eprintln!("hint: {}", hint);
+                    }
+                }
+                return Err(e);
+            }
+        };
+
+        commits.push(load_commit(repo, &oid)?);
+    }
+
+    Ok(commits)
+}
+
+/// Runs the remaining commands in the todo list, clearing the sequencer state once all are applied.
+/// On conflict the todo list is written out and the error is returned, leaving the state for `--continue`.
+pub fn resume_sequencer(repo: &mut Repository, sequencer: &mut Sequencer) -> Result<(), Error> {
+    while let Some((action, commit)) = sequencer.next_command() {
+        match action {
+            Action::Pick => pick(repo, sequencer, &commit)?,
+            Action::Revert => revert(repo, sequencer, &commit)?,
+        }
+        sequencer.drop_command()?;
+    }
+
+    sequencer.quit()?;
+    Ok(())
+}
+
+/// Commits the resolved conflict of the stopped command, then carries on with the rest of the todo list
+pub fn handle_continue(repo: &mut Repository, sequencer: &mut Sequencer) -> Result<(), Error> {
+    repo.index.load()?;
+
+    let root_path = repo.path.clone();
+    {
+        let mut commit_writer = CommitWriter::new(
+            &root_path,
+            root_path.join(".ash"),
+            &mut repo.database,
+            &mut repo.index,
+            &repo.refs,
+        );
+
+        match commit_writer.pending_commit.merge_type() {
+            Some(PendingCommitType::CherryPick) => {
+                commit_writer.handle_conflicted_index()?;
+                let editor_cmd = commit_writer.get_editor_command();
+                commit_writer.write_cherry_pick_commit(Some(editor_cmd), Some(CHERRY_PICK_NOTES))?;
+            },
+            Some(PendingCommitType::Revert) => {
+                commit_writer.handle_conflicted_index()?;
+                let editor_cmd = commit_writer.get_editor_command();
+                commit_writer.write_revert_commit(Some(editor_cmd))?;
+            },
+            Some(PendingCommitType::Merge) => {
+                return Err(Error::Generic("A merge is in progress; use 'ash merge --continue'".into()));
+            },
+            None => {},
+        }
+    }
+
+    sequencer.load()?;
+    sequencer.drop_command()?;
+    resume_sequencer(repo, sequencer)
+}
+
+/// Restores HEAD, the index and the working tree to the commit the operation started from
+/// and removes all sequencer state.
+pub fn handle_abort(repo: &mut Repository, sequencer: &mut Sequencer) -> Result<(), Error> {
+    clear_pending_commit(repo);
+
+    let original_head = sequencer.abort()?;
+
+    repo.index.load_for_update()?;
+    if let Err(e) = restore_commit(repo, &original_head) {
+        repo.index.rollback()?;
+        return Err(e);
+    }
+    repo.index.set_changed(true);
+    repo.index.write_updates()?;
+
+    let current_head = repo.refs.read_head()?;
+    if current_head.as_deref() != Some(original_head.as_str()) {
+        repo.refs.update_head(&original_head, "reset: moving to ORIG_HEAD")?;
+    }
+
+    Ok(())
+}
+
+/// Removes all sequencer state, leaving HEAD, the index and the working tree as they are
+pub fn handle_quit(repo: &mut Repository, sequencer: &mut Sequencer) -> Result<(), Error> {
+    clear_pending_commit(repo);
+    sequencer.quit()
+}
+
+fn pick(repo: &mut Repository, sequencer: &mut Sequencer, commit: &Commit) -> Result<(), Error> {
+    let commit_oid = commit.get_oid().cloned().unwrap_or_default();
+    let short = repo.database.short_oid(&commit_oid);
+
+    let inputs = CherryPick::new(
+        HEAD.to_owned(),
+        format!("{}... {}", short, commit.title_line().trim()),
+        repo.refs.read_head()?.unwrap_or_default(),
+        commit_oid,
+        select_parent(sequencer, commit)?.into_iter().collect(),
+    );
+
+    let message = commit.get_message().to_string();
+
+    if resolve_merge(repo, &inputs)? {
+        return fail_on_conflict(repo, sequencer, &inputs, PendingCommitType::CherryPick, &message);
+    }
+
+    let root_path = repo.path.clone();
+    let mut commit_writer = CommitWriter::new(
+        &root_path,
+        root_path.join(".ash"),
+        &mut repo.database,
+        &mut repo.index,
+        &repo.refs,
+    );
+
+    let head_oid = commit_writer.refs.read_head()?.unwrap_or_default();
+    let author = commit.get_author().cloned();
+    let new_commit = commit_writer.write_commit(vec![head_oid], &message, author)?;
+    commit_writer.print_commit(&new_commit)?;
+
+    Ok(())
+}
+
+fn revert(repo: &mut Repository, sequencer: &mut Sequencer, commit: &Commit) -> Result<(), Error> {
+    let commit_oid = commit.get_oid().cloned().unwrap_or_default();
+    let short = repo.database.short_oid(&commit_oid);
+
+    let inputs = CherryPick::new(
+        HEAD.to_owned(),
+        format!("parent of {}... {}", short, commit.title_line().trim()),
+        repo.refs.read_head()?.unwrap_or_default(),
+        select_parent(sequencer, commit)?
+            .ok_or_else(|| Error::Generic(format!("commit {} has no parent", commit_oid)))?,
+        vec![commit_oid.clone()],
+    );
+
+    let message = format!(
+        "Revert \"{}\"\n\nThis reverts commit {}.\n",
+        commit.title_line().trim(),
+        commit_oid
+    );
+
+    if resolve_merge(repo, &inputs)? {
+        return fail_on_conflict(repo, sequencer, &inputs, PendingCommitType::Revert, &message);
+    }
+
+    let root_path = repo.path.clone();
+    let mut commit_writer = CommitWriter::new(
+        &root_path,
+        root_path.join(".ash"),
+        &mut repo.database,
+        &mut repo.index,
+        &repo.refs,
+    );
+
+    let editor_cmd = commit_writer.get_editor_command();
+    let message_path = commit_writer.commit_message_path();
+    let message = Editor::edit(message_path, Some(editor_cmd), |editor| {
+        editor.write(&message)?;
+        editor.write("")?;
+        editor.note(COMMIT_NOTES)?;
+        Ok(())
+    })?
+    .ok_or_else(|| Error::Generic("Aborting revert due to empty commit message".into()))?;
+
+    let head_oid = commit_writer.refs.read_head()?.unwrap_or_default();
+    let new_commit = commit_writer.write_commit(vec![head_oid], &message, None)?;
+    commit_writer.print_commit(&new_commit)?;
+
+    Ok(())
+}
+
+/// Applies the merge described by `inputs` to the index and working tree.
+/// Returns whether conflicts were left in the index.
+fn resolve_merge(repo: &mut Repository, inputs: &CherryPick) -> Result<bool, Error> {
+    repo.index.load_for_update()?;
+
+    let result = Resolve::new(&mut repo.database, &repo.workspace, &mut repo.index, inputs, MergeStrategy::Recursive).execute(&NoProgress);
+    let has_conflict = repo.index.has_conflict();
+
+    if let Err(e) = result {
+        if !has_conflict {
+            repo.index.rollback()?;
+            return Err(e);
+        }
+    }
+
+    repo.index.write_updates()?;
+    Ok(has_conflict)
+}
+
+fn fail_on_conflict(
+    repo: &mut Repository,
+    sequencer: &mut Sequencer,
+    inputs: &CherryPick,
+    merge_type: PendingCommitType,
+    message: &str,
+) -> Result<(), Error> {
+    sequencer.dump()?;
+
+    let root_path = repo.path.clone();
+    let commit_writer = CommitWriter::new(
+        &root_path,
+        root_path.join(".ash"),
+        &mut repo.database,
+        &mut repo.index,
+        &repo.refs,
+    );
+
+    commit_writer.pending_commit.start(&inputs.right_oid, merge_type)?;
+
+    let editor_command = commit_writer.get_editor_command();
+    let message_path = commit_writer.pending_commit.message_path.clone();
+
+    Editor::edit(message_path, Some(editor_command), |editor| {
+        editor.write(message)?;
+        editor.write("")?;
+        editor.note("Conflicts:")?;
+        for name in commit_writer.index.conflict_paths() {
+            editor.note(&format!("\t{}", name))?;
+        }
+        editor.close();
+
+        Ok(())
+    })?;
+
+    println!("error: could not apply {}", inputs.right_name);
+    for line in CONFLICT_NOTES.lines() {
+        println!("hint: {}", line);
+    }
+
+    let operation = match merge_type {
+        PendingCommitType::Revert => "Revert",
+        _ => "Cherry-pick",
+    };
+    Err(Error::Generic(format!("{} failed due to conflicts", operation)))
+}
+
+fn select_parent(sequencer: &Sequencer, commit: &Commit) -> Result<Option<String>, Error> {
+    let commit_oid = commit.get_oid().cloned().unwrap_or_default();
+    let mainline = sequencer.get_option("mainline")?;
+    let parents = commit.get_parents();
+
+    if parents.len() <= 1 {
+        if mainline.is_some() {
+            return Err(Error::Generic(format!(
+                "mainline was specified but commit {} is not a merge",
+                commit_oid
+            )));
+        }
+        return Ok(commit.get_parent().cloned());
+    }
+
+    let mainline = mainline.ok_or_else(|| Error::Generic(format!(
+        "commit {} is a merge but no -m option was given",
+        commit_oid
+    )))?;
+
+    // Mainline numbers are 1-based, matching the order parents are recorded in
+    let parent = mainline.parse::<usize>().ok()
+        .filter(|&n| n >= 1)
+        .and_then(|n| parents.get(n - 1))
+        .ok_or_else(|| Error::Generic(format!(
+            "commit {} does not have parent {}",
+            commit_oid, mainline
+        )))?;
+
+    Ok(Some(parent.clone()))
+}
+
+/// Rewrites every path touched since `commit_oid` to its content in that commit,
+/// removing files it does not contain, then rebuilds the index from its tree.
+fn restore_commit(repo: &mut Repository, commit_oid: &str) -> Result<(), Error> {
+    let filter = PathFilter::new();
+    let target: HashMap<PathBuf, DatabaseEntry> = repo.database
+        .tree_diff(None, Some(commit_oid), &filter)?
+        .into_iter()
+        .filter_map(|(path, (_, entry))| entry.map(|entry| (path, entry)))
+        .filter(|(_, entry)| !entry.get_file_mode().is_directory())
+        .collect();
+
+    // Paths moved by commits made since the operation started
+    let mut paths: HashSet<PathBuf> = match repo.refs.read_head()? {
+        Some(head) if head != commit_oid => repo.database
+            .tree_diff(Some(commit_oid), Some(&head), &filter)?
+            .into_keys()
+            .collect(),
+        _ => HashSet::new(),
+    };
+
+    // Paths staged or left conflicted by the stopped command
+    for entry in repo.index.each_entry() {
+        let path = PathBuf::from(entry.get_path());
+        let unchanged = entry.stage == 0
+            && target.get(&path).is_some_and(|t| t.get_oid() == entry.get_oid());
+        if !unchanged {
+            paths.insert(path);
+        }
+    }
+
+    for path in paths {
+        match target.get(&path) {
+            Some(entry) => {
+                let blob = repo.database.load(entry.get_oid())?;
+                repo.workspace.write_file(&path, &blob.to_bytes())?;
+            },
+            None => {
+                if repo.workspace.root_path.join(&path).is_file() {
+                    repo.workspace.remove_file(&path)?;
+                }
+            }
+        }
+    }
+
+    ResetCommand::reset_tree(repo, commit_oid, None)
+}
+
+fn clear_pending_commit(repo: &mut Repository) {
+    let root_path = repo.path.clone();
+    let commit_writer = CommitWriter::new(
+        &root_path,
+        root_path.join(".ash"),
+        &mut repo.database,
+        &mut repo.index,
+        &repo.refs,
+    );
+
+    for merge_type in [PendingCommitType::CherryPick, PendingCommitType::Revert] {
+        if commit_writer.pending_commit.in_progress(merge_type) {
+            let _ = commit_writer.pending_commit.clear(merge_type);
+        }
+    }
+}
+
+fn load_commit(repo: &mut Repository, oid: &str) -> Result<Commit, Error> {
+    let object = repo.database.load(oid)?;
+    object.as_any().downcast_ref::<Commit>()
+        .cloned()
+        .ok_or_else(|| Error::Generic(format!("Object {} is not a commit", oid)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::branch::BranchCommand;
+    use crate::commands::checkout::CheckoutCommand;
+    use crate::commands::cherry_pick::CherryPickCommand;
+    use crate::commands::commit::CommitCommand;
+    use crate::commands::merge::MergeCommand;
+    use crate::core::merge::diff3::ConflictStyle;
+    use crate::core::merge::resolve::MergeStrategy;
+    use crate::test_support::TestRepo;
+
+    #[test]
+    fn cherry_pick_keeps_the_original_author() {
+        let repo = TestRepo::new();
+        repo.commit_files(&[("f.txt", "base\n")], "base");
+        BranchCommand::execute("topic", None).unwrap();
+        repo.write("g.txt", "picked\n");
+        repo.add(&["g.txt"]);
+        CommitCommand::execute("picked", false, None, false, Some("Ada Lovelace <ada@example.com>"), Some("2024-01-15 18:30:00"), &[]).unwrap();
+        let picked = repo.head();
+        CheckoutCommand::execute("topic").unwrap();
+        repo.commit_files(&[("h.txt", "topic\n")], "topic");
+
+        CherryPickCommand::execute(std::slice::from_ref(&picked), false, false, false, None).unwrap();
+
+        let mut r = repo.repo();
+        let original = load_commit(&mut r, &picked).unwrap();
+        let copy = load_commit(&mut r, &repo.head()).unwrap();
+        assert_ne!(repo.head(), picked);
+        assert_eq!(repo.read("g.txt"), "picked\n");
+
+        let (author, original_author) = (copy.get_author().unwrap(), original.get_author().unwrap());
+        assert_eq!(author.name, "Ada Lovelace");
+        assert_eq!(author.email, "ada@example.com");
+        assert_eq!(author.timestamp, original_author.timestamp);
+
+        let committer = copy.get_committer().unwrap();
+        assert_eq!((committer.name.as_str(), committer.email.as_str()), ("Test Author", "author@test.invalid"));
+        assert_ne!(committer.timestamp, original_author.timestamp);
+    }
+
+    #[test]
+    fn cherry_pick_of_a_merge_applies_its_changes_against_the_mainline_parent() {
+        let repo = TestRepo::new();
+        repo.commit_files(&[("f.txt", "base\n")], "base");
+        BranchCommand::execute("side", None).unwrap();
+        BranchCommand::execute("topic", None).unwrap();
+        repo.commit_files(&[("m.txt", "master\n")], "master");
+        CheckoutCommand::execute("topic").unwrap();
+        repo.commit_files(&[("t.txt", "topic\n")], "topic");
+        CheckoutCommand::execute("master").unwrap();
+        MergeCommand::execute("topic", Some("merge topic"), MergeStrategy::Recursive, ConflictStyle::Merge, false, false).unwrap();
+        let merge = repo.head();
+        CheckoutCommand::execute("side").unwrap();
+
+        let err = CherryPickCommand::execute(std::slice::from_ref(&merge), false, false, false, None).unwrap_err();
+        assert!(err.to_string().contains("is a merge but no -m option was given"), "{}", err);
+        CherryPickCommand::execute(&[], false, false, true, None).unwrap();
+
+        let err = CherryPickCommand::execute(std::slice::from_ref(&merge), false, false, false, Some(3)).unwrap_err();
+        assert!(err.to_string().contains("does not have parent 3"), "{}", err);
+        CherryPickCommand::execute(&[], false, false, true, None).unwrap();
+
+        CherryPickCommand::execute(&[merge], false, false, false, Some(1)).unwrap();
+
+        assert_eq!(repo.read("t.txt"), "topic\n");
+        assert!(!repo.path().join("m.txt").exists());
+    }
+}
@@ -0,0 +1,81 @@
+use crate::errors::error::Error;
+use crate::core::color::Color;
+use crate::core::database::commit::Commit;
+use crate::core::database::database::Database;
+use crate::core::database::tag::Tag;
+use crate::core::database::tree::Tree;
+use crate::core::diff::myers::is_binary_content;
+use crate::core::pager::Pager;
+use crate::core::path_filter::PathFilter;
+use crate::core::refs::Refs;
+use crate::core::repository::repository::Repository;
+use crate::core::revision::Revision;
+use crate::commands::cat_file::format_tree_entries;
+use crate::commands::log::{build_reverse_refs, show_commit_medium, show_patch};
+
+/// Implements the 'show' command for AsheraFlow.
+/// Displays a commit with its patch, the entries of a tree, the content of a blob,
+/// or an annotated tag followed by the object it tags.
+pub struct ShowCommand;
+
+impl ShowCommand {
+    /// Shows the object named by `rev`, HEAD when none is given.
+    pub fn execute(rev: Option<&str>) -> Result<(), Error> {
+        let mut repo = Repository::new(".")?;
+        let rev = rev.unwrap_or("HEAD");
+        let oid = Revision::new(&mut repo, rev).resolve_object()?;
+
+        let mut pager = Pager::new();
+        pager.start()?;
+        let shown = Self::show_object(&mut pager, &mut repo.database, &repo.refs, &oid);
+        pager.close()?;
+        shown
+    }
+
+    fn show_object(pager: &mut Pager, database: &mut Database, refs: &Refs, oid: &str) -> Result<(), Error> {
+        let object = database.load(oid)?;
+
+        if let Some(commit) = object.as_any().downcast_ref::<Commit>() {
+            let reverse_refs = build_reverse_refs(refs, database)?;
+            let current_ref = refs.current_ref()?;
+            show_commit_medium(pager, commit, false, "short", &reverse_refs, &current_ref)?;
+            pager.write("\n")?;
+
+            // Merges are diffed against their first parent
+            let parent = commit.get_parent().map(|parent| parent.as_str());
+            show_patch(pager, database, parent, oid, &PathFilter::new())?;
+        } else if let Some(tag) = object.as_any().downcast_ref::<Tag>() {
+            pager.write(&format!("{} {}\n", Color::yellow("tag"), tag.name))?;
+            pager.write(&format!("Tagger: {} <{}>\n", tag.tagger.name, tag.tagger.email))?;
+            pager.write(&format!("Date:   {}\n\n", tag.tagger.short_date()))?;
+            for line in tag.message.lines() {
+                pager.write(&format!("{}\n", line))?;
+            }
+            pager.write("\n")?;
+
+            let target = tag.get_object().to_string();
+            Self::show_object(pager, database, refs, &target)?;
+        } else if let Some(tree) = object.as_any().downcast_ref::<Tree>() {
+            Self::show_tree(pager, tree, oid)?;
+        } else {
+            let content = object.to_bytes();
+            if is_binary_content(&content) {
+                pager.write(&format!("Binary blob {} ({} bytes)\n", oid, content.len()))?;
+            } else {
+                pager.write(&String::from_utf8_lossy(&content))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Lists a tree's entries as `<mode> <type> <oid>\t<name>`, sorted by name
+    fn show_tree(pager: &mut Pager, tree: &Tree, oid: &str) -> Result<(), Error> {
+        pager.write(&format!("{} {}\n\n", Color::yellow("tree"), oid))?;
+        for line in format_tree_entries(tree) {
+            pager.write(&format!("{}\n", line))?;
+        }
+
+        Ok(())
+    }
+}
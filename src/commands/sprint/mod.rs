@@ -5,6 +5,8 @@ pub mod sprint_velocity;
 pub mod sprint_advance;
 pub mod sprint_commitmap;
 pub mod sprint_view;
+pub mod sprint_close;
+pub mod sprint_report;
 
 pub use sprint_start::SprintStartCommand;
 pub use sprint_info::SprintInfoCommand;
@@ -12,4 +14,6 @@ pub use sprint_burndown::SprintBurndownCommand;
 pub use sprint_velocity::SprintVelocityCommand;
 pub use sprint_advance::SprintAdvanceCommand;
 pub use sprint_commitmap::SprintCommitMapCommand;
-pub use sprint_view::SprintViewCommand; 
\ No newline at end of file
+pub use sprint_view::SprintViewCommand;
+pub use sprint_close::SprintCloseCommand;
+pub use sprint_report::SprintReportCommand; 
\ No newline at end of file
@@ -1,7 +1,5 @@
 use std::path::Path;
-use std::collections::HashMap;
-use std::io;
-use std::time::{Duration as StdDuration, Instant};
+use std::io::{self, IsTerminal};
 use chrono::{NaiveDateTime, Utc, Duration, NaiveDate, Datelike};
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
@@ -10,7 +8,7 @@ use crossterm::{
 };
 use tui::{
     backend::{Backend, CrosstermBackend},
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Constraint, Direction, Layout},
     style::{Color, Modifier, Style},
     symbols,
     text::{Span, Spans},
@@ -20,11 +18,12 @@ use tui::{
 
 use crate::errors::error::Error;
 use crate::core::branch_metadata::BranchMetadataManager;
-use crate::core::sprint::sprint::{SprintManager, TaskStatus};
+use crate::core::sprint::sprint::{BurndownData, SprintManager};
 
 pub struct SprintBurndownCommand;
 
-struct BurndownData {
+/// What the chart and table show around the burndown series
+struct BurndownChart {
     sprint_name: String,
     total_points: u32,
     days_passed: usize,
@@ -32,13 +31,14 @@ struct BurndownData {
     total_days: usize,
     start_date: NaiveDate,
     end_date: NaiveDate,
-    daily_progress: Vec<(usize, u32)>,
     ideal_progress: Vec<(f64, f64)>,
     actual_progress: Vec<(f64, f64)>,
 }
 
 impl SprintBurndownCommand {
-    pub fn execute(sprint_name: Option<&str>) -> Result<(), Error> {
+    /// Shows the burndown of the active or named sprint. `format` is `csv` or `json` to print the
+    /// series for other tools; otherwise a chart is drawn, or a table when output is not a terminal.
+    pub fn execute(sprint_name: Option<&str>, format: Option<&str>) -> Result<(), Error> {
         // Initialize the repository path
         let root_path = Path::new(".");
         let git_path = root_path.join(".ash");
@@ -71,6 +71,21 @@ impl SprintBurndownCommand {
             }
         };
         
+        let data = sprint_manager.burndown_data(&branch_name, &sprint_metadata)?;
+        
+        match format {
+            Some("csv") => {
+                print!("{}", burndown_csv(&data));
+                return Ok(());
+            },
+            Some("json") => {
+                println!("{}", burndown_json(&data)?);
+                return Ok(());
+            },
+            Some(other) => return Err(Error::Generic(format!("Unknown burndown format: {} (expected csv or json)", other))),
+            None => {},
+        }
+        
         // Get tasks for this sprint
         let tasks = match sprint_manager.get_sprint_tasks(&branch_name) {
             Ok(tasks) => tasks,
@@ -99,19 +114,8 @@ impl SprintBurndownCommand {
         let days_passed = std::cmp::min((current_date - start_date).num_days() as usize, total_days);
         let days_remaining = total_days - days_passed;
         
-        // Get daily completion progress
-        let daily_progress = get_daily_progress(&tasks, start_date, total_days);
-        
-        // Create ideal and actual data points for the chart
-        let (ideal_progress, actual_progress) = create_chart_data(
-            total_story_points,
-            total_days,
-            start_date,
-            &daily_progress
-        );
-        
         // Prepare burndown data for visualization
-        let burndown_data = BurndownData {
+        let chart = BurndownChart {
             sprint_name: sprint_metadata.name.clone(),
             total_points: total_story_points,
             days_passed,
@@ -119,118 +123,66 @@ impl SprintBurndownCommand {
             total_days,
             start_date,
             end_date,
-            daily_progress,
-            ideal_progress,
-            actual_progress,
+            ideal_progress: chart_points(&data.ideal),
+            actual_progress: chart_points(&data.actual),
         };
         
-        // Show interactive UI
-        show_burndown_chart(burndown_data)?;
+        // Show interactive UI, or plain numbers when output is not a terminal
+        if io::stdout().is_terminal() {
+            show_burndown_chart(chart)?;
+        } else {
+            print_burndown_table(&chart, &data);
+        }
         
         Ok(())
     }
 }
 
-fn get_daily_progress(tasks: &HashMap<String, crate::core::sprint::sprint::Task>, 
-                     start_date: NaiveDate, 
-                     total_days: usize) -> Vec<(usize, u32)> {
-    let mut daily_completed = vec![0; total_days + 1];
-    
-    let sprint_end_date = start_date + Duration::days(total_days as i64);
-    
-    for (_, task) in tasks {
-        if task.status == TaskStatus::Done && task.completed_at.is_some() {
-            let completed_date = NaiveDateTime::from_timestamp_opt(task.completed_at.unwrap() as i64, 0)
-                .unwrap()
-                .date();
-            
-            // Check if the task was completed during the sprint period
-            if completed_date >= start_date && completed_date <= sprint_end_date {
-                let day_idx = (completed_date - start_date).num_days() as usize;
-                if day_idx <= total_days {
-                    if let Some(points) = task.story_points {
-                        daily_completed[day_idx] += points;
-                    } else {
-                        // Default 1 point if not specified
-                        daily_completed[day_idx] += 1;
-                    }
-                }
-            }
-        }
-    }
-    
-    // Convert to cumulative completion
-    let mut result = Vec::new();
-    let mut cumulative = 0;
-    
-    for (day, points) in daily_completed.iter().enumerate() {
-        cumulative += points;
-        result.push((day, cumulative));
-    }
-    
-    result
+/// One `(day, remaining points)` chart point per value
+fn chart_points(values: &[f64]) -> Vec<(f64, f64)> {
+    values.iter().enumerate().map(|(day, remaining)| (day as f64, *remaining)).collect()
 }
 
-fn create_chart_data(total_points: u32, 
-                   total_days: usize,
-                   start_date: NaiveDate,
-                   daily_progress: &[(usize, u32)]) -> (Vec<(f64, f64)>, Vec<(f64, f64)>) {
-    // Calculate ideal progress (linear downward trend)
-    let mut ideal_progress = Vec::new();
-    
-    // Calculate working days (exclude weekends)
-    let mut working_days = Vec::new();
-    for d in 0..=total_days {
-        let date = start_date + Duration::days(d as i64);
-        if date.weekday().number_from_monday() < 6 {
-            working_days.push(d);
-        }
-    }
-    
-    let total_working_days = working_days.len();
-    let points_per_working_day = if total_working_days > 0 {
-        total_points as f64 / total_working_days as f64
-    } else {
-        0.0
-    };
-    
-    for d in 0..=total_days {
-        let day = d as f64;
-        
-        // Calculate working days up to current day
-        let working_days_so_far = working_days.iter().filter(|&&wd| wd <= d).count();
-        
-        // Calculate ideal remaining points (subtracting ideal progress)
-        let ideal_remaining = total_points as f64 - (working_days_so_far as f64 * points_per_working_day);
-        
-        // Add the point to the chart
-        ideal_progress.push((day, ideal_remaining.max(0.0)));
+/// The series as CSV with a `date,ideal,actual` header; days after today have an empty actual value
+fn burndown_csv(data: &BurndownData) -> String {
+    let mut csv = String::from("date,ideal,actual\n");
+    for (day, (date, ideal)) in data.dates.iter().zip(&data.ideal).enumerate() {
+        let actual = data.actual.get(day).map(|remaining| remaining.to_string()).unwrap_or_default();
+        csv.push_str(&format!("{},{:.2},{}\n", date, ideal, actual));
     }
+    csv
+}
+
+/// The series as one JSON object holding the `dates`, `ideal` and `actual` arrays
+fn burndown_json(data: &BurndownData) -> Result<String, Error> {
+    serde_json::to_string_pretty(data)
+        .map_err(|e| Error::Generic(format!("Failed to serialize burndown data: {}", e)))
+}
+
+/// Prints the burndown as a day-by-day table of ideal and actual remaining points
+fn print_burndown_table(chart: &BurndownChart, data: &BurndownData) {
+    println!(
+        "Sprint \"{}\" burndown: {} - {} | {}/{} days | {} story points",
+        chart.sprint_name,
+        chart.start_date.format("%Y-%m-%d"),
+        chart.end_date.format("%Y-%m-%d"),
+        chart.days_passed,
+        chart.total_days,
+        chart.total_points
+    );
+    println!("{:>4}  {:<10}  {:>6}  {:>6}", "Day", "Date", "Ideal", "Actual");
     
-    // Calculate actual progress
-    let mut actual_progress = Vec::new();
-    for d in 0..=total_days {
-        let day = d as f64;
-        
-        // Calculate actual points remaining (total - completed at this day)
-        let actual_points = if d < daily_progress.len() {
-            total_points - daily_progress[d].1
-        } else {
-            // If we don't have data for this day, use the last known value
-            if !daily_progress.is_empty() {
-                total_points - daily_progress.last().unwrap().1
-            } else {
-                total_points // No progress yet
-            }
+    for (day, (date, ideal)) in data.dates.iter().zip(&data.ideal).enumerate() {
+        let actual = match data.actual.get(day) {
+            Some(remaining) => format!("{}", *remaining as u32),
+            None => "-".to_string(),
         };
         
-        actual_progress.push((day, actual_points as f64));
+        println!("{:>4}  {:<10}  {:>6.1}  {:>6}", day, date, ideal, actual);
     }
-    
-    (ideal_progress, actual_progress)
 }
 
-fn show_burndown_chart(data: BurndownData) -> Result<(), Error> {
+fn show_burndown_chart(data: BurndownChart) -> Result<(), Error> {
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -257,7 +209,7 @@ fn show_burndown_chart(data: BurndownData) -> Result<(), Error> {
     Ok(())
 }
 
-fn run_app<B: Backend>(terminal: &mut Terminal<B>, data: BurndownData) -> io::Result<()> {
+fn run_app<B: Backend>(terminal: &mut Terminal<B>, data: BurndownChart) -> io::Result<()> {
     let mut scale_factor = 1.0; // Zoom factor for chart
     
     loop {
@@ -284,7 +236,7 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, data: BurndownData) -> io::Re
     }
 }
 
-fn ui<B: Backend>(f: &mut tui::Frame<B>, data: &BurndownData, scale_factor: f32) {
+fn ui<B: Backend>(f: &mut tui::Frame<B>, data: &BurndownChart, scale_factor: f32) {
     let size = f.size();
     
     // Layout
@@ -369,7 +321,7 @@ fn ui<B: Backend>(f: &mut tui::Frame<B>, data: &BurndownData, scale_factor: f32)
             .data(&data.ideal_progress),
         Dataset::default()
             .name("Actual Progress")
-            .marker(symbols::Marker::Dot)
+            .marker(symbols::Marker::Braille)
             .graph_type(GraphType::Line)
             .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
             .data(&data.actual_progress)
@@ -0,0 +1,204 @@
+use std::path::Path;
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::errors::error::Error;
+use crate::commands::tag::TagCommand;
+use crate::core::branch_metadata::{SprintMetadata, BranchMetadataManager};
+use crate::core::database::tag::Tag;
+use crate::core::refs::Refs;
+use crate::core::repository::repository::Repository;
+use crate::core::sprint::sprint::SprintManager;
+use crate::core::sprint::{Task, TaskStatus};
+
+/// Handles closing the active sprint in AsheraFlow.
+pub struct SprintCloseCommand;
+
+impl SprintCloseCommand {
+    /// Closes the named sprint, or the active one, so it no longer counts as active, and records
+    /// its planned and completed points for `sprint velocity`.
+    /// With `tag`, an annotated tag `sprint-<name>-end` is created at HEAD holding the sprint summary.
+    /// With `carry_over_to`, unfinished tasks are moved to that sprint.
+    pub fn execute(sprint_name: Option<&str>, tag: bool, carry_over_to: Option<&str>) -> Result<(), Error> {
+        // Initialize the repository path
+        let root_path = Path::new(".");
+        let git_path = root_path.join(".ash");
+
+        // Verify .ash directory exists
+        if !git_path.exists() {
+            return Err(Error::Generic("Not an ash repository: .ash directory not found".into()));
+        }
+
+        let branch_manager = BranchMetadataManager::new(root_path);
+
+        let (sprint_key, mut sprint_metadata) = match sprint_name {
+            Some(name) => Self::find_sprint(&branch_manager, name)?,
+            None => match branch_manager.find_active_sprint()? {
+                Some((branch, metadata)) => (branch, metadata),
+                None => return Err(Error::Generic("No active sprint found. Start a sprint first with 'ash sprint start'.".into())),
+            },
+        };
+
+        if sprint_metadata.is_closed() {
+            return Err(Error::Generic(format!("Sprint '{}' is already closed", sprint_metadata.name)));
+        }
+
+        let sprint_branch = Self::sprint_branch(&sprint_key);
+
+        // The sprint receiving unfinished tasks must be a different one that is still open
+        let carry_over = match carry_over_to {
+            Some(name) => {
+                let (target_key, target_metadata) = Self::find_sprint(&branch_manager, name)?;
+                if target_key == sprint_key {
+                    return Err(Error::Generic(format!("Cannot carry tasks over to the sprint being closed ('{}')", name)));
+                }
+                if target_metadata.is_closed() {
+                    return Err(Error::Generic(format!("Cannot carry tasks over to '{}': the sprint is closed", target_metadata.name)));
+                }
+                Some((Self::sprint_branch(&target_key), target_metadata.name))
+            },
+            None => None,
+        };
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        sprint_metadata.closed_timestamp = Some(now);
+
+        // Work out the tag and what it points at before anything is written, so a clash or
+        // an unborn HEAD leaves the sprint open
+        let tag_name = format!("{}-end", sprint_metadata.to_branch_name());
+        let mut repo = Repository::new(".")?;
+        let tag_target = if tag {
+            if !Refs::is_valid_refname(&tag_name) {
+                return Err(Error::Generic(format!("'{}' is not a valid tag name.", tag_name)));
+            }
+            if repo.refs.read_ref(&format!("refs/tags/{}", tag_name))?.is_some() {
+                return Err(Error::Generic(format!("tag '{}' already exists", tag_name)));
+            }
+            match repo.refs.read_head()? {
+                Some(oid) => Some(oid),
+                None => return Err(Error::Generic("HEAD reference not found".into())),
+            }
+        } else {
+            None
+        };
+
+        let sprint_manager = SprintManager::new(root_path);
+        let tasks = sprint_manager
+            .get_sprint_tasks(&sprint_branch)
+            .unwrap_or_else(|_| HashMap::new());
+
+        // Record the final velocity before any tasks are carried over
+        let (planned, completed) = tasks.values().fold((0, 0), |(total, completed), task| {
+            let points = task.story_points.unwrap_or(0);
+            if task.status == TaskStatus::Done {
+                (total + points, completed + points)
+            } else {
+                (total + points, completed)
+            }
+        });
+        sprint_metadata.planned_points = Some(planned);
+        sprint_metadata.completed_points = Some(completed);
+
+        let summary = Self::summary(&tasks, &sprint_metadata);
+
+        // get_sprint_metadata looks under refs/meta/<key> first, so the closed state is
+        // always what later lookups see
+        branch_manager.store_sprint_metadata(&sprint_key, &sprint_metadata)?;
+
+        println!("Sprint '{}' closed.", sprint_metadata.name);
+        println!("Velocity: {}/{} story points completed", completed, planned);
+
+        if let Some((target_branch, target_name)) = carry_over {
+            let moved = sprint_manager.carry_over_tasks(&sprint_branch, &target_branch)?;
+            if moved.is_empty() {
+                println!("No unfinished tasks to carry over.");
+            } else {
+                println!("Carried over {} task(s) to sprint '{}': {}", moved.len(), target_name, moved.join(", "));
+            }
+        }
+
+        if let Some(head_oid) = tag_target {
+            let mut tag_object = Tag::new(
+                head_oid.clone(),
+                "commit".to_string(),
+                tag_name.clone(),
+                TagCommand::current_tagger(),
+                summary,
+            );
+            let tag_oid = repo.database.store(&mut tag_object)?;
+            repo.refs.create_tag(&tag_name, &tag_oid)?;
+
+            let short_oid = if head_oid.len() >= 7 { &head_oid[0..7] } else { &head_oid };
+            println!("Tagged {} as '{}'", short_oid, tag_name);
+        }
+
+        Ok(())
+    }
+
+    /// Looks up a sprint by name, returning its metadata key as `get_all_sprints` reports it
+    fn find_sprint(branch_manager: &BranchMetadataManager, name: &str) -> Result<(String, SprintMetadata), Error> {
+        let wanted = name.strip_prefix("sprint-").unwrap_or(name).to_lowercase();
+        branch_manager.get_all_sprints()?
+            .into_iter()
+            .find(|(key, metadata)| {
+                key.to_lowercase() == wanted
+                    || metadata.to_branch_name() == format!("sprint-{}", wanted.replace(' ', "-"))
+            })
+            .ok_or_else(|| Error::Generic(format!("Sprint '{}' not found", name)))
+    }
+
+    fn sprint_branch(sprint_key: &str) -> String {
+        if sprint_key.starts_with("sprint-") {
+            sprint_key.to_string()
+        } else {
+            format!("sprint-{}", sprint_key)
+        }
+    }
+
+    /// Summary of the sprint's dates and task progress, used as the tag message
+    fn summary(tasks: &HashMap<String, Task>, metadata: &SprintMetadata) -> String {
+        let done = tasks.values().filter(|task| task.status == TaskStatus::Done).count();
+        let total_points = metadata.planned_points.unwrap_or(0);
+        let completed_points = metadata.completed_points.unwrap_or(0);
+
+        let closed = metadata.closed_timestamp.unwrap_or_else(|| metadata.end_timestamp());
+
+        let mut message = format!("Sprint '{}' end\n\n", metadata.name);
+        message.push_str(&format!("Start date: {}\n", SprintMetadata::format_date(metadata.start_timestamp)));
+        message.push_str(&format!("Planned end: {}\n", SprintMetadata::format_date(metadata.end_timestamp())));
+        message.push_str(&format!("Closed: {}\n", SprintMetadata::format_date(closed)));
+        message.push_str(&format!("Tasks completed: {}/{}\n", done, tasks.len()));
+        message.push_str(&format!("Story points completed: {}/{}\n", completed_points, total_points));
+        message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use crate::commands::sprint::sprint_start::SprintStartCommand;
+    use crate::test_support::TestRepo;
+
+    #[test]
+    fn tagging_without_a_head_leaves_the_sprint_open() {
+        let repo = TestRepo::new();
+        repo.commit_files(&[("f.txt", "one\n")], "first");
+        SprintStartCommand::execute("alpha", 14).unwrap();
+        let head = fs::read_to_string(repo.path().join(".ash/HEAD")).unwrap();
+        fs::write(repo.path().join(".ash/HEAD"), "ref: refs/heads/unborn\n").unwrap();
+
+        assert!(SprintCloseCommand::execute(None, true, None).is_err());
+        let branch_manager = BranchMetadataManager::new(Path::new("."));
+        let (_, metadata) = branch_manager.find_active_sprint().unwrap().expect("sprint was closed");
+        assert!(!metadata.is_closed());
+
+        fs::write(repo.path().join(".ash/HEAD"), head).unwrap();
+        SprintCloseCommand::execute(None, true, None).unwrap();
+        assert!(branch_manager.find_active_sprint().unwrap().is_none());
+        assert!(repo.repo().refs.read_ref("refs/tags/sprint-alpha-end").unwrap().is_some());
+    }
+}
@@ -84,8 +84,8 @@ impl SprintCommitMapCommand {
             .unwrap()
             .date();
         
-        // Get all commits in the sprint branch
-        let commits = get_commits_in_branch(&mut repository, &branch_name)?;
+        // Get the commits made on the sprint branch during the sprint
+        let commits = sprint_commits(&mut repository, &branch_name, start_date, end_date)?;
         
         // Create commit heatmap pentru fiecare zi din sprint (map data -> activitate orară)
         let mut commit_heatmap = HashMap::new();
@@ -101,17 +101,13 @@ impl SprintCommitMapCommand {
         // Contorizează commit-urile
         for commit in commits {
             let commit_time = NaiveDateTime::from_timestamp_opt(commit.committer.timestamp.timestamp(), 0).unwrap();
-            let commit_date = commit_time.date();
             
-            // Only count commits within the sprint period
-            if commit_date >= start_date && commit_date <= end_date {
-                // Get hour of day (0-23)
-                let hour_idx = commit_time.hour() as usize;
-                
-                // Incrementează numărul de commit-uri pentru această zi și oră
-                if let Some(day_data) = commit_heatmap.get_mut(&commit_date) {
-                    day_data[hour_idx] += 1;
-                }
+            // Get hour of day (0-23)
+            let hour_idx = commit_time.hour() as usize;
+            
+            // Incrementează numărul de commit-uri pentru această zi și oră
+            if let Some(day_data) = commit_heatmap.get_mut(&commit_time.date()) {
+                day_data[hour_idx] += 1;
             }
         }
         
@@ -447,6 +443,17 @@ fn create_commit_map_content(data: &CommitMapData, visible_height: usize) -> Vec
     lines
 }
 
+/// Commits on a sprint branch whose commit date falls within the sprint's start and end dates
+pub(crate) fn sprint_commits(repository: &mut Repository, branch: &str, start_date: NaiveDate, end_date: NaiveDate) -> Result<Vec<Commit>, Error> {
+    let commits = get_commits_in_branch(repository, branch)?;
+    Ok(commits.into_iter()
+        .filter(|commit| {
+            let commit_date = commit.committer.timestamp.date_naive();
+            commit_date >= start_date && commit_date <= end_date
+        })
+        .collect())
+}
+
 // Helper function to get all commits in a branch
 fn get_commits_in_branch(repository: &mut Repository, branch: &str) -> Result<Vec<Commit>, Error> {
     let mut commits = Vec::new();
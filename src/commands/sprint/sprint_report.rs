@@ -0,0 +1,210 @@
+use std::path::Path;
+use std::collections::HashMap;
+use chrono::DateTime;
+use serde::Serialize;
+
+use crate::errors::error::Error;
+use crate::commands::sprint::sprint_commitmap::sprint_commits;
+use crate::core::branch_metadata::{BranchMetadataManager, SprintMetadata};
+use crate::core::commit_metadata::CommitMetadataManager;
+use crate::core::repository::repository::Repository;
+use crate::core::sprint::sprint::{SprintManager, TaskStatus};
+
+/// Prints an end-of-sprint retrospective: story points, task status, commits and contributors.
+pub struct SprintReportCommand;
+
+#[derive(Serialize)]
+struct SprintReport {
+    sprint: String,
+    branch: String,
+    start_date: String,
+    end_date: String,
+    closed: bool,
+    planned_points: u32,
+    completed_points: u32,
+    tasks: Vec<TaskReport>,
+    commit_count: usize,
+    contributors: Vec<Contributor>,
+}
+
+#[derive(Serialize)]
+struct TaskReport {
+    id: String,
+    description: String,
+    status: String,
+    story_points: Option<u32>,
+    commits: usize,
+    depends_on: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct Contributor {
+    name: String,
+    email: String,
+    commits: usize,
+}
+
+impl SprintReportCommand {
+    /// Reports on the named sprint, or the active one. With `json` the report is printed as one
+    /// JSON object instead of a formatted summary.
+    pub fn execute(sprint_name: Option<&str>, json: bool) -> Result<(), Error> {
+        let root_path = Path::new(".");
+        let git_path = root_path.join(".ash");
+
+        if !git_path.exists() {
+            return Err(Error::Generic("Not an ash repository: .ash directory not found".into()));
+        }
+
+        let mut repository = Repository::new(".")?;
+        let branch_manager = BranchMetadataManager::new(root_path);
+        let sprint_manager = SprintManager::new(root_path);
+        let task_manager = CommitMetadataManager::new(root_path);
+
+        // Find the target sprint (active sprint if none specified)
+        let (branch_name, sprint_metadata) = match sprint_name {
+            Some(name) => {
+                let branch_name = format!("sprint-{}", name.to_lowercase());
+                match branch_manager.get_sprint_metadata(&branch_name)? {
+                    Some(meta) => (branch_name, meta),
+                    None => return Err(Error::Generic(format!("Sprint '{}' not found", name))),
+                }
+            },
+            None => {
+                match branch_manager.find_active_sprint()? {
+                    Some((branch, meta)) => (branch, meta),
+                    None => return Err(Error::Generic("No active sprint found".into())),
+                }
+            }
+        };
+
+        let report = Self::build_report(&mut repository, &sprint_manager, &task_manager, &branch_name, &sprint_metadata)?;
+
+        if json {
+            let text = serde_json::to_string_pretty(&report)
+                .map_err(|e| Error::Generic(format!("Failed to serialize sprint report: {}", e)))?;
+            println!("{}", text);
+        } else {
+            print_report(&report);
+        }
+
+        Ok(())
+    }
+
+    fn build_report(
+        repository: &mut Repository,
+        sprint_manager: &SprintManager,
+        task_manager: &CommitMetadataManager,
+        branch_name: &str,
+        metadata: &SprintMetadata,
+    ) -> Result<SprintReport, Error> {
+        let mut tasks: Vec<_> = sprint_manager.get_sprint_tasks(branch_name)?.into_values().collect();
+        tasks.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let planned_points = tasks.iter().filter_map(|task| task.story_points).sum();
+        let completed_points = tasks.iter()
+            .filter(|task| task.status == TaskStatus::Done)
+            .filter_map(|task| task.story_points)
+            .sum();
+
+        let mut task_reports = Vec::new();
+        for task in tasks {
+            // Dependencies are only kept in the task metadata, not on sprint tasks
+            let depends_on = task_manager.get_task_metadata(&task.id)?
+                .map(|meta| meta.depends_on)
+                .unwrap_or_default();
+            task_reports.push(TaskReport {
+                status: status_label(&task.status).to_string(),
+                commits: task.commits.len(),
+                id: task.id,
+                description: task.description,
+                story_points: task.story_points,
+                depends_on,
+            });
+        }
+
+        // Commits are mapped to the sprint by the sprint's date window, as in `sprint commitmap`
+        let start_date = DateTime::from_timestamp(metadata.start_timestamp as i64, 0)
+            .unwrap()
+            .date_naive();
+        let end_date = DateTime::from_timestamp(metadata.end_timestamp() as i64, 0)
+            .unwrap()
+            .date_naive();
+        let commits = sprint_commits(repository, branch_name, start_date, end_date)?;
+
+        let mut counts: HashMap<(String, String), usize> = HashMap::new();
+        for commit in &commits {
+            *counts.entry((commit.author.name.clone(), commit.author.email.clone())).or_insert(0) += 1;
+        }
+        let mut contributors: Vec<Contributor> = counts.into_iter()
+            .map(|((name, email), commits)| Contributor { name, email, commits })
+            .collect();
+        contributors.sort_by(|a, b| b.commits.cmp(&a.commits).then_with(|| a.name.cmp(&b.name)));
+
+        Ok(SprintReport {
+            sprint: metadata.name.clone(),
+            branch: branch_name.to_string(),
+            start_date: SprintMetadata::format_date(metadata.start_timestamp),
+            end_date: SprintMetadata::format_date(metadata.end_timestamp()),
+            closed: metadata.is_closed(),
+            planned_points,
+            completed_points,
+            tasks: task_reports,
+            commit_count: commits.len(),
+            contributors,
+        })
+    }
+}
+
+fn status_label(status: &TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::Todo => "todo",
+        TaskStatus::InProgress => "in progress",
+        TaskStatus::Done => "done",
+    }
+}
+
+fn print_report(report: &SprintReport) {
+    // How many contributors the summary lists; the JSON report has all of them
+    const TOP_CONTRIBUTORS: usize = 5;
+
+    let progress = if report.planned_points > 0 {
+        report.completed_points as f32 / report.planned_points as f32 * 100.0
+    } else {
+        0.0
+    };
+
+    println!("Sprint Report: {}{}", report.sprint, if report.closed { " (closed)" } else { "" });
+    println!("  Branch: {}", report.branch);
+    println!("  Dates: {} - {}", report.start_date, report.end_date);
+    println!();
+    println!("Story Points:");
+    println!("  Planned: {}", report.planned_points);
+    println!("  Completed: {} ({:.1}%)", report.completed_points, progress);
+    println!();
+
+    let done = report.tasks.iter().filter(|task| task.status == "done").count();
+    println!("Tasks ({} of {} done):", done, report.tasks.len());
+    if report.tasks.is_empty() {
+        println!("  No tasks in this sprint");
+    }
+    for task in &report.tasks {
+        let points = task.story_points.map(|points| format!("{}sp", points)).unwrap_or_else(|| "-".to_string());
+        println!(
+            "  {:<12} {:<13} {:>4}  {:>3} commit(s)  {}",
+            task.id,
+            format!("[{}]", task.status.to_uppercase()),
+            points,
+            task.commits,
+            task.description
+        );
+    }
+    println!();
+
+    println!("Commits: {}", report.commit_count);
+    if !report.contributors.is_empty() {
+        println!("Top Contributors:");
+        for contributor in report.contributors.iter().take(TOP_CONTRIBUTORS) {
+            println!("  {:>4}  {} <{}>", contributor.commits, contributor.name, contributor.email);
+        }
+    }
+}
@@ -71,21 +71,28 @@ impl SprintVelocityCommand {
         let mut completion_rates = Vec::with_capacity(sprint_count);
         
         for (branch_name, sprint_metadata) in &sprint_branches {
-            // Get tasks for this sprint
-            if let Ok(tasks) = sprint_manager.get_sprint_tasks(branch_name) {
-                let mut planned = 0;
-                let mut completed = 0;
-                
-                for (_, task) in &tasks {
-                    if let Some(points) = task.story_points {
-                        planned += points;
-                        
-                        if task.status == TaskStatus::Done {
-                            completed += points;
+            // Closed sprints recorded their points when closing; tasks may have been carried over since
+            let points = match (sprint_metadata.planned_points, sprint_metadata.completed_points) {
+                (Some(planned), Some(completed)) => Some((planned, completed)),
+                _ => sprint_manager.get_sprint_tasks(branch_name).ok().map(|tasks| {
+                    let mut planned = 0;
+                    let mut completed = 0;
+                    
+                    for task in tasks.values() {
+                        if let Some(points) = task.story_points {
+                            planned += points;
+                            
+                            if task.status == TaskStatus::Done {
+                                completed += points;
+                            }
                         }
                     }
-                }
-                
+                    
+                    (planned, completed)
+                }),
+            };
+            
+            if let Some((planned, completed)) = points {
                 let completion_rate = if planned > 0 {
                     (completed as f64 / planned as f64) * 100.0
                 } else {
@@ -0,0 +1,409 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::errors::error::Error;
+use crate::core::repository::repository::Repository;
+use crate::core::database::blob::Blob;
+use crate::core::database::commit::Commit;
+use crate::core::database::entry::DatabaseEntry;
+use crate::core::database::tree::store_tree;
+use crate::core::file_mode::FileMode;
+use crate::core::merge::inputs::CherryPick;
+use crate::core::merge::resolve::{MergeStrategy, Resolve};
+use crate::core::progress::NoProgress;
+use crate::core::refs::{Reference, HEAD};
+use crate::core::path_filter::PathFilter;
+use crate::commands::commit_writer::CommitWriter;
+use crate::commands::reset::ResetCommand;
+use crate::commands::patch_mode;
+use crate::core::workspace::path_key;
+
+/// Ref pointing at the most recent stash entry; older entries live in its reflog.
+const STASH_REF: &str = "refs/stash";
+
+/// Implements the 'stash' command for AsheraFlow.
+/// Saves local modifications away and restores them later.
+pub struct StashCommand;
+
+impl StashCommand {
+    /// Records the index and working tree as two commits on top of HEAD, then resets the index
+    /// and working tree to HEAD. As in git, the working-tree commit is the stash entry, with HEAD
+    /// as its first parent and the index commit as its second.
+    /// With `include_untracked`, untracked files are recorded in a parentless commit, which
+    /// becomes the third parent, and removed.
+    /// With `keep_index`, the index and working tree are reset to the staged state instead of HEAD.
+    pub fn push(message: Option<&str>, include_untracked: bool, keep_index: bool) -> Result<(), Error> {
+        let mut repo = Repository::new(".")?;
+        let root_path = repo.path.clone();
+        let git_path = root_path.join(".ash");
+
+        let head_oid = repo.refs.read_head()?
+            .ok_or_else(|| Error::Generic("You do not have the initial commit yet".to_string()))?;
+        let head_commit = Self::load_commit(&mut repo, &head_oid)?;
+
+        repo.index.load_for_update()?;
+
+        // Tree of the staged changes
+        let index_tree = {
+            let mut writer = CommitWriter::new(
+                &root_path,
+                git_path.clone(),
+                &mut repo.database,
+                &mut repo.index,
+                &repo.refs,
+            );
+            writer.write_tree()
+        };
+        let index_tree = match index_tree {
+            Ok(tree) => tree,
+            Err(e) => {
+                repo.index.rollback()?;
+                return Err(e);
+            }
+        };
+
+        // Tree of the tracked files as they are in the working tree
+        let work_tree = match Self::write_work_tree(&mut repo) {
+            Ok(oid) => oid,
+            Err(e) => {
+                repo.index.rollback()?;
+                return Err(e);
+            }
+        };
+
+        let untracked = if include_untracked {
+            Self::untracked_files(&repo)?
+        } else {
+            Vec::new()
+        };
+
+        if index_tree == head_commit.get_tree() && work_tree == head_commit.get_tree() && untracked.is_empty() {
+            repo.index.rollback()?;
+            println!("No local changes to save");
+            return Ok(());
+        }
+
+        let branch = Self::current_branch_name(&repo)?;
+        let summary = format!(
+            "{}: {} {}",
+            branch,
+            repo.database.short_oid(&head_oid),
+            head_commit.title_line()
+        );
+        let description = match message {
+            Some(message) => format!("On {}: {}", branch, message),
+            None => format!("WIP on {}", summary),
+        };
+
+        let author = {
+            let writer = CommitWriter::new(
+                &root_path,
+                git_path.clone(),
+                &mut repo.database,
+                &mut repo.index,
+                &repo.refs,
+            );
+            writer.current_author()
+        };
+
+        let mut index_commit = Commit::new(
+            vec![head_oid.clone()],
+            index_tree,
+            author.clone(),
+            format!("index on {}", summary),
+        );
+        let index_commit_oid = repo.database.store(&mut index_commit)?;
+
+        let mut parents = vec![head_oid.clone(), index_commit_oid.clone()];
+        if !untracked.is_empty() {
+            let untracked_tree = Self::write_files_tree(&mut repo, &untracked)?;
+            let mut untracked_commit = Commit::new(
+                Vec::new(),
+                untracked_tree,
+                author.clone(),
+                format!("untracked files on {}", summary),
+            );
+            let untracked_commit_oid = repo.database.store(&mut untracked_commit)?;
+            parents.push(untracked_commit_oid);
+        }
+
+        let mut work_commit = Commit::new(
+            parents,
+            work_tree,
+            author,
+            description.clone(),
+        );
+        let work_commit_oid = repo.database.store(&mut work_commit)?;
+
+        // Reset the working tree and index back to HEAD, or to the staged state with --keep-index
+        let target_oid = if keep_index { &index_commit_oid } else { &head_oid };
+        if let Err(e) = Self::restore_head(&mut repo, &work_commit_oid, target_oid) {
+            repo.index.rollback()?;
+            return Err(e);
+        }
+        for path in &untracked {
+            Self::remove_untracked(&repo.workspace.root_path, path)?;
+        }
+        repo.index.set_changed(true);
+        repo.index.write_updates()?;
+
+        let old_stash = repo.refs.read_ref(STASH_REF)?;
+        repo.refs.update_ref(STASH_REF, &work_commit_oid)?;
+        repo.refs.append_reflog(STASH_REF, old_stash.as_deref(), &work_commit_oid, &description)?;
+
+        println!("Saved working directory and index state {}", description);
+        Ok(())
+    }
+
+    /// Applies the most recent stash entry to the working tree and drops it.
+    /// On conflicts the entry is kept and conflict markers are written.
+    pub fn pop() -> Result<(), Error> {
+        let mut repo = Repository::new(".")?;
+
+        let mut entries = repo.refs.read_reflog(STASH_REF)?;
+        let stash_oid = match entries.last() {
+            Some(entry) => entry.new_oid.clone(),
+            None => return Err(Error::Generic("No stash entries found.".to_string())),
+        };
+
+        let head_oid = repo.refs.read_head()?
+            .ok_or_else(|| Error::Generic("You do not have the initial commit yet".to_string()))?;
+        let stash_commit = Self::load_commit(&mut repo, &stash_oid)?;
+        let stash_base = stash_commit.get_parent().cloned()
+            .ok_or_else(|| Error::Generic(format!("{} is not a stash commit", stash_oid)))?;
+
+        // Untracked files are only restored where nothing is in the way
+        let untracked = match stash_commit.get_parents().get(2) {
+            Some(oid) => patch_mode::commit_files(&mut repo, oid)?,
+            None => Default::default(),
+        };
+        let mut untracked: Vec<_> = untracked.into_iter().collect();
+        untracked.sort_by(|a, b| a.0.cmp(&b.0));
+        for (path, _) in &untracked {
+            if repo.workspace.root_path.join(path).exists() {
+                return Err(Error::Generic(format!("{} already exists, no checkout; could not restore untracked files from stash", path)));
+            }
+        }
+
+        let inputs = CherryPick::new(
+            "Updated upstream".to_string(),
+            "Stashed changes".to_string(),
+            head_oid,
+            stash_oid.clone(),
+            vec![stash_base],
+        );
+
+        repo.index.load_for_update()?;
+
+        let result = Resolve::new(&mut repo.database, &repo.workspace, &mut repo.index, &inputs, MergeStrategy::Recursive).execute(&NoProgress);
+
+        if repo.index.has_conflict() {
+            repo.index.write_updates()?;
+            println!("The stash entry is kept in case you need it again.");
+            return Err(Error::Generic("Conflicts while applying stash; fix them and drop the entry manually".to_string()));
+        }
+
+        if let Err(e) = result {
+            repo.index.rollback()?;
+            return Err(e);
+        }
+
+        repo.index.write_updates()?;
+
+        for (path, (oid, _)) in &untracked {
+            let blob = repo.database.load(oid)?;
+            repo.workspace.write_file(Path::new(path), &blob.to_bytes())?;
+        }
+
+        // Drop the applied entry and point refs/stash at the next one
+        entries.pop();
+        repo.refs.rewrite_reflog(STASH_REF, &entries)?;
+        match entries.last() {
+            Some(entry) => repo.refs.update_ref(STASH_REF, &entry.new_oid)?,
+            None => repo.refs.delete_ref(STASH_REF)?,
+        }
+
+        println!("Dropped refs/stash@{{0}} ({})", stash_oid);
+        Ok(())
+    }
+
+    /// Lists stash entries, newest first.
+    pub fn list() -> Result<(), Error> {
+        let repo = Repository::new(".")?;
+        let entries = repo.refs.read_reflog(STASH_REF)?;
+
+        for (n, entry) in entries.iter().rev().enumerate() {
+            println!("stash@{{{}}}: {}", n, entry.message);
+        }
+
+        Ok(())
+    }
+
+    /// Stores blobs for the tracked files in the working tree and returns the resulting tree OID.
+    /// Tracked files missing from the working tree are left out, recording their deletion.
+    fn write_work_tree(repo: &mut Repository) -> Result<String, Error> {
+        let mut tracked: Vec<String> = repo.index.each_entry()
+            .map(|entry| entry.get_path().to_string())
+            .collect();
+        tracked.dedup();
+
+        Self::write_files_tree(repo, &tracked)
+    }
+
+    /// Files in the working tree that are neither tracked nor ignored
+    fn untracked_files(repo: &Repository) -> Result<Vec<String>, Error> {
+        let mut untracked: Vec<String> = repo.workspace.list_files()?
+            .iter()
+            .map(|path| path_key(path))
+            .filter(|path| !repo.index.tracked(path))
+            .collect();
+        untracked.sort();
+        Ok(untracked)
+    }
+
+    /// Stores blobs for the given working tree files and returns the OID of a tree holding them.
+    /// Files missing from the working tree are left out.
+    fn write_files_tree(repo: &mut Repository, paths: &[String]) -> Result<String, Error> {
+        let mut entries = Vec::with_capacity(paths.len());
+        for path in paths.iter().cloned() {
+            let path_buf = PathBuf::from(&path);
+            if !repo.workspace.path_exists(&path_buf)? {
+                continue;
+            }
+
+            let data = repo.workspace.read_file(&path_buf)?;
+            let stat = repo.workspace.stat_file(&path_buf)?;
+
+            let mut blob = Blob::new(data);
+            let oid = repo.database.store(&mut blob)?;
+            let mode = FileMode::from_metadata(&stat);
+
+            entries.push(DatabaseEntry::new(path, oid, &mode.to_octal_string()));
+        }
+
+        store_tree(&mut repo.database, &entries)
+    }
+
+    /// Rewrites the working tree files that differ between the stash and HEAD, then resets the index to HEAD
+    fn restore_head(repo: &mut Repository, stash_oid: &str, head_oid: &str) -> Result<(), Error> {
+        let diff = repo.database.tree_diff(Some(stash_oid), Some(head_oid), &PathFilter::new())?;
+
+        for (path, (_, new_entry)) in diff {
+            match new_entry {
+                Some(entry) if !entry.get_file_mode().is_directory() => {
+                    let blob = repo.database.load(entry.get_oid())?;
+                    repo.workspace.write_file(&path, &blob.to_bytes())?;
+                },
+                Some(_) => {},
+                None => {
+                    if repo.workspace.root_path.join(&path).is_file() {
+                        repo.workspace.remove_file(&path)?;
+                    }
+                }
+            }
+        }
+
+        ResetCommand::reset_tree(repo, head_oid, None)
+    }
+
+    /// Removes a stashed untracked file and any directories it leaves empty
+    fn remove_untracked(root_path: &Path, path: &str) -> Result<(), Error> {
+        let full_path = root_path.join(path);
+        fs::remove_file(&full_path)?;
+
+        let mut dir = full_path.parent();
+        while let Some(parent) = dir {
+            if parent == root_path || fs::remove_dir(parent).is_err() {
+                break;
+            }
+            dir = parent.parent();
+        }
+
+        Ok(())
+    }
+
+    fn load_commit(repo: &mut Repository, oid: &str) -> Result<Commit, Error> {
+        let object = repo.database.load(oid)?;
+        object.as_any().downcast_ref::<Commit>()
+            .cloned()
+            .ok_or_else(|| Error::Generic(format!("Object {} is not a commit", oid)))
+    }
+
+    fn current_branch_name(repo: &Repository) -> Result<String, Error> {
+        Ok(match repo.refs.current_ref()? {
+            Reference::Symbolic(path) if path != HEAD => repo.refs.short_name(&path),
+            _ => "(no branch)".to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestRepo;
+
+    fn stash_entries(repo: &TestRepo) -> usize {
+        repo.repo().refs.read_reflog(STASH_REF).unwrap().len()
+    }
+
+    #[test]
+    fn pop_restores_a_pushed_change_and_drops_the_entry() {
+        let repo = TestRepo::new();
+        repo.commit_files(&[("f.txt", "one\ntwo\nthree\n"), ("g.txt", "other\n")], "base");
+        repo.write("f.txt", "one\nTWO\nthree\n");
+
+        StashCommand::push(None, false, false).unwrap();
+        assert_eq!(repo.read("f.txt"), "one\ntwo\nthree\n");
+        assert_eq!(stash_entries(&repo), 1);
+
+        StashCommand::pop().unwrap();
+        assert_eq!(repo.read("f.txt"), "one\nTWO\nthree\n");
+        assert_eq!(repo.read("g.txt"), "other\n");
+        assert_eq!(stash_entries(&repo), 0);
+        assert_eq!(repo.repo().refs.read_ref(STASH_REF).unwrap(), None);
+    }
+
+    #[test]
+    fn include_untracked_stashes_and_restores_untracked_files() {
+        let repo = TestRepo::new();
+        repo.commit_files(&[("f.txt", "one\n")], "base");
+        repo.write("f.txt", "changed\n");
+        repo.write("notes/new.txt", "untracked\n");
+
+        StashCommand::push(None, true, false).unwrap();
+        assert_eq!(repo.read("f.txt"), "one\n");
+        assert!(!repo.exists("notes"));
+
+        // Like git: HEAD, then the index commit, then the untracked files commit
+        let mut r = repo.repo();
+        let stash_oid = r.refs.read_ref(STASH_REF).unwrap().unwrap();
+        let stash = StashCommand::load_commit(&mut r, &stash_oid).unwrap();
+        assert_eq!(stash.get_parents().len(), 3);
+        assert_eq!(stash.get_parents()[0], repo.head());
+        let untracked = patch_mode::commit_files(&mut r, &stash.get_parents()[2]).unwrap();
+        assert_eq!(untracked.keys().collect::<Vec<_>>(), ["notes/new.txt"]);
+
+        StashCommand::pop().unwrap();
+        assert_eq!(repo.read("f.txt"), "changed\n");
+        assert_eq!(repo.read("notes/new.txt"), "untracked\n");
+        assert_eq!(stash_entries(&repo), 0);
+    }
+
+    #[test]
+    fn keep_index_leaves_staged_changes_in_place() {
+        let repo = TestRepo::new();
+        repo.commit_files(&[("staged.txt", "one\n"), ("unstaged.txt", "one\n")], "base");
+        repo.write("staged.txt", "staged\n");
+        repo.add(&["staged.txt"]);
+        repo.write("unstaged.txt", "unstaged\n");
+
+        StashCommand::push(None, false, true).unwrap();
+        assert_eq!(repo.read("staged.txt"), "staged\n");
+        assert_eq!(repo.staged_oid("staged.txt"), Some(repo.blob_oid("staged\n")));
+        assert_eq!(repo.read("unstaged.txt"), "one\n");
+
+        StashCommand::pop().unwrap();
+        assert_eq!(repo.read("staged.txt"), "staged\n");
+        assert_eq!(repo.read("unstaged.txt"), "unstaged\n");
+        assert_eq!(stash_entries(&repo), 0);
+    }
+}
@@ -8,19 +8,32 @@ use crate::core::color::Color;
 use crate::core::database::database::Database;
 use crate::core::database::blob::Blob;
 use crate::core::database::entry::DatabaseEntry;
-use crate::core::database::tree::{Tree, TreeEntry};
+use crate::core::database::tree::{index_tree_entries, Tree, TreeEntry};
 use crate::core::database::commit::Commit;
 use crate::core::file_mode::FileMode;
 use crate::core::index::entry::Entry;
 use crate::core::index::index::Index;
 use crate::core::refs::Refs;
-use crate::core::workspace::Workspace;
+use crate::core::workspace::{path_key, WalkEntry, Workspace};
+use crate::core::tree_cache::TreeCache;
+use crate::core::untracked_cache::UntrackedCache;
 use crate::errors::error::Error;
+use crate::core::repository::repository::Repository;
 use crate::core::database::tree::TREE_MODE;
+use crate::core::refs::Reference;
+use crate::core::branch_metadata::BranchMetadataManager;
+use crate::core::merge::common_ancestors::ahead_behind;
+
+/// File under `.ash` caching ahead/behind counts, keyed by the pair of tips they were computed for
+const AHEAD_BEHIND_CACHE: &str = "ahead-behind";
+/// Maximum number of tip pairs remembered in the ahead/behind cache
+const AHEAD_BEHIND_CACHE_LIMIT: usize = 32;
+/// Object ID used in porcelain v2 output for a missing side
+const NULL_OID: &str = "0000000000000000000000000000000000000000";
 
 /// Enum representing the types of changes detected by the status command.
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
-enum ChangeType {
+pub enum ChangeType {
     WorkspaceModified,
     WorkspaceDeleted,
     IndexAdded,
@@ -28,6 +41,21 @@ enum ChangeType {
     IndexDeleted,
 }
 
+/// Result of comparing HEAD, the index and the workspace, independent of how it is printed.
+#[derive(Debug, Default)]
+pub struct StatusReport {
+    /// Files (or `dir/` entries) present in the workspace but not in the index
+    pub untracked: HashSet<String>,
+    /// Paths with at least one recorded change
+    pub changed: HashSet<String>,
+    /// Map of path -> set of change types
+    pub changes: HashMap<String, HashSet<ChangeType>>,
+    /// Flattened HEAD tree (files and directories), keyed by path
+    pub head_tree: HashMap<String, DatabaseEntry>,
+    /// Workspace metadata of the tracked files, read once while scanning
+    pub stats: HashMap<String, fs::Metadata>,
+}
+
 /// Main struct for the status command logic.
 pub struct StatusCommand;
 
@@ -79,47 +107,40 @@ impl StatusCommand {
         FileMode::from_metadata(stat)
     }
     
-    /// Check if a directory contains trackable files (recursively)
-    fn is_trackable_dir(dir_path: &Path) -> Result<bool, Error> {
-        if !dir_path.is_dir() {
-            return Ok(false);
+    /// Check if a directory contains trackable files (recursively), skipping hidden entries.
+    /// With an untracked cache, a directory whose contents haven't changed isn't walked again.
+    fn is_trackable_dir(workspace: &Workspace, dir_path: &Path, cache: Option<&mut UntrackedCache>) -> Result<bool, Error> {
+        let key = path_key(dir_path);
+        let mut cache = cache;
+        if let Some(trackable) = cache.as_deref_mut().and_then(|cache| cache.lookup(&key)) {
+            return Ok(trackable);
         }
         
-        // Check if directory contains non-hidden files
-        match std::fs::read_dir(dir_path) {
-            Ok(entries) => {
-                for entry_result in entries {
-                    match entry_result {
-                        Ok(entry) => {
-                            let path = entry.path();
-                            let file_name = entry.file_name();
-                            
-                            // Skip hidden files and directories
-                            if let Some(name) = file_name.to_str() {
-                                if name.starts_with('.') {
-                                    continue;
-                                }
-                            }
-                            
-                            if path.is_file() {
-                                // Found a trackable file
-                                return Ok(true);
-                            } else if path.is_dir() {
-                                // Recursively check subdirectories
-                                if Self::is_trackable_dir(&path)? {
-                                    return Ok(true);
-                                }
-                            }
-                        },
-                        Err(e) => return Err(Error::IO(e)),
-                    }
-                }
-                
-                // No trackable files found
-                Ok(false)
-            },
-            Err(e) => Err(Error::IO(e)),
+        let is_hidden = |entry: &WalkEntry| {
+            entry.path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.'))
+        };
+        
+        // Every directory read, so the cache can tell when the answer goes stale
+        let mut visited = vec![dir_path.to_path_buf()];
+        let mut trackable = false;
+        for entry in workspace.walk(dir_path, |entry| !is_hidden(entry))? {
+            let entry = entry?;
+            if is_hidden(&entry) {
+                continue;
+            }
+            if entry.metadata.is_file() {
+                trackable = true;
+                break;
+            }
+            if entry.metadata.is_dir() {
+                visited.push(entry.path);
+            }
+        }
+        
+        if let Some(cache) = cache {
+            cache.record(&key, trackable, &visited);
         }
+        Ok(trackable)
     }
     
     /// Get status for a specific path based on change types
@@ -210,14 +231,62 @@ impl StatusCommand {
         Ok(())
     }
 
-    /// Load the HEAD tree with diagnostics
+    /// Whether the staged entries hash to the tree of HEAD's commit. Only the commit object is
+    /// read; the index trees are hashed in memory without being stored.
+    fn index_matches_head(
+        refs: &Refs,
+        database: &mut Database,
+        index: &Index
+    ) -> Result<bool, Error> {
+        if index.has_conflict() {
+            return Ok(false);
+        }
+        
+        let head_oid = match refs.read_head()? {
+            Some(oid) => oid,
+            None => return Ok(false),
+        };
+        let commit_obj = database.load(&head_oid)?;
+        let head_tree_oid = match commit_obj.as_any().downcast_ref::<Commit>() {
+            Some(commit) => commit.get_tree().to_string(),
+            None => return Ok(false),
+        };
+        
+        let entries = index_tree_entries(index);
+        
+        let mut root = Tree::build(entries.iter())?;
+        root.traverse(|tree| {
+            database.hash_object(tree)?;
+            Ok(())
+        })?;
+        
+        Ok(root.get_oid() == Some(&head_tree_oid))
+    }
+    
+    /// File entries of HEAD's tree, taken from an index known to match it
+    fn head_tree_from_index(index: &Index) -> HashMap<String, DatabaseEntry> {
+        index.each_entry()
+            .map(|entry| (
+                entry.get_path().to_string(),
+                DatabaseEntry::new(entry.get_path().to_string(), entry.get_oid().to_string(), &entry.mode_octal())
+            ))
+            .collect()
+    }
+    
+    /// Load the HEAD tree with diagnostics. The flattened tree is cached per HEAD commit,
+    /// so an unchanged HEAD is only traversed once
     fn load_head_tree(
         refs: &Refs,
-        database: &mut Database
+        database: &mut Database,
+        cache: &TreeCache
     ) -> Result<HashMap<String, DatabaseEntry>, Error> {
         let mut head_tree = HashMap::new();
         
         if let Some(head_oid) = refs.read_head()? {
+            if let Some(cached) = cache.load(&head_oid) {
+                return Ok(cached);
+            }
+            
             let commit_obj = match database.load(&head_oid) {
                 Ok(obj) => {
                     obj
@@ -260,6 +329,9 @@ impl StatusCommand {
             }
             
             Self::traverse_tree_structure(database, root_tree_oid, PathBuf::new(), &mut head_tree)?;
+            
+            // The cache only saves time; status still works when it can't be written
+            cache.store(&head_oid, &head_tree).ok();
         }
         
         Ok(head_tree)
@@ -284,7 +356,7 @@ impl StatusCommand {
                     prefix.join(name)
                 };
                 
-                let path_str = entry_path.to_string_lossy().to_string();
+                let path_str = path_key(&entry_path);
                 
                 match entry {
                     TreeEntry::Blob(oid, mode) => {
@@ -328,7 +400,7 @@ impl StatusCommand {
                         prefix.join(name)
                     };
                     
-                    let path_str = entry_path.to_string_lossy().to_string();
+                    let path_str = path_key(&entry_path);
                     
                     match entry {
                         TreeEntry::Blob(blob_oid, mode) => {
@@ -419,7 +491,8 @@ impl StatusCommand {
         }
     }
 
-    // Helper method to determine if a mode string represents a directory
+    /// Whether a tree entry is a directory, judged by its mode alone; the name says nothing,
+    /// as files like `Makefile` or `LICENSE` have no extension either
     fn is_directory_from_mode(mode_str: &str) -> bool {
         let mode = FileMode::parse(mode_str);
         mode.is_directory()
@@ -435,34 +508,32 @@ impl StatusCommand {
         };
         
         // Check if any file in the index has this path as a prefix
-        index.entries.keys().any(|file_path| file_path.starts_with(&normalized_path))
+        index.keys.iter().any(|(file_path, _)| file_path.starts_with(&normalized_path))
     }
     
-    /// Main execution method
-    pub fn execute(porcelain: bool) -> Result<(), Error> {
-        let start_time = Instant::now();
-        
-        // Initialize paths and components
-        let root_path = Path::new(".");
-        let git_path = root_path.join(".ash");
-        
-        // Check if .ash directory exists
-        if !git_path.exists() {
-            return Err(Error::Generic("Not an ash repository (or any of the parent directories): .ash directory not found".into()));
-        }
-        
-        let workspace = Workspace::new(root_path);
-        let mut database = Database::new(git_path.join("objects"));
-        let mut index = Index::new(git_path.join("index"));
-        let refs = Refs::new(&git_path);
-        
-        // Load the index (with lock for potential timestamp updates)
-        if !index.load_for_update()? {
-            return Err(Error::Generic("Failed to acquire lock on index file".to_string()));
-        }
-        
-        // Load the HEAD tree with diagnostics
-        let head_tree = Self::load_head_tree(&refs, &mut database)?;
+    /// Compute the status of the repository without printing anything.
+    ///
+    /// The index must already be loaded; entries whose content is unchanged but whose
+    /// timestamps differ get their stat data refreshed, so callers holding the index lock
+    /// can persist those updates afterwards. With `untracked_cache`, untracked directories
+    /// that haven't changed since the last run are answered from `.ash/untracked-cache`.
+    pub fn compute(
+        workspace: &Workspace,
+        database: &mut Database,
+        index: &mut Index,
+        refs: &Refs,
+        untracked_cache: bool,
+    ) -> Result<StatusReport, Error> {
+        // Fast path: when the index hashes to HEAD's tree nothing is staged, so the
+        // HEAD tree doesn't have to be loaded and compared entry by entry
+        let index_matches_head = Self::index_matches_head(refs, database, index)?;
+        let head_tree = if index_matches_head {
+            Self::head_tree_from_index(index)
+        } else {
+            // Load the HEAD tree with diagnostics
+            let cache = TreeCache::new(&workspace.root_path.join(".ash"));
+            Self::load_head_tree(refs, database, &cache)?
+        };
         
         // Get tracked files from index
         let index_entries: HashMap<String, String> = index
@@ -492,42 +563,51 @@ impl StatusCommand {
         }
         
         // Step 1: Scan workspace to find untracked files
+        let mut cache = if untracked_cache {
+            Some(UntrackedCache::load(&workspace.root_path, &workspace.root_path.join(".ash")))
+        } else {
+            None
+        };
         Self::scan_workspace(
-            &workspace,
+            workspace,
             &mut untracked,
             &index_entries,
             &tracked_dirs,
-            root_path,
-            &PathBuf::new(),
-            &mut stats_cache
+            &mut stats_cache,
+            cache.as_mut()
         )?;
+        if let Some(cache) = cache.as_mut() {
+            // The cache only saves time; status still works when it can't be written
+            cache.save().ok();
+        }
         
-        // Step 2: Compare index entries with HEAD
-        for entry in index.each_entry() {
-            Self::check_index_against_head_tree(
-                entry,
+        if !index_matches_head {
+            // Step 2: Compare index entries with HEAD
+            for entry in index.each_entry() {
+                Self::check_index_against_head_tree(
+                    entry,
+                    &head_tree,
+                    &mut changed,
+                    &mut changes
+                );
+            }
+            
+            // Step 3: Find files deleted from index (in HEAD but not in index)
+            Self::check_head_tree_against_index(
                 &head_tree,
+                index,
                 &mut changed,
                 &mut changes
             );
         }
         
-        // Step 3: Find files deleted from index (in HEAD but not in index)
-        Self::check_head_tree_against_index(
-            &head_tree,
-            &index,
-            &mut changed,
-            &mut changes
-        );
+        // Step 4: Compare index entries with workspace (working tree changes).
+        // Files whose timestamps changed are collected and hashed together afterwards.
+        let mut content_checks: Vec<(&String, &String, std::fs::Metadata)> = Vec::new();
         
-        // Step 4: Compare index entries with workspace (working tree changes)
         for (path, oid) in &index_entries {
-            let path_buf = PathBuf::from(path);
-            
-            // Check if file exists
-            if !workspace.path_exists(&path_buf)? {
-                // File is in index but not in workspace (deleted)
-                Self::record_change(&mut changed, &mut changes, path.clone(), ChangeType::WorkspaceDeleted);
+            // Skip-worktree paths are not materialized, so their absence is not a deletion
+            if index.get_entry(path).is_some_and(|entry| entry.is_skip_worktree()) {
                 continue;
             }
             
@@ -554,31 +634,57 @@ impl StatusCommand {
                 }
                 
                 // If timestamps don't match, need to check content hash
-                match workspace.read_file(&path_buf) {
-                    Ok(data) => {
-                        // Calculate hash using database
-                        let computed_oid = database.hash_file_data(&data);
-                        
-                        if &computed_oid != oid {
-                            // File has changed, mark as modified
-                            Self::record_change(&mut changed, &mut changes, path.clone(), ChangeType::WorkspaceModified);
-                        } else {
-                            // File hasn't actually changed, just timestamps
-                            // Update index entry with new timestamps to avoid re-reading next time
-                            index.update_entry_stat(path, &metadata)?;
-                        }
-                    },
-                    Err(_) => {
-                        // If we can't read the file for any reason, consider it modified
-                        Self::record_change(&mut changed, &mut changes, path.clone(), ChangeType::WorkspaceModified);
-                    }
-                }
+                content_checks.push((path, oid, metadata.clone()));
             } else {
-                // No metadata in cache for an indexed file, assume it's been deleted
+                // The scan saw every tracked file that exists, so this one was deleted
                 Self::record_change(&mut changed, &mut changes, path.clone(), ChangeType::WorkspaceDeleted);
             }
         }
         
+        let candidate_paths: Vec<PathBuf> = content_checks.iter()
+            .map(|(path, _, _)| PathBuf::from(path.as_str()))
+            .collect();
+        // Files are hashed as add would store them, so with `core.eol` set they are compared
+        // in their normalized form
+        let hashes = workspace.hash_files_parallel(&candidate_paths)?;
+        
+        for ((path, oid, metadata), rel_path) in content_checks.into_iter().zip(&candidate_paths) {
+            if hashes.get(rel_path) != Some(oid) {
+                // File has changed, mark as modified
+                Self::record_change(&mut changed, &mut changes, path.clone(), ChangeType::WorkspaceModified);
+            } else {
+                // File hasn't actually changed, just timestamps
+                // Update index entry with new timestamps to avoid re-reading next time
+                index.update_entry_stat(path, &metadata)?;
+            }
+        }
+        
+        Ok(StatusReport { untracked, changed, changes, head_tree, stats: stats_cache })
+    }
+
+    /// Main execution method
+    pub fn execute(porcelain: bool, porcelain_v2: bool, short: bool, show_branch: bool, untracked_cache: bool) -> Result<(), Error> {
+        let start_time = Instant::now();
+        
+        // Initialize paths and components
+        let root_path = Path::new(".");
+        let git_path = root_path.join(".ash");
+        
+        // Check if .ash directory exists
+        if !git_path.exists() {
+            return Err(Error::Generic("Not an ash repository (or any of the parent directories): .ash directory not found".into()));
+        }
+        
+        let mut repo = Repository::new(".")?;
+        
+        // Load the index (with lock for potential timestamp updates)
+        if !repo.load_index_for_update()? {
+            return Err(Error::Generic("Failed to acquire lock on index file".to_string()));
+        }
+        let Repository { workspace, mut database, mut index, refs, .. } = repo;
+        
+        let report = Self::compute(&workspace, &mut database, &mut index, &refs, untracked_cache)?;
+        
         // Write any timestamp updates to index
         if index.is_changed() {
             index.write_updates()?;
@@ -587,108 +693,97 @@ impl StatusCommand {
             index.rollback()?;
         }
         
+        let branch = Self::current_branch(&refs)?;
+        
         // Display results
-        if porcelain {
+        if porcelain_v2 {
+            // Richer machine-readable output (--porcelain=v2 option)
+            Self::print_branch_headers_v2(&git_path, &refs, &mut database)?;
+            Self::print_porcelain_v2(&report, &index);
+        } else if porcelain {
             // Machine-readable output (--porcelain option)
-            Self::print_porcelain(&untracked, &changed, &changes);
+            let branch_header = if show_branch { Some(branch.as_deref()) } else { None };
+            Self::print_porcelain(branch_header, &report.untracked, &report.changed, &report.changes);
+        } else if short {
+            // Compact colored output (--short option)
+            Self::print_branch_header_short(&git_path, &refs, &mut database)?;
+            Self::print_short(&report.untracked, &report.changed, &report.changes);
         } else {
             // Human-readable output
-            Self::print_human_readable(&untracked, &changed, &changes);
+            let head_oid = refs.read_head()?;
+            let tracking = match (&branch, &head_oid) {
+                (Some(branch), Some(oid)) => BranchMetadataManager::new(root_path).tracking_summary(branch, oid, &mut database)?,
+                _ => None,
+            };
+            Self::print_human_readable(branch.as_deref(), head_oid.as_deref(), tracking.as_deref(), &report.untracked, &report.changed, &report.changes);
         }
         
         let elapsed = start_time.elapsed();
-        if !porcelain {
+        if !porcelain && !porcelain_v2 && !short {
             println!("\n{} {:.2}s", Color::cyan("Status completed in"), elapsed.as_secs_f32());
         }
         
         Ok(())
     }
 
+    /// Finds untracked paths and caches the metadata of tracked files. Only directories that
+    /// are tracked or hold tracked files are entered; any other directory with trackable
+    /// content is reported as a whole.
     fn scan_workspace(
         workspace: &Workspace,
         untracked: &mut HashSet<String>,
         index_entries: &HashMap<String, String>,
         tracked_dirs: &HashSet<PathBuf>,
-        root_path: &Path,
-        prefix: &Path,
         stats_cache: &mut HashMap<String, fs::Metadata>,
+        mut untracked_cache: Option<&mut UntrackedCache>,
     ) -> Result<(), Error> {
-        let current_path = if prefix.as_os_str().is_empty() {
-            root_path.to_path_buf()
-        } else {
-            root_path.join(prefix)
-        };
+        let walk = workspace.walk(Path::new(""), |entry| {
+            index_entries.contains_key(&path_key(&entry.path)) || tracked_dirs.contains(&entry.path)
+        })?;
         
-        // List files in current directory
-        match std::fs::read_dir(&current_path) {
-            Ok(entries) => {
-                for entry_result in entries {
-                    match entry_result {
-                        Ok(entry) => {
-                            let file_name = entry.file_name();
-                            let entry_path = entry.path();
-                            
-                            // Skip .ash directory
-                            if file_name == ".ash" {
-                                continue;
-                            }
-                            
-                            // Get relative path from root
-                            let rel_path = if prefix.as_os_str().is_empty() {
-                                PathBuf::from(file_name)
-                            } else {
-                                prefix.join(file_name)
-                            };
-                            
-                            let rel_path_str = rel_path.to_string_lossy().to_string();
-                            
-                            // Check if path is tracked in index
-                            let is_tracked = index_entries.contains_key(&rel_path_str);
-                            let is_in_tracked_dir = tracked_dirs.contains(&rel_path);
-                            
-                            if entry_path.is_dir() {
-                                if is_tracked || is_in_tracked_dir {
-                                    // If directory is tracked or contains tracked files, 
-                                    // scan it recursively
-                                    Self::scan_workspace(
-                                        workspace, 
-                                        untracked, 
-                                        index_entries, 
-                                        tracked_dirs,
-                                        root_path,
-                                        &rel_path,
-                                        stats_cache
-                                    )?;
-                                } else if Self::is_trackable_dir(&entry_path)? {
-                                    // If directory contains trackable files, mark it
-                                    untracked.insert(format!("{}/", rel_path_str));
-                                }
-                                // If directory is empty or contains only ignored files, skip it
-                            } else if !is_tracked {
-                                // File is not tracked in index
-                                untracked.insert(rel_path_str);
-                            } else {
-                                // File is tracked - cache metadata for later comparisons
-                                if let Ok(metadata) = entry_path.metadata() {
-                                    stats_cache.insert(rel_path_str, metadata);
-                                }
-                            }
-                        },
-                        Err(e) => return Err(Error::IO(e)),
-                    }
+        for entry in walk {
+            let entry = entry?;
+            let rel_path_str = path_key(&entry.path);
+            let is_tracked = index_entries.contains_key(&rel_path_str);
+            
+            if entry.metadata.is_dir() {
+                if !is_tracked && !tracked_dirs.contains(&entry.path) && Self::is_trackable_dir(workspace, &entry.path, untracked_cache.as_deref_mut())? {
+                    untracked.insert(format!("{}/", rel_path_str));
                 }
-            },
-            Err(e) => return Err(Error::IO(e)),
+                // If directory is empty or contains only hidden files, skip it
+            } else if !is_tracked {
+                untracked.insert(rel_path_str);
+            } else {
+                // File is tracked - keep its metadata for the later comparisons
+                stats_cache.insert(rel_path_str, entry.metadata);
+            }
         }
         
         Ok(())
     }
     
+    /// Name of the checked-out branch, or `None` when HEAD is detached
+    fn current_branch(refs: &Refs) -> Result<Option<String>, Error> {
+        Ok(match refs.current_ref()? {
+            Reference::Symbolic(path) if path != "HEAD" => Some(refs.short_name(&path)),
+            _ => None,
+        })
+    }
+    
+    /// `branch_header` is `Some` when `--branch` asked for a `## <branch>` line,
+    /// holding the branch name or `None` for a detached HEAD
     fn print_porcelain(
+        branch_header: Option<Option<&str>>,
         untracked: &HashSet<String>,
         changed: &HashSet<String>,
         changes: &HashMap<String, HashSet<ChangeType>>,
     ) {
+        match branch_header {
+            Some(Some(branch)) => println!("## {}", branch),
+            Some(None) => println!("## HEAD (no branch)"),
+            None => {},
+        }
+        
         // Collect all files to sort them
         let mut all_files: Vec<String> = Vec::new();
         
@@ -725,7 +820,237 @@ impl StatusCommand {
         }
     }
     
+    /// Print the `## <branch>...<upstream> [ahead N, behind M]` header of the short format
+    fn print_branch_header_short(git_path: &Path, refs: &Refs, database: &mut Database) -> Result<(), Error> {
+        let branch = match Self::current_branch(refs)? {
+            Some(branch) => branch,
+            None => {
+                println!("## {}", Color::red("HEAD (no branch)"));
+                return Ok(());
+            }
+        };
+        
+        let head_oid = match refs.read_head()? {
+            Some(oid) => oid,
+            None => {
+                println!("## No commits yet on {}", Color::green(&branch));
+                return Ok(());
+            }
+        };
+        
+        let mut header = format!("## {}", Color::green(&branch));
+        
+        let root_path = git_path.parent().unwrap_or_else(|| Path::new("."));
+        if let Some(upstream) = BranchMetadataManager::new(root_path).get_upstream(&branch)? {
+            header.push_str(&format!("...{}", Color::red(&upstream)));
+            
+            if let Some(upstream_oid) = refs.read_ref(&upstream)? {
+                let (ahead, behind) = Self::cached_ahead_behind(git_path, database, &head_oid, &upstream_oid)?;
+                let mut counts = Vec::new();
+                if ahead > 0 {
+                    counts.push(format!("ahead {}", Color::green(&ahead.to_string())));
+                }
+                if behind > 0 {
+                    counts.push(format!("behind {}", Color::red(&behind.to_string())));
+                }
+                if !counts.is_empty() {
+                    header.push_str(&format!(" [{}]", counts.join(", ")));
+                }
+            }
+        }
+        
+        println!("{}", header);
+        Ok(())
+    }
+    
+    /// Print `XY path` lines, coloring staged changes green and unstaged ones red
+    fn print_short(
+        untracked: &HashSet<String>,
+        changed: &HashSet<String>,
+        changes: &HashMap<String, HashSet<ChangeType>>,
+    ) {
+        let mut all_files: Vec<&String> = changed.iter().chain(untracked.iter()).collect();
+        all_files.sort();
+        all_files.dedup();
+        
+        for path in all_files {
+            if untracked.contains(path) {
+                println!("{} {}", Color::red("??"), path);
+                continue;
+            }
+            
+            let status = Self::status_for(path, changes);
+            let (staged, unstaged) = status.split_at(1);
+            println!("{}{} {}", Color::green(staged), Color::red(unstaged), path);
+        }
+    }
+    
+    /// Print the `# branch.*` header lines of the porcelain v2 format
+    fn print_branch_headers_v2(git_path: &Path, refs: &Refs, database: &mut Database) -> Result<(), Error> {
+        let head_oid = refs.read_head()?;
+        
+        println!("# branch.oid {}", head_oid.as_deref().unwrap_or("(initial)"));
+        
+        let branch = match Self::current_branch(refs)? {
+            Some(branch) => branch,
+            None => {
+                println!("# branch.head (detached)");
+                return Ok(());
+            }
+        };
+        
+        println!("# branch.head {}", branch);
+        
+        let root_path = git_path.parent().unwrap_or_else(|| Path::new("."));
+        let upstream = match BranchMetadataManager::new(root_path).get_upstream(&branch)? {
+            Some(upstream) => upstream,
+            None => return Ok(()),
+        };
+        
+        println!("# branch.upstream {}", upstream);
+        
+        // Ahead/behind counts are only known when both tips exist
+        if let (Some(local_oid), Some(upstream_oid)) = (head_oid, refs.read_ref(&upstream)?) {
+            let (ahead, behind) = Self::cached_ahead_behind(git_path, database, &local_oid, &upstream_oid)?;
+            println!("# branch.ab +{} -{}", ahead, behind);
+        }
+        
+        Ok(())
+    }
+    
+    /// Compute ahead/behind counts, reusing results cached for the same pair of tips
+    fn cached_ahead_behind(
+        git_path: &Path,
+        database: &mut Database,
+        local_oid: &str,
+        upstream_oid: &str,
+    ) -> Result<(usize, usize), Error> {
+        let cache_path = git_path.join(AHEAD_BEHIND_CACHE);
+        
+        // Each line: <local oid> <upstream oid> <ahead> <behind>
+        let mut entries: Vec<(String, String, usize, usize)> = fs::read_to_string(&cache_path)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| {
+                let parts: Vec<&str> = line.split(' ').collect();
+                if parts.len() != 4 {
+                    return None;
+                }
+                let ahead = parts[2].parse().ok()?;
+                let behind = parts[3].parse().ok()?;
+                Some((parts[0].to_string(), parts[1].to_string(), ahead, behind))
+            })
+            .collect();
+        
+        if let Some((_, _, ahead, behind)) = entries.iter().find(|(l, u, _, _)| l == local_oid && u == upstream_oid) {
+            return Ok((*ahead, *behind));
+        }
+        
+        let (ahead, behind) = ahead_behind(database, local_oid, upstream_oid)?;
+        
+        entries.push((local_oid.to_string(), upstream_oid.to_string(), ahead, behind));
+        let skip = entries.len().saturating_sub(AHEAD_BEHIND_CACHE_LIMIT);
+        let content: String = entries[skip..]
+            .iter()
+            .map(|(l, u, a, b)| format!("{} {} {} {}\n", l, u, a, b))
+            .collect();
+        
+        // The cache is only an optimization, so failing to write it is not an error
+        let _ = fs::write(&cache_path, content);
+        
+        Ok((ahead, behind))
+    }
+    
+    /// Print per-file records in the porcelain v2 format
+    fn print_porcelain_v2(report: &StatusReport, index: &Index) {
+        for record in Self::porcelain_v2_records(report, index) {
+            println!("{}", record);
+        }
+    }
+    
+    /// Per-file records of the porcelain v2 format, in path order: `1` records for changed
+    /// paths, `u` records for unmerged ones, then `?` records for untracked paths
+    fn porcelain_v2_records(report: &StatusReport, index: &Index) -> Vec<String> {
+        let unmerged: HashSet<String> = index.conflict_paths().into_iter().collect();
+        let mut paths: Vec<&String> = report.changed.union(&unmerged).collect();
+        paths.sort();
+        
+        let mut records = Vec::new();
+        for path in paths {
+            let worktree_mode = match report.stats.get(path.as_str()) {
+                Some(metadata) => format!("{:06o}", Self::mode_for_stat(metadata).0),
+                None => "000000".to_string(),
+            };
+            
+            if unmerged.contains(path) {
+                // Stages 1, 2 and 3 hold the base, ours and theirs
+                let entries = [1, 2, 3].map(|stage| index.entry_for_path(path, stage));
+                let stages = entries.map(|entry| match entry {
+                    Some(entry) => (format!("{:06o}", entry.get_mode().0), entry.get_oid().to_string()),
+                    None => ("000000".to_string(), NULL_OID.to_string()),
+                });
+                
+                records.push(format!(
+                    "u {} N... {} {} {} {} {} {} {} {}",
+                    Self::unmerged_status(entries.map(|entry| entry.is_some())),
+                    stages[0].0, stages[1].0, stages[2].0, worktree_mode,
+                    stages[0].1, stages[1].1, stages[2].1, path
+                ));
+                continue;
+            }
+            
+            let xy = Self::status_for(path, &report.changes).replace(' ', ".");
+            
+            let (head_mode, head_oid) = match report.head_tree.get(path.as_str()) {
+                Some(entry) => (format!("{:06o}", entry.get_file_mode().0), entry.get_oid().to_string()),
+                None => ("000000".to_string(), NULL_OID.to_string()),
+            };
+            
+            let (index_mode, index_oid) = match index.get_entry(path) {
+                Some(entry) => (format!("{:06o}", entry.get_mode().0), entry.get_oid().to_string()),
+                None => ("000000".to_string(), NULL_OID.to_string()),
+            };
+            
+            records.push(format!(
+                "1 {} N... {} {} {} {} {} {}",
+                xy, head_mode, index_mode, worktree_mode, head_oid, index_oid, path
+            ));
+        }
+        
+        let mut untracked: Vec<&String> = report.untracked.iter().collect();
+        untracked.sort();
+        records.extend(untracked.into_iter().map(|path| format!("? {}", path)));
+        
+        records
+    }
+    
+    /// Git's two-letter code for an unmerged path, from which of the base, ours and theirs
+    /// stages are present
+    fn unmerged_status(present: [bool; 3]) -> &'static str {
+        match present {
+            [true, false, false] => "DD",
+            [false, true, false] => "AU",
+            [true, true, false] => "UD",
+            [false, false, true] => "UA",
+            [true, false, true] => "DU",
+            [false, true, true] => "AA",
+            _ => "UU",
+        }
+    }
+    
+    /// The first line of the long format: the checked-out branch, or where HEAD is detached
+    fn branch_line(branch: Option<&str>, head_oid: Option<&str>) -> String {
+        match (branch, head_oid) {
+            (Some(branch), _) => format!("On branch {}", Color::green(branch)),
+            (None, Some(oid)) => Color::red(&format!("HEAD detached at {}", &oid[..oid.len().min(7)])),
+            (None, None) => Color::red("HEAD detached"),
+        }
+    }
+    
     fn print_human_readable(
+        branch: Option<&str>,
+        head_oid: Option<&str>,
+        tracking: Option<&str>,
         untracked: &HashSet<String>,
         changed: &HashSet<String>,
         changes: &HashMap<String, HashSet<ChangeType>>,
@@ -754,7 +1079,10 @@ impl StatusCommand {
             }
         }
         
-        println!("On branch {}", Color::green("master"));
+        println!("{}", Self::branch_line(branch, head_oid));
+        if let Some(tracking) = tracking {
+            println!("[{}]", tracking);
+        }
         
         // Display changes in index (HEAD -> Index)
         if !changes_to_be_committed.is_empty() {
@@ -812,4 +1140,158 @@ impl StatusCommand {
             println!("{}", Color::green("nothing to commit, working tree clean"));
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::branch::BranchCommand;
+    use crate::commands::checkout::CheckoutCommand;
+    use crate::test_support::TestRepo;
+    use std::time::{Duration, SystemTime};
+    
+    /// Moves the modification time of `paths` a minute back, out of the racy windows
+    fn backdate(paths: &[PathBuf]) {
+        let past = SystemTime::now() - Duration::from_secs(60);
+        for path in paths {
+            fs::File::open(path).unwrap().set_modified(past).unwrap();
+        }
+    }
+    
+    fn untracked(repo: &TestRepo, untracked_cache: bool) -> HashSet<String> {
+        let mut r = repo.repo();
+        r.index.load().unwrap();
+        StatusCommand::compute(&r.workspace, &mut r.database, &mut r.index, &r.refs, untracked_cache).unwrap().untracked
+    }
+    
+    #[test]
+    fn header_names_the_checked_out_branch() {
+        let repo = TestRepo::new();
+        repo.commit_files(&[("f.txt", "one\n")], "first");
+        BranchCommand::execute("feature", None).unwrap();
+        CheckoutCommand::execute("feature").unwrap();
+        
+        let r = repo.repo();
+        let branch = StatusCommand::current_branch(&r.refs).unwrap();
+        assert_eq!(branch.as_deref(), Some("feature"));
+        assert_eq!(StatusCommand::branch_line(branch.as_deref(), None), format!("On branch {}", Color::green("feature")));
+    }
+    
+    #[test]
+    fn header_shows_a_detached_head() {
+        let repo = TestRepo::new();
+        let head = repo.commit_files(&[("f.txt", "one\n")], "first");
+        CheckoutCommand::execute(&head).unwrap();
+        
+        let r = repo.repo();
+        let branch = StatusCommand::current_branch(&r.refs).unwrap();
+        assert_eq!(branch, None);
+        assert_eq!(StatusCommand::branch_line(None, Some(&head)), Color::red(&format!("HEAD detached at {}", &head[..7])));
+    }
+    
+    #[test]
+    fn porcelain_v2_reports_unmerged_stages() {
+        let repo = TestRepo::new();
+        repo.commit_files(&[("both.txt", "base\n"), ("gone.txt", "base\n"), ("clean.txt", "clean\n")], "base");
+        repo.write("clean.txt", "edited\n");
+        
+        let mut r = repo.repo();
+        r.index.load_for_update().unwrap();
+        let side = |content: &str| {
+            let oid = repo.blob_oid(content);
+            Some(DatabaseEntry::new(String::new(), oid, "100644"))
+        };
+        r.index.add_conflict(Path::new("both.txt"), vec![side("base\n"), side("ours\n"), side("theirs\n")]);
+        r.index.add_conflict(Path::new("gone.txt"), vec![side("base\n"), side("ours\n"), None]);
+        r.index.write_updates().unwrap();
+        fs::remove_file(repo.path().join("gone.txt")).unwrap();
+        
+        let mut r = repo.repo();
+        r.index.load().unwrap();
+        let report = StatusCommand::compute(&r.workspace, &mut r.database, &mut r.index, &r.refs, false).unwrap();
+        let records = StatusCommand::porcelain_v2_records(&report, &r.index);
+        
+        assert_eq!(records, vec![
+            format!(
+                "u UU N... 100644 100644 100644 100644 {} {} {} both.txt",
+                repo.blob_oid("base\n"), repo.blob_oid("ours\n"), repo.blob_oid("theirs\n")
+            ),
+            format!("1 .M N... 100644 100644 100644 {0} {0} clean.txt", repo.blob_oid("clean\n")),
+            format!(
+                "u UD N... 100644 100644 000000 000000 {} {} {} gone.txt",
+                repo.blob_oid("base\n"), repo.blob_oid("ours\n"), NULL_OID
+            ),
+        ]);
+        assert_eq!(StatusCommand::unmerged_status([false, true, true]), "AA");
+        assert_eq!(StatusCommand::unmerged_status([true, false, false]), "DD");
+    }
+    
+    #[test]
+    fn extensionless_files_are_reported_as_modified() {
+        let repo = TestRepo::new();
+        repo.commit_files(&[("Makefile", "all:\n"), ("LICENSE", "MIT\n"), ("src/main.c", "int main;\n")], "files");
+        repo.write("Makefile", "all: build\n");
+        repo.write("LICENSE", "Apache-2.0\n");
+        repo.add(&["LICENSE"]);
+        
+        let mut r = repo.repo();
+        r.index.load().unwrap();
+        let report = StatusCommand::compute(&r.workspace, &mut r.database, &mut r.index, &r.refs, false).unwrap();
+        
+        assert!(report.changes["Makefile"].contains(&ChangeType::WorkspaceModified), "{:?}", report.changes);
+        assert!(report.changes["LICENSE"].contains(&ChangeType::IndexModified), "{:?}", report.changes);
+        assert!(!report.changed.contains("src/main.c"));
+        assert!(report.untracked.is_empty(), "{:?}", report.untracked);
+        assert!(!StatusCommand::is_directory_from_mode("100644"));
+        assert!(StatusCommand::is_directory_from_mode("40000"));
+    }
+    
+    #[test]
+    fn untracked_cache_notices_files_added_deep_inside_a_cached_directory() {
+        let repo = TestRepo::new();
+        repo.commit_files(&[("f.txt", "one\n")], "first");
+        let dirs = [repo.path().join("build"), repo.path().join("build/a"), repo.path().join("build/a/b")];
+        fs::create_dir_all(&dirs[2]).unwrap();
+        backdate(&dirs);
+        
+        assert!(untracked(&repo, true).is_empty());
+        let cache = fs::read_to_string(repo.path().join(".ash/untracked-cache")).unwrap();
+        assert!(cache.starts_with("D 0 build\n"), "{}", cache);
+        
+        repo.write("build/a/b/new.txt", "new\n");
+        assert_eq!(untracked(&repo, true), HashSet::from(["build/".to_string()]));
+        
+        fs::remove_file(dirs[2].join("new.txt")).unwrap();
+        assert!(untracked(&repo, true).is_empty());
+    }
+    
+    #[test]
+    fn status_without_the_untracked_cache_writes_no_cache_file() {
+        let repo = TestRepo::new();
+        repo.commit_files(&[("f.txt", "one\n")], "first");
+        let dirs = [repo.path().join("build"), repo.path().join("build/a")];
+        fs::create_dir_all(&dirs[1]).unwrap();
+        backdate(&dirs);
+        
+        assert!(untracked(&repo, false).is_empty());
+        assert!(!repo.path().join(".ash/untracked-cache").exists());
+    }
+    
+    #[test]
+    fn clean_status_leaves_the_index_file_untouched() {
+        let repo = TestRepo::new();
+        repo.commit_files(&[("f.txt", "one\n"), ("dir/g.txt", "two\n")], "first");
+        let index_path = repo.path().join(".ash/index");
+        backdate(std::slice::from_ref(&index_path));
+        let written = fs::metadata(&index_path).unwrap().modified().unwrap();
+        
+        let mut r = repo.repo();
+        assert!(r.index.load_for_update().unwrap());
+        let report = StatusCommand::compute(&r.workspace, &mut r.database, &mut r.index, &r.refs, false).unwrap();
+        assert!(report.changed.is_empty(), "{:?}", report.changes);
+        assert!(!r.index.is_changed());
+        
+        assert!(!r.index.write_updates().unwrap());
+        assert_eq!(fs::metadata(&index_path).unwrap().modified().unwrap(), written);
+    }
+}
@@ -0,0 +1,99 @@
+use crate::errors::error::Error;
+use crate::core::repository::repository::Repository;
+use crate::core::revision::Revision;
+use crate::core::color::Color;
+use crate::core::config::Config;
+use crate::core::refs::{Reference, Refs};
+use crate::core::database::author::Author;
+use crate::core::database::tag::Tag;
+
+/// Implements the 'tag' command for AsheraFlow.
+/// Handles creating lightweight and annotated tags, deleting tags and listing them.
+pub struct TagCommand;
+
+impl TagCommand {
+    /// Executes the tag command.
+    /// With no name (or `list` set) the tags are listed; with `delete` the named tag is removed;
+    /// otherwise a tag is created at `target` (HEAD by default). A `message` makes the tag annotated.
+    pub fn execute(
+        name: Option<&str>,
+        target: Option<&str>,
+        message: Option<&str>,
+        delete: bool,
+        list: bool,
+    ) -> Result<(), Error> {
+        let mut repo = Repository::new(".")?;
+        
+        let name = match name {
+            Some(name) if !list => name,
+            _ => return Self::list_tags(&repo),
+        };
+        
+        if delete {
+            let oid = repo.refs.delete_tag(name)?;
+            let short_oid = if oid.len() >= 7 { &oid[0..7] } else { &oid };
+            println!("Deleted tag '{}' (was {})", name, Color::yellow(short_oid));
+            return Ok(());
+        }
+        
+        // Reject bad names before anything, such as an annotated tag object, is written
+        if !Refs::is_valid_refname(name) {
+            return Err(Error::Generic(format!("'{}' is not a valid tag name.", name)));
+        }
+        
+        let target = target.unwrap_or("HEAD");
+        let mut revision = Revision::new(&mut repo, target);
+        let target_oid = match revision.resolve("commit") {
+            Ok(oid) => oid,
+            Err(e) => {
+                for err in revision.errors {
+                    eprintln!("error: {}", err.message);
+                    for hint in &err.hint {
+                        eprintln!("hint: {}", hint);
+                    }
+                }
+                return Err(e);
+            }
+        };
+        
+        let tag_oid = match message {
+            Some(message) => {
+                let mut tag = Tag::new(
+                    target_oid,
+                    "commit".to_string(),
+                    name.to_string(),
+                    Self::current_tagger(),
+                    message.to_string(),
+                );
+                repo.database.store(&mut tag)?
+            },
+            None => target_oid,
+        };
+        
+        repo.refs.create_tag(name, &tag_oid)
+    }
+    
+    /// Lists all tags sorted by name
+    fn list_tags(repo: &Repository) -> Result<(), Error> {
+        let mut names: Vec<String> = repo.refs.list_tags()?
+            .iter()
+            .filter_map(|r| match r {
+                Reference::Symbolic(path) => Some(repo.refs.short_name(path)),
+                Reference::Direct(_) => None,
+            })
+            .collect();
+        
+        names.sort();
+        
+        for name in names {
+            println!("{}", name);
+        }
+        
+        Ok(())
+    }
+    
+    /// Identity recorded as the tagger of annotated tags
+    pub fn current_tagger() -> Author {
+        Config::current().identity(&["GIT_COMMITTER", "GIT_AUTHOR"])
+    }
+}
@@ -5,15 +5,20 @@ use crate::core::sprint::sprint::SprintManager;
 use crate::core::sprint::{TaskStatus, Task, Sprint};
 use crate::commands::checkout::CheckoutCommand;
 use crate::commands::merge::MergeCommand;
+use crate::core::merge::diff3::ConflictStyle;
+use crate::core::merge::resolve::MergeStrategy;
 use crate::core::refs::{Refs, Reference};
-use crate::core::commit_metadata::{TaskMetadata, CommitMetadataManager, TaskStatus as CommitTaskStatus};
+use crate::core::commit_metadata::{unmet_dependencies, TaskMetadata, CommitMetadataManager, TaskStatus as CommitTaskStatus};
 use crate::core::branch_metadata::BranchMetadataManager;
 use crate::core::repository::repository::Repository;
+use crate::validators::task_validator::validate_task_id;
 
 pub struct TaskCompleteCommand;
 
 impl TaskCompleteCommand {
     pub fn execute(id: &str, auto_merge: bool) -> Result<(), Error> {
+        validate_task_id(id)?;
+        
         // Initialize the repository path
         let root_path = Path::new(".");
         let git_path = root_path.join(".ash");
@@ -23,6 +28,19 @@ impl TaskCompleteCommand {
             return Err(Error::Generic("Not an ash repository: .ash directory not found".into()));
         }
         
+        // A task can only be completed once every task it depends on is done
+        let all_tasks = CommitMetadataManager::new(root_path).list_all_tasks()?;
+        if let Some(task) = all_tasks.iter().find(|task| task.id == id) {
+            let unmet: Vec<&str> = unmet_dependencies(task, &all_tasks).iter().map(|dep| dep.id.as_str()).collect();
+            if !unmet.is_empty() {
+                return Err(Error::Generic(format!(
+                    "Cannot complete task '{}': it depends on unfinished tasks: {}",
+                    id,
+                    unmet.join(", ")
+                )));
+            }
+        }
+        
         // Create branch metadata manager to check for sprints
         let branch_manager = BranchMetadataManager::new(root_path);
         
@@ -403,7 +421,7 @@ impl TaskCompleteCommand {
             println!("Merging task branch '{}'...", task_branch);
             let merge_message = format!("Merge task/{} into {}", id, sprint_branch_name);
             
-            match MergeCommand::execute(&task_branch, Some(&merge_message)) {
+            match MergeCommand::execute(&task_branch, Some(&merge_message), MergeStrategy::Recursive, ConflictStyle::Merge, false, false) {
                 Ok(_) => println!("Successfully merged task branch into sprint branch"),
                 Err(e) => println!("Merge failed: {}. You may need to resolve conflicts and merge manually.", e),
             }
@@ -2,15 +2,24 @@ use std::path::Path;
 
 use crate::errors::error::Error;
 use crate::core::sprint::{SprintManager, Task, Sprint};
-use crate::core::commit_metadata::{TaskMetadata, TaskStatus, CommitMetadataManager};
+use crate::core::commit_metadata::{Priority, TaskMetadata, TaskStatus, CommitMetadataManager};
 use crate::core::branch_metadata::BranchMetadataManager;
 use crate::commands::branch::BranchCommand;
 use crate::commands::checkout::CheckoutCommand;
+use crate::validators::task_validator::validate_task_id;
 
 pub struct TaskCreateCommand;
 
 impl TaskCreateCommand {
-    pub fn execute(id: &str, description: &str, story_points: Option<u32>) -> Result<(), Error> {
+    /// Creates a task in the current sprint and switches to its branch. `depends_on` lists
+    /// existing tasks that must be done before this one can be completed.
+    pub fn execute(id: &str, description: &str, story_points: Option<u32>, depends_on: &[String], priority: Priority) -> Result<(), Error> {
+        // The id becomes part of the task branch name
+        validate_task_id(id)?;
+        for dependency in depends_on {
+            validate_task_id(dependency)?;
+        }
+        
         // Initialize the repository path
         let root_path = Path::new(".");
         let git_path = root_path.join(".ash");
@@ -152,9 +161,15 @@ impl TaskCreateCommand {
         let expected_sprint_branch = format!("sprint-{}", actual_sprint_branch);
         println!("Creating task in sprint: {}", actual_sprint_metadata.name);
         
-        // Validate ID (alphanumeric with dashes and underscores)
-        if !id.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') {
-            return Err(Error::Generic("Task ID must contain only alphanumeric characters, dashes, or underscores.".into()));
+        // Dependencies must name other, existing tasks
+        let task_manager = CommitMetadataManager::new(root_path);
+        for dep in depends_on {
+            if dep == id {
+                return Err(Error::Generic(format!("Task '{}' cannot depend on itself.", id)));
+            }
+            if task_manager.get_task_metadata(dep)?.is_none() {
+                return Err(Error::Generic(format!("Dependency '{}' is not a known task.", dep)));
+            }
         }
         
         // Initialize sprint manager for accessing sprint data
@@ -249,7 +264,6 @@ impl TaskCreateCommand {
         sprint_manager.save_sprint(&current_sprint)?;
         
         // Create and store task metadata
-        let task_manager = CommitMetadataManager::new(root_path);
         let task_metadata = TaskMetadata {
             id: id.to_string(),
             description: description.to_string(),
@@ -259,6 +273,8 @@ impl TaskCreateCommand {
             started_at: Some(current_time),
             completed_at: None,
             commit_ids: Vec::new(),
+            depends_on: depends_on.to_vec(),
+            priority,
         };
         
         // Store task metadata
@@ -286,6 +302,12 @@ impl TaskCreateCommand {
             println!("  Story Points: None");
         }
         
+        println!("  Priority: {}", priority.as_str());
+        
+        if !depends_on.is_empty() {
+            println!("  Depends on: {}", depends_on.join(", "));
+        }
+        
         // Display sprint information
         println!("\nSprint progress:");
         println!("  Total Story Points: {}", current_sprint.total_story_points);
@@ -1,5 +1,5 @@
 use std::path::Path;
-use std::io;
+use std::io::{self, IsTerminal};
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
 use crossterm::{
@@ -12,14 +12,15 @@ use tui::{
     widgets::{Block, Borders, List, ListItem, ListState},
     layout::{Layout, Constraint, Direction, Rect},
     style::{Color, Modifier, Style},
-    text::{Span, Spans},
+    text::{Line, Span, Spans},
     Terminal,
 };
+use serde::Serialize;
 
 use crate::errors::error::Error;
 use crate::core::branch_metadata::BranchMetadataManager;
 use crate::core::sprint::sprint::{SprintManager, TaskStatus, Sprint};
-use crate::core::commit_metadata::{CommitMetadataManager, TaskMetadata};
+use crate::core::commit_metadata::{order_by_dependencies, unmet_dependencies, CommitMetadataManager, Priority};
 use crate::commands::checkout::CheckoutCommand;
 use crate::core::repository::repository::Repository;
 
@@ -78,12 +79,50 @@ struct TaskInfo {
     story_points: Option<u32>,
     status: crate::core::commit_metadata::TaskStatus,
     sprint_branch: Option<String>,
+    /// Open and still waiting on unfinished dependencies
+    blocked: bool,
+    priority: Priority,
+}
+
+/// A task as printed by the plain and JSON output formats
+#[derive(Serialize)]
+struct TaskOutput<'a> {
+    id: &'a str,
+    description: &'a str,
+    story_points: Option<u32>,
+    status: &'static str,
+    priority: &'static str,
+    sprint_branch: Option<&'a str>,
+}
+
+impl<'a> From<&'a TaskInfo> for TaskOutput<'a> {
+    fn from(task: &'a TaskInfo) -> Self {
+        TaskOutput {
+            id: &task.id,
+            description: &task.description,
+            story_points: task.story_points,
+            status: status_label(&task.status),
+            priority: task.priority.as_str(),
+            sprint_branch: task.sprint_branch.as_deref(),
+        }
+    }
+}
+
+#[derive(PartialEq)]
+enum OutputFormat {
+    Tui,
+    Plain,
+    Json,
 }
 
 pub struct TaskApp {
     tasks: StatefulList<TaskInfo>,
     current_branch: String,
     filter_mode: FilterMode,
+    sort_mode: SortMode,
+    /// Tasks were loaded in dependency order, which the sort cycle can return to
+    dependency_order: bool,
+    dependency_cycle: Option<Vec<String>>,
 }
 
 #[derive(PartialEq)]
@@ -93,13 +132,46 @@ enum FilterMode {
     ActiveTasks,
 }
 
+#[derive(PartialEq)]
+enum SortMode {
+    /// Highest priority first, then by id
+    Priority,
+    /// In progress, then todo, then done
+    Status,
+    Id,
+    /// The order tasks were loaded in with `--deps`
+    Dependencies,
+}
+
 impl TaskApp {
-    fn new(tasks: Vec<TaskInfo>, current_branch: String) -> TaskApp {
-        TaskApp {
+    fn new(tasks: Vec<TaskInfo>, current_branch: String, dependency_order: bool, dependency_cycle: Option<Vec<String>>) -> TaskApp {
+        let mut app = TaskApp {
             tasks: StatefulList::with_items(tasks),
             current_branch,
             filter_mode: FilterMode::CurrentSprint,
-        }
+            sort_mode: if dependency_order { SortMode::Dependencies } else { SortMode::Priority },
+            dependency_order,
+            dependency_cycle,
+        };
+        app.select_first_filtered();
+        app
+    }
+
+    fn next_sort_mode(&mut self) {
+        self.sort_mode = match self.sort_mode {
+            SortMode::Priority => SortMode::Status,
+            SortMode::Status => SortMode::Id,
+            SortMode::Id if self.dependency_order => SortMode::Dependencies,
+            SortMode::Id | SortMode::Dependencies => SortMode::Priority,
+        };
+        
+        self.select_first_filtered();
+    }
+    
+    // Selectează primul task din lista filtrată și sortată
+    fn select_first_filtered(&mut self) {
+        let first = self.get_filtered_indices().first().copied();
+        self.tasks.state.select(first);
     }
 
     fn next_filter_mode(&mut self) {
@@ -110,9 +182,7 @@ impl TaskApp {
         };
         
         // Resetăm selecția pentru a asigura că e vizibilă după schimbarea filtrului
-        if !self.tasks.items.is_empty() {
-            self.tasks.state.select(Some(0));
-        }
+        self.select_first_filtered();
     }
     
     // Navigare adaptată la task-urile filtrate
@@ -185,9 +255,9 @@ impl TaskApp {
         }
     }
     
-    // Obține lista de indici ai task-urilor care trec de filtru
+    // Obține lista de indici ai task-urilor care trec de filtru, în ordinea de sortare curentă
     fn get_filtered_indices(&self) -> Vec<usize> {
-        self.tasks.items.iter().enumerate()
+        let mut indices: Vec<usize> = self.tasks.items.iter().enumerate()
             .filter(|(_, task)| {
                 match self.filter_mode {
                     FilterMode::All => true,
@@ -205,7 +275,22 @@ impl TaskApp {
                 }
             })
             .map(|(i, _)| i)
-            .collect()
+            .collect();
+        
+        let items = &self.tasks.items;
+        match self.sort_mode {
+            SortMode::Priority => indices.sort_by(|&a, &b| {
+                items[b].priority.cmp(&items[a].priority).then_with(|| items[a].id.cmp(&items[b].id))
+            }),
+            SortMode::Status => indices.sort_by(|&a, &b| {
+                status_rank(&items[a].status).cmp(&status_rank(&items[b].status))
+                    .then_with(|| items[a].id.cmp(&items[b].id))
+            }),
+            SortMode::Id => indices.sort_by(|&a, &b| items[a].id.cmp(&items[b].id)),
+            SortMode::Dependencies => {}
+        }
+        
+        indices
     }
 
     fn checkout_task(&mut self) -> Result<(), Error> {
@@ -235,6 +320,32 @@ impl TaskApp {
     }
 }
 
+fn status_rank(status: &crate::core::commit_metadata::TaskStatus) -> u8 {
+    match status {
+        crate::core::commit_metadata::TaskStatus::InProgress => 0,
+        crate::core::commit_metadata::TaskStatus::Todo => 1,
+        crate::core::commit_metadata::TaskStatus::Done => 2,
+    }
+}
+
+fn status_label(status: &crate::core::commit_metadata::TaskStatus) -> &'static str {
+    match status {
+        crate::core::commit_metadata::TaskStatus::Todo => "todo",
+        crate::core::commit_metadata::TaskStatus::InProgress => "in progress",
+        crate::core::commit_metadata::TaskStatus::Done => "done",
+    }
+}
+
+fn priority_span(priority: Priority) -> Span<'static> {
+    let color = match priority {
+        Priority::Critical => Color::Red,
+        Priority::High => Color::Magenta,
+        Priority::Medium => Color::Blue,
+        Priority::Low => Color::Gray,
+    };
+    Span::styled(format!("[{}]", priority.as_str()), Style::default().fg(color))
+}
+
 pub struct TaskListCommand {
     pub repo_path: String,
     pub args: Vec<String>,
@@ -242,6 +353,8 @@ pub struct TaskListCommand {
 
 impl TaskListCommand {
     pub fn execute(&self) -> Result<(), Error> {
+        let format = self.output_format()?;
+        
         // Initialize managers
         let branch_manager = BranchMetadataManager::new(Path::new(&self.repo_path));
         let task_manager = CommitMetadataManager::new(Path::new(&self.repo_path));
@@ -251,7 +364,20 @@ impl TaskListCommand {
         let current_branch = branch_manager.get_current_branch()?;
         
         // Get all tasks
-        let all_tasks = task_manager.list_all_tasks()?;
+        let mut all_tasks = task_manager.list_all_tasks()?;
+        
+        // Optionally put dependencies before the tasks that depend on them
+        let mut dependency_cycle = None;
+        let dependency_order = self.args.iter().any(|arg| arg == "--order=deps" || arg == "--deps");
+        if dependency_order {
+            let ordering = order_by_dependencies(&all_tasks);
+            let position: HashMap<&str, usize> = ordering.order.iter()
+                .enumerate()
+                .map(|(i, id)| (id.as_str(), i))
+                .collect();
+            all_tasks.sort_by_key(|task| position.get(task.id.as_str()).copied().unwrap_or(usize::MAX));
+            dependency_cycle = ordering.cycle;
+        }
         
         // Find active sprint
         let active_sprint = branch_manager.find_active_sprint()?;
@@ -311,11 +437,22 @@ impl TaskListCommand {
                 story_points: task.story_points,
                 status: task.status.clone(),
                 sprint_branch,
+                blocked: task.status != crate::core::commit_metadata::TaskStatus::Done
+                    && !unmet_dependencies(task, &all_tasks).is_empty(),
+                priority: task.priority,
             };
             
             task_infos.push(task_info);
         }
         
+        if format != OutputFormat::Tui {
+            // Without the TUI there is no interactive sorting; use its default order
+            if !dependency_order {
+                task_infos.sort_by(|a, b| b.priority.cmp(&a.priority).then_with(|| a.id.cmp(&b.id)));
+            }
+            return Self::print_tasks(&task_infos, &format, dependency_cycle.as_deref());
+        }
+        
         // Setup terminal UI
         enable_raw_mode()?;
         let mut stdout = io::stdout();
@@ -324,7 +461,7 @@ impl TaskListCommand {
         let mut terminal = Terminal::new(backend)?;
         
         // Create app state
-        let app = TaskApp::new(task_infos, current_branch);
+        let app = TaskApp::new(task_infos, current_branch, dependency_order, dependency_cycle);
         
         // Run the app
         let res = Self::run_app(&mut terminal, app);
@@ -345,6 +482,64 @@ impl TaskListCommand {
         Ok(())
     }
 
+    /// Picks the output from `--format=plain|json` or `--no-tui`; without either the TUI is used
+    /// only when stdout is a terminal.
+    fn output_format(&self) -> Result<OutputFormat, Error> {
+        let mut format = None;
+        let mut args = self.args.iter();
+        while let Some(arg) = args.next() {
+            let value = match arg.as_str() {
+                "--no-tui" => Some("plain"),
+                "--format" => match args.next() {
+                    Some(value) => Some(value.as_str()),
+                    None => return Err(Error::Generic("Option '--format' requires a value".into())),
+                },
+                a if a.starts_with("--format=") => Some(&a["--format=".len()..]),
+                _ => None,
+            };
+            if let Some(value) = value {
+                format = Some(match value {
+                    "plain" => OutputFormat::Plain,
+                    "json" => OutputFormat::Json,
+                    "tui" => OutputFormat::Tui,
+                    other => return Err(Error::Generic(format!("Unknown task list format: {} (expected plain or json)", other))),
+                });
+            }
+        }
+        
+        Ok(format.unwrap_or_else(|| {
+            if io::stdout().is_terminal() { OutputFormat::Tui } else { OutputFormat::Plain }
+        }))
+    }
+    
+    fn print_tasks(tasks: &[TaskInfo], format: &OutputFormat, dependency_cycle: Option<&[String]>) -> Result<(), Error> {
+        if *format == OutputFormat::Json {
+            let output: Vec<TaskOutput> = tasks.iter().map(TaskOutput::from).collect();
+            let text = serde_json::to_string_pretty(&output)
+                .map_err(|e| Error::Generic(format!("Failed to serialize tasks: {}", e)))?;
+            println!("{}", text);
+            return Ok(());
+        }
+        
+        for task in tasks {
+            let points = task.story_points.map(|points| format!(" ({}sp)", points)).unwrap_or_default();
+            println!(
+                "{:<12} {:<13} {:<10} {}{}{}",
+                task.id,
+                format!("[{}]", status_label(&task.status).to_uppercase()),
+                format!("[{}]", task.priority.as_str()),
+                if task.blocked { "(blocked) " } else { "" },
+                task.description,
+                points
+            );
+        }
+        if let Some(cycle) = dependency_cycle {
+            eprintln!("warning: dependency cycle: {}", cycle.join(" -> "));
+        }
+        
+        Ok(())
+    }
+
     fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: TaskApp) -> io::Result<()> {
         loop {
             terminal.draw(|f| Self::ui(f, &mut app))?;
@@ -366,6 +561,10 @@ impl TaskListCommand {
                         // Toggle filter mode
                         app.next_filter_mode();
                     },
+                    KeyCode::Char('s') => {
+                        // Cycle sort order
+                        app.next_sort_mode();
+                    },
                     _ => {}
                 }
             }
@@ -381,46 +580,9 @@ impl TaskListCommand {
             .borders(Borders::ALL);
         f.render_widget(block, size);
         
-        // Filter tasks based on current filter mode
-        let filtered_tasks: Vec<&TaskInfo> = app.tasks.items.iter()
-            .filter(|task| {
-                match app.filter_mode {
-                    FilterMode::All => true,
-                    FilterMode::CurrentSprint => {
-                        if let Some(branch) = &task.sprint_branch {
-                            branch == &app.current_branch || 
-                            (app.current_branch.contains("-task-") && branch.contains(&app.current_branch.split("-task-").next().unwrap_or("")))
-                        } else {
-                            false
-                        }
-                    },
-                    FilterMode::ActiveTasks => {
-                        task.status == crate::core::commit_metadata::TaskStatus::InProgress
-                    }
-                }
-            })
-            .collect();
-        
-        // Create a mapping from filtered index to original index
-        let index_map: Vec<usize> = app.tasks.items.iter().enumerate()
-            .filter(|(_, task)| {
-                match app.filter_mode {
-                    FilterMode::All => true,
-                    FilterMode::CurrentSprint => {
-                        if let Some(branch) = &task.sprint_branch {
-                            branch == &app.current_branch || 
-                            (app.current_branch.contains("-task-") && branch.contains(&app.current_branch.split("-task-").next().unwrap_or("")))
-                        } else {
-                            false
-                        }
-                    },
-                    FilterMode::ActiveTasks => {
-                        task.status == crate::core::commit_metadata::TaskStatus::InProgress
-                    }
-                }
-            })
-            .map(|(i, _)| i)
-            .collect();
+        // Filter and sort tasks based on the current modes, keeping the original indices
+        let index_map: Vec<usize> = app.get_filtered_indices();
+        let filtered_tasks: Vec<&TaskInfo> = index_map.iter().map(|&i| &app.tasks.items[i]).collect();
         
         // Create list items for filtered tasks
         let items: Vec<ListItem> = filtered_tasks.iter().enumerate().map(|(filtered_idx, task)| {
@@ -442,12 +604,22 @@ impl TaskListCommand {
             
             let task_text = format!("{} - {} {}", task.id, task.description, points_str);
             
+            // Open tasks still waiting on unfinished dependencies are marked as blocked
+            let blocked_span = if task.blocked {
+                Span::styled("⛓ ", Style::default().fg(Color::Red))
+            } else {
+                Span::raw("")
+            };
+            
             // Determine color based on task status
             let spans = match task.status {
                 crate::core::commit_metadata::TaskStatus::Todo => {
                     Spans::from(vec![
                         Span::styled(status_str, Style::default().fg(Color::Red)),
                         Span::raw(" "),
+                        priority_span(task.priority),
+                        Span::raw(" "),
+                        blocked_span,
                         Span::styled(task_text, Style::default()),
                     ])
                 },
@@ -455,6 +627,9 @@ impl TaskListCommand {
                     Spans::from(vec![
                         Span::styled(status_str, Style::default().fg(Color::Yellow)),
                         Span::raw(" "),
+                        priority_span(task.priority),
+                        Span::raw(" "),
+                        blocked_span,
                         Span::styled(task_text, Style::default()),
                     ])
                 },
@@ -462,6 +637,9 @@ impl TaskListCommand {
                     Spans::from(vec![
                         Span::styled(status_str, Style::default().fg(Color::Green)),
                         Span::raw(" "),
+                        priority_span(task.priority),
+                        Span::raw(" "),
+                        blocked_span,
                         Span::styled(task_text, Style::default()),
                     ])
                 },
@@ -477,14 +655,24 @@ impl TaskListCommand {
             FilterMode::ActiveTasks => "Filter: Active Tasks",
         };
         
+        let sort_text = match app.sort_mode {
+            SortMode::Priority => "Sort: Priority",
+            SortMode::Status => "Sort: Status",
+            SortMode::Id => "Sort: Id",
+            SortMode::Dependencies => "Sort: Dependencies",
+        };
+        
         // Create help text
         let help_text = vec![
             Spans::from(vec![
                 Span::raw("↑/↓: Navigate  "),
                 Span::raw("Enter: Checkout Task  "),
                 Span::raw("f: Change Filter  "),
+                Span::raw("s: Change Sort  "),
                 Span::raw("q: Quit  "),
                 Span::styled(filter_text, Style::default().fg(Color::Yellow)),
+                Span::raw("  "),
+                Span::styled(sort_text, Style::default().fg(Color::Yellow)),
             ]),
         ];
         
@@ -493,14 +681,20 @@ impl TaskListCommand {
             .block(Block::default().borders(Borders::NONE));
         
         // Create status text showing total tasks displayed vs. total tasks
-        let status_text = vec![
-            Spans::from(vec![
-                Span::styled(
-                    format!("Displaying {} of {} tasks", filtered_tasks.len(), app.tasks.items.len()),
-                    Style::default().fg(Color::Cyan),
-                ),
-            ]),
+        let mut status_spans = vec![
+            Span::styled(
+                format!("Displaying {} of {} tasks", filtered_tasks.len(), app.tasks.items.len()),
+                Style::default().fg(Color::Cyan),
+            ),
         ];
+        if let Some(cycle) = &app.dependency_cycle {
+            status_spans.push(Span::raw("  "));
+            status_spans.push(Span::styled(
+                format!("Dependency cycle: {}", cycle.join(" -> ")),
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ));
+        }
+        let status_text = vec![Line::from(status_spans)];
         
         let status_paragraph = tui::widgets::Paragraph::new(status_text)
             .style(Style::default())
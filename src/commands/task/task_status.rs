@@ -2,10 +2,13 @@ use std::path::Path;
 
 use crate::errors::error::Error;
 use crate::core::commit_metadata::CommitMetadataManager;
+use crate::validators::task_validator::validate_task_id;
 pub struct TaskStatusCommand;
 
 impl TaskStatusCommand {
     pub fn execute(id: &str) -> Result<(), Error> {
+        validate_task_id(id)?;
+        
         let task_manager = CommitMetadataManager::new(Path::new("."));
         
         match task_manager.get_task_metadata(id)? {
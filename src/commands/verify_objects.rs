@@ -0,0 +1,29 @@
+use crate::errors::error::Error;
+use crate::core::color::Color;
+use crate::core::repository::repository::Repository;
+
+/// Implements the 'verify-objects' command for AsheraFlow.
+/// Scans every stored object for corruption by recomputing its ID from its content.
+pub struct VerifyObjectsCommand;
+
+impl VerifyObjectsCommand {
+    /// Verifies all loose and packed objects, listing the corrupt ones and a summary.
+    /// Returns whether every object is intact.
+    pub fn execute() -> Result<bool, Error> {
+        let repo = Repository::new(".")?;
+        let (scanned, corrupt) = repo.database.verify_objects()?;
+
+        for (oid, reason) in &corrupt {
+            println!("{} {}: {}", Color::red("corrupt"), oid, reason);
+        }
+        println!(
+            "Scanned {} object{}: {} ok, {} corrupt",
+            scanned,
+            if scanned == 1 { "" } else { "s" },
+            scanned - corrupt.len(),
+            corrupt.len()
+        );
+
+        Ok(corrupt.is_empty())
+    }
+}
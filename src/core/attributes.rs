@@ -0,0 +1,95 @@
+/// Per-path text/binary overrides read from `.ashattributes`.
+use std::fs;
+use std::path::Path;
+use regex::Regex;
+
+/// One `.ashattributes` line: a path pattern and whether it marks matches as binary
+#[derive(Debug, Clone)]
+struct Rule {
+    pattern: Regex,
+    binary: bool,
+}
+
+/// Parsed `.ashattributes` rules. Lines look like `*.png binary` or `*.txt text`;
+/// `-text` and `-diff` also mark a path as binary. Later lines override earlier ones.
+#[derive(Debug, Clone, Default)]
+pub struct Attributes {
+    rules: Vec<Rule>,
+}
+
+impl Attributes {
+    /// Reads `.ashattributes` at the workspace root; a missing file gives no rules
+    pub fn load(root_path: &Path) -> Self {
+        match fs::read_to_string(root_path.join(".ashattributes")) {
+            Ok(content) => Self::parse(&content),
+            Err(_) => Attributes::default(),
+        }
+    }
+
+    pub fn parse(content: &str) -> Self {
+        let mut rules = Vec::new();
+
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = line.split_whitespace();
+            let pattern = match fields.next().and_then(Self::compile) {
+                Some(pattern) => pattern,
+                None => continue,
+            };
+
+            // The last text/binary attribute on the line decides
+            let mut binary = None;
+            for attribute in fields {
+                match attribute {
+                    "binary" | "-text" | "-diff" => binary = Some(true),
+                    "text" | "diff" => binary = Some(false),
+                    _ => {}
+                }
+            }
+
+            if let Some(binary) = binary {
+                rules.push(Rule { pattern, binary });
+            }
+        }
+
+        Attributes { rules }
+    }
+
+    /// Whether `path` is forced to binary (`Some(true)`) or text (`Some(false)`);
+    /// None leaves the decision to content sniffing
+    pub fn is_binary(&self, path: &str) -> Option<bool> {
+        let path = path.replace('\\', "/");
+        self.rules.iter()
+            .rev()
+            .find(|rule| rule.pattern.is_match(&path))
+            .map(|rule| rule.binary)
+    }
+
+    // A pattern without a slash matches the file name in any directory; one with a
+    // slash is anchored at the workspace root
+    fn compile(pattern: &str) -> Option<Regex> {
+        let anchored = pattern.contains('/');
+        let pattern = pattern.trim_start_matches('/');
+
+        let mut regex = String::from(if anchored { "^" } else { "(^|/)" });
+        let mut chars = pattern.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '*' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    regex.push_str(".*");
+                }
+                '*' => regex.push_str("[^/]*"),
+                '?' => regex.push_str("[^/]"),
+                _ => regex.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+        regex.push('$');
+
+        Regex::new(&regex).ok()
+    }
+}
@@ -3,9 +3,11 @@ use std::collections::HashMap;
 use std::time::{SystemTime, UNIX_EPOCH, Duration};
 use crate::errors::error::Error;
 use crate::core::refs::Refs;
+use crate::core::config::Config;
 use crate::core::database::database::Database;
 use crate::core::repository::repository::Repository;
 use crate::core::database::sprint_metadata_object::SprintMetadataObject;
+use crate::core::merge::common_ancestors::ahead_behind;
 
 /// Stores metadata for a sprint, including name, start time, and duration.
 #[derive(Debug, Clone)]
@@ -13,6 +15,11 @@ pub struct SprintMetadata {
     pub name: String, 
     pub start_timestamp: u64,
     pub duration_days: u32,
+    /// When the sprint was closed, if it has been
+    pub closed_timestamp: Option<u64>,
+    /// Story points planned and completed, recorded when the sprint is closed
+    pub planned_points: Option<u32>,
+    pub completed_points: Option<u32>,
 }
 
 impl SprintMetadata {
@@ -27,6 +34,9 @@ impl SprintMetadata {
             name,
             start_timestamp: now,
             duration_days,
+            closed_timestamp: None,
+            planned_points: None,
+            completed_points: None,
         }
     }
 
@@ -35,8 +45,12 @@ impl SprintMetadata {
         self.start_timestamp + (self.duration_days as u64 * 24 * 60 * 60)
     }
 
-    /// Checks if the sprint is currently active.
+    /// Checks if the sprint is currently active. A closed sprint is never active.
     pub fn is_active(&self) -> bool {
+        if self.is_closed() {
+            return false;
+        }
+        
         let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap_or_default()
@@ -45,6 +59,11 @@ impl SprintMetadata {
         now <= self.end_timestamp()
     }
 
+    /// Checks if the sprint has been closed.
+    pub fn is_closed(&self) -> bool {
+        self.closed_timestamp.is_some()
+    }
+
     /// Formats a timestamp as a human-readable date string.
     pub fn format_date(timestamp: u64) -> String {
         let dt = chrono::DateTime::from_timestamp(timestamp as i64, 0)
@@ -59,8 +78,17 @@ impl SprintMetadata {
     }
 
     /// Encodes sprint metadata into a branch description string.
+    /// The close time is appended as a fifth field once the sprint is closed, followed by the
+    /// planned and completed points recorded at that time.
     pub fn encode(&self) -> String {
-        format!("SPRINT:{}:{}:{}", self.name, self.start_timestamp, self.duration_days)
+        match (self.closed_timestamp, self.planned_points, self.completed_points) {
+            (Some(closed), Some(planned), Some(completed)) => format!(
+                "SPRINT:{}:{}:{}:{}:{}:{}",
+                self.name, self.start_timestamp, self.duration_days, closed, planned, completed
+            ),
+            (Some(closed), _, _) => format!("SPRINT:{}:{}:{}:{}", self.name, self.start_timestamp, self.duration_days, closed),
+            (None, _, _) => format!("SPRINT:{}:{}:{}", self.name, self.start_timestamp, self.duration_days),
+        }
     }
 
     /// Decodes sprint metadata from a branch description string.
@@ -70,11 +98,21 @@ impl SprintMetadata {
             let name = parts[1].to_string();
             let start_timestamp = parts[2].parse::<u64>().ok()?;
             let duration_days = parts[3].parse::<u32>().ok()?;
+            let closed_timestamp = match parts.get(4) {
+                Some(closed) => Some(closed.parse::<u64>().ok()?),
+                None => None,
+            };
+            // Sprints closed before velocity was recorded stop at the close time
+            let planned_points = parts.get(5).and_then(|points| points.parse::<u32>().ok());
+            let completed_points = parts.get(6).and_then(|points| points.parse::<u32>().ok());
 
             Some(SprintMetadata {
                 name,
                 start_timestamp,
                 duration_days,
+                closed_timestamp,
+                planned_points,
+                completed_points,
             })
         } else {
             None
@@ -123,6 +161,82 @@ impl BranchMetadataManager {
         }
     }
 
+    /// Read the upstream branch tracked by `branch_name`, if one is configured.
+    /// The relationship is stored as `branch.<name>.remote` and `branch.<name>.merge` in the
+    /// repository config; a remote of `.` means a local branch. Returns the short name of the
+    /// upstream, as `main` or `origin/main`.
+    pub fn get_upstream(&self, branch_name: &str) -> Result<Option<String>, Error> {
+        let config = Config::load(&self.repo_path.join(".ash"));
+        let section = format!("branch.{}", branch_name);
+        
+        let (remote, merge) = match (config.get(&section, "remote"), config.get(&section, "merge")) {
+            (Some(remote), Some(merge)) if !remote.is_empty() && !merge.is_empty() => (remote, merge),
+            _ => return Ok(None),
+        };
+        
+        let merge = merge.strip_prefix("refs/heads/").unwrap_or(merge);
+        if remote == "." {
+            Ok(Some(merge.to_string()))
+        } else {
+            Ok(Some(format!("{}/{}", remote, merge)))
+        }
+    }
+
+    /// Make `branch_name` track `upstream`, which must name a local branch or a
+    /// remote-tracking branch as `<remote>/<branch>`.
+    pub fn set_upstream(&self, branch_name: &str, upstream: &str) -> Result<(), Error> {
+        let git_path = self.repo_path.join(".ash");
+        let refs = Refs::new(&git_path);
+        
+        if !refs.branch_exists(branch_name)? {
+            return Err(Error::Generic(format!("branch '{}' does not exist", branch_name)));
+        }
+        
+        let (remote, merge) = if refs.branch_exists(upstream)? {
+            (".", upstream)
+        } else {
+            match upstream.split_once('/') {
+                Some((remote, branch)) if refs.read_ref(&format!("refs/remotes/{}", upstream))?.is_some() => (remote, branch),
+                _ => return Err(Error::Generic(format!("the requested upstream branch '{}' does not exist", upstream))),
+            }
+        };
+        
+        let mut config = Config::load(&git_path);
+        let section = format!("branch.{}", branch_name);
+        config.set(&section, "remote", remote);
+        config.set(&section, "merge", &format!("refs/heads/{}", merge));
+        config.save()
+    }
+
+    /// Describe where `branch_name` (at `branch_oid`) stands against its upstream, as
+    /// `upstream: X, ahead N, behind M`. Zero counts are left out, and an upstream that no
+    /// longer resolves is reported as gone. Returns None when no upstream is set.
+    pub fn tracking_summary(&self, branch_name: &str, branch_oid: &str, database: &mut Database) -> Result<Option<String>, Error> {
+        let upstream = match self.get_upstream(branch_name)? {
+            Some(upstream) => upstream,
+            None => return Ok(None),
+        };
+        
+        let refs = Refs::new(self.repo_path.join(".ash"));
+        let mut summary = format!("upstream: {}", upstream);
+        
+        match refs.read_ref(&upstream)? {
+            Some(upstream_oid) => {
+                // Diverged or unrelated histories simply count on both sides
+                let (ahead, behind) = ahead_behind(database, branch_oid, &upstream_oid)?;
+                if ahead > 0 {
+                    summary.push_str(&format!(", ahead {}", ahead));
+                }
+                if behind > 0 {
+                    summary.push_str(&format!(", behind {}", behind));
+                }
+            },
+            None => summary.push_str(", gone"),
+        }
+        
+        Ok(Some(summary))
+    }
+
     /// Store sprint metadata in the object database
     pub fn store_sprint_metadata(&self, branch_name: &str, metadata: &SprintMetadata) -> Result<(), Error> {
         // Create a repository and get access to database
@@ -1,5 +1,34 @@
 /// Provides ANSI color codes and utilities for colored terminal output in AsheraFlow.
 use std::env;
+use std::sync::OnceLock;
+use crate::core::config::Config;
+
+/// Environment variable overriding `color.ui`
+pub const COLOR_ENV: &str = "ASH_COLOR";
+
+static MODE: OnceLock<ColorMode> = OnceLock::new();
+static ENABLED: OnceLock<bool> = OnceLock::new();
+
+/// When output is colored, as given to `--color=<when>` or `color.ui`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    Always,
+    Never,
+    /// Color only when stdout is a terminal
+    Auto,
+}
+
+impl ColorMode {
+    /// Parses `always`, `never` or `auto`, along with the boolean forms `color.ui` accepts
+    pub fn parse(value: &str) -> Option<ColorMode> {
+        match value.to_lowercase().as_str() {
+            "always" => Some(ColorMode::Always),
+            "never" | "false" | "no" | "off" => Some(ColorMode::Never),
+            "auto" | "true" | "yes" | "on" => Some(ColorMode::Auto),
+            _ => None,
+        }
+    }
+}
 
 pub struct Color;
 
@@ -30,39 +59,40 @@ impl Color {
     pub const UNDERLINE: &'static str = "\x1b[4m";
     pub const REVERSED: &'static str = "\x1b[7m";
 
-    /// Checks if colors should be enabled for terminal output.
-    fn is_enabled() -> bool {
-        // Check for color support
-        if let Ok(color_value) = env::var("ASH_COLOR") {
-            match color_value.as_str() {
-                "always" => true,
-                "never" => false,
-                _ => Self::has_color_support(),
-            }
-        } else {
-            // Default to auto-detection
-            Self::has_color_support()
-        }
+    /// Sets the color mode chosen on the command line. Must run before anything is colored,
+    /// as the mode is resolved once; later calls are ignored.
+    pub fn set_mode(mode: ColorMode) {
+        let _ = MODE.set(mode);
+    }
+
+    /// The color mode: `--color`, then `ASH_COLOR`, then `color.ui` in the config, then auto
+    pub fn mode() -> ColorMode {
+        *MODE.get_or_init(|| {
+            env::var(COLOR_ENV).ok()
+                .and_then(|value| ColorMode::parse(&value))
+                .or_else(|| Config::current().get("color", "ui").and_then(ColorMode::parse))
+                .unwrap_or(ColorMode::Auto)
+        })
     }
 
-    /// Detects if the terminal supports colors.
+    /// Checks if colors should be enabled for terminal output. Every helper below returns
+    /// its text unchanged when this is false.
+    pub fn is_enabled() -> bool {
+        *ENABLED.get_or_init(|| match Self::mode() {
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+            ColorMode::Auto => Self::has_color_support(),
+        })
+    }
+
+    /// Detects if stdout is a terminal that supports colors.
     fn has_color_support() -> bool {
-        if let Ok(term) = env::var("TERM") {
-            // Most terminals with color support have TERM with "color" or "256"
-            if term.contains("color") || term.contains("256") {
-                return true;
-            }
-        }
-        
-        // Check for COLORTERM environment variable
-        if let Ok(colorterm) = env::var("COLORTERM") {
-            if !colorterm.is_empty() {
-                return true;
-            }
+        if !atty::is(atty::Stream::Stdout) {
+            return false;
         }
-        
-        // Default to enabled
-        true
+
+        // Only a dumb terminal is known not to understand ANSI codes
+        !matches!(env::var("TERM").as_deref(), Ok("dumb"))
     }
 
     // Helper function to color text
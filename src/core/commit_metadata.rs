@@ -17,6 +17,39 @@ pub enum TaskStatus {
     Done,
 }
 
+/// How urgent a task is; variants are declared from least to most urgent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    Low,
+    #[default]
+    Medium,
+    High,
+    Critical,
+}
+
+impl Priority {
+    /// Parses a priority name such as `high`, ignoring case
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "low" => Some(Priority::Low),
+            "medium" => Some(Priority::Medium),
+            "high" => Some(Priority::High),
+            "critical" => Some(Priority::Critical),
+            _ => None,
+        }
+    }
+
+    /// The upper-case name used in encoded metadata and task list tags
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Priority::Low => "LOW",
+            Priority::Medium => "MEDIUM",
+            Priority::High => "HIGH",
+            Priority::Critical => "CRITICAL",
+        }
+    }
+}
+
 /// Stores metadata for a task, including description, status, and commit history.
 #[derive(Debug, Clone)]
 pub struct TaskMetadata {
@@ -28,6 +61,9 @@ pub struct TaskMetadata {
     pub started_at: Option<u64>,
     pub completed_at: Option<u64>,
     pub commit_ids: Vec<String>,
+    /// IDs of the tasks that must be done before this one
+    pub depends_on: Vec<String>,
+    pub priority: Priority,
 }
 
 impl TaskMetadata {
@@ -45,6 +81,8 @@ impl TaskMetadata {
             started_at: None,
             completed_at: None,
             commit_ids: Vec::new(),
+            depends_on: Vec::new(),
+            priority: Priority::default(),
         }
     }
 
@@ -111,14 +149,16 @@ impl TaskMetadata {
         };
         
         format!(
-            "TASK-METADATA:{}:{}:{}:{}:{}:{}:{}",
+            "TASK-METADATA:{}:{}:{}:{}:{}:{}:{}:{}:{}",
             self.id,
             self.description,
             story_points_str,
             status_str,
             self.created_at,
             started_at_str,
-            completed_at_str
+            completed_at_str,
+            self.depends_on.join(","),
+            self.priority.as_str()
         )
     }
     
@@ -156,6 +196,15 @@ impl TaskMetadata {
                 parts[7].parse::<u64>().ok()
             };
             
+            // Metadata written before dependencies were tracked has no ninth field
+            let depends_on = match parts.get(8) {
+                Some(deps) => deps.split(',').filter(|d| !d.is_empty()).map(String::from).collect(),
+                None => Vec::new(),
+            };
+            
+            // Likewise, metadata written before priorities were tracked has no tenth field
+            let priority = parts.get(9).and_then(|p| Priority::parse(p)).unwrap_or_default();
+            
             Some(TaskMetadata {
                 id,
                 description,
@@ -165,6 +214,8 @@ impl TaskMetadata {
                 started_at,
                 completed_at,
                 commit_ids: Vec::new(),
+                depends_on,
+                priority,
             })
         } else {
             None
@@ -172,6 +223,80 @@ impl TaskMetadata {
     }
 }
 
+/// Tasks ordered so that every task comes after the tasks it depends on.
+#[derive(Debug, Default)]
+pub struct DependencyOrder {
+    /// Task IDs in dependency order; ties keep the input order
+    pub order: Vec<String>,
+    /// A dependency cycle, as a path that starts and ends with the same task ID
+    pub cycle: Option<Vec<String>>,
+}
+
+/// The tasks `task` depends on that are not done yet. Dependencies on unknown tasks are ignored.
+pub fn unmet_dependencies<'a>(task: &TaskMetadata, tasks: &'a [TaskMetadata]) -> Vec<&'a TaskMetadata> {
+    task.depends_on.iter()
+        .filter_map(|dep| tasks.iter().find(|t| &t.id == dep))
+        .filter(|dep| dep.status != TaskStatus::Done)
+        .collect()
+}
+
+/// Topologically sorts tasks by `depends_on`. Dependencies on unknown tasks are ignored.
+/// Tasks caught in or behind a cycle are appended in input order and one cycle is reported.
+pub fn order_by_dependencies(tasks: &[TaskMetadata]) -> DependencyOrder {
+    let known: HashSet<&str> = tasks.iter().map(|t| t.id.as_str()).collect();
+    let deps: HashMap<&str, Vec<&str>> = tasks.iter()
+        .map(|t| {
+            let task_deps = t.depends_on.iter()
+                .map(String::as_str)
+                .filter(|d| known.contains(d) && *d != t.id)
+                .collect();
+            (t.id.as_str(), task_deps)
+        })
+        .collect();
+    
+    let mut placed: HashSet<&str> = HashSet::new();
+    let mut order = Vec::with_capacity(tasks.len());
+    
+    // Repeatedly place every task whose dependencies are all placed
+    loop {
+        let ready: Vec<&str> = tasks.iter()
+            .map(|t| t.id.as_str())
+            .filter(|id| !placed.contains(id) && deps[id].iter().all(|d| placed.contains(d)))
+            .collect();
+        if ready.is_empty() {
+            break;
+        }
+        for id in ready {
+            placed.insert(id);
+            order.push(id.to_string());
+        }
+    }
+    
+    let remaining: Vec<&str> = tasks.iter()
+        .map(|t| t.id.as_str())
+        .filter(|id| !placed.contains(id))
+        .collect();
+    
+    // Every remaining task has an unplaced dependency, so following them must revisit a task
+    let cycle = remaining.first().map(|&start| {
+        let mut path = vec![start];
+        let mut current = start;
+        loop {
+            current = deps[current].iter().copied().find(|d| !placed.contains(d)).unwrap_or(start);
+            if let Some(pos) = path.iter().position(|&id| id == current) {
+                let mut cycle: Vec<String> = path[pos..].iter().map(|id| id.to_string()).collect();
+                cycle.push(current.to_string());
+                return cycle;
+            }
+            path.push(current);
+        }
+    });
+    
+    order.extend(remaining.iter().map(|id| id.to_string()));
+    
+    DependencyOrder { order, cycle }
+}
+
 pub struct CommitMetadataManager {
     repo_path: PathBuf,
 }
@@ -0,0 +1,245 @@
+/// Repository settings read from and written to `.ash/config`.
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::errors::error::Error;
+use chrono::Local;
+use crate::core::database::author::Author;
+use crate::core::lockfile::Lockfile;
+
+/// Parsed `.ash/config`. The file is INI-style, with git-style sections:
+///
+/// ```text
+/// [user]
+///     name = Ada Lovelace
+///     email = ada@example.com
+/// [core]
+///     editor = vim
+///     eol = crlf
+/// ```
+///
+/// Section and key names are case-insensitive; values are kept as written. A section may
+/// have a case-sensitive subsection, as in `[branch "main"]`, which is named `branch.main`.
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    path: PathBuf,
+    sections: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl Config {
+    /// Reads `config` from the `.ash` directory; a missing file gives no settings
+    pub fn load(git_path: &Path) -> Self {
+        let path = git_path.join("config");
+        let sections = match fs::read_to_string(&path) {
+            Ok(content) => Self::parse(&content),
+            Err(_) => BTreeMap::new(),
+        };
+        Config { path, sections }
+    }
+
+    /// The configuration of the repository in the current directory
+    pub fn current() -> Self {
+        Self::load(Path::new(".ash"))
+    }
+
+    fn parse(content: &str) -> BTreeMap<String, BTreeMap<String, String>> {
+        let mut sections: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
+        let mut section = String::new();
+
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+                section = match name.trim().split_once(' ') {
+                    Some((name, subsection)) => format!("{}.{}", name.to_lowercase(), subsection.trim().trim_matches('"')),
+                    None => name.trim().to_lowercase(),
+                };
+                continue;
+            }
+
+            if let Some((key, value)) = line.split_once('=') {
+                let value = value.trim().trim_matches('"').to_string();
+                sections.entry(section.clone()).or_default().insert(key.trim().to_lowercase(), value);
+            }
+        }
+
+        sections
+    }
+
+    /// Splits a `section.key` or `section.subsection.key` name as given on the command line
+    pub fn split_name(name: &str) -> Result<(String, String), Error> {
+        match name.rsplit_once('.') {
+            Some((section, key)) if !section.is_empty() && !key.is_empty() => {
+                Ok((Self::section_name(section), key.to_lowercase()))
+            }
+            _ => Err(Error::Generic(format!("key does not contain a section: {}", name))),
+        }
+    }
+
+    // Lowercases the section part of a name, keeping any subsection as written
+    fn section_name(section: &str) -> String {
+        match section.split_once('.') {
+            Some((name, subsection)) => format!("{}.{}", name.to_lowercase(), subsection),
+            None => section.to_lowercase(),
+        }
+    }
+
+    /// The value of `key` in `section`, if set
+    pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.sections.get(&Self::section_name(section))?
+            .get(&key.to_lowercase())
+            .map(String::as_str)
+    }
+
+    /// The value of `key` in `section`, or else the first of `env_vars` that is set.
+    /// Settings that used to come only from the environment keep working this way.
+    pub fn get_or_env(&self, section: &str, key: &str, env_vars: &[&str]) -> Option<String> {
+        self.get(section, key)
+            .map(String::from)
+            .or_else(|| env_vars.iter().find_map(|var| std::env::var(var).ok()))
+    }
+
+    /// The user's identity, stamped with the current time: `user.name` and `user.email`, or else
+    /// `<PREFIX>_NAME` and `<PREFIX>_EMAIL` for the first of `env_fallbacks` that is set (e.g.
+    /// `GIT_AUTHOR`). The name falls back to `$USER` and the email to `<name>@localhost`.
+    pub fn identity(&self, env_fallbacks: &[&str]) -> Author {
+        let name_vars: Vec<String> = env_fallbacks.iter().map(|prefix| format!("{}_NAME", prefix)).collect();
+        let email_vars: Vec<String> = env_fallbacks.iter().map(|prefix| format!("{}_EMAIL", prefix)).collect();
+        let mut name_vars: Vec<&str> = name_vars.iter().map(String::as_str).collect();
+        name_vars.push("USER");
+        let email_vars: Vec<&str> = email_vars.iter().map(String::as_str).collect();
+
+        let name = self.get_or_env("user", "name", &name_vars)
+            .unwrap_or_else(|| "Unknown".to_string());
+        let email = self.get_or_env("user", "email", &email_vars)
+            .unwrap_or_else(|| format!("{}@localhost", name));
+
+        Author {
+            name,
+            email,
+            timestamp: Local::now().into(),
+        }
+    }
+
+    /// Sets `key` in `section`; call `save` to write the change
+    pub fn set(&mut self, section: &str, key: &str, value: &str) {
+        self.sections
+            .entry(Self::section_name(section))
+            .or_default()
+            .insert(key.to_lowercase(), value.to_string());
+    }
+
+    /// Moves every setting of section `from` to section `to`, replacing what `to` held.
+    /// Returns false when `from` has no settings.
+    pub fn rename_section(&mut self, from: &str, to: &str) -> bool {
+        match self.sections.remove(&Self::section_name(from)) {
+            Some(values) => {
+                self.sections.insert(Self::section_name(to), values);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Writes the settings back to `.ash/config`
+    pub fn save(&self) -> Result<(), Error> {
+        let mut content = String::new();
+        for (section, values) in &self.sections {
+            match section.split_once('.') {
+                Some((name, subsection)) => content.push_str(&format!("[{} \"{}\"]\n", name, subsection)),
+                None if !section.is_empty() => content.push_str(&format!("[{}]\n", section)),
+                None => {},
+            }
+            for (key, value) in values {
+                content.push_str(&format!("\t{} = {}\n", key, value));
+            }
+        }
+
+        let mut lockfile = Lockfile::new(&self.path);
+        lockfile.hold_for_update()?;
+        lockfile.write(&content)?;
+        lockfile.commit_ref()?;
+        Ok(())
+    }
+}
+
+/// Line endings written to the working tree for text files, from `core.eol`.
+/// When set, text files are stored with LF in the repository whatever they use on disk.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Eol {
+    Lf,
+    Crlf,
+    /// CRLF on Windows, LF elsewhere
+    Native,
+}
+
+impl Eol {
+    /// The configured setting; None (the default) leaves line endings untouched
+    pub fn from_config(config: &Config) -> Option<Self> {
+        match config.get("core", "eol")?.to_lowercase().as_str() {
+            "lf" => Some(Eol::Lf),
+            "crlf" => Some(Eol::Crlf),
+            "native" => Some(Eol::Native),
+            _ => None,
+        }
+    }
+
+    /// Whether text files are written with CRLF line endings
+    pub fn uses_crlf(self) -> bool {
+        match self {
+            Eol::Lf => false,
+            Eol::Crlf => true,
+            Eol::Native => cfg!(windows),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identity_prefers_config_then_environment() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::load(dir.path());
+        std::env::set_var("ASH_TEST_IDENTITY_NAME", "Env Name");
+        std::env::set_var("ASH_TEST_IDENTITY_EMAIL", "env@example.org");
+
+        let identity = config.identity(&["ASH_TEST_UNSET_IDENTITY", "ASH_TEST_IDENTITY"]);
+        assert_eq!((identity.name.as_str(), identity.email.as_str()), ("Env Name", "env@example.org"));
+
+        config.set("user", "name", "Ada");
+        config.set("user", "email", "ada@example.com");
+        let identity = config.identity(&["ASH_TEST_IDENTITY"]);
+        assert_eq!((identity.name.as_str(), identity.email.as_str()), ("Ada", "ada@example.com"));
+    }
+
+    #[test]
+    fn identity_email_falls_back_to_localhost() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::load(dir.path());
+        config.set("user", "name", "Ada");
+
+        let identity = config.identity(&["ASH_TEST_UNSET_IDENTITY"]);
+        assert_eq!(identity.email, "Ada@localhost");
+    }
+
+    #[test]
+    fn subsections_keep_their_case_through_a_save() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::load(dir.path());
+        config.set("Branch.Feature", "remote", ".");
+        config.save().unwrap();
+
+        let content = fs::read_to_string(dir.path().join("config")).unwrap();
+        assert_eq!(content, "[branch \"Feature\"]\n\tremote = .\n");
+
+        let config = Config::load(dir.path());
+        assert_eq!(config.get("branch.Feature", "remote"), Some("."));
+        assert_eq!(config.get("branch.feature", "remote"), None);
+        assert_eq!(Config::split_name("branch.Feature.Remote").unwrap(), ("branch.Feature".to_string(), "remote".to_string()));
+    }
+}
@@ -1,7 +1,8 @@
 // src/core/database/author.rs
-use chrono::{DateTime, TimeZone, Utc};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
 use std::fmt;
 use regex::Regex;
+use crate::errors::error::Error;
 
 #[derive(Debug, Clone)]
 pub struct Author {
@@ -52,6 +53,46 @@ impl Author {
         }
     }
     
+    /// Parse a `Name <email>` identity as given to `commit --author`, timestamped now
+    pub fn parse_ident(ident: &str) -> Result<Self, Error> {
+        let malformed = || Error::Generic(format!("--author '{}' is not 'Name <email>'", ident));
+        
+        let (name, rest) = ident.split_once('<').ok_or_else(malformed)?;
+        let (email, trailing) = rest.split_once('>').ok_or_else(malformed)?;
+        let name = name.trim();
+        let email = email.trim();
+        
+        if name.is_empty() || email.is_empty() || !trailing.trim().is_empty() || email.contains('<') {
+            return Err(malformed());
+        }
+        
+        Ok(Author::new(name.to_string(), email.to_string()))
+    }
+    
+    /// Parse a `--date` value: RFC 3339 / ISO 8601 (`2024-05-01T12:00:00+02:00`),
+    /// `YYYY-MM-DD HH:MM:SS` or `YYYY-MM-DD` in UTC, or `@<unix seconds>`
+    pub fn parse_date(date: &str) -> Result<DateTime<Utc>, Error> {
+        let date = date.trim();
+        
+        if let Some(seconds) = date.strip_prefix('@') {
+            if let Ok(seconds) = seconds.parse::<i64>() {
+                if let chrono::LocalResult::Single(dt) = Utc.timestamp_opt(seconds, 0) {
+                    return Ok(dt);
+                }
+            }
+        } else if let Ok(dt) = DateTime::parse_from_rfc3339(date) {
+            return Ok(dt.with_timezone(&Utc));
+        } else if let Ok(dt) = NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M:%S")
+            .or_else(|_| NaiveDateTime::parse_from_str(date, "%Y-%m-%dT%H:%M:%S"))
+        {
+            return Ok(Utc.from_utc_datetime(&dt));
+        } else if let Some(dt) = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok().and_then(|d| d.and_hms_opt(0, 0, 0)) {
+            return Ok(Utc.from_utc_datetime(&dt));
+        }
+        
+        Err(Error::Generic(format!("invalid date format: {}", date)))
+    }
+    
     /// Format the author's date in short format (YYYY-MM-DD)
     pub fn short_date(&self) -> String {
         self.timestamp.format("%Y-%m-%d").to_string()
@@ -8,7 +8,7 @@ use std::collections::HashMap;
 #[derive(Debug, Clone)]
 pub struct Commit {
     pub oid: Option<String>,
-    pub parent: Option<String>,
+    pub parents: Vec<String>,
     pub tree: String,
     pub author: Author,
     pub committer: Author,
@@ -41,7 +41,7 @@ impl GitObject for Commit {
         
         lines.push(format!("tree {}", self.tree));
         
-        if let Some(parent) = &self.parent {
+        for parent in &self.parents {
             lines.push(format!("parent {}", parent));
         }
         
@@ -69,19 +69,19 @@ impl GitObject for Commit {
 }
 
 impl Commit {
-    pub fn new(parent: Option<String>, tree: String, author: Author, message: String) -> Self {
+    pub fn new(parents: Vec<String>, tree: String, author: Author, message: String) -> Self {
         Commit {
             oid: None,
-            parent,
+            parents,
             tree,
             author: author.clone(),
             committer: author,
-            message,
+            message: normalize_message(&message),
         }
     }
 
     pub fn new_with_committer(
-        parent: Option<String>,
+        parents: Vec<String>,
         tree: String,
         author: Author,
         committer: Author,
@@ -89,11 +89,11 @@ impl Commit {
     ) -> Self {
         Commit {
             oid: None,
-            parent,
+            parents,
             tree,
             author,
             committer,
-            message,
+            message: normalize_message(&message),
         }
     }
 
@@ -103,7 +103,12 @@ impl Commit {
     
     // Ensure these methods are implemented
     pub fn get_parent(&self) -> Option<&String> {
-        self.parent.as_ref()
+        self.parents.first()
+    }
+
+    /// All parents in recorded order; merge commits list the merged-in tip second
+    pub fn get_parents(&self) -> &[String] {
+        &self.parents
     }
     
     pub fn get_author(&self) -> Option<&Author> {
@@ -148,7 +153,7 @@ impl Commit {
         
         lines.push(format!("tree {}", self.tree));
         
-        if let Some(parent) = &self.parent {
+        for parent in &self.parents {
             lines.push(format!("parent {}", parent));
         }
         
@@ -169,23 +174,38 @@ impl Commit {
         };
         
         let mut lines = content.lines();
-        let mut headers = HashMap::new();
+        let mut headers: HashMap<String, String> = HashMap::new();
         let mut message = String::new();
         let mut reading_message = false;
         
+        let mut last_header: Option<String> = None;
+        let mut parents = Vec::new();
+        
         // Parsează headerele până la linia goală
+        let mut first_message_line = true;
         while let Some(line) = lines.next() {
+            // Blank lines inside the message separate paragraphs and are kept
+            if reading_message {
+                if !first_message_line {
+                    message.push('\n');
+                }
+                first_message_line = false;
+                message.push_str(line);
+                continue;
+            }
+            
             if line.is_empty() {
                 reading_message = true;
                 continue;
             }
             
-            if reading_message {
-                if !message.is_empty() {
-                    message.push('\n');
+            // Continuation lines (e.g. git's multi-line gpgsig) start with a space
+            if let Some(continuation) = line.strip_prefix(' ') {
+                if let Some(value) = last_header.as_ref().and_then(|key| headers.get_mut(key)) {
+                    value.push('\n');
+                    value.push_str(continuation);
+                    continue;
                 }
-                message.push_str(line);
-                continue;
             }
             
             // Parsează headerul liniei curente
@@ -194,7 +214,14 @@ impl Commit {
                 return Err(Error::Generic(format!("Invalid commit header: {}", line)));
             }
             
-            headers.insert(parts[0].to_string(), parts[1].to_string());
+            // Every parent header is kept, in order; other repeated headers keep their first value
+            if parts[0] == "parent" {
+                parents.push(parts[1].to_string());
+                last_header = None;
+                continue;
+            }
+            headers.entry(parts[0].to_string()).or_insert_with(|| parts[1].to_string());
+            last_header = Some(parts[0].to_string());
         }
         
         // Extrage tree, parent și author
@@ -202,8 +229,6 @@ impl Commit {
             .ok_or_else(|| Error::Generic("Missing tree in commit".to_string()))?
             .clone();
         
-        let parent = headers.get("parent").cloned();
-        
         let author_str = headers.get("author")
             .ok_or_else(|| Error::Generic("Missing author in commit".to_string()))?;
         
@@ -223,11 +248,17 @@ impl Commit {
 
         Ok(Commit {
             oid: None,
-            parent,
+            parents,
             tree,
             author,
             committer,
             message,
         })
     }
-}
\ No newline at end of file
+}
+
+/// Converts CRLF and lone CR line endings to LF, so messages written on any platform
+/// are stored and shown with single line spacing
+pub fn normalize_message(message: &str) -> String {
+    message.replace("\r\n", "\n").replace('\r', "\n")
+}
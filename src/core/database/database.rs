@@ -1,9 +1,10 @@
 // src/core/database/database.rs
 use std::fs::{self, File};
 use std::io::Write;
-use std::path::PathBuf;
-use std::io::Read;
-use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use std::io::{BufReader, Cursor, Read};
+use std::collections::{HashMap, HashSet};
 use sha1::{Digest, Sha1};
 use flate2::write::ZlibEncoder;
 use flate2::read::ZlibDecoder;
@@ -11,25 +12,67 @@ use flate2::Compression;
 use crate::core::path_filter::PathFilter;
 use crate::errors::error::Error;
 use crate::core::database::blob::Blob;
-use crate::core::database::tree::Tree;
+use crate::core::database::tree::{Tree, TreeEntry};
 use crate::core::database::commit::Commit;
+use crate::core::database::tag::Tag;
 use std::any::Any;
 
 use super::entry::DatabaseEntry;
+use super::pack::{PackReader, PackWriter};
 use super::tree_diff::TreeDiff;
 
+/// Environment variable listing extra object directories to read from, separated by ':',
+/// e.g. a git repository's `.git/objects`. Alternates are only ever read, never written.
+const ALTERNATES_ENV: &str = "ASH_ALTERNATE_OBJECT_DIRECTORIES";
+
+/// Upper bound on the files `hash_files_with` keeps open at the same time
+const MAX_OPEN_FILES: usize = 64;
+
+/// Size of the chunks files and objects are streamed in
+const STREAM_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Longest object header accepted while streaming ("<type> <size>\0")
+const MAX_HEADER_LEN: usize = 64;
+
 pub struct Database {
     pub pathname: PathBuf,
+    /// Read-only object directories consulted after `pathname` (e.g. a git repository's `.git/objects`)
+    alternates: Vec<PathBuf>,
+    /// Packs under `pathname/pack`, consulted before loose objects
+    packs: Vec<PackReader>,
     temp_chars: Vec<char>,
     objects: HashMap<String, Box<dyn GitObject>>,
+    batch: Option<WriteBatch>,
+}
+
+/// Objects written between `begin_batch` and `flush_batch`
+struct WriteBatch {
+    /// One compressor reused for every object, instead of setting one up per write
+    encoder: ZlibEncoder<Vec<u8>>,
+    /// Directory the objects are staged in, laid out like the object store itself
+    staging: PathBuf,
+    /// Fan-out directories created under `staging` so far
+    dirs: HashSet<String>,
+    /// Objects staged so far
+    oids: HashSet<String>,
+}
+
+impl Drop for WriteBatch {
+    fn drop(&mut self) {
+        // Objects never moved into place leave nothing behind
+        let _ = fs::remove_dir_all(&self.staging);
+    }
 }
 
 impl Clone for Database {
     fn clone(&self) -> Self {
         Database {
             pathname: self.pathname.clone(),
+            alternates: self.alternates.clone(),
+            packs: self.packs.clone(),
             temp_chars: self.temp_chars.clone(),
             objects: HashMap::new(), // We don't clone the objects cache
+            batch: None,
         }
     }
 }
@@ -49,15 +92,218 @@ impl Database {
             .chain('0'..='9')
             .collect();
 
+        let alternates = Self::read_alternates(&pathname);
+        let packs = PackReader::open_all(&pathname.join("pack"));
+
         Database {
             pathname,
+            alternates,
+            packs,
             temp_chars,
             objects: HashMap::new(),
+            batch: None,
+        }
+    }
+
+    /// Add a read-only object directory, such as the `objects` directory of a git repository.
+    /// Loose objects written by git use the same zlib + "<type> <size>\0" format as ash objects.
+    pub fn add_alternate(&mut self, path: PathBuf) {
+        if path != self.pathname && !self.alternates.contains(&path) {
+            self.alternates.push(path);
         }
     }
 
+    /// Collect alternates from `objects/info/alternates` and the alternates environment variable
+    fn read_alternates(pathname: &Path) -> Vec<PathBuf> {
+        let mut alternates = Vec::new();
+
+        if let Ok(content) = fs::read_to_string(pathname.join("info").join("alternates")) {
+            for line in content.lines().map(str::trim) {
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                // Relative entries are resolved against the objects directory, as git does
+                alternates.push(pathname.join(line));
+            }
+        }
+
+        if let Ok(value) = std::env::var(ALTERNATES_ENV) {
+            alternates.extend(value.split(':').filter(|p| !p.is_empty()).map(PathBuf::from));
+        }
+
+        alternates
+    }
+
+    /// Find the file holding a loose object, checking the primary store before alternates
+    fn find_object_path(&self, oid: &str) -> Option<PathBuf> {
+        if oid.len() < 3 {
+            return None;
+        }
+
+        std::iter::once(&self.pathname)
+            .chain(self.alternates.iter())
+            .map(|dir| dir.join(&oid[0..2]).join(&oid[2..]))
+            .find(|path| path.exists())
+    }
+
     pub fn exists(&self, oid: &str) -> bool {
-        self.object_path(oid).exists()
+        self.packs.iter().any(|pack| pack.contains(oid)) || self.find_object_path(oid).is_some()
+    }
+
+    /// Re-reads the pack indexes, picking up packs written since this database was opened
+    pub fn reload_packs(&mut self) {
+        self.packs = PackReader::open_all(&self.pathname.join("pack"));
+    }
+
+    /// IDs of the loose objects in the primary store, excluding alternates
+    pub fn loose_objects(&self) -> Result<Vec<String>, Error> {
+        let mut oids = Vec::new();
+
+        for dir in fs::read_dir(&self.pathname)? {
+            let dir = dir?;
+            let prefix = dir.file_name().to_string_lossy().to_string();
+            if prefix.len() != 2 || !prefix.chars().all(|c| c.is_ascii_hexdigit()) || !dir.path().is_dir() {
+                continue;
+            }
+
+            for file in fs::read_dir(dir.path())? {
+                let name = file?.file_name().to_string_lossy().to_string();
+                // Skip temp files left by interrupted writes
+                if name.len() == 38 && name.chars().all(|c| c.is_ascii_hexdigit()) {
+                    oids.push(format!("{}{}", prefix, name));
+                }
+            }
+        }
+
+        oids.sort();
+        Ok(oids)
+    }
+
+    /// Marks every object reachable from `roots` through commit parents and trees and tag
+    /// targets. Missing objects are skipped.
+    pub fn reachable_from<I>(&self, roots: I) -> Result<HashSet<String>, Error>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut reachable = HashSet::new();
+        let mut pending: Vec<String> = roots.into_iter().collect();
+
+        while let Some(oid) = pending.pop() {
+            if reachable.contains(&oid) || !self.exists(&oid) {
+                continue;
+            }
+
+            let (obj_type, content) = self.read_raw(&oid)?;
+            reachable.insert(oid);
+
+            match obj_type.as_str() {
+                "commit" => {
+                    let commit = Commit::parse(&content)?;
+                    pending.push(commit.get_tree().to_string());
+                    pending.extend(commit.get_parents().iter().cloned());
+                },
+                "tree" => {
+                    let tree = Tree::parse(&content)?;
+                    for entry in tree.get_entries().values() {
+                        match entry {
+                            TreeEntry::Blob(oid, _) => pending.push(oid.clone()),
+                            TreeEntry::Tree(subtree) => pending.extend(subtree.get_oid().cloned()),
+                        }
+                    }
+                },
+                "tag" => pending.push(Tag::parse(&content)?.get_object().to_string()),
+                _ => {}
+            }
+        }
+
+        Ok(reachable)
+    }
+
+    /// Deletes the loose objects in the primary store that are not in `reachable` and were
+    /// last modified before `older_than`, so objects a running command has only just written
+    /// survive. Packed objects are left alone. With `dry_run` nothing is deleted.
+    /// Returns the ID and on-disk size of every object pruned (or that would be).
+    pub fn prune(&self, reachable: &HashSet<String>, older_than: SystemTime, dry_run: bool) -> Result<Vec<(String, u64)>, Error> {
+        let mut pruned = Vec::new();
+
+        for oid in self.loose_objects()? {
+            if reachable.contains(&oid) {
+                continue;
+            }
+
+            let path = self.object_path(&oid);
+            let metadata = fs::metadata(&path)?;
+            if metadata.modified()? >= older_than {
+                continue;
+            }
+            let size = metadata.len();
+
+            if !dry_run {
+                fs::remove_file(&path)?;
+                if let Some(dir) = path.parent() {
+                    // Only succeeds once the fan-out directory is empty
+                    let _ = fs::remove_dir(dir);
+                }
+            }
+
+            pruned.push((oid, size));
+        }
+
+        Ok(pruned)
+    }
+
+    /// Moves every loose object in the primary store into a new pack.
+    /// The loose files are only deleted once the pack has been written and verified.
+    /// Returns the path of the new pack and the number of objects it holds, or `None` if
+    /// there was nothing to pack.
+    pub fn pack_loose_objects(&mut self) -> Result<Option<(PathBuf, usize)>, Error> {
+        let oids = self.loose_objects()?;
+        if oids.is_empty() {
+            return Ok(None);
+        }
+
+        let mut objects = Vec::with_capacity(oids.len());
+        // File names the packed trees give their entries, so versions of a file delta well
+        let mut names = HashMap::new();
+        for oid in &oids {
+            let data = Self::read_loose(&self.object_path(oid))?;
+            if self.hash_content(&data) != *oid {
+                return Err(Error::Generic(format!("Loose object {} is corrupt", oid)));
+            }
+
+            if data.starts_with(b"tree ") {
+                if let Some(null) = data.iter().position(|&b| b == 0) {
+                    let tree = Tree::parse(&data[null + 1..])?;
+                    for (name, entry) in tree.get_entries() {
+                        let entry_oid = match entry {
+                            TreeEntry::Blob(oid, _) => Some(oid.clone()),
+                            TreeEntry::Tree(subtree) => subtree.get_oid().cloned(),
+                        };
+                        if let Some(entry_oid) = entry_oid {
+                            names.entry(entry_oid).or_insert_with(|| name.clone());
+                        }
+                    }
+                }
+            }
+
+            objects.push((oid.clone(), data));
+        }
+
+        let idx_path = PackWriter::write(&self.pathname.join("pack"), &objects, &names)?;
+        let pack = PackReader::open(&idx_path)?;
+        pack.verify()?;
+
+        for oid in &oids {
+            let path = self.object_path(oid);
+            fs::remove_file(&path)?;
+            if let Some(dir) = path.parent() {
+                // Only succeeds once the fan-out directory is empty
+                let _ = fs::remove_dir(dir);
+            }
+        }
+
+        self.reload_packs();
+        Ok(Some((pack.pack_path().to_path_buf(), pack.len())))
     }
 
     /// Încarcă un obiect din baza de date folosind OID-ul său
@@ -93,7 +339,14 @@ impl Database {
         
         // Write only if object doesn't already exist
         if !self.exists(&oid) {
-            self.write_object(&oid, &content)?;
+            match self.batch.take() {
+                Some(mut batch) => {
+                    let written = self.write_batched(&mut batch, &oid, &content);
+                    self.batch = Some(batch);
+                    written?;
+                },
+                None => self.write_object(&oid, &content)?,
+            }
         }
     
         // Set OID on object
@@ -103,6 +356,60 @@ impl Database {
         Ok(oid)
     }
 
+    /// Computes an object's OID and sets it on the object without writing anything
+    pub fn hash_object(&self, object: &mut impl GitObject) -> Result<String, Error> {
+        let content = self.serialize_object(object)?;
+        let oid = self.hash_content(&content);
+        object.set_oid(oid.clone());
+        
+        Ok(oid)
+    }
+
+    /// Starts writing stored objects as one batch. The objects share a compressor and
+    /// are staged in a directory of their own until `flush_batch` moves them into place.
+    pub fn begin_batch(&mut self) {
+        if self.batch.is_none() {
+            self.batch = Some(WriteBatch {
+                encoder: ZlibEncoder::new(Vec::new(), Compression::best()),
+                staging: self.pathname.join(self.generate_temp_name()),
+                dirs: HashSet::new(),
+                oids: HashSet::new(),
+            });
+        }
+    }
+
+    /// Moves every object written since `begin_batch` into place and leaves batch mode,
+    /// returning how many objects were written
+    pub fn flush_batch(&mut self) -> Result<usize, Error> {
+        let batch = match self.batch.take() {
+            Some(batch) => batch,
+            None => return Ok(0),
+        };
+
+        // A fan-out directory the store doesn't have yet is moved over whole,
+        // saving a rename per object; dropping the batch removes whatever is left
+        let mut moved = HashSet::new();
+        for dir in &batch.dirs {
+            let target = self.pathname.join(dir);
+            if !target.exists() && fs::rename(batch.staging.join(dir), &target).is_ok() {
+                moved.insert(dir.as_str());
+            }
+        }
+
+        for oid in &batch.oids {
+            if !moved.contains(&oid[0..2]) {
+                fs::rename(batch.staging.join(&oid[0..2]).join(&oid[2..]), self.object_path(oid))?;
+            }
+        }
+
+        Ok(batch.oids.len())
+    }
+
+    /// Leaves batch mode without storing the objects written since `begin_batch`
+    pub fn discard_batch(&mut self) {
+        self.batch = None;
+    }
+
     pub fn serialize_object(&self, object: &impl GitObject) -> Result<Vec<u8>, Error> {
         let obj_type = object.get_type();
         let content = object.to_bytes();
@@ -156,29 +463,50 @@ impl Database {
         Ok(())
     }
 
+    /// Writes an object of the current batch into the batch's staging directory
+    fn write_batched(&self, batch: &mut WriteBatch, oid: &str, content: &[u8]) -> Result<(), Error> {
+        if batch.oids.contains(oid) {
+            return Ok(());
+        }
+
+        let dirname = batch.staging.join(&oid[0..2]);
+        if !batch.dirs.contains(&oid[0..2]) {
+            fs::create_dir_all(&dirname)?;
+            batch.dirs.insert(oid[0..2].to_string());
+        }
+
+        // Resetting the encoder finishes this object's stream and readies it for the next
+        batch.encoder.write_all(content)?;
+        let compressed = batch.encoder.reset(Vec::new())?;
+        fs::write(dirname.join(&oid[2..]), compressed)?;
+
+        batch.oids.insert(oid.to_string());
+        Ok(())
+    }
+
     /// Obține calea către un obiect bazat pe OID
     fn object_path(&self, oid: &str) -> PathBuf {
         self.pathname.join(&oid[0..2]).join(&oid[2..])
     }
 
-    /// Citește un obiect din baza de date și îl parsează
-    /// Read and parse an object from the database
-    fn read_object(&self, oid: &str) -> Result<Box<dyn GitObject>, Error> {
-        let path = self.object_path(oid);
-        
-        if !path.exists() {
-            return Err(Error::Generic(format!("Object not found: {}", oid)));
+    /// Read an object's type and its content exactly as stored, without parsing it
+    pub fn read_raw(&self, oid: &str) -> Result<(String, Vec<u8>), Error> {
+        let mut packed = None;
+        for pack in &self.packs {
+            packed = pack.read(oid)?;
+            if packed.is_some() {
+                break;
+            }
         }
         
-        // Read the file
-        let mut file = File::open(&path)?;
-        let mut compressed_data = Vec::new();
-        file.read_to_end(&mut compressed_data)?;
-        
-        // Decompress data
-        let mut decoder = ZlibDecoder::new(&compressed_data[..]);
-        let mut data = Vec::new();
-        decoder.read_to_end(&mut data)?;
+        let data = match packed {
+            Some(data) => data,
+            None => {
+                let path = self.find_object_path(oid)
+                    .ok_or_else(|| Error::Generic(format!("Object not found: {}", oid)))?;
+                Self::read_loose(&path)?
+            }
+        };
         
         // Parse header
         let null_pos = data.iter().position(|&b| b == 0)
@@ -192,13 +520,73 @@ impl Database {
             return Err(Error::Generic(format!("Invalid header format: {}", header)));
         }
         
-        let obj_type = parts[0];
-        let obj_size: usize = parts[1].parse()
+        let obj_type = parts[0].to_string();
+        parts[1].parse::<usize>()
             .map_err(|_| Error::Generic(format!("Invalid size in header: {}", parts[1])))?;
         
-        
         // Extract content (after null byte)
-        let content = &data[null_pos + 1..];
+        Ok((obj_type, data[null_pos + 1..].to_vec()))
+    }
+
+    /// Opens a blob for reading without loading it into memory. Loose blobs are decompressed
+    /// as they are read; packed ones are rebuilt from their pack first, since they may be deltas.
+    pub fn open_blob(&self, oid: &str) -> Result<Box<dyn Read>, Error> {
+        if self.packs.iter().any(|pack| pack.contains(oid)) {
+            let (obj_type, data) = self.read_raw(oid)?;
+            if obj_type != "blob" {
+                return Err(Error::Generic(format!("Object {} is not a blob", oid)));
+            }
+            return Ok(Box::new(Cursor::new(data)));
+        }
+
+        let path = self.find_object_path(oid)
+            .ok_or_else(|| Error::Generic(format!("Object not found: {}", oid)))?;
+        let mut decoder = ZlibDecoder::new(BufReader::new(File::open(&path)?));
+
+        // Consume the header a byte at a time so the decoder is left at the content
+        let mut header = Vec::new();
+        let mut byte = [0u8; 1];
+        loop {
+            decoder.read_exact(&mut byte)?;
+            if byte[0] == 0 {
+                break;
+            }
+            if header.len() == MAX_HEADER_LEN {
+                return Err(Error::Generic("Invalid object format: missing null byte".to_string()));
+            }
+            header.push(byte[0]);
+        }
+
+        let header = String::from_utf8_lossy(&header);
+        let size = match header.split_once(' ') {
+            Some(("blob", size)) => size.parse::<u64>()
+                .map_err(|_| Error::Generic(format!("Invalid size in header: {}", size)))?,
+            Some(_) => return Err(Error::Generic(format!("Object {} is not a blob", oid))),
+            None => return Err(Error::Generic(format!("Invalid header format: {}", header))),
+        };
+
+        Ok(Box::new(decoder.take(size)))
+    }
+
+    /// Read and decompress a loose object file
+    fn read_loose(path: &PathBuf) -> Result<Vec<u8>, Error> {
+        let mut file = File::open(path)?;
+        let mut compressed_data = Vec::new();
+        file.read_to_end(&mut compressed_data)?;
+        
+        let mut decoder = ZlibDecoder::new(&compressed_data[..]);
+        let mut data = Vec::new();
+        decoder.read_to_end(&mut data)?;
+        
+        Ok(data)
+    }
+
+    /// Citește un obiect din baza de date și îl parsează
+    /// Read and parse an object from the database
+    fn read_object(&self, oid: &str) -> Result<Box<dyn GitObject>, Error> {
+        let (obj_type, data) = self.read_raw(oid)?;
+        let obj_type = obj_type.as_str();
+        let content = &data[..];
         
         // Parse object based on type
         let mut object: Box<dyn GitObject> = match obj_type {
@@ -226,6 +614,7 @@ impl Database {
                 Ok(commit) => Box::new(commit),
                 Err(e) => return Err(e),
             },
+            "tag" => Box::new(Tag::parse(content)?),
             "sprint-meta" => {
                 // Parse the metadata from the encoded string
                 let encoded = String::from_utf8_lossy(content).to_string();
@@ -264,11 +653,82 @@ impl Database {
     
     /// Helper method to calculate hash for raw data (useful for status command)
     pub fn hash_file_data(&self, data: &[u8]) -> String {
-        let header = format!("blob {}\0", data.len());
-        let mut full_content = header.as_bytes().to_vec();
-        full_content.extend(data);
-        
-        self.hash_content(&full_content)
+        hash_blob_data(data)
+    }
+
+    /// Reads and hashes files as blobs on a pool of threads, returning each path's blob OID.
+    /// If any file cannot be read, the error for the first failing path in sorted order is returned.
+    pub fn hash_files_parallel(&self, paths: &[PathBuf]) -> Result<HashMap<PathBuf, String>, Error> {
+        hash_files_with(paths, hash_blob_file)
+    }
+
+    /// Re-reads every loose object in the primary store and every packed object on a pool of
+    /// threads, recomputing each ID from the decompressed `"<type> <size>\0<content>"` data.
+    /// An object stored more than once is checked in every copy. Returns the number of copies
+    /// scanned and, for each one that fails, its ID and the reason, naming the pack if it has one.
+    pub fn verify_objects(&self) -> Result<(usize, Vec<(String, String)>), Error> {
+        // Every (object, location) pair is read: its loose file, and each pack holding it
+        let mut objects: Vec<(String, Option<usize>)> = self.loose_objects()?
+            .into_iter()
+            .map(|oid| (oid, None))
+            .collect();
+        for (index, pack) in self.packs.iter().enumerate() {
+            objects.extend(pack.oids().map(|oid| (oid, Some(index))));
+        }
+        objects.sort();
+
+        if objects.is_empty() {
+            return Ok((0, Vec::new()));
+        }
+
+        let workers = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+            .min(MAX_OPEN_FILES)
+            .min(objects.len());
+        let objects = &objects;
+        let packs = &self.packs;
+        let pathname = &self.pathname;
+
+        // Worker `w` takes every `workers`-th object starting at `w`
+        let corrupt: Vec<Vec<(String, String)>> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..workers)
+                .map(|w| {
+                    scope.spawn(move || {
+                        objects.iter()
+                            .skip(w)
+                            .step_by(workers)
+                            .filter_map(|(oid, pack)| {
+                                let data = match pack {
+                                    Some(index) => packs[*index].read(oid)
+                                        .and_then(|data| data.ok_or_else(|| Error::Generic("missing from its pack".to_string()))),
+                                    None => Self::read_loose(&pathname.join(&oid[0..2]).join(&oid[2..])),
+                                };
+                                let failure = match data {
+                                    Ok(data) => {
+                                        let actual = format!("{:x}", Sha1::digest(&data));
+                                        (actual != *oid).then(|| format!("content hashes to {}", actual))
+                                    },
+                                    Err(e) => Some(e.to_string()),
+                                };
+                                failure.map(|reason| match pack {
+                                    Some(index) => (oid.clone(), format!("{} (in {})", reason, packs[*index].pack_path().display())),
+                                    None => (oid.clone(), reason),
+                                })
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+
+            handles.into_iter()
+                .map(|handle| handle.join().expect("verification thread panicked"))
+                .collect()
+        });
+
+        let mut corrupt: Vec<(String, String)> = corrupt.into_iter().flatten().collect();
+        corrupt.sort();
+        Ok((objects.len(), corrupt))
     }
 
     pub fn prefix_match(&self, prefix: &str) -> Result<Vec<String>, Error> {
@@ -282,31 +742,42 @@ impl Database {
             return Ok(Vec::new());
         }
         
-        // Get the directory path for this prefix
+        // Get the directory name for this prefix
         let dir_name = &prefix[0..2];
-        let dir_path = self.pathname.join(dir_name);
+        let mut matches = Vec::new();
         
-        if !dir_path.exists() || !dir_path.is_dir() {
-            return Ok(Vec::new());
+        for pack in &self.packs {
+            for oid in pack.prefix_match(prefix) {
+                if !matches.contains(&oid) {
+                    matches.push(oid);
+                }
+            }
         }
         
-        // Read all files in the directory
-        let entries = std::fs::read_dir(&dir_path).map_err(|e| Error::IO(e))?;
-        
-        // Filter files that match our prefix
-        let mut matches = Vec::new();
-        for entry_result in entries {
-            match entry_result {
-                Ok(entry) => {
-                    let file_name = entry.file_name().to_string_lossy().to_string();
-                    let full_id = format!("{}{}", dir_name, file_name);
-                    
-                    // Check if this ID starts with our prefix
-                    if full_id.starts_with(prefix) {
-                        matches.push(full_id);
-                    }
-                },
-                Err(_) => continue,
+        for objects_dir in std::iter::once(&self.pathname).chain(self.alternates.iter()) {
+            let dir_path = objects_dir.join(dir_name);
+            
+            if !dir_path.exists() || !dir_path.is_dir() {
+                continue;
+            }
+            
+            // Read all files in the directory
+            let entries = std::fs::read_dir(&dir_path).map_err(Error::IO)?;
+            
+            // Filter files that match our prefix
+            for entry_result in entries {
+                match entry_result {
+                    Ok(entry) => {
+                        let file_name = entry.file_name().to_string_lossy().to_string();
+                        let full_id = format!("{}{}", dir_name, file_name);
+                        
+                        // Check if this ID starts with our prefix
+                        if full_id.starts_with(prefix) && !matches.contains(&full_id) {
+                            matches.push(full_id);
+                        }
+                    },
+                    Err(_) => continue,
+                }
             }
         }
         
@@ -355,4 +826,248 @@ impl Database {
         
         Err(Error::Generic(format!("Invalid object identifier: {}", partial_oid)))
     }
-}
\ No newline at end of file
+}
+
+/// Computes the OID `data` would have when stored as a blob
+pub fn hash_blob_data(data: &[u8]) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(format!("blob {}\0", data.len()).as_bytes());
+    hasher.update(data);
+    format!("{:x}", hasher.finalize())
+}
+
+/// Hashes every path with `hash` on a pool of threads, returning each path's OID. Each worker
+/// holds one file open at a time, so at most `MAX_OPEN_FILES` are open at once. If any path
+/// fails, the error for the first failing path in sorted order is returned.
+pub fn hash_files_with<F, E>(paths: &[PathBuf], hash: F) -> Result<HashMap<PathBuf, String>, Error>
+where
+    F: Fn(&Path) -> Result<String, E> + Sync,
+    E: std::fmt::Display + Send,
+{
+    if paths.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let workers = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .min(MAX_OPEN_FILES)
+        .min(paths.len());
+
+    // Worker `w` takes every `workers`-th path starting at `w`
+    let hash = &hash;
+    let results: Vec<Vec<(&PathBuf, Result<String, E>)>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|w| {
+                scope.spawn(move || {
+                    paths.iter()
+                        .skip(w)
+                        .step_by(workers)
+                        .map(|path| (path, hash(path)))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        handles.into_iter()
+            .map(|handle| handle.join().expect("hashing thread panicked"))
+            .collect()
+    });
+
+    let mut hashes = HashMap::with_capacity(paths.len());
+    let mut first_error: Option<(&PathBuf, E)> = None;
+
+    for (path, result) in results.into_iter().flatten() {
+        match result {
+            Ok(oid) => {
+                hashes.insert(path.clone(), oid);
+            },
+            Err(e) => {
+                if first_error.as_ref().is_none_or(|(first, _)| path < *first) {
+                    first_error = Some((path, e));
+                }
+            }
+        }
+    }
+
+    match first_error {
+        Some((path, e)) => Err(Error::Generic(format!("Failed to read '{}': {}", path.display(), e))),
+        None => Ok(hashes),
+    }
+}
+
+/// Computes the blob OID of a file's raw content, reading it in chunks rather than whole.
+/// A file that shrinks while being read is reported as an error instead of hashed short.
+pub fn hash_blob_file(path: &Path) -> std::io::Result<String> {
+    let file = File::open(path)?;
+    let size = file.metadata()?.len();
+
+    let mut hasher = Sha1::new();
+    hasher.update(format!("blob {}\0", size).as_bytes());
+
+    let mut reader = file.take(size);
+    let mut buffer = vec![0u8; STREAM_CHUNK_SIZE];
+    let mut read = 0u64;
+    loop {
+        let n = reader.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+        read += n as u64;
+    }
+
+    if read != size {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::UnexpectedEof,
+            format!("{} changed while being hashed", path.display()),
+        ));
+    }
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestRepo;
+    use std::path::Path;
+
+    /// Every object file under `root`, as paths relative to it in order
+    fn stored_paths(root: &Path) -> Vec<String> {
+        let mut files = Vec::new();
+        for dir in fs::read_dir(root).unwrap() {
+            let dir = dir.unwrap().path();
+            for file in fs::read_dir(&dir).unwrap() {
+                let file = file.unwrap().path();
+                files.push(file.strip_prefix(root).unwrap().to_string_lossy().into_owned());
+            }
+        }
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn batched_objects_match_unbatched_ones() {
+        let unbatched = tempfile::tempdir().unwrap();
+        let batched = tempfile::tempdir().unwrap();
+        let mut plain = Database::new(unbatched.path().to_path_buf());
+        let mut database = Database::new(batched.path().to_path_buf());
+        plain.store(&mut Blob::new(b"blob 0\n".to_vec())).unwrap();
+        database.store(&mut Blob::new(b"blob 0\n".to_vec())).unwrap();
+
+        // Fills a fan-out directory that already exists as well as new ones
+        database.begin_batch();
+        let mut oids = Vec::new();
+        for i in 0..50 {
+            let data = format!("blob {}\n", i % 40).into_bytes();
+            let oid = database.store(&mut Blob::new(data.clone())).unwrap();
+            assert_eq!(plain.store(&mut Blob::new(data)).unwrap(), oid);
+            oids.push(oid);
+        }
+        assert!(!database.exists(&oids[1]));
+        assert_eq!(database.flush_batch().unwrap(), 39);
+
+        assert_eq!(stored_paths(batched.path()), stored_paths(unbatched.path()));
+        for oid in &oids {
+            assert_eq!(database.load(oid).unwrap().to_bytes(), plain.load(oid).unwrap().to_bytes());
+        }
+    }
+
+    #[test]
+    fn discarded_batch_leaves_nothing_behind() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut database = Database::new(dir.path().to_path_buf());
+
+        database.begin_batch();
+        let oid = database.store(&mut Blob::new(b"hello\n".to_vec())).unwrap();
+        database.discard_batch();
+
+        assert!(!database.exists(&oid));
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+        assert_eq!(database.flush_batch().unwrap(), 0);
+    }
+
+    #[test]
+    fn verify_objects_checks_every_copy() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut database = Database::new(dir.path().to_path_buf());
+        let oid = database.store(&mut Blob::new(b"hello\n".to_vec())).unwrap();
+
+        // A packed copy of the same object whose content does not match its ID
+        let corrupt = (oid.clone(), b"blob 6\0jello\n".to_vec());
+        PackWriter::write(&dir.path().join("pack"), &[corrupt], &HashMap::new()).unwrap();
+        database.reload_packs();
+
+        let (scanned, failures) = database.verify_objects().unwrap();
+        assert_eq!(scanned, 2);
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].0, oid);
+        assert!(failures[0].1.contains("pack-"), "{}", failures[0].1);
+    }
+
+    #[test]
+    fn hashing_fails_on_the_first_unreadable_path_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let database = Database::new(dir.path().join("objects"));
+        let readable = dir.path().join("a.txt");
+        fs::write(&readable, "hello\n").unwrap();
+
+        let hashes = database.hash_files_parallel(std::slice::from_ref(&readable)).unwrap();
+        assert_eq!(hashes[&readable], database.hash_file_data(b"hello\n"));
+
+        let paths = vec![dir.path().join("missing-z.txt"), readable, dir.path().join("missing-b.txt")];
+        let err = database.hash_files_parallel(&paths).unwrap_err().to_string();
+        assert!(err.contains("missing-b.txt"), "{}", err);
+    }
+
+    /// Writes `content` as a loose object the way git does, returning its ID
+    fn write_git_object(objects: &Path, kind: &str, content: &[u8]) -> String {
+        let mut data = format!("{} {}\0", kind, content.len()).into_bytes();
+        data.extend_from_slice(content);
+        let oid = hex::encode(Sha1::digest(&data));
+
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&data).unwrap();
+        fs::create_dir_all(objects.join(&oid[..2])).unwrap();
+        fs::write(objects.join(&oid[..2]).join(&oid[2..]), encoder.finish().unwrap()).unwrap();
+        oid
+    }
+
+    #[test]
+    fn objects_written_by_git_load_through_the_alternates_variable() {
+        // The variable is process-wide, so this takes the same turn as tests that run commands
+        let repo = TestRepo::new();
+        let git_objects = repo.path().join(".git").join("objects");
+
+        let blob = write_git_object(&git_objects, "blob", b"hello\n");
+        let mut tree_content = b"100644 a.txt\0".to_vec();
+        tree_content.extend_from_slice(&hex::decode(&blob).unwrap());
+        let tree = write_git_object(&git_objects, "tree", &tree_content);
+        let commit_content = format!(
+            "tree {}\nauthor Ada <ada@example.com> 1700000000 +0000\ncommitter Ada <ada@example.com> 1700000000 +0000\n\ninitial\n",
+            tree
+        );
+        let commit = write_git_object(&git_objects, "commit", commit_content.as_bytes());
+
+        std::env::set_var(ALTERNATES_ENV, &git_objects);
+        let mut database = Database::new(repo.path().join(".ash").join("objects"));
+        std::env::remove_var(ALTERNATES_ENV);
+
+        for oid in [&blob, &tree, &commit] {
+            assert!(database.exists(oid), "{}", oid);
+        }
+
+        let object = database.load(&commit).unwrap();
+        let loaded = object.as_any().downcast_ref::<Commit>().unwrap();
+        assert_eq!(loaded.get_tree(), tree);
+        assert_eq!(loaded.get_author().unwrap().name, "Ada");
+
+        let object = database.load(&tree).unwrap();
+        let loaded = object.as_any().downcast_ref::<Tree>().unwrap();
+        assert_eq!(loaded.get_entries().len(), 1);
+
+        let object = database.load(&blob).unwrap();
+        assert_eq!(object.to_bytes(), b"hello\n");
+    }
+}
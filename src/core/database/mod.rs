@@ -1,9 +1,11 @@
 pub mod database;
 pub mod blob;
 pub mod commit;
+pub mod tag;
 pub mod tree;
 pub mod author;
 pub mod entry;
 pub mod tree_diff;
 pub mod sprint_metadata_object;
-pub mod task_metadata_object;
\ No newline at end of file
+pub mod task_metadata_object;
+pub mod pack;
\ No newline at end of file
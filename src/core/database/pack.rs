@@ -0,0 +1,729 @@
+// src/core/database/pack.rs
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use sha1::{Digest, Sha1};
+use flate2::write::ZlibEncoder;
+use flate2::read::ZlibDecoder;
+use flate2::Compression;
+use crate::errors::error::Error;
+
+/// Signature at the start of every pack file
+const PACK_SIGNATURE: &[u8; 4] = b"PACK";
+
+/// Signature at the start of every pack index
+const IDX_SIGNATURE: &[u8; 4] = b"AIDX";
+
+/// Version 1 records hold whole objects; version 2 records may be deltas
+const PACK_VERSION: u32 = 2;
+const IDX_VERSION: u32 = 1;
+
+const RECORD_FULL: u8 = 0;
+const RECORD_DELTA: u8 = 1;
+
+/// How many of the preceding objects are tried as delta bases for each object
+const DELTA_WINDOW: usize = 10;
+/// Longest chain of deltas that may be followed to rebuild an object
+const MAX_DELTA_DEPTH: usize = 50;
+/// Blocks of the base this long are indexed when searching for copies
+const DELTA_BLOCK: usize = 16;
+
+const PACK_HEADER_SIZE: usize = 12;
+const IDX_HEADER_SIZE: usize = 8;
+const FANOUT_SIZE: usize = 256 * 4;
+const OID_SIZE: usize = 20;
+
+/// Writes a pack of objects together with its index.
+///
+/// A pack is `PACK <version> <count>` followed by one record per object and a trailing SHA-1
+/// of everything before it. Each record is a 4-byte length, a kind byte and the zlib-compressed
+/// payload. A full record's payload is the object exactly as a loose object
+/// (`"<type> <size>\0<content>"`), so any object type can be packed; a delta record also holds
+/// the 8-byte offset of its base record, and its payload rebuilds the object from the base.
+///
+/// Objects are ordered by type, then file name, then descending size before deltification,
+/// so versions of the same file sit next to each other with the largest one first as a base.
+///
+/// The index holds a 256-entry fan-out table, the sorted object IDs, the offset of each record
+/// in the pack, then the pack checksum and a checksum of the index itself.
+pub struct PackWriter;
+
+impl PackWriter {
+    /// Writes `pack-<checksum>.pack` and `.idx` into `pack_dir` from `(oid, data)` pairs,
+    /// where `data` is the uncompressed loose object data. `names` maps object IDs to the file
+    /// name they were seen under, used to group versions of a file. Returns the path of the index.
+    pub fn write(pack_dir: &Path, objects: &[(String, Vec<u8>)], names: &HashMap<String, String>) -> Result<PathBuf, Error> {
+        fs::create_dir_all(pack_dir)?;
+
+        let mut pack = Vec::new();
+        pack.extend_from_slice(PACK_SIGNATURE);
+        pack.extend_from_slice(&PACK_VERSION.to_be_bytes());
+        pack.extend_from_slice(&(objects.len() as u32).to_be_bytes());
+
+        let mut order: Vec<&(String, Vec<u8>)> = objects.iter().collect();
+        order.sort_by_key(|(oid, data)| {
+            let name = names.get(oid).map(String::as_str).unwrap_or("");
+            (type_rank(data), name, Reverse(data.len()))
+        });
+
+        // Recently written objects as (data, record offset, delta depth), the candidate bases
+        let mut window: Vec<(&[u8], u64, usize)> = Vec::with_capacity(DELTA_WINDOW);
+        let mut entries = Vec::with_capacity(objects.len());
+
+        for (oid, data) in order {
+            let offset = pack.len() as u64;
+
+            // Smallest delta against a base of the same type, if it is worth storing
+            let best = window.iter()
+                .filter(|(base, _, depth)| *depth < MAX_DELTA_DEPTH && type_rank(base) == type_rank(data))
+                .map(|(base, base_offset, depth)| (create_delta(base, data), *base_offset, depth + 1))
+                .min_by_key(|(delta, _, _)| delta.len())
+                .filter(|(delta, _, _)| delta.len() < data.len() / 2);
+
+            let depth = match best {
+                Some((delta, base_offset, depth)) => {
+                    let compressed = compress(&delta)?;
+                    pack.extend_from_slice(&(compressed.len() as u32).to_be_bytes());
+                    pack.push(RECORD_DELTA);
+                    pack.extend_from_slice(&base_offset.to_be_bytes());
+                    pack.extend_from_slice(&compressed);
+                    depth
+                },
+                None => {
+                    let compressed = compress(data)?;
+                    pack.extend_from_slice(&(compressed.len() as u32).to_be_bytes());
+                    pack.push(RECORD_FULL);
+                    pack.extend_from_slice(&compressed);
+                    0
+                },
+            };
+
+            entries.push((oid_to_bytes(oid)?, offset));
+
+            if window.len() == DELTA_WINDOW {
+                window.remove(0);
+            }
+            window.push((&data[..], offset, depth));
+        }
+
+        let pack_checksum = sha1_digest(&pack);
+        pack.extend_from_slice(&pack_checksum);
+
+        entries.sort();
+        entries.dedup_by(|a, b| a.0 == b.0);
+
+        let mut idx = Vec::new();
+        idx.extend_from_slice(IDX_SIGNATURE);
+        idx.extend_from_slice(&IDX_VERSION.to_be_bytes());
+
+        // fanout[b] counts the objects whose first byte is <= b
+        let mut counts = [0u32; 256];
+        for (oid, _) in &entries {
+            counts[oid[0] as usize] += 1;
+        }
+        let mut total = 0u32;
+        for count in counts.iter() {
+            total += count;
+            idx.extend_from_slice(&total.to_be_bytes());
+        }
+
+        for (oid, _) in &entries {
+            idx.extend_from_slice(oid);
+        }
+        for (_, offset) in &entries {
+            idx.extend_from_slice(&offset.to_be_bytes());
+        }
+
+        idx.extend_from_slice(&pack_checksum);
+        let idx_checksum = sha1_digest(&idx);
+        idx.extend_from_slice(&idx_checksum);
+
+        let name = format!("pack-{}", hex(&pack_checksum));
+        let pack_path = pack_dir.join(format!("{}.pack", name));
+        let idx_path = pack_dir.join(format!("{}.idx", name));
+
+        // The index is written last so readers never see an index without its pack
+        write_synced(&pack_path, &pack)?;
+        write_synced(&idx_path, &idx)?;
+
+        Ok(idx_path)
+    }
+}
+
+/// Reads objects from a pack through its index.
+/// The index is kept in memory; records are read from the pack file on demand.
+#[derive(Clone)]
+pub struct PackReader {
+    pack_path: PathBuf,
+    version: u32,
+    fanout: Vec<u32>,
+    oids: Vec<[u8; OID_SIZE]>,
+    offsets: Vec<u64>,
+}
+
+impl PackReader {
+    /// Opens the index at `idx_path` and the `.pack` file next to it
+    pub fn open(idx_path: &Path) -> Result<Self, Error> {
+        let data = fs::read(idx_path)?;
+        let invalid = || Error::Generic(format!("Invalid pack index: {}", idx_path.display()));
+
+        if data.len() < IDX_HEADER_SIZE + FANOUT_SIZE + 2 * OID_SIZE || &data[0..4] != IDX_SIGNATURE {
+            return Err(invalid());
+        }
+        if read_u32(&data[4..8]) != IDX_VERSION {
+            return Err(Error::Generic(format!("Unsupported pack index version in {}", idx_path.display())));
+        }
+
+        let (body, checksum) = data.split_at(data.len() - OID_SIZE);
+        if sha1_digest(body) != checksum {
+            return Err(Error::Generic(format!("Pack index checksum mismatch: {}", idx_path.display())));
+        }
+
+        let fanout: Vec<u32> = data[IDX_HEADER_SIZE..IDX_HEADER_SIZE + FANOUT_SIZE]
+            .chunks(4)
+            .map(read_u32)
+            .collect();
+        // Each entry counts the objects up to its first byte, so the table never decreases
+        if fanout.windows(2).any(|pair| pair[0] > pair[1]) {
+            return Err(invalid());
+        }
+        let count = fanout[255] as usize;
+
+        let oids_start = IDX_HEADER_SIZE + FANOUT_SIZE;
+        let offsets_start = oids_start + count * OID_SIZE;
+        if body.len() != offsets_start + count * 8 + OID_SIZE {
+            return Err(invalid());
+        }
+
+        let oids = data[oids_start..offsets_start]
+            .chunks(OID_SIZE)
+            .map(|chunk| {
+                let mut oid = [0u8; OID_SIZE];
+                oid.copy_from_slice(chunk);
+                oid
+            })
+            .collect();
+        let offsets = data[offsets_start..offsets_start + count * 8]
+            .chunks(8)
+            .map(|chunk| {
+                let mut bytes = [0u8; 8];
+                bytes.copy_from_slice(chunk);
+                u64::from_be_bytes(bytes)
+            })
+            .collect();
+
+        let pack_path = idx_path.with_extension("pack");
+        let mut header = [0u8; PACK_HEADER_SIZE];
+        File::open(&pack_path)?.read_exact(&mut header)?;
+        if &header[0..4] != PACK_SIGNATURE {
+            return Err(Error::Generic(format!("Invalid pack file: {}", pack_path.display())));
+        }
+        let version = read_u32(&header[4..8]);
+        if version == 0 || version > PACK_VERSION {
+            return Err(Error::Generic(format!("Unsupported pack version in {}", pack_path.display())));
+        }
+        if read_u32(&header[8..12]) as usize != count {
+            return Err(invalid());
+        }
+
+        Ok(PackReader {
+            pack_path,
+            version,
+            fanout,
+            oids,
+            offsets,
+        })
+    }
+
+    /// Opens every pack index in `pack_dir`, skipping any that can't be read
+    pub fn open_all(pack_dir: &Path) -> Vec<Self> {
+        let mut idx_paths: Vec<PathBuf> = match fs::read_dir(pack_dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| path.extension().is_some_and(|ext| ext == "idx"))
+                .collect(),
+            Err(_) => return Vec::new(),
+        };
+        idx_paths.sort();
+
+        idx_paths.iter()
+            .filter_map(|path| Self::open(path).ok())
+            .collect()
+    }
+
+    pub fn pack_path(&self) -> &Path {
+        &self.pack_path
+    }
+
+    pub fn len(&self) -> usize {
+        self.oids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.oids.is_empty()
+    }
+
+    /// All object IDs in the pack, in sorted order
+    pub fn oids(&self) -> impl Iterator<Item = String> + '_ {
+        self.oids.iter().map(|oid| hex(oid))
+    }
+
+    pub fn contains(&self, oid: &str) -> bool {
+        self.position(oid).is_some()
+    }
+
+    /// Object IDs in the pack starting with the hex `prefix`
+    pub fn prefix_match(&self, prefix: &str) -> Vec<String> {
+        let first = match prefix.get(0..2).and_then(|b| u8::from_str_radix(b, 16).ok()) {
+            Some(first) => first as usize,
+            None => return Vec::new(),
+        };
+        let (start, end) = self.fanout_range(first);
+
+        self.oids[start..end].iter()
+            .map(|oid| hex(oid))
+            .filter(|oid| oid.starts_with(prefix))
+            .collect()
+    }
+
+    /// Returns the uncompressed `"<type> <size>\0<content>"` data of `oid`, if the pack holds it
+    pub fn read(&self, oid: &str) -> Result<Option<Vec<u8>>, Error> {
+        let position = match self.position(oid) {
+            Some(position) => position,
+            None => return Ok(None),
+        };
+
+        let mut file = File::open(&self.pack_path)?;
+        self.read_record(&mut file, self.offsets[position], 0).map(Some)
+    }
+
+    /// Reads the record at `offset`, rebuilding it from its base chain if it is a delta
+    fn read_record(&self, file: &mut File, offset: u64, depth: usize) -> Result<Vec<u8>, Error> {
+        if depth > MAX_DELTA_DEPTH {
+            return Err(Error::Generic(format!("Delta chain too long in {}", self.pack_path.display())));
+        }
+
+        file.seek(SeekFrom::Start(offset))?;
+
+        let mut length = [0u8; 4];
+        file.read_exact(&mut length)?;
+
+        // Version 1 records are always whole objects and have no kind byte
+        let mut kind = [RECORD_FULL];
+        if self.version >= 2 {
+            file.read_exact(&mut kind)?;
+        }
+
+        let base_offset = match kind[0] {
+            RECORD_FULL => None,
+            RECORD_DELTA => {
+                let mut base = [0u8; 8];
+                file.read_exact(&mut base)?;
+                Some(u64::from_be_bytes(base))
+            },
+            other => return Err(Error::Generic(format!("Unknown record kind {} in {}", other, self.pack_path.display()))),
+        };
+
+        let mut compressed = vec![0u8; read_u32(&length) as usize];
+        file.read_exact(&mut compressed)?;
+
+        let mut decoder = ZlibDecoder::new(&compressed[..]);
+        let mut data = Vec::new();
+        decoder.read_to_end(&mut data)?;
+
+        match base_offset {
+            Some(base_offset) if base_offset < offset => {
+                let base = self.read_record(file, base_offset, depth + 1)?;
+                apply_delta(&base, &data)
+            },
+            Some(_) => Err(Error::Generic(format!("Delta base follows its delta in {}", self.pack_path.display()))),
+            None => Ok(data),
+        }
+    }
+
+    /// Checks the pack's header and checksum against the index, and that every object
+    /// can be read back and hashes to its ID
+    pub fn verify(&self) -> Result<(), Error> {
+        let pack = fs::read(&self.pack_path)?;
+        if pack.len() < PACK_HEADER_SIZE + OID_SIZE || &pack[0..4] != PACK_SIGNATURE {
+            return Err(Error::Generic(format!("Invalid pack file: {}", self.pack_path.display())));
+        }
+        if read_u32(&pack[8..12]) as usize != self.oids.len() {
+            return Err(Error::Generic(format!("Pack {} does not match its index", self.pack_path.display())));
+        }
+
+        let (body, checksum) = pack.split_at(pack.len() - OID_SIZE);
+        if sha1_digest(body) != checksum {
+            return Err(Error::Generic(format!("Pack checksum mismatch: {}", self.pack_path.display())));
+        }
+
+        for oid in self.oids() {
+            let data = self.read(&oid)?
+                .ok_or_else(|| Error::Generic(format!("Object {} missing from pack", oid)))?;
+            if hex(&sha1_digest(&data)) != oid {
+                return Err(Error::Generic(format!("Packed object {} is corrupt", oid)));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Range of `oids` whose first byte is `first`
+    fn fanout_range(&self, first: usize) -> (usize, usize) {
+        let start = if first == 0 { 0 } else { self.fanout[first - 1] as usize };
+        (start, self.fanout[first] as usize)
+    }
+
+    fn position(&self, oid: &str) -> Option<usize> {
+        let oid = oid_to_bytes(oid).ok()?;
+        let (start, end) = self.fanout_range(oid[0] as usize);
+
+        self.oids[start..end]
+            .binary_search(&oid)
+            .ok()
+            .map(|i| start + i)
+    }
+}
+
+/// Sort rank of a loose object's type, taken from its `"<type> <size>\0"` header
+fn type_rank(data: &[u8]) -> u8 {
+    if data.starts_with(b"commit ") {
+        0
+    } else if data.starts_with(b"tree ") {
+        1
+    } else if data.starts_with(b"blob ") {
+        2
+    } else if data.starts_with(b"tag ") {
+        3
+    } else {
+        4
+    }
+}
+
+fn compress(data: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
+
+/// Encodes `target` as instructions against `base`: the target size as 4 bytes, then a
+/// sequence of copies (`0x80`, 4-byte offset, 4-byte length) and inserts (a length byte
+/// of 1..=127 followed by that many literal bytes)
+fn create_delta(base: &[u8], target: &[u8]) -> Vec<u8> {
+    let mut delta = Vec::new();
+    delta.extend_from_slice(&(target.len() as u32).to_be_bytes());
+
+    // First position of every aligned block in the base
+    let mut blocks: HashMap<&[u8], usize> = HashMap::new();
+    for start in (0..base.len().saturating_sub(DELTA_BLOCK - 1)).step_by(DELTA_BLOCK) {
+        blocks.entry(&base[start..start + DELTA_BLOCK]).or_insert(start);
+    }
+
+    let mut literal: Vec<u8> = Vec::new();
+    let mut pos = 0;
+    while pos < target.len() {
+        let found = target.get(pos..pos + DELTA_BLOCK).and_then(|block| blocks.get(block));
+
+        match found {
+            Some(&start) => {
+                // Extend the match forwards as far as both sides agree
+                let mut len = DELTA_BLOCK;
+                while start + len < base.len() && pos + len < target.len() && base[start + len] == target[pos + len] {
+                    len += 1;
+                }
+
+                flush_literal(&mut delta, &mut literal);
+                delta.push(0x80);
+                delta.extend_from_slice(&(start as u32).to_be_bytes());
+                delta.extend_from_slice(&(len as u32).to_be_bytes());
+                pos += len;
+            },
+            None => {
+                literal.push(target[pos]);
+                pos += 1;
+            },
+        }
+    }
+    flush_literal(&mut delta, &mut literal);
+
+    delta
+}
+
+fn flush_literal(delta: &mut Vec<u8>, literal: &mut Vec<u8>) {
+    for chunk in literal.chunks(0x7f) {
+        delta.push(chunk.len() as u8);
+        delta.extend_from_slice(chunk);
+    }
+    literal.clear();
+}
+
+/// Rebuilds an object from its base and a delta made by `create_delta`
+fn apply_delta(base: &[u8], delta: &[u8]) -> Result<Vec<u8>, Error> {
+    let corrupt = || Error::Generic("Corrupt delta in pack".to_string());
+
+    if delta.len() < 4 {
+        return Err(corrupt());
+    }
+    let size = read_u32(&delta[0..4]) as usize;
+    let mut result = Vec::with_capacity(size);
+
+    let mut pos = 4;
+    while pos < delta.len() {
+        let op = delta[pos];
+        pos += 1;
+
+        if op == 0x80 {
+            let args = delta.get(pos..pos + 8).ok_or_else(corrupt)?;
+            let start = read_u32(&args[0..4]) as usize;
+            let len = read_u32(&args[4..8]) as usize;
+            result.extend_from_slice(base.get(start..start + len).ok_or_else(corrupt)?);
+            pos += 8;
+        } else if op > 0 && op < 0x80 {
+            let len = op as usize;
+            result.extend_from_slice(delta.get(pos..pos + len).ok_or_else(corrupt)?);
+            pos += len;
+        } else {
+            return Err(corrupt());
+        }
+    }
+
+    if result.len() != size {
+        return Err(corrupt());
+    }
+    Ok(result)
+}
+
+fn write_synced(path: &Path, data: &[u8]) -> Result<(), Error> {
+    let mut file = File::create(path)?;
+    file.write_all(data)?;
+    file.sync_all()?;
+    Ok(())
+}
+
+fn sha1_digest(data: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha1::new();
+    hasher.update(data);
+    hasher.finalize().to_vec()
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn oid_to_bytes(oid: &str) -> Result<[u8; OID_SIZE], Error> {
+    if oid.len() != OID_SIZE * 2 || !oid.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(Error::Generic(format!("Invalid object ID: {}", oid)));
+    }
+
+    let mut bytes = [0u8; OID_SIZE];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&oid[i * 2..i * 2 + 2], 16)
+            .map_err(|_| Error::Generic(format!("Invalid object ID: {}", oid)))?;
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::database::author::Author;
+    use crate::core::database::blob::Blob;
+    use crate::core::database::commit::Commit;
+    use crate::core::database::database::{Database, GitObject};
+    use crate::core::database::entry::DatabaseEntry;
+    use crate::core::database::tag::Tag;
+    use crate::core::database::tree::Tree;
+
+    fn store(database: &mut Database, object: &mut impl GitObject) -> (String, Vec<u8>) {
+        let oid = database.store(object).unwrap();
+        (oid, database.serialize_object(object).unwrap())
+    }
+
+    /// Stores a blob, a tree holding it, a commit of that tree and a tag of the commit as
+    /// loose objects, returning each one's ID and loose object data
+    fn store_every_type(database: &mut Database) -> Vec<(String, Vec<u8>)> {
+        let author = Author::new("Pack Test".to_string(), "pack@test.invalid".to_string());
+
+        let blob = store(database, &mut Blob::new(b"packed content\n".to_vec()));
+        let entry = DatabaseEntry::new("file.txt".to_string(), blob.0.clone(), "100644");
+        let tree = store(database, &mut Tree::build(std::iter::once(&entry)).unwrap());
+        let commit = store(database, &mut Commit::new(Vec::new(), tree.0.clone(), author.clone(), "Packed commit".to_string()));
+        let tag = store(database, &mut Tag::new(commit.0.clone(), "commit".to_string(), "v1".to_string(), author, "Packed tag".to_string()));
+
+        vec![blob, tree, commit, tag]
+    }
+
+    #[test]
+    fn pack_round_trips_every_object_type() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut database = Database::new(dir.path().join("objects"));
+        let objects = store_every_type(&mut database);
+
+        let idx_path = PackWriter::write(&dir.path().join("pack"), &objects, &HashMap::new()).unwrap();
+        let pack = PackReader::open(&idx_path).unwrap();
+        pack.verify().unwrap();
+        assert_eq!(pack.len(), objects.len());
+
+        for (oid, data) in &objects {
+            assert!(pack.contains(oid));
+            assert_eq!(pack.prefix_match(&oid[..7]), vec![oid.clone()]);
+            assert_eq!(pack.read(oid).unwrap().as_ref(), Some(data));
+        }
+
+        let missing = "0".repeat(40);
+        assert!(!pack.contains(&missing));
+        assert_eq!(pack.read(&missing).unwrap(), None);
+    }
+
+    #[test]
+    fn packed_objects_load_once_loose_files_are_gone() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut database = Database::new(dir.path().to_path_buf());
+        let objects = store_every_type(&mut database);
+
+        let (_, count) = database.pack_loose_objects().unwrap().unwrap();
+        assert_eq!(count, objects.len());
+        assert!(database.loose_objects().unwrap().is_empty());
+
+        // A fresh database finds every object through the pack index
+        let mut database = Database::new(dir.path().to_path_buf());
+        for (oid, data) in &objects {
+            assert!(database.exists(oid));
+            let object = database.load(oid).unwrap();
+            let content = object.to_bytes();
+            let mut loaded = format!("{} {}\0", object.get_type(), content.len()).into_bytes();
+            loaded.extend_from_slice(&content);
+            assert_eq!(&loaded, data, "{}", oid);
+        }
+    }
+
+    /// `len` bytes that don't repeat in 16-byte blocks, from a fixed seed
+    fn noise(len: usize, seed: u32) -> Vec<u8> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                (state >> 16) as u8
+            })
+            .collect()
+    }
+
+    fn assert_round_trip(base: &[u8], target: &[u8]) -> Vec<u8> {
+        let delta = create_delta(base, target);
+        assert_eq!(apply_delta(base, &delta).unwrap(), target);
+        delta
+    }
+
+    #[test]
+    fn delta_round_trips() {
+        let base = noise(4096, 1);
+
+        assert_round_trip(&base, b"");
+        assert_round_trip(b"", &base);
+        assert_round_trip(b"", b"");
+
+        // An identical target is a single copy of the whole base
+        assert_eq!(assert_round_trip(&base, &base).len(), 4 + 9);
+
+        let mut appended = base.clone();
+        appended.extend_from_slice(b"appended line\n");
+        let delta = assert_round_trip(&base, &appended);
+        assert!(delta.len() < 64, "{} bytes", delta.len());
+
+        let mut prepended = b"prepended line\n".to_vec();
+        prepended.extend_from_slice(&base);
+        assert_round_trip(&base, &prepended);
+
+        let mut edited = base.clone();
+        edited[2000..2010].copy_from_slice(b"0123456789");
+        assert_round_trip(&base, &edited);
+
+        assert_round_trip(&base, &noise(4096, 2));
+    }
+
+    #[test]
+    fn delta_round_trips_large_copies() {
+        // Copies longer than a length byte can express, taken out of order
+        let base = noise(1 << 20, 3);
+        let mut target = base[500_000..].to_vec();
+        target.extend_from_slice(&noise(300, 4));
+        target.extend_from_slice(&base[..400_000]);
+
+        let delta = assert_round_trip(&base, &target);
+        assert!(delta.len() < 400, "{} bytes", delta.len());
+    }
+
+    #[test]
+    fn apply_delta_rejects_corrupt_deltas() {
+        let base = noise(256, 5);
+        let delta = create_delta(&base, &base);
+
+        assert!(apply_delta(&base, &delta[..3]).is_err());
+        assert!(apply_delta(&base, &delta[..delta.len() - 1]).is_err());
+        assert!(apply_delta(&base[..100], &delta).is_err());
+
+        let mut wrong_size = delta.clone();
+        wrong_size[3] ^= 1;
+        assert!(apply_delta(&base, &wrong_size).is_err());
+    }
+
+    #[test]
+    fn pack_rebuilds_deltified_versions() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut database = Database::new(dir.path().join("objects"));
+
+        // Each version of the file appends to the previous one
+        let mut content = noise(8192, 6);
+        let mut objects = Vec::new();
+        let mut names = HashMap::new();
+        for version in 0..5 {
+            content.extend_from_slice(format!("version {}\n", version).as_bytes());
+            let object = store(&mut database, &mut Blob::new(content.clone()));
+            names.insert(object.0.clone(), "file.bin".to_string());
+            objects.push(object);
+        }
+
+        let idx_path = PackWriter::write(&dir.path().join("pack"), &objects, &names).unwrap();
+        let pack = PackReader::open(&idx_path).unwrap();
+        pack.verify().unwrap();
+
+        // All but the largest version are stored as deltas
+        let pack_size = fs::metadata(pack.pack_path()).unwrap().len() as usize;
+        assert!(pack_size < 2 * content.len(), "{} bytes", pack_size);
+        for (oid, data) in &objects {
+            assert_eq!(pack.read(oid).unwrap().as_ref(), Some(data));
+        }
+    }
+
+    #[test]
+    fn open_rejects_a_bad_fanout_table() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut database = Database::new(dir.path().join("objects"));
+        let objects = store_every_type(&mut database);
+        let idx_path = PackWriter::write(&dir.path().join("pack"), &objects, &HashMap::new()).unwrap();
+        let idx = fs::read(&idx_path).unwrap();
+
+        // A first entry above the next one, with the index checksum kept valid
+        let mut data = idx.clone();
+        data[IDX_HEADER_SIZE..IDX_HEADER_SIZE + 4].copy_from_slice(&(objects.len() as u32 + 1).to_be_bytes());
+        let checksum_start = data.len() - OID_SIZE;
+        let checksum = sha1_digest(&data[..checksum_start]);
+        data[checksum_start..].copy_from_slice(&checksum);
+        fs::write(&idx_path, data).unwrap();
+        assert!(PackReader::open(&idx_path).is_err());
+
+        // A table that ends short of the pack's object count
+        fs::write(&idx_path, &idx).unwrap();
+        let pack_path = idx_path.with_extension("pack");
+        let mut pack = fs::read(&pack_path).unwrap();
+        pack[8..12].copy_from_slice(&(objects.len() as u32 + 1).to_be_bytes());
+        fs::write(&pack_path, pack).unwrap();
+        assert!(PackReader::open(&idx_path).is_err());
+    }
+}
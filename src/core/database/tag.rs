@@ -0,0 +1,110 @@
+// src/core/database/tag.rs
+use super::{author::Author, database::GitObject};
+use crate::errors::error::Error;
+use std::any::Any;
+use std::collections::HashMap;
+use std::str;
+
+/// Annotated tag object, pointing at another object (usually a commit)
+#[derive(Debug, Clone)]
+pub struct Tag {
+    pub oid: Option<String>,
+    pub object: String,
+    pub object_type: String,
+    pub name: String,
+    pub tagger: Author,
+    pub message: String,
+}
+
+impl GitObject for Tag {
+    fn get_type(&self) -> &str {
+        "tag"
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let lines = [
+            format!("object {}", self.object),
+            format!("type {}", self.object_type),
+            format!("tag {}", self.name),
+            format!("tagger {}", self.tagger),
+            String::new(),
+            self.message.clone(),
+        ];
+
+        lines.join("\n").into_bytes()
+    }
+
+    fn set_oid(&mut self, oid: String) {
+        self.oid = Some(oid);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn GitObject> {
+        Box::new(self.clone())
+    }
+}
+
+impl Tag {
+    pub fn new(object: String, object_type: String, name: String, tagger: Author, message: String) -> Self {
+        Tag {
+            oid: None,
+            object,
+            object_type,
+            name,
+            tagger,
+            message,
+        }
+    }
+
+    pub fn get_object(&self) -> &str {
+        &self.object
+    }
+
+    pub fn title_line(&self) -> String {
+        self.message.lines().next().unwrap_or("").to_string()
+    }
+
+    /// Parse a tag object from its serialized content
+    pub fn parse(data: &[u8]) -> Result<Self, Error> {
+        let content = str::from_utf8(data)
+            .map_err(|_| Error::Generic("Invalid UTF-8 in tag".to_string()))?;
+
+        let (header, message) = match content.find("\n\n") {
+            Some(pos) => (&content[..pos], content[pos + 2..].to_string()),
+            None => (content, String::new()),
+        };
+
+        let mut headers = HashMap::new();
+        for line in header.lines() {
+            let parts: Vec<&str> = line.splitn(2, ' ').collect();
+            if parts.len() != 2 {
+                return Err(Error::Generic(format!("Invalid tag header: {}", line)));
+            }
+            headers.insert(parts[0], parts[1].to_string());
+        }
+
+        let field = |name: &str| {
+            headers.get(name)
+                .cloned()
+                .ok_or_else(|| Error::Generic(format!("Missing {} in tag", name)))
+        };
+
+        let object = field("object")?;
+        let object_type = field("type")?;
+        let name = field("tag")?;
+        let tagger = Author::parse(&field("tagger")?)
+            .map_err(|_| Error::Generic("Invalid tagger format".to_string()))?;
+
+        Ok(Tag {
+            oid: None,
+            object,
+            object_type,
+            name,
+            tagger,
+            message,
+        })
+    }
+}
@@ -2,6 +2,7 @@
 use crate::core::database::database::Database;
 use crate::core::database::entry::DatabaseEntry;
 use crate::core::file_mode::FileMode;
+use crate::core::index::index::Index;
 use super::database::GitObject;
 use crate::errors::error::Error;
 use itertools::Itertools;
@@ -11,6 +12,7 @@ use std::path::PathBuf;
 
 #[derive(Debug)]
 #[derive(Clone)]
+#[derive(Default)]
 pub struct Tree {
     oid: Option<String>,
     entries: HashMap<String, TreeEntry>,
@@ -477,4 +479,68 @@ impl Tree {
         
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+/// Tree entries for the stage-0 entries of `index`. Conflict stages (1-3) never go into a
+/// tree; callers that must not drop unmerged paths check `Index::has_conflict` first.
+pub fn index_tree_entries(index: &Index) -> Vec<DatabaseEntry> {
+    index.each_entry()
+        .filter(|entry| entry.stage == 0)
+        .map(|entry| DatabaseEntry::new(
+            entry.get_path().to_string(),
+            entry.get_oid().to_string(),
+            &entry.mode_octal()
+        ))
+        .collect()
+}
+
+/// Builds the tree of `entries` and stores it with all of its subtrees as a single batch,
+/// returning the root tree's ID
+pub fn store_tree(database: &mut Database, entries: &[DatabaseEntry]) -> Result<String, Error> {
+    let mut root = Tree::build(entries.iter())?;
+    
+    database.begin_batch();
+    let stored = root.traverse(|tree| {
+        database.store(tree)?;
+        Ok(())
+    });
+    let flushed = database.flush_batch();
+    stored?;
+    flushed?;
+    
+    root.get_oid().cloned()
+        .ok_or_else(|| Error::Generic("Tree OID not set after storage".into()))
+}
+
+/// Stores the tree of the index's stage-0 entries, returning its ID
+pub fn build_tree_from_index(database: &mut Database, index: &Index) -> Result<String, Error> {
+    store_tree(database, &index_tree_entries(index))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::database::blob::Blob;
+    use std::path::Path;
+    
+    #[test]
+    fn build_tree_from_index_skips_conflict_stages() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut database = Database::new(dir.path().join("objects"));
+        let clean = database.store(&mut Blob::new(b"clean\n".to_vec())).unwrap();
+        let ours = database.store(&mut Blob::new(b"ours\n".to_vec())).unwrap();
+        let theirs = database.store(&mut Blob::new(b"theirs\n".to_vec())).unwrap();
+        
+        let stat = std::fs::metadata(dir.path()).unwrap();
+        let mut index = Index::new(dir.path().join("index"));
+        index.add(Path::new("clean.txt"), &clean, &stat).unwrap();
+        let side = |oid: &str| Some(DatabaseEntry::new("conflict.txt".to_string(), oid.to_string(), "100644"));
+        index.add_conflict(Path::new("conflict.txt"), vec![side(&clean), side(&ours), side(&theirs)]);
+        
+        let tree_oid = build_tree_from_index(&mut database, &index).unwrap();
+        let tree_obj = database.load(&tree_oid).unwrap();
+        let tree = tree_obj.as_any().downcast_ref::<Tree>().unwrap();
+        let names: Vec<&String> = tree.get_entries().keys().collect();
+        assert_eq!(names, vec!["clean.txt"]);
+    }
+}
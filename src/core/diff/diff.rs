@@ -1,7 +1,7 @@
 // src/core/diff/diff.rs - versiune completă îmbunătățită
 use std::fs;
 use std::path::Path;
-use crate::core::workspace::Workspace;
+use crate::core::workspace::{path_key, Workspace};
 use crate::core::database::database::{Database, GitObject};
 use crate::core::database::blob::Blob;
 use crate::errors::error::Error;
@@ -11,9 +11,28 @@ use super::myers;
 /// Dimensiunea maximă a unui fișier pentru diff (pentru a evita probleme de performanță)
 const MAX_DIFF_SIZE: usize = 10 * 1024 * 1024; // 10 MB
 
-/// Împarte un șir în linii
+/// Marcaj adăugat de `split_lines` ultimei linii care nu se termină cu `\n`, astfel încât
+/// aceasta să difere de aceeași linie urmată de newline
+pub const NO_NEWLINE: char = '\0';
+
+/// Împarte un șir în linii; o ultimă linie fără `\n` primește marcajul `NO_NEWLINE`
 pub fn split_lines(content: &str) -> Vec<String> {
-    content.lines().map(|s| s.to_string()).collect()
+    let mut lines: Vec<String> = content.lines().map(|s| s.to_string()).collect();
+    if !content.ends_with('\n') {
+        if let Some(last) = lines.last_mut() {
+            last.push(NO_NEWLINE);
+        }
+    }
+    lines
+}
+
+/// Formatează o linie de diff cu prefixul dat; o linie marcată cu `NO_NEWLINE` este urmată
+/// de `\ No newline at end of file`
+pub fn format_line(prefix: &str, line: &str) -> String {
+    match line.strip_suffix(NO_NEWLINE) {
+        Some(line) => format!("{}{}\n\\ No newline at end of file\n", prefix, line),
+        None => format!("{}{}\n", prefix, line),
+    }
 }
 
 /// Citește un fișier și împarte conținutul său în linii
@@ -96,8 +115,16 @@ pub fn diff_with_database(
     oid: &str,
     context_lines: usize
 ) -> Result<String, Error> {
+    // Fișierele binare sunt recunoscute după primii octeți, fără a le încărca întregi
+    let path_str = path_key(file_path);
+    let working_file = std::fs::File::open(workspace.root_path.join(file_path)).map_err(Error::IO)?;
+    if workspace.is_binary_stream(&path_str, working_file)?
+        || workspace.is_binary_stream(&path_str, database.open_blob(oid)?)? {
+        return Ok(format!("Binary files differ"));
+    }
+    
     // Citește copia de lucru
-    let working_content = workspace.read_file(file_path)?;
+    let working_content = workspace.normalize_eol(&path_str, workspace.read_file(file_path)?);
     
     // Citește versiunea din baza de date
     let blob_obj = database.load(oid)?;
@@ -108,14 +135,6 @@ pub fn diff_with_database(
     
     let db_content = blob.to_bytes();
     
-    // Verifică dacă conținutul este binar
-    let working_is_binary = myers::is_binary_content(&working_content);
-    let db_is_binary = myers::is_binary_content(&db_content);
-    
-    if working_is_binary || db_is_binary {
-        return Ok(format!("Binary files differ"));
-    }
-    
     // Verifică dacă fișierele sunt prea mari pentru diff
     if working_content.len() > MAX_DIFF_SIZE || db_content.len() > MAX_DIFF_SIZE {
         return Ok(format!("File too large for diff: maximum size is {} bytes", MAX_DIFF_SIZE));
@@ -179,8 +198,16 @@ pub fn incremental_diff_with_database(
     oid: &str,
     context_lines: usize
 ) -> Result<String, Error> {
+    // Fișierele binare sunt recunoscute după primii octeți, fără a le încărca întregi
+    let path_str = path_key(file_path);
+    let working_file = std::fs::File::open(workspace.root_path.join(file_path)).map_err(Error::IO)?;
+    if workspace.is_binary_stream(&path_str, working_file)?
+        || workspace.is_binary_stream(&path_str, database.open_blob(oid)?)? {
+        return Ok(format!("Binary files differ"));
+    }
+    
     // Citește copia de lucru
-    let working_content = workspace.read_file(file_path)?;
+    let working_content = workspace.normalize_eol(&path_str, workspace.read_file(file_path)?);
     
     // Citește versiunea din baza de date
     let blob_obj = database.load(oid)?;
@@ -191,11 +218,6 @@ pub fn incremental_diff_with_database(
     
     let db_content = blob.to_bytes();
     
-    // Verifică dacă conținutul este binar
-    if myers::is_binary_content(&working_content) || myers::is_binary_content(&db_content) {
-        return Ok(format!("Binary files differ"));
-    }
-    
     // Pentru fișiere mari, folosim o abordare incrementală, procesând porțiuni din fișier
     if working_content.len() > MAX_DIFF_SIZE || db_content.len() > MAX_DIFF_SIZE {
         // Împarte fișierele în secțiuni de 100kb
@@ -0,0 +1,198 @@
+// src/core/diff/hunks.rs - hunks that can be selected and applied one by one
+use crate::core::color::Color;
+use super::diff::format_line;
+use super::myers::{self, Edit};
+
+/// A single line of a hunk
+#[derive(Debug, Clone, PartialEq)]
+pub enum HunkLine {
+    Context(String),
+    Delete(String),
+    Insert(String),
+}
+
+/// A group of nearby changes between two versions of a file, with surrounding context
+#[derive(Debug, Clone)]
+pub struct Hunk {
+    /// Index of the hunk's first line in the old version
+    pub a_start: usize,
+    /// Index of the hunk's first line in the new version
+    pub b_start: usize,
+    pub lines: Vec<HunkLine>,
+}
+
+impl Hunk {
+    /// Number of old-version lines the hunk covers
+    pub fn a_len(&self) -> usize {
+        self.lines.iter().filter(|line| !matches!(line, HunkLine::Insert(_))).count()
+    }
+
+    /// Number of new-version lines the hunk covers
+    pub fn b_len(&self) -> usize {
+        self.lines.iter().filter(|line| !matches!(line, HunkLine::Delete(_))).count()
+    }
+
+    /// The `@@ -a,b +c,d @@` header line
+    pub fn header(&self) -> String {
+        let a_len = self.a_len();
+        let b_len = self.b_len();
+        // Empty ranges are reported at the line before them, as in unified diffs
+        let a_start = if a_len == 0 { self.a_start } else { self.a_start + 1 };
+        let b_start = if b_len == 0 { self.b_start } else { self.b_start + 1 };
+        format!("@@ -{},{} +{},{} @@", a_start, a_len, b_start, b_len)
+    }
+
+    /// The hunk as colored unified diff text, header included
+    pub fn to_colored_string(&self) -> String {
+        let mut text = Color::cyan(&self.header());
+        text.push('\n');
+
+        for line in &self.lines {
+            let rendered = match line {
+                HunkLine::Context(content) => format_line(" ", content),
+                HunkLine::Delete(content) => format_line("-", content),
+                HunkLine::Insert(content) => format_line("+", content),
+            };
+
+            // A missing-newline marker line stays uncolored
+            for rendered_line in rendered.lines() {
+                match rendered_line.chars().next() {
+                    Some('-') => text.push_str(&Color::red(rendered_line)),
+                    Some('+') => text.push_str(&Color::green(rendered_line)),
+                    _ => text.push_str(rendered_line),
+                }
+                text.push('\n');
+            }
+        }
+
+        text
+    }
+}
+
+/// Splits the changes from `a` to `b` into hunks with `context` lines around each change.
+/// Changes closer than twice the context are merged, so hunks never overlap.
+pub fn build_hunks(a: &[String], b: &[String], context: usize) -> Vec<Hunk> {
+    let edits = myers::diff_lines(a, b);
+
+    // Positions of each edit in both versions; an edit script covers every line in order
+    let mut ops = Vec::with_capacity(edits.len());
+    let (mut a_pos, mut b_pos) = (0, 0);
+    for edit in &edits {
+        ops.push((edit.clone(), a_pos, b_pos));
+        match edit {
+            Edit::Equal(_, _) => {
+                a_pos += 1;
+                b_pos += 1;
+            },
+            Edit::Delete(_) => a_pos += 1,
+            Edit::Insert(_) => b_pos += 1,
+        }
+    }
+
+    let changes: Vec<usize> = ops.iter()
+        .enumerate()
+        .filter(|(_, (edit, _, _))| !matches!(edit, Edit::Equal(_, _)))
+        .map(|(i, _)| i)
+        .collect();
+
+    // Group changes whose context would touch or overlap
+    let mut groups: Vec<(usize, usize)> = Vec::new();
+    for &i in &changes {
+        match groups.last_mut() {
+            Some((_, end)) if i - *end <= 2 * context + 1 => *end = i,
+            _ => groups.push((i, i)),
+        }
+    }
+
+    groups.into_iter()
+        .map(|(first, last)| {
+            let start = first.saturating_sub(context);
+            let end = (last + context + 1).min(ops.len());
+            let (_, a_start, b_start) = ops[start];
+
+            let lines = ops[start..end].iter()
+                .map(|(edit, _, _)| match edit {
+                    Edit::Equal(i, _) => HunkLine::Context(a[*i].clone()),
+                    Edit::Delete(i) => HunkLine::Delete(a[*i].clone()),
+                    Edit::Insert(j) => HunkLine::Insert(b[*j].clone()),
+                })
+                .collect();
+
+            Hunk { a_start, b_start, lines }
+        })
+        .collect()
+}
+
+/// Applies `hunks`, built against `a` and given in order, to `a`. Applying every hunk
+/// yields the new version; applying a subset yields a version with only those changes.
+pub fn apply_hunks(a: &[String], hunks: &[&Hunk]) -> Vec<String> {
+    let mut result = Vec::with_capacity(a.len());
+    let mut pos = 0;
+
+    for hunk in hunks {
+        result.extend_from_slice(&a[pos..hunk.a_start]);
+        pos = hunk.a_start;
+
+        for line in &hunk.lines {
+            match line {
+                HunkLine::Context(_) => {
+                    result.push(a[pos].clone());
+                    pos += 1;
+                },
+                HunkLine::Delete(_) => pos += 1,
+                HunkLine::Insert(content) => result.push(content.clone()),
+            }
+        }
+    }
+
+    result.extend_from_slice(&a[pos..]);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(|line| format!("{}\n", line)).collect()
+    }
+
+    #[test]
+    fn distant_changes_split_into_separate_hunks() {
+        let a = lines("1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12");
+        let b = lines("1\ntwo\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12\n13");
+        let hunks = build_hunks(&a, &b, 3);
+
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(hunks[0].header(), "@@ -1,5 +1,5 @@");
+        assert_eq!(hunks[1].header(), "@@ -10,3 +10,4 @@");
+        assert_eq!(hunks[1].lines.last(), Some(&HunkLine::Insert("13\n".to_string())));
+    }
+
+    #[test]
+    fn nearby_changes_share_a_hunk() {
+        let a = lines("1\n2\n3\n4\n5\n6\n7\n8\n9\n10");
+        let b = lines("1\ntwo\n3\n4\n5\n6\n7\neight\n9\n10");
+        let hunks = build_hunks(&a, &b, 3);
+
+        assert_eq!(hunks.len(), 1);
+        assert_eq!(hunks[0].header(), "@@ -1,10 +1,10 @@");
+    }
+
+    #[test]
+    fn applying_a_subset_of_hunks_keeps_only_those_changes() {
+        let a = lines("1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12\n13\n14\n15\n16\n17\n18");
+        let b = lines("1\ntwo\n3\n4\n5\n6\n7\n8\n10\n11\n12\n13\n14\n15\n16\n17\n18\n19");
+        let hunks = build_hunks(&a, &b, 2);
+        assert_eq!(hunks.len(), 3);
+
+        let all: Vec<&Hunk> = hunks.iter().collect();
+        assert_eq!(apply_hunks(&a, &all), b);
+        assert_eq!(apply_hunks(&a, &[]), a);
+
+        let middle = apply_hunks(&a, &[&hunks[1]]);
+        assert_eq!(middle, lines("1\n2\n3\n4\n5\n6\n7\n8\n10\n11\n12\n13\n14\n15\n16\n17\n18"));
+        let outer = apply_hunks(&a, &[&hunks[0], &hunks[2]]);
+        assert_eq!(outer, lines("1\ntwo\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12\n13\n14\n15\n16\n17\n18\n19"));
+    }
+}
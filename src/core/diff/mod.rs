@@ -1,2 +1,6 @@
 pub mod myers;
-pub mod diff;
\ No newline at end of file
+#[allow(clippy::module_inception)]
+pub mod diff;
+pub mod hunks;
+pub mod rename;
+pub mod patch;
\ No newline at end of file
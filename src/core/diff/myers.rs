@@ -1,5 +1,6 @@
 // src/core/diff/myers.rs - Implementare corectată și simplificată
 use std::cmp;
+use super::diff::format_line;
 
 /// Reprezintă o singură operație de editare într-un diff
 #[derive(Debug, Clone, PartialEq)]
@@ -102,6 +103,29 @@ pub fn diff_lines(a: &[String], b: &[String]) -> Vec<Edit> {
     edits
 }
 
+/// Split a line into words for `--word-diff`. Runs of whitespace are kept as tokens of
+/// their own, so concatenating the tokens gives back the line exactly.
+pub fn split_words(line: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut start = 0;
+    let mut in_space = None;
+    
+    for (idx, c) in line.char_indices() {
+        let is_space = c.is_whitespace();
+        if in_space.is_some_and(|s| s != is_space) {
+            words.push(&line[start..idx]);
+            start = idx;
+        }
+        in_space = Some(is_space);
+    }
+    
+    if start < line.len() {
+        words.push(&line[start..]);
+    }
+    
+    words
+}
+
 /// Determină dacă un fișier este binar (conține caractere nul sau un procent ridicat de caractere non-text)
 pub fn is_binary_content(content: &[u8]) -> bool {
     if content.is_empty() {
@@ -142,7 +166,7 @@ pub fn format_diff(a: &[String], b: &[String], edits: &[Edit], context_lines: us
         result.push_str(" @@\n");
         
         for line in b {
-            result.push_str(&format!("+{}\n", line));
+            result.push_str(&format_line("+", line));
         }
         
         return result;
@@ -153,7 +177,7 @@ pub fn format_diff(a: &[String], b: &[String], edits: &[Edit], context_lines: us
         result.push_str(" +0,0 @@\n");
         
         for line in a {
-            result.push_str(&format!("-{}\n", line));
+            result.push_str(&format_line("-", line));
         }
         
         return result;
@@ -285,7 +309,7 @@ pub fn format_diff(a: &[String], b: &[String], edits: &[Edit], context_lines: us
             if idx - prev_idx <= context_lines {
                 // Linie de context în limita distanței
                 current_hunk.push(idx);
-            } else {
+            } else if idx - prev_idx > 2 * context_lines {
                 // Am depășit distanța de context - finalizăm hunk-ul curent
                 if !current_hunk.is_empty() {
                     // Sortăm și eliminăm duplicatele
@@ -298,6 +322,7 @@ pub fn format_diff(a: &[String], b: &[String], edits: &[Edit], context_lines: us
                 // Resetăm indexul ultimei schimbări
                 prev_change_idx = None;
             }
+            // Altfel așteptăm: contextul următoarei schimbări poate încă uni hunk-urile
         }
     }
     
@@ -317,7 +342,7 @@ pub fn format_diff(a: &[String], b: &[String], edits: &[Edit], context_lines: us
         result.push_str(" @@\n");
         
         for i in 0..std::cmp::min(a.len(), b.len()) {
-            result.push_str(&format!(" {}\n", a[i]));
+            result.push_str(&format_line(" ", &a[i]));
         }
         
         return result;
@@ -353,20 +378,25 @@ pub fn format_diff(a: &[String], b: &[String], edits: &[Edit], context_lines: us
             }
         }
         
-        // Corectăm valorile min/max dacă nu am găsit referințe
-        if a_min == usize::MAX {
-            a_min = 0;
-        }
-        if b_min == usize::MAX {
-            b_min = 0;
-        }
-        
-        let a_count = a_max - a_min;
-        let b_count = b_max - b_min;
+        // Un interval gol (ex. doar inserții la -U0) indică, ca în git, linia după care
+        // are loc schimbarea: numărul de linii din acel fișier dinaintea hunk-ului
+        let first = hunk_indices[0].min(line_model.len());
+        let a_start = if a_min == usize::MAX {
+            line_model[..first].iter().filter(|(_, a_idx, _)| a_idx.is_some()).count()
+        } else {
+            a_min + 1
+        };
+        let b_start = if b_min == usize::MAX {
+            line_model[..first].iter().filter(|(_, _, b_idx)| b_idx.is_some()).count()
+        } else {
+            b_min + 1
+        };
+        let a_count = if a_min == usize::MAX { 0 } else { a_max - a_min };
+        let b_count = if b_min == usize::MAX { 0 } else { b_max - b_min };
         
         // Adăugăm header-ul hunk-ului
         result.push_str(&format!("@@ -{},{} +{},{} @@\n", 
-                          a_min + 1, a_count, b_min + 1, b_count));
+                          a_start, a_count, b_start, b_count));
         
         // Formatăm liniile în hunk
         for &idx in &hunk_indices {
@@ -381,7 +411,7 @@ pub fn format_diff(a: &[String], b: &[String], edits: &[Edit], context_lines: us
                     // Linie egală (prezentă în ambele fișiere)
                     if let Some(a_i) = a_idx {
                         if a_i < a.len() {
-                            result.push_str(&format!(" {}\n", a[a_i]));
+                            result.push_str(&format_line(" ", &a[a_i]));
                         }
                     }
                 },
@@ -389,7 +419,7 @@ pub fn format_diff(a: &[String], b: &[String], edits: &[Edit], context_lines: us
                     // Linie ștearsă (prezentă doar în A)
                     if let Some(a_i) = a_idx {
                         if a_i < a.len() {
-                            result.push_str(&format!("-{}\n", a[a_i]));
+                            result.push_str(&format_line("-", &a[a_i]));
                         }
                     }
                 },
@@ -397,7 +427,7 @@ pub fn format_diff(a: &[String], b: &[String], edits: &[Edit], context_lines: us
                     // Linie inserată (prezentă doar în B)
                     if let Some(b_i) = b_idx {
                         if b_i < b.len() {
-                            result.push_str(&format!("+{}\n", b[b_i]));
+                            result.push_str(&format_line("+", &b[b_i]));
                         }
                     }
                 },
@@ -407,4 +437,38 @@ pub fn format_diff(a: &[String], b: &[String], edits: &[Edit], context_lines: us
     }
     
     result
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Hunk headers for twenty numbered lines with the given (1-based) lines changed
+    fn hunk_headers(changed: &[usize], context: usize) -> Vec<String> {
+        let old: Vec<String> = (1..=20).map(|n| format!("line {}", n)).collect();
+        let mut new = old.clone();
+        for &line in changed {
+            new[line - 1] = format!("changed {}", line);
+        }
+
+        let edits = diff_lines(&old, &new);
+        format_diff(&old, &new, &edits, context)
+            .lines()
+            .filter(|line| line.starts_with("@@"))
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn hunk_ranges_follow_the_context_size() {
+        assert_eq!(hunk_headers(&[10], 0), vec!["@@ -10,1 +10,1 @@"]);
+        assert_eq!(hunk_headers(&[10], 3), vec!["@@ -7,7 +7,7 @@"]);
+        assert_eq!(hunk_headers(&[10], 5), vec!["@@ -5,11 +5,11 @@"]);
+    }
+
+    #[test]
+    fn wider_context_joins_nearby_hunks() {
+        assert_eq!(hunk_headers(&[5, 15], 0), vec!["@@ -5,1 +5,1 @@", "@@ -15,1 +15,1 @@"]);
+        assert_eq!(hunk_headers(&[5, 15], 5), vec!["@@ -1,20 +1,20 @@"]);
+    }
+}
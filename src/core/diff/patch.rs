@@ -0,0 +1,348 @@
+// src/core/diff/patch.rs - reading unified patches back and applying them
+use crate::core::file_mode::FileMode;
+use crate::errors::error::Error;
+use super::diff::NO_NEWLINE;
+use super::hunks::{apply_hunks, Hunk, HunkLine};
+
+/// One file's changes in a patch
+#[derive(Debug, Clone, Default)]
+pub struct FilePatch {
+    /// Path before the change; None for a created file
+    pub old_path: Option<String>,
+    /// Path after the change; None for a deleted file
+    pub new_path: Option<String>,
+    pub old_mode: Option<FileMode>,
+    pub new_mode: Option<FileMode>,
+    pub hunks: Vec<Hunk>,
+    /// The patch only says that the files differ, not how
+    pub binary: bool,
+}
+
+impl FilePatch {
+    /// The path the patch is reported under: the new one, or the old one for a deletion
+    pub fn path(&self) -> &str {
+        self.new_path.as_deref().or(self.old_path.as_deref()).unwrap_or_default()
+    }
+
+    /// Whether the file moves from one path to another
+    pub fn is_rename(&self) -> bool {
+        matches!((&self.old_path, &self.new_path), (Some(old), Some(new)) if old != new)
+    }
+
+    /// Applies the hunks to `content`. Each hunk is tried where its header places it, shifted
+    /// by the offset of the hunks before it, and otherwise at the nearest place its context
+    /// and removed lines match.
+    pub fn apply(&self, content: &[u8]) -> Result<Vec<u8>, Error> {
+        if self.hunks.is_empty() {
+            return Ok(content.to_vec());
+        }
+
+        let lines = content_lines(content);
+        let mut placed = Vec::with_capacity(self.hunks.len());
+        let mut offset = 0isize;
+        let mut min_start = 0;
+
+        for hunk in &self.hunks {
+            let expected = (hunk.a_start as isize + offset).max(0) as usize;
+            let start = find_hunk(&lines, hunk, expected, min_start).ok_or_else(|| {
+                Error::Generic(format!("patch failed: {}:{}", self.path(), hunk.a_start + 1))
+            })?;
+
+            offset = start as isize - hunk.a_start as isize;
+            min_start = start + hunk.a_len();
+            placed.push(Hunk { a_start: start, b_start: hunk.b_start, lines: hunk.lines.clone() });
+        }
+
+        let placed: Vec<&Hunk> = placed.iter().collect();
+        Ok(join_lines(&apply_hunks(&lines, &placed)))
+    }
+}
+
+/// Splits content into lines like `diff::split_lines`, but keeps carriage returns, so that
+/// patches carry files' line endings unchanged
+pub fn content_lines(content: &[u8]) -> Vec<String> {
+    let text = String::from_utf8_lossy(content);
+    text.split_inclusive('\n')
+        .map(|line| match line.strip_suffix('\n') {
+            Some(line) => line.to_string(),
+            None => format!("{}{}", line, NO_NEWLINE),
+        })
+        .collect()
+}
+
+/// Joins lines from `content_lines` back into content
+pub fn join_lines(lines: &[String]) -> Vec<u8> {
+    let mut content = String::new();
+    for line in lines {
+        match line.strip_suffix(NO_NEWLINE) {
+            Some(line) => content.push_str(line),
+            None => {
+                content.push_str(line);
+                content.push('\n');
+            }
+        }
+    }
+    content.into_bytes()
+}
+
+/// Parses the files of a patch as written by `ash diff` or `git diff`, or a plain unified
+/// diff with `---`/`+++` headers. Text before the first file (a commit message, say)
+/// is skipped.
+pub fn parse_patch(text: &str) -> Result<Vec<FilePatch>, Error> {
+    let lines: Vec<&str> = text.split_inclusive('\n')
+        .map(|line| line.strip_suffix('\n').unwrap_or(line))
+        .collect();
+
+    let mut patches = Vec::new();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let mut patch = if let Some(names) = file_header(lines[i]) {
+            let (old, new) = parse_git_names(names)
+                .ok_or_else(|| Error::Generic(format!("corrupt patch at line {}", i + 1)))?;
+            i += 1;
+            FilePatch { old_path: Some(old), new_path: Some(new), ..Default::default() }
+        } else if lines[i].starts_with("--- ") && lines.get(i + 1).is_some_and(|next| next.starts_with("+++ ")) {
+            FilePatch::default()
+        } else {
+            i += 1;
+            continue;
+        };
+
+        // Extended headers, up to the first hunk or the next file
+        while i < lines.len() && file_header(lines[i]).is_none() && !lines[i].starts_with("@@") {
+            let line = lines[i];
+            if let Some(mode) = line.strip_prefix("new file mode ") {
+                patch.old_path = None;
+                patch.new_mode = Some(FileMode::parse(mode.trim()));
+            } else if let Some(mode) = line.strip_prefix("deleted file mode ") {
+                patch.new_path = None;
+                patch.old_mode = Some(FileMode::parse(mode.trim()));
+            } else if let Some(mode) = line.strip_prefix("old mode ") {
+                patch.old_mode = Some(FileMode::parse(mode.trim()));
+            } else if let Some(mode) = line.strip_prefix("new mode ") {
+                patch.new_mode = Some(FileMode::parse(mode.trim()));
+            } else if let Some(path) = line.strip_prefix("rename from ") {
+                patch.old_path = Some(path.to_string());
+            } else if let Some(path) = line.strip_prefix("rename to ") {
+                patch.new_path = Some(path.to_string());
+            } else if let Some(name) = line.strip_prefix("--- ") {
+                patch.old_path = patch_name(name, "a/");
+            } else if let Some(name) = line.strip_prefix("+++ ") {
+                patch.new_path = patch_name(name, "b/");
+            } else if line.starts_with("Binary files ") || line == "GIT binary patch" {
+                patch.binary = true;
+            } else if patch.old_path.is_none() && patch.new_path.is_none() {
+                // A plain diff's headers end at the first line that is not one
+                break;
+            }
+            i += 1;
+        }
+
+        while i < lines.len() && lines[i].starts_with("@@") {
+            let (hunk, next) = parse_hunk(&lines, i)?;
+            patch.hunks.push(hunk);
+            i = next;
+        }
+
+        if patch.old_path.is_none() && patch.new_path.is_none() {
+            return Err(Error::Generic(format!("patch with no file names near line {}", i)));
+        }
+        patches.push(patch);
+    }
+
+    if patches.is_empty() {
+        return Err(Error::Generic("No valid patches in input".to_string()));
+    }
+
+    Ok(patches)
+}
+
+/// The names after `diff --git` or `diff --ash` at the start of a file's patch
+fn file_header(line: &str) -> Option<&str> {
+    line.strip_prefix("diff --git ").or_else(|| line.strip_prefix("diff --ash "))
+}
+
+/// The two paths of a `diff --git a/<old> b/<new>` line
+fn parse_git_names(names: &str) -> Option<(String, String)> {
+    let rest = names.strip_prefix("a/")?;
+    // Unrenamed paths are the same on both sides, which also settles paths with spaces
+    if rest.len() >= 3 && rest.len() % 2 == 1 {
+        let half = (rest.len() - 3) / 2;
+        if rest.get(half..half + 3) == Some(" b/") && rest.get(..half) == rest.get(half + 3..) {
+            return Some((rest[..half].to_string(), rest[half + 3..].to_string()));
+        }
+    }
+    let (old, new) = rest.split_once(" b/")?;
+    Some((old.to_string(), new.to_string()))
+}
+
+/// The path named on a `---`/`+++` line, without its `a/`/`b/` prefix; None for /dev/null
+fn patch_name(name: &str, prefix: &str) -> Option<String> {
+    // A timestamp may follow the name after a tab
+    let name = name.split('\t').next().unwrap_or(name).trim_end();
+    if name == "/dev/null" {
+        return None;
+    }
+    Some(name.strip_prefix(prefix).unwrap_or(name).to_string())
+}
+
+/// Parses the hunk whose `@@ -a,b +c,d @@` header is at `lines[start]`, returning it and the
+/// index of the line after it
+fn parse_hunk(lines: &[&str], start: usize) -> Result<(Hunk, usize), Error> {
+    let corrupt = |line: usize| Error::Generic(format!("corrupt patch at line {}", line + 1));
+
+    let ranges = lines[start].strip_prefix("@@ -")
+        .and_then(|rest| rest.split_once(" @@"))
+        .map(|(ranges, _)| ranges)
+        .ok_or_else(|| corrupt(start))?;
+    let (old_range, new_range) = ranges.split_once(" +").ok_or_else(|| corrupt(start))?;
+    let (a_line, a_len) = parse_range(old_range).ok_or_else(|| corrupt(start))?;
+    let (b_line, b_len) = parse_range(new_range).ok_or_else(|| corrupt(start))?;
+
+    // An empty range names the line before it; otherwise lines count from one
+    let a_start = if a_len == 0 { a_line } else { a_line.saturating_sub(1) };
+    let b_start = if b_len == 0 { b_line } else { b_line.saturating_sub(1) };
+
+    let mut hunk_lines = Vec::new();
+    let (mut a_seen, mut b_seen) = (0, 0);
+    let mut i = start + 1;
+
+    while a_seen < a_len || b_seen < b_len {
+        let line = *lines.get(i).ok_or_else(|| corrupt(i))?;
+        let (kind, content) = match line.chars().next() {
+            // Some tools strip the space from empty context lines
+            None => (' ', ""),
+            Some(kind) => (kind, &line[kind.len_utf8()..]),
+        };
+
+        match kind {
+            ' ' => {
+                hunk_lines.push(HunkLine::Context(content.to_string()));
+                a_seen += 1;
+                b_seen += 1;
+            },
+            '-' => {
+                hunk_lines.push(HunkLine::Delete(content.to_string()));
+                a_seen += 1;
+            },
+            '+' => {
+                hunk_lines.push(HunkLine::Insert(content.to_string()));
+                b_seen += 1;
+            },
+            '\\' => mark_no_newline(&mut hunk_lines),
+            _ => return Err(corrupt(i)),
+        }
+        i += 1;
+    }
+
+    // The marker for the hunk's last line comes after the counted lines
+    if lines.get(i).is_some_and(|line| line.starts_with('\\')) {
+        mark_no_newline(&mut hunk_lines);
+        i += 1;
+    }
+
+    Ok((Hunk { a_start, b_start, lines: hunk_lines }, i))
+}
+
+/// Parses `<line>[,<count>]`; the count defaults to one
+fn parse_range(range: &str) -> Option<(usize, usize)> {
+    match range.split_once(',') {
+        Some((line, count)) => Some((line.parse().ok()?, count.parse().ok()?)),
+        None => Some((range.parse().ok()?, 1)),
+    }
+}
+
+/// Records that the last hunk line has no newline after it
+fn mark_no_newline(lines: &mut [HunkLine]) {
+    if let Some(HunkLine::Context(content) | HunkLine::Delete(content) | HunkLine::Insert(content)) = lines.last_mut() {
+        content.push(NO_NEWLINE);
+    }
+}
+
+/// Where `hunk` applies to `lines`: the matching position nearest `expected`, at or after
+/// `min_start` so that hunks stay in order
+fn find_hunk(lines: &[String], hunk: &Hunk, expected: usize, min_start: usize) -> Option<usize> {
+    let old: Vec<&String> = hunk.lines.iter()
+        .filter_map(|line| match line {
+            HunkLine::Context(content) | HunkLine::Delete(content) => Some(content),
+            HunkLine::Insert(_) => None,
+        })
+        .collect();
+
+    let matches_at = |start: usize| {
+        start >= min_start
+            && start + old.len() <= lines.len()
+            && old.iter().zip(&lines[start..]).all(|(wanted, actual)| *wanted == actual)
+    };
+
+    (0..=lines.len().max(expected)).find_map(|distance| {
+        let before = expected.checked_sub(distance).filter(|&start| matches_at(start));
+        before.or_else(|| Some(expected + distance).filter(|&start| matches_at(start)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RENAME: &str = "diff --git a/old.txt b/new.txt\n\
+                          similarity index 80%\n\
+                          rename from old.txt\n\
+                          rename to new.txt\n\
+                          index 1111111..2222222 100644\n\
+                          --- a/old.txt\n\
+                          +++ b/new.txt\n\
+                          @@ -1,3 +1,3 @@\n \
+                          one\n\
+                          -two\n\
+                          +TWO\n \
+                          three\n";
+
+    #[test]
+    fn parses_a_rename_with_changes() {
+        let patches = parse_patch(RENAME).unwrap();
+
+        assert_eq!(patches.len(), 1);
+        assert_eq!(patches[0].old_path.as_deref(), Some("old.txt"));
+        assert_eq!(patches[0].new_path.as_deref(), Some("new.txt"));
+        assert!(patches[0].is_rename());
+        assert_eq!(patches[0].apply(b"one\ntwo\nthree\n").unwrap(), b"one\nTWO\nthree\n");
+    }
+
+    #[test]
+    fn applies_hunks_that_moved() {
+        let patches = parse_patch(RENAME).unwrap();
+
+        assert_eq!(patches[0].apply(b"zero\none\ntwo\nthree\n").unwrap(), b"zero\none\nTWO\nthree\n");
+        assert!(patches[0].apply(b"one\n2\nthree\n").is_err());
+    }
+
+    #[test]
+    fn parses_created_and_deleted_files_without_a_trailing_newline() {
+        let patch = "diff --git a/gone.txt b/gone.txt\n\
+                     deleted file mode 100644\n\
+                     index 1111111..0000000\n\
+                     --- a/gone.txt\n\
+                     +++ /dev/null\n\
+                     @@ -1 +0,0 @@\n\
+                     -bye\n\
+                     diff --git a/made.txt b/made.txt\n\
+                     new file mode 100755\n\
+                     index 0000000..3333333\n\
+                     --- /dev/null\n\
+                     +++ b/made.txt\n\
+                     @@ -0,0 +1,2 @@\n\
+                     +#!/bin/sh\n\
+                     +exit 0\n\
+                     \\ No newline at end of file\n";
+
+        let patches = parse_patch(patch).unwrap();
+
+        assert_eq!((patches[0].old_path.as_deref(), patches[0].new_path.as_deref()), (Some("gone.txt"), None));
+        assert_eq!(patches[0].apply(b"bye\n").unwrap(), b"");
+        assert_eq!((patches[1].old_path.as_deref(), patches[1].new_path.as_deref()), (None, Some("made.txt")));
+        assert_eq!(patches[1].new_mode, Some(FileMode::EXECUTABLE));
+        assert_eq!(patches[1].apply(b"").unwrap(), b"#!/bin/sh\nexit 0");
+    }
+}
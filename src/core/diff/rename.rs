@@ -0,0 +1,228 @@
+/// Rename detection between the files deleted and the files added by a change.
+/// Files with identical content are paired first, which is cheap. Pairing the rest by
+/// similarity compares every deleted file with every added one, so it is skipped once the
+/// change is larger than the configured rename limit.
+use std::collections::HashMap;
+use crate::core::config::Config;
+use crate::core::database::database::hash_blob_data;
+use crate::errors::error::Error;
+
+/// Default for `diff.renameLimit`
+pub const DEFAULT_DIFF_RENAME_LIMIT: usize = 1000;
+/// Default for `merge.renameLimit`
+pub const DEFAULT_MERGE_RENAME_LIMIT: usize = 7000;
+/// Percentage of content a file must keep to be taken as renamed
+pub const MIN_SIMILARITY: usize = 50;
+
+/// A deleted or added file considered for rename detection
+#[derive(Debug, Clone)]
+pub struct RenameCandidate {
+    pub path: String,
+    pub oid: String,
+}
+
+impl RenameCandidate {
+    pub fn new(path: &str, oid: &str) -> Self {
+        RenameCandidate { path: path.to_string(), oid: oid.to_string() }
+    }
+}
+
+/// A file deleted at `old_path` and added at `new_path`, keeping `score` percent of its content
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rename {
+    pub old_path: String,
+    pub new_path: String,
+    pub score: usize,
+}
+
+/// The renames found in a change
+#[derive(Debug, Default)]
+pub struct Renames {
+    pub pairs: Vec<Rename>,
+    /// When similarity detection was skipped, the limit that would have allowed it
+    pub skipped: Option<usize>,
+}
+
+/// The warning shown when similarity detection was skipped, naming the setting
+/// (`merge.renameLimit` or `diff.renameLimit`) and the limit that would allow it
+pub fn limit_warning(setting: &str, needed: usize) -> String {
+    format!(
+        "warning: exhaustive rename detection was skipped due to too many files.\n\
+         warning: you may want to set your {} variable to at least {} and retry the command.",
+        setting, needed
+    )
+}
+
+/// The rename limit for `command` ("diff" or "merge"): `<command>.renameLimit`, then
+/// `diff.renameLimit`, then the command's default. Zero means no limit.
+pub fn rename_limit(config: &Config, command: &str) -> usize {
+    let default = if command == "merge" { DEFAULT_MERGE_RENAME_LIMIT } else { DEFAULT_DIFF_RENAME_LIMIT };
+    config.get(command, "renameLimit")
+        .or_else(|| config.get("diff", "renameLimit"))
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(default)
+}
+
+/// Pairs `deleted` files with `added` ones. Identical content is always paired; the rest are
+/// compared with the contents given by `load` unless there are more than `limit` files on
+/// either side of the comparison, in which case `skipped` records the limit needed.
+/// Empty files are never taken as renamed.
+pub fn detect_renames<F>(
+    deleted: &[RenameCandidate],
+    added: &[RenameCandidate],
+    limit: usize,
+    mut load: F,
+) -> Result<Renames, Error>
+where
+    F: FnMut(&str) -> Result<Vec<u8>, Error>,
+{
+    let mut renames = Renames::default();
+
+    let empty_oid = hash_blob_data(b"");
+    let mut deleted: Vec<&RenameCandidate> = deleted.iter().filter(|c| c.oid != empty_oid).collect();
+    let mut added: Vec<&RenameCandidate> = added.iter().filter(|c| c.oid != empty_oid).collect();
+    deleted.sort_by(|a, b| a.path.cmp(&b.path));
+    added.sort_by(|a, b| a.path.cmp(&b.path));
+
+    // Exact renames: each added file takes the first deleted file with the same object ID
+    let mut remaining_added = Vec::new();
+    for candidate in added {
+        match deleted.iter().position(|d| d.oid == candidate.oid) {
+            Some(i) => {
+                let source = deleted.remove(i);
+                renames.pairs.push(Rename {
+                    old_path: source.path.clone(),
+                    new_path: candidate.path.clone(),
+                    score: 100,
+                });
+            }
+            None => remaining_added.push(candidate),
+        }
+    }
+    let added = remaining_added;
+
+    if deleted.is_empty() || added.is_empty() {
+        return Ok(renames);
+    }
+
+    if limit > 0 && deleted.len() * added.len() > limit * limit {
+        renames.skipped = Some(deleted.len().max(added.len()));
+        return Ok(renames);
+    }
+
+    let deleted_contents = deleted.iter().map(|c| load(&c.oid)).collect::<Result<Vec<_>, Error>>()?;
+    let added_contents = added.iter().map(|c| load(&c.oid)).collect::<Result<Vec<_>, Error>>()?;
+
+    let mut scores = Vec::new();
+    for (i, old) in deleted_contents.iter().enumerate() {
+        for (j, new) in added_contents.iter().enumerate() {
+            let score = similarity(old, new);
+            if score >= MIN_SIMILARITY {
+                scores.push((score, i, j));
+            }
+        }
+    }
+
+    // Best matches first; ties keep path order so the result is stable
+    scores.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)).then(a.2.cmp(&b.2)));
+
+    let mut used_deleted = vec![false; deleted.len()];
+    let mut used_added = vec![false; added.len()];
+    for (score, i, j) in scores {
+        if used_deleted[i] || used_added[j] {
+            continue;
+        }
+        used_deleted[i] = true;
+        used_added[j] = true;
+        renames.pairs.push(Rename {
+            old_path: deleted[i].path.clone(),
+            new_path: added[j].path.clone(),
+            score,
+        });
+    }
+
+    Ok(renames)
+}
+
+/// Percentage of the larger content made up of lines the two contents share
+pub fn similarity(old: &[u8], new: &[u8]) -> usize {
+    let larger = old.len().max(new.len());
+    if larger == 0 {
+        return 100;
+    }
+
+    let mut counts: HashMap<&[u8], usize> = HashMap::new();
+    for line in old.split_inclusive(|&byte| byte == b'\n') {
+        *counts.entry(line).or_default() += 1;
+    }
+
+    let mut common = 0;
+    for line in new.split_inclusive(|&byte| byte == b'\n') {
+        if let Some(count) = counts.get_mut(line) {
+            if *count > 0 {
+                *count -= 1;
+                common += line.len();
+            }
+        }
+    }
+
+    common * 100 / larger
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contents(oid: &str) -> Result<Vec<u8>, Error> {
+        Ok(match oid {
+            "a" => b"one\ntwo\nthree\nfour\n".to_vec(),
+            "b" => b"one\ntwo\nthree\nFOUR\n".to_vec(),
+            "c" => b"something\nelse\nentirely\n".to_vec(),
+            _ => Vec::new(),
+        })
+    }
+
+    #[test]
+    fn pairs_identical_and_similar_files() {
+        let deleted = [RenameCandidate::new("old.txt", "a"), RenameCandidate::new("moved.txt", "c")];
+        let added = [RenameCandidate::new("new.txt", "b"), RenameCandidate::new("dir/moved.txt", "c")];
+
+        let renames = detect_renames(&deleted, &added, 10, contents).unwrap();
+
+        assert_eq!(renames.pairs, vec![
+            Rename { old_path: "moved.txt".into(), new_path: "dir/moved.txt".into(), score: 100 },
+            Rename { old_path: "old.txt".into(), new_path: "new.txt".into(), score: 73 },
+        ]);
+        assert!(renames.skipped.is_none());
+    }
+
+    #[test]
+    fn skips_similarity_past_the_limit_but_keeps_exact_renames() {
+        let deleted: Vec<_> = (0..3).map(|i| RenameCandidate::new(&format!("d{}", i), "a"))
+            .chain([RenameCandidate::new("x", "c")])
+            .collect();
+        let added: Vec<_> = (0..3).map(|i| RenameCandidate::new(&format!("n{}", i), "b"))
+            .chain([RenameCandidate::new("y", "c")])
+            .collect();
+
+        let renames = detect_renames(&deleted, &added, 2, |_| panic!("contents loaded past the limit")).unwrap();
+
+        assert_eq!(renames.pairs.len(), 1);
+        assert_eq!(renames.skipped, Some(3));
+        assert!(limit_warning("merge.renameLimit", 3).contains("merge.renameLimit variable to at least 3"));
+    }
+
+    #[test]
+    fn reads_the_limit_from_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::load(dir.path());
+        assert_eq!(rename_limit(&config, "merge"), DEFAULT_MERGE_RENAME_LIMIT);
+
+        config.set("diff", "renamelimit", "20");
+        assert_eq!(rename_limit(&config, "merge"), 20);
+        assert_eq!(rename_limit(&config, "diff"), 20);
+
+        config.set("merge", "renamelimit", "5");
+        assert_eq!(rename_limit(&config, "merge"), 5);
+    }
+}
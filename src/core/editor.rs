@@ -1,12 +1,16 @@
 use std::fs;
 use std::fs::{File, OpenOptions};
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
+use crate::core::config::Config;
 use crate::errors::error::Error;
 
 /// Default editor used for commit messages and other editing operations.
+#[cfg(windows)]
+const DEFAULT_EDITOR: &str = "notepad";
+#[cfg(not(windows))]
 const DEFAULT_EDITOR: &str = "vi";
 
 /// Handles file editing operations for commit messages and notes in AsheraFlow.
@@ -15,7 +19,8 @@ pub struct Editor {
     path: PathBuf,
     command: String,
     closed: bool,
-    file: File,
+    /// Open until the editor is launched
+    file: Option<File>,
 }
 
 impl Editor {
@@ -32,10 +37,18 @@ impl Editor {
             path,
             command: command.unwrap_or_else(|| DEFAULT_EDITOR.to_owned()),
             closed: false,
-            file,
+            file: Some(file),
         })
     }
 
+    /// The editor to launch: `core.editor`, then $ASH_EDITOR, $GIT_EDITOR, $VISUAL and
+    /// $EDITOR, then the platform default
+    pub fn resolve_command(config: &Config) -> String {
+        config.get_or_env("core", "editor", &["ASH_EDITOR", "GIT_EDITOR", "VISUAL", "EDITOR"])
+            .filter(|command| !command.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_EDITOR.to_owned())
+    }
+
     /// Edits a file using the specified command and callback function.
     pub fn edit<F>(path: PathBuf, command: Option<String>, f: F) -> Result<Option<String>, Error>
     where
@@ -43,17 +56,18 @@ impl Editor {
     {
         let mut editor = Editor::new(path, command)?;
         f(&mut editor)?;
-        editor.edit_file()
+        editor.launch()
     }
 
     /// Writes a string to the file being edited.
     pub fn write(&mut self, string: &str) -> Result<(), Error> {
-        if self.closed {
-            return Ok(());
-        }
-        self.file.write_all(string.as_bytes())
+        let file = match self.file.as_mut() {
+            Some(file) if !self.closed => file,
+            _ => return Ok(()),
+        };
+        file.write_all(string.as_bytes())
             .map_err(|e| Error::Generic(format!("Failed to write to file: {}", e)))?;
-        self.file.write_all(b"\n")
+        file.write_all(b"\n")
             .map_err(|e| Error::Generic(format!("Failed to write newline to file: {}", e)))?;
 
         Ok(())
@@ -61,11 +75,12 @@ impl Editor {
 
     /// Writes a note (comment) to the file being edited.
     pub fn note(&mut self, string: &str) -> Result<(), Error> {
-        if self.closed {
-            return Ok(());
-        }
+        let file = match self.file.as_mut() {
+            Some(file) if !self.closed => file,
+            _ => return Ok(()),
+        };
         for line in string.lines() {
-            write!(self.file, "# {}\n", line)
+            writeln!(file, "# {}", line)
                 .map_err(|e| Error::Generic(format!("Failed to write note to file: {}", e)))?;
         }
 
@@ -76,16 +91,26 @@ impl Editor {
         self.closed = true;
     }
 
-    pub fn edit_file(&mut self) -> Result<Option<String>, Error> {
+    /// Runs the editor on the written file; returns the message without comment
+    /// lines, or None when the editing was closed or left no message
+    pub fn launch(&mut self) -> Result<Option<String>, Error> {
         // Close the file before launching the editor
-        drop(std::mem::replace(&mut self.file, unsafe { std::mem::zeroed() }));
+        self.file.take();
 
         if self.closed {
             return Ok(None);
         }
 
+        let content = Self::edit_file(&self.path, &self.command)?;
+        Ok(self.remove_notes(content))
+    }
+
+    /// Opens `path` in `program` and returns the file's content once the editor exits.
+    /// `program` may carry arguments (e.g. `code --wait`). An editor that exits
+    /// unsuccessfully is an error.
+    pub fn edit_file(path: &Path, program: &str) -> Result<String, Error> {
         // Split the command for safer execution
-        let parts: Vec<&str> = self.command.split_whitespace().collect();
+        let parts: Vec<&str> = program.split_whitespace().collect();
         if parts.is_empty() {
             return Err(Error::Generic("Empty editor command".to_string()));
         }
@@ -94,20 +119,17 @@ impl Editor {
         for part in parts.iter().skip(1) {
             cmd.arg(part);
         }
-        cmd.arg(&self.path);
+        cmd.arg(path);
 
         let status = cmd.status()
-            .map_err(|e| Error::Generic(format!("Failed to run editor: {}", e)))?;
+            .map_err(|e| Error::Generic(format!("Failed to run editor '{}': {}", program, e)))?;
 
         if !status.success() {
-            return Err(Error::Generic(format!("Editor exited with status: {}", status)));
+            return Err(Error::Generic(format!("There was a problem with the editor '{}' ({})", program, status)));
         }
 
-        // Read the file and remove comments
-        let content = fs::read_to_string(&self.path)
-            .map_err(|e| Error::Generic(format!("Failed to read edited file: {}", e)))?;
-
-        Ok(self.remove_notes(content))
+        fs::read_to_string(path)
+            .map_err(|e| Error::Generic(format!("Failed to read edited file: {}", e)))
     }
 
     fn remove_notes(&self, content: String) -> Option<String> {
@@ -123,3 +145,46 @@ impl Editor {
         }
     }
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    // An editor command running a shell script with the file to edit as `$1`
+    fn fake_editor(dir: &Path, script: &str) -> String {
+        let path = dir.join("editor.sh");
+        fs::write(&path, script).unwrap();
+        format!("sh {}", path.display())
+    }
+
+    #[test]
+    fn edit_returns_what_the_editor_wrote_without_notes() {
+        let dir = tempfile::tempdir().unwrap();
+        let editor = fake_editor(dir.path(), "printf 'Edited\\n' >> \"$1\"\n");
+
+        let message = Editor::edit(dir.path().join("MSG"), Some(editor), |editor| {
+            editor.write("Initial")?;
+            editor.note("a note\nanother note")
+        }).unwrap();
+
+        assert_eq!(message.as_deref(), Some("Initial\nEdited\n"));
+    }
+
+    #[test]
+    fn failing_editor_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let editor = fake_editor(dir.path(), "printf 'Edited\\n' >> \"$1\"\nexit 1\n");
+
+        let result = Editor::edit(dir.path().join("MSG"), Some(editor), |editor| editor.write("Initial"));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn core_editor_is_preferred() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::load(dir.path());
+        config.set("core", "editor", "my-editor --wait");
+
+        assert_eq!(Editor::resolve_command(&config), "my-editor --wait");
+    }
+}
@@ -0,0 +1,179 @@
+//! Lane bookkeeping for `log --graph`: draws the `*`, `|`, `\`, `/` and `_` columns to the
+//! left of each commit as history is walked in topological order.
+
+/// Rows drawn for one commit
+pub struct GraphRows {
+    /// Prefix of the commit's first line, with `*` in the commit's lane
+    pub commit: String,
+    /// Prefix of the commit's remaining lines
+    pub padding: String,
+    /// Lines connecting the commit to its parents' lanes, printed after the commit
+    pub transitions: Vec<String>,
+}
+
+/// The commits each lane is waiting for, left to right
+#[derive(Default)]
+pub struct Graph {
+    columns: Vec<String>,
+}
+
+impl Graph {
+    pub fn new() -> Self {
+        Graph { columns: Vec::new() }
+    }
+
+    /// Places `oid` in its lane and moves that lane on to `parents`. Commits must be fed
+    /// children first; each lane then waits for exactly one commit, so lanes that reach the
+    /// same parent are folded together rather than crossing.
+    pub fn next(&mut self, oid: &str, parents: &[String]) -> GraphRows {
+        let idx = match self.columns.iter().position(|column| column == oid) {
+            Some(idx) => idx,
+            None => {
+                // A new tip starts its own lane on the right
+                self.columns.push(oid.to_string());
+                self.columns.len() - 1
+            }
+        };
+
+        let width = self.columns.len();
+        let mut commit = Self::lanes(width);
+        commit[2 * idx] = '*';
+        let mut padding = Self::lanes(width);
+        if parents.is_empty() {
+            padding[2 * idx] = ' ';
+        }
+
+        let mut transitions = Vec::new();
+
+        if parents.is_empty() {
+            // The lane ends here; lanes to its right shift left
+            self.columns.remove(idx);
+            if idx < self.columns.len() {
+                let mut row = Self::blank(width);
+                for i in 0..idx {
+                    row[2 * i] = '|';
+                }
+                for j in idx + 1..width {
+                    row[2 * j - 1] = '/';
+                }
+                transitions.push(Self::render(&row));
+            }
+        } else {
+            self.columns[idx] = parents[0].clone();
+
+            // Every further parent branches off just right of the commit's lane; the last
+            // one goes first so the lanes end up in parent order
+            for parent in parents[1..].iter().rev() {
+                let width = self.columns.len();
+                let mut row = Self::blank(width + 1);
+                for i in 0..=idx {
+                    row[2 * i] = '|';
+                }
+                row[2 * idx + 1] = '\\';
+                for j in idx + 1..width {
+                    row[2 * j + 1] = '\\';
+                }
+                transitions.push(Self::render(&row));
+                self.columns.insert(idx + 1, parent.clone());
+            }
+        }
+
+        // Fold lanes waiting for the same commit into the leftmost one
+        while let Some((m, k)) = self.first_duplicate() {
+            let width = self.columns.len();
+            let mut row = Self::blank(width);
+            for i in 0..k {
+                row[2 * i] = '|';
+            }
+            for i in m..k - 1 {
+                row[2 * i + 1] = '_';
+            }
+            row[2 * k - 1] = '/';
+            for j in k + 1..width {
+                row[2 * j - 1] = '/';
+            }
+            transitions.push(Self::render(&row));
+            self.columns.remove(k);
+        }
+
+        GraphRows {
+            commit: format!("{} ", Self::render(&commit)),
+            padding: format!("{} ", Self::render(&padding)),
+            transitions,
+        }
+    }
+
+    // The leftmost lane `m` and the first lane `k` to its right waiting for the same commit
+    fn first_duplicate(&self) -> Option<(usize, usize)> {
+        for k in 1..self.columns.len() {
+            if let Some(m) = self.columns[..k].iter().position(|column| *column == self.columns[k]) {
+                return Some((m, k));
+            }
+        }
+        None
+    }
+
+    fn lanes(width: usize) -> Vec<char> {
+        let mut row = Self::blank(width);
+        for i in 0..width {
+            row[2 * i] = '|';
+        }
+        row
+    }
+
+    fn blank(width: usize) -> Vec<char> {
+        vec![' '; 2 * width]
+    }
+
+    fn render(row: &[char]) -> String {
+        row.iter().collect::<String>().trim_end().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Feeds `history` (children first) through a graph and returns the drawing, one
+    // string per line, with each commit's name after its `*`
+    fn draw(history: &[(&str, &[&str])]) -> Vec<String> {
+        let mut graph = Graph::new();
+        let mut lines = Vec::new();
+        for (oid, parents) in history {
+            let parents: Vec<String> = parents.iter().map(|parent| parent.to_string()).collect();
+            let rows = graph.next(oid, &parents);
+            lines.push(format!("{}{}", rows.commit, oid));
+            lines.extend(rows.transitions);
+        }
+        lines
+    }
+
+    #[test]
+    fn linear_history_stays_in_one_lane() {
+        let lines = draw(&[("c", &["b"]), ("b", &["a"]), ("a", &[])]);
+        assert_eq!(lines, vec!["* c", "* b", "* a"]);
+    }
+
+    #[test]
+    fn merge_branches_off_and_folds_back() {
+        let lines = draw(&[
+            ("m", &["a2", "b1"]),
+            ("a2", &["a1"]),
+            ("b1", &["a1"]),
+            ("a1", &[]),
+        ]);
+        assert_eq!(lines, vec!["* m", "|\\", "* | a2", "| * b1", "|/", "* a1"]);
+    }
+
+    #[test]
+    fn octopus_merge_opens_a_lane_per_parent() {
+        let lines = draw(&[
+            ("o", &["a", "b", "c"]),
+            ("a", &["r"]),
+            ("b", &["r"]),
+            ("c", &["r"]),
+            ("r", &[]),
+        ]);
+        assert_eq!(lines, vec!["* o", "|\\", "|\\ \\", "* | | a", "| * | b", "|/ /", "| * c", "|/", "* r"]);
+    }
+}
@@ -3,6 +3,7 @@ use crate::errors::error::Error;
 
 pub const CHECKSUM_SIZE: usize = 20;
 
+#[derive(Default)]
 pub struct Checksum {
     digest: Sha1,
 }
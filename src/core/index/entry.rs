@@ -4,7 +4,12 @@ use std::path::Path;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::core::file_mode::FileMode;
+use crate::core::workspace::path_key;
 const MAX_PATH_SIZE: u16 = 0xfff;
+// Set in the flags when an extended flags field follows (index version 3)
+const EXTENDED_FLAG: u16 = 0x4000;
+// Bit in the extended flags marking a path that is not materialized in the workspace
+const SKIP_WORKTREE_FLAG: u16 = 0x4000;
 
 #[derive(Debug, Clone)]
 pub struct Entry {
@@ -24,11 +29,13 @@ pub struct Entry {
     pub path: String,
     // Add this field:
     pub stage: u8,  // 0 = normal, 1 = base, 2 = ours, 3 = theirs
+    // Path left out of the workspace (sparse checkout); it is not compared with disk
+    pub skip_worktree: bool,
 }
 
 impl Entry {
     pub fn create(pathname: &Path, oid: &str, stat: &fs::Metadata) -> Self {
-        let path = pathname.to_string_lossy().to_string();
+        let path = path_key(pathname);
         
         // Determine if file is executable (mode 755) or regular (mode 644)
         let mode = FileMode::from_metadata(stat);
@@ -60,6 +67,7 @@ impl Entry {
             flags,
             path,
             stage: 0,  // Default stage is 0 (normal entry)
+            skip_worktree: false,
         }
     }
     pub fn mode_octal(&self) -> String {
@@ -119,6 +127,16 @@ impl Entry {
         &self.path
     }
 
+    /// Whether the path is skipped when comparing the index with the workspace
+    pub fn is_skip_worktree(&self) -> bool {
+        self.skip_worktree
+    }
+
+    /// Whether the entry needs the extended flags of index version 3
+    pub fn is_extended(&self) -> bool {
+        self.skip_worktree
+    }
+
     // Setteri pentru proprietățile care ar putea necesita actualizare
     pub fn set_ctime(&mut self, ctime: u32) {
         self.ctime = ctime;
@@ -173,9 +191,17 @@ impl Entry {
         
         // Add flags with stage bits
         // Stage is stored in the high bits of the flags field
-        let flags_with_stage = self.flags | ((self.stage as u16) << 12);
+        let mut flags_with_stage = self.flags | ((self.stage as u16) << 12);
+        if self.is_extended() {
+            flags_with_stage |= EXTENDED_FLAG;
+        }
         result.extend_from_slice(&flags_with_stage.to_be_bytes());
         
+        if self.is_extended() {
+            let extended_flags = if self.skip_worktree { SKIP_WORKTREE_FLAG } else { 0 };
+            result.extend_from_slice(&extended_flags.to_be_bytes());
+        }
+        
         // Add path
         result.extend_from_slice(self.path.as_bytes());
         result.push(0); // Null terminator
@@ -215,8 +241,20 @@ impl Entry {
         let flags = flags_with_stage & 0x0FFF; // Lower 12 bits
         let stage = ((flags_with_stage >> 12) & 0x3) as u8; // Upper 2 bits (stage 0-3)
         
-        // Path starts at byte 62 and continues until null byte
-        let mut path_end = 62;
+        // Version 3 entries may carry 2 bytes of extended flags before the path
+        let mut path_start = 62;
+        let mut skip_worktree = false;
+        if flags_with_stage & EXTENDED_FLAG != 0 {
+            if data.len() < 64 {
+                return Err(crate::errors::error::Error::Generic("Entry data too short".to_string()));
+            }
+            let extended_flags = u16::from_be_bytes([data[62], data[63]]);
+            skip_worktree = extended_flags & SKIP_WORKTREE_FLAG != 0;
+            path_start = 64;
+        }
+        
+        // Path follows the flags and continues until null byte
+        let mut path_end = path_start;
         while path_end < data.len() && data[path_end] != 0 {
             path_end += 1;
         }
@@ -225,7 +263,7 @@ impl Entry {
             return Err(crate::errors::error::Error::Generic("No null terminator for path".to_string()));
         }
         
-        let path = match std::str::from_utf8(&data[62..path_end]) {
+        let path = match std::str::from_utf8(&data[path_start..path_end]) {
             Ok(s) => s.to_string(),
             Err(_) => return Err(crate::errors::error::Error::Generic("Invalid UTF-8 in path".to_string())),
         };
@@ -245,9 +283,20 @@ impl Entry {
             flags,
             path,
             stage,
+            skip_worktree,
         })
     }
     
+    /// Whether both entries record the same stat information (times, size and mode)
+    pub fn same_stat(&self, other: &Entry) -> bool {
+        self.ctime == other.ctime
+            && self.ctime_nsec == other.ctime_nsec
+            && self.mtime == other.mtime
+            && self.mtime_nsec == other.mtime_nsec
+            && self.size == other.size
+            && self.mode == other.mode
+    }
+    
     // Update stat information for an entry
     pub fn update_stat(&mut self, stat: &std::fs::Metadata) {
         // Update timestamps
@@ -10,17 +10,27 @@ use crate::core::index::entry::Entry;
 use crate::core::index::checksum::Checksum;
 use crate::core::index::checksum::CHECKSUM_SIZE;
 use crate::core::file_mode::FileMode;
+use crate::core::workspace::path_key;
 
 const HEADER_FORMAT: &str = "DIRC";
 const VERSION: u32 = 2;
+// Written instead of VERSION when some entry carries extended flags
+const EXTENDED_VERSION: u32 = 3;
 const HEADER_SIZE: usize = 12;
 
+/// Index entries are keyed by path and stage. A merged path has a single stage-0 entry;
+/// an unmerged path has up to three conflict stages (1 = base, 2 = ours, 3 = theirs) and no stage 0.
+pub type EntryKey = (String, u8);
+
 pub struct Index {
     pathname: PathBuf,
-    pub entries: HashMap<String, Entry>,
-    pub keys: BTreeSet<String>,
+    pub entries: HashMap<EntryKey, Entry>,
+    pub keys: BTreeSet<EntryKey>,
     lockfile: Lockfile,
     pub changed: bool,
+    /// Entries whose stat data was refreshed since the index was loaded. They make the index
+    /// worth writing even though nothing was staged.
+    refreshed: HashSet<EntryKey>,
 }
 
 impl Index {
@@ -31,6 +41,7 @@ impl Index {
             keys: BTreeSet::new(),
             lockfile: Lockfile::new(pathname),
             changed: false,
+            refreshed: HashSet::new(),
         };
         
         index.clear();
@@ -42,30 +53,60 @@ impl Index {
         &self.pathname
     }
     
+    /// Returns the stage-0 entry for a path, or its lowest conflict stage when the path is unmerged
     pub fn get_entry(&self, key: &str) -> Option<&Entry> {
-        self.entries.get(key)
+        (0..=3).find_map(|stage| self.entry_for_path(key, stage))
     }
     
     pub fn get_entry_mut(&mut self, key: &str) -> Option<&mut Entry> {
-        self.entries.get_mut(key)
+        self.entries.get_mut(&(key.to_string(), 0))
+    }
+    
+    pub fn entry_for_path(&self, path: &str, stage: u8) -> Option<&Entry> {
+        self.entries.get(&(path.to_string(), stage))
     }
     
-    pub fn get_keys(&self) -> &BTreeSet<String> {
+    pub fn get_keys(&self) -> &BTreeSet<EntryKey> {
         &self.keys
     }
     
+    /// Whether the index differs from the file it was loaded from, by staged changes or
+    /// refreshed stat data
     pub fn is_changed(&self) -> bool {
-        self.changed
+        self.changed || !self.refreshed.is_empty()
     }
     
     pub fn set_changed(&mut self, changed: bool) {
         self.changed = changed;
+        if !changed {
+            self.refreshed.clear();
+        }
     }
     
+    /// Records `stat` for an unchanged file. Stat data identical to what the entry already
+    /// holds doesn't mark the index as changed, so confirming a clean file costs no write.
     pub fn update_entry_stat(&mut self, path: &str, stat: &std::fs::Metadata) -> Result<(), Error> {
+        let key = (path.to_string(), 0);
+        if let Some(entry) = self.entries.get_mut(&key) {
+            let mut refreshed = entry.clone();
+            refreshed.update_stat(stat);
+            if !refreshed.same_stat(entry) {
+                *entry = refreshed;
+                self.refreshed.insert(key);
+            }
+            Ok(())
+        } else {
+            Err(Error::Generic(format!("Entry not found for key: {}", path)))
+        }
+    }
+    
+    /// Marks or unmarks a tracked path as skip-worktree, so it is left out of workspace comparisons
+    pub fn set_skip_worktree(&mut self, path: &str, skip: bool) -> Result<(), Error> {
         if let Some(entry) = self.get_entry_mut(path) {
-            entry.update_stat(stat);
-            self.changed = true;
+            if entry.skip_worktree != skip {
+                entry.skip_worktree = skip;
+                self.changed = true;
+            }
             Ok(())
         } else {
             Err(Error::Generic(format!("Entry not found for key: {}", path)))
@@ -76,17 +117,30 @@ impl Index {
         self.entries.clear();
         self.keys.clear();
         self.changed = false;
+        self.refreshed.clear();
     }
 
     pub fn add(&mut self, pathname: &Path, oid: &str, stat: &fs::Metadata) -> Result<(), Error> {
         let entry = Entry::create(pathname, oid, stat);
+        // Staging a path resolves it, so any conflict stages it had are dropped
+        self.remove_conflict(entry.get_path());
         self.store_entry(entry);
         self.changed = true;
         Ok(())
     }
     
+    /// Stages `oid` at `pathname` without stat information, for content that doesn't match
+    /// the working tree. The zeroed stat makes status re-hash the file instead of trusting it.
+    pub fn add_without_stat(&mut self, pathname: &Path, oid: &str, mode: FileMode) {
+        let mut entry = create_stage_entry(pathname, oid, 0);
+        entry.set_mode(mode);
+        self.remove_conflict(entry.get_path());
+        self.store_entry(entry);
+        self.changed = true;
+    }
+    
     fn store_entry(&mut self, entry: Entry) {
-        let key = entry.get_path().to_string();
+        let key = (entry.get_path().to_string(), entry.stage);
         self.keys.insert(key.clone());
         self.entries.insert(key, entry);
     }
@@ -149,10 +203,10 @@ impl Index {
             )));
         }
         
-        if version != VERSION {
+        if version != VERSION && version != EXTENDED_VERSION {
             return Err(Error::Generic(format!(
-                "Version: expected '{}' but found '{}'",
-                VERSION, version
+                "Version: expected '{}' or '{}' but found '{}'",
+                VERSION, EXTENDED_VERSION, version
             )));
         }
         
@@ -211,9 +265,13 @@ impl Index {
         Ok(())
     }
     
+    /// Writes the index through its lockfile if anything changed, otherwise just releases
+    /// the lock. The file is rewritten whole (its checksum covers every entry), so each call
+    /// costs at most one write: a status run on a clean tree performs none, one that refreshes
+    /// the stat data of touched but unmodified files performs exactly one.
     pub fn write_updates(&mut self) -> Result<bool, Error> {
         // If no changes were made, just release the lock and return
-        if !self.changed {
+        if !self.is_changed() {
             self.lockfile.rollback()
                 .map_err(|e| Error::Generic(format!("Rollback error: {:?}", e)))?;
             return Ok(false);
@@ -224,9 +282,14 @@ impl Index {
         
         // Generate header
         let entry_count = self.entries.len() as u32;
+        let version = if self.entries.values().any(|entry| entry.is_extended()) {
+            EXTENDED_VERSION
+        } else {
+            VERSION
+        };
         let mut header = Vec::with_capacity(HEADER_SIZE);
         header.extend_from_slice(HEADER_FORMAT.as_bytes());
-        header.extend_from_slice(&VERSION.to_be_bytes());
+        header.extend_from_slice(&version.to_be_bytes());
         header.extend_from_slice(&entry_count.to_be_bytes());
         
         
@@ -261,14 +324,16 @@ impl Index {
         self.lockfile.commit_ref()
             .map_err(|e| Error::Generic(format!("Commit error: {:?}", e)))?;
         
-        // Reset the changed flag
+        // Reset the changed flags
         self.changed = false;
+        self.refreshed.clear();
         
         Ok(true)
     }
 
     pub fn rollback(&mut self) -> Result<(), Error> {
         self.changed = false;
+        self.refreshed.clear();
         self.lockfile.rollback()
             .map_err(|e| Error::Lock(format!("Failed to release lock: {:?}", e)))
     }
@@ -306,10 +371,10 @@ impl Index {
         }
         
         let version = u32::from_be_bytes([header_data[4], header_data[5], header_data[6], header_data[7]]);
-        if version != VERSION {
+        if version != VERSION && version != EXTENDED_VERSION {
             return Err(Error::Generic(format!(
-                "Unsupported index version: expected {}, got {}",
-                VERSION, version
+                "Unsupported index version: expected {} or {}, got {}",
+                VERSION, EXTENDED_VERSION, version
             )));
         }
         
@@ -413,15 +478,15 @@ impl Index {
     
     // Helper method to check if a file is indexed
     pub fn tracked(&self, path: &str) -> bool {
-        self.entries.contains_key(path)
+        (0..=3).any(|stage| self.entries.contains_key(&(path.to_string(), stage)))
     }
     
     pub fn tracked_file(&self, path: &Path) -> bool {
-        self.tracked(&path.to_string_lossy())
+        self.tracked(&path_key(path))
     }
     
     pub fn tracked_directory(&self, path: &Path) -> bool {
-        let path_str = path.to_string_lossy().to_string();
+        let path_str = path_key(path);
         let prefix = if path_str.ends_with('/') {
             path_str.clone()
         } else {
@@ -429,26 +494,30 @@ impl Index {
         };
         
         // Check if any key starts with this prefix
-        self.keys.iter().any(|key| key.starts_with(&prefix))
+        self.keys.iter().any(|(key, _)| key.starts_with(&prefix))
     }
     
     pub fn child_paths(&self, path: &Path) -> Vec<PathBuf> {
-        let path_str = path.to_string_lossy().to_string();
+        let path_str = path_key(path);
         let prefix = if path_str.ends_with('/') {
             path_str.clone()
         } else {
             format!("{}/", path_str)
         };
         
-        self.keys
+        let mut paths: Vec<PathBuf> = self.keys
             .iter()
-            .filter(|key| key.starts_with(&prefix) || **key == path_str)
-            .map(|key| PathBuf::from(key))
-            .collect()
+            .filter(|(key, _)| key.starts_with(&prefix) || *key == path_str)
+            .map(|(key, _)| PathBuf::from(key))
+            .collect();
+        
+        // Unmerged paths appear once per conflict stage
+        paths.dedup();
+        paths
     }
     
     pub fn remove(&mut self, path: &Path) -> Result<(), Error> {
-        let path_str = path.to_string_lossy().to_string();
+        let path_str = path_key(path);
         
         // Remove the entry and its children
         self.remove_entry(&path_str);
@@ -458,10 +527,13 @@ impl Index {
         Ok(())
     }
     
-    /// Remove a specific entry from the index
+    /// Remove every stage of a path from the index
     fn remove_entry(&mut self, path: &str) {
-        if self.entries.remove(path).is_some() {
-            self.keys.remove(path);
+        for stage in 0..=3 {
+            let key = (path.to_string(), stage);
+            if self.entries.remove(&key).is_some() {
+                self.keys.remove(&key);
+            }
         }
     }
     
@@ -470,8 +542,8 @@ impl Index {
         let path_prefix = format!("{}/", path);
         
         // Collect keys to remove (can't modify while iterating)
-        let keys_to_remove: Vec<String> = self.keys.iter()
-            .filter(|key| key.starts_with(&path_prefix))
+        let keys_to_remove: Vec<EntryKey> = self.keys.iter()
+            .filter(|(key, _)| key.starts_with(&path_prefix))
             .cloned()
             .collect();
         
@@ -485,13 +557,10 @@ impl Index {
     // Add conflict entry (for merge conflicts)
     pub fn add_conflict(&mut self, path: &Path, entries: Vec<Option<DatabaseEntry>>) {
         // Create conflict stage entries (1-3) for the conflicting versions
-        let path_str = path.to_string_lossy().to_string();
+        let path_str = path_key(path);
         
-        // Clear any existing entry
-        if self.entries.contains_key(&path_str) {
-            self.entries.remove(&path_str);
-            self.keys.remove(&path_str);
-        }
+        // Clear any existing entry, whatever its stage
+        self.remove_entry(&path_str);
         
         // Add each conflict stage entry
         // Stage 1: Base version
@@ -542,7 +611,7 @@ impl Index {
     // Resolve a conflict by setting the given path to the final resolution
     pub fn resolve_conflict(&mut self, path: &Path, oid: &str, stat: &std::fs::Metadata) -> Result<(), Error> {
         // First, remove any conflict entries for this path
-        let path_str = path.to_string_lossy().to_string();
+        let path_str = path_key(path);
         self.remove_conflict(&path_str);
         
         // Then add the resolved entry
@@ -552,29 +621,20 @@ impl Index {
         Ok(())
     }
     
-    // Remove conflict entries for a path
+    // Remove conflict entries (stages 1-3) for a path, keeping any stage-0 entry
     fn remove_conflict(&mut self, path_str: &str) {
-        
-        // Get all entries for this path with their stages
-        let entries_to_remove: Vec<(String, u8)> = self.entries.iter()
-            .filter(|(k, v)| k == &path_str && v.stage > 0)
-            .map(|(k, v)| (k.clone(), v.stage))
-            .collect();
-        
-        // Remove each conflict entry
-        for (key, stage) in entries_to_remove {
-            self.entries.remove(&key);
-        }
-        
-        // Check if there are any entries left for this path
-        if !self.entries.iter().any(|(k, _)| k == path_str) {
-            self.keys.remove(path_str);
+        for stage in 1..=3 {
+            let key = (path_str.to_string(), stage);
+            if self.entries.remove(&key).is_some() {
+                self.keys.remove(&key);
+                self.changed = true;
+            }
         }
     }
     
     // Method to resolve directory conflicts
     pub fn resolve_directory_conflict(&mut self, dir_path: &Path) -> Result<(), Error> {
-        let dir_path_str = dir_path.to_string_lossy().to_string();
+        let dir_path_str = path_key(dir_path);
         
         // Remove the conflict entries for the directory itself
         self.remove_conflict(&dir_path_str);
@@ -588,9 +648,9 @@ impl Index {
         
         // Find all entries that start with this prefix
         let conflict_paths: Vec<String> = self.entries.iter()
-            .filter(|(path, entry)| 
+            .filter(|((path, _), entry)| 
                 entry.stage > 0 && path.starts_with(&dir_prefix))
-            .map(|(path, _)| path.clone())
+            .map(|((path, _), _)| path.clone())
             .collect();
         
         // Remove each conflict entry
@@ -620,12 +680,13 @@ fn create_stage_entry(path: &Path, oid: &str, stage: u8) -> Entry {
         size: 0,
         oid: oid.to_string(),
         flags: 0,
-        path: path.to_string_lossy().to_string(),
+        path: path_key(path),
         stage,
+        skip_worktree: false,
     };
     
     // Set stage in flags
-    entry.flags = (path.to_string_lossy().len() as u16) | ((stage as u16) << 12);
+    entry.flags = (path_key(path).len() as u16) | ((stage as u16) << 12);
     
     entry
 }
\ No newline at end of file
@@ -3,7 +3,15 @@ use std::collections::HashSet; // Adaugă HashMap dacă nu există deja
 
 use crate::core::database::database::Database;
 use crate::errors::error::Error;
-use crate::core::merge::common_ancestors::CommonAncestors;
+use crate::core::merge::common_ancestors::{is_ancestor, CommonAncestors};
+
+/// Best common ancestors of two commits, sorted so repeated calls list them in the same order.
+/// Criss-cross histories can have several; unrelated histories have none.
+pub fn merge_base(database: &mut Database, one: &str, two: &str) -> Result<Vec<String>, Error> {
+    let mut bases = Bases::new(database)?.find(one, two)?;
+    bases.sort();
+    Ok(bases)
+}
 
 pub struct Bases<'a> {
     database: &'a mut Database,
@@ -67,36 +75,23 @@ impl<'a> Bases<'a> {
             return Ok(());
         }
 
-        // Găsește strămoșii comuni între commit și ceilalți, folosind one și two
-        // Acest apel pare greșit - ar trebui să verificăm direct relația părinte-copil
-        // Folosind CommonAncestors între `commit` și `other_oid`
-
         // Verificăm dacă `commit` este strămoș pentru oricare `other_oid`
+        // (aceeași verificare ca `ash merge-base --is-ancestor commit other_oid`)
         for other_oid in &others {
-             let mut is_ancestor_check = CommonAncestors::new(self.database, commit, &[*other_oid])?;
-             // common.find() returnează strămoșii comuni. Dacă `commit` e strămoș al lui `other_oid`,
-             // atunci `find` ar trebui să returneze commit-uri mai vechi decât `commit`.
-             // Verificarea corectă este dacă `other_oid` este accesibil din `commit` și nu invers.
-             // Vom folosi o verificare mai directă: este `commit` un strămoș al lui `other_oid`?
-             // Git folosește `git merge-base --is-ancestor commit other_oid`
-             // Simulăm asta:
-             if is_ancestor_check.find()?.contains(&commit.to_string()) { // Verifică dacă commit e printre strămoși
+            if is_ancestor(self.database, commit, other_oid)? {
                 // Dacă `commit` este strămoș al lui `other_oid`, atunci `commit` este redundant
                 self.redundant.insert(commit.to_string());
-                // Putem ieși devreme dacă l-am marcat deja
-                 return Ok(());
-             }
+                return Ok(());
+            }
         }
 
-         // Verificăm dacă oricare `other_oid` este strămoș al lui `commit`
-         for other_oid_str in others { // Iterăm prin others ca &str
-             let mut is_descendant_check = CommonAncestors::new(self.database, other_oid_str, &[commit])?;
-             // Verificăm dacă `other_oid_str` este strămoș al lui `commit`
-             if is_descendant_check.find()?.contains(&other_oid_str.to_string()) {
-                 // Dacă `other_oid_str` este strămoș al lui `commit`, atunci `other_oid_str` este redundant
-                 self.redundant.insert(other_oid_str.to_string());
-             }
-         }
+        // Verificăm dacă oricare `other_oid` este strămoș al lui `commit`
+        for other_oid_str in others {
+            if is_ancestor(self.database, other_oid_str, commit)? {
+                // Dacă `other_oid_str` este strămoș al lui `commit`, atunci `other_oid_str` este redundant
+                self.redundant.insert(other_oid_str.to_string());
+            }
+        }
 
         Ok(())
     }
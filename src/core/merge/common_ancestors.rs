@@ -11,6 +11,61 @@ pub enum Flag {
     Stale,
 }
 
+/// Returns true if `ancestor` is reachable from `descendant` by following parent links.
+/// A commit counts as its own ancestor.
+pub fn is_ancestor(database: &mut Database, ancestor: &str, descendant: &str) -> Result<bool, Error> {
+    let mut queue = VecDeque::new();
+    let mut visited = HashSet::new();
+    queue.push_back(descendant.to_string());
+
+    while let Some(oid) = queue.pop_front() {
+        if oid == ancestor {
+            return Ok(true);
+        }
+        if !visited.insert(oid.clone()) {
+            continue;
+        }
+
+        let commit_obj = database.load(&oid)?;
+        if let Some(commit) = commit_obj.as_any().downcast_ref::<Commit>() {
+            queue.extend(commit.get_parents().iter().cloned());
+        }
+    }
+
+    Ok(false)
+}
+
+/// Counts the commits reachable from `local` but not from `upstream` (ahead) and the
+/// commits reachable from `upstream` but not from `local` (behind).
+pub fn ahead_behind(database: &mut Database, local: &str, upstream: &str) -> Result<(usize, usize), Error> {
+    let local_reachable = reachable_commits(database, local)?;
+    let upstream_reachable = reachable_commits(database, upstream)?;
+
+    let ahead = local_reachable.difference(&upstream_reachable).count();
+    let behind = upstream_reachable.difference(&local_reachable).count();
+
+    Ok((ahead, behind))
+}
+
+fn reachable_commits(database: &mut Database, tip: &str) -> Result<HashSet<String>, Error> {
+    let mut queue = VecDeque::new();
+    let mut visited = HashSet::new();
+    queue.push_back(tip.to_string());
+
+    while let Some(oid) = queue.pop_front() {
+        if !visited.insert(oid.clone()) {
+            continue;
+        }
+
+        let commit_obj = database.load(&oid)?;
+        if let Some(commit) = commit_obj.as_any().downcast_ref::<Commit>() {
+            queue.extend(commit.get_parents().iter().cloned());
+        }
+    }
+
+    Ok(visited)
+}
+
 pub struct CommonAncestors<'a> {
     database: &'a mut Database,
     flags: HashMap<String, HashSet<Flag>>,
@@ -127,8 +182,8 @@ impl<'a> CommonAncestors<'a> {
         commit_oid: &str,
         flags: &HashSet<Flag>,
     ) -> Result<(), Error> {
-        // If commit has a parent, add it to the queue with the same flags
-        if let Some(parent_oid) = commit.get_parent() {
+        // Add every parent to the queue with the same flags
+        for parent_oid in commit.get_parents() {
             // Get or create flags entry for parent
             let current_flags = self.flags.entry(parent_oid.to_string()).or_insert_with(HashSet::new);
             
@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 use std::fmt::Write;
 use crate::errors::error::Error;
+use crate::core::diff::myers::{diff_lines, Edit};
 
 // Helper to convert a string into a vector of lines with their endings preserved
 struct LinesWithEndings<'a> {
@@ -38,68 +39,6 @@ impl<'a> Iterator for LinesWithEndings<'a> {
     }
 }
 
-// Simplified representation of edits for our diff3 purposes
-#[derive(Debug, PartialEq)]
-enum EditType {
-    Eql,
-    Add,
-    Del,
-}
-
-#[derive(Debug)]
-struct Edit {
-    r#type: EditType,
-    a_line: Option<LineInfo>,
-    b_line: Option<LineInfo>,
-}
-
-#[derive(Debug)]
-struct LineInfo {
-    number: usize,
-    content: String,
-}
-
-// Function to calculate simple line-by-line diff between two texts
-fn diff(a: &str, b: &str) -> Vec<Edit> {
-    let a_lines: Vec<_> = a.lines().collect();
-    let b_lines: Vec<_> = b.lines().collect();
-    
-    let mut result = Vec::new();
-    let mut i = 0;
-    let mut j = 0;
-    
-    while i < a_lines.len() || j < b_lines.len() {
-        if i < a_lines.len() && j < b_lines.len() && a_lines[i] == b_lines[j] {
-            // Equal lines
-            result.push(Edit {
-                r#type: EditType::Eql,
-                a_line: Some(LineInfo { number: i, content: a_lines[i].to_string() }),
-                b_line: Some(LineInfo { number: j, content: b_lines[j].to_string() }),
-            });
-            i += 1;
-            j += 1;
-        } else if j < b_lines.len() && (i >= a_lines.len() || i < a_lines.len() && a_lines[i] != b_lines[j]) {
-            // Line added in b
-            result.push(Edit {
-                r#type: EditType::Add,
-                a_line: None,
-                b_line: Some(LineInfo { number: j, content: b_lines[j].to_string() }),
-            });
-            j += 1;
-        } else if i < a_lines.len() {
-            // Line deleted from a
-            result.push(Edit {
-                r#type: EditType::Del,
-                a_line: Some(LineInfo { number: i, content: a_lines[i].to_string() }),
-                b_line: None,
-            });
-            i += 1;
-        }
-    }
-    
-    result
-}
-
 /// Performs a three-way merge between original (o), ours (a), and theirs (b) content
 pub fn merge(o: &str, a: &str, b: &str) -> Result<MergeResult, Error> {
     let o: Vec<_> = LinesWithEndings::new(o).map(|l| l.to_string()).collect();
@@ -159,19 +98,17 @@ impl Diff3 {
     fn match_set(&self, file: &[String]) -> MatchSet {
         let mut matches = HashMap::new();
 
-        // Generate diff between original and this file
-        let o_content = self.o.join("");
-        let file_content = file.join("");
+        // Lines are matched without their endings so a final line that lost or gained its
+        // newline still anchors the merge; write_chunk compares them whole, so the newline
+        // change itself is kept
+        let strip = |lines: &[String]| -> Vec<String> {
+            lines.iter().map(|line| line.trim_end_matches('\n').to_string()).collect()
+        };
         
-        // Generate diff using our custom diff function
-        for edit in diff(&o_content, &file_content) {
-            match edit.r#type {
-                EditType::Eql => {
-                    if let (Some(a_line), Some(b_line)) = (edit.a_line, edit.b_line) {
-                        matches.insert(a_line.number, b_line.number);
-                    }
-                },
-                _ => {}
+        // Line numbers are 1-based, like the positions walked in generate_chunks
+        for edit in diff_lines(&strip(&self.o), &strip(file)) {
+            if let Edit::Equal(o_index, file_index) = edit {
+                matches.insert(o_index + 1, file_index + 1);
             }
         }
 
@@ -287,6 +224,26 @@ impl Diff3 {
     }
 }
 
+/// How conflicting chunks are written out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictStyle {
+    /// Our and their sides only
+    Merge,
+    /// Our side, the common ancestor after `|||||||`, then their side
+    Diff3,
+}
+
+impl ConflictStyle {
+    /// Parses a `--conflict-style` value
+    pub fn from_option(option: &str) -> Option<Self> {
+        match option {
+            "merge" => Some(ConflictStyle::Merge),
+            "diff3" => Some(ConflictStyle::Diff3),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum Chunk {
     Clean {
@@ -300,10 +257,10 @@ pub enum Chunk {
 }
 
 impl Chunk {
-    pub fn to_string(&self, a_name: Option<&str>, b_name: Option<&str>) -> String {
+    pub fn to_string(&self, a_name: Option<&str>, b_name: Option<&str>, style: ConflictStyle) -> String {
         match self {
             Chunk::Clean { lines } => lines.join(""),
-            Chunk::Conflict { o_lines: _, a_lines, b_lines } => {
+            Chunk::Conflict { o_lines, a_lines, b_lines } => {
                 fn separator(text: &mut String, r#char: &str, name: Option<&str>) {
                     text.push_str(&r#char.repeat(7));
                     if let Some(name) = name {
@@ -312,15 +269,25 @@ impl Chunk {
                     text.push('\n');
                 }
 
+                // A side whose last line has no newline still needs one before the marker
+                fn side(text: &mut String, lines: &[String]) {
+                    for line in lines {
+                        text.push_str(line);
+                    }
+                    if !lines.is_empty() && !text.ends_with('\n') {
+                        text.push('\n');
+                    }
+                }
+
                 let mut text = String::new();
                 separator(&mut text, "<", a_name);
-                for line in a_lines {
-                    text.push_str(line);
+                side(&mut text, a_lines);
+                if style == ConflictStyle::Diff3 {
+                    separator(&mut text, "|", None);
+                    side(&mut text, o_lines);
                 }
                 separator(&mut text, "=", None);
-                for line in b_lines {
-                    text.push_str(line);
-                }
+                side(&mut text, b_lines);
                 separator(&mut text, ">", b_name);
 
                 text
@@ -348,11 +315,58 @@ impl MergeResult {
         true
     }
 
-    pub fn to_string(&self, a_name: Option<&str>, b_name: Option<&str>) -> String {
-        self.chunks
-            .iter()
-            .map(|chunk| chunk.to_string(a_name, b_name))
-            .collect::<Vec<_>>()
-            .join("")
+    /// The merged text. Only the end of the result may lack a newline: a chunk taken from
+    /// the end of one side gets its newline back when more content follows it.
+    pub fn to_string(&self, a_name: Option<&str>, b_name: Option<&str>, style: ConflictStyle) -> String {
+        let mut text = String::new();
+        for chunk in &self.chunks {
+            let chunk_text = chunk.to_string(a_name, b_name, style);
+            if chunk_text.is_empty() {
+                continue;
+            }
+            if !text.is_empty() && !text.ends_with('\n') {
+                text.push('\n');
+            }
+            text.push_str(&chunk_text);
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn merged(o: &str, a: &str, b: &str) -> String {
+        let result = merge(o, a, b).unwrap();
+        assert!(result.is_clean());
+        result.to_string(None, None, ConflictStyle::Merge)
+    }
+
+    #[test]
+    fn merge_keeps_a_missing_trailing_newline() {
+        assert_eq!(merged("a\nb\nc", "a\nB\nc", "a\nb\nc"), "a\nB\nc");
+        assert_eq!(merged("a\nb\nc", "a\nb\nc", "a\nb\nC"), "a\nb\nC");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn merge_adds_a_newline_before_content_appended_after_the_last_line() {
+        assert_eq!(merged("a\nb", "A\nb", "a\nb\nc"), "A\nb\nc");
+        assert_eq!(merged("a\nb", "a\nb", "a\nb\nc\n"), "a\nb\nc\n");
+    }
+
+    #[test]
+    fn diff3_style_shows_the_base_block() {
+        let result = merge("a\nb\nc\n", "a\nB\nc\n", "a\nX\nc\n").unwrap();
+        assert!(!result.is_clean());
+
+        assert_eq!(
+            result.to_string(Some("HEAD"), Some("topic"), ConflictStyle::Diff3),
+            "a\n<<<<<<< HEAD\nB\n|||||||\nb\n=======\nX\n>>>>>>> topic\nc\n"
+        );
+        assert_eq!(
+            result.to_string(Some("HEAD"), Some("topic"), ConflictStyle::Merge),
+            "a\n<<<<<<< HEAD\nB\n=======\nX\n>>>>>>> topic\nc\n"
+        );
+    }
+}
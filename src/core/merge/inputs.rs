@@ -1,6 +1,6 @@
 // src/core/merge/inputs.rs
 use crate::errors::error::Error;
-use crate::core::merge::bases::Bases;
+use crate::core::merge::bases::merge_base;
 use crate::core::database::database::Database;
 use crate::core::refs::Refs;
 // Eliminăm importul Revision dacă nu este folosit direct aici
@@ -35,12 +35,7 @@ impl Inputs {
         let right_oid = Self::resolve_rev(database, refs, &right_name)?;
 
         // Find the common base(s) between the two commits
-        // --- FIX APPLIED HERE ---
-        // 1. Call Bases::new with only the database argument
-        let mut common = Bases::new(database)?;
-        let base_oids = common.find(&left_oid, &right_oid)?;
-        // 2. Call common.find with the left_oid and right_oid arguments
-        let base_oids = common.find(&left_oid, &right_oid)?;
+        let base_oids = merge_base(database, &left_oid, &right_oid)?;
 
         Ok(Self {
             left_name,
@@ -2,29 +2,59 @@
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
+use crate::core::config::Config;
 use crate::core::database::blob::Blob;
 use crate::core::database::database::{Database, GitObject};
 use crate::core::database::entry::DatabaseEntry;
 use crate::core::database::tree::{Tree, TreeEntry};
+use crate::core::diff::rename::{self, Rename, RenameCandidate, Renames};
 use crate::core::file_mode::FileMode;
 use crate::core::index::index::Index;
 use crate::core::workspace::Workspace;
 use crate::errors::error::Error;
-use crate::core::merge::diff3;
+use crate::core::merge::diff3::{self, ConflictStyle};
 use crate::core::merge::inputs::MergeInputs;
 use crate::core::path_filter::PathFilter;
+use crate::core::progress::{NoProgress, Progress};
+
+/// How conflicting paths are resolved during a merge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// Three-way merge; overlapping edits are left as conflicts
+    Recursive,
+    /// Conflicting paths take the left (our) side
+    Ours,
+    /// Conflicting paths take the right (their) side
+    Theirs,
+}
+
+impl MergeStrategy {
+    /// Parses a `-X <option>` value
+    pub fn from_option(option: &str) -> Option<Self> {
+        match option {
+            "ours" => Some(MergeStrategy::Ours),
+            "theirs" => Some(MergeStrategy::Theirs),
+            _ => None,
+        }
+    }
+}
+
+/// Changes from the merge base to one side, by path: (base entry, side's entry)
+type DiffMap = HashMap<PathBuf, (Option<DatabaseEntry>, Option<DatabaseEntry>)>;
 
 pub struct Resolve<'a, T: MergeInputs> {
     database: &'a mut Database,
     workspace: &'a Workspace,
     index: &'a mut Index,
     inputs: &'a T,
+    strategy: MergeStrategy,
     left_diff: HashMap<PathBuf, (Option<DatabaseEntry>, Option<DatabaseEntry>)>,
     right_diff: HashMap<PathBuf, (Option<DatabaseEntry>, Option<DatabaseEntry>)>,
     clean_diff: HashMap<PathBuf, (Option<DatabaseEntry>, Option<DatabaseEntry>)>,
     conflicts: HashMap<String, Vec<Option<DatabaseEntry>>>,
     untracked: HashMap<String, DatabaseEntry>, // For renamed files in conflicts
-    pub on_progress: fn(String),
+    progress: &'a dyn Progress,
+    pub conflict_style: ConflictStyle,
 }
 
 impl<'a, T: MergeInputs> Resolve<'a, T> {
@@ -33,24 +63,27 @@ impl<'a, T: MergeInputs> Resolve<'a, T> {
         workspace: &'a Workspace,
         index: &'a mut Index,
         inputs: &'a T,
+        strategy: MergeStrategy,
     ) -> Self {
         Self {
             database,
             workspace,
             index,
             inputs,
+            strategy,
             left_diff: HashMap::new(),
             right_diff: HashMap::new(),
             clean_diff: HashMap::new(),
             conflicts: HashMap::new(),
             untracked: HashMap::new(),
-            on_progress: |_info| (),
+            progress: &NoProgress,
+            conflict_style: ConflictStyle::Merge,
         }
     }
 
-     // Main execution logic for recursive merge
-     pub fn execute(&mut self) -> Result<(), Error> {
-         println!("Executing merge resolution");
+     // Main execution logic for recursive merge; conflicts and applied changes are reported to `progress`
+     pub fn execute(&mut self, progress: &'a dyn Progress) -> Result<(), Error> {
+         self.progress = progress;
 
          // Prepare the tree differences and identify conflicts
          self.prepare_tree_diffs()?; // Populates self.conflicts and self.untracked
@@ -66,14 +99,13 @@ impl<'a, T: MergeInputs> Resolve<'a, T> {
 
          // Check if conflicts were detected
          if !self.conflicts.is_empty() {
-             println!("Found {} conflicts.", self.conflicts.len());
+             self.log(format!("Found {} conflicts.", self.conflicts.len()));
              // Return error indicating conflicts, index lock is kept by caller (main.rs)
              // because index.write_updates() will be called there to save conflict state.
              return Err(Error::Generic("Automatic merge failed; fix conflicts and then commit the result.".into()));
          }
 
          // No conflicts were found during preparation and resolution
-         println!("Merge resolved successfully with no conflicts.");
          Ok(()) // Index lock released by caller (main.rs) via index.write_updates()
      }
 
@@ -114,17 +146,14 @@ impl<'a, T: MergeInputs> Resolve<'a, T> {
 
 
     fn apply_clean_changes(&mut self) -> Result<(), Error> {
-        println!("Applying {} clean changes...", self.clean_diff.len());
+        self.progress.start(self.clean_diff.len());
         let clean_diff_clone = self.clean_diff.clone(); // Clone to allow mutable borrow of self later
         for (path, (_, new_entry_opt)) in clean_diff_clone { // Iterate over the clone
-            println!("  Applying change for: {}", path.display());
             if let Some(new_entry) = new_entry_opt {
                 if !new_entry.get_file_mode().is_directory() {
-                    println!("    Updating file...");
                     // Call helper method using self
                     self.update_workspace_file(&path, new_entry.get_oid(), &new_entry.get_file_mode())?;
                 } else {
-                    println!("    Ensuring directory exists...");
                     self.workspace.make_directory(&path)?;
                     // Optionally add directory to index if needed
                     // let stat = self.workspace.stat_file(&path)?;
@@ -132,7 +161,6 @@ impl<'a, T: MergeInputs> Resolve<'a, T> {
                 }
             } else {
                 // Entry is None, meaning deletion
-                println!("    Deleting path...");
                 let path_str = path.to_string_lossy().to_string();
                 let full_path = self.workspace.root_path.join(&path); // Use full path for checks
                 if full_path.exists() {
@@ -141,13 +169,12 @@ impl<'a, T: MergeInputs> Resolve<'a, T> {
                      } else {
                           self.workspace.remove_file(&path)?;
                      }
-                } else {
-                    println!("    Path {} already removed.", path.display());
                 }
                 self.index.remove(&PathBuf::from(&path_str))?;
             }
+            self.progress.inc(1);
         }
-        println!("Finished applying clean changes.");
+        self.progress.finish();
         Ok(())
     }
 
@@ -194,7 +221,7 @@ impl<'a, T: MergeInputs> Resolve<'a, T> {
     }
 
     fn log(&self, message: String) {
-        (self.on_progress)(message);
+        self.progress.message(&message);
     }
 
     // --- Logging functions ---
@@ -243,6 +270,7 @@ impl<'a, T: MergeInputs> Resolve<'a, T> {
 
     fn merge_blobs(
         &mut self,
+        path: &str,
         base_oid: Option<&str>,
         left_oid: Option<&str>,
         right_oid: Option<&str>,
@@ -250,21 +278,46 @@ impl<'a, T: MergeInputs> Resolve<'a, T> {
         if let Some(result) = Resolve::<T>::merge3_oid(base_oid, left_oid, right_oid) {
             return Ok((true, result.to_string()));
         }
-        let blobs: Vec<String> = vec![base_oid, left_oid, right_oid]
+        let contents: Vec<Vec<u8>> = vec![base_oid, left_oid, right_oid]
             .into_iter()
-            .map(|oid| -> Result<String, Error> {
+            .map(|oid| -> Result<Vec<u8>, Error> {
                 if let Some(oid_str) = oid {
                      if oid_str.len() == 40 && oid_str.chars().all(|c| c.is_ascii_hexdigit()) {
                          let blob_obj = self.database.load(oid_str)?;
-                         let content = blob_obj.to_bytes();
-                         Ok(String::from_utf8_lossy(&content).to_string())
-                     } else { Ok(String::new()) }
-                } else { Ok(String::new()) }
+                         Ok(blob_obj.to_bytes())
+                     } else { Ok(Vec::new()) }
+                } else { Ok(Vec::new()) }
             })
-            .collect::<Result<Vec<String>, Error>>()?;
+            .collect::<Result<Vec<Vec<u8>>, Error>>()?;
+
+        // Binary files are never merged line by line: the path conflicts, keeping our version
+        if contents.iter().any(|content| self.workspace.is_binary(path, content)) {
+            return Ok(match self.strategy {
+                MergeStrategy::Ours => (true, left_oid.unwrap_or("").to_string()),
+                MergeStrategy::Theirs => (true, right_oid.unwrap_or("").to_string()),
+                MergeStrategy::Recursive => {
+                    self.log(format!("warning: Cannot merge binary files: {} ({} vs. {})", path, self.inputs.left_name(), self.inputs.right_name()));
+                    (false, left_oid.or(right_oid).unwrap_or("").to_string())
+                }
+            });
+        }
+
+        let blobs: Vec<String> = contents.iter()
+            .map(|content| String::from_utf8_lossy(content).to_string())
+            .collect();
 
         let merge_result = diff3::merge(&blobs[0], &blobs[1], &blobs[2])?;
-        let result_text = merge_result.to_string( Some(&self.inputs.left_name()), Some(&self.inputs.right_name()), );
+
+        // A conflicting path is taken whole from the preferred side; an empty OID records a deletion
+        if !merge_result.is_clean() {
+            match self.strategy {
+                MergeStrategy::Ours => return Ok((true, left_oid.unwrap_or("").to_string())),
+                MergeStrategy::Theirs => return Ok((true, right_oid.unwrap_or("").to_string())),
+                MergeStrategy::Recursive => {}
+            }
+        }
+
+        let result_text = merge_result.to_string( Some(&self.inputs.left_name()), Some(&self.inputs.right_name()), self.conflict_style );
         let mut blob = Blob::new(result_text.as_bytes().to_vec());
         self.database.store(&mut blob)?;
         let blob_oid = blob.get_oid().map(|s| s.to_string()).unwrap_or_default();
@@ -279,6 +332,11 @@ impl<'a, T: MergeInputs> Resolve<'a, T> {
     ) -> (bool, FileMode) {
         if left_mode == base_mode || left_mode == right_mode { return (true, right_mode.unwrap_or(FileMode::REGULAR)); }
         if right_mode == base_mode { return (true, left_mode.unwrap_or(FileMode::REGULAR)); }
+        match self.strategy {
+            MergeStrategy::Ours => return (true, left_mode.or(right_mode).unwrap_or(FileMode::REGULAR)),
+            MergeStrategy::Theirs => return (true, right_mode.or(left_mode).unwrap_or(FileMode::REGULAR)),
+            MergeStrategy::Recursive => {}
+        }
         if left_mode.is_none() { return (right_mode.is_none(), right_mode.unwrap_or(FileMode::REGULAR)); }
         if right_mode.is_none() { return (false, left_mode.unwrap_or(FileMode::REGULAR)); }
         (false, left_mode.unwrap_or(FileMode::REGULAR))
@@ -309,6 +367,8 @@ impl<'a, T: MergeInputs> Resolve<'a, T> {
         self.conflicts = HashMap::new();
         self.untracked = HashMap::new();
 
+        self.follow_renames()?;
+
         let mut all_paths = HashSet::new();
         all_paths.extend(self.left_diff.keys().cloned());
         all_paths.extend(self.right_diff.keys().cloned());
@@ -322,8 +382,15 @@ impl<'a, T: MergeInputs> Resolve<'a, T> {
              // Clone entries needed for same_path_conflict and potential later use
              let base_entry = self.left_diff.get(&path).and_then(|(old, _)| old.clone())
                  .or_else(|| self.right_diff.get(&path).and_then(|(old, _)| old.clone()));
-             let left_entry = self.left_diff.get(&path).and_then(|(_, new)| new.clone());
-             let right_entry = self.right_diff.get(&path).and_then(|(_, new)| new.clone());
+             // A path missing from one side's diff is unchanged there, so that side keeps the base
+             let left_entry = match self.left_diff.get(&path) {
+                 Some((_, new)) => new.clone(),
+                 None => base_entry.clone(),
+             };
+             let right_entry = match self.right_diff.get(&path) {
+                 Some((_, new)) => new.clone(),
+                 None => base_entry.clone(),
+             };
 
              // Extract booleans needed for parent checks *before* potentially moving entries
              let left_new_is_some = left_entry.is_some();
@@ -360,6 +427,76 @@ impl<'a, T: MergeInputs> Resolve<'a, T> {
         Ok(())
     }
 
+    /// Finds files renamed on one side and edited in place on the other, and moves the edit
+    /// to the new path so both are merged there. Comparing files by similarity is bounded by
+    /// `merge.renameLimit`; past it only identical files are paired, with a warning.
+    fn follow_renames(&mut self) -> Result<(), Error> {
+        let limit = rename::rename_limit(&Config::load(&self.workspace.root_path.join(".ash")), "merge");
+        let left_renames = Self::find_renames(&mut *self.database, &self.left_diff, limit)?;
+        let right_renames = Self::find_renames(&mut *self.database, &self.right_diff, limit)?;
+
+        if let Some(needed) = left_renames.skipped.max(right_renames.skipped) {
+            eprintln!("{}", rename::limit_warning("merge.renameLimit", needed));
+        }
+
+        for rename in &left_renames.pairs {
+            if Self::follow_rename(&mut self.left_diff, &mut self.right_diff, rename).is_some() {
+                self.log(format!("Following rename of {} to {} in {}", rename.old_path, rename.new_path, self.inputs.left_name()));
+            }
+        }
+        for rename in &right_renames.pairs {
+            if let Some(base) = Self::follow_rename(&mut self.right_diff, &mut self.left_diff, rename) {
+                // Our edited copy at the old path goes away with their rename
+                self.clean_diff.insert(PathBuf::from(&rename.old_path), (Some(base), None));
+                self.log(format!("Following rename of {} to {} in {}", rename.old_path, rename.new_path, self.inputs.right_name()));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Pairs the files deleted and added by one side of the merge
+    fn find_renames(database: &mut Database, diff: &DiffMap, limit: usize) -> Result<Renames, Error> {
+        let mut deleted = Vec::new();
+        let mut added = Vec::new();
+
+        for (path, change) in diff {
+            let path = path.to_string_lossy();
+            match change {
+                (Some(old), None) if !old.get_file_mode().is_directory() => deleted.push(RenameCandidate::new(&path, old.get_oid())),
+                (None, Some(new)) if !new.get_file_mode().is_directory() => added.push(RenameCandidate::new(&path, new.get_oid())),
+                _ => {}
+            }
+        }
+
+        rename::detect_renames(&deleted, &added, limit, |oid| Ok(database.load(oid)?.to_bytes()))
+    }
+
+    /// Moves the edit that `edited` made to the old path of `rename` over to its new path, with
+    /// the old content as the base there. Returns the base entry when the edit was moved.
+    fn follow_rename(renamed: &mut DiffMap, edited: &mut DiffMap, rename: &Rename) -> Option<DatabaseEntry> {
+        let old_path = PathBuf::from(&rename.old_path);
+        let new_path = PathBuf::from(&rename.new_path);
+        if edited.contains_key(&new_path) {
+            return None;
+        }
+
+        let edit = match edited.get(&old_path) {
+            Some((Some(_), Some(entry))) if !entry.get_file_mode().is_directory() => entry.clone(),
+            _ => return None,
+        };
+        let base = renamed.remove(&old_path)?.0?;
+
+        let moved = |entry: &DatabaseEntry| DatabaseEntry::new(rename.new_path.clone(), entry.get_oid().to_string(), entry.get_mode());
+        if let Some((new_base, _)) = renamed.get_mut(&new_path) {
+            *new_base = Some(moved(&base));
+        }
+        edited.remove(&old_path);
+        edited.insert(new_path, (Some(moved(&base)), Some(moved(&edit))));
+
+        Some(base)
+    }
+
     // Takes &self because logging helpers might need self.inputs
      fn check_parent_dir_conflict(
          &self,
@@ -516,6 +653,16 @@ impl<'a, T: MergeInputs> Resolve<'a, T> {
              return Ok(());
          }
 
+         // Only one side changed the path, so that side's version is taken as is
+         let version = |entry: &Option<DatabaseEntry>| entry.as_ref().map(|e| (e.get_oid().to_string(), e.get_file_mode()));
+         if version(&right) == version(&base) {
+              return Ok(());
+         }
+         if version(&left) == version(&base) {
+              self.clean_diff.insert(path.to_path_buf(), (base, right));
+              return Ok(());
+         }
+
          let base_oid_str = base.as_ref().map(|b| b.get_oid());
          let left_oid_str = left.as_ref().map(|l| l.get_oid());
          let right_oid_str = right.as_ref().map(|r| r.get_oid());
@@ -536,7 +683,7 @@ impl<'a, T: MergeInputs> Resolve<'a, T> {
               if let Some(oid) = merged_oid { (true, oid.to_string()) }
               else { (false, left_oid_str.unwrap_or("").to_string()) } // Conflict
          } else {
-              self.merge_blobs(base_oid_str, left_oid_str, right_oid_str)?
+              self.merge_blobs(&path_str, base_oid_str, left_oid_str, right_oid_str)?
          };
 
          let merged_entry = if left.is_some() || right.is_some() {
@@ -746,6 +893,7 @@ impl<'a, T: MergeInputs> Resolve<'a, T> {
           let blob_obj = self.database.load(oid)?;
           let content = blob_obj.to_bytes();
           self.workspace.write_file(&path, &content)?;
+          self.workspace.set_mode(path, *mode)?;
           let stat = self.workspace.stat_file(&path)?;
           self.index.add(&path, oid, &stat)?;
           Ok(())
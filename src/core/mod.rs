@@ -12,7 +12,14 @@ pub mod diff;
 pub mod pager;
 pub mod revision;
 pub mod path_filter;
+pub mod pathspec;
+pub mod tree_cache;
+pub mod untracked_cache;
+pub mod progress;
+pub mod config;
+pub mod attributes;
 pub mod revlist;
+pub mod graph;
 pub mod merge;
 pub mod metadata;
 pub mod editor;
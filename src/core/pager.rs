@@ -2,76 +2,91 @@
 use std::env;
 use std::io::{self, Write};
 use std::process::{Command, Stdio};
+use crate::core::config::Config;
 use crate::errors::error::Error;
 
+/// Environment variable set by the global `--no-pager` option
+pub const NO_PAGER_ENV: &str = "ASH_NO_PAGER";
+
+/// Lines assumed to fit on screen when the terminal size can't be read
+const DEFAULT_SCREEN_LINES: usize = 24;
+
 pub struct Pager {
     enabled: bool,
     command: String,
     process: Option<std::process::Child>,
     stdout: Option<std::process::ChildStdin>,
     early_exit: bool,  // Flag to track if user exited pager early
+    line_prefix: Option<String>,  // Written before each line, e.g. graph lanes
+    first_prefix: Option<String>, // Replaces line_prefix for the next line only
+    at_line_start: bool,
+    /// Output held back until it is known not to fit on one screen; None once the pager
+    /// runs, or when writing straight to stdout
+    pending: Option<String>,
+    pending_lines: usize,
+    /// Lines that fit on screen; longer output is sent through the pager
+    threshold: usize,
 }
 
 impl Pager {
     /// Creates a new Pager, detecting the available command in the system.
+    /// The pager is `ASH_PAGER`, then `core.pager`, then `PAGER`, then `less -FRX`; an empty
+    /// command or `cat` writes straight to stdout, as does `--no-pager` or piped output.
     pub fn new() -> Self {
         // Verify if we should use a pager at all (terminal output vs pipe)
         let force_pager = env::var("ASH_FORCE_PAGER").map(|v| v == "1").unwrap_or(false);
-        
+        let no_pager = env::var(NO_PAGER_ENV).map(|v| v == "1").unwrap_or(false);
+
         // Skip pager if output is not to a terminal, unless forced
-        let use_pager = force_pager || atty::is(atty::Stream::Stdout);
-        
-        if !use_pager {
-            return Pager {
-                enabled: false,
-                command: "cat".to_string(),
-                process: None,
-                stdout: None,
-                early_exit: false,
-            };
-        }
-        
-        // Check if there's an explicitly set pager command
-        let command = if let Ok(pager) = env::var("ASH_PAGER") {
-            pager
-        } else if let Ok(pager) = env::var("PAGER") {
-            pager
-        } else {
-            // Auto-detect available pager
-            let candidates = ["less", "more", "cat", "pager"];
-            for cmd in candidates {
-                if Self::command_exists(cmd) {
-                    if cmd == "less" {
-                        return Pager {
-                            enabled: true,
-                            command: "less -FRX".to_string(), // -F: quit if one screen, -R: preserve ANSI colors, -X: don't clear screen
-                            process: None,
-                            stdout: None,
-                            early_exit: false,
-                        };
-                    }
-                    return Pager {
-                        enabled: true,
-                        command: cmd.to_string(),
-                        process: None,
-                        stdout: None,
-                        early_exit: false,
-                    };
-                }
-            }
-            // If no pager is found, we'll use stdout directly
-            "cat".to_string()
-        };
-        
+        let use_pager = !no_pager && (force_pager || atty::is(atty::Stream::Stdout));
+
+        let command = if use_pager { Self::pager_command() } else { String::new() };
+        let command = if command.trim() == "cat" { String::new() } else { command };
+
+        let threshold = crossterm::terminal::size()
+            .map(|(_, rows)| rows as usize)
+            .unwrap_or(DEFAULT_SCREEN_LINES)
+            .saturating_sub(1);
+
         Pager {
             enabled: true,
             command,
             process: None,
             stdout: None,
             early_exit: false,
+            line_prefix: None,
+            first_prefix: None,
+            at_line_start: true,
+            pending: None,
+            pending_lines: 0,
+            threshold,
         }
     }
-    
+
+    /// The configured pager command, or the first available one
+    fn pager_command() -> String {
+        if let Ok(pager) = env::var("ASH_PAGER") {
+            return pager;
+        }
+        if let Some(pager) = Config::current().get("core", "pager") {
+            return pager.to_string();
+        }
+        if let Ok(pager) = env::var("PAGER") {
+            return pager;
+        }
+
+        // Auto-detect available pager
+        for cmd in ["less", "more", "pager"] {
+            if Self::command_exists(cmd) {
+                // -F: quit if one screen, -R: preserve ANSI colors, -X: don't clear screen
+                return if cmd == "less" { "less -FRX".to_string() } else { cmd.to_string() };
+            }
+        }
+
+        // If no pager is found, we'll use stdout directly
+        String::new()
+    }
+
     /// Check if a command exists in the system
     fn command_exists(cmd: &str) -> bool {
         let check_cmd = if cfg!(target_os = "windows") {
@@ -87,120 +102,160 @@ impl Pager {
                 .stderr(Stdio::null())
                 .status()
         };
-        
+
         match check_cmd {
             Ok(status) => status.success(),
             Err(_) => false,
         }
     }
-    
-    /// Initialize the pager for use
+
+    /// Initialize the pager for use. The pager process is only started once the output
+    /// grows past one screen; shorter output is written to stdout when the pager closes.
     pub fn start(&mut self) -> Result<(), Error> {
-        // If not enabled, do nothing
-        if !self.enabled {
+        // If not enabled, or writing straight to stdout, do nothing
+        if !self.enabled || self.command.is_empty() {
             return Ok(());
         }
-        
+
+        self.pending = Some(String::new());
+        self.pending_lines = 0;
+
+        Ok(())
+    }
+
+    /// Starts the pager process; if it can't be started, output goes to stdout instead
+    fn spawn(&mut self) {
         // Extract command and arguments
         let parts: Vec<&str> = self.command.split_whitespace().collect();
         if parts.is_empty() {
-            return Err(Error::Generic("Invalid pager command".into()));
+            return;
         }
-        
+
         let mut cmd = Command::new(parts[0]);
         for arg in &parts[1..] {
             cmd.arg(arg);
         }
-        
+
+        // A plain `less` from PAGER still needs -R to show colors, as git arranges
+        if env::var_os("LESS").is_none() {
+            cmd.env("LESS", "FRX");
+        }
+
         // Configure stdin/stdout
         cmd.stdin(Stdio::piped())
             .stdout(Stdio::inherit()) // Connect the pager's stdout to the terminal
             .stderr(Stdio::inherit());
-        
-        // Start the process
-        let mut process = match cmd.spawn() {
-            Ok(p) => p,
-            Err(e) => {
-                // Fallback to direct stdout if we can't start the pager
-                self.enabled = false;
-                return Ok(());
-            }
-        };
-        
-        // Get handle to stdin
-        let stdin = match process.stdin.take() {
-            Some(s) => s,
-            None => {
-                // Fallback to direct stdout if we can't get stdin handle
-                self.enabled = false;
-                return Ok(());
+
+        // Start the process, falling back to direct stdout if it or its stdin is unavailable
+        if let Ok(mut process) = cmd.spawn() {
+            if let Some(stdin) = process.stdin.take() {
+                self.stdout = Some(stdin);
             }
-        };
-        
-        self.process = Some(process);
-        self.stdout = Some(stdin);
-        
-        Ok(())
+            self.process = Some(process);
+        }
+    }
+
+    /// Prefix every following line with `rest`, except the next one, which gets `first`
+    pub fn set_line_prefix(&mut self, first: &str, rest: &str) {
+        self.first_prefix = Some(first.to_string());
+        self.line_prefix = Some(rest.to_string());
     }
-    
+
+    /// Stop prefixing lines
+    pub fn clear_line_prefix(&mut self) {
+        self.first_prefix = None;
+        self.line_prefix = None;
+    }
+
     /// Write text to the pager
     pub fn write(&mut self, text: &str) -> Result<(), Error> {
+        if self.line_prefix.is_none() {
+            return self.write_raw(text);
+        }
+
+        let mut prefixed = String::new();
+        for line in text.split_inclusive('\n') {
+            if self.at_line_start {
+                match self.first_prefix.take() {
+                    Some(prefix) => prefixed.push_str(&prefix),
+                    None => prefixed.push_str(self.line_prefix.as_deref().unwrap_or("")),
+                }
+            }
+            prefixed.push_str(line);
+            self.at_line_start = line.ends_with('\n');
+        }
+
+        self.write_raw(&prefixed)
+    }
+
+    fn write_raw(&mut self, text: &str) -> Result<(), Error> {
         // If pager is not enabled or user exited, don't write anything
         if !self.enabled || self.early_exit {
             return Ok(());
         }
-        
-        // If no stdout handle, write directly
-        if self.stdout.is_none() {
-            print!("{}", text);
-            io::stdout().flush().map_err(|e| Error::IO(e))?;
-            return Ok(());
+
+        // Hold output back until it no longer fits on one screen
+        if let Some(pending) = self.pending.as_mut() {
+            pending.push_str(text);
+            self.pending_lines += text.matches('\n').count();
+            if self.pending_lines <= self.threshold {
+                return Ok(());
+            }
+
+            let pending = self.pending.take().unwrap_or_default();
+            self.spawn();
+            return self.emit(&pending);
         }
-        
-        // Write to pager
-        if let Some(stdin) = &mut self.stdout {
-            match stdin.write_all(text.as_bytes()) {
-                Ok(_) => {
-                    // Try to flush, but ignore broken pipe error
-                    match stdin.flush() {
-                        Ok(_) => {},
-                        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => {
-                            // User exited pager, mark early exit
-                            self.early_exit = true;
-                            return Ok(());
-                        },
-                        Err(e) => return Err(Error::IO(e)),
-                    }
-                },
-                Err(e) if e.kind() == io::ErrorKind::BrokenPipe => {
-                    // User exited pager, mark early exit
-                    self.early_exit = true;
-                    return Ok(());
-                },
-                Err(e) => {
-                    return Err(Error::IO(e));
-                }
+
+        self.emit(text)
+    }
+
+    /// Sends text to the pager process, or to stdout when there is none. A closed pipe
+    /// means the reader quit early, so it stops output instead of failing.
+    fn emit(&mut self, text: &str) -> Result<(), Error> {
+        let result = match self.stdout.as_mut() {
+            Some(stdin) => stdin.write_all(text.as_bytes()).and_then(|_| stdin.flush()),
+            None => {
+                let mut stdout = io::stdout().lock();
+                stdout.write_all(text.as_bytes()).and_then(|_| stdout.flush())
             }
+        };
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => {
+                // User exited pager, mark early exit
+                self.early_exit = true;
+                Ok(())
+            },
+            Err(e) => Err(Error::IO(e)),
         }
-        
-        Ok(())
     }
-    
+
     /// Close the pager and wait for the process to terminate
     pub fn close(&mut self) -> Result<(), Error> {
+        // Output that fit on one screen is written without a pager
+        if let Some(pending) = self.pending.take() {
+            if self.enabled && !self.early_exit {
+                self.emit(&pending)?;
+            }
+        }
+
         // If user already exited pager, just clean up
         if self.early_exit {
             self.enabled = false;
             self.stdout.take();
-            self.process.take();
+            if let Some(mut process) = self.process.take() {
+                let _ = process.wait();
+            }
             return Ok(());
         }
-        
+
         // Only try to close if we have an active process
         if let Some(mut process) = self.process.take() {
             // First, drop the stdin handle to close the pager's input
             self.stdout.take();
-            
+
             // Then wait for the process to terminate
             match process.wait() {
                 Ok(_) => {},
@@ -212,31 +267,37 @@ impl Pager {
                 }
             }
         }
-        
+
         // Make sure we're marked as disabled
         self.enabled = false;
-        
+
         Ok(())
     }
-    
+
     /// Check if the pager exited early (user pressed 'q')
     pub fn exited_early(&self) -> bool {
         self.early_exit
     }
-    
+
     /// Disable the pager
     pub fn disable(&mut self) {
         self.enabled = false;
     }
-    
+
     /// Check if the pager is enabled
     pub fn is_enabled(&self) -> bool {
         self.enabled && !self.early_exit
     }
 }
 
+impl Default for Pager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Drop for Pager {
     fn drop(&mut self) {
         let _ = self.close();
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,72 @@
+/// Expands glob pathspecs (`*`, `**`, `?`) given to commands into concrete paths.
+use std::collections::BTreeSet;
+use regex::Regex;
+use crate::errors::error::Error;
+use crate::core::workspace::{path_key, Workspace};
+
+/// Whether `spec` contains glob metacharacters; other specs are used as literal paths
+pub fn is_glob(spec: &str) -> bool {
+    spec.contains(['*', '?'])
+}
+
+/// Expands every glob in `specs` to the workspace files and `index_paths` it matches, in path
+/// order. Literal specs are passed through unchanged. A glob matching nothing is an error.
+pub fn expand<'a, I>(specs: &[String], workspace: &Workspace, index_paths: I) -> Result<Vec<String>, Error>
+where
+    I: IntoIterator<Item = &'a String>,
+{
+    if !specs.iter().any(|spec| is_glob(spec)) {
+        return Ok(specs.to_vec());
+    }
+
+    // Candidates are only listed once a glob needs them
+    let mut candidates: BTreeSet<String> = index_paths.into_iter().cloned().collect();
+    candidates.extend(workspace.list_files()?.iter().map(|path| path_key(path)));
+
+    let mut expanded = Vec::new();
+    for spec in specs {
+        if !is_glob(spec) {
+            expanded.push(spec.clone());
+            continue;
+        }
+
+        let pattern = compile(spec)
+            .ok_or_else(|| Error::Pattern(format!("invalid pathspec '{}'", spec)))?;
+        let before = expanded.len();
+        expanded.extend(candidates.iter().filter(|path| pattern.is_match(path)).cloned());
+
+        if expanded.len() == before {
+            return Err(Error::Generic(format!("pathspec '{}' did not match any files", spec)));
+        }
+    }
+
+    Ok(expanded)
+}
+
+// Patterns are anchored at the workspace root. `*` and `?` stay within one path component;
+// `**` crosses directories, and `**/` also matches no directory at all
+fn compile(spec: &str) -> Option<Regex> {
+    let spec = spec.trim_start_matches("./").trim_start_matches('/');
+
+    let mut regex = String::from("^");
+    let mut chars = spec.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex.push_str("(.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            _ => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+
+    Regex::new(&regex).ok()
+}
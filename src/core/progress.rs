@@ -0,0 +1,123 @@
+/// Progress reporting for long operations such as checkout and merge.
+/// Operations report through the `Progress` trait; `TerminalProgress` draws a bar on an
+/// interactive terminal and stays silent otherwise.
+use std::cell::Cell;
+use std::env;
+use std::io::Write;
+
+/// Environment variable set by `--progress` ("1") and `--no-progress` ("0")
+pub const PROGRESS_ENV: &str = "ASH_PROGRESS";
+
+/// Width of the drawn bar, in characters
+const BAR_WIDTH: usize = 30;
+
+/// Receives progress from a long operation. Methods take `&self` so a reporter can be shared
+/// by the helpers of one operation; implementations keep their state in cells.
+pub trait Progress {
+    /// Begins a run of `total` units of work
+    fn start(&self, total: usize);
+    /// Records `n` more units as done
+    fn inc(&self, n: usize);
+    /// Ends the current run
+    fn finish(&self);
+    /// Shows a line of information alongside the progress
+    fn message(&self, message: &str);
+}
+
+/// Reports nothing
+pub struct NoProgress;
+
+impl Progress for NoProgress {
+    fn start(&self, _total: usize) {}
+    fn inc(&self, _n: usize) {}
+    fn finish(&self) {}
+    fn message(&self, _message: &str) {}
+}
+
+/// Draws `<label>: <percent> [<bar>] (<done>/<total>)` on stderr, rewriting it in place.
+/// Messages go to stdout above the bar.
+pub struct TerminalProgress {
+    label: String,
+    enabled: bool,
+    total: Cell<usize>,
+    done: Cell<usize>,
+    active: Cell<bool>,
+}
+
+impl TerminalProgress {
+    /// Enabled when `ASH_PROGRESS` says so, otherwise when stdout is a terminal
+    pub fn new(label: &str) -> Self {
+        let enabled = match env::var(PROGRESS_ENV).ok().as_deref() {
+            Some("1") => true,
+            Some("0") => false,
+            _ => atty::is(atty::Stream::Stdout),
+        };
+
+        TerminalProgress {
+            label: label.to_string(),
+            enabled,
+            total: Cell::new(0),
+            done: Cell::new(0),
+            active: Cell::new(false),
+        }
+    }
+
+    fn render(&self) {
+        let total = self.total.get();
+        let done = self.done.get().min(total);
+        let percent = (done * 100).checked_div(total).unwrap_or(100);
+        let filled = (done * BAR_WIDTH).checked_div(total).unwrap_or(BAR_WIDTH);
+
+        let mut stderr = std::io::stderr();
+        let _ = write!(
+            stderr,
+            "\r{}: {:>3}% [{}{}] ({}/{})",
+            self.label, percent, "#".repeat(filled), " ".repeat(BAR_WIDTH - filled), done, total
+        );
+        let _ = stderr.flush();
+    }
+
+    fn clear_line(&self) {
+        let _ = write!(std::io::stderr(), "\r\x1b[K");
+    }
+}
+
+impl Progress for TerminalProgress {
+    fn start(&self, total: usize) {
+        self.total.set(total);
+        self.done.set(0);
+        self.active.set(true);
+        if self.enabled {
+            self.render();
+        }
+    }
+
+    fn inc(&self, n: usize) {
+        self.done.set(self.done.get() + n);
+        if self.enabled && self.active.get() {
+            self.render();
+        }
+    }
+
+    fn finish(&self) {
+        if self.enabled && self.active.get() {
+            self.done.set(self.total.get());
+            self.render();
+            eprintln!(", done.");
+        }
+        self.active.set(false);
+    }
+
+    fn message(&self, message: &str) {
+        if !self.enabled {
+            return;
+        }
+        if self.active.get() {
+            self.clear_line();
+        }
+        println!("{}", message);
+        if self.active.get() {
+            self.render();
+        }
+    }
+}
@@ -1,15 +1,23 @@
 /// Handles reference management for branches, tags, and HEAD in AsheraFlow.
-use std::fs::{self, File};
-use std::io::Read;
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 use regex::Regex;
 use crate::errors::error::Error;
 use crate::core::lockfile::Lockfile;
+use crate::core::config::Config;
+use crate::core::database::author::Author;
+use crate::core::workspace::path_key;
 
 // Constants
 pub const HEAD: &str = "HEAD";
 const DEFAULT_BRANCH: &str = "master";
 const SYMREF_PREFIX: &str = "ref: ";
+const NULL_OID: &str = "0000000000000000000000000000000000000000";
+/// File holding many refs as "<value> <name>" lines, consulted when a ref has no loose file
+const PACKED_REFS: &str = "packed-refs";
+const PACKED_REFS_HEADER: &str = "# pack-refs";
 lazy_static::lazy_static! {
     static ref SYMREF_REGEX: Regex = Regex::new(r"^ref: (.+)$").unwrap();
 }
@@ -21,6 +29,42 @@ pub enum Reference {
     Symbolic(String),     // Symbolic reference to another ref
 }
 
+/// A single reflog line: a ref moving from `old_oid` to `new_oid`.
+#[derive(Debug, Clone)]
+pub struct ReflogEntry {
+    pub old_oid: String,
+    pub new_oid: String,
+    pub committer: Author,
+    pub message: String,
+}
+
+impl ReflogEntry {
+    /// Parse a line of the form "<old-oid> <new-oid> <identity>\t<message>"
+    fn parse(line: &str) -> Option<Self> {
+        let (head, message) = match line.split_once('\t') {
+            Some((head, message)) => (head, message),
+            None => (line, ""),
+        };
+        
+        let mut parts = head.splitn(3, ' ');
+        let old_oid = parts.next()?.to_string();
+        let new_oid = parts.next()?.to_string();
+        let committer = Author::parse(parts.next()?).ok()?;
+        
+        Some(ReflogEntry {
+            old_oid,
+            new_oid,
+            committer,
+            message: message.to_string(),
+        })
+    }
+    
+    /// Serialize the entry back to its reflog line (without the trailing newline)
+    fn to_line(&self) -> String {
+        format!("{} {} {}\t{}", self.old_oid, self.new_oid, self.committer, self.message)
+    }
+}
+
 /// Custom errors for reference management.
 #[derive(Debug)]
 pub enum RefError {
@@ -33,6 +77,10 @@ pub struct Refs {
     pathname: PathBuf,
     refs_path: PathBuf,
     heads_path: PathBuf,
+    tags_path: PathBuf,
+    /// Another repository directory, such as a `.git`, whose HEAD and refs are read when
+    /// missing here. It is never written.
+    fallback: Option<Box<Refs>>,
 }
 
 impl Refs {
@@ -41,19 +89,31 @@ impl Refs {
         let path = pathname.as_ref().to_path_buf();
         let refs_path = path.join("refs");
         let heads_path = refs_path.join("heads");
+        let tags_path = refs_path.join("tags");
         
         Refs {
             pathname: path,
             refs_path,
             heads_path,
+            tags_path,
+            fallback: None,
         }
     }
 
+    /// Resolve HEAD and refs not found in this repository from `git_dir`, read-only.
+    /// Git stores loose refs, symrefs and packed-refs in the same layout as ash.
+    pub fn set_fallback<P: AsRef<Path>>(&mut self, git_dir: P) {
+        self.fallback = Some(Box::new(Refs::new(git_dir)));
+    }
+
     /// Reads the HEAD reference, following symbolic references.
     pub fn read_head(&self) -> Result<Option<String>, Error> {
         let head_path = self.pathname.join(HEAD);
         if !head_path.exists() {
-            return Ok(None);
+            return match &self.fallback {
+                Some(fallback) => fallback.read_head(),
+                None => Ok(None),
+            };
         }
         
         self.read_symref(&head_path)
@@ -61,27 +121,155 @@ impl Refs {
 
     /// Sets HEAD to point to a branch or commit.
     pub fn set_head(&self, revision: &str, oid: &str) -> Result<(), Error> {
-        let head_path = self.pathname.join(HEAD);
-        let branch_path = self.heads_path.join(revision);
-        
-        if File::open(&branch_path).is_ok() {
-            // If the revision is a valid branch name, create a symbolic ref
-            let relative = branch_path.strip_prefix(&self.pathname)
-                .map_err(|_| Error::PathResolution(format!(
-                    "Failed to create relative path from '{}' to '{}'",
-                    self.pathname.display(), branch_path.display()
-                )))?;
-                
-            self.update_ref_file(&head_path, &format!("{}{}", SYMREF_PREFIX, relative.display()))
+        if self.branch_exists(revision)? {
+            // If the revision is a branch name, create a symbolic ref
+            self.set_head_symbolic(&format!("refs/heads/{}", revision))
         } else {
             // Otherwise, store the commit ID directly
-            self.update_ref_file(&head_path, oid)
+            self.update_head_detached(oid)
         }
     }
+    
+    /// Attaches HEAD to a ref such as `refs/heads/<branch>`.
+    pub fn set_head_symbolic(&self, ref_name: &str) -> Result<(), Error> {
+        self.update_ref_file(&self.pathname.join(HEAD), &format!("{}{}", SYMREF_PREFIX, ref_name))
+    }
+    
+    /// Detaches HEAD, writing the commit ID into it directly.
+    pub fn update_head_detached(&self, oid: &str) -> Result<(), Error> {
+        self.update_ref_file(&self.pathname.join(HEAD), oid)
+    }
+    
+    /// Whether `refs/heads/<name>` exists, loose or packed.
+    pub fn branch_exists(&self, name: &str) -> Result<bool, Error> {
+        Ok(self.read_ref(&format!("refs/heads/{}", name))?.is_some())
+    }
 
-    // Update HEAD, following symbolic references
-    pub fn update_head(&self, oid: &str) -> Result<(), Error> {
-        self.update_symref(&self.pathname.join(HEAD), oid)
+    // Update HEAD, following symbolic references, and record the move in the reflog
+    pub fn update_head(&self, oid: &str, message: &str) -> Result<(), Error> {
+        let old_oid = self.read_head()?;
+        let current = self.current_ref()?;
+        
+        self.update_symref(&self.pathname.join(HEAD), oid)?;
+        
+        self.append_reflog(HEAD, old_oid.as_deref(), oid, message)?;
+        if let Reference::Symbolic(path) = current {
+            if path != HEAD {
+                self.append_reflog(&path, old_oid.as_deref(), oid, message)?;
+            }
+        }
+        
+        Ok(())
+    }
+    
+    // Append an entry to the reflog of a ref ("HEAD" or "refs/heads/<branch>")
+    pub fn append_reflog(&self, ref_name: &str, old_oid: Option<&str>, new_oid: &str, message: &str) -> Result<(), Error> {
+        let log_path = self.reflog_path(ref_name);
+        
+        if let Some(parent) = log_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| {
+                Error::DirectoryCreation(format!(
+                    "Failed to create directory '{}': {}",
+                    parent.display(),
+                    e
+                ))
+            })?;
+        }
+        
+        // Keep each entry on a single line
+        let message = message.lines().next().unwrap_or("");
+        let line = format!(
+            "{} {} {}\t{}\n",
+            old_oid.unwrap_or(NULL_OID),
+            new_oid,
+            self.reflog_identity(),
+            message
+        );
+        
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&log_path)?;
+        file.write_all(line.as_bytes())?;
+        
+        Ok(())
+    }
+    
+    // Read all reflog entries of a ref, oldest first
+    pub fn read_reflog(&self, ref_name: &str) -> Result<Vec<ReflogEntry>, Error> {
+        let log_path = self.reflog_path(ref_name);
+        
+        if !log_path.exists() {
+            return Ok(Vec::new());
+        }
+        
+        let content = fs::read_to_string(&log_path)?;
+        
+        Ok(content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(ReflogEntry::parse)
+            .collect())
+    }
+    
+    // Replace the whole reflog of a ref with the given entries (oldest first)
+    pub fn rewrite_reflog(&self, ref_name: &str, entries: &[ReflogEntry]) -> Result<(), Error> {
+        let log_path = self.reflog_path(ref_name);
+        
+        if entries.is_empty() {
+            if log_path.exists() {
+                fs::remove_file(&log_path)?;
+            }
+            return Ok(());
+        }
+        
+        let content: String = entries.iter()
+            .map(|entry| format!("{}\n", entry.to_line()))
+            .collect();
+        
+        fs::write(&log_path, content)?;
+        Ok(())
+    }
+    
+    // Location of the reflog for a ref; bare names are treated as branches,
+    // unless only a top-level ref of that name (such as "stash") has a log
+    fn reflog_path(&self, ref_name: &str) -> PathBuf {
+        let logs_path = self.pathname.join("logs");
+        
+        if ref_name == HEAD || ref_name == "@" {
+            logs_path.join(HEAD)
+        } else if ref_name.starts_with("refs/") {
+            logs_path.join(ref_name)
+        } else {
+            let branch_log = logs_path.join("refs").join("heads").join(ref_name);
+            let top_level_log = logs_path.join("refs").join(ref_name);
+            if !branch_log.exists() && top_level_log.exists() {
+                top_level_log
+            } else {
+                branch_log
+            }
+        }
+    }
+    
+    // Delete a ref file such as "refs/stash"
+    pub fn delete_ref(&self, name: &str) -> Result<(), Error> {
+        let ref_path = if name.starts_with("refs/") {
+            self.pathname.join(name)
+        } else {
+            self.refs_path.join(name)
+        };
+        
+        if ref_path.exists() {
+            fs::remove_file(&ref_path)?;
+        }
+        self.remove_packed_ref(&ref_path)?;
+        
+        Ok(())
+    }
+    
+    // Identity recorded in reflog entries
+    fn reflog_identity(&self) -> Author {
+        Config::load(&self.pathname).identity(&["GIT_COMMITTER", "GIT_AUTHOR"])
     }
     
     // Update a reference directly with an OID
@@ -119,14 +307,41 @@ impl Refs {
         
         // Check if branch already exists
         let branch_path = self.heads_path.join(branch_name);
-        if branch_path.exists() {
+        if self.read_oid_or_symref(&branch_path)?.is_some() {
             return Err(Error::Generic(format!(
                 "A branch named '{}' already exists.", branch_name
             )));
         }
         
         // Create the branch reference file
-        self.update_ref_file(&branch_path, oid)
+        self.update_ref_file(&branch_path, oid)?;
+        
+        let short_oid = &oid[0..std::cmp::min(7, oid.len())];
+        self.append_reflog(branch_name, None, oid, &format!("branch: Created from {}", short_oid))
+    }
+    
+    // Point HEAD at a branch that has no commits yet, so the next commit becomes a root commit
+    pub fn set_head_to_unborn_branch(&self, branch_name: &str) -> Result<(), Error> {
+        if !self.is_valid_branch_name(branch_name) {
+            return Err(Error::Generic(format!(
+                "'{}' is not a valid branch name.", branch_name
+            )));
+        }
+        
+        let branch_path = self.heads_path.join(branch_name);
+        if self.read_oid_or_symref(&branch_path)?.is_some() {
+            return Err(Error::Generic(format!(
+                "A branch named '{}' already exists.", branch_name
+            )));
+        }
+        
+        let relative = branch_path.strip_prefix(&self.pathname)
+            .map_err(|_| Error::PathResolution(format!(
+                "Failed to create relative path from '{}' to '{}'",
+                self.pathname.display(), branch_path.display()
+            )))?;
+        
+        self.update_ref_file(&self.pathname.join(HEAD), &format!("{}{}", SYMREF_PREFIX, relative.display()))
     }
     
     // Read a reference by name (branch, HEAD, etc.)
@@ -140,10 +355,14 @@ impl Refs {
         // 1. Direct under .ash directory
         // 2. Under .ash/refs
         // 3. Under .ash/refs/heads (branches)
+        // 4. Under .ash/refs/tags (tags)
+        // 5. Under .ash/refs/remotes (remote-tracking branches)
         let paths = [
             self.pathname.join(name),
             self.refs_path.join(name),
             self.heads_path.join(name),
+            self.tags_path.join(name),
+            self.refs_path.join("remotes").join(name),
         ];
         
         for path in &paths {
@@ -152,14 +371,30 @@ impl Refs {
             }
         }
         
-        // Reference not found
-        Ok(None)
+        // Fall back to packed refs, in the same order
+        let packed = self.read_packed_refs()?;
+        for path in &paths {
+            if let Some(value) = self.ref_name(path).and_then(|name| packed.get(&name)) {
+                return Ok(Some(value.clone()));
+            }
+        }
+        
+        // Reference not found here; try the read-only fallback, if any
+        match &self.fallback {
+            Some(fallback) => fallback.read_ref(name),
+            None => Ok(None),
+        }
     }
     
     // Read a reference file and parse as OID or symref
     fn read_oid_or_symref(&self, path: &Path) -> Result<Option<Reference>, Error> {
         if !path.exists() {
-            return Ok(None);
+            // A ref without a loose file may still be packed
+            let packed = match self.ref_name(path) {
+                Some(name) => self.read_packed_refs()?.remove(&name),
+                None => None,
+            };
+            return Ok(packed.map(Reference::Direct));
         }
         
         let mut file = match File::open(path) {
@@ -236,7 +471,8 @@ impl Refs {
         lockfile.commit_ref()
             .map_err(|e| Error::Generic(format!("Commit error: {:?}", e)))?;
         
-        Ok(())
+        // The loose file now holds the ref, so drop any packed copy
+        self.remove_packed_ref(path)
     }
     
     // Update a symref, following it to its target
@@ -269,24 +505,34 @@ impl Refs {
                     .map_err(|e| Error::Generic(format!("Write error: {:?}", e)))?;
                 
                 lockfile.commit_ref()
-                    .map_err(|e| Error::Generic(format!("Commit error: {:?}", e)))
+                    .map_err(|e| Error::Generic(format!("Commit error: {:?}", e)))?;
+                
+                self.remove_packed_ref(path)
             }
         }
     }
     
-    // Check if a branch name is valid (not matching the invalid patterns)
-    fn is_valid_branch_name(&self, name: &str) -> bool {
-        // Define invalid patterns for branch names
+    // Check if a branch name is valid: a valid ref name that can't be mistaken for HEAD or an option
+    pub fn is_valid_branch_name(&self, name: &str) -> bool {
+        Self::is_valid_refname(name) && name != HEAD && !name.starts_with('-')
+    }
+    
+    /// Whether `name` is a well-formed ref name (the part after `refs/heads/` or `refs/tags/`).
+    /// Slash-separated components may not be empty, start with `.` or end with `.lock`; the name
+    /// may not contain `..`, `@{`, control characters, spaces or any of `~^:?*[\`, nor end with
+    /// `.` or be a lone `@`.
+    pub fn is_valid_refname(name: &str) -> bool {
         lazy_static::lazy_static! {
             static ref INVALID_NAME: Regex = Regex::new(r"(?x)
-                ^\.|              # starts with .
-                /\.|              # contains a path component starting with .
-                \.\.|             # contains ..
                 ^/|               # starts with /
                 /$|               # ends with /
-                /|                # contains slash anywhere
-                \.lock$|          # ends with .lock
+                //|               # contains an empty component
+                (^|/)\.|          # a component starts with .
+                \.lock(/|$)|      # a component ends with .lock
+                \.\.|             # contains ..
+                \.$|              # ends with .
                 @\{|              # contains @{
+                ^@$|              # is a lone @
                 [\x00-\x20*:?\[\\\^~\x7f] # contains control chars or special chars
             ").unwrap();
         }
@@ -330,6 +576,11 @@ impl Refs {
             path_buf.strip_prefix("refs/heads/")
                 .map(|p| p.to_string_lossy().to_string())
                 .unwrap_or_else(|_| path.to_string())
+        } else if path_buf.starts_with("refs/tags/") {
+            // Remove refs/tags/ prefix for tag names
+            path_buf.strip_prefix("refs/tags/")
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_else(|_| path.to_string())
         } else {
             path.to_string()
         }
@@ -340,8 +591,71 @@ impl Refs {
         self.list_refs(&self.heads_path)
     }
     
+    // List all tags in the repository
+    pub fn list_tags(&self) -> Result<Vec<Reference>, Error> {
+        self.list_refs(&self.tags_path)
+    }
+    
+    // Create a tag pointing at the given object (a commit or an annotated tag object)
+    pub fn create_tag(&self, tag_name: &str, oid: &str) -> Result<(), Error> {
+        if !Self::is_valid_refname(tag_name) {
+            return Err(Error::Generic(format!(
+                "'{}' is not a valid tag name.", tag_name
+            )));
+        }
+        
+        let tag_path = self.tags_path.join(tag_name);
+        if self.read_oid_or_symref(&tag_path)?.is_some() {
+            return Err(Error::Generic(format!(
+                "tag '{}' already exists", tag_name
+            )));
+        }
+        
+        self.update_ref_file(&tag_path, oid)
+    }
+    
+    // Delete a tag and return the OID it pointed to
+    pub fn delete_tag(&self, tag_name: &str) -> Result<String, Error> {
+        let tag_path = self.tags_path.join(tag_name);
+        
+        let oid = match self.read_symref(&tag_path)? {
+            Some(oid) => oid,
+            None => {
+                return Err(Error::Generic(format!(
+                    "tag '{}' not found.", tag_name
+                )));
+            }
+        };
+        
+        if tag_path.exists() {
+            fs::remove_file(&tag_path)
+                .map_err(Error::IO)?;
+            self.delete_parent_directories(&tag_path)?;
+        }
+        self.remove_packed_ref(&tag_path)?;
+        
+        Ok(oid)
+    }
+    
     // List all refs in a directory, recursively
     fn list_refs(&self, dir: &Path) -> Result<Vec<Reference>, Error> {
+        let mut refs = self.list_loose_refs(dir)?;
+        
+        if let Some(dir_name) = self.ref_name(dir) {
+            let prefix = format!("{}/", dir_name);
+            for name in self.read_packed_refs()?.into_keys() {
+                let reference = Reference::Symbolic(name.clone());
+                if name.starts_with(&prefix) && !refs.contains(&reference) {
+                    refs.push(reference);
+                }
+            }
+        }
+        
+        Ok(refs)
+    }
+    
+    // List the loose ref files in a directory, recursively
+    fn list_loose_refs(&self, dir: &Path) -> Result<Vec<Reference>, Error> {
         if !dir.exists() {
             return Ok(Vec::new());
         }
@@ -356,7 +670,7 @@ impl Refs {
                         
                         if path.is_dir() {
                             // Recursively list refs in subdirectories
-                            let mut subrefs = self.list_refs(&path)?;
+                            let mut subrefs = self.list_loose_refs(&path)?;
                             refs.append(&mut subrefs);
                         } else {
                             // Add this file as a reference
@@ -400,9 +714,18 @@ impl Refs {
             }
         };
         
-        // Delete the branch file
-        fs::remove_file(&branch_path)
-            .map_err(|e| Error::IO(e))?;
+        // Delete the branch file and any packed copy
+        if branch_path.exists() {
+            fs::remove_file(&branch_path)
+                .map_err(Error::IO)?;
+        }
+        self.remove_packed_ref(&branch_path)?;
+        
+        // Drop the branch's reflog along with it
+        let log_path = self.reflog_path(branch_name);
+        if log_path.exists() {
+            fs::remove_file(&log_path)?;
+        }
             
         // Clean up empty parent directories
         self.delete_parent_directories(&branch_path)?;
@@ -413,13 +736,79 @@ impl Refs {
         Ok(oid)
     }
     
+    /// Renames `refs/heads/<old_name>` to `refs/heads/<new_name>`, carrying its reflog along and
+    /// re-pointing HEAD if it was on the old branch. An existing `new_name` is only replaced with
+    /// `force`, and never when it is the current branch. Returns the branch's OID.
+    pub fn rename_branch(&self, old_name: &str, new_name: &str, force: bool) -> Result<String, Error> {
+        if !self.is_valid_branch_name(new_name) {
+            return Err(Error::Generic(format!(
+                "'{}' is not a valid branch name.", new_name
+            )));
+        }
+        
+        let old_ref = format!("refs/heads/{}", old_name);
+        let new_ref = format!("refs/heads/{}", new_name);
+        
+        let oid = self.read_ref(&old_ref)?.ok_or_else(|| Error::Generic(format!(
+            "Branch '{}' not found.", old_name
+        )))?;
+        
+        let current = self.current_ref()?;
+        let on_branch = |name: &str| matches!(&current, Reference::Symbolic(path) if *path == name);
+        
+        if old_name != new_name && self.branch_exists(new_name)? {
+            if !force {
+                return Err(Error::Generic(format!(
+                    "A branch named '{}' already exists.", new_name
+                )));
+            }
+            if on_branch(&new_ref) {
+                return Err(Error::Generic(format!(
+                    "Cannot force update the current branch '{}'.", new_name
+                )));
+            }
+        }
+        
+        // Keep the old reflog in memory while the ref itself moves
+        let old_log = self.reflog_path(&old_ref);
+        let log = if old_log.exists() { Some(fs::read(&old_log)?) } else { None };
+        
+        // Remove the old branch, loose and packed
+        let old_path = self.heads_path.join(old_name);
+        if old_path.exists() {
+            fs::remove_file(&old_path)?;
+        }
+        self.remove_packed_ref(&old_path)?;
+        self.delete_parent_directories(&old_path)?;
+        if log.is_some() {
+            fs::remove_file(&old_log)?;
+        }
+        
+        // Write the new branch with the old history
+        self.update_ref_file(&self.heads_path.join(new_name), &oid)?;
+        if let Some(log) = log {
+            let new_log = self.reflog_path(&new_ref);
+            if let Some(parent) = new_log.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&new_log, log)?;
+        }
+        self.append_reflog(&new_ref, Some(&oid), &oid, &format!("Branch: renamed {} to {}", old_ref, new_ref))?;
+        
+        if on_branch(&old_ref) {
+            self.set_head_symbolic(&new_ref)?;
+        }
+        
+        Ok(oid)
+    }
+    
     // Delete empty parent directories after removing a branch
     fn delete_parent_directories(&self, path: &Path) -> Result<(), Error> {
         let mut current = path.parent().map(|p| p.to_path_buf());
         
         while let Some(dir) = current {
-            // Stop if we've reached the .git/refs/heads directory
-            if dir == self.heads_path {
+            // Stop if we've reached the .git/refs/heads or .git/refs/tags directory
+            if dir == self.heads_path || dir == self.tags_path || dir == self.refs_path {
                 break;
             }
             
@@ -443,6 +832,117 @@ impl Refs {
         Ok(())
     }
     
+    /// Moves every loose ref under `refs/` into the packed-refs file and deletes the loose
+    /// files. Symbolic refs stay loose. Returns the number of refs packed.
+    pub fn pack_refs(&self) -> Result<usize, Error> {
+        let mut packed = self.read_packed_refs()?;
+        let mut packed_paths = Vec::new();
+        
+        for reference in self.list_loose_refs(&self.refs_path)? {
+            let name = match reference {
+                Reference::Symbolic(name) => name,
+                Reference::Direct(_) => continue,
+            };
+            // Skip lock files left by interrupted updates
+            if name.ends_with(".lock") {
+                continue;
+            }
+            
+            let path = self.pathname.join(&name);
+            if let Some(Reference::Direct(value)) = self.read_oid_or_symref(&path)? {
+                packed.insert(self.ref_name(&path).unwrap_or(name), value);
+                packed_paths.push(path);
+            }
+        }
+        
+        if packed_paths.is_empty() {
+            return Ok(0);
+        }
+        
+        // Write the packed file before deleting anything, so no ref is ever missing
+        self.write_packed_refs(&packed)?;
+        
+        for path in &packed_paths {
+            fs::remove_file(path)?;
+            self.delete_parent_directories(path)?;
+        }
+        
+        Ok(packed_paths.len())
+    }
+    
+    // Name of the ref stored at `path`, e.g. "refs/heads/master"
+    fn ref_name(&self, path: &Path) -> Option<String> {
+        path.strip_prefix(&self.pathname)
+            .ok()
+            .map(path_key)
+    }
+    
+    // Read the packed-refs file as a map of ref names to values
+    fn read_packed_refs(&self) -> Result<BTreeMap<String, String>, Error> {
+        let content = match fs::read_to_string(self.pathname.join(PACKED_REFS)) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+            Err(e) => return Err(Error::IO(e)),
+        };
+        
+        Ok(content.lines()
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| line.split_once(' '))
+            .map(|(value, name)| (name.to_string(), value.to_string()))
+            .collect())
+    }
+    
+    // Replace the packed-refs file with `refs`, removing it when empty
+    fn write_packed_refs(&self, refs: &BTreeMap<String, String>) -> Result<(), Error> {
+        let path = self.pathname.join(PACKED_REFS);
+        let mut lockfile = Lockfile::new(&path);
+        
+        let acquired = lockfile.hold_for_update()
+            .map_err(|e| Error::Generic(format!("Lock error: {:?}", e)))?;
+        
+        if !acquired {
+            return Err(Error::Generic(format!(
+                "Could not acquire lock on '{}'", path.display()
+            )));
+        }
+        
+        if refs.is_empty() {
+            lockfile.rollback()?;
+            if path.exists() {
+                fs::remove_file(&path)?;
+            }
+            return Ok(());
+        }
+        
+        let mut content = format!("{}\n", PACKED_REFS_HEADER);
+        for (name, value) in refs {
+            content.push_str(&format!("{} {}\n", value, name));
+        }
+        
+        lockfile.write(&content)
+            .map_err(|e| Error::Generic(format!("Write error: {:?}", e)))?;
+        
+        lockfile.commit_ref()
+            .map_err(|e| Error::Generic(format!("Commit error: {:?}", e)))?;
+        
+        Ok(())
+    }
+    
+    // Drop the packed copy of the ref stored at `path`, if there is one
+    fn remove_packed_ref(&self, path: &Path) -> Result<(), Error> {
+        let name = match self.ref_name(path) {
+            Some(name) => name,
+            None => return Ok(()),
+        };
+        
+        let mut packed = self.read_packed_refs()?;
+        if packed.remove(&name).is_some() {
+            self.write_packed_refs(&packed)?;
+        }
+        
+        Ok(())
+    }
+    
     // List all refs with a specific prefix
     pub fn list_refs_with_prefix(&self, prefix: &str) -> Result<Vec<Reference>, Error> {
         let mut refs = Vec::new();
@@ -461,13 +961,17 @@ impl Refs {
             }
         };
         
-        // Check if directory exists
-        if !prefix_dir.exists() {
-            return Ok(refs);
+        // Read references from directory, recursively
+        if prefix_dir.exists() {
+            self.read_refs_with_prefix(&prefix_dir, prefix, &mut refs)?;
         }
         
-        // Read references from directory, recursively
-        self.read_refs_with_prefix(&prefix_dir, prefix, &mut refs)?;
+        for name in self.read_packed_refs()?.into_keys() {
+            let reference = Reference::Symbolic(name.clone());
+            if name.starts_with(prefix) && !refs.contains(&reference) {
+                refs.push(reference);
+            }
+        }
         
         Ok(refs)
     }
@@ -512,4 +1016,47 @@ impl Refs {
         
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const OID: &str = "06b2ff61bd0f514093ae6808e64b66c939f20aeb";
+    
+    #[test]
+    fn packed_refs_keep_name_valued_entries_readable() {
+        let dir = tempfile::tempdir().unwrap();
+        let refs = Refs::new(dir.path());
+        refs.update_ref("refs/heads/master", OID).unwrap();
+        refs.update_ref("refs/upstream/feature", "master").unwrap();
+        
+        assert_eq!(refs.pack_refs().unwrap(), 2);
+        assert!(!dir.path().join("refs/upstream/feature").exists());
+        assert_eq!(refs.read_ref("refs/upstream/feature").unwrap().as_deref(), Some("master"));
+        assert_eq!(refs.read_ref("refs/heads/master").unwrap().as_deref(), Some(OID));
+        
+        refs.delete_ref("refs/upstream/feature").unwrap();
+        assert_eq!(refs.read_ref("refs/upstream/feature").unwrap(), None);
+        assert_eq!(refs.read_ref("master").unwrap().as_deref(), Some(OID));
+    }
+
+    #[test]
+    fn git_refs_resolve_read_only_through_the_fallback() {
+        let dir = tempfile::tempdir().unwrap();
+        let git_dir = dir.path().join(".git");
+        fs::create_dir_all(git_dir.join("refs").join("heads")).unwrap();
+        fs::write(git_dir.join("HEAD"), "ref: refs/heads/main\n").unwrap();
+        fs::write(git_dir.join("refs").join("heads").join("main"), format!("{}\n", OID)).unwrap();
+        fs::write(git_dir.join(PACKED_REFS), format!("# pack-refs with: peeled fully-peeled sorted \n{} refs/tags/v1\n^{}\n", OID, OID)).unwrap();
+
+        let mut refs = Refs::new(dir.path().join(".ash"));
+        refs.set_fallback(&git_dir);
+
+        assert_eq!(refs.read_head().unwrap().as_deref(), Some(OID));
+        assert_eq!(refs.read_ref("main").unwrap().as_deref(), Some(OID));
+        assert_eq!(refs.read_ref("v1").unwrap().as_deref(), Some(OID));
+        assert_eq!(refs.read_ref("missing").unwrap(), None);
+        assert!(!dir.path().join(".ash").exists());
+    }
+}
@@ -5,7 +5,7 @@ use crate::errors::error::Error;
 use crate::core::database::blob::Blob;
 use crate::core::database::entry::DatabaseEntry;
 use crate::core::index::entry::Entry;
-use crate::core::workspace::Workspace;
+use crate::core::workspace::{path_key, WalkEntry, Workspace};
 use crate::core::index::index::Index;
 use crate::core::database::database::{Database, GitObject};
 
@@ -56,51 +56,26 @@ impl<'a> Inspector<'a> {
         Ok(false)
     }
     
-    /// Check if a directory contains any untracked files
+    /// Check if a directory contains any untracked files; `dir_path` is relative to the workspace root
     fn directory_contains_untracked(&self, dir_path: &Path) -> Result<bool, Error> {
-        if !dir_path.is_dir() {
-            return Ok(false);
-        } 
-        // Get all entries in the directory
-        match std::fs::read_dir(dir_path) {
-            Ok(entries) => {
-                for entry in entries {
-                    match entry {
-                        Ok(entry) => {
-                            let path = entry.path();
-                            let file_name = entry.file_name();
-                            
-                            // Skip hidden files and .ash directory
-                            let name_str = file_name.to_string_lossy();
-                            if name_str.starts_with('.') || name_str == ".ash" {
-                                continue;
-                            }
-                            
-                            // Get relative path from workspace root
-                            let rel_path = match path.strip_prefix(&self.workspace.root_path) {
-                                Ok(p) => p,
-                                Err(_) => continue,
-                            };
-                            
-                            let rel_path_str = rel_path.to_string_lossy().to_string();
-                            
-                            // If it's a file not in the index, it's untracked
-                            if path.is_file() && !self.index.tracked(&rel_path_str) {
-                                return Ok(true);
-                            } else if path.is_dir() {
-                                // Recursively check subdirectories
-                                if self.directory_contains_untracked(&path)? {
-                                    return Ok(true);
-                                }
-                            }
-                        },
-                        Err(_) => continue,
-                    }
-                }
-                Ok(false)
-            },
-            Err(e) => Err(Error::IO(e)),
+        // Skip hidden files and directories
+        let is_hidden = |entry: &WalkEntry| {
+            entry.path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.'))
+        };
+        
+        for entry in self.workspace.walk(dir_path, |entry| !is_hidden(entry))? {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(_) => continue,
+            };
+            
+            // If it's a file not in the index, it's untracked
+            if entry.metadata.is_file() && !is_hidden(&entry) && !self.index.tracked(&path_key(&entry.path)) {
+                return Ok(true);
+            }
         }
+        
+        Ok(false)
     }
     
     /// Compare an index entry to a file in the workspace
@@ -130,7 +105,8 @@ impl<'a> Inspector<'a> {
             }
         };
         
-        // Calculate hash of content
+        // Calculate hash of content, normalized the way add stores it
+        let data = self.workspace.normalize_eol(&entry.path, data);
         let actual_oid = self.database.hash_file_data(&data);
         
         // Compare with expected OID
@@ -178,15 +154,12 @@ impl<'a> Inspector<'a> {
 
     /// Read a file from the workspace and compare with a stored blob
     pub fn compare_workspace_vs_blob(&self, path: &Path, oid: &str) -> Result<bool, Error> {
-        // Read the file from workspace
-        let workspace_data = match self.workspace.read_file(path) {
-            Ok(data) => data,
+        // Hash the file from workspace
+        let workspace_oid = match self.workspace.hash_file(path) {
+            Ok(oid) => oid,
             Err(_) => return Ok(true), // If we can't read, consider it different
         };
         
-        // Calculate hash
-        let workspace_oid = self.database.hash_file_data(&workspace_data);
-        
         // Compare OIDs
         let matches = workspace_oid == oid;
         
@@ -4,10 +4,12 @@ use std::path::{Path, PathBuf};
 use crate::core::database::blob::Blob;
 use crate::core::database::tree::{Tree, TreeEntry};
 use crate::core::file_mode::FileMode;
+use crate::core::workspace::path_key;
 use crate::errors::error::Error;
 use crate::core::repository::repository::Repository;
 use crate::core::database::entry::DatabaseEntry;
 use crate::core::repository::inspector::{Inspector, ChangeType};
+use crate::core::progress::Progress;
 
 // Define conflict types for different error scenarios
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
@@ -66,7 +68,9 @@ impl<'a> Migration<'a> {
         println!("Force flag applied - ignoring potential conflicts");
     }
 
-    pub fn apply_changes(&mut self) -> Result<(), Error> {
+    /// Checks for conflicts, then updates the workspace and index, reporting each applied
+    /// change to `progress`
+    pub fn apply_changes(&mut self, progress: &dyn Progress) -> Result<(), Error> {
         // Analyze changes using Inspector to detect conflicts
         self.analyze_changes()?;
         
@@ -77,18 +81,16 @@ impl<'a> Migration<'a> {
         }
         
         // Apply the planned changes
-        self.execute_changes()?;
+        self.execute_changes(progress)?;
         
         // Final phase: perform a comprehensive cleanup of empty directories
-        self.cleanup_empty_directories()?;
+        self.cleanup_empty_directories(progress)?;
         
         Ok(())
     }
     
     // New method to perform more comprehensive directory cleanup
-    fn cleanup_empty_directories(&mut self) -> Result<(), Error> {
-        println!("Performing final empty directory cleanup");
-        
+    fn cleanup_empty_directories(&mut self, progress: &dyn Progress) -> Result<(), Error> {
         // First get all directories that exist in the workspace
         let workspace_dirs = self.find_all_workspace_directories()?;
         
@@ -128,38 +130,26 @@ impl<'a> Migration<'a> {
             };
             
             if is_effectively_empty {
-                println!("Removing empty directory in final cleanup: {}", dir.display());
-                
                 // First try normal directory removal
-                match std::fs::remove_dir(&full_path) {
-                    Ok(_) => {
-                        println!("Successfully removed empty directory: {}", dir.display());
-                    },
-                    Err(e) => {
-                        // If that fails, try force removal for directories that might have hidden files
-                        println!("Standard removal failed, trying force removal: {} - {}", dir.display(), e);
-                        
-                        // First remove any hidden files
-                        if let Ok(entries) = std::fs::read_dir(&full_path) {
-                            for entry in entries.filter_map(Result::ok) {
-                                let entry_path = entry.path();
-                                let name = entry.file_name();
-                                let name_str = name.to_string_lossy();
-                                
-                                if name_str.starts_with('.') && entry_path.is_file() {
-                                    if let Err(e) = std::fs::remove_file(&entry_path) {
-                                        println!("Warning: Failed to remove hidden file: {} - {}", entry_path.display(), e);
-                                    }
+                if std::fs::remove_dir(&full_path).is_err() {
+                    // If that fails, remove any hidden files and try again
+                    if let Ok(entries) = std::fs::read_dir(&full_path) {
+                        for entry in entries.filter_map(Result::ok) {
+                            let entry_path = entry.path();
+                            let name = entry.file_name();
+                            let name_str = name.to_string_lossy();
+                            
+                            if name_str.starts_with('.') && entry_path.is_file() {
+                                if let Err(e) = std::fs::remove_file(&entry_path) {
+                                    progress.message(&format!("warning: failed to remove hidden file: {} - {}", entry_path.display(), e));
                                 }
                             }
                         }
-                        
-                        // Try removal again
-                        if let Err(e) = std::fs::remove_dir(&full_path) {
-                            println!("Warning: Still could not remove directory: {} - {}", dir.display(), e);
-                        } else {
-                            println!("Successfully removed directory after clearing hidden files: {}", dir.display());
-                        }
+                    }
+                    
+                    // Try removal again
+                    if let Err(e) = std::fs::remove_dir(&full_path) {
+                        progress.message(&format!("warning: could not remove directory: {} - {}", dir.display(), e));
                     }
                 }
             }
@@ -170,66 +160,21 @@ impl<'a> Migration<'a> {
 
     fn find_all_workspace_directories(&self) -> Result<HashSet<PathBuf>, Error> {
         let mut dirs = HashSet::new();
-        let root_path = &self.repo.workspace.root_path;
-        
-        // Skip .ash directory
-        let git_dir = root_path.join(".ash");
+        dirs.insert(PathBuf::new());
         
-        self.collect_directories_recursive(root_path, &PathBuf::new(), &mut dirs, &git_dir)?;
+        // Skip hidden directories (the walk never enters .ash)
+        let walk = self.repo.workspace.walk(Path::new(""), |entry| {
+            !entry.path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.'))
+        })?;
         
-        Ok(dirs)
-    }
-    
-    // Helper to recursively collect directories
-    fn collect_directories_recursive(
-        &self, 
-        full_path: &Path, 
-        rel_path: &Path, 
-        dirs: &mut HashSet<PathBuf>,
-        git_dir: &Path
-    ) -> Result<(), Error> {
-        // Skip if this is the .ash directory
-        if full_path == git_dir {
-            return Ok(());
-        }
-        
-        // Skip if path doesn't exist or isn't a directory
-        if !full_path.exists() || !full_path.is_dir() {
-            return Ok(());
-        }
-        
-        // Add this directory
-        dirs.insert(rel_path.to_path_buf());
-        
-        // Process subdirectories
-        if let Ok(entries) = std::fs::read_dir(full_path) {
-            for entry_result in entries {
-                if let Ok(entry) = entry_result {
-                    let entry_path = entry.path();
-                    let entry_name = entry.file_name();
-                    
-                    // Skip hidden directories
-                    if entry_name.to_string_lossy().starts_with('.') {
-                        continue;
-                    }
-                    
-                    // Only process directories
-                    if entry_path.is_dir() {
-                        // Get relative path
-                        let entry_rel_path = if rel_path.as_os_str().is_empty() {
-                            PathBuf::from(entry_name)
-                        } else {
-                            rel_path.join(entry_name)
-                        };
-                        
-                        // Recursively collect this directory
-                        self.collect_directories_recursive(&entry_path, &entry_rel_path, dirs, git_dir)?;
-                    }
-                }
+        for entry in walk.flatten() {
+            let hidden = entry.path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.'));
+            if entry.metadata.is_dir() && !hidden {
+                dirs.insert(entry.path);
             }
         }
         
-        Ok(())
+        Ok(dirs)
     }
     
     // Helper method to find all potentially empty directories in the workspace
@@ -255,8 +200,6 @@ impl<'a> Migration<'a> {
     }
     
     fn analyze_changes(&mut self) -> Result<(), Error> {
-        println!("Analyzing changes for migration");
-        
         // Create Inspector to help analyze the repository state
         let inspector = Inspector::new(
             &self.repo.workspace,
@@ -270,11 +213,9 @@ impl<'a> Migration<'a> {
         
         // If there are any uncommitted changes, record them as conflicts
         if !workspace_changes.is_empty() {
-            println!("Found uncommitted changes in workspace:");
             for (path, change_type) in &workspace_changes {
                 match change_type {
                     ChangeType::Modified | ChangeType::Added | ChangeType::Deleted => {
-                        println!("  {} - {:?}", path, change_type);
                         self.conflicts.get_mut(&ConflictType::UncommittedChanges).unwrap().insert(path.clone());
                     },
                     _ => {} // Ignore untracked files here
@@ -309,7 +250,6 @@ impl<'a> Migration<'a> {
         
         // Add deletions to our change list
         for path in deleted_files {
-            println!("Planning deletion for file: {}", path.display());
             self.changes_to_make.push(Change::Delete { path });
         }
         
@@ -339,7 +279,6 @@ impl<'a> Migration<'a> {
                     
                     if changed_from_old.is_some() && changed_from_new.is_some() {
                         // Index has changes compared to both old and new - conflict
-                        println!("Index entry for {} differs from both old and new trees", path_str);
                         self.conflicts.get_mut(&ConflictType::StaleFile).unwrap().insert(path_str.clone());
                         continue;
                     }
@@ -347,7 +286,6 @@ impl<'a> Migration<'a> {
                     // Use compare_workspace_vs_blob to check if workspace content matches the indexed content
                     if let Ok(has_changes) = inspector.compare_workspace_vs_blob(&path, index_entry.get_oid()) {
                         if has_changes {
-                            println!("Uncommitted changes in workspace file: {}", path_str);
                             self.conflicts.get_mut(&ConflictType::StaleFile).unwrap().insert(path_str.clone());
                             continue;
                         }
@@ -359,14 +297,12 @@ impl<'a> Migration<'a> {
                     if stat.is_file() {
                         if new_entry.is_some() {
                             // Would overwrite untracked file
-                            println!("Untracked file would be overwritten: {}", path_str);
                             self.conflicts.get_mut(&ConflictType::UntrackedOverwritten).unwrap().insert(path_str.clone());
                             continue;
                         }
                     } else if stat.is_dir() {
                         // Check for untracked files in directory using Inspector
                         if inspector.trackable_file(&path, &stat)? {
-                            println!("Directory contains untracked files: {}", path_str);
                             self.conflicts.get_mut(&ConflictType::StaleDirectory).unwrap().insert(path_str.clone());
                             continue;
                         }
@@ -469,8 +405,8 @@ impl<'a> Migration<'a> {
     }
     
     // Execute all planned changes
-    fn execute_changes(&mut self) -> Result<(), Error> {
-        println!("Executing {} changes", self.changes_to_make.len());
+    fn execute_changes(&mut self, progress: &dyn Progress) -> Result<(), Error> {
+        progress.start(self.changes_to_make.len());
         
         // Clone the changes to avoid borrowing issues
         let changes_clone = self.changes_to_make.clone();
@@ -481,7 +417,6 @@ impl<'a> Migration<'a> {
         // First, handle deletions
         for change in &changes_clone {
             if let Change::Delete { path } = change {
-                println!("Removing file: {}", path.display());
                 self.repo.workspace.remove_file(path)?;
                 
                 // Also remove from index
@@ -494,6 +429,7 @@ impl<'a> Migration<'a> {
                         affected_dirs.insert(parent.to_path_buf());
                     }
                 }
+                progress.inc(1);
             }
         }
         
@@ -522,7 +458,6 @@ impl<'a> Migration<'a> {
         
         // Create all needed directories
         for dir in dir_list {
-            println!("Creating directory: {}", dir.display());
             self.repo.workspace.make_directory(&dir)?;
         }
         
@@ -532,16 +467,15 @@ impl<'a> Migration<'a> {
                 Change::Create { path, entry } | Change::Update { path, entry } => {
                     // Check if this is a directory entry
                     if entry.get_mode() == "040000" || FileMode::parse(entry.get_mode()).is_directory() {
-                        println!("Creating directory: {}", path.display());
                         self.repo.workspace.make_directory(&path)?;
                         
                         // Process directory contents
                         self.process_directory_contents(&path, &entry.get_oid())?;
                     } else {
                         // Write the file and update index
-                        println!("Writing file: {}", path.display());
                         self.write_file(&path, &entry)?;
                     }
+                    progress.inc(1);
                 },
                 _ => {}
             }
@@ -555,21 +489,30 @@ impl<'a> Migration<'a> {
                 continue;
             }
             
-            println!("Checking if directory is empty: {}", dir.display());
             self.repo.workspace.remove_directory(&dir)?;
         }
         
+        progress.finish();
         Ok(())
     }
     
+    // Write a blob's content to the workspace, streaming it unless line endings must be converted
+    fn write_blob(&mut self, path: &Path, oid: &str) -> Result<(), Error> {
+        if self.repo.workspace.converts_on_checkout() {
+            let blob_obj = self.repo.database.load(oid)?;
+            let blob_data = self.repo.workspace.denormalize_eol(&path_key(path), blob_obj.to_bytes());
+            return self.repo.workspace.write_file(path, &blob_data);
+        }
+        
+        let mut blob = self.repo.database.open_blob(oid)?;
+        self.repo.workspace.write_stream(path, &mut blob)
+    }
+    
     // Write a file to the workspace and update the index
     fn write_file(&mut self, path: &Path, entry: &DatabaseEntry) -> Result<(), Error> {
-        // Get blob contents
-        let blob_obj = self.repo.database.load(&entry.get_oid())?;
-        let blob_data = blob_obj.to_bytes();
-        
-        // Write to workspace
-        self.repo.workspace.write_file(path, &blob_data)?;
+        // Write to workspace with the entry's mode, so the stat below matches the tree
+        self.write_blob(path, &entry.get_oid())?;
+        self.repo.workspace.set_mode(path, FileMode::parse(entry.get_mode()))?;
         
         // Update index
         if let Ok(stat) = self.repo.workspace.stat_file(path) {
@@ -581,8 +524,6 @@ impl<'a> Migration<'a> {
     
     // Process a directory's contents recursively
     fn process_directory_contents(&mut self, directory_path: &Path, directory_oid: &str) -> Result<(), Error> {
-        println!("Processing directory contents: {}", directory_path.display());
-        
         // Load the tree object
         let obj = self.repo.database.load(directory_oid)?;
         
@@ -597,17 +538,6 @@ impl<'a> Migration<'a> {
             // Now, get current files in the workspace
             let current_files = self.get_all_workspace_files(directory_path)?;
             
-            // Debug output
-            println!("Target files for {}: {}", directory_path.display(), target_files.len());
-            for (path, (oid, _)) in &target_files {
-                println!("  Target file: {} -> {}", path.display(), oid);
-            }
-            
-            println!("Current files for {}: {}", directory_path.display(), current_files.len());
-            for path in &current_files {
-                println!("  Current file: {}", path.display());
-            }
-            
             // First ensure all directories exist
             let mut directories = HashSet::new();
             for path in target_files.keys() {
@@ -625,27 +555,21 @@ impl<'a> Migration<'a> {
             
             // Create all necessary directories
             for dir in dir_list {
-                println!("Creating directory: {}", dir.display());
                 self.repo.workspace.make_directory(&dir)?;
             }
             
             // Now create/update all target files
-            for (path, (oid, _)) in &target_files {
+            for (path, (oid, mode)) in &target_files {
                 // Create parent directories if needed
                 if let Some(parent) = path.parent() {
                     if parent != directory_path && !parent.exists() {
-                        println!("Creating parent directory: {}", parent.display());
                         self.repo.workspace.make_directory(parent)?;
                     }
                 }
                 
-                // Write the file content
-                println!("Writing file: {}", path.display());
-                
                 // Get and write the blob content
-                let blob_obj = self.repo.database.load(oid)?;
-                let blob_data = blob_obj.to_bytes();
-                self.repo.workspace.write_file(path, &blob_data)?;
+                self.write_blob(path, oid)?;
+                self.repo.workspace.set_mode(path, *mode)?;
                 
                 // Update index
                 if let Ok(stat) = self.repo.workspace.stat_file(path) {
@@ -669,7 +593,6 @@ impl<'a> Migration<'a> {
             
             // Delete files that exist in current state but not in target state
             for file_path in sorted_files_to_remove {
-                println!("Removing file that doesn't exist in target: {}", file_path.display());
                 self.repo.workspace.remove_file(&file_path)?;
                 
                 // Also remove from index
@@ -11,10 +11,13 @@ use super::migration::Migration;
 
 pub struct Repository {
     pub path: PathBuf,
+    /// The `.ash` directory
+    pub git_path: PathBuf,
     pub database: Database,
     pub refs: Refs,
     pub workspace: Workspace,
     pub index: Index,
+    index_loaded: bool,
 }
 
 impl Repository {
@@ -23,20 +26,51 @@ impl Repository {
             Error::PathResolution(format!("Failed to resolve path '{}': {}", path, e))
         })?;
         
+        // A git repository is never opened as the store, since commands write through it;
+        // its objects can be read by listing `.git/objects` as an alternate
         let git_path = path_buf.join(".ash");
         
         let db_path = git_path.join("objects");
         let index_path = git_path.join("index");
         
+        let mut database = Database::new(db_path);
+        let mut refs = Refs::new(&git_path);
+        
+        // Without an .ash directory, a git repository's objects and refs are read in its place
+        let dot_git = path_buf.join(".git");
+        if !git_path.exists() && dot_git.is_dir() {
+            database.add_alternate(dot_git.join("objects"));
+            refs.set_fallback(&dot_git);
+        }
+        
         Ok(Repository {
             workspace: Workspace::new(&path_buf),
             index: Index::new(index_path),
-            database: Database::new(db_path),
-            refs: Refs::new(&git_path),
+            database,
+            refs,
+            git_path,
             path: path_buf,
+            index_loaded: false,
         })
     }
 
+    /// Loads the index for reading unless an earlier call already did
+    pub fn load_index(&mut self) -> Result<(), Error> {
+        if !self.index_loaded {
+            self.index.load()?;
+            self.index_loaded = true;
+        }
+        Ok(())
+    }
+
+    /// Takes the index lock and loads the index, for commands that write it back.
+    /// Returns false if another process holds the lock.
+    pub fn load_index_for_update(&mut self) -> Result<bool, Error> {
+        let acquired = self.index.load_for_update()?;
+        self.index_loaded = acquired;
+        Ok(acquired)
+    }
+
     pub fn create_git_directory(&self) -> Result<PathBuf, Error> {
         let git_path = self.path.join(".ash");
         self.create_directory(&git_path)?;
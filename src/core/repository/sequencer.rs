@@ -85,10 +85,10 @@ impl Sequencer {
 
     /// Start a new sequencing operation
     pub fn start(&mut self, options: &HashMap<String, String>) -> Result<(), Error> {
-        // Create sequencer directory if it doesn't exist
-        if !self.pathname.exists() {
-            fs::create_dir_all(&self.pathname)?;
+        if self.pathname.exists() {
+            return Err(Error::Generic("a cherry-pick or revert is already in progress; try --continue, --abort or --quit".to_string()));
         }
+        fs::create_dir_all(&self.pathname)?;
 
         // Load and store the HEAD reference
         let refs = Refs::new(&self.repo_path);
@@ -181,10 +181,10 @@ impl Sequencer {
         for line in content.lines() {
             if let Some(captures) = line_regex.captures(line) {
                 let action = &captures[1];
-                let oid = &captures[2];
+                let oid = database.resolve_oid(&captures[2])?;
                 
                 // Load the commit object
-                let obj = database.load(oid)?;
+                let obj = database.load(&oid)?;
                 let commit = match obj.as_any().downcast_ref::<Commit>() {
                     Some(commit) => commit.clone(),
                     None => return Err(Error::Generic(format!("Invalid commit object: {}", oid)))
@@ -217,8 +217,13 @@ impl Sequencer {
         Ok(())
     }
 
-    /// Abort the current sequencing operation
-    pub fn abort(&mut self) -> Result<(), Error> {
+    /// Abort the current sequencing operation, returning the HEAD it started from.
+    /// The caller is responsible for restoring the index and working tree to it.
+    pub fn abort(&mut self) -> Result<String, Error> {
+        if !self.head_path.exists() {
+            return Err(Error::Generic("no cherry-pick or revert in progress".to_string()));
+        }
+
         // Load the original HEAD
         let head_oid = fs::read_to_string(&self.head_path)?.trim().to_owned();
         
@@ -237,22 +242,7 @@ impl Sequencer {
             return Err(Error::Generic("Cannot abort: Working directory has been modified since last command".to_string()));
         }
 
-        // Reset to original HEAD
-        self.hard_reset(&head_oid)?;
-
-        Ok(())
-    }
-
-    /// Perform a hard reset to the given commit
-    fn hard_reset(&self, commit_oid: &str) -> Result<(), Error> {
-        // Reset HEAD
-        let refs = Refs::new(&self.repo_path);
-        refs.update_head(commit_oid)?;
-        
-        // TODO: Reset working directory and index
-        // This would need to be implemented
-
-        Ok(())
+        Ok(head_oid)
     }
 
     /// Quit the current sequencing operation
@@ -4,17 +4,26 @@ use regex::Regex;
 use crate::errors::error::Error;
 use crate::core::repository::repository::Repository;
 use crate::core::database::commit::Commit;
+use crate::core::database::tag::Tag;
+use crate::core::merge::bases::merge_base;
+use crate::core::branch_metadata::BranchMetadataManager;
+use crate::core::refs::Reference;
 
 // Constants for revision types
 pub const HEAD: &str = "HEAD";
 pub const COMMIT: &str = "commit";
+/// Expected type accepted by `resolve_to_type` for objects of any type
+const ANY: &str = "";
 
 /// Defines the revision node types for AST representation.
 #[derive(Debug, Clone)]
 enum RevisionNode {
     Ref(String),
-    Parent(Box<RevisionNode>),
+    Parent(Box<RevisionNode>, usize),
     Ancestor(Box<RevisionNode>, usize),
+    Reflog(String, usize),
+    /// `<branch>@{upstream}` / `@{u}`; an empty name means the current branch
+    Upstream(String),
     Range(Box<RevisionNode>, Box<RevisionNode>),
     Exclude(Box<RevisionNode>),
 }
@@ -25,6 +34,82 @@ pub struct HintedError {
     pub hint: Vec<String>,
 }
 
+/// A revision argument as given on the command line, before resolution.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RevisionSpec {
+    /// A single revision such as `main` or `HEAD~3`.
+    Single(String),
+    /// `A..B`: commits reachable from B but not from A.
+    Range { exclude: String, include: String },
+    /// `A...B`: commits reachable from either A or B but not both.
+    Symmetric(String, String),
+    /// `^A`: hides every commit reachable from A.
+    Exclude(String),
+}
+
+impl RevisionSpec {
+    /// Splits a revision argument on `..`/`...` and a leading `^`.
+    /// An empty side of a range defaults to HEAD.
+    pub fn parse(expr: &str) -> Self {
+        let or_head = |side: &str| {
+            if side.is_empty() { HEAD.to_string() } else { side.to_string() }
+        };
+
+        if let Some(pos) = expr.find("...") {
+            return RevisionSpec::Symmetric(or_head(&expr[..pos]), or_head(&expr[pos + 3..]));
+        }
+
+        if let Some(pos) = expr.find("..") {
+            return RevisionSpec::Range {
+                exclude: or_head(&expr[..pos]),
+                include: or_head(&expr[pos + 2..]),
+            };
+        }
+
+        if let Some(rev) = expr.strip_prefix('^') {
+            return RevisionSpec::Exclude(rev.to_string());
+        }
+
+        RevisionSpec::Single(expr.to_string())
+    }
+}
+
+/// Resolves revision arguments into the commit OIDs to walk from and the commit OIDs
+/// whose history must be hidden from the walk.
+pub fn resolve_walk_tips(repo: &mut Repository, revisions: &[String]) -> Result<(Vec<String>, Vec<String>), Error> {
+    let mut include = Vec::new();
+    let mut exclude = Vec::new();
+
+    for expr in revisions {
+        match RevisionSpec::parse(expr) {
+            RevisionSpec::Single(rev) => include.push(resolve_commit(repo, &rev)?),
+            RevisionSpec::Exclude(rev) => exclude.push(resolve_commit(repo, &rev)?),
+            RevisionSpec::Range { exclude: a, include: b } => {
+                exclude.push(resolve_commit(repo, &a)?);
+                include.push(resolve_commit(repo, &b)?);
+            },
+            RevisionSpec::Symmetric(a, b) => {
+                let a_oid = resolve_commit(repo, &a)?;
+                let b_oid = resolve_commit(repo, &b)?;
+
+                // Everything reachable from both sides is reachable from their merge bases
+                exclude.extend(merge_base(&mut repo.database, &a_oid, &b_oid)?);
+
+                include.push(a_oid);
+                include.push(b_oid);
+            },
+        }
+    }
+
+    Ok((include, exclude))
+}
+
+/// Resolves a single revision expression to a commit OID.
+fn resolve_commit(repo: &mut Repository, expr: &str) -> Result<String, Error> {
+    let mut revision = Revision::new(repo, expr);
+    revision.resolve(COMMIT)
+}
+
 /// Main struct for revision parsing and resolution logic.
 pub struct Revision<'a> {
     repo: &'a mut Repository,  // Changed from database to repo
@@ -51,8 +136,10 @@ impl<'a> Revision<'a> {
     fn parse(revision: &str) -> Option<RevisionNode> {
         // Regex patterns for revision operators
         lazy_static::lazy_static! {
-            static ref PARENT_PATTERN: Regex = Regex::new(r"^(.+)\^$").unwrap();
-            static ref ANCESTOR_PATTERN: Regex = Regex::new(r"^(.+)~(\d+)$").unwrap();
+            static ref PARENT_PATTERN: Regex = Regex::new(r"^(.+)\^(\d*)$").unwrap();
+            static ref ANCESTOR_PATTERN: Regex = Regex::new(r"^(.+)~(\d*)$").unwrap();
+            static ref REFLOG_PATTERN: Regex = Regex::new(r"^(.*)@\{(\d+)\}$").unwrap();
+            static ref UPSTREAM_PATTERN: Regex = Regex::new(r"(?i)^(.*)@\{(u|upstream)\}$").unwrap();
             static ref RANGE_PATTERN: Regex = Regex::new(r"^(.*)\.\.(.*)$").unwrap();
             static ref EXCLUDE_PATTERN: Regex = Regex::new(r"^\^(.+)$").unwrap();
             static ref INVALID_NAME: Regex = Regex::new(r"(?x)
@@ -101,13 +188,14 @@ impl<'a> Revision<'a> {
             return Self::parse(rev).map(|node| RevisionNode::Exclude(Box::new(node)));
         }
         
-        // Check for parent notation (rev^)
+        // Check for parent notation (rev^ or rev^N)
         if let Some(captures) = PARENT_PATTERN.captures(revision) {
             let rev = captures.get(1).unwrap().as_str();
-            return Self::parse(rev).map(|node| RevisionNode::Parent(Box::new(node)));
+            let n = captures.get(2).unwrap().as_str().parse::<usize>().unwrap_or(1);
+            return Self::parse(rev).map(|node| RevisionNode::Parent(Box::new(node), n));
         }
         
-        // Check for ancestor notation (rev~N)
+        // Check for ancestor notation (rev~ or rev~N)
         if let Some(captures) = ANCESTOR_PATTERN.captures(revision) {
             let rev = captures.get(1).unwrap().as_str();
             let n = captures.get(2).unwrap().as_str().parse::<usize>().unwrap_or(1);
@@ -115,6 +203,29 @@ impl<'a> Revision<'a> {
             return Self::parse(rev).map(|node| RevisionNode::Ancestor(Box::new(node), n));
         }
         
+        // Check for reflog notation (ref@{N}, or @{N} for HEAD)
+        if let Some(captures) = REFLOG_PATTERN.captures(revision) {
+            let rev = captures.get(1).unwrap().as_str();
+            let n = captures.get(2).unwrap().as_str().parse::<usize>().unwrap_or(0);
+            let name = if rev.is_empty() || rev == "@" { HEAD } else { rev };
+            
+            if INVALID_NAME.is_match(name) {
+                return None;
+            }
+            return Some(RevisionNode::Reflog(name.to_string(), n));
+        }
+        
+        // Check for upstream notation (branch@{upstream}, or @{u} for the current branch)
+        if let Some(captures) = UPSTREAM_PATTERN.captures(revision) {
+            let rev = captures.get(1).unwrap().as_str();
+            let name = if rev == "@" || rev == HEAD { "" } else { rev };
+            
+            if !name.is_empty() && INVALID_NAME.is_match(name) {
+                return None;
+            }
+            return Some(RevisionNode::Upstream(name.to_string()));
+        }
+        
         // Check if it's a valid reference name
         if !INVALID_NAME.is_match(revision) {
             let name = REF_ALIASES.get(revision).unwrap_or(&revision);
@@ -129,6 +240,11 @@ impl<'a> Revision<'a> {
         self.resolve_to_type(expected_type)
     }
     
+    /// Resolve a revision to an object ID of any type (commit, tree, blob or tag)
+    pub fn resolve_object(&mut self) -> Result<String, Error> {
+        self.resolve_to_type(ANY)
+    }
+    
     // Resolve a revision to an object ID of a specific type
     pub fn resolve_to_type(&mut self, expected_type: &str) -> Result<String, Error> {
         if let Some(node) = &self.query {
@@ -145,6 +261,8 @@ impl<'a> Revision<'a> {
                         Err(Error::Generic(format!("Not a valid object name: '{}'", self.expr)))
                     }
                 },
+                // Walking errors already name the failing part of the expression
+                Err(e @ Error::Generic(_)) if Self::is_walk_node(&node_clone) => Err(e),
                 Err(_) => Err(Error::Generic(format!("Not a valid object name: '{}'", self.expr))),
             }
         } else {
@@ -156,17 +274,28 @@ impl<'a> Revision<'a> {
     fn resolve_node(&mut self, node: &RevisionNode) -> Result<String, Error> {
         match node {
             RevisionNode::Ref(name) => self.read_ref(name),
-            RevisionNode::Parent(rev) => {
+            RevisionNode::Parent(rev, n) => {
                 let oid = self.resolve_node(rev)?;
-                self.commit_parent(&oid)
+                // rev^0 names the commit itself
+                if *n == 0 {
+                    self.load_typed_object(&oid, COMMIT)?;
+                    return Ok(oid);
+                }
+                self.commit_parent(&oid, *n).map_err(|_| Error::Generic(format!(
+                    "revision {}^{}: no such parent", Self::describe(rev), n
+                )))
             },
             RevisionNode::Ancestor(rev, n) => {
                 let mut oid = self.resolve_node(rev)?;
                 for _ in 0..*n {
-                    oid = self.commit_parent(&oid)?;
+                    oid = self.commit_parent(&oid, 1).map_err(|_| Error::Generic(format!(
+                        "revision {}~{}: not enough ancestors", Self::describe(rev), n
+                    )))?;
                 }
                 Ok(oid)
             },
+            RevisionNode::Reflog(name, n) => self.read_reflog_entry(name, *n),
+            RevisionNode::Upstream(branch) => self.read_upstream(branch),
             RevisionNode::Range(start, end) => {
                 // For a range A..B, we return B and mark A as excluded
                 // This matches Git's behavior where log A..B shows commits reachable from B but not from A
@@ -187,38 +316,10 @@ impl<'a> Revision<'a> {
     
     // Get a reference value or try to match an abbreviated object ID
     fn read_ref(&mut self, name: &str) -> Result<String, Error> {
-        // First try to read as a reference
-        // In a real implementation, this would use a refs object to look up references
-        // For now, we'll just handle HEAD specially
-        if name == HEAD {
-            // Return HEAD reference (would normally be implemented via refs system)
-            // For our example, we'll try to load HEAD from an expected location
-            let head_file = std::path::Path::new(".ash/HEAD");
-            if head_file.exists() {
-                if let Ok(content) = std::fs::read_to_string(head_file) {
-                    let content = content.trim();
-                    if content.starts_with("ref: ") {
-                        let ref_path = content.strip_prefix("ref: ").unwrap();
-                        let ref_file = std::path::Path::new(".ash").join(ref_path);
-                        if ref_file.exists() {
-                            if let Ok(oid) = std::fs::read_to_string(ref_file) {
-                                return Ok(oid.trim().to_string());
-                            }
-                        }
-                    } else {
-                        return Ok(content.to_string());
-                    }
-                }
-            }
-        }
-        
-        // Try as a branch reference
-        let ref_path = format!(".ash/refs/heads/{}", name);
-        let ref_file = std::path::Path::new(&ref_path);
-        if ref_file.exists() {
-            if let Ok(oid) = std::fs::read_to_string(ref_file) {
-                return Ok(oid.trim().to_string());
-            }
+        // First try HEAD, branches and tags through the repository's refs,
+        // peeling annotated tags down to the object they point at
+        if let Some(oid) = self.repo.refs.read_ref(name)? {
+            return self.peel_tag(&oid);
         }
         
         // Then try as an abbreviated object ID
@@ -235,19 +336,89 @@ impl<'a> Revision<'a> {
         }
     }
     
-    // Get the parent of a commit
-    fn commit_parent(&mut self, oid: &str) -> Result<String, Error> {
+    // Follow annotated tag objects until reaching a non-tag object
+    fn peel_tag(&mut self, oid: &str) -> Result<String, Error> {
+        let mut current = oid.to_string();
+        loop {
+            let object = self.repo.database.load(&current)?;
+            match object.as_any().downcast_ref::<Tag>() {
+                Some(tag) => current = tag.get_object().to_string(),
+                None => return Ok(current),
+            }
+        }
+    }
+    
+    // Get the nth parent of a commit (1-based, following the order of the parent headers)
+    fn commit_parent(&mut self, oid: &str, n: usize) -> Result<String, Error> {
         // Ensure it's a commit
         let commit = self.load_typed_object(oid, COMMIT)?;
         
         // Get its parent
         if let Some(commit) = commit.as_any().downcast_ref::<Commit>() {
-            if let Some(parent) = commit.get_parent() {
+            if let Some(parent) = n.checked_sub(1).and_then(|i| commit.get_parents().get(i)) {
                 return Ok(parent.clone());
             }
         }
         
-        Err(Error::Generic(format!("Commit '{}' has no parent", oid)))
+        Err(Error::Generic(format!("Commit '{}' has no parent {}", oid, n)))
+    }
+    
+    // Read the OID recorded by the nth most recent reflog entry of a ref (0 is the newest)
+    fn read_reflog_entry(&mut self, name: &str, n: usize) -> Result<String, Error> {
+        let entries = self.repo.refs.read_reflog(name)?;
+        
+        if entries.is_empty() {
+            return Err(Error::Generic(format!("log for '{}' does not exist", name)));
+        }
+        
+        if n >= entries.len() {
+            return Err(Error::Generic(format!(
+                "log for '{}' only has {} entries", name, entries.len()
+            )));
+        }
+        
+        Ok(entries[entries.len() - 1 - n].new_oid.clone())
+    }
+    
+    // Resolve the upstream configured for a branch (the current one when `branch` is empty)
+    fn read_upstream(&mut self, branch: &str) -> Result<String, Error> {
+        let branch = if branch.is_empty() {
+            match self.repo.refs.current_ref()? {
+                Reference::Symbolic(path) if path != HEAD => self.repo.refs.short_name(&path),
+                _ => return Err(Error::Generic("HEAD does not point to a branch".to_string())),
+            }
+        } else {
+            branch.to_string()
+        };
+        
+        let upstream = BranchMetadataManager::new(&self.repo.path).get_upstream(&branch)?
+            .ok_or_else(|| Error::Generic(format!("no upstream configured for branch '{}'", branch)))?;
+        
+        match self.repo.refs.read_ref(&upstream)? {
+            Some(oid) => self.peel_tag(&oid),
+            None => Err(Error::Generic(format!(
+                "upstream branch '{}' of '{}' does not exist", upstream, branch
+            ))),
+        }
+    }
+    
+    // Whether a node walks history, so its errors are worth reporting verbatim
+    fn is_walk_node(node: &RevisionNode) -> bool {
+        matches!(node, RevisionNode::Parent(..) | RevisionNode::Ancestor(..) | RevisionNode::Reflog(..) | RevisionNode::Upstream(..))
+    }
+    
+    // Render a node back to revision syntax for error messages
+    fn describe(node: &RevisionNode) -> String {
+        match node {
+            RevisionNode::Ref(name) => name.clone(),
+            RevisionNode::Parent(rev, 1) => format!("{}^", Self::describe(rev)),
+            RevisionNode::Parent(rev, n) => format!("{}^{}", Self::describe(rev), n),
+            RevisionNode::Ancestor(rev, n) => format!("{}~{}", Self::describe(rev), n),
+            RevisionNode::Reflog(name, n) => format!("{}@{{{}}}", name, n),
+            RevisionNode::Upstream(name) => format!("{}@{{upstream}}", name),
+            RevisionNode::Range(start, end) => format!("{}..{}", Self::describe(start), Self::describe(end)),
+            RevisionNode::Exclude(rev) => format!("^{}", Self::describe(rev)),
+        }
     }
     
     // Load an object and verify its type
@@ -259,7 +430,7 @@ impl<'a> Revision<'a> {
         let object = self.repo.database.load(oid)?;
         
         // Check if the object is of the expected type
-        if object.get_type() != expected_type {
+        if expected_type != ANY && object.get_type() != expected_type {
             // Add an error message
             let message = format!("object {} is a {}, not a {}", 
                                  oid, object.get_type(), expected_type);
@@ -278,7 +449,7 @@ impl<'a> Revision<'a> {
     fn verify_object_type(&mut self, oid: &str, expected_type: &str) -> Result<bool, Error> {
         let object = self.repo.database.load(oid)?;
         
-        if object.get_type() != expected_type {
+        if expected_type != ANY && object.get_type() != expected_type {
             let message = format!("object {} is a {}, not a {}", 
                                  oid, object.get_type(), expected_type);
             self.errors.push(HintedError { 
@@ -323,4 +494,52 @@ impl<'a> Revision<'a> {
         self.errors.push(HintedError { message, hint: hints });
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::Config;
+    use crate::test_support::TestRepo;
+
+    #[test]
+    fn resolve_object_accepts_every_object_type() {
+        let repo = TestRepo::new();
+        let head = repo.commit_files(&[("f.txt", "hello\n")], "first");
+        let mut r = repo.repo();
+        let object = r.database.load(&head).unwrap();
+        let tree = object.as_any().downcast_ref::<Commit>().unwrap().get_tree().to_string();
+        let blob = repo.blob_oid("hello\n");
+
+        assert_eq!(Revision::new(&mut r, "HEAD").resolve_object().unwrap(), head);
+        assert_eq!(Revision::new(&mut r, &tree).resolve_object().unwrap(), tree);
+        assert_eq!(Revision::new(&mut r, &blob).resolve_object().unwrap(), blob);
+
+        assert!(Revision::new(&mut r, &tree).resolve(COMMIT).is_err());
+    }
+
+    #[test]
+    fn upstream_is_read_from_the_branch_config() {
+        let repo = TestRepo::new();
+        let first = repo.commit_files(&[("f.txt", "one\n")], "first");
+        let second = repo.commit_files(&[("f.txt", "two\n")], "second");
+        let mut r = repo.repo();
+        r.refs.create_branch("feature", &first).unwrap();
+        r.refs.update_ref("refs/remotes/origin/main", &first).unwrap();
+
+        assert!(Revision::new(&mut r, "feature@{u}").resolve(COMMIT).is_err());
+
+        let metadata = BranchMetadataManager::new(&r.path);
+        metadata.set_upstream("feature", "master").unwrap();
+        let config = Config::load(&r.git_path);
+        assert_eq!(config.get("branch.feature", "remote"), Some("."));
+        assert_eq!(config.get("branch.feature", "merge"), Some("refs/heads/master"));
+        assert_eq!(Revision::new(&mut r, "feature@{upstream}").resolve(COMMIT).unwrap(), second);
+
+        metadata.set_upstream("master", "origin/main").unwrap();
+        assert_eq!(metadata.get_upstream("master").unwrap().as_deref(), Some("origin/main"));
+        assert_eq!(Revision::new(&mut r, "@{u}").resolve(COMMIT).unwrap(), first);
+
+        assert!(metadata.set_upstream("feature", "origin/missing").is_err());
+    }
+}
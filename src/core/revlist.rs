@@ -271,22 +271,28 @@ impl<'a> RevList<'a> {
             return Ok(());
         }
         
-        // Get parent commit
-        if let Some(parent_oid) = self.get_parent(commit) {
-            // If current commit is uninteresting, mark parent as uninteresting
-            if self.is_marked(&oid, &Flag::Uninteresting) {
-                self.mark(&parent_oid, Flag::Uninteresting);
-                self.mark_parents_uninteresting(&parent_oid)?;
-            }
-            
-            // If path filtering is active, simplify commit
-            let original_commit = self.load_commit(&oid)?;
-            if !self.path_filter.path().as_os_str().is_empty() {
-                self.simplify_commit(&original_commit)?;
+        let parents = self.get_parents(commit.as_ref());
+        if parents.is_empty() {
+            return Ok(());
+        }
+        
+        // If current commit is uninteresting, mark its parents as uninteresting
+        if self.is_marked(&oid, &Flag::Uninteresting) {
+            for parent_oid in &parents {
+                self.mark(parent_oid, Flag::Uninteresting);
+                self.mark_parents_uninteresting(parent_oid)?;
             }
-            
-            // Add parent to queue
-            let parent_commit = self.load_commit(&parent_oid)?;
+        }
+        
+        // If path filtering is active, simplify commit
+        let original_commit = self.load_commit(&oid)?;
+        if !self.path_filter.path().as_os_str().is_empty() {
+            self.simplify_commit(&original_commit)?;
+        }
+        
+        // Add every parent to the queue, so merged-in history is walked too
+        for parent_oid in &parents {
+            let parent_commit = self.load_commit(parent_oid)?;
             self.enqueue_commit(parent_commit.clone_box());
         }
         
@@ -387,6 +393,15 @@ impl<'a> RevList<'a> {
         }
     }
     
+    /// Helper to get every parent OID of a commit
+    fn get_parents(&self, commit: &dyn GitObject) -> Vec<String> {
+        if let Some(commit) = commit.as_any().downcast_ref::<Commit>() {
+            commit.get_parents().to_vec()
+        } else {
+            Vec::new()
+        }
+    }
+    
     /// Helper to get commit date
     fn get_commit_date(&self, commit: &Box<dyn GitObject>) -> Result<i64, Error> {
         if let Some(commit) = commit.as_any().downcast_ref::<Commit>() {
@@ -2,7 +2,9 @@ use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::fs;
 use std::time::{SystemTime, UNIX_EPOCH, Duration};
+use chrono::{DateTime, Datelike, Duration as DateDuration, NaiveDate, Utc};
 use serde::{Serialize, Deserialize};
+use crate::core::branch_metadata::SprintMetadata;
 use crate::core::repository::repository::Repository;
 
 use crate::errors::error::Error;
@@ -172,6 +174,15 @@ impl Sprint {
     }
 }
 
+/// Story points remaining on each day of a sprint, from its first day to its last.
+/// `actual` stops at today, so it is shorter than `dates` while the sprint is running.
+#[derive(Debug, Clone, Serialize)]
+pub struct BurndownData {
+    pub dates: Vec<String>,
+    pub ideal: Vec<f64>,
+    pub actual: Vec<f64>,
+}
+
 // Sprint Manager to handle storage and loading
 pub struct SprintManager {
     pub repo_path: PathBuf,
@@ -225,13 +236,6 @@ impl SprintManager {
         // Create branch metadata manager
         let branch_manager = crate::core::branch_metadata::BranchMetadataManager::new(&self.repo_path);
         
-        // Create metadata
-        let metadata = crate::core::branch_metadata::SprintMetadata {
-            name: sprint.name.clone(),
-            start_timestamp: sprint.start_date,
-            duration_days: ((sprint.end_date - sprint.start_date) / 86400) as u32,
-        };
-        
         // Extract the branch name without the sprint- prefix if present
         let branch_name = if sprint.branch.starts_with("sprint-") {
             sprint.branch.strip_prefix("sprint-").unwrap_or(&sprint.branch).to_string()
@@ -239,6 +243,19 @@ impl SprintManager {
             sprint.branch.clone()
         };
         
+        // Sprint doesn't track closing; keep whatever was recorded when the sprint was closed
+        let existing = branch_manager.get_sprint_metadata(&branch_name).ok().flatten();
+        
+        // Create metadata
+        let metadata = crate::core::branch_metadata::SprintMetadata {
+            name: sprint.name.clone(),
+            start_timestamp: sprint.start_date,
+            duration_days: ((sprint.end_date - sprint.start_date) / 86400) as u32,
+            closed_timestamp: existing.as_ref().and_then(|meta| meta.closed_timestamp),
+            planned_points: existing.as_ref().and_then(|meta| meta.planned_points),
+            completed_points: existing.and_then(|meta| meta.completed_points),
+        };
+        
         // Store metadata
         branch_manager.store_sprint_metadata(&branch_name, &metadata)?;
         
@@ -246,6 +263,9 @@ impl SprintManager {
         let task_manager = crate::core::commit_metadata::CommitMetadataManager::new(&self.repo_path);
         
         for (id, task) in &sprint.tasks {
+            // Sprint tasks don't carry dependencies or priority; keep whatever was recorded for the task
+            let existing = task_manager.get_task_metadata(id).ok().flatten();
+            
             // Convert Sprint Task to TaskMetadata
             let task_metadata = crate::core::commit_metadata::TaskMetadata {
                 id: task.id.clone(),
@@ -260,6 +280,8 @@ impl SprintManager {
                 started_at: task.started_at,
                 completed_at: task.completed_at,
                 commit_ids: task.commits.clone(),
+                depends_on: existing.as_ref().map(|meta| meta.depends_on.clone()).unwrap_or_default(),
+                priority: existing.map(|meta| meta.priority).unwrap_or_default(),
             };
             
             // Store task metadata
@@ -280,6 +302,27 @@ impl SprintManager {
         }
     }
     
+    /// Moves the unfinished tasks of `from_branch` to `to_branch` by repointing their
+    /// `refs/meta/tasksprint` entries. Task branches are left as they are. Returns the moved ids.
+    pub fn carry_over_tasks(&self, from_branch: &str, to_branch: &str) -> Result<Vec<String>, Error> {
+        let repo = Repository::new(
+            self.repo_path.to_str().unwrap_or(".")
+        )?;
+        
+        let mut moved: Vec<String> = self.get_sprint_tasks(from_branch)?
+            .into_values()
+            .filter(|task| task.status != TaskStatus::Done)
+            .map(|task| task.id)
+            .collect();
+        moved.sort();
+        
+        for id in &moved {
+            repo.refs.update_ref(&format!("refs/meta/tasksprint/{}", id), to_branch)?;
+        }
+        
+        Ok(moved)
+    }
+    
     pub fn get_sprint_tasks(&self, sprint_branch: &str) -> Result<HashMap<String, Task>, Error> {
         // Gather tasks for this sprint by reading metadata refs/meta/tasksprint/<id>
         let mut tasks = HashMap::new();
@@ -319,4 +362,88 @@ impl SprintManager {
         }
         Ok(tasks)
     }
-} 
\ No newline at end of file
+
+    /// Builds the burndown series for a sprint: the ideal line spreads the sprint's points
+    /// over its working days, the actual line subtracts the points of tasks done each day
+    pub fn burndown_data(&self, sprint_branch: &str, metadata: &SprintMetadata) -> Result<BurndownData, Error> {
+        let tasks = self.get_sprint_tasks(sprint_branch)?;
+        let total_points: u32 = tasks.values().filter_map(|task| task.story_points).sum();
+
+        let start_date = DateTime::from_timestamp(metadata.start_timestamp as i64, 0)
+            .unwrap()
+            .date_naive();
+        let end_date = DateTime::from_timestamp(metadata.end_timestamp() as i64, 0)
+            .unwrap()
+            .date_naive();
+        let current_date = Utc::now().naive_utc().date();
+
+        let total_days = (end_date - start_date).num_days().max(0) as usize;
+        let days_passed = ((current_date - start_date).num_days().max(0) as usize).min(total_days);
+
+        let daily_progress = get_daily_progress(&tasks, start_date, total_days);
+
+        Ok(BurndownData {
+            dates: (0..=total_days)
+                .map(|d| (start_date + DateDuration::days(d as i64)).format("%Y-%m-%d").to_string())
+                .collect(),
+            ideal: ideal_remaining(total_points, start_date, total_days),
+            actual: (0..=days_passed)
+                .map(|d| {
+                    // Days without data keep the last known value
+                    let completed = daily_progress.get(d).or(daily_progress.last()).copied().unwrap_or(0);
+                    total_points.saturating_sub(completed) as f64
+                })
+                .collect(),
+        })
+    }
+}
+
+// Cumulative points completed by the end of each sprint day; tasks without points count as one
+fn get_daily_progress(tasks: &HashMap<String, Task>, start_date: NaiveDate, total_days: usize) -> Vec<u32> {
+    let mut daily_completed = vec![0; total_days + 1];
+    let sprint_end_date = start_date + DateDuration::days(total_days as i64);
+
+    for task in tasks.values() {
+        if task.status != TaskStatus::Done {
+            continue;
+        }
+        let completed_date = match task.completed_at.and_then(|ts| DateTime::from_timestamp(ts as i64, 0)) {
+            Some(completed) => completed.date_naive(),
+            None => continue,
+        };
+
+        // Only tasks completed during the sprint period count
+        if completed_date >= start_date && completed_date <= sprint_end_date {
+            let day_idx = (completed_date - start_date).num_days() as usize;
+            daily_completed[day_idx] += task.story_points.unwrap_or(1);
+        }
+    }
+
+    let mut cumulative = 0;
+    daily_completed.iter()
+        .map(|points| {
+            cumulative += points;
+            cumulative
+        })
+        .collect()
+}
+
+// Ideal points remaining on each day, burning down evenly over working days (weekends excluded)
+fn ideal_remaining(total_points: u32, start_date: NaiveDate, total_days: usize) -> Vec<f64> {
+    let working_days: Vec<usize> = (0..=total_days)
+        .filter(|d| (start_date + DateDuration::days(*d as i64)).weekday().number_from_monday() < 6)
+        .collect();
+
+    let points_per_working_day = if working_days.is_empty() {
+        0.0
+    } else {
+        total_points as f64 / working_days.len() as f64
+    };
+
+    (0..=total_days)
+        .map(|d| {
+            let working_days_so_far = working_days.iter().filter(|&&wd| wd <= d).count();
+            (total_points as f64 - working_days_so_far as f64 * points_per_working_day).max(0.0)
+        })
+        .collect()
+}
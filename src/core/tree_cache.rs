@@ -0,0 +1,63 @@
+/// Caches the flattened tree of a commit in `.ash/cache/head-tree`, so repeated status runs
+/// on an unchanged HEAD don't have to load every tree object again.
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::errors::error::Error;
+use crate::core::database::entry::DatabaseEntry;
+
+/// The cache holds one commit at a time. Its first line is the commit ID, followed by one
+/// `<mode> <oid>\t<path>` line per entry, directories included.
+pub struct TreeCache {
+    path: PathBuf,
+}
+
+impl TreeCache {
+    pub fn new(git_path: &Path) -> Self {
+        TreeCache {
+            path: git_path.join("cache").join("head-tree"),
+        }
+    }
+
+    /// The cached entries of `commit_oid`'s tree by path; None when the cache is missing,
+    /// unreadable or holds another commit
+    pub fn load(&self, commit_oid: &str) -> Option<HashMap<String, DatabaseEntry>> {
+        let content = fs::read_to_string(&self.path).ok()?;
+        let mut lines = content.lines();
+        if lines.next()? != commit_oid {
+            return None;
+        }
+
+        let mut entries = HashMap::new();
+        for line in lines {
+            let (header, path) = line.split_once('\t')?;
+            let (mode, oid) = header.split_once(' ')?;
+            entries.insert(path.to_string(), DatabaseEntry::new(path.to_string(), oid.to_string(), mode));
+        }
+        Some(entries)
+    }
+
+    /// Replaces the cache with `entries` for `commit_oid`. The data is written to a file of
+    /// this process's own and renamed into place, so concurrent runs never see a partial cache.
+    pub fn store(&self, commit_oid: &str, entries: &HashMap<String, DatabaseEntry>) -> Result<(), Error> {
+        // A path with a newline can't be stored in the line format; such trees aren't cached
+        if entries.keys().any(|path| path.contains('\n')) {
+            return Ok(());
+        }
+
+        let mut content = format!("{}\n", commit_oid);
+        for (path, entry) in entries {
+            content.push_str(&format!("{} {}\t{}\n", entry.get_mode(), entry.get_oid(), path));
+        }
+
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let temp_path = self.path.with_extension(format!("{}.tmp", std::process::id()));
+        fs::write(&temp_path, content)?;
+        fs::rename(&temp_path, &self.path).map_err(|e| {
+            let _ = fs::remove_file(&temp_path);
+            Error::IO(e)
+        })
+    }
+}
@@ -0,0 +1,158 @@
+/// Remembers, for untracked directories, whether they hold trackable files, so status can
+/// skip walking them again while nothing inside has changed. Stored in `.ash/untracked-cache`
+/// next to the index.
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use crate::errors::error::Error;
+use crate::core::workspace::path_key;
+
+/// Directories modified this recently aren't cached: a change in the same clock tick as
+/// the scan could go unnoticed
+const RACY_WINDOW: Duration = Duration::from_secs(2);
+
+/// What was found under one untracked directory, and the modification time of every
+/// directory read to find it. Adding or removing an entry changes the mtime of the
+/// directory holding it, so the answer stays valid while all those mtimes are unchanged.
+#[derive(Debug, Clone)]
+struct CachedDir {
+    trackable: bool,
+    dirs: Vec<(String, u128)>,
+}
+
+pub struct UntrackedCache {
+    root_path: PathBuf,
+    path: PathBuf,
+    entries: HashMap<String, CachedDir>,
+    /// Directories looked up or recorded in this run; the others are dropped on save
+    used: HashSet<String>,
+    dirty: bool,
+}
+
+impl UntrackedCache {
+    /// Reads the cache from `git_path`; a missing or unreadable cache starts empty
+    pub fn load(root_path: &Path, git_path: &Path) -> Self {
+        let path = git_path.join("untracked-cache");
+        let entries = fs::read_to_string(&path)
+            .map(|content| Self::parse(&content))
+            .unwrap_or_default();
+
+        UntrackedCache {
+            root_path: root_path.to_path_buf(),
+            path,
+            entries,
+            used: HashSet::new(),
+            dirty: false,
+        }
+    }
+
+    // Each directory is a `D <0|1> <path>` line followed by one `M <mtime-ns> <path>` line
+    // per directory read while scanning it
+    fn parse(content: &str) -> HashMap<String, CachedDir> {
+        let mut entries = HashMap::new();
+        let mut current: Option<(String, CachedDir)> = None;
+
+        for line in content.lines() {
+            let mut parts = line.splitn(3, ' ');
+            match (parts.next(), parts.next(), parts.next()) {
+                (Some("D"), Some(flag), Some(path)) => {
+                    entries.extend(current.take());
+                    let cached = CachedDir { trackable: flag == "1", dirs: Vec::new() };
+                    current = Some((path.to_string(), cached));
+                },
+                (Some("M"), Some(mtime), Some(path)) => {
+                    if let (Some((_, cached)), Ok(mtime)) = (current.as_mut(), mtime.parse()) {
+                        cached.dirs.push((path.to_string(), mtime));
+                    }
+                },
+                _ => return HashMap::new(),
+            }
+        }
+        entries.extend(current);
+
+        entries
+    }
+
+    /// Whether `dir` holds trackable files, if the cached answer is still valid
+    pub fn lookup(&mut self, dir: &str) -> Option<bool> {
+        let cached = self.entries.get(dir)?;
+        let unchanged = cached.dirs.iter()
+            .all(|(path, mtime)| Self::mtime(&self.root_path.join(path)) == Some(*mtime));
+
+        if !unchanged {
+            self.entries.remove(dir);
+            self.dirty = true;
+            return None;
+        }
+
+        self.used.insert(dir.to_string());
+        Some(cached.trackable)
+    }
+
+    /// Records the answer for `dir`, found by reading the directories in `visited`.
+    /// Nothing is recorded while one of them is too recently modified to be trusted.
+    pub fn record(&mut self, dir: &str, trackable: bool, visited: &[PathBuf]) {
+        if dir.contains('\n') {
+            return;
+        }
+        let racy_after = SystemTime::now()
+            .checked_sub(RACY_WINDOW)
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |time| time.as_nanos());
+
+        let mut dirs = Vec::with_capacity(visited.len());
+        for path in visited {
+            let key = path_key(path);
+            match Self::mtime(&self.root_path.join(path)) {
+                Some(mtime) if mtime < racy_after && !key.contains('\n') => dirs.push((key, mtime)),
+                _ => return,
+            }
+        }
+
+        self.entries.insert(dir.to_string(), CachedDir { trackable, dirs });
+        self.used.insert(dir.to_string());
+        self.dirty = true;
+    }
+
+    /// Writes the cache back if it changed, keeping only directories seen in this run.
+    /// The data is written to a file of this process's own and renamed into place.
+    pub fn save(&mut self) -> Result<(), Error> {
+        let stale = self.entries.len() != self.used.len();
+        if !self.dirty && !stale {
+            return Ok(());
+        }
+
+        let used = &self.used;
+        self.entries.retain(|dir, _| used.contains(dir));
+
+        let mut dirs: Vec<&String> = self.entries.keys().collect();
+        dirs.sort();
+
+        let mut content = String::new();
+        for dir in dirs {
+            let cached = &self.entries[dir];
+            content.push_str(&format!("D {} {}\n", if cached.trackable { 1 } else { 0 }, dir));
+            for (path, mtime) in &cached.dirs {
+                content.push_str(&format!("M {} {}\n", mtime, path));
+            }
+        }
+
+        let temp_path = self.path.with_extension(format!("{}.tmp", std::process::id()));
+        fs::write(&temp_path, content)?;
+        fs::rename(&temp_path, &self.path).map_err(|e| {
+            let _ = fs::remove_file(&temp_path);
+            Error::IO(e)
+        })?;
+
+        self.dirty = false;
+        Ok(())
+    }
+
+    fn mtime(path: &Path) -> Option<u128> {
+        fs::metadata(path).ok()?
+            .modified().ok()?
+            .duration_since(UNIX_EPOCH).ok()
+            .map(|time| time.as_nanos())
+    }
+}
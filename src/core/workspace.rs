@@ -1,19 +1,120 @@
 /// Handles workspace management, file listing, and ignore patterns for AsheraFlow repositories.
 use std::fs;
+use std::io::Read;
 use std::path::{Path, PathBuf};
 use std::collections::{HashMap, HashSet};
 use regex::Regex; // Asigură-te că ai adăugat `regex = "1"` în Cargo.toml
 use crate::errors::error::Error;
+use crate::core::attributes::Attributes;
+use crate::core::config::{Config, Eol};
+use crate::core::database::database::{hash_blob_data, hash_blob_file, hash_files_with};
+use crate::core::file_mode::FileMode;
+
+/// Converts a relative path to the `/`-separated form used for index and tree keys.
+/// Only the platform separator is rewritten, so a `\` in a Unix file name is kept.
+pub fn path_key(path: &Path) -> String {
+    let path = path.to_string_lossy();
+    if std::path::MAIN_SEPARATOR == '/' {
+        path.into_owned()
+    } else {
+        path.replace(std::path::MAIN_SEPARATOR, "/")
+    }
+}
+
+/// How much of a file is read to guess whether it is binary
+const BINARY_SAMPLE_SIZE: usize = 8192;
 
 pub struct Workspace {
     pub root_path: PathBuf,
+    /// Text/binary overrides from `.ashattributes`
+    pub attributes: Attributes,
+    /// Line-ending normalization from `core.eol` in `.ash/config`; None leaves content as is
+    pub eol: Option<Eol>,
 }
 
 impl Workspace {
     pub fn new(root_path: &Path) -> Self {
         Workspace {
             root_path: root_path.to_path_buf(),
+            attributes: Attributes::load(root_path),
+            eol: Eol::from_config(&Config::load(&root_path.join(".ash"))),
+        }
+    }
+
+    /// Whether `path` should be treated as binary: `.ashattributes` decides when it
+    /// has a rule for the path, otherwise the content is sniffed
+    pub fn is_binary(&self, path: &str, content: &[u8]) -> bool {
+        self.attributes.is_binary(path)
+            .unwrap_or_else(|| crate::core::diff::myers::is_binary_content(content))
+    }
+
+    /// Like `is_binary`, but only reads the start of `reader`, the part content sniffing
+    /// looks at, so large files never have to be loaded to be recognized as binary
+    pub fn is_binary_stream(&self, path: &str, reader: impl Read) -> Result<bool, Error> {
+        if let Some(binary) = self.attributes.is_binary(path) {
+            return Ok(binary);
+        }
+
+        let mut sample = Vec::with_capacity(BINARY_SAMPLE_SIZE);
+        reader.take(BINARY_SAMPLE_SIZE as u64).read_to_end(&mut sample)?;
+        Ok(crate::core::diff::myers::is_binary_content(&sample))
+    }
+
+    /// The blob OID of a working tree file as add would store it. Without `core.eol` the file
+    /// is hashed in chunks; with it the content has to be normalized first, so it is read whole
+    pub fn hash_file(&self, path: &Path) -> Result<String, Error> {
+        if self.eol.is_none() {
+            return hash_blob_file(&self.root_path.join(path)).map_err(Error::IO);
+        }
+
+        let data = self.normalize_eol(&path_key(path), self.read_file(path)?);
+        Ok(hash_blob_data(&data))
+    }
+
+    /// `hash_file` for many files at once, on a pool of threads. If any file cannot be read,
+    /// the error for the first failing path in sorted order is returned
+    pub fn hash_files_parallel(&self, paths: &[PathBuf]) -> Result<HashMap<PathBuf, String>, Error> {
+        hash_files_with(paths, |path| self.hash_file(path))
+    }
+
+    /// Whether checkout may rewrite file content (CRLF line endings), so blobs can't be
+    /// streamed to disk as they are
+    pub fn converts_on_checkout(&self) -> bool {
+        self.eol.is_some_and(Eol::uses_crlf)
+    }
+
+    /// Content as it is stored in the repository: with `core.eol` set, CRLF line endings
+    /// of text files become LF. Binary files are returned unchanged
+    pub fn normalize_eol(&self, path: &str, content: Vec<u8>) -> Vec<u8> {
+        if self.eol.is_none() || !content.contains(&b'\r') || self.is_binary(path, &content) {
+            return content;
+        }
+
+        let mut normalized = Vec::with_capacity(content.len());
+        for (i, &byte) in content.iter().enumerate() {
+            if byte == b'\r' && content.get(i + 1) == Some(&b'\n') {
+                continue;
+            }
+            normalized.push(byte);
         }
+        normalized
+    }
+
+    /// Content as it is written to the working tree: when `core.eol` asks for CRLF, the
+    /// LF line endings of text files become CRLF. Binary files are returned unchanged
+    pub fn denormalize_eol(&self, path: &str, content: Vec<u8>) -> Vec<u8> {
+        if !self.eol.is_some_and(Eol::uses_crlf) || self.is_binary(path, &content) {
+            return content;
+        }
+
+        let mut denormalized = Vec::with_capacity(content.len() + content.len() / 32);
+        for (i, &byte) in content.iter().enumerate() {
+            if byte == b'\n' && (i == 0 || content[i - 1] != b'\r') {
+                denormalized.push(b'\r');
+            }
+            denormalized.push(byte);
+        }
+        denormalized
     }
 
     // Load ignore patterns from .ashignore
@@ -45,83 +146,50 @@ impl Workspace {
     pub fn list_files(&self) -> Result<Vec<PathBuf>, Error> {
         let ignore_patterns = self.load_ignore_patterns();
         let mut files = Vec::new();
-        self.list_files_recursive(&self.root_path, PathBuf::new(), &mut files, &ignore_patterns)?;
+
+        // An ignored directory is only skipped as a whole when a pattern names the directory itself;
+        // otherwise its files are checked one by one
+        let walk = self.walk(Path::new(""), |entry| {
+            let rel_path_str = path_key(&entry.path);
+            !self.matches_any_pattern(&rel_path_str, &ignore_patterns) || !ignore_patterns.iter().any(|p| {
+                let norm_p = p.replace("\\", "/");
+                (norm_p.ends_with('/') && rel_path_str.starts_with(&norm_p[..norm_p.len()-1])) || norm_p == rel_path_str
+            })
+        })?;
+
+        for entry in walk {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(Error::IO(e)) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+                    eprintln!("Warning: Permission denied reading a directory in {}", self.root_path.display());
+                    continue;
+                },
+                Err(e) => return Err(e),
+            };
+
+            if entry.metadata.is_file() && !self.matches_any_pattern(&path_key(&entry.path), &ignore_patterns) {
+                files.push(entry.path);
+            }
+        }
+
         Ok(files)
     }
 
-
-     // Helper for recursive listing
-     fn list_files_recursive(
-         &self,
-         abs_dir_path: &Path,
-         rel_dir_path: PathBuf, // Pass relative path for checking ignores
-         files: &mut Vec<PathBuf>,
-         ignore_patterns: &HashSet<String>,
-     ) -> Result<(), Error> {
-         match fs::read_dir(abs_dir_path) {
-             Ok(entries) => {
-                 for entry_result in entries {
-                     match entry_result {
-                         Ok(entry) => {
-                             let entry_abs_path = entry.path();
-                             let file_name = entry.file_name();
-
-                             // Construct relative path for ignore checking
-                             let entry_rel_path = rel_dir_path.join(&file_name);
-                             let rel_path_str = entry_rel_path.to_string_lossy().to_string().replace("\\", "/"); // Normalize
-
-                             // --- Ignore Check ---
-                             if self.matches_any_pattern(&rel_path_str, ignore_patterns) {
-                                 // If the pattern specifically targets a directory (ends with /), ignore it and don't recurse
-                                 // Also ignore if it's an exact match for a non-directory pattern
-                                  if entry_abs_path.is_dir() {
-                                       // Check if any pattern matches this directory specifically
-                                       let dir_pattern_match = ignore_patterns.iter().any(|p| {
-                                            let norm_p = p.replace("\\", "/");
-                                            (norm_p.ends_with('/') && rel_path_str.starts_with(&norm_p[..norm_p.len()-1])) || norm_p == rel_path_str
-                                       });
-                                       if dir_pattern_match {
-                                            //println!("Ignoring directory and contents: {}", rel_path_str);
-                                            continue; // Skip recursion
-                                       }
-                                  } else {
-                                       // If it's a file and matches any pattern, ignore it
-                                       //println!("Ignoring file: {}", rel_path_str);
-                                       continue;
-                                  }
-                             }
-                             // --- End Ignore Check ---
-
-                             if entry_abs_path.is_dir() {
-                                 // Recursively scan subdirectories
-                                 self.list_files_recursive(&entry_abs_path, entry_rel_path, files, ignore_patterns)?;
-                             } else if entry_abs_path.is_file() {
-                                 // Add file if it's not ignored
-                                 files.push(entry_rel_path);
-                             }
-                         },
-                         Err(e) => {
-                              if e.kind() == std::io::ErrorKind::PermissionDenied {
-                                   eprintln!("Warning: Permission denied reading entry in {}", abs_dir_path.display());
-                                   continue;
-                              } else {
-                                   return Err(Error::IO(e));
-                              }
-                         }
-                     }
-                 }
-                 Ok(())
-             },
-             Err(e) => {
-                 if e.kind() == std::io::ErrorKind::PermissionDenied {
-                     eprintln!("Warning: Permission denied reading directory {}", abs_dir_path.display());
-                     Ok(())
-                 } else {
-                     Err(Error::IO(e))
-                 }
-             }
-         }
-     }
+    /// Walks the workspace depth-first from `start` (relative to the root), yielding every file
+    /// and directory with its metadata, read once. A directory is entered only when `descend`
+    /// accepts it; `.ash` is never entered.
+    pub fn walk<F>(&self, start: &Path, descend: F) -> Result<Walk<F>, Error>
+    where
+        F: FnMut(&WalkEntry) -> bool,
+    {
+        let entries = fs::read_dir(self.root_path.join(start)).map_err(Error::IO)?;
+
+        Ok(Walk {
+            stack: vec![(start.to_path_buf(), entries)],
+            pending_error: None,
+            descend,
+        })
+    }
 
     // List files starting from a specific path (for add command)
     pub fn list_files_from(&self, start_path: &Path, index_entries: &HashMap<String, String>) -> Result<(Vec<PathBuf>, Vec<String>), Error> {
@@ -184,7 +252,8 @@ impl Workspace {
         } else {
             let rel_path_str = rel_start_path.to_string_lossy().to_string();
             let ignore_patterns = self.load_ignore_patterns();
-            if !self.matches_any_pattern(&rel_path_str, &ignore_patterns) {
+            // Ignore rules only apply to untracked files
+            if expected_files.contains(&rel_path_str) || !self.matches_any_pattern(&rel_path_str, &ignore_patterns) {
                 files_found.push(rel_start_path);
             }
             expected_files.remove(&rel_path_str);
@@ -213,8 +282,14 @@ impl Workspace {
                             let rel_path_str = entry_rel_path.to_string_lossy().to_string().replace("\\", "/");
 
                             if self.matches_any_pattern(&rel_path_str, ignore_patterns) {
-                                 // Skip ignored paths entirely
-                                continue;
+                                // Ignore rules only apply to untracked files: keep tracked files,
+                                // and directories holding some, so their changes are still picked up
+                                let dir_prefix = format!("{}/", rel_path_str);
+                                let holds_tracked = expected_files.contains(&rel_path_str)
+                                    || expected_files.iter().any(|path| path.starts_with(&dir_prefix));
+                                if !holds_tracked {
+                                    continue;
+                                }
                             }
 
                             if entry_path.is_dir() {
@@ -337,6 +412,11 @@ impl Workspace {
     }
 
     pub fn write_file(&self, path: &Path, data: &[u8]) -> Result<(), Error> {
+        self.write_stream(path, &mut &data[..])
+    }
+
+    /// Writes everything `reader` yields to `path`, a chunk at a time
+    pub fn write_stream(&self, path: &Path, reader: &mut dyn Read) -> Result<(), Error> {
         let full_path = self.root_path.join(path);
         if let Some(parent) = full_path.parent() {
              if !parent.exists() {
@@ -344,8 +424,78 @@ impl Workspace {
                 std::fs::create_dir_all(parent).map_err(Error::IO)?;
              }
         }
-        //println!("Writing file: {} ({} bytes)", full_path.display(), data.len());
-        std::fs::write(&full_path, data).map_err(Error::IO)
+        // Tracked files are overwritten even when marked read-only
+        self.make_writable(path)?;
+        let permission_denied = |e: std::io::Error| match e.kind() {
+            std::io::ErrorKind::PermissionDenied => Error::Generic(format!(
+                "unable to write '{}': permission denied", path.display()
+            )),
+            _ => Error::IO(e),
+        };
+        let mut file = fs::File::create(&full_path).map_err(permission_denied)?;
+        std::io::copy(reader, &mut file).map_err(Error::IO)?;
+        Ok(())
+    }
+
+    /// Sets or clears the executable bits of a written file to match `mode`, so the stat
+    /// recorded in the index agrees with the tree entry it came from
+    pub fn set_mode(&self, path: &Path, mode: FileMode) -> Result<(), Error> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let full_path = self.root_path.join(path);
+            let mut permissions = fs::metadata(&full_path).map_err(Error::IO)?.permissions();
+            let current = permissions.mode();
+            // Executable for everyone who can read the file, as `chmod +x` would
+            let wanted = if FileMode::is_executable(mode.0) {
+                current | ((current & 0o444) >> 2)
+            } else {
+                current & !0o111
+            };
+
+            if wanted != current {
+                permissions.set_mode(wanted);
+                fs::set_permissions(&full_path, permissions).map_err(Error::IO)?;
+            }
+        }
+
+        #[cfg(not(unix))]
+        let _ = (path, mode);
+
+        Ok(())
+    }
+
+    /// Gives the owner write permission on an existing read-only file so it can be overwritten
+    fn make_writable(&self, path: &Path) -> Result<(), Error> {
+        let full_path = self.root_path.join(path);
+        let metadata = match fs::symlink_metadata(&full_path) {
+            Ok(metadata) if metadata.is_file() => metadata,
+            _ => return Ok(()),
+        };
+        let mut permissions = metadata.permissions();
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            if permissions.mode() & 0o200 != 0 {
+                return Ok(());
+            }
+            permissions.set_mode(permissions.mode() | 0o200);
+        }
+
+        #[cfg(not(unix))]
+        {
+            if !permissions.readonly() {
+                return Ok(());
+            }
+            #[allow(clippy::permissions_set_readonly_false)]
+            permissions.set_readonly(false);
+        }
+
+        fs::set_permissions(&full_path, permissions).map_err(|e| Error::Generic(format!(
+            "unable to write '{}': file is read-only and could not be made writable: {}", path.display(), e
+        )))
     }
 
     // Includes logging added previously
@@ -486,4 +636,111 @@ impl Workspace {
             Ok(content.to_string())
         }
     }
-}
\ No newline at end of file
+}
+/// A file or directory found by `Workspace::walk`
+pub struct WalkEntry {
+    /// Path relative to the workspace root
+    pub path: PathBuf,
+    pub metadata: fs::Metadata,
+}
+
+/// Iterator behind `Workspace::walk`. Open directories share one stack instead of a
+/// recursive call per level.
+pub struct Walk<F> {
+    stack: Vec<(PathBuf, fs::ReadDir)>,
+    // A directory that could not be opened, reported after the directory itself
+    pending_error: Option<Error>,
+    descend: F,
+}
+
+impl<F> Iterator for Walk<F>
+where
+    F: FnMut(&WalkEntry) -> bool,
+{
+    type Item = Result<WalkEntry, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(error) = self.pending_error.take() {
+            return Some(Err(error));
+        }
+
+        loop {
+            let (dir, entries) = self.stack.last_mut()?;
+            let entry = match entries.next() {
+                Some(Ok(entry)) => entry,
+                Some(Err(e)) => return Some(Err(Error::IO(e))),
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
+            };
+
+            if entry.file_name() == ".ash" {
+                continue;
+            }
+
+            let path = dir.join(entry.file_name());
+            let abs_path = entry.path();
+
+            // Follows symlinks like `Path::is_dir`; dangling links are skipped
+            let metadata = match fs::metadata(&abs_path) {
+                Ok(metadata) => metadata,
+                Err(_) => continue,
+            };
+
+            let walk_entry = WalkEntry { path, metadata };
+            if walk_entry.metadata.is_dir() && (self.descend)(&walk_entry) {
+                match fs::read_dir(&abs_path) {
+                    Ok(children) => self.stack.push((walk_entry.path.clone(), children)),
+                    Err(e) => self.pending_error = Some(Error::IO(e)),
+                }
+            }
+
+            return Some(Ok(walk_entry));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::branch::BranchCommand;
+    use crate::commands::checkout::CheckoutCommand;
+    use crate::test_support::TestRepo;
+
+    #[test]
+    fn crlf_files_round_trip_through_add_and_checkout() {
+        let repo = TestRepo::new();
+        let mut config = Config::load(&repo.path().join(".ash"));
+        config.set("core", "eol", "crlf");
+        config.save().unwrap();
+
+        repo.commit_files(&[("f.txt", "a\r\nb\r\n")], "crlf");
+        assert_eq!(repo.staged_oid("f.txt"), Some(repo.blob_oid("a\nb\n")));
+        BranchCommand::execute("first", None).unwrap();
+
+        repo.commit_files(&[("f.txt", "a\r\nc\r\n")], "change");
+        assert_eq!(repo.staged_oid("f.txt"), Some(repo.blob_oid("a\nc\n")));
+
+        CheckoutCommand::execute("first").unwrap();
+        assert_eq!(repo.read("f.txt"), "a\r\nb\r\n");
+        assert_eq!(repo.repo().workspace.hash_file(Path::new("f.txt")).unwrap(), repo.blob_oid("a\nb\n"));
+    }
+
+    #[test]
+    fn checkout_overwrites_a_read_only_tracked_file() {
+        let repo = TestRepo::new();
+        repo.commit_files(&[("f.txt", "one\n")], "first");
+        BranchCommand::execute("first", None).unwrap();
+        repo.commit_files(&[("f.txt", "two\n")], "second");
+
+        let path = repo.path().join("f.txt");
+        let mut permissions = fs::metadata(&path).unwrap().permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(&path, permissions).unwrap();
+
+        CheckoutCommand::execute("first").unwrap();
+        assert_eq!(repo.read("f.txt"), "one\n");
+        assert!(!fs::metadata(&path).unwrap().permissions().readonly());
+    }
+}
@@ -0,0 +1,10 @@
+/// Library target for AsheraFlow.
+/// Exposes the core repository logic so it can be driven without the CLI (e.g. from benchmarks).
+pub mod cli;
+pub mod commands;
+pub mod validators;
+pub mod errors;
+pub mod core;
+
+#[cfg(test)]
+pub(crate) mod test_support;
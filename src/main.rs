@@ -3,9 +3,10 @@ use std::env;
 use std::process;
 use cli::args::Command;
 use cli::parser::CliParser;
-use commands::checkout::CheckoutCommand;
+use commands::checkout::{CheckoutCommand, RestoreSource};
 use commands::commit::CommitCommand;
 use commands::diff::DiffCommand;
+use commands::apply::ApplyCommand;
 use commands::init::InitCommand;
 use commands::add::AddCommand;
 use commands::log::LogCommand;
@@ -13,34 +14,46 @@ use commands::status::StatusCommand;
 use commands::branch::BranchCommand;
 use commands::merge::MergeCommand;
 use commands::merge_tool::MergeToolCommand;
+use commands::merge_base::MergeBaseCommand;
+use commands::rev_parse::RevParseCommand;
+use commands::tag::TagCommand;
+use commands::reflog::ReflogCommand;
+use commands::ls_files::LsFilesCommand;
+use commands::gc::GcCommand;
+use commands::prune::PruneCommand;
+use commands::stash::StashCommand;
 use commands::rm::RmCommand;
 use commands::reset::ResetCommand;
+use commands::restore::RestoreCommand;
+use commands::config::ConfigCommand;
+use commands::show::ShowCommand;
+use commands::cat_file::CatFileCommand;
+use commands::fsck::FsckCommand;
+use commands::verify_objects::VerifyObjectsCommand;
 use commands::sprint::{
     SprintStartCommand, SprintInfoCommand, SprintCommitMapCommand,
     SprintBurndownCommand, SprintVelocityCommand, SprintAdvanceCommand,
-    SprintViewCommand,
+    SprintViewCommand, SprintCloseCommand, SprintReportCommand,
 };
 use commands::task::task_create::TaskCreateCommand;
 use commands::task::task_complete::TaskCompleteCommand;
 use commands::task::task_status::TaskStatusCommand;
 use commands::task::task_list::TaskListCommand;
-use std::path::Path;
-use crate::core::index::index::Index;
-use crate::core::refs::Refs;
 use crate::errors::error::Error;
 use crate::core::repository::repository::Repository;
-use crate::core::database::database::Database;
+use crate::core::commit_metadata::Priority;
+use crate::core::merge::diff3::ConflictStyle;
+use crate::core::merge::resolve::MergeStrategy;
+use crate::core::progress::PROGRESS_ENV;
+use crate::core::pager::NO_PAGER_ENV;
+use crate::core::color::{Color, ColorMode};
 use commands::commit_writer::CommitWriter;
 use crate::core::repository::pending_commit::PendingCommitType;
 use commands::commit::get_editor_command;
 use commands::cherry_pick::CherryPickCommand;
 use commands::revert::RevertCommand;
 
-mod cli;
-mod commands;
-mod validators;
-mod errors;
-mod core;
+use asheraflow::{cli, commands, core, errors};
 
 /// Entry point for the AsheraFlow CLI application.
 /// Parses command-line arguments and dispatches to the appropriate command handler.
@@ -49,26 +62,67 @@ mod core;
 const ORIG_HEAD: &str = "ORIG_HEAD";
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
+
+    // Global options come before the command name
+    while let Some(option) = args.get(1) {
+        match option.as_str() {
+            "--no-pager" | "-P" => env::set_var(NO_PAGER_ENV, "1"),
+            "--color" => set_color_mode("always"),
+            "--no-color" => set_color_mode("never"),
+            option if option.starts_with("--color=") => set_color_mode(&option["--color=".len()..]),
+            _ => break,
+        }
+        args.remove(1);
+    }
 
     // Parse CLI arguments and execute the corresponding command
     match CliParser::parse(args) {
         Ok(cli_args) => {
             match cli_args.command {
                 Command::Init { path } => handle_init_command(&path),
-                Command::Commit { message, amend, reuse_message, edit } => 
-                    handle_commit_command(&message, amend, reuse_message, edit),
-                Command::Add { paths } => handle_add_command(&paths),
-                Command::Status { porcelain, color } => handle_status_command(porcelain, &color),
-                Command::Diff { paths, cached } => handle_diff_command(&paths, cached),
-                Command::Branch { name, start_point, verbose, delete, force } => {
-                    handle_branch_command(&name, start_point.as_deref(), verbose, delete, force)
+                Command::Commit { message, amend, reuse_message, edit, author, date, paths } => 
+                    handle_commit_command(&message, amend, reuse_message, edit, author, date, &paths),
+                Command::Add { paths, patch } => handle_add_command(&paths, patch),
+                Command::Status { porcelain, porcelain_v2, short, branch, color, no_untracked_cache } => handle_status_command(porcelain, porcelain_v2, short, branch, &color, !no_untracked_cache),
+                Command::Diff { paths, cached, stat, word_diff, context, git, find_renames, color } => {
+                    handle_diff_command(&paths, cached, stat, word_diff, context, git, find_renames, &color)
                 },
-                Command::Checkout { target } => handle_checkout_command(&target),
-                Command::Log { revisions, abbrev, format, patch, decorate } => {
-                    handle_log_command(&revisions, abbrev, &format, patch, &decorate)
+                Command::Apply { patch, cached, index, check } => handle_apply_command(patch.as_deref(), cached, index, check),
+                Command::Branch { name, start_point, verbose, delete, force, rename, set_upstream } => {
+                    handle_branch_command(&name, start_point.as_deref(), verbose, delete, force, rename, set_upstream.as_deref())
                 },
-                Command::Merge { branch, message, abort, continue_merge, tool } => {
+                Command::Checkout { target, orphan, patch, paths, new_branch, force, progress } => {
+                    set_progress_override(progress);
+                    handle_checkout_command(&target, orphan, patch, &paths, new_branch.as_deref(), force)
+                },
+                Command::Log { revisions, abbrev, format, patch, decorate, graph, topo_order, author, since, until, max_count } => {
+                    let mut filters = HashMap::new();
+                    if topo_order {
+                        filters.insert("topo_order".to_string(), "true".to_string());
+                    }
+                    if let Some(author) = author {
+                        filters.insert("author".to_string(), author);
+                    }
+                    if let Some(since) = since {
+                        filters.insert("since".to_string(), since);
+                    }
+                    if let Some(until) = until {
+                        filters.insert("until".to_string(), until);
+                    }
+                    if let Some(max_count) = max_count {
+                        filters.insert("max_count".to_string(), max_count.to_string());
+                    }
+                    handle_log_command(&revisions, abbrev, &format, patch, &decorate, graph, filters)
+                },
+                Command::Show { revision } => handle_show_command(revision.as_deref()),
+                Command::Fsck { verbose, connectivity_only } => handle_fsck_command(verbose, connectivity_only),
+                Command::VerifyObjects => handle_verify_objects_command(),
+                Command::CatFile { object, show_type, show_size, pretty } => {
+                    handle_cat_file_command(&object, show_type, show_size, pretty)
+                },
+                Command::Merge { branches, message, abort, continue_merge, tool, strategy, conflict_style, no_commit, no_ff, progress } => {
+                    set_progress_override(progress);
                     if abort {
                         handle_merge_abort_command();
                     } else if continue_merge {
@@ -76,17 +130,40 @@ fn main() {
                             Ok(_) => process::exit(0),
                             Err(e) => exit_with_error(&format!("fatal: {}", e)),
                         }
-                    } else if tool.is_some() && branch.is_empty() {
+                    } else if tool.is_some() && branches.is_empty() {
                         handle_merge_tool_command(tool.as_deref());
+                    } else if branches.len() > 1 {
+                        handle_octopus_merge_command(&branches, message.as_deref(), strategy);
                     } else {
-                        handle_merge_command(&branch, message.as_deref());
+                        handle_merge_command(&branches[0], message.as_deref(), strategy, conflict_style, no_commit, no_ff);
                     }
                 },
-                Command::Rm { files, cached, force, recursive } => {
-                    handle_rm_command(&files, cached, force, recursive)
+                Command::MergeBase { commits, is_ancestor, all } => {
+                    handle_merge_base_command(&commits, is_ancestor, all)
+                },
+                Command::RevParse { revisions } => handle_rev_parse_command(&revisions),
+                Command::Stash { action, message, include_untracked, keep_index } => {
+                    handle_stash_command(&action, message.as_deref(), include_untracked, keep_index)
+                },
+                Command::LsFiles { stage, unmerged } => handle_ls_files_command(stage, unmerged),
+                Command::Gc => handle_gc_command(),
+                Command::Prune { dry_run, expire } => handle_prune_command(dry_run, expire.as_deref()),
+                Command::Reflog { ref_name } => handle_reflog_command(ref_name.as_deref()),
+                Command::Tag { name, target, message, delete, list } => {
+                    handle_tag_command(name.as_deref(), target.as_deref(), message.as_deref(), delete, list)
+                },
+                Command::Rm { files, cached, force, recursive, dry_run } => {
+                    handle_rm_command(&files, cached, force, recursive, dry_run)
+                },
+                Command::Config { name, value } => {
+                    handle_config_command(&name, value.as_deref())
+                },
+                Command::Restore { paths, staged, worktree, source } => {
+                    handle_restore_command(&paths, staged, worktree, source.as_deref())
                 },
-                Command::Reset { files, soft, mixed, hard, force, reuse_message } => {
-                    handle_reset_command(&files, soft, mixed, hard, force, reuse_message.as_deref())
+                Command::Reset { revision, files, soft, mixed, hard, force, reuse_message, patch, progress } => {
+                    set_progress_override(progress);
+                    handle_reset_command(revision.as_deref(), &files, soft, mixed, hard, force, reuse_message.as_deref(), patch)
                 },
                 Command::CherryPick { args, r#continue, abort, quit, mainline } => {
                     handle_cherry_pick_command(&args, r#continue, abort, quit, mainline)
@@ -105,8 +182,8 @@ fn main() {
                     handle_sprint_commitmap_command(sprint_name.as_deref())
                 },
                 // Add other sprint command handlers
-                Command::SprintBurndown { sprint_name } => {
-                    handle_sprint_burndown_command(sprint_name.as_deref())
+                Command::SprintBurndown { sprint_name, format } => {
+                    handle_sprint_burndown_command(sprint_name.as_deref(), format.as_deref())
                 },
                 Command::SprintVelocity {} => {
                     handle_sprint_velocity_command()
@@ -117,9 +194,15 @@ fn main() {
                 Command::SprintView {} => {
                     handle_sprint_view_command()
                 },
+                Command::SprintClose { sprint_name, tag, carry_over_to } => {
+                    handle_sprint_close_command(sprint_name.as_deref(), tag, carry_over_to.as_deref())
+                },
+                Command::SprintReport { sprint_name, json } => {
+                    handle_sprint_report_command(sprint_name.as_deref(), json)
+                },
                 // Task management commands
-                Command::TaskCreate { id, description, story_points } => {
-                    handle_task_create_command(&id, &description, story_points)
+                Command::TaskCreate { id, description, story_points, depends_on, priority } => {
+                    handle_task_create_command(&id, &description, story_points, &depends_on, priority)
                 },
                 Command::TaskComplete { id, story_points: _, auto_merge } => {
                     handle_task_complete_command(&id, auto_merge)
@@ -148,8 +231,8 @@ fn main() {
 }
 
 /// Handles the 'commit' command, creating a new commit or amending an existing one.
-fn handle_commit_command(message: &str, amend: bool, reuse_message: Option<String>, edit: bool) {
-    match CommitCommand::execute(message, amend, reuse_message.as_deref(), edit) {
+fn handle_commit_command(message: &str, amend: bool, reuse_message: Option<String>, edit: bool, author: Option<String>, date: Option<String>, paths: &[String]) {
+    match CommitCommand::execute(message, amend, reuse_message.as_deref(), edit, author.as_deref(), date.as_deref(), paths) {
         Ok(_) => process::exit(0),
         Err(e) => exit_with_error(&format!("fatal: {}", e)),
     }
@@ -163,33 +246,52 @@ fn handle_init_command(path: &str) {
     }
 }
 
-/// Handles the 'add' command, staging files for commit.
-fn handle_add_command(paths: &[String]) {
-    match AddCommand::execute(paths) {
+/// Handles the 'add' command, staging files for commit, or chosen hunks with --patch.
+fn handle_add_command(paths: &[String], patch: bool) {
+    let result = if patch {
+        AddCommand::execute_patch(paths)
+    } else {
+        AddCommand::execute(paths)
+    };
+    match result {
         Ok(_) => process::exit(0),
         Err(e) => exit_with_error(&format!("fatal: {}", e)),
     }
 }
 
 /// Handles the 'status' command, displaying the current state of the working directory and index.
-fn handle_status_command(porcelain: bool, color: &str) {
-    std::env::set_var("ASH_COLOR", color);
-    match StatusCommand::execute(porcelain) {
+fn handle_status_command(porcelain: bool, porcelain_v2: bool, short: bool, branch: bool, color: &str, untracked_cache: bool) {
+    // Without an explicit --color, color.ui or the terminal decides
+    if color != "auto" {
+        set_color_mode(color);
+    }
+    match StatusCommand::execute(porcelain, porcelain_v2, short, branch, untracked_cache) {
         Ok(_) => process::exit(0),
         Err(e) => exit_with_error(&format!("fatal: {}", e)),
     }
 }
 
 /// Handles the 'diff' command, showing changes between commits, commit and working tree, etc.
-fn handle_diff_command(paths: &[String], cached: bool) {
-    match DiffCommand::execute(paths, cached) {
+#[allow(clippy::too_many_arguments)]
+fn handle_diff_command(paths: &[String], cached: bool, stat: bool, word_diff: bool, context: usize, git: bool, find_renames: bool, color: &str) {
+    if color != "auto" {
+        set_color_mode(color);
+    }
+    match DiffCommand::execute(paths, cached, stat, word_diff, context, git, find_renames) {
         Ok(_) => process::exit(0),
         Err(e) => exit_with_error(&format!("fatal: {}", e)),
     }
 }
 
+fn handle_apply_command(patch: Option<&str>, cached: bool, index: bool, check: bool) {
+    match ApplyCommand::execute(patch, cached, index, check) {
+        Ok(_) => process::exit(0),
+        Err(e) => exit_with_error(&format!("error: {}", e)),
+    }
+}
+
 /// Handles the 'branch' command, managing branches (create, delete, list, etc.).
-fn handle_branch_command(name: &str, start_point: Option<&str>, verbose: bool, delete: bool, force: bool) {
+fn handle_branch_command(name: &str, start_point: Option<&str>, verbose: bool, delete: bool, force: bool, rename: bool, set_upstream: Option<&str>) {
     if verbose {
         std::env::set_var("ASH_BRANCH_VERBOSE", "1");
     }
@@ -199,6 +301,12 @@ fn handle_branch_command(name: &str, start_point: Option<&str>, verbose: bool, d
     if force {
         std::env::set_var("ASH_BRANCH_FORCE", "1");
     }
+    if rename {
+        std::env::set_var("ASH_BRANCH_MOVE", "1");
+    }
+    if let Some(upstream) = set_upstream {
+        std::env::set_var("ASH_BRANCH_SET_UPSTREAM", upstream);
+    }
     match BranchCommand::execute(name, start_point) {
         Ok(_) => process::exit(0),
         Err(e) => exit_with_error(&format!("fatal: {}", e)),
@@ -207,21 +315,71 @@ fn handle_branch_command(name: &str, start_point: Option<&str>, verbose: bool, d
 
 
 /// Handles the 'log' command, displaying commit logs with various formatting options.
-fn handle_log_command(revisions: &[String], abbrev: bool, format: &str, patch: bool, decorate: &str) {
-    let mut options = HashMap::new();
+fn handle_log_command(revisions: &[String], abbrev: bool, format: &str, patch: bool, decorate: &str, graph: bool, filters: HashMap<String, String>) {
+    let mut options = filters;
     options.insert("abbrev".to_string(), abbrev.to_string());
     options.insert("format".to_string(), format.to_string());
     options.insert("patch".to_string(), patch.to_string());
     options.insert("decorate".to_string(), decorate.to_string());
+    options.insert("graph".to_string(), graph.to_string());
     match LogCommand::execute(revisions, &options) {
         Ok(_) => process::exit(0),
         Err(e) => exit_with_error(&format!("fatal: {}", e)),
     }
 }
 
+/// Handles the 'show' command, displaying a commit, tree, blob or tag.
+fn handle_show_command(revision: Option<&str>) {
+    match ShowCommand::execute(revision) {
+        Ok(_) => process::exit(0),
+        Err(e) => exit_with_error(&format!("fatal: {}", e)),
+    }
+}
+
+/// Handles the 'fsck' command, exiting with 1 if any object is missing or corrupt.
+fn handle_fsck_command(verbose: bool, connectivity_only: bool) {
+    match FsckCommand::execute(verbose, connectivity_only) {
+        Ok(true) => process::exit(0),
+        Ok(false) => process::exit(1),
+        Err(e) => exit_with_error(&format!("fatal: {}", e)),
+    }
+}
+
+/// Handles the 'verify-objects' command, exiting with 1 if any object is corrupt.
+fn handle_verify_objects_command() {
+    match VerifyObjectsCommand::execute() {
+        Ok(true) => process::exit(0),
+        Ok(false) => process::exit(1),
+        Err(e) => exit_with_error(&format!("fatal: {}", e)),
+    }
+}
+
+/// Handles the 'cat-file' command, printing an object's type, size or content.
+fn handle_cat_file_command(object: &str, show_type: bool, show_size: bool, pretty: bool) {
+    match CatFileCommand::execute(object, show_type, show_size, pretty) {
+        Ok(_) => process::exit(0),
+        Err(e) => exit_with_error(&format!("fatal: {}", e)),
+    }
+}
+
 /// Handles the 'checkout' command, switching branches or restoring working tree files.
-fn handle_checkout_command(target: &str) {
-    match CheckoutCommand::execute(target) {
+fn handle_checkout_command(target: &str, orphan: bool, patch: bool, paths: &[String], new_branch: Option<&str>, force: bool) {
+    let result = if let Some(branch) = new_branch {
+        let start_point = if target.is_empty() { None } else { Some(target) };
+        CheckoutCommand::execute_new_branch(branch, start_point, force)
+    } else if patch {
+        let revision = if target.is_empty() { None } else { Some(target) };
+        CheckoutCommand::execute_patch(revision, paths)
+    } else if !paths.is_empty() {
+        let source = if target.is_empty() { RestoreSource::Index } else { RestoreSource::Revision(target.to_string()) };
+        CheckoutCommand::restore_paths(source, paths)
+    } else if orphan {
+        CheckoutCommand::execute_orphan(target)
+    } else {
+        CheckoutCommand::execute(target)
+    };
+
+    match result {
         Ok(_) => process::exit(0),
         Err(e) => exit_with_error(&format!("fatal: {}", e)),
     }
@@ -238,34 +396,28 @@ fn handle_merge_tool_command(tool: Option<&str>) {
 /// Handles the 'merge --continue' operation, resuming a merge, cherry-pick, or revert after conflicts are resolved.
 fn handle_merge_continue_command() -> Result<(), Error> {
     println!("Checking for unresolved conflicts...");
-    let root_path = Path::new(".");
-    let git_path = root_path.join(".ash");
-    if !git_path.exists() {
+    let mut repo = Repository::new(".")?;
+    if !repo.git_path.exists() {
         return Err(Error::Generic("Not an AsheraFlow repository: .ash directory not found".into()));
     }
-    let db_path = git_path.join("objects");
-    let mut database = Database::new(db_path);
-    let index_path = git_path.join("index");
-    if !index_path.exists() {
+    if !repo.git_path.join("index").exists() {
         return Err(Error::Generic("No index file found.".into()));
     }
-    let mut index = Index::new(index_path);
-    match index.load() {
+    match repo.load_index() {
         Ok(_) => println!("Index loaded successfully"),
         Err(e) => return Err(Error::Generic(format!("Error loading index: {}", e))),
     }
-    if index.has_conflict() {
+    if repo.index.has_conflict() {
         return Err(Error::Generic(
             "Cannot continue due to unresolved conflicts. Fix conflicts and add the files.".into(),
         ));
     }
-    let refs = Refs::new(&git_path);
     let mut commit_writer = CommitWriter::new(
-        root_path,
-        git_path.clone(),
-        &mut database,
-        &mut index,
-        &refs
+        &repo.path,
+        repo.git_path.clone(),
+        &mut repo.database,
+        &mut repo.index,
+        &repo.refs
     );
     if commit_writer.pending_commit.in_progress(PendingCommitType::Merge) {
         return commit_writer.resume_merge(PendingCommitType::Merge, get_editor_command());
@@ -280,17 +432,105 @@ fn handle_merge_continue_command() -> Result<(), Error> {
     }
 }
 
+/// Handles the 'merge-base' command, exiting with 1 if the ancestry check fails or no common ancestor exists.
+fn handle_merge_base_command(commits: &[String], is_ancestor: bool, all: bool) {
+    match MergeBaseCommand::execute(commits, is_ancestor, all) {
+        Ok(true) => process::exit(0),
+        Ok(false) => process::exit(1),
+        Err(e) => exit_with_error(&format!("fatal: {}", e)),
+    }
+}
+
+/// Handles the 'rev-parse' command, printing the commit IDs revisions resolve to.
+fn handle_rev_parse_command(revisions: &[String]) {
+    match RevParseCommand::execute(revisions) {
+        Ok(_) => process::exit(0),
+        Err(e) => exit_with_error(&format!("fatal: {}", e)),
+    }
+}
+
+/// Handles the 'stash' command, saving, restoring or listing stashed changes.
+fn handle_stash_command(action: &str, message: Option<&str>, include_untracked: bool, keep_index: bool) {
+    let result = match action {
+        "pop" => StashCommand::pop(),
+        "list" => StashCommand::list(),
+        _ => StashCommand::push(message, include_untracked, keep_index),
+    };
+
+    match result {
+        Ok(_) => process::exit(0),
+        Err(e) => exit_with_error(&format!("fatal: {}", e)),
+    }
+}
+
+/// Handles the 'ls-files' command, listing the paths in the index.
+fn handle_ls_files_command(stage: bool, unmerged: bool) {
+    match LsFilesCommand::execute(stage, unmerged) {
+        Ok(_) => process::exit(0),
+        Err(e) => exit_with_error(&format!("fatal: {}", e)),
+    }
+}
+
+/// Handles the 'gc' command, packing loose objects.
+fn handle_gc_command() {
+    match GcCommand::execute() {
+        Ok(_) => process::exit(0),
+        Err(e) => exit_with_error(&format!("fatal: {}", e)),
+    }
+}
+
+/// Handles the 'prune' command, deleting unreachable loose objects.
+fn handle_prune_command(dry_run: bool, expire: Option<&str>) {
+    match PruneCommand::execute(dry_run, expire) {
+        Ok(_) => process::exit(0),
+        Err(e) => exit_with_error(&format!("fatal: {}", e)),
+    }
+}
+
+/// Handles the 'reflog' command, listing the history of a ref.
+fn handle_reflog_command(ref_name: Option<&str>) {
+    match ReflogCommand::execute(ref_name) {
+        Ok(_) => process::exit(0),
+        Err(e) => exit_with_error(&format!("fatal: {}", e)),
+    }
+}
+
+/// Handles the 'tag' command, creating, deleting or listing tags.
+fn handle_tag_command(name: Option<&str>, target: Option<&str>, message: Option<&str>, delete: bool, list: bool) {
+    match TagCommand::execute(name, target, message, delete, list) {
+        Ok(_) => process::exit(0),
+        Err(e) => exit_with_error(&format!("fatal: {}", e)),
+    }
+}
+
 /// Handles the 'rm' command, removing files from the working tree and/or index.
-fn handle_rm_command(files: &[String], cached: bool, force: bool, recursive: bool) {
-    match RmCommand::execute(files, cached, force, recursive) {
+fn handle_rm_command(files: &[String], cached: bool, force: bool, recursive: bool, dry_run: bool) {
+    match RmCommand::execute(files, cached, force, recursive, dry_run) {
+        Ok(_) => process::exit(0),
+        Err(e) => exit_with_error(&format!("fatal: {}", e)),
+    }
+}
+
+/// Handles the 'restore' command, restoring index entries and/or working-tree files.
+fn handle_restore_command(paths: &[String], staged: bool, worktree: bool, source: Option<&str>) {
+    match RestoreCommand::execute(paths, staged, worktree, source) {
+        Ok(_) => process::exit(0),
+        Err(e) => exit_with_error(&format!("fatal: {}", e)),
+    }
+}
+
+/// Handles the 'config' command, reading or writing a setting in .ash/config.
+fn handle_config_command(name: &str, value: Option<&str>) {
+    match ConfigCommand::execute(name, value) {
         Ok(_) => process::exit(0),
         Err(e) => exit_with_error(&format!("fatal: {}", e)),
     }
 }
 
 /// Handles the 'reset' command, resetting current HEAD to the specified state.
-fn handle_reset_command(files: &[String], soft: bool, mixed: bool, hard: bool, force: bool, reuse_message: Option<&str>) {
-    match ResetCommand::execute(files, soft, mixed, hard, force, reuse_message) {
+#[allow(clippy::too_many_arguments)]
+fn handle_reset_command(revision: Option<&str>, files: &[String], soft: bool, mixed: bool, hard: bool, force: bool, reuse_message: Option<&str>, patch: bool) {
+    match ResetCommand::execute(revision, files, soft, mixed, hard, force, reuse_message, patch) {
         Ok(_) => process::exit(0),
         Err(e) => exit_with_error(&format!("fatal: {}", e)),
     }
@@ -312,6 +552,22 @@ fn handle_revert_command(commits: &[String], continue_op: bool, abort: bool, qui
     }
 }
 
+/// Applies `--progress`/`--no-progress` to the progress reporters the command creates.
+/// Without either flag, progress is shown only when stdout is a terminal.
+fn set_progress_override(progress: Option<bool>) {
+    if let Some(enabled) = progress {
+        env::set_var(PROGRESS_ENV, if enabled { "1" } else { "0" });
+    }
+}
+
+/// Applies `--color=<when>` for every colored string the command writes
+fn set_color_mode(when: &str) {
+    match ColorMode::parse(when) {
+        Some(mode) => Color::set_mode(mode),
+        None => exit_with_error(&format!("fatal: invalid --color value '{}': expected always, never or auto", when)),
+    }
+}
+
 /// Utility function to print an error message and exit the process with code 1.
 fn exit_with_error(message: &str) -> ! {
     eprintln!("{}", message);
@@ -319,8 +575,8 @@ fn exit_with_error(message: &str) -> ! {
 }
 
 /// Handles the 'merge' command, merging changes from another branch into the current branch.
-fn handle_merge_command(branch: &str, message: Option<&str>) {
-    match MergeCommand::execute(branch, message) {
+fn handle_merge_command(branch: &str, message: Option<&str>, strategy: MergeStrategy, conflict_style: ConflictStyle, no_commit: bool, no_ff: bool) {
+    match MergeCommand::execute(branch, message, strategy, conflict_style, no_commit, no_ff) {
         Ok(_) => process::exit(0),
         Err(e) => {
             if e.to_string().contains("Already up to date") {
@@ -337,13 +593,21 @@ fn handle_merge_command(branch: &str, message: Option<&str>) {
     }
 }
 
+/// Handles a 'merge' of several branches, recording them all in one octopus merge commit.
+fn handle_octopus_merge_command(branches: &[String], message: Option<&str>, strategy: MergeStrategy) {
+    match MergeCommand::execute_octopus(branches, message, strategy) {
+        Ok(_) => process::exit(0),
+        Err(e) => exit_with_error(&format!("fatal: {}", e)),
+    }
+}
+
 /// Handles the 'merge --abort' operation, aborting an in-progress merge and resetting to the original state.
 fn handle_merge_abort_command() {
     let mut repo = match Repository::new(".") {
         Ok(r) => r,
         Err(e) => exit_with_error(&format!("fatal: {}", e)),
     };
-    let git_path = Path::new(".").join(".ash");
+    let git_path = repo.git_path.clone();
     let merge_head_path = git_path.join("MERGE_HEAD");
     if !merge_head_path.exists() {
         exit_with_error("fatal: There is no merge to abort");
@@ -355,7 +619,7 @@ fn handle_merge_abort_command() {
         Ok(content) => content.trim().to_string(),
         Err(e) => exit_with_error(&format!("fatal: Failed to read ORIG_HEAD: {}", e)),
     };
-    match ResetCommand::execute(&[orig_head], false, false, true, true, None) {
+    match ResetCommand::execute(Some(&orig_head), &[], false, false, true, true, None, false) {
         Ok(_) => {
             println!("Merge aborted");
             process::exit(0);
@@ -389,8 +653,8 @@ fn handle_sprint_commitmap_command(sprint_name: Option<&str>) {
 }
 
 /// Handles the 'sprint burndown' command, displaying the burndown chart for a sprint.
-fn handle_sprint_burndown_command(sprint_name: Option<&str>) {
-    match SprintBurndownCommand::execute(sprint_name) {
+fn handle_sprint_burndown_command(sprint_name: Option<&str>, format: Option<&str>) {
+    match SprintBurndownCommand::execute(sprint_name, format) {
         Ok(_) => process::exit(0),
         Err(e) => exit_with_error(&format!("fatal: {}", e)),
     }
@@ -419,10 +683,26 @@ fn handle_sprint_view_command() {
         Err(e) => exit_with_error(&format!("fatal: {}", e)),
     }
 }
+/// Handles the 'sprint close' command, closing a sprint and optionally tagging its end and carrying over its tasks.
+/// Handles the 'sprint close' command, closing the active sprint and optionally tagging its end.
+fn handle_sprint_close_command(sprint_name: Option<&str>, tag: bool, carry_over_to: Option<&str>) {
+    match SprintCloseCommand::execute(sprint_name, tag, carry_over_to) {
+        Ok(_) => process::exit(0),
+        Err(e) => exit_with_error(&format!("fatal: {}", e)),
+    }
+}
+
+/// Handles the 'sprint report' command, printing a retrospective summary of a sprint.
+fn handle_sprint_report_command(sprint_name: Option<&str>, json: bool) {
+    match SprintReportCommand::execute(sprint_name, json) {
+        Ok(_) => process::exit(0),
+        Err(e) => exit_with_error(&format!("fatal: {}", e)),
+    }
+}
 
 /// Handles the 'task create' command, creating a new task with the given details.
-fn handle_task_create_command(id: &str, description: &str, story_points: Option<u32>) {
-    match TaskCreateCommand::execute(id, description, story_points) {
+fn handle_task_create_command(id: &str, description: &str, story_points: Option<u32>, depends_on: &[String], priority: Priority) {
+    match TaskCreateCommand::execute(id, description, story_points, depends_on, priority) {
         Ok(_) => process::exit(0),
         Err(e) => exit_with_error(&format!("fatal: {}", e)),
     }
@@ -0,0 +1,106 @@
+/// Helpers for unit tests that drive commands end to end.
+/// Commands work on the repository in the current directory, so tests that run them take
+/// turns, each inside a fresh repository in a temporary directory.
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
+use tempfile::TempDir;
+use crate::commands::add::AddCommand;
+use crate::commands::commit::CommitCommand;
+use crate::commands::init::InitCommand;
+use crate::core::repository::repository::Repository;
+
+static CURRENT_DIR: Mutex<()> = Mutex::new(());
+
+/// An initialized repository that is the current directory until dropped
+pub struct TestRepo {
+    dir: TempDir,
+    previous_dir: PathBuf,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl TestRepo {
+    pub fn new() -> Self {
+        // A failed test leaves the lock poisoned; the next one still starts from a fresh directory
+        let lock = CURRENT_DIR.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let previous_dir = env::current_dir().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+
+        env::set_current_dir(dir.path()).unwrap();
+        env::set_var("GIT_AUTHOR_NAME", "Test Author");
+        env::set_var("GIT_AUTHOR_EMAIL", "author@test.invalid");
+        env::set_var("ASH_NO_PAGER", "1");
+        InitCommand::execute(".").unwrap();
+
+        TestRepo { dir, previous_dir, _lock: lock }
+    }
+
+    pub fn path(&self) -> PathBuf {
+        self.dir.path().to_path_buf()
+    }
+
+    pub fn repo(&self) -> Repository {
+        Repository::new(".").unwrap()
+    }
+
+    /// Writes `content` to `path`, creating parent directories
+    pub fn write(&self, path: &str, content: &str) {
+        let full_path = self.dir.path().join(path);
+        if let Some(parent) = full_path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        fs::write(full_path, content).unwrap();
+    }
+
+    pub fn read(&self, path: &str) -> String {
+        fs::read_to_string(self.dir.path().join(path)).unwrap()
+    }
+
+    pub fn exists(&self, path: &str) -> bool {
+        self.dir.path().join(path).exists()
+    }
+
+    /// The blob staged at stage 0 for `path`, if any
+    pub fn staged_oid(&self, path: &str) -> Option<String> {
+        let mut repo = self.repo();
+        repo.index.load().unwrap();
+        repo.index.entry_for_path(path, 0).map(|entry| entry.get_oid().to_string())
+    }
+
+    /// The ID `content` has as a blob
+    pub fn blob_oid(&self, content: &str) -> String {
+        self.repo().database.hash_file_data(content.as_bytes())
+    }
+
+    pub fn add(&self, paths: &[&str]) {
+        let paths: Vec<String> = paths.iter().map(|path| path.to_string()).collect();
+        AddCommand::execute(&paths).unwrap();
+    }
+
+    /// Commits the index and returns the new HEAD
+    pub fn commit(&self, message: &str) -> String {
+        CommitCommand::execute(message, false, None, false, None, None, &[]).unwrap();
+        self.head()
+    }
+
+    /// Writes, stages and commits files given as (path, content) pairs
+    pub fn commit_files(&self, files: &[(&str, &str)], message: &str) -> String {
+        for (path, content) in files {
+            self.write(path, content);
+        }
+        let paths: Vec<&str> = files.iter().map(|(path, _)| *path).collect();
+        self.add(&paths);
+        self.commit(message)
+    }
+
+    pub fn head(&self) -> String {
+        self.repo().refs.read_head().unwrap().expect("HEAD has no commit")
+    }
+}
+
+impl Drop for TestRepo {
+    fn drop(&mut self) {
+        let _ = env::set_current_dir(&self.previous_dir);
+    }
+}
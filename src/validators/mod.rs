@@ -1 +1,2 @@
 pub mod path_validator;
+pub mod task_validator;
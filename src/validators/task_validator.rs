@@ -0,0 +1,42 @@
+use crate::errors::error::Error;
+
+/// Checks that `id` can be used as a task id. Task ids end up in branch names such as
+/// `sprint-x-task-<id>`, so only ASCII letters, digits, `_` and `-` are allowed.
+pub fn validate_task_id(id: &str) -> Result<(), Error> {
+    if id.is_empty() {
+        return Err(Error::Generic("Task id cannot be empty".to_string()));
+    }
+
+    if let Some(c) = id.chars().find(|c| !(c.is_ascii_alphanumeric() || *c == '_' || *c == '-')) {
+        return Err(Error::Generic(format!(
+            "Invalid task id '{}': character {:?} is not allowed (use letters, digits, '_' and '-')",
+            id, c
+        )));
+    }
+
+    // A leading dash would be read as an option, and ref names cannot start with one either
+    if id.starts_with('-') {
+        return Err(Error::Generic(format!("Invalid task id '{}': cannot start with '-'", id)));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_letters_digits_underscores_and_dashes() {
+        for id in ["42", "T-1", "login_form", "ABC-123_x"] {
+            assert!(validate_task_id(id).is_ok(), "{} should be accepted", id);
+        }
+    }
+
+    #[test]
+    fn rejects_empty_and_unsafe_ids() {
+        for id in ["", "-1", "a b", "a/b", "a..b", "x~1", "ünï", "a:b"] {
+            assert!(validate_task_id(id).is_err(), "{:?} should be rejected", id);
+        }
+    }
+}